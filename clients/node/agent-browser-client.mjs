@@ -0,0 +1,2085 @@
+/**
+ * Thin Node client for the agent-browser MCP TCP interface (localhost:8084).
+ *
+ * @generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit.
+ *
+ * No dependencies. Requests may run concurrently and are matched by id. If
+ * the connection drops, pending calls reject and the next call reconnects
+ * (a new MCP session). Server notifications are emitted as "notification".
+ * Each connection authenticates with `token`, or the one the server wrote
+ * to ~/.agent-browser/token.
+ */
+
+import { EventEmitter } from "node:events";
+import { readFileSync } from "node:fs";
+import net from "node:net";
+import { homedir } from "node:os";
+import { join } from "node:path";
+
+export const PROTOCOL_VERSION = "2025-06-18";
+export const DEFAULT_HOST = "127.0.0.1";
+export const DEFAULT_PORT = 8084;
+
+/** Every tool in the server's registry, in tools/list order */
+export const TOOL_NAMES = [
+  "playwright_navigate",
+  "browser_page_info",
+  "browser_bring_to_front",
+  "browser_wait_for_load",
+  "playwright_go_back",
+  "playwright_go_forward",
+  "playwright_reload",
+  "playwright_click",
+  "playwright_dblclick",
+  "playwright_context_click",
+  "scroll_into_view",
+  "playwright_fill",
+  "playwright_select",
+  "browser_list_forms",
+  "playwright_fill_form",
+  "playwright_press_key",
+  "playwright_get_text",
+  "playwright_get_html",
+  "browser_extract_content",
+  "browser_find_text",
+  "browser_collect_items",
+  "browser_extract_table",
+  "assert_page",
+  "browser_accessibility_tree",
+  "playwright_focus",
+  "playwright_blur",
+  "playwright_get_focused_element",
+  "playwright_get_element",
+  "playwright_evaluate",
+  "type_text",
+  "set_date",
+  "browser_emulate_locale",
+  "browser_set_geolocation",
+  "browser_throttle",
+  "playwright_screenshot",
+  "browser_screenshot_elements",
+  "browser_export_pdf",
+  "explore_menu",
+  "canvas_read_pixels",
+  "media_control",
+  "media_get_state",
+  "media_capture_frame",
+  "browser_follow_popups",
+  "browser_list_frames",
+  "browser_list_service_workers",
+  "browser_unregister_service_workers",
+  "browser_clear_site_data",
+  "browser_get_cookies",
+  "browser_set_cookie",
+  "browser_clear_cookies",
+  "browser_list_downloads",
+  "browser_wait_for_download",
+  "browser_profiling_start",
+  "browser_profiling_stop",
+  "browser_recording_start",
+  "browser_recording_stop",
+  "network_list_websockets",
+  "network_capture_configure",
+  "network_list_requests",
+  "network_get_request",
+  "session_artifacts_list",
+  "artifacts_search",
+  "session_report",
+  "history_retry",
+  "session_list",
+  "session_observe",
+  "session_inject",
+  "history_stats",
+  "alerts_test",
+  "storage_status",
+  "server_metrics",
+  "telemetry_status",
+  "capabilities_diff",
+  "preflight",
+  "passkey_enable",
+  "passkey_status",
+  "passkey_list",
+  "passkey_clear",
+  "passkey_authorize",
+  "passkey_authorization_status",
+  "passkey_grant_create",
+  "passkey_grant_redeem",
+  "recovery_secret_store",
+  "recovery_secret_list",
+  "recovery_authorize",
+  "recovery_secret_get",
+  "recovery_secret_delete",
+  "state_set",
+  "state_get",
+  "state_delete",
+  "data_transform",
+  "snapshot_save",
+  "snapshot_get",
+  "baseline_set",
+  "baseline_list",
+  "baseline_delete",
+  "screenshot_diff",
+  "watch_add",
+  "watch_list",
+  "watch_remove",
+  "playwright_detect_modal",
+  "playwright_dismiss_modal",
+  "browser_handle_dialogs",
+  "browser_respond_dialog",
+  "browser_list_dialogs",
+  "audit_accessibility",
+  "check_links",
+  "audit_seo",
+  "dismiss_consent",
+];
+
+/** JSON-RPC error returned by the server */
+export class AgentBrowserError extends Error {
+  constructor(code, message, data) {
+    super(`${message} (${code})`);
+    this.code = code;
+    this.data = data;
+  }
+}
+
+/**
+ * @typedef {Object} ToolResult
+ * @property {Array<{type: string, text?: string, data?: string, mimeType?: string}>} content
+ * @property {any} [structuredContent]
+ * @property {boolean} [isError]
+ */
+
+/** Token written by a server on this machine, re-read on every connect */
+function localToken() {
+  try {
+    return readFileSync(join(homedir(), ".agent-browser", "token"), "utf8").trim() || null;
+  } catch {
+    return null;
+  }
+}
+
+/** Text blocks of a tool result joined with newlines */
+export function toolText(result) {
+  return (result.content ?? []).filter((c) => c.text !== undefined).map((c) => c.text).join("\n");
+}
+
+/** structuredContent, or the text block parsed as JSON */
+export function toolJson(result) {
+  return result.structuredContent ?? JSON.parse(toolText(result));
+}
+
+/**
+ * @typedef {Object} PlaywrightNavigateArgs
+ * @property {string} url - The URL to navigate to
+ * @property {boolean} [bypassServiceWorker] - Bypass registered service workers for this navigation so the network response is used instead of a cached app shell (default: false)
+ * @property {number} [timeoutMs] - How long to wait for waitUntil (default: 20000)
+ * @property {string} [waitUntil] - Block until the new page reaches this state: DOMContentLoaded fired, load fired, or load plus 500 ms without network requests (default: don't wait)
+ */
+
+/**
+ * @typedef {Object} BrowserPageInfoArgs
+ */
+
+/**
+ * @typedef {Object} BrowserBringToFrontArgs
+ * @property {boolean} [checkOnly] - Only report whether the tab is in the foreground, without changing anything (default: false)
+ * @property {boolean} [focusWindow] - Also focus the tab's window above other windows (default: true)
+ */
+
+/**
+ * @typedef {Object} BrowserWaitForLoadArgs
+ * @property {boolean} [expectNavigation] - Only accept a document created after the previous command started, e.g. the page a click submitted a form to; without it the current page counts (default: false)
+ * @property {number} [idleMs] - Quiet period networkidle needs (default: 500)
+ * @property {number} [maxInflight] - Requests allowed to stay open during the quiet period, for pages with long polls or streams (default: 0)
+ * @property {string} [state] - domcontentloaded: the HTML is parsed; load: the page and its subresources finished loading; networkidle: load, then idleMs without network requests (default: load)
+ * @property {number} [timeoutMs] - How long to wait (default: 20000)
+ */
+
+/**
+ * @typedef {Object} PlaywrightGoBackArgs
+ * @property {number} [timeoutMs] - How long to wait for the page to load (default: 20000)
+ */
+
+/**
+ * @typedef {Object} PlaywrightGoForwardArgs
+ * @property {number} [timeoutMs] - How long to wait for the page to load (default: 20000)
+ */
+
+/**
+ * @typedef {Object} PlaywrightReloadArgs
+ * @property {boolean} [bypassCache] - Ignore the HTTP cache, like a hard reload (default: false)
+ * @property {number} [timeoutMs] - How long to wait for the page to load (default: 20000)
+ */
+
+/**
+ * @typedef {Object} PlaywrightClickArgs
+ * @property {boolean} [avoidStickyChrome] - Scroll the element clear of fixed/sticky headers and footers before clicking (default: false)
+ * @property {string} [button] - Mouse button (default: left)
+ * @property {boolean} [captureReceipt] - After the click, save a screenshot, the final URL, and confirmation text to the session's receipts as evidence of the submission (default: false)
+ * @property {number} [clickCount] - Number of clicks; 2 also fires dblclick (default: 1)
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector for the element to click
+ */
+
+/**
+ * @typedef {Object} PlaywrightDblclickArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector for the element to double-click
+ */
+
+/**
+ * @typedef {Object} PlaywrightContextClickArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector for the element to right-click
+ */
+
+/**
+ * @typedef {Object} ScrollIntoViewArgs
+ * @property {string} [block] - Vertical alignment within the unobscured viewport (default: center)
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {number} [offsetBottom] - Pixels obscured at the bottom of the viewport; overrides detection
+ * @property {number} [offsetTop] - Pixels obscured at the top of the viewport; overrides detection
+ * @property {string} [selector] - CSS selector for the element
+ * @property {string} [stickyCompensation] - auto detects fixed/sticky chrome at the viewport edges, none scrolls like element.scrollIntoView (default: auto)
+ */
+
+/**
+ * @typedef {Object} PlaywrightFillArgs
+ * @property {string} value - The text to type into the input
+ * @property {boolean} [captureReceipt] - After filling, save a screenshot, the final URL, and confirmation text to the session's receipts (the typed value is not recorded; default: false)
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector for the input element
+ */
+
+/**
+ * @typedef {Object} PlaywrightSelectArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {any} [index] - Zero-based option position(s) to select
+ * @property {any} [label] - Visible option text(s) to select
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector for the <select> element
+ * @property {any} [value] - Option value attribute(s) to select
+ */
+
+/**
+ * @typedef {Object} BrowserListFormsArgs
+ * @property {boolean} [includeHidden] - Also list hidden inputs and fields that aren't displayed, each with a visible flag (default: false)
+ * @property {number} [maxOptions] - Options listed per select; optionCount has the total (default: 50)
+ * @property {string} [selector] - Only list forms inside this CSS selector, or just this form (default: the whole page)
+ */
+
+/**
+ * @typedef {Object} PlaywrightFillFormArgs
+ * @property {any[]} fields
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {boolean} [stopOnError] - Stop at the first field that fails (default: false)
+ */
+
+/**
+ * @typedef {Object} PlaywrightPressKeyArgs
+ * @property {string} key - Enter, Tab, Escape, Backspace, Delete, Space, ArrowUp/Down/Left/Right, Home, End, PageUp, PageDown, F1-F12 or a single character; a combo like "Shift+Tab" or "Control+a" is also accepted
+ * @property {number} [count] - Number of times to press the key (default: 1, max: 50)
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string[]} [modifiers] - Modifier keys held during the press
+ * @property {string} [selector] - CSS selector of an element to focus before pressing (default: the currently focused element)
+ */
+
+/**
+ * @typedef {Object} PlaywrightGetTextArgs
+ * @property {boolean} [all] - Return every matching element's text instead of the first (default: false)
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {number} [limit] - Matches returned with all: true (default: 100, max: 1000)
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {number} [maxLength] - Characters returned per element before truncating (default: 50000, max: 1000000)
+ * @property {string} [property] - innerText (as rendered, hidden elements skipped) or textContent (all text nodes) (default: innerText)
+ * @property {string} [selector] - CSS selector (default: body)
+ */
+
+/**
+ * @typedef {Object} PlaywrightGetHtmlArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {number} [maxLength] - Characters returned before truncating (default: 200000, max: 5000000)
+ * @property {string} [selector] - CSS selector of the element (default: the whole document)
+ * @property {boolean} [stripComments] - Remove HTML comments (default: true)
+ * @property {boolean} [stripScripts] - Remove <script>, <noscript>, <template>, on* attributes and javascript: links (default: true)
+ * @property {boolean} [stripStyles] - Remove <style>, stylesheet links and style attributes (default: false)
+ */
+
+/**
+ * @typedef {Object} BrowserExtractContentArgs
+ * @property {boolean} [includeImages] - Render images as ![alt](src) (default: false)
+ * @property {boolean} [includeLinks] - Render links inline as [text](url); they are listed in links either way (default: true)
+ * @property {number} [maxLength] - Characters of Markdown returned before truncating at a paragraph break (default: 20000, max: 1000000)
+ * @property {string} [selector] - CSS selector of the element to convert, skipping main-content detection
+ */
+
+/**
+ * @typedef {Object} BrowserFindTextArgs
+ * @property {boolean} [caseSensitive] - Match case exactly (default: false)
+ * @property {boolean} [clear] - Only remove the highlights of the previous search
+ * @property {number} [contextChars] - Characters of context returned on each side of a match (default: 40, max: 500)
+ * @property {boolean} [highlight] - Highlight the matches on the page until the next search (default: true)
+ * @property {number} [maxResults] - Maximum matches returned; the count covers all of them (default: 20, max: 500)
+ * @property {string} [query] - Text to find, or a JavaScript regular expression with regex
+ * @property {boolean} [regex] - Treat query as a regular expression (default: false)
+ * @property {number} [scrollTo] - Zero-based index of the match to scroll into view and mark as current
+ * @property {string} [selector] - Only search inside this CSS selector (default: the whole page)
+ * @property {boolean} [wholeWord] - Only match at word boundaries (default: false)
+ */
+
+/**
+ * @typedef {Object} BrowserCollectItemsArgs
+ * @property {string} itemSelector - CSS selector matching each item of the feed, e.g. "article.post"
+ * @property {string} [container] - CSS selector of the element that scrolls (default: the items' scrolling ancestor, or the page)
+ * @property {Object} [fields] - Values to read from each item: name -> "selector" (text), "selector@attribute" or "@attribute" (of the item itself), e.g. {"title": "h2", "url": "a@href"}. Default: text and link
+ * @property {number} [idleScrolls] - Scrolls at the bottom without new items before the feed counts as ended (default: 3)
+ * @property {string} [loadMoreSelector] - Button to click when the bottom is reached, for feeds with a "Load more" button
+ * @property {number} [maxItems] - Stop after this many items (default: 100)
+ * @property {number} [maxScrolls] - Stop after this many scrolls (default: 50)
+ * @property {number} [maxTextLength] - Longest text returned per value (default: 300)
+ * @property {number} [timeoutMs] - Stop after this long (default: 30000, at most the command timeout less 2 s)
+ * @property {number} [waitMs] - How long to wait for new items after each scroll (default: 1500)
+ */
+
+/**
+ * @typedef {Object} BrowserExtractTableArgs
+ * @property {string} [format] - Rows as objects keyed by header, or as arrays in column order (default: objects)
+ * @property {number} [headerRows] - Number of leading rows that hold column headers; 0 names columns "Column 1", ... (default: detected)
+ * @property {number} [index] - Zero-based index among the page's visible tables
+ * @property {number} [maxRows] - Maximum data rows returned (default: 500)
+ * @property {boolean} [saveCsv] - Also save the table as tables/<time>.csv in the session's artifact directory and return csvPath (default: false)
+ * @property {string} [selector] - CSS selector of the table, or of an element containing it
+ */
+
+/**
+ * @typedef {Object} AssertPageArgs
+ * @property {any[]} assertions
+ */
+
+/**
+ * @typedef {Object} BrowserAccessibilityTreeArgs
+ * @property {boolean} [interestingOnly] - Only landmarks, headings, widgets, lists, tables and images; generic containers and plain text are dropped and their children hoisted (default: true)
+ * @property {number} [maxDepth] - Deepest level of nodes returned (default: 100)
+ * @property {number} [maxNodes] - Nodes returned before the tree is truncated (default: 2000)
+ * @property {string} [selector] - CSS selector of the subtree to return (default: the whole page)
+ */
+
+/**
+ * @typedef {Object} PlaywrightFocusArgs
+ * @property {string} selector - CSS selector for the element to focus
+ */
+
+/**
+ * @typedef {Object} PlaywrightBlurArgs
+ * @property {string} [selector] - CSS selector for the element to blur (default: document.activeElement)
+ */
+
+/**
+ * @typedef {Object} PlaywrightGetFocusedElementArgs
+ */
+
+/**
+ * @typedef {Object} PlaywrightGetElementArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {Object} [locator] - Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+ * @property {string} [selector] - CSS selector; the first match is described and count reports how many matched
+ */
+
+/**
+ * @typedef {Object} PlaywrightEvaluateArgs
+ * @property {string} expression - Expression to evaluate, e.g. document.title, or a function such as (sel) => document.querySelectorAll(sel).length, which is called with args
+ * @property {any[]} [args] - JSON arguments passed to the function (ignored for plain expressions)
+ * @property {boolean} [awaitPromise] - Wait for a returned promise and return its value (default: true)
+ * @property {number} [frameId] - Frame to run in, from browser_list_frames (default: the top frame)
+ */
+
+/**
+ * @typedef {Object} TypeTextArgs
+ * @property {boolean} [clear] - Clear existing field contents before typing (default: false)
+ * @property {string} [composition] - Emit compositionstart/update/end events: auto enables them for CJK text (default: auto)
+ * @property {number} [delayMs] - Delay between keystrokes in milliseconds (default: 50)
+ * @property {any[]} [fields] - Fields typed in order within one command
+ * @property {number} [jitterMs] - Random extra delay added to each keystroke, 0..jitterMs (default: 0)
+ * @property {string} [selector] - CSS selector of the field to type into (single-field form)
+ * @property {string} [text] - Text to type (single-field form)
+ */
+
+/**
+ * @typedef {Object} SetDateArgs
+ * @property {string} date - Date as YYYY-MM-DD, or YYYY-MM-DDTHH:MM for datetime inputs
+ * @property {string} selector - CSS selector for the date input or the element that opens the picker
+ * @property {string} [strategy] - inject sets the value and fires input/change events, widget clicks through the calendar, auto tries inject then widget (default: auto)
+ */
+
+/**
+ * @typedef {Object} BrowserEmulateLocaleArgs
+ * @property {string} [acceptLanguage] - Accept-Language header value (default: derived from locale, e.g. de-DE,de;q=0.9)
+ * @property {string} [locale] - BCP 47 locale, e.g. de-DE or ja-JP
+ * @property {boolean} [reset] - Remove all overrides and return to the browser's settings
+ * @property {string} [timezone] - IANA timezone, e.g. Europe/Berlin or America/New_York
+ */
+
+/**
+ * @typedef {Object} BrowserSetGeolocationArgs
+ * @property {number} [accuracy] - Accuracy radius in meters (default: 100)
+ * @property {boolean} [grantPermission] - Allow the location permission for the origin so no prompt appears (default: true)
+ * @property {number} [latitude] - Latitude in degrees
+ * @property {number} [longitude] - Longitude in degrees
+ * @property {string} [origin] - Origin to allow location access for, e.g. https://shop.example (default: the current page's)
+ * @property {boolean} [reset] - Remove the override and the permissions this tool granted
+ */
+
+/**
+ * @typedef {Object} BrowserThrottleArgs
+ * @property {number} [cpuRate] - CPU slowdown factor, 1 (none) to 20; 4 approximates a mid-range phone
+ * @property {number} [downloadKbps] - Download bandwidth in kilobits per second (overrides the profile's)
+ * @property {number} [latencyMs] - Added round-trip latency in milliseconds (overrides the profile's)
+ * @property {string} [network] - Network profile; none removes network throttling
+ * @property {boolean} [reset] - Remove all network and CPU throttling
+ * @property {number} [uploadKbps] - Upload bandwidth in kilobits per second (overrides the profile's)
+ */
+
+/**
+ * @typedef {Object} PlaywrightScreenshotArgs
+ * @property {any} [frame] - Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+ * @property {boolean} [fullPage] - Whether to take a full page screenshot
+ * @property {string} [highlightLabel] - Label drawn on the highlighted elements, numbered when several match (default: each element's tag, id and classes)
+ * @property {string} [highlightSelector] - CSS selector of elements to outline and label in the screenshot; the overlay is removed after capture
+ * @property {string} [savePath] - Write the PNG to this file instead of returning it; must be inside the client's declared roots (relative paths use the first root)
+ * @property {string} [selector] - Optional CSS selector to screenshot a specific element
+ */
+
+/**
+ * @typedef {Object} BrowserScreenshotElementsArgs
+ * @property {string[]} selectors - CSS selectors of the elements to capture, in the order images are returned
+ * @property {boolean} [all] - Capture every element each selector matches rather than the first (default: false)
+ * @property {number} [maxElements] - Maximum elements captured in total (default: 20, max: 100)
+ * @property {number} [padding] - Pixels of surrounding page included on each side (default: 0, max: 100)
+ * @property {boolean} [save] - Write the images to elements/<time>/ in the session's artifact directory and return their paths instead of base64 (default: false)
+ */
+
+/**
+ * @typedef {Object} BrowserExportPdfArgs
+ * @property {boolean} [landscape] - Landscape orientation (default: false)
+ * @property {boolean} [printBackground] - Include background graphics (default: true)
+ * @property {string} [savePath] - Write the PDF to this file; must be inside the client's declared roots (relative paths use the first root)
+ * @property {number} [scale] - Rendering scale between 0.1 and 2 (default: 1)
+ */
+
+/**
+ * @typedef {Object} ExploreMenuArgs
+ * @property {string} trigger - CSS selector for the element that opens the menu
+ * @property {string} [action] - How to open the menu (default: hover)
+ * @property {string} [itemSelector] - CSS selector for items inside the menu (default: menu items, options, links, and buttons)
+ * @property {string} [menuSelector] - CSS selector for the revealed menu container (default: common ARIA menu/listbox roles)
+ * @property {boolean} [restore] - Close the menu again after capturing it (default: true)
+ * @property {number} [timeoutMs] - How long to wait for the menu to appear (default: 2000)
+ */
+
+/**
+ * @typedef {Object} CanvasReadPixelsArgs
+ * @property {string} selector - CSS selector for the canvas element
+ * @property {number} [grid] - Cells per side for average mode, 1-16 (default: 1)
+ * @property {number} [height] - Region height (default: rest of the canvas)
+ * @property {string} [mode] - image returns a base64 PNG, average returns mean RGBA per grid cell (default: image)
+ * @property {number} [width] - Region width (default: rest of the canvas)
+ * @property {number} [x] - Left edge of the region in canvas pixels (default: 0)
+ * @property {number} [y] - Top edge of the region in canvas pixels (default: 0)
+ */
+
+/**
+ * @typedef {Object} MediaControlArgs
+ * @property {string} action - Operation to perform
+ * @property {string} [selector] - CSS selector for the media element (default: first audio/video on the page)
+ * @property {number} [time] - Target position in seconds (required for seek)
+ */
+
+/**
+ * @typedef {Object} MediaGetStateArgs
+ * @property {string} [selector] - CSS selector for the media element (default: every audio/video on the page)
+ */
+
+/**
+ * @typedef {Object} MediaCaptureFrameArgs
+ * @property {string} [selector] - CSS selector for the video element (default: first video on the page)
+ * @property {number} [time] - Seek to this position in seconds before capturing
+ */
+
+/**
+ * @typedef {Object} BrowserFollowPopupsArgs
+ * @property {boolean} enabled - Turn follow mode on or off for this session
+ */
+
+/**
+ * @typedef {Object} BrowserListFramesArgs
+ * @property {boolean} [includeAboutBlank] - Include about:blank and srcdoc frames (default: false)
+ */
+
+/**
+ * @typedef {Object} BrowserListServiceWorkersArgs
+ */
+
+/**
+ * @typedef {Object} BrowserUnregisterServiceWorkersArgs
+ * @property {string} [scope] - Only unregister the registration with this scope URL (default: all for the origin)
+ */
+
+/**
+ * @typedef {Object} BrowserClearSiteDataArgs
+ * @property {string[]} [dataTypes] - Data types to clear (default: all)
+ * @property {string} [origin] - Origin to clear, e.g. https://example.com (default: current tab's origin)
+ * @property {boolean} [reload] - Reload the tab after clearing (default: false)
+ */
+
+/**
+ * @typedef {Object} BrowserGetCookiesArgs
+ * @property {string} [domain] - Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+ * @property {boolean} [includeValues] - Include cookie values (default: false)
+ * @property {string} [name] - Only cookies with this name
+ * @property {string} [url] - Only cookies the browser would send to this URL (instead of domain)
+ */
+
+/**
+ * @typedef {Object} BrowserSetCookieArgs
+ * @property {string} name - Cookie name
+ * @property {string} value - Cookie value
+ * @property {string} [domain] - Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+ * @property {number} [expires] - Expiry as a Unix timestamp in seconds (default: session cookie)
+ * @property {boolean} [httpOnly] - Hide the cookie from page scripts
+ * @property {number} [maxAgeSeconds] - Expiry in seconds from now (instead of expires)
+ * @property {string} [path] - Cookie path (default: /)
+ * @property {string} [sameSite] - SameSite policy; none implies secure
+ * @property {boolean} [secure] - Only send over HTTPS
+ * @property {string} [url] - Only cookies the browser would send to this URL (instead of domain)
+ */
+
+/**
+ * @typedef {Object} BrowserClearCookiesArgs
+ * @property {string} [domain] - Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+ * @property {string} [name] - Only delete cookies with this name
+ * @property {string} [url] - Only cookies the browser would send to this URL (instead of domain)
+ */
+
+/**
+ * @typedef {Object} BrowserListDownloadsArgs
+ * @property {string} [filenameContains] - Only downloads whose file path contains this text, e.g. .csv
+ * @property {number} [limit] - Maximum downloads to return (default: 20)
+ * @property {string} [state] - Only downloads in this state
+ * @property {string} [urlContains] - Only downloads whose URL contains this text
+ */
+
+/**
+ * @typedef {Object} BrowserWaitForDownloadArgs
+ * @property {string} [filenameContains] - Only a download whose file path contains this text, e.g. .csv
+ * @property {number} [id] - Download id from browser_list_downloads
+ * @property {boolean} [saveArtifact] - Move the file into downloads/ in this session's artifact directory (default: false)
+ * @property {string} [savePath] - Move the file to this path inside the session's roots (instead of saveArtifact)
+ * @property {number} [sinceSeconds] - Also match downloads that started this many seconds before the call (default: 60)
+ * @property {number} [timeoutMs] - How long to wait for the download to finish (default: 30000)
+ * @property {string} [urlContains] - Only a download whose URL contains this text
+ */
+
+/**
+ * @typedef {Object} BrowserProfilingStartArgs
+ * @property {string[]} [categories] - Trace categories (default: those the DevTools Performance panel records)
+ * @property {string[]} [coverage] - Coverage to record (default: both; [] for none)
+ * @property {boolean} [trace] - Record a performance trace (default: true)
+ */
+
+/**
+ * @typedef {Object} BrowserProfilingStopArgs
+ */
+
+/**
+ * @typedef {Object} BrowserRecordingStartArgs
+ * @property {string} [format] - Video format; needs ffmpeg, without which a Motion-JPEG AVI is saved instead (default: webm)
+ * @property {number} [maxWidth] - Scale frames down to fit this many pixels wide and high (default: 1280)
+ * @property {number} [quality] - JPEG quality of the captured frames (default: 70)
+ */
+
+/**
+ * @typedef {Object} BrowserRecordingStopArgs
+ */
+
+/**
+ * @typedef {Object} NetworkListWebsocketsArgs
+ * @property {boolean} [includeMessages] - Include recent message previews (default: true)
+ * @property {boolean} [includeWebRTC] - Include WebRTC connection events (default: true)
+ * @property {number} [limit] - Maximum number of connections/events to return (default: 50)
+ * @property {string} [urlPattern] - Only include connections whose URL contains this substring
+ */
+
+/**
+ * @typedef {Object} NetworkCaptureConfigureArgs
+ * @property {boolean} [captureBodies] - Capture request/response bodies (default: false)
+ * @property {number} [maxBodyBytes] - Maximum stored body size in bytes; longer bodies are truncated (default: 65536)
+ * @property {string[]} [redactHeaders] - Additional header names whose values are redacted
+ * @property {string[]} [redactPatterns] - Additional regex patterns replaced with [REDACTED] in URLs, headers, and bodies
+ */
+
+/**
+ * @typedef {Object} NetworkListRequestsArgs
+ * @property {boolean} [allTabs] - List requests from every tab (default: false)
+ * @property {number} [limit] - Maximum requests to return (default: 50)
+ * @property {any} [status] - Only requests with this status: a code like 404, a class like "4xx", or "failed" for requests that got no response
+ * @property {number} [tabId] - List requests from this tab instead
+ * @property {string} [type] - Only requests of this resource type; fetch() and XHR calls are xmlhttprequest
+ * @property {string} [urlPattern] - Only requests whose URL contains this substring, e.g. /api/
+ */
+
+/**
+ * @typedef {Object} NetworkGetRequestArgs
+ * @property {string} [id] - Captured request id
+ * @property {number} [limit] - Maximum number of entries to return, newest first (default: 10)
+ * @property {string} [urlPattern] - Match requests whose URL contains this substring
+ */
+
+/**
+ * @typedef {Object} SessionArtifactsListArgs
+ */
+
+/**
+ * @typedef {Object} ArtifactsSearchArgs
+ * @property {string} query - Words that must all appear; "quoted phrases", prefix* and -excluded words are supported
+ * @property {string[]} [kinds] - Only search these sources (default: all)
+ * @property {number} [limit] - Maximum results (default 20, max 100)
+ * @property {string} [session] - Only results from this session label (see session_list)
+ * @property {string} [since] - Only results from this time on: RFC 3339, YYYY-MM-DD, or an age such as 24h, 7d or 2w
+ * @property {string} [until] - Only results up to this time, in the same formats as since
+ */
+
+/**
+ * @typedef {Object} SessionReportArgs
+ * @property {boolean} [includeScreenshots] - Embed the session's PNG/JPEG artifacts (default: true)
+ * @property {string} [title] - Report heading (default: Agent session report)
+ */
+
+/**
+ * @typedef {Object} HistoryRetryArgs
+ * @property {Object} [arguments] - Arguments to change before re-running; each key replaces the original value, null removes it
+ * @property {string} [callId] - Failed call to re-run, e.g. c12 (default: the session's most recent failure)
+ * @property {string} [session] - Session whose call to re-run, as listed by session_list (default: this session); the call runs in that session
+ */
+
+/**
+ * @typedef {Object} SessionListArgs
+ */
+
+/**
+ * @typedef {Object} SessionObserveArgs
+ * @property {string} session - Session to observe, from session_list
+ * @property {boolean} [stop] - Stop observing instead (default: false)
+ */
+
+/**
+ * @typedef {Object} SessionInjectArgs
+ * @property {string} session - Session to run the call as, from session_list
+ * @property {string} tool - Tool to call
+ * @property {Object} [arguments] - The tool's arguments
+ */
+
+/**
+ * @typedef {Object} HistoryStatsArgs
+ * @property {boolean} [compact] - Fold events older than the retention window into daily summaries before reporting (default: false)
+ * @property {number} [days] - Most recent days with activity to include (default: 30, max: 366)
+ */
+
+/**
+ * @typedef {Object} AlertsTestArgs
+ * @property {string} [class] - Event class whose route to test (default: every class)
+ */
+
+/**
+ * @typedef {Object} StorageStatusArgs
+ * @property {boolean} [cleanup] - Delete least recently used files in categories over quota before reporting (default: false)
+ */
+
+/**
+ * @typedef {Object} ServerMetricsArgs
+ */
+
+/**
+ * @typedef {Object} TelemetryStatusArgs
+ * @property {number} [epsilon] - Privacy budget for the export; smaller adds more noise (default: 1)
+ * @property {boolean} [reset] - Delete the collected counts first (default: false)
+ */
+
+/**
+ * @typedef {Object} CapabilitiesDiffArgs
+ * @property {Object} [manifest] - Saved tools/list result ({"tools": [...]})
+ * @property {string} [path] - Path to a saved manifest JSON file on the server host (used when manifest is omitted)
+ */
+
+/**
+ * @typedef {Object} PreflightArgs
+ * @property {string} url - http:// or https:// URL to check
+ * @property {number} [timeoutMs] - Timeout per stage in milliseconds (default: 5000, max: 30000)
+ */
+
+/**
+ * @typedef {Object} PasskeyEnableArgs
+ * @property {boolean} enabled - Whether to enable passkey automation
+ */
+
+/**
+ * @typedef {Object} PasskeyStatusArgs
+ */
+
+/**
+ * @typedef {Object} PasskeyListArgs
+ */
+
+/**
+ * @typedef {Object} PasskeyClearArgs
+ */
+
+/**
+ * @typedef {Object} PasskeyAuthorizeArgs
+ * @property {number} [duration_hours] - Number of hours to authorize access (default: 8)
+ */
+
+/**
+ * @typedef {Object} PasskeyAuthorizationStatusArgs
+ */
+
+/**
+ * @typedef {Object} PasskeyGrantCreateArgs
+ * @property {string} rp_id - Relying party whose credential the grant covers, e.g. github.com
+ * @property {number} [duration_minutes] - Minutes the grant stays valid (default: 5, max: 15)
+ * @property {string} [operation] - Limit the grant to signing in (get) or registering (create) (default: either)
+ */
+
+/**
+ * @typedef {Object} PasskeyGrantRedeemArgs
+ * @property {string} token - Grant token (abgrant1....)
+ */
+
+/**
+ * @typedef {Object} RecoverySecretStoreArgs
+ * @property {string} kind - Type of recovery secret
+ * @property {string} rp_id - Relying party / site the secrets belong to (e.g. github.com)
+ * @property {string[]} values - Secret values; each backup code is stored and consumed individually
+ * @property {string} [label] - Security question text (required for security_answer)
+ */
+
+/**
+ * @typedef {Object} RecoverySecretListArgs
+ * @property {string} [rp_id] - Only list secrets for this relying party
+ */
+
+/**
+ * @typedef {Object} RecoveryAuthorizeArgs
+ * @property {number} [duration_minutes] - Number of minutes to allow retrieval (default: 5, max: 15)
+ */
+
+/**
+ * @typedef {Object} RecoverySecretGetArgs
+ * @property {string} kind - Type of recovery secret
+ * @property {string} rp_id - Relying party to retrieve a secret for
+ * @property {string} [label] - Security question text to match (security_answer only)
+ */
+
+/**
+ * @typedef {Object} RecoverySecretDeleteArgs
+ * @property {string} rp_id - Relying party whose secrets should be removed
+ */
+
+/**
+ * @typedef {Object} StateSetArgs
+ * @property {string} key - Key to store the value under
+ * @property {any} value - Any JSON value (max 64KB)
+ * @property {string} [workspace] - Workspace namespace (default: "default")
+ */
+
+/**
+ * @typedef {Object} StateGetArgs
+ * @property {string} [key] - Key to read (omit to list the workspace)
+ * @property {string} [prefix] - When listing, only include keys starting with this prefix
+ * @property {string} [workspace] - Workspace namespace (default: "default")
+ */
+
+/**
+ * @typedef {Object} StateDeleteArgs
+ * @property {string} key - Key to delete
+ * @property {string} [workspace] - Workspace namespace (default: "default")
+ */
+
+/**
+ * @typedef {Object} DataTransformArgs
+ * @property {string} expression - Expression over `data`, e.g. "data.filter(r => r.price > 10).map(r => ({ name: r.name.trim(), price: r.price }))"
+ * @property {any} [data] - Input value (omit to read it from the state store with key)
+ * @property {string} [key] - State store key to read the input from instead of data
+ * @property {string} [saveAs] - Also store the result under this state store key
+ * @property {string} [workspace] - State store workspace for key and saveAs (default: "default")
+ */
+
+/**
+ * @typedef {Object} SnapshotSaveArgs
+ * @property {string} name - Snapshot name (letters, digits, '-', '_', '.')
+ * @property {string[]} [include] - Parts to capture (default: all)
+ * @property {Object} [metadata] - Free-form metadata stored with the snapshot
+ * @property {boolean} [overwrite] - Replace an existing snapshot with the same name (default: false)
+ */
+
+/**
+ * @typedef {Object} SnapshotGetArgs
+ * @property {string[]} [include] - Parts to return (default: all stored parts)
+ * @property {string} [name] - Snapshot name (omit to list all snapshots)
+ */
+
+/**
+ * @typedef {Object} BaselineSetArgs
+ * @property {string} name - Baseline name (letters, digits, '-', '_', '.')
+ * @property {boolean} [overwrite] - Replace an existing image for this name and viewport (default: false)
+ */
+
+/**
+ * @typedef {Object} BaselineListArgs
+ * @property {string} [name] - Only this baseline
+ * @property {string} [url] - Only baselines captured on URLs containing this text
+ * @property {string} [viewport] - Only this viewport size, e.g. 1280x720
+ */
+
+/**
+ * @typedef {Object} BaselineDeleteArgs
+ * @property {string} name - Baseline name
+ * @property {string} [viewport] - Delete only this viewport's image, e.g. 390x844 (default: all viewports)
+ */
+
+/**
+ * @typedef {Object} ScreenshotDiffArgs
+ * @property {string} baseline - Baseline name (see baseline_set)
+ * @property {number} [maxDiffRatio] - Largest share of differing pixels that still passes (default: 0.001)
+ * @property {number} [tolerance] - Per-channel difference ignored as noise (default: 16)
+ */
+
+/**
+ * @typedef {Object} WatchAddArgs
+ * @property {string} url - Page to watch (http or https)
+ * @property {string} [attribute] - Read this attribute instead of the element's text
+ * @property {number} [interval_seconds] - Seconds between checks (default: 3600, minimum: 60)
+ * @property {string} [pattern] - Regex applied to the extracted text; the first capture group (or the whole match) is compared
+ * @property {string} [selector] - CSS selector of the element to read (default: the page body)
+ * @property {string} [webhook] - URL that receives a JSON POST on every change
+ */
+
+/**
+ * @typedef {Object} WatchListArgs
+ */
+
+/**
+ * @typedef {Object} WatchRemoveArgs
+ * @property {string} id - Watch ID returned by watch_add
+ */
+
+/**
+ * @typedef {Object} PlaywrightDetectModalArgs
+ * @property {boolean} [includeHidden] - Include hidden modals (default: false)
+ * @property {number} [maxResults] - Maximum number of modals to detect (default: 1)
+ * @property {number} [minZIndex] - Minimum z-index to consider (default: 100)
+ */
+
+/**
+ * @typedef {Object} PlaywrightDismissModalArgs
+ * @property {string} [strategy] - Dismissal strategy: auto tries all methods, button clicks dismiss button, escape presses ESC, backdrop clicks overlay, remove forcibly removes from DOM (default: auto)
+ * @property {number} [timeout] - Timeout in milliseconds (default: 5000)
+ * @property {number} [waitAfter] - Wait time after dismissal to verify (default: 500)
+ */
+
+/**
+ * @typedef {Object} BrowserHandleDialogsArgs
+ * @property {string} mode - accept clicks OK (and leaves the page on beforeunload), dismiss clicks Cancel, manual waits for browser_respond_dialog, off stops watching
+ * @property {string} [promptText] - Text accept enters into prompt() dialogs (default: the prompt's default value)
+ */
+
+/**
+ * @typedef {Object} BrowserRespondDialogArgs
+ * @property {boolean} [accept] - Click OK rather than Cancel (default: true)
+ * @property {string} [promptText] - Text to enter into a prompt() dialog before accepting
+ */
+
+/**
+ * @typedef {Object} BrowserListDialogsArgs
+ * @property {number} [limit] - Maximum number of dialogs to return (default: 20)
+ * @property {boolean} [openOnly] - Only include dialogs still waiting for an answer (default: false)
+ * @property {number} [tabId] - Only include dialogs from this tab
+ */
+
+/**
+ * @typedef {Object} AuditAccessibilityArgs
+ * @property {number} [maxNodes] - Example elements returned per violated rule (default: 10, max: 100)
+ * @property {string[]} [rules] - Rules to run (default: all)
+ * @property {string} [selector] - Only audit elements inside this CSS selector (default: whole document)
+ */
+
+/**
+ * @typedef {Object} CheckLinksArgs
+ * @property {boolean} [allowPrivate] - Check links to loopback, private and link-local addresses (default: false)
+ * @property {number} [concurrency] - Requests in flight at once (default: 8, max: 32)
+ * @property {boolean} [includeResources] - Also check image, script, stylesheet and media URLs (default: false, anchors only)
+ * @property {number} [maxLinks] - Most distinct links to check (default: 100, max: 500)
+ * @property {boolean} [sameOriginOnly] - Only check links on the page's own origin (default: false)
+ * @property {number} [timeoutMs] - Per-link timeout including redirects (default: 8000, max: 30000)
+ */
+
+/**
+ * @typedef {Object} AuditSeoArgs
+ * @property {number} [linkChecks] - How many key links (canonical, alternates, then page links) to check the status of (default: 10, max: 50)
+ * @property {boolean} [siteChecks] - Fetch robots.txt, sitemaps and key links from the server (default: true)
+ * @property {number} [timeoutMs] - Per-request timeout for site checks (default: 5000, max: 30000)
+ */
+
+/**
+ * @typedef {Object} DismissConsentArgs
+ * @property {boolean} [auto] - Enable or disable running dismiss_consent automatically after each navigation (with the given mode) instead of running it now
+ * @property {boolean} [fallbackToModal] - Fall back to generic modal dismissal when no known platform matches (default: true)
+ * @property {string} [mode] - Accept all or reject all non-essential cookies (default: reject)
+ */
+
+export class AgentBrowserClient extends EventEmitter {
+  constructor({
+    host = DEFAULT_HOST,
+    port = DEFAULT_PORT,
+    timeoutMs = 60000,
+    clientName = "agent-browser-node",
+    token = null,
+  } = {}) {
+    super();
+    this.token = token;
+    this.host = host;
+    this.port = port;
+    this.timeoutMs = timeoutMs;
+    this.clientName = clientName;
+    this.protocolVersion = null;
+    this.socket = null;
+    this.connecting = null;
+    this.pending = new Map();
+    this.nextId = 1;
+  }
+
+  /** Connect and initialize; called implicitly by every request */
+  connect() {
+    if (this.socket) return Promise.resolve();
+    this.connecting ??= this.#open().finally(() => {
+      this.connecting = null;
+    });
+    return this.connecting;
+  }
+
+  close() {
+    this.socket?.end();
+    this.socket = null;
+  }
+
+  /** Send a raw JSON-RPC request and resolve with its result */
+  async request(method, params = {}) {
+    await this.connect();
+    return this.#send(method, params);
+  }
+
+  /** Call any tool by name; undefined arguments are omitted */
+  async callTool(name, args = {}) {
+    const argumentsObject = Object.fromEntries(Object.entries(args).filter(([, v]) => v !== undefined));
+    const result = await this.request("tools/call", { name, arguments: argumentsObject });
+    // Tools the server answers itself return their JSON without a content wrapper
+    return result?.content === undefined ? { content: [], structuredContent: result } : result;
+  }
+
+  async listTools() {
+    return (await this.request("tools/list")).tools ?? [];
+  }
+
+  async ping() {
+    await this.request("ping");
+  }
+
+  async #open() {
+    const socket = net.createConnection({ host: this.host, port: this.port });
+    socket.setNoDelay(true);
+    await new Promise((resolve, reject) => {
+      socket.once("connect", resolve);
+      socket.once("error", reject);
+    });
+
+    let buffer = "";
+    socket.setEncoding("utf8");
+    socket.on("data", (chunk) => {
+      buffer += chunk;
+      let newline;
+      while ((newline = buffer.indexOf("\n")) >= 0) {
+        const line = buffer.slice(0, newline).trim();
+        buffer = buffer.slice(newline + 1);
+        if (line) this.#dispatch(socket, line);
+      }
+    });
+    socket.on("error", () => {});
+    socket.on("close", () => {
+      if (this.socket === socket) this.socket = null;
+      for (const { reject, timer } of this.pending.values()) {
+        clearTimeout(timer);
+        reject(new Error("Connection to agent-browser closed"));
+      }
+      this.pending.clear();
+    });
+
+    this.socket = socket;
+    try {
+      const token = this.token ?? localToken();
+      if (token) await this.#send("authenticate", { token });
+      const result = await this.#send("initialize", {
+        protocolVersion: PROTOCOL_VERSION,
+        capabilities: {},
+        clientInfo: { name: this.clientName, version: "0.1.0" },
+      });
+      this.protocolVersion = result.protocolVersion ?? null;
+      this.#write({ jsonrpc: "2.0", method: "notifications/initialized", params: {} });
+    } catch (error) {
+      this.close();
+      throw error;
+    }
+  }
+
+  #write(message) {
+    this.socket.write(JSON.stringify(message) + "\n");
+  }
+
+  #send(method, params) {
+    const id = this.nextId++;
+    return new Promise((resolve, reject) => {
+      const timer = setTimeout(() => {
+        this.pending.delete(id);
+        reject(new Error(`Request ${method} timed out`));
+      }, this.timeoutMs);
+      this.pending.set(id, { resolve, reject, timer });
+      this.#write({ jsonrpc: "2.0", id, method, params });
+    });
+  }
+
+  #dispatch(socket, line) {
+    let message;
+    try {
+      message = JSON.parse(line);
+    } catch {
+      return;
+    }
+    if (message.method !== undefined) {
+      if (message.id !== undefined && message.method === "ping") {
+        // Server keep-alive
+        socket.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result: {} }) + "\n");
+      } else if (message.id !== undefined) {
+        // No client capabilities are declared, so other server requests are unsupported
+        socket.write(
+          JSON.stringify({
+            jsonrpc: "2.0",
+            id: message.id,
+            error: { code: -32601, message: `Method not found: ${message.method}` },
+          }) + "\n",
+        );
+      } else {
+        this.emit("notification", message.method, message.params);
+      }
+      return;
+    }
+    const pending = this.pending.get(message.id);
+    if (!pending) return;
+    this.pending.delete(message.id);
+    clearTimeout(pending.timer);
+    if (message.error) {
+      pending.reject(new AgentBrowserError(message.error.code, message.error.message, message.error.data));
+    } else {
+      pending.resolve(message.result);
+    }
+  }
+
+  // --------------------------------------------------------------------------
+  // Tools
+  // --------------------------------------------------------------------------
+
+  /**
+   * Navigate to a URL in the browser
+   * @param {PlaywrightNavigateArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightNavigate(args) {
+    return this.callTool("playwright_navigate", args);
+  }
+
+  /**
+   * Answer "where am I?" in one cheap call: the current URL, title, ready state, scroll position, viewport and document size
+   * @param {BrowserPageInfoArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserPageInfo(args = {}) {
+    return this.callTool("browser_page_info", args);
+  }
+
+  /**
+   * Make the automated tab the active tab of a focused, unminimized window and report whether the page is now visible. Background tabs throttle timers and pause animations, media and requestAnimationFrame, which silently stalls some pages
+   * @param {BrowserBringToFrontArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserBringToFront(args = {}) {
+    return this.callTool("browser_bring_to_front", args);
+  }
+
+  /**
+   * Wait until the page reaches a load state, instead of sleeping after an action. With expectNavigation, waits for the page the previous action navigated to
+   * @param {BrowserWaitForLoadArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserWaitForLoad(args = {}) {
+    return this.callTool("browser_wait_for_load", args);
+  }
+
+  /**
+   * Go back one page in the tab's history and wait for it to load; returns the resulting URL and title
+   * @param {PlaywrightGoBackArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGoBack(args = {}) {
+    return this.callTool("playwright_go_back", args);
+  }
+
+  /**
+   * Go forward one page in the tab's history and wait for it to load; returns the resulting URL and title
+   * @param {PlaywrightGoForwardArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGoForward(args = {}) {
+    return this.callTool("playwright_go_forward", args);
+  }
+
+  /**
+   * Reload the current page and wait for it to load; returns the resulting URL and title
+   * @param {PlaywrightReloadArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightReload(args = {}) {
+    return this.callTool("playwright_reload", args);
+  }
+
+  /**
+   * Click an element on the page
+   * @param {PlaywrightClickArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightClick(args = {}) {
+    return this.callTool("playwright_click", args);
+  }
+
+  /**
+   * Double-click an element, e.g. to edit a cell or select a word
+   * @param {PlaywrightDblclickArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightDblclick(args = {}) {
+    return this.callTool("playwright_dblclick", args);
+  }
+
+  /**
+   * Right-click an element to open its context menu
+   * @param {PlaywrightContextClickArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightContextClick(args = {}) {
+    return this.callTool("playwright_context_click", args);
+  }
+
+  /**
+   * Scroll an element into view, compensating for fixed/sticky headers and footers so it is not left underneath them. Reports whether the element ended up visible, and why not if it didn't
+   * @param {ScrollIntoViewArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  scrollIntoView(args = {}) {
+    return this.callTool("scroll_into_view", args);
+  }
+
+  /**
+   * Fill out an input field
+   * @param {PlaywrightFillArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightFill(args) {
+    return this.callTool("playwright_fill", args);
+  }
+
+  /**
+   * Choose options in a <select> dropdown by value, visible label, or index (playwright_fill does not work on selects). Pass a list to choose several options in a multi-select; the previous selection is replaced
+   * @param {PlaywrightSelectArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightSelect(args = {}) {
+    return this.callTool("playwright_select", args);
+  }
+
+  /**
+   * List the forms on the page with their fields and submit buttons, to plan a playwright_fill_form call instead of guessing selectors. Each field has a selector addressing only it, its fillType for playwright_fill_form, label, name, required flag, current value (never passwords), and the options of selects and radio groups. Fields outside any <form> are listed as one extra form marked formless
+   * @param {BrowserListFormsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserListForms(args = {}) {
+    return this.callTool("browser_list_forms", args);
+  }
+
+  /**
+   * Fill several form fields in one call, in order, and report success per field. Each field's value decides how it is filled unless type is given: a string or number is typed into a text input, textarea or contenteditable (replacing its content) or chooses a <select> option by value or label; true/false sets a checkbox; a string or true picks a radio button (the selector may match any radio in the group); a list chooses options in a multi-select. Fields that fail are reported and the rest are still filled unless stopOnError is set
+   * @param {PlaywrightFillFormArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightFillForm(args) {
+    return this.callTool("playwright_fill_form", args);
+  }
+
+  /**
+   * Press a key, optionally focusing an element first: Enter to submit a search box, Tab/Shift+Tab to move focus, Escape to close a popup, arrows to move through menus, lists and selects. Key events go to the focused element, and their usual effect (submit, focus change, toggle, scroll) is applied unless the page cancels it
+   * @param {PlaywrightPressKeyArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightPressKey(args) {
+    return this.callTool("playwright_press_key", args);
+  }
+
+  /**
+   * Read the text of an element (or the whole page body) without a screenshot. Returns the rendered innerText by default, or the raw textContent including hidden nodes; with all: true, returns the text of every match
+   * @param {PlaywrightGetTextArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGetText(args = {}) {
+    return this.callTool("playwright_get_text", args);
+  }
+
+  /**
+   * Return the outerHTML of an element, or of the whole document, to inspect page structure and build selectors. Current form values are included (passwords never are); scripts and inline event handlers are stripped by default
+   * @param {PlaywrightGetHtmlArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGetHtml(args = {}) {
+    return this.callTool("playwright_get_html", args);
+  }
+
+  /**
+   * Extract the page's main content as clean Markdown (title, byline, body, links), leaving out navigation, ads and other boilerplate. Far smaller than the HTML or a screenshot; use it to read articles, docs and search results
+   * @param {BrowserExtractContentArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserExtractContent(args = {}) {
+    return this.callTool("browser_extract_content", args);
+  }
+
+  /**
+   * Find a string or regular expression in the page's visible text, like Ctrl+F: returns the match count and each match with surrounding context and its element, and can scroll to and highlight one match. Use it to locate a phrase instead of reading the whole page
+   * @param {BrowserFindTextArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserFindText(args = {}) {
+    return this.callTool("browser_find_text", args);
+  }
+
+  /**
+   * Harvest an infinite-scroll feed in one call: scrolls repeatedly, waits for new items to load, and returns every distinct item matching itemSelector until maxItems, maxScrolls or timeoutMs is reached or the feed stops growing. Each item has its text and first link, or the named fields. The page is left scrolled where collection stopped
+   * @param {BrowserCollectItemsArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  browserCollectItems(args) {
+    return this.callTool("browser_collect_items", args);
+  }
+
+  /**
+   * Extract an HTML table as structured rows keyed by column header. Cells spanning several rows or columns are repeated into each one, and header rows are detected from <thead> or <th> cells. Picks the table by selector or index, or else the page's largest visible table. saveCsv also writes it as a CSV file to the session's artifact directory
+   * @param {BrowserExtractTableArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserExtractTable(args = {}) {
+    return this.callTool("browser_extract_table", args);
+  }
+
+  /**
+   * Check a list of assertions against the current page in one call and return a pass/fail report. Types: exists/visible (selector), text (selector, default the whole page), url, and cookie (name; present, or with a matcher on its value). text and url need one of equals, contains or matches (a JavaScript regular expression); not inverts an assertion
+   * @param {AssertPageArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  assertPage(args) {
+    return this.callTool("assert_page", args);
+  }
+
+  /**
+   * Return the page's accessibility tree as JSON: each node's role, accessible name, value, heading level and states (checked, expanded, disabled, focused, ...) plus a CSS selector to act on it. A cheaper, more reliable page representation than screenshots for text-only models
+   * @param {BrowserAccessibilityTreeArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserAccessibilityTree(args = {}) {
+    return this.callTool("browser_accessibility_tree", args);
+  }
+
+  /**
+   * Focus an element, firing focus/focusin events so focus-triggered validation and widgets run
+   * @param {PlaywrightFocusArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightFocus(args) {
+    return this.callTool("playwright_focus", args);
+  }
+
+  /**
+   * Remove focus from an element (default: the focused element), firing blur/focusout so on-blur validation runs
+   * @param {PlaywrightBlurArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightBlur(args = {}) {
+    return this.callTool("playwright_blur", args);
+  }
+
+  /**
+   * Describe the currently focused element (tag, id, name, type, value, unique selector)
+   * @param {PlaywrightGetFocusedElementArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGetFocusedElement(args = {}) {
+    return this.callTool("playwright_get_focused_element", args);
+  }
+
+  /**
+   * Describe an element's geometry and state: bounding box (viewport and page coordinates), whether it is visible (and what hides it), enabled, how much of it is in the viewport, and whether a click at its center would reach it or what covers it. Use it to decide whether to scroll first, whether a click can work, or where to crop a screenshot
+   * @param {PlaywrightGetElementArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightGetElement(args = {}) {
+    return this.callTool("playwright_get_element", args);
+  }
+
+  /**
+   * Run a JavaScript expression or function in the page and return its JSON-serializable result. Disabled unless the server runs with --allow-evaluate or the session is inside a passkey_authorize window
+   * @param {PlaywrightEvaluateArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightEvaluate(args) {
+    return this.callTool("playwright_evaluate", args);
+  }
+
+  /**
+   * Type into one or more fields with realistic keyboard events (keydown/keypress/input/keyup per character, IME composition events for CJK) instead of setting values directly, for frameworks that ignore programmatic value changes
+   * @param {TypeTextArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  typeText(args = {}) {
+    return this.callTool("type_text", args);
+  }
+
+  /**
+   * Set a date on a native date input or JS date-picker widget, injecting the value first and falling back to navigating the calendar
+   * @param {SetDateArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  setDate(args) {
+    return this.callTool("set_date", args);
+  }
+
+  /**
+   * Override Accept-Language, the JavaScript locale (navigator.language, Intl) and the timezone for this session's tab, to reproduce region-specific prices and formats
+   * @param {BrowserEmulateLocaleArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserEmulateLocale(args = {}) {
+    return this.callTool("browser_emulate_locale", args);
+  }
+
+  /**
+   * Spoof the position navigator.geolocation reports for this session's tab and allow the location permission for the site, to exercise store locators and delivery flows. The override lasts until reset or the tab closes
+   * @param {BrowserSetGeolocationArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserSetGeolocation(args = {}) {
+    return this.callTool("browser_set_geolocation", args);
+  }
+
+  /**
+   * Emulate a slow network (3G, 4G, offline) and/or CPU slowdown for this session's tab, to measure pages under realistic constraints
+   * @param {BrowserThrottleArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserThrottle(args = {}) {
+    return this.callTool("browser_throttle", args);
+  }
+
+  /**
+   * Take a screenshot of the current page or a specific element. With highlightSelector, the matched elements are outlined and labeled in the image, to check what an action targeted
+   * @param {PlaywrightScreenshotArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightScreenshot(args = {}) {
+    return this.callTool("playwright_screenshot", args);
+  }
+
+  /**
+   * Screenshot several elements in one call, e.g. every product card, instead of one playwright_screenshot per element. Elements visible together share one capture. Returns one PNG per element, or with save, file paths in the session's artifact directory
+   * @param {BrowserScreenshotElementsArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  browserScreenshotElements(args) {
+    return this.callTool("browser_screenshot_elements", args);
+  }
+
+  /**
+   * Export the current page as a PDF, returned as base64 or written to a file inside the client's declared roots
+   * @param {BrowserExportPdfArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserExportPdf(args = {}) {
+    return this.callTool("browser_export_pdf", args);
+  }
+
+  /**
+   * Open a menu by hovering or clicking its trigger, capture the revealed items (text and selectors), and optionally close it again, all in one operation
+   * @param {ExploreMenuArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  exploreMenu(args) {
+    return this.callTool("explore_menu", args);
+  }
+
+  /**
+   * Read a region of a canvas (2D or WebGL) as a PNG image or as average colors over a grid, to verify chart or canvas rendering
+   * @param {CanvasReadPixelsArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  canvasReadPixels(args) {
+    return this.callTool("canvas_read_pixels", args);
+  }
+
+  /**
+   * Play, pause, seek, mute, or unmute an audio/video element
+   * @param {MediaControlArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  mediaControl(args) {
+    return this.callTool("media_control", args);
+  }
+
+  /**
+   * Read playback state of audio/video elements: paused, currentTime, duration, muted, volume, readyState, and errors
+   * @param {MediaGetStateArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  mediaGetState(args = {}) {
+    return this.callTool("media_get_state", args);
+  }
+
+  /**
+   * Capture the current frame of a video (or its poster image before playback) as a PNG
+   * @param {MediaCaptureFrameArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  mediaCaptureFrame(args = {}) {
+    return this.callTool("media_capture_frame", args);
+  }
+
+  /**
+   * Automatically switch this session to tabs/windows opened by its current tab (OAuth popups, external links) and back when they close; switches are reported in tool results
+   * @param {BrowserFollowPopupsArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  browserFollowPopups(args) {
+    return this.callTool("browser_follow_popups", args);
+  }
+
+  /**
+   * List the frame hierarchy of the current tab (frame id, URL, name, parent frame id) to find which frame contains a widget
+   * @param {BrowserListFramesArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserListFrames(args = {}) {
+    return this.callTool("browser_list_frames", args);
+  }
+
+  /**
+   * List service worker registrations for the current origin (scope, script URL, state)
+   * @param {BrowserListServiceWorkersArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserListServiceWorkers(args = {}) {
+    return this.callTool("browser_list_service_workers", args);
+  }
+
+  /**
+   * Unregister service workers for the current origin so the next load fetches a fresh app shell
+   * @param {BrowserUnregisterServiceWorkersArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserUnregisterServiceWorkers(args = {}) {
+    return this.callTool("browser_unregister_service_workers", args);
+  }
+
+  /**
+   * Clear cache, cookies, and storage for an origin (default: current tab's origin) to reproduce a first visit or recover from corrupted client state
+   * @param {BrowserClearSiteDataArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserClearSiteData(args = {}) {
+    return this.callTool("browser_clear_site_data", args);
+  }
+
+  /**
+   * List cookies for a domain or URL (default: the current tab's URL) to check login state or carry a session into another flow. Values are omitted unless includeValues is true
+   * @param {BrowserGetCookiesArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserGetCookies(args = {}) {
+    return this.callTool("browser_get_cookies", args);
+  }
+
+  /**
+   * Set a cookie for a domain or URL (default: the current tab's URL)
+   * @param {BrowserSetCookieArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  browserSetCookie(args) {
+    return this.callTool("browser_set_cookie", args);
+  }
+
+  /**
+   * Delete cookies for a domain or URL (default: the current tab's URL), optionally only those with a given name; leaves other site data alone
+   * @param {BrowserClearCookiesArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserClearCookies(args = {}) {
+    return this.callTool("browser_clear_cookies", args);
+  }
+
+  /**
+   * List the browser's downloads, newest first, with their state (in_progress, complete, interrupted), progress and file path on disk
+   * @param {BrowserListDownloadsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserListDownloads(args = {}) {
+    return this.callTool("browser_list_downloads", args);
+  }
+
+  /**
+   * Wait for a download to finish (e.g. after clicking "Export CSV") and return its file path. Without id, waits for the newest matching download started in the last sinceSeconds. Optionally moves the file into this session's artifacts or to savePath
+   * @param {BrowserWaitForDownloadArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserWaitForDownload(args = {}) {
+    return this.callTool("browser_wait_for_download", args);
+  }
+
+  /**
+   * Start recording JS/CSS code coverage and a performance trace in the current tab over the DevTools Protocol (Chrome shows a debugging banner while recording). Interact with the page, then call browser_profiling_stop
+   * @param {BrowserProfilingStartArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserProfilingStart(args = {}) {
+    return this.callTool("browser_profiling_start", args);
+  }
+
+  /**
+   * Stop recording started by browser_profiling_start, save the trace (opens in DevTools or Perfetto) and coverage files to this session's artifact directory, and summarize unused bytes per script/stylesheet and long tasks
+   * @param {BrowserProfilingStopArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserProfilingStop(args = {}) {
+    return this.callTool("browser_profiling_stop", args);
+  }
+
+  /**
+   * Start recording a video of the current tab, e.g. to review later why a multi-step run failed. Chrome only paints visible tabs, so keep the tab in front (Chrome shows a debugging banner while recording). Call browser_recording_stop to save the video
+   * @param {BrowserRecordingStartArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserRecordingStart(args = {}) {
+    return this.callTool("browser_recording_start", args);
+  }
+
+  /**
+   * Stop the recording started by browser_recording_start and encode it into a video in ~/.agent-browser/recordings. Returns the path, duration and frame count
+   * @param {BrowserRecordingStopArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserRecordingStop(args = {}) {
+    return this.callTool("browser_recording_stop", args);
+  }
+
+  /**
+   * List captured WebSocket connections (lifecycle, message counts/bytes, recent message previews) and WebRTC connection events
+   * @param {NetworkListWebsocketsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  networkListWebsockets(args = {}) {
+    return this.callTool("network_list_websockets", args);
+  }
+
+  /**
+   * Configure network capture: optionally keep request/response bodies up to a size limit, and add header names or regex patterns to redact before storage. Auth headers and common secret patterns are always redacted
+   * @param {NetworkCaptureConfigureArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  networkCaptureConfigure(args = {}) {
+    return this.callTool("network_capture_configure", args);
+  }
+
+  /**
+   * List recent HTTP requests the page made (method, URL, status, type, start time, duration, size), newest first, to see which API calls actually fired and how they answered. Defaults to this session's tab, or the active tab. Use network_get_request with an id for headers
+   * @param {NetworkListRequestsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  networkListRequests(args = {}) {
+    return this.callTool("network_list_requests", args);
+  }
+
+  /**
+   * Get captured HTTP requests (headers, redacted bodies when enabled) by request id or URL substring
+   * @param {NetworkGetRequestArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  networkGetRequest(args = {}) {
+    return this.callTool("network_get_request", args);
+  }
+
+  /**
+   * List files this session has written to its artifact directory (used for savePath when the client declares no roots); idle session directories are cleaned up automatically
+   * @param {SessionArtifactsListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  sessionArtifactsList(args = {}) {
+    return this.callTool("session_artifacts_list", args);
+  }
+
+  /**
+   * Full-text search across everything the server kept, from every session: page text of saved snapshots, session reports and text artifacts, and audit log events. Use it to find where something was seen before (e.g. query "invoice total" with since "7d"). Results are ranked, newest first among equals, with a snippet
+   * @param {ArtifactsSearchArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  artifactsSearch(args) {
+    return this.callTool("artifacts_search", args);
+  }
+
+  /**
+   * Write a self-contained HTML report of what this session did: every tool call with its timing and outcome, the session's screenshots, a network summary and credential/authorization audit events. Typed values and secrets are left out; returns the file path
+   * @param {SessionReportArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  sessionReport(args = {}) {
+    return this.callTool("session_report", args);
+  }
+
+  /**
+   * Re-run a failed tool call from a session's timeline, optionally with edited arguments, to nudge a stuck session forward. The retry is recorded with the id of the call it re-ran and the first call of the chain, on the timeline and in the audit log. Call ids (c1, c2, ...) appear in session_report
+   * @param {HistoryRetryArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  historyRetry(args = {}) {
+    return this.callTool("history_retry", args);
+  }
+
+  /**
+   * List the MCP sessions connected on any transport (stdio agent, TCP, WebSocket, HTTP, SSE) with their client, connection time and observers, plus this session's own id
+   * @param {SessionListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  sessionList(args = {}) {
+    return this.callTool("session_list", args);
+  }
+
+  /**
+   * Receive another session's tool calls as notifications/session_activity (call id, tool, redacted arguments, duration, error) so a supervisor can follow an agent; stop with stop: true
+   * @param {SessionObserveArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  sessionObserve(args) {
+    return this.callTool("session_observe", args);
+  }
+
+  /**
+   * Run a tool call as another session (e.g. the stdio agent), for a supervisor taking a step on its behalf. The call is recorded on that session's timeline and in the audit log as injected by this session, and that session receives notifications/session_injected. Session, passkey and recovery tools and playwright_evaluate can't be injected
+   * @param {SessionInjectArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  sessionInject(args) {
+    return this.callTool("session_inject", args);
+  }
+
+  /**
+   * Summarize authorization and credential activity per day from the audit log: event counts by kind and relying party and distinct sessions. Events older than the retention window are kept as daily summaries; optionally compact now
+   * @param {HistoryStatsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  historyStats(args = {}) {
+    return this.callTool("history_stats", args);
+  }
+
+  /**
+   * Send a test alert through the channels configured in ~/.agent-browser/alerts.json and report each channel's delivery result, along with the configured channels and per-class routes
+   * @param {AlertsTestArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  alertsTest(args = {}) {
+    return this.callTool("alerts_test", args);
+  }
+
+  /**
+   * Report disk usage of the ~/.agent-browser data directory against the quotas for artifacts, recordings and audit log segments; optionally run the least-recently-used cleanup now
+   * @param {StorageStatusArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  storageStatus(args = {}) {
+    return this.callTool("storage_status", args);
+  }
+
+  /**
+   * Report the extension command queue (in-flight depth, limit, high-water mark, admitted/rejected/shed counts), pending requests, connected MCP sessions and the companion browser pool
+   * @param {ServerMetricsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  serverMetrics(args = {}) {
+    return this.callTool("server_metrics", args);
+  }
+
+  /**
+   * Show the opt-in local usage telemetry (tool call and failure counts only, never URLs or content; enabled with --telemetry) and a differentially private export of it for sharing with maintainers
+   * @param {TelemetryStatusArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  telemetryStatus(args = {}) {
+    return this.callTool("telemetry_status", args);
+  }
+
+  /**
+   * Developer tool: diff the server's tool registry against a saved manifest (a tools/list result) and report added, removed and changed tool schemas, flagging breaking changes
+   * @param {CapabilitiesDiffArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  capabilitiesDiff(args = {}) {
+    return this.callTool("capabilities_diff", args);
+  }
+
+  /**
+   * Check a URL's reachability from the server (DNS, TCP connect, TLS handshake, HTTP status) without navigating the browser; each stage is timed and the verdict says where it failed
+   * @param {PreflightArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  preflight(args) {
+    return this.callTool("preflight", args);
+  }
+
+  /**
+   * Enable or disable passkey automation for WebAuthn flows
+   * @param {PasskeyEnableArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyEnable(args) {
+    return this.callTool("passkey_enable", args);
+  }
+
+  /**
+   * Get the current status of passkey automation
+   * @param {PasskeyStatusArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyStatus(args = {}) {
+    return this.callTool("passkey_status", args);
+  }
+
+  /**
+   * List all stored passkey credentials
+   * @param {PasskeyListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyList(args = {}) {
+    return this.callTool("passkey_list", args);
+  }
+
+  /**
+   * Clear all stored passkey credentials
+   * @param {PasskeyClearArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyClear(args = {}) {
+    return this.callTool("passkey_clear", args);
+  }
+
+  /**
+   * Authorize AI agent to use passkeys for a limited time (requires Touch ID on macOS)
+   * @param {PasskeyAuthorizeArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyAuthorize(args = {}) {
+    return this.callTool("passkey_authorize", args);
+  }
+
+  /**
+   * Check if AI agent is currently authorized to use passkeys
+   * @param {PasskeyAuthorizationStatusArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyAuthorizationStatus(args = {}) {
+    return this.callTool("passkey_authorization_status", args);
+  }
+
+  /**
+   * Mint a signed token that lets another MCP client (e.g. a sub-agent) use one site's passkey exactly once, without authorizing that client generally. Requires an active passkey authorization; the token expires within 15 minutes and when this session's authorization ends
+   * @param {PasskeyGrantCreateArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyGrantCreate(args) {
+    return this.callTool("passkey_grant_create", args);
+  }
+
+  /**
+   * Redeem a token from passkey_grant_create for this session. The session may then use that site's passkey once; the token cannot be redeemed again
+   * @param {PasskeyGrantRedeemArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  passkeyGrantRedeem(args) {
+    return this.callTool("passkey_grant_redeem", args);
+  }
+
+  /**
+   * Store account recovery secrets (backup codes or security question answers) encrypted at rest. Requires elevated authorization (recovery_authorize)
+   * @param {RecoverySecretStoreArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  recoverySecretStore(args) {
+    return this.callTool("recovery_secret_store", args);
+  }
+
+  /**
+   * List stored recovery secrets with values redacted. Requires an active passkey authorization
+   * @param {RecoverySecretListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  recoverySecretList(args = {}) {
+    return this.callTool("recovery_secret_list", args);
+  }
+
+  /**
+   * Grant short-lived elevated authorization to store, retrieve or delete recovery secrets (requires an active passkey authorization, max 15 minutes)
+   * @param {RecoveryAuthorizeArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  recoveryAuthorize(args = {}) {
+    return this.callTool("recovery_authorize", args);
+  }
+
+  /**
+   * Retrieve a recovery secret under elevated authorization. Backup codes are consumed and never returned again
+   * @param {RecoverySecretGetArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  recoverySecretGet(args) {
+    return this.callTool("recovery_secret_get", args);
+  }
+
+  /**
+   * Delete all recovery secrets stored for a relying party. Requires elevated authorization (recovery_authorize)
+   * @param {RecoverySecretDeleteArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  recoverySecretDelete(args) {
+    return this.callTool("recovery_secret_delete", args);
+  }
+
+  /**
+   * Persist a small JSON value under a key so later sessions can read it back
+   * @param {StateSetArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  stateSet(args) {
+    return this.callTool("state_set", args);
+  }
+
+  /**
+   * Read a stored value by key, or list all keys in a workspace when key is omitted
+   * @param {StateGetArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  stateGet(args = {}) {
+    return this.callTool("state_get", args);
+  }
+
+  /**
+   * Delete a stored key
+   * @param {StateDeleteArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  stateDelete(args) {
+    return this.callTool("state_delete", args);
+  }
+
+  /**
+   * Reshape JSON with a JavaScript expression evaluated in the server (e.g. filter, map or rename fields of extracted rows) without another round trip or page evaluate. The input is bound to `data`. Supports literals, template strings, regex literals, spread, optional chaining, arrow functions and common Array/String/Number/Object/Math/JSON methods; no statements, assignments or host access, and evaluation is step-limited
+   * @param {DataTransformArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  dataTransform(args) {
+    return this.callTool("data_transform", args);
+  }
+
+  /**
+   * Capture the current page (DOM, accessibility tree, screenshot) and persist it under a name for later sessions to reference
+   * @param {SnapshotSaveArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  snapshotSave(args) {
+    return this.callTool("snapshot_save", args);
+  }
+
+  /**
+   * Load a saved snapshot by name, or list saved snapshots when name is omitted
+   * @param {SnapshotGetArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  snapshotGet(args = {}) {
+    return this.callTool("snapshot_get", args);
+  }
+
+  /**
+   * Screenshot the current page and store it as a named visual regression baseline for the current viewport size (one image per viewport under each name)
+   * @param {BaselineSetArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  baselineSet(args) {
+    return this.callTool("baseline_set", args);
+  }
+
+  /**
+   * List stored visual regression baselines with their URL, viewport and capture time
+   * @param {BaselineListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  baselineList(args = {}) {
+    return this.callTool("baseline_list", args);
+  }
+
+  /**
+   * Delete a visual regression baseline, or only its image for one viewport
+   * @param {BaselineDeleteArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  baselineDelete(args) {
+    return this.callTool("baseline_delete", args);
+  }
+
+  /**
+   * Screenshot the current page and compare it pixel by pixel with a stored baseline at the same viewport size. Reports the share of differing pixels; on failure the capture and a diff image are saved to the session's artifacts
+   * @param {ScreenshotDiffArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  screenshotDiff(args) {
+    return this.callTool("screenshot_diff", args);
+  }
+
+  /**
+   * Watch a page for changes: re-visit the URL on an interval in a background tab, extract a value, and send notifications/watch_changed (and POST to a webhook) when it differs from the previous run
+   * @param {WatchAddArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  watchAdd(args) {
+    return this.callTool("watch_add", args);
+  }
+
+  /**
+   * List page watches with their last value, last check, and change count
+   * @param {WatchListArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  watchList(args = {}) {
+    return this.callTool("watch_list", args);
+  }
+
+  /**
+   * Stop watching a page
+   * @param {WatchRemoveArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  watchRemove(args) {
+    return this.callTool("watch_remove", args);
+  }
+
+  /**
+   * Detect if a modal, popup, or overlay is present on the page
+   * @param {PlaywrightDetectModalArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightDetectModal(args = {}) {
+    return this.callTool("playwright_detect_modal", args);
+  }
+
+  /**
+   * Attempt to dismiss any detected modals on the page
+   * @param {PlaywrightDismissModalArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  playwrightDismissModal(args = {}) {
+    return this.callTool("playwright_dismiss_modal", args);
+  }
+
+  /**
+   * Choose how this session's tab answers JavaScript dialogs (alert, confirm, prompt, beforeunload). accept and dismiss answer them automatically; manual leaves them open and fails other commands on the tab with a 'JavaScript dialog open' error until browser_respond_dialog answers. Without a policy an unexpected confirm() blocks the page until the command times out
+   * @param {BrowserHandleDialogsArgs} args
+   * @returns {Promise<ToolResult>}
+   */
+  browserHandleDialogs(args) {
+    return this.callTool("browser_handle_dialogs", args);
+  }
+
+  /**
+   * Answer the JavaScript dialog open on this session's tab, e.g. after a 'JavaScript dialog open' error under the manual policy of browser_handle_dialogs
+   * @param {BrowserRespondDialogArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserRespondDialog(args = {}) {
+    return this.callTool("browser_respond_dialog", args);
+  }
+
+  /**
+   * List recent JavaScript dialogs reported while browser_handle_dialogs was on, newest first: type, message, page URL, whether it is still open, and how it was answered
+   * @param {BrowserListDialogsArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  browserListDialogs(args = {}) {
+    return this.callTool("browser_list_dialogs", args);
+  }
+
+  /**
+   * Check the current page for accessibility problems (missing alt text and labels, unnamed buttons and links, low color contrast, invalid ARIA, heading order, and more) and return violations grouped by rule with impact, WCAG reference and example elements, plus a 0-100 score over the rules that applied
+   * @param {AuditAccessibilityArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  auditAccessibility(args = {}) {
+    return this.callTool("audit_accessibility", args);
+  }
+
+  /**
+   * Find broken links on the current page: extracts its links and checks each from the server (HEAD, falling back to GET) with bounded concurrency, following redirects. Returns each link's final status, redirect chain and link text, with a summary of ok, broken (4xx/5xx) and unreachable links. Hosts on private or loopback addresses are skipped unless the page is on one or allowPrivate is set
+   * @param {CheckLinksArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  checkLinks(args = {}) {
+    return this.callTool("check_links", args);
+  }
+
+  /**
+   * Audit the current page for search-engine readiness: title, meta description, canonical, robots meta, heading structure, hreflang, Open Graph and structured data from the page, plus server-side checks of robots.txt rules for the page, sitemap availability and the HTTP status of the canonical URL and key links. Returns the collected facts and issues graded error/warning/notice
+   * @param {AuditSeoArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  auditSeo(args = {}) {
+    return this.callTool("audit_seo", args);
+  }
+
+  /**
+   * Accept or reject a cookie-consent banner using rules for common consent platforms (OneTrust, Cookiebot, Didomi, Quantcast, Usercentrics, ...), or turn automatic handling after every navigation on or off
+   * @param {DismissConsentArgs} [args]
+   * @returns {Promise<ToolResult>}
+   */
+  dismissConsent(args = {}) {
+    return this.callTool("dismiss_consent", args);
+  }
+}
@@ -0,0 +1,1273 @@
+"""Thin Python client for the agent-browser MCP TCP interface (localhost:8084).
+
+@generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit.
+
+Standard library only. One request is in flight at a time per client; use a
+client per thread for concurrency. If the connection drops, the failing call
+raises and the next call reconnects (a new MCP session). Each connection
+authenticates with `token`, or the one the server wrote to
+~/.agent-browser/token.
+"""
+
+import itertools
+import json
+import os
+import socket
+import threading
+from typing import Any, Callable, Dict, List, Optional
+
+PROTOCOL_VERSION = "2025-06-18"
+DEFAULT_HOST = "127.0.0.1"
+DEFAULT_PORT = 8084
+
+# Every tool in the server's registry, in tools/list order
+TOOL_NAMES = [
+    "playwright_navigate",
+    "browser_page_info",
+    "browser_bring_to_front",
+    "browser_wait_for_load",
+    "playwright_go_back",
+    "playwright_go_forward",
+    "playwright_reload",
+    "playwright_click",
+    "playwright_dblclick",
+    "playwright_context_click",
+    "scroll_into_view",
+    "playwright_fill",
+    "playwright_select",
+    "browser_list_forms",
+    "playwright_fill_form",
+    "playwright_press_key",
+    "playwright_get_text",
+    "playwright_get_html",
+    "browser_extract_content",
+    "browser_find_text",
+    "browser_collect_items",
+    "browser_extract_table",
+    "assert_page",
+    "browser_accessibility_tree",
+    "playwright_focus",
+    "playwright_blur",
+    "playwright_get_focused_element",
+    "playwright_get_element",
+    "playwright_evaluate",
+    "type_text",
+    "set_date",
+    "browser_emulate_locale",
+    "browser_set_geolocation",
+    "browser_throttle",
+    "playwright_screenshot",
+    "browser_screenshot_elements",
+    "browser_export_pdf",
+    "explore_menu",
+    "canvas_read_pixels",
+    "media_control",
+    "media_get_state",
+    "media_capture_frame",
+    "browser_follow_popups",
+    "browser_list_frames",
+    "browser_list_service_workers",
+    "browser_unregister_service_workers",
+    "browser_clear_site_data",
+    "browser_get_cookies",
+    "browser_set_cookie",
+    "browser_clear_cookies",
+    "browser_list_downloads",
+    "browser_wait_for_download",
+    "browser_profiling_start",
+    "browser_profiling_stop",
+    "browser_recording_start",
+    "browser_recording_stop",
+    "network_list_websockets",
+    "network_capture_configure",
+    "network_list_requests",
+    "network_get_request",
+    "session_artifacts_list",
+    "artifacts_search",
+    "session_report",
+    "history_retry",
+    "session_list",
+    "session_observe",
+    "session_inject",
+    "history_stats",
+    "alerts_test",
+    "storage_status",
+    "server_metrics",
+    "telemetry_status",
+    "capabilities_diff",
+    "preflight",
+    "passkey_enable",
+    "passkey_status",
+    "passkey_list",
+    "passkey_clear",
+    "passkey_authorize",
+    "passkey_authorization_status",
+    "passkey_grant_create",
+    "passkey_grant_redeem",
+    "recovery_secret_store",
+    "recovery_secret_list",
+    "recovery_authorize",
+    "recovery_secret_get",
+    "recovery_secret_delete",
+    "state_set",
+    "state_get",
+    "state_delete",
+    "data_transform",
+    "snapshot_save",
+    "snapshot_get",
+    "baseline_set",
+    "baseline_list",
+    "baseline_delete",
+    "screenshot_diff",
+    "watch_add",
+    "watch_list",
+    "watch_remove",
+    "playwright_detect_modal",
+    "playwright_dismiss_modal",
+    "browser_handle_dialogs",
+    "browser_respond_dialog",
+    "browser_list_dialogs",
+    "audit_accessibility",
+    "check_links",
+    "audit_seo",
+    "dismiss_consent",
+]
+
+
+def _local_token() -> Optional[str]:
+    """Token written by a server on this machine, re-read on every connect"""
+    try:
+        with open(os.path.expanduser("~/.agent-browser/token")) as f:
+            return f.read().strip() or None
+    except OSError:
+        return None
+
+
+class AgentBrowserError(Exception):
+    """JSON-RPC error returned by the server"""
+
+    def __init__(self, code: int, message: str, data: Any = None):
+        super().__init__(f"{message} ({code})")
+        self.code = code
+        self.message = message
+        self.data = data
+
+
+class ToolResult:
+    """Result of tools/call"""
+
+    def __init__(self, raw: Any):
+        self.raw = raw
+        if not isinstance(raw, dict) or "content" not in raw:
+            # Tools the server answers itself return their JSON without a content wrapper
+            raw = {"content": [], "structuredContent": raw}
+        self.content: List[Dict[str, Any]] = raw.get("content", [])
+        self.structured_content: Optional[Any] = raw.get("structuredContent")
+        self.is_error: bool = raw.get("isError", False)
+
+    @property
+    def text(self) -> str:
+        return "\n".join(c["text"] for c in self.content if "text" in c)
+
+    def json(self) -> Any:
+        """structuredContent, or the text block parsed as JSON"""
+        if self.structured_content is not None:
+            return self.structured_content
+        return json.loads(self.text)
+
+
+class AgentBrowserClient:
+    def __init__(
+        self,
+        host: str = DEFAULT_HOST,
+        port: int = DEFAULT_PORT,
+        timeout: float = 60.0,
+        client_name: str = "agent-browser-python",
+        on_notification: Optional[Callable[[str, Any], None]] = None,
+        token: Optional[str] = None,
+    ):
+        self.host = host
+        self.port = port
+        self.timeout = timeout
+        self.client_name = client_name
+        self.on_notification = on_notification
+        self.token = token
+        self.protocol_version: Optional[str] = None
+        self._sock: Optional[socket.socket] = None
+        self._file = None
+        self._lock = threading.Lock()
+        self._ids = itertools.count(1)
+
+    def __enter__(self) -> "AgentBrowserClient":
+        self.connect()
+        return self
+
+    def __exit__(self, *exc) -> None:
+        self.close()
+
+    def connect(self) -> None:
+        with self._lock:
+            if self._sock is None:
+                self._connect_locked()
+
+    def close(self) -> None:
+        with self._lock:
+            self._close_locked()
+
+    def request(self, method: str, params: Optional[Dict[str, Any]] = None) -> Any:
+        """Send a raw JSON-RPC request and return its result"""
+        with self._lock:
+            if self._sock is None:
+                self._connect_locked()
+            try:
+                return self._exchange(method, params or {})
+            except (OSError, ValueError):
+                self._close_locked()
+                raise
+
+    def call_tool(self, name: str, arguments: Optional[Dict[str, Any]] = None) -> ToolResult:
+        """Call any tool by name; arguments set to None are omitted"""
+        arguments = {k: v for k, v in (arguments or {}).items() if v is not None}
+        return ToolResult(self.request("tools/call", {"name": name, "arguments": arguments}))
+
+    def list_tools(self) -> List[Dict[str, Any]]:
+        return self.request("tools/list").get("tools", [])
+
+    def ping(self) -> None:
+        self.request("ping")
+
+    def _connect_locked(self) -> None:
+        self._sock = socket.create_connection((self.host, self.port), timeout=self.timeout)
+        self._file = self._sock.makefile("rb")
+        try:
+            token = self.token or _local_token()
+            if token:
+                self._exchange("authenticate", {"token": token})
+            result = self._exchange(
+                "initialize",
+                {
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": self.client_name, "version": "0.1.0"},
+                },
+            )
+            self.protocol_version = result.get("protocolVersion")
+            self._send({"jsonrpc": "2.0", "method": "notifications/initialized", "params": {}})
+        except Exception:
+            self._close_locked()
+            raise
+
+    def _close_locked(self) -> None:
+        if self._sock is not None:
+            try:
+                self._sock.close()
+            finally:
+                self._sock = None
+                self._file = None
+
+    def _send(self, message: Dict[str, Any]) -> None:
+        self._sock.sendall(json.dumps(message).encode() + b"\n")
+
+    def _exchange(self, method: str, params: Dict[str, Any]) -> Any:
+        request_id = next(self._ids)
+        self._send({"jsonrpc": "2.0", "id": request_id, "method": method, "params": params})
+        while True:
+            line = self._file.readline()
+            if not line:
+                raise ConnectionError("Connection to agent-browser closed")
+            if not line.strip():
+                continue
+            message = json.loads(line)
+            if "method" in message:
+                if "id" in message and message["method"] == "ping":
+                    # Server keep-alive
+                    self._send({"jsonrpc": "2.0", "id": message["id"], "result": {}})
+                elif "id" in message:
+                    # No client capabilities are declared, so other server requests are unsupported
+                    self._send(
+                        {
+                            "jsonrpc": "2.0",
+                            "id": message["id"],
+                            "error": {"code": -32601, "message": f"Method not found: {message['method']}"},
+                        }
+                    )
+                elif self.on_notification is not None:
+                    self.on_notification(message["method"], message.get("params"))
+                continue
+            if message.get("id") != request_id:
+                continue
+            if "error" in message:
+                error = message["error"]
+                raise AgentBrowserError(error.get("code", -32000), error.get("message", ""), error.get("data"))
+            return message.get("result")
+
+    # ------------------------------------------------------------------
+    # Tools
+    # ------------------------------------------------------------------
+
+    def playwright_navigate(self, *, url: str, bypass_service_worker: Optional[bool] = None, timeout_ms: Optional[float] = None, wait_until: Optional[str] = None) -> ToolResult:
+        """Navigate to a URL in the browser
+
+        :param url: The URL to navigate to
+        :param bypass_service_worker: Bypass registered service workers for this navigation so the network response is used instead of a cached app shell (default: false)
+        :param timeout_ms: How long to wait for waitUntil (default: 20000)
+        :param wait_until: Block until the new page reaches this state: DOMContentLoaded fired, load fired, or load plus 500 ms without network requests (default: don't wait)
+        """
+        return self.call_tool("playwright_navigate", {"url": url, "bypassServiceWorker": bypass_service_worker, "timeoutMs": timeout_ms, "waitUntil": wait_until})
+
+    def browser_page_info(self) -> ToolResult:
+        """Answer "where am I?" in one cheap call: the current URL, title, ready state, scroll position, viewport and document size"""
+        return self.call_tool("browser_page_info", {})
+
+    def browser_bring_to_front(self, *, check_only: Optional[bool] = None, focus_window: Optional[bool] = None) -> ToolResult:
+        """Make the automated tab the active tab of a focused, unminimized window and report whether the page is now visible. Background tabs throttle timers and pause animations, media and requestAnimationFrame, which silently stalls some pages
+
+        :param check_only: Only report whether the tab is in the foreground, without changing anything (default: false)
+        :param focus_window: Also focus the tab's window above other windows (default: true)
+        """
+        return self.call_tool("browser_bring_to_front", {"checkOnly": check_only, "focusWindow": focus_window})
+
+    def browser_wait_for_load(self, *, expect_navigation: Optional[bool] = None, idle_ms: Optional[float] = None, max_inflight: Optional[int] = None, state: Optional[str] = None, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Wait until the page reaches a load state, instead of sleeping after an action. With expectNavigation, waits for the page the previous action navigated to
+
+        :param expect_navigation: Only accept a document created after the previous command started, e.g. the page a click submitted a form to; without it the current page counts (default: false)
+        :param idle_ms: Quiet period networkidle needs (default: 500)
+        :param max_inflight: Requests allowed to stay open during the quiet period, for pages with long polls or streams (default: 0)
+        :param state: domcontentloaded: the HTML is parsed; load: the page and its subresources finished loading; networkidle: load, then idleMs without network requests (default: load)
+        :param timeout_ms: How long to wait (default: 20000)
+        """
+        return self.call_tool("browser_wait_for_load", {"expectNavigation": expect_navigation, "idleMs": idle_ms, "maxInflight": max_inflight, "state": state, "timeoutMs": timeout_ms})
+
+    def playwright_go_back(self, *, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Go back one page in the tab's history and wait for it to load; returns the resulting URL and title
+
+        :param timeout_ms: How long to wait for the page to load (default: 20000)
+        """
+        return self.call_tool("playwright_go_back", {"timeoutMs": timeout_ms})
+
+    def playwright_go_forward(self, *, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Go forward one page in the tab's history and wait for it to load; returns the resulting URL and title
+
+        :param timeout_ms: How long to wait for the page to load (default: 20000)
+        """
+        return self.call_tool("playwright_go_forward", {"timeoutMs": timeout_ms})
+
+    def playwright_reload(self, *, bypass_cache: Optional[bool] = None, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Reload the current page and wait for it to load; returns the resulting URL and title
+
+        :param bypass_cache: Ignore the HTTP cache, like a hard reload (default: false)
+        :param timeout_ms: How long to wait for the page to load (default: 20000)
+        """
+        return self.call_tool("playwright_reload", {"bypassCache": bypass_cache, "timeoutMs": timeout_ms})
+
+    def playwright_click(self, *, avoid_sticky_chrome: Optional[bool] = None, button: Optional[str] = None, capture_receipt: Optional[bool] = None, click_count: Optional[int] = None, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Click an element on the page
+
+        :param avoid_sticky_chrome: Scroll the element clear of fixed/sticky headers and footers before clicking (default: false)
+        :param button: Mouse button (default: left)
+        :param capture_receipt: After the click, save a screenshot, the final URL, and confirmation text to the session's receipts as evidence of the submission (default: false)
+        :param click_count: Number of clicks; 2 also fires dblclick (default: 1)
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector for the element to click
+        """
+        return self.call_tool("playwright_click", {"avoidStickyChrome": avoid_sticky_chrome, "button": button, "captureReceipt": capture_receipt, "clickCount": click_count, "frame": frame, "locator": locator, "selector": selector})
+
+    def playwright_dblclick(self, *, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Double-click an element, e.g. to edit a cell or select a word
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector for the element to double-click
+        """
+        return self.call_tool("playwright_dblclick", {"frame": frame, "locator": locator, "selector": selector})
+
+    def playwright_context_click(self, *, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Right-click an element to open its context menu
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector for the element to right-click
+        """
+        return self.call_tool("playwright_context_click", {"frame": frame, "locator": locator, "selector": selector})
+
+    def scroll_into_view(self, *, block: Optional[str] = None, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, offset_bottom: Optional[float] = None, offset_top: Optional[float] = None, selector: Optional[str] = None, sticky_compensation: Optional[str] = None) -> ToolResult:
+        """Scroll an element into view, compensating for fixed/sticky headers and footers so it is not left underneath them. Reports whether the element ended up visible, and why not if it didn't
+
+        :param block: Vertical alignment within the unobscured viewport (default: center)
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param offset_bottom: Pixels obscured at the bottom of the viewport; overrides detection
+        :param offset_top: Pixels obscured at the top of the viewport; overrides detection
+        :param selector: CSS selector for the element
+        :param sticky_compensation: auto detects fixed/sticky chrome at the viewport edges, none scrolls like element.scrollIntoView (default: auto)
+        """
+        return self.call_tool("scroll_into_view", {"block": block, "frame": frame, "locator": locator, "offsetBottom": offset_bottom, "offsetTop": offset_top, "selector": selector, "stickyCompensation": sticky_compensation})
+
+    def playwright_fill(self, *, value: str, capture_receipt: Optional[bool] = None, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Fill out an input field
+
+        :param value: The text to type into the input
+        :param capture_receipt: After filling, save a screenshot, the final URL, and confirmation text to the session's receipts (the typed value is not recorded; default: false)
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector for the input element
+        """
+        return self.call_tool("playwright_fill", {"value": value, "captureReceipt": capture_receipt, "frame": frame, "locator": locator, "selector": selector})
+
+    def playwright_select(self, *, frame: Optional[Any] = None, index: Optional[Any] = None, label: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None, value: Optional[Any] = None) -> ToolResult:
+        """Choose options in a <select> dropdown by value, visible label, or index (playwright_fill does not work on selects). Pass a list to choose several options in a multi-select; the previous selection is replaced
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param index: Zero-based option position(s) to select
+        :param label: Visible option text(s) to select
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector for the <select> element
+        :param value: Option value attribute(s) to select
+        """
+        return self.call_tool("playwright_select", {"frame": frame, "index": index, "label": label, "locator": locator, "selector": selector, "value": value})
+
+    def browser_list_forms(self, *, include_hidden: Optional[bool] = None, max_options: Optional[int] = None, selector: Optional[str] = None) -> ToolResult:
+        """List the forms on the page with their fields and submit buttons, to plan a playwright_fill_form call instead of guessing selectors. Each field has a selector addressing only it, its fillType for playwright_fill_form, label, name, required flag, current value (never passwords), and the options of selects and radio groups. Fields outside any <form> are listed as one extra form marked formless
+
+        :param include_hidden: Also list hidden inputs and fields that aren't displayed, each with a visible flag (default: false)
+        :param max_options: Options listed per select; optionCount has the total (default: 50)
+        :param selector: Only list forms inside this CSS selector, or just this form (default: the whole page)
+        """
+        return self.call_tool("browser_list_forms", {"includeHidden": include_hidden, "maxOptions": max_options, "selector": selector})
+
+    def playwright_fill_form(self, *, fields: List[Any], frame: Optional[Any] = None, stop_on_error: Optional[bool] = None) -> ToolResult:
+        """Fill several form fields in one call, in order, and report success per field. Each field's value decides how it is filled unless type is given: a string or number is typed into a text input, textarea or contenteditable (replacing its content) or chooses a <select> option by value or label; true/false sets a checkbox; a string or true picks a radio button (the selector may match any radio in the group); a list chooses options in a multi-select. Fields that fail are reported and the rest are still filled unless stopOnError is set
+
+        :param fields: 
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param stop_on_error: Stop at the first field that fails (default: false)
+        """
+        return self.call_tool("playwright_fill_form", {"fields": fields, "frame": frame, "stopOnError": stop_on_error})
+
+    def playwright_press_key(self, *, key: str, count: Optional[float] = None, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, modifiers: Optional[List[str]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Press a key, optionally focusing an element first: Enter to submit a search box, Tab/Shift+Tab to move focus, Escape to close a popup, arrows to move through menus, lists and selects. Key events go to the focused element, and their usual effect (submit, focus change, toggle, scroll) is applied unless the page cancels it
+
+        :param key: Enter, Tab, Escape, Backspace, Delete, Space, ArrowUp/Down/Left/Right, Home, End, PageUp, PageDown, F1-F12 or a single character; a combo like "Shift+Tab" or "Control+a" is also accepted
+        :param count: Number of times to press the key (default: 1, max: 50)
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param modifiers: Modifier keys held during the press
+        :param selector: CSS selector of an element to focus before pressing (default: the currently focused element)
+        """
+        return self.call_tool("playwright_press_key", {"key": key, "count": count, "frame": frame, "locator": locator, "modifiers": modifiers, "selector": selector})
+
+    def playwright_get_text(self, *, all: Optional[bool] = None, frame: Optional[Any] = None, limit: Optional[float] = None, locator: Optional[Dict[str, Any]] = None, max_length: Optional[float] = None, property: Optional[str] = None, selector: Optional[str] = None) -> ToolResult:
+        """Read the text of an element (or the whole page body) without a screenshot. Returns the rendered innerText by default, or the raw textContent including hidden nodes; with all: true, returns the text of every match
+
+        :param all: Return every matching element's text instead of the first (default: false)
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param limit: Matches returned with all: true (default: 100, max: 1000)
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param max_length: Characters returned per element before truncating (default: 50000, max: 1000000)
+        :param property: innerText (as rendered, hidden elements skipped) or textContent (all text nodes) (default: innerText)
+        :param selector: CSS selector (default: body)
+        """
+        return self.call_tool("playwright_get_text", {"all": all, "frame": frame, "limit": limit, "locator": locator, "maxLength": max_length, "property": property, "selector": selector})
+
+    def playwright_get_html(self, *, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, max_length: Optional[float] = None, selector: Optional[str] = None, strip_comments: Optional[bool] = None, strip_scripts: Optional[bool] = None, strip_styles: Optional[bool] = None) -> ToolResult:
+        """Return the outerHTML of an element, or of the whole document, to inspect page structure and build selectors. Current form values are included (passwords never are); scripts and inline event handlers are stripped by default
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param max_length: Characters returned before truncating (default: 200000, max: 5000000)
+        :param selector: CSS selector of the element (default: the whole document)
+        :param strip_comments: Remove HTML comments (default: true)
+        :param strip_scripts: Remove <script>, <noscript>, <template>, on* attributes and javascript: links (default: true)
+        :param strip_styles: Remove <style>, stylesheet links and style attributes (default: false)
+        """
+        return self.call_tool("playwright_get_html", {"frame": frame, "locator": locator, "maxLength": max_length, "selector": selector, "stripComments": strip_comments, "stripScripts": strip_scripts, "stripStyles": strip_styles})
+
+    def browser_extract_content(self, *, include_images: Optional[bool] = None, include_links: Optional[bool] = None, max_length: Optional[float] = None, selector: Optional[str] = None) -> ToolResult:
+        """Extract the page's main content as clean Markdown (title, byline, body, links), leaving out navigation, ads and other boilerplate. Far smaller than the HTML or a screenshot; use it to read articles, docs and search results
+
+        :param include_images: Render images as ![alt](src) (default: false)
+        :param include_links: Render links inline as [text](url); they are listed in links either way (default: true)
+        :param max_length: Characters of Markdown returned before truncating at a paragraph break (default: 20000, max: 1000000)
+        :param selector: CSS selector of the element to convert, skipping main-content detection
+        """
+        return self.call_tool("browser_extract_content", {"includeImages": include_images, "includeLinks": include_links, "maxLength": max_length, "selector": selector})
+
+    def browser_find_text(self, *, case_sensitive: Optional[bool] = None, clear: Optional[bool] = None, context_chars: Optional[float] = None, highlight: Optional[bool] = None, max_results: Optional[float] = None, query: Optional[str] = None, regex: Optional[bool] = None, scroll_to: Optional[int] = None, selector: Optional[str] = None, whole_word: Optional[bool] = None) -> ToolResult:
+        """Find a string or regular expression in the page's visible text, like Ctrl+F: returns the match count and each match with surrounding context and its element, and can scroll to and highlight one match. Use it to locate a phrase instead of reading the whole page
+
+        :param case_sensitive: Match case exactly (default: false)
+        :param clear: Only remove the highlights of the previous search
+        :param context_chars: Characters of context returned on each side of a match (default: 40, max: 500)
+        :param highlight: Highlight the matches on the page until the next search (default: true)
+        :param max_results: Maximum matches returned; the count covers all of them (default: 20, max: 500)
+        :param query: Text to find, or a JavaScript regular expression with regex
+        :param regex: Treat query as a regular expression (default: false)
+        :param scroll_to: Zero-based index of the match to scroll into view and mark as current
+        :param selector: Only search inside this CSS selector (default: the whole page)
+        :param whole_word: Only match at word boundaries (default: false)
+        """
+        return self.call_tool("browser_find_text", {"caseSensitive": case_sensitive, "clear": clear, "contextChars": context_chars, "highlight": highlight, "maxResults": max_results, "query": query, "regex": regex, "scrollTo": scroll_to, "selector": selector, "wholeWord": whole_word})
+
+    def browser_collect_items(self, *, item_selector: str, container: Optional[str] = None, fields: Optional[Dict[str, Any]] = None, idle_scrolls: Optional[int] = None, load_more_selector: Optional[str] = None, max_items: Optional[int] = None, max_scrolls: Optional[int] = None, max_text_length: Optional[int] = None, timeout_ms: Optional[int] = None, wait_ms: Optional[int] = None) -> ToolResult:
+        """Harvest an infinite-scroll feed in one call: scrolls repeatedly, waits for new items to load, and returns every distinct item matching itemSelector until maxItems, maxScrolls or timeoutMs is reached or the feed stops growing. Each item has its text and first link, or the named fields. The page is left scrolled where collection stopped
+
+        :param item_selector: CSS selector matching each item of the feed, e.g. "article.post"
+        :param container: CSS selector of the element that scrolls (default: the items' scrolling ancestor, or the page)
+        :param fields: Values to read from each item: name -> "selector" (text), "selector@attribute" or "@attribute" (of the item itself), e.g. {"title": "h2", "url": "a@href"}. Default: text and link
+        :param idle_scrolls: Scrolls at the bottom without new items before the feed counts as ended (default: 3)
+        :param load_more_selector: Button to click when the bottom is reached, for feeds with a "Load more" button
+        :param max_items: Stop after this many items (default: 100)
+        :param max_scrolls: Stop after this many scrolls (default: 50)
+        :param max_text_length: Longest text returned per value (default: 300)
+        :param timeout_ms: Stop after this long (default: 30000, at most the command timeout less 2 s)
+        :param wait_ms: How long to wait for new items after each scroll (default: 1500)
+        """
+        return self.call_tool("browser_collect_items", {"itemSelector": item_selector, "container": container, "fields": fields, "idleScrolls": idle_scrolls, "loadMoreSelector": load_more_selector, "maxItems": max_items, "maxScrolls": max_scrolls, "maxTextLength": max_text_length, "timeoutMs": timeout_ms, "waitMs": wait_ms})
+
+    def browser_extract_table(self, *, format: Optional[str] = None, header_rows: Optional[int] = None, index: Optional[int] = None, max_rows: Optional[int] = None, save_csv: Optional[bool] = None, selector: Optional[str] = None) -> ToolResult:
+        """Extract an HTML table as structured rows keyed by column header. Cells spanning several rows or columns are repeated into each one, and header rows are detected from <thead> or <th> cells. Picks the table by selector or index, or else the page's largest visible table. saveCsv also writes it as a CSV file to the session's artifact directory
+
+        :param format: Rows as objects keyed by header, or as arrays in column order (default: objects)
+        :param header_rows: Number of leading rows that hold column headers; 0 names columns "Column 1", ... (default: detected)
+        :param index: Zero-based index among the page's visible tables
+        :param max_rows: Maximum data rows returned (default: 500)
+        :param save_csv: Also save the table as tables/<time>.csv in the session's artifact directory and return csvPath (default: false)
+        :param selector: CSS selector of the table, or of an element containing it
+        """
+        return self.call_tool("browser_extract_table", {"format": format, "headerRows": header_rows, "index": index, "maxRows": max_rows, "saveCsv": save_csv, "selector": selector})
+
+    def assert_page(self, *, assertions: List[Any]) -> ToolResult:
+        """Check a list of assertions against the current page in one call and return a pass/fail report. Types: exists/visible (selector), text (selector, default the whole page), url, and cookie (name; present, or with a matcher on its value). text and url need one of equals, contains or matches (a JavaScript regular expression); not inverts an assertion
+
+        :param assertions: 
+        """
+        return self.call_tool("assert_page", {"assertions": assertions})
+
+    def browser_accessibility_tree(self, *, interesting_only: Optional[bool] = None, max_depth: Optional[int] = None, max_nodes: Optional[int] = None, selector: Optional[str] = None) -> ToolResult:
+        """Return the page's accessibility tree as JSON: each node's role, accessible name, value, heading level and states (checked, expanded, disabled, focused, ...) plus a CSS selector to act on it. A cheaper, more reliable page representation than screenshots for text-only models
+
+        :param interesting_only: Only landmarks, headings, widgets, lists, tables and images; generic containers and plain text are dropped and their children hoisted (default: true)
+        :param max_depth: Deepest level of nodes returned (default: 100)
+        :param max_nodes: Nodes returned before the tree is truncated (default: 2000)
+        :param selector: CSS selector of the subtree to return (default: the whole page)
+        """
+        return self.call_tool("browser_accessibility_tree", {"interestingOnly": interesting_only, "maxDepth": max_depth, "maxNodes": max_nodes, "selector": selector})
+
+    def playwright_focus(self, *, selector: str) -> ToolResult:
+        """Focus an element, firing focus/focusin events so focus-triggered validation and widgets run
+
+        :param selector: CSS selector for the element to focus
+        """
+        return self.call_tool("playwright_focus", {"selector": selector})
+
+    def playwright_blur(self, *, selector: Optional[str] = None) -> ToolResult:
+        """Remove focus from an element (default: the focused element), firing blur/focusout so on-blur validation runs
+
+        :param selector: CSS selector for the element to blur (default: document.activeElement)
+        """
+        return self.call_tool("playwright_blur", {"selector": selector})
+
+    def playwright_get_focused_element(self) -> ToolResult:
+        """Describe the currently focused element (tag, id, name, type, value, unique selector)"""
+        return self.call_tool("playwright_get_focused_element", {})
+
+    def playwright_get_element(self, *, frame: Optional[Any] = None, locator: Optional[Dict[str, Any]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Describe an element's geometry and state: bounding box (viewport and page coordinates), whether it is visible (and what hides it), enabled, how much of it is in the viewport, and whether a click at its center would reach it or what covers it. Use it to decide whether to scroll first, whether a click can work, or where to crop a screenshot
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param locator: Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector
+        :param selector: CSS selector; the first match is described and count reports how many matched
+        """
+        return self.call_tool("playwright_get_element", {"frame": frame, "locator": locator, "selector": selector})
+
+    def playwright_evaluate(self, *, expression: str, args: Optional[List[Any]] = None, await_promise: Optional[bool] = None, frame_id: Optional[float] = None) -> ToolResult:
+        """Run a JavaScript expression or function in the page and return its JSON-serializable result. Disabled unless the server runs with --allow-evaluate or the session is inside a passkey_authorize window
+
+        :param expression: Expression to evaluate, e.g. document.title, or a function such as (sel) => document.querySelectorAll(sel).length, which is called with args
+        :param args: JSON arguments passed to the function (ignored for plain expressions)
+        :param await_promise: Wait for a returned promise and return its value (default: true)
+        :param frame_id: Frame to run in, from browser_list_frames (default: the top frame)
+        """
+        return self.call_tool("playwright_evaluate", {"expression": expression, "args": args, "awaitPromise": await_promise, "frameId": frame_id})
+
+    def type_text(self, *, clear: Optional[bool] = None, composition: Optional[str] = None, delay_ms: Optional[float] = None, fields: Optional[List[Any]] = None, jitter_ms: Optional[float] = None, selector: Optional[str] = None, text: Optional[str] = None) -> ToolResult:
+        """Type into one or more fields with realistic keyboard events (keydown/keypress/input/keyup per character, IME composition events for CJK) instead of setting values directly, for frameworks that ignore programmatic value changes
+
+        :param clear: Clear existing field contents before typing (default: false)
+        :param composition: Emit compositionstart/update/end events: auto enables them for CJK text (default: auto)
+        :param delay_ms: Delay between keystrokes in milliseconds (default: 50)
+        :param fields: Fields typed in order within one command
+        :param jitter_ms: Random extra delay added to each keystroke, 0..jitterMs (default: 0)
+        :param selector: CSS selector of the field to type into (single-field form)
+        :param text: Text to type (single-field form)
+        """
+        return self.call_tool("type_text", {"clear": clear, "composition": composition, "delayMs": delay_ms, "fields": fields, "jitterMs": jitter_ms, "selector": selector, "text": text})
+
+    def set_date(self, *, date: str, selector: str, strategy: Optional[str] = None) -> ToolResult:
+        """Set a date on a native date input or JS date-picker widget, injecting the value first and falling back to navigating the calendar
+
+        :param date: Date as YYYY-MM-DD, or YYYY-MM-DDTHH:MM for datetime inputs
+        :param selector: CSS selector for the date input or the element that opens the picker
+        :param strategy: inject sets the value and fires input/change events, widget clicks through the calendar, auto tries inject then widget (default: auto)
+        """
+        return self.call_tool("set_date", {"date": date, "selector": selector, "strategy": strategy})
+
+    def browser_emulate_locale(self, *, accept_language: Optional[str] = None, locale: Optional[str] = None, reset: Optional[bool] = None, timezone: Optional[str] = None) -> ToolResult:
+        """Override Accept-Language, the JavaScript locale (navigator.language, Intl) and the timezone for this session's tab, to reproduce region-specific prices and formats
+
+        :param accept_language: Accept-Language header value (default: derived from locale, e.g. de-DE,de;q=0.9)
+        :param locale: BCP 47 locale, e.g. de-DE or ja-JP
+        :param reset: Remove all overrides and return to the browser's settings
+        :param timezone: IANA timezone, e.g. Europe/Berlin or America/New_York
+        """
+        return self.call_tool("browser_emulate_locale", {"acceptLanguage": accept_language, "locale": locale, "reset": reset, "timezone": timezone})
+
+    def browser_set_geolocation(self, *, accuracy: Optional[float] = None, grant_permission: Optional[bool] = None, latitude: Optional[float] = None, longitude: Optional[float] = None, origin: Optional[str] = None, reset: Optional[bool] = None) -> ToolResult:
+        """Spoof the position navigator.geolocation reports for this session's tab and allow the location permission for the site, to exercise store locators and delivery flows. The override lasts until reset or the tab closes
+
+        :param accuracy: Accuracy radius in meters (default: 100)
+        :param grant_permission: Allow the location permission for the origin so no prompt appears (default: true)
+        :param latitude: Latitude in degrees
+        :param longitude: Longitude in degrees
+        :param origin: Origin to allow location access for, e.g. https://shop.example (default: the current page's)
+        :param reset: Remove the override and the permissions this tool granted
+        """
+        return self.call_tool("browser_set_geolocation", {"accuracy": accuracy, "grantPermission": grant_permission, "latitude": latitude, "longitude": longitude, "origin": origin, "reset": reset})
+
+    def browser_throttle(self, *, cpu_rate: Optional[float] = None, download_kbps: Optional[float] = None, latency_ms: Optional[float] = None, network: Optional[str] = None, reset: Optional[bool] = None, upload_kbps: Optional[float] = None) -> ToolResult:
+        """Emulate a slow network (3G, 4G, offline) and/or CPU slowdown for this session's tab, to measure pages under realistic constraints
+
+        :param cpu_rate: CPU slowdown factor, 1 (none) to 20; 4 approximates a mid-range phone
+        :param download_kbps: Download bandwidth in kilobits per second (overrides the profile's)
+        :param latency_ms: Added round-trip latency in milliseconds (overrides the profile's)
+        :param network: Network profile; none removes network throttling
+        :param reset: Remove all network and CPU throttling
+        :param upload_kbps: Upload bandwidth in kilobits per second (overrides the profile's)
+        """
+        return self.call_tool("browser_throttle", {"cpuRate": cpu_rate, "downloadKbps": download_kbps, "latencyMs": latency_ms, "network": network, "reset": reset, "uploadKbps": upload_kbps})
+
+    def playwright_screenshot(self, *, frame: Optional[Any] = None, full_page: Optional[bool] = None, highlight_label: Optional[str] = None, highlight_selector: Optional[str] = None, save_path: Optional[str] = None, selector: Optional[str] = None) -> ToolResult:
+        """Take a screenshot of the current page or a specific element. With highlightSelector, the matched elements are outlined and labeled in the image, to check what an action targeted
+
+        :param frame: Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames
+        :param full_page: Whether to take a full page screenshot
+        :param highlight_label: Label drawn on the highlighted elements, numbered when several match (default: each element's tag, id and classes)
+        :param highlight_selector: CSS selector of elements to outline and label in the screenshot; the overlay is removed after capture
+        :param save_path: Write the PNG to this file instead of returning it; must be inside the client's declared roots (relative paths use the first root)
+        :param selector: Optional CSS selector to screenshot a specific element
+        """
+        return self.call_tool("playwright_screenshot", {"frame": frame, "fullPage": full_page, "highlightLabel": highlight_label, "highlightSelector": highlight_selector, "savePath": save_path, "selector": selector})
+
+    def browser_screenshot_elements(self, *, selectors: List[str], all: Optional[bool] = None, max_elements: Optional[float] = None, padding: Optional[float] = None, save: Optional[bool] = None) -> ToolResult:
+        """Screenshot several elements in one call, e.g. every product card, instead of one playwright_screenshot per element. Elements visible together share one capture. Returns one PNG per element, or with save, file paths in the session's artifact directory
+
+        :param selectors: CSS selectors of the elements to capture, in the order images are returned
+        :param all: Capture every element each selector matches rather than the first (default: false)
+        :param max_elements: Maximum elements captured in total (default: 20, max: 100)
+        :param padding: Pixels of surrounding page included on each side (default: 0, max: 100)
+        :param save: Write the images to elements/<time>/ in the session's artifact directory and return their paths instead of base64 (default: false)
+        """
+        return self.call_tool("browser_screenshot_elements", {"selectors": selectors, "all": all, "maxElements": max_elements, "padding": padding, "save": save})
+
+    def browser_export_pdf(self, *, landscape: Optional[bool] = None, print_background: Optional[bool] = None, save_path: Optional[str] = None, scale: Optional[float] = None) -> ToolResult:
+        """Export the current page as a PDF, returned as base64 or written to a file inside the client's declared roots
+
+        :param landscape: Landscape orientation (default: false)
+        :param print_background: Include background graphics (default: true)
+        :param save_path: Write the PDF to this file; must be inside the client's declared roots (relative paths use the first root)
+        :param scale: Rendering scale between 0.1 and 2 (default: 1)
+        """
+        return self.call_tool("browser_export_pdf", {"landscape": landscape, "printBackground": print_background, "savePath": save_path, "scale": scale})
+
+    def explore_menu(self, *, trigger: str, action: Optional[str] = None, item_selector: Optional[str] = None, menu_selector: Optional[str] = None, restore: Optional[bool] = None, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Open a menu by hovering or clicking its trigger, capture the revealed items (text and selectors), and optionally close it again, all in one operation
+
+        :param trigger: CSS selector for the element that opens the menu
+        :param action: How to open the menu (default: hover)
+        :param item_selector: CSS selector for items inside the menu (default: menu items, options, links, and buttons)
+        :param menu_selector: CSS selector for the revealed menu container (default: common ARIA menu/listbox roles)
+        :param restore: Close the menu again after capturing it (default: true)
+        :param timeout_ms: How long to wait for the menu to appear (default: 2000)
+        """
+        return self.call_tool("explore_menu", {"trigger": trigger, "action": action, "itemSelector": item_selector, "menuSelector": menu_selector, "restore": restore, "timeoutMs": timeout_ms})
+
+    def canvas_read_pixels(self, *, selector: str, grid: Optional[float] = None, height: Optional[float] = None, mode: Optional[str] = None, width: Optional[float] = None, x: Optional[float] = None, y: Optional[float] = None) -> ToolResult:
+        """Read a region of a canvas (2D or WebGL) as a PNG image or as average colors over a grid, to verify chart or canvas rendering
+
+        :param selector: CSS selector for the canvas element
+        :param grid: Cells per side for average mode, 1-16 (default: 1)
+        :param height: Region height (default: rest of the canvas)
+        :param mode: image returns a base64 PNG, average returns mean RGBA per grid cell (default: image)
+        :param width: Region width (default: rest of the canvas)
+        :param x: Left edge of the region in canvas pixels (default: 0)
+        :param y: Top edge of the region in canvas pixels (default: 0)
+        """
+        return self.call_tool("canvas_read_pixels", {"selector": selector, "grid": grid, "height": height, "mode": mode, "width": width, "x": x, "y": y})
+
+    def media_control(self, *, action: str, selector: Optional[str] = None, time: Optional[float] = None) -> ToolResult:
+        """Play, pause, seek, mute, or unmute an audio/video element
+
+        :param action: Operation to perform
+        :param selector: CSS selector for the media element (default: first audio/video on the page)
+        :param time: Target position in seconds (required for seek)
+        """
+        return self.call_tool("media_control", {"action": action, "selector": selector, "time": time})
+
+    def media_get_state(self, *, selector: Optional[str] = None) -> ToolResult:
+        """Read playback state of audio/video elements: paused, currentTime, duration, muted, volume, readyState, and errors
+
+        :param selector: CSS selector for the media element (default: every audio/video on the page)
+        """
+        return self.call_tool("media_get_state", {"selector": selector})
+
+    def media_capture_frame(self, *, selector: Optional[str] = None, time: Optional[float] = None) -> ToolResult:
+        """Capture the current frame of a video (or its poster image before playback) as a PNG
+
+        :param selector: CSS selector for the video element (default: first video on the page)
+        :param time: Seek to this position in seconds before capturing
+        """
+        return self.call_tool("media_capture_frame", {"selector": selector, "time": time})
+
+    def browser_follow_popups(self, *, enabled: bool) -> ToolResult:
+        """Automatically switch this session to tabs/windows opened by its current tab (OAuth popups, external links) and back when they close; switches are reported in tool results
+
+        :param enabled: Turn follow mode on or off for this session
+        """
+        return self.call_tool("browser_follow_popups", {"enabled": enabled})
+
+    def browser_list_frames(self, *, include_about_blank: Optional[bool] = None) -> ToolResult:
+        """List the frame hierarchy of the current tab (frame id, URL, name, parent frame id) to find which frame contains a widget
+
+        :param include_about_blank: Include about:blank and srcdoc frames (default: false)
+        """
+        return self.call_tool("browser_list_frames", {"includeAboutBlank": include_about_blank})
+
+    def browser_list_service_workers(self) -> ToolResult:
+        """List service worker registrations for the current origin (scope, script URL, state)"""
+        return self.call_tool("browser_list_service_workers", {})
+
+    def browser_unregister_service_workers(self, *, scope: Optional[str] = None) -> ToolResult:
+        """Unregister service workers for the current origin so the next load fetches a fresh app shell
+
+        :param scope: Only unregister the registration with this scope URL (default: all for the origin)
+        """
+        return self.call_tool("browser_unregister_service_workers", {"scope": scope})
+
+    def browser_clear_site_data(self, *, data_types: Optional[List[str]] = None, origin: Optional[str] = None, reload: Optional[bool] = None) -> ToolResult:
+        """Clear cache, cookies, and storage for an origin (default: current tab's origin) to reproduce a first visit or recover from corrupted client state
+
+        :param data_types: Data types to clear (default: all)
+        :param origin: Origin to clear, e.g. https://example.com (default: current tab's origin)
+        :param reload: Reload the tab after clearing (default: false)
+        """
+        return self.call_tool("browser_clear_site_data", {"dataTypes": data_types, "origin": origin, "reload": reload})
+
+    def browser_get_cookies(self, *, domain: Optional[str] = None, include_values: Optional[bool] = None, name: Optional[str] = None, url: Optional[str] = None) -> ToolResult:
+        """List cookies for a domain or URL (default: the current tab's URL) to check login state or carry a session into another flow. Values are omitted unless includeValues is true
+
+        :param domain: Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+        :param include_values: Include cookie values (default: false)
+        :param name: Only cookies with this name
+        :param url: Only cookies the browser would send to this URL (instead of domain)
+        """
+        return self.call_tool("browser_get_cookies", {"domain": domain, "includeValues": include_values, "name": name, "url": url})
+
+    def browser_set_cookie(self, *, name: str, value: str, domain: Optional[str] = None, expires: Optional[float] = None, http_only: Optional[bool] = None, max_age_seconds: Optional[int] = None, path: Optional[str] = None, same_site: Optional[str] = None, secure: Optional[bool] = None, url: Optional[str] = None) -> ToolResult:
+        """Set a cookie for a domain or URL (default: the current tab's URL)
+
+        :param name: Cookie name
+        :param value: Cookie value
+        :param domain: Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+        :param expires: Expiry as a Unix timestamp in seconds (default: session cookie)
+        :param http_only: Hide the cookie from page scripts
+        :param max_age_seconds: Expiry in seconds from now (instead of expires)
+        :param path: Cookie path (default: /)
+        :param same_site: SameSite policy; none implies secure
+        :param secure: Only send over HTTPS
+        :param url: Only cookies the browser would send to this URL (instead of domain)
+        """
+        return self.call_tool("browser_set_cookie", {"name": name, "value": value, "domain": domain, "expires": expires, "httpOnly": http_only, "maxAgeSeconds": max_age_seconds, "path": path, "sameSite": same_site, "secure": secure, "url": url})
+
+    def browser_clear_cookies(self, *, domain: Optional[str] = None, name: Optional[str] = None, url: Optional[str] = None) -> ToolResult:
+        """Delete cookies for a domain or URL (default: the current tab's URL), optionally only those with a given name; leaves other site data alone
+
+        :param domain: Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)
+        :param name: Only delete cookies with this name
+        :param url: Only cookies the browser would send to this URL (instead of domain)
+        """
+        return self.call_tool("browser_clear_cookies", {"domain": domain, "name": name, "url": url})
+
+    def browser_list_downloads(self, *, filename_contains: Optional[str] = None, limit: Optional[int] = None, state: Optional[str] = None, url_contains: Optional[str] = None) -> ToolResult:
+        """List the browser's downloads, newest first, with their state (in_progress, complete, interrupted), progress and file path on disk
+
+        :param filename_contains: Only downloads whose file path contains this text, e.g. .csv
+        :param limit: Maximum downloads to return (default: 20)
+        :param state: Only downloads in this state
+        :param url_contains: Only downloads whose URL contains this text
+        """
+        return self.call_tool("browser_list_downloads", {"filenameContains": filename_contains, "limit": limit, "state": state, "urlContains": url_contains})
+
+    def browser_wait_for_download(self, *, filename_contains: Optional[str] = None, id: Optional[int] = None, save_artifact: Optional[bool] = None, save_path: Optional[str] = None, since_seconds: Optional[int] = None, timeout_ms: Optional[int] = None, url_contains: Optional[str] = None) -> ToolResult:
+        """Wait for a download to finish (e.g. after clicking "Export CSV") and return its file path. Without id, waits for the newest matching download started in the last sinceSeconds. Optionally moves the file into this session's artifacts or to savePath
+
+        :param filename_contains: Only a download whose file path contains this text, e.g. .csv
+        :param id: Download id from browser_list_downloads
+        :param save_artifact: Move the file into downloads/ in this session's artifact directory (default: false)
+        :param save_path: Move the file to this path inside the session's roots (instead of saveArtifact)
+        :param since_seconds: Also match downloads that started this many seconds before the call (default: 60)
+        :param timeout_ms: How long to wait for the download to finish (default: 30000)
+        :param url_contains: Only a download whose URL contains this text
+        """
+        return self.call_tool("browser_wait_for_download", {"filenameContains": filename_contains, "id": id, "saveArtifact": save_artifact, "savePath": save_path, "sinceSeconds": since_seconds, "timeoutMs": timeout_ms, "urlContains": url_contains})
+
+    def browser_profiling_start(self, *, categories: Optional[List[str]] = None, coverage: Optional[List[str]] = None, trace: Optional[bool] = None) -> ToolResult:
+        """Start recording JS/CSS code coverage and a performance trace in the current tab over the DevTools Protocol (Chrome shows a debugging banner while recording). Interact with the page, then call browser_profiling_stop
+
+        :param categories: Trace categories (default: those the DevTools Performance panel records)
+        :param coverage: Coverage to record (default: both; [] for none)
+        :param trace: Record a performance trace (default: true)
+        """
+        return self.call_tool("browser_profiling_start", {"categories": categories, "coverage": coverage, "trace": trace})
+
+    def browser_profiling_stop(self) -> ToolResult:
+        """Stop recording started by browser_profiling_start, save the trace (opens in DevTools or Perfetto) and coverage files to this session's artifact directory, and summarize unused bytes per script/stylesheet and long tasks"""
+        return self.call_tool("browser_profiling_stop", {})
+
+    def browser_recording_start(self, *, format: Optional[str] = None, max_width: Optional[int] = None, quality: Optional[int] = None) -> ToolResult:
+        """Start recording a video of the current tab, e.g. to review later why a multi-step run failed. Chrome only paints visible tabs, so keep the tab in front (Chrome shows a debugging banner while recording). Call browser_recording_stop to save the video
+
+        :param format: Video format; needs ffmpeg, without which a Motion-JPEG AVI is saved instead (default: webm)
+        :param max_width: Scale frames down to fit this many pixels wide and high (default: 1280)
+        :param quality: JPEG quality of the captured frames (default: 70)
+        """
+        return self.call_tool("browser_recording_start", {"format": format, "maxWidth": max_width, "quality": quality})
+
+    def browser_recording_stop(self) -> ToolResult:
+        """Stop the recording started by browser_recording_start and encode it into a video in ~/.agent-browser/recordings. Returns the path, duration and frame count"""
+        return self.call_tool("browser_recording_stop", {})
+
+    def network_list_websockets(self, *, include_messages: Optional[bool] = None, include_web_r_t_c: Optional[bool] = None, limit: Optional[float] = None, url_pattern: Optional[str] = None) -> ToolResult:
+        """List captured WebSocket connections (lifecycle, message counts/bytes, recent message previews) and WebRTC connection events
+
+        :param include_messages: Include recent message previews (default: true)
+        :param include_web_r_t_c: Include WebRTC connection events (default: true)
+        :param limit: Maximum number of connections/events to return (default: 50)
+        :param url_pattern: Only include connections whose URL contains this substring
+        """
+        return self.call_tool("network_list_websockets", {"includeMessages": include_messages, "includeWebRTC": include_web_r_t_c, "limit": limit, "urlPattern": url_pattern})
+
+    def network_capture_configure(self, *, capture_bodies: Optional[bool] = None, max_body_bytes: Optional[float] = None, redact_headers: Optional[List[str]] = None, redact_patterns: Optional[List[str]] = None) -> ToolResult:
+        """Configure network capture: optionally keep request/response bodies up to a size limit, and add header names or regex patterns to redact before storage. Auth headers and common secret patterns are always redacted
+
+        :param capture_bodies: Capture request/response bodies (default: false)
+        :param max_body_bytes: Maximum stored body size in bytes; longer bodies are truncated (default: 65536)
+        :param redact_headers: Additional header names whose values are redacted
+        :param redact_patterns: Additional regex patterns replaced with [REDACTED] in URLs, headers, and bodies
+        """
+        return self.call_tool("network_capture_configure", {"captureBodies": capture_bodies, "maxBodyBytes": max_body_bytes, "redactHeaders": redact_headers, "redactPatterns": redact_patterns})
+
+    def network_list_requests(self, *, all_tabs: Optional[bool] = None, limit: Optional[int] = None, status: Optional[Any] = None, tab_id: Optional[int] = None, type: Optional[str] = None, url_pattern: Optional[str] = None) -> ToolResult:
+        """List recent HTTP requests the page made (method, URL, status, type, start time, duration, size), newest first, to see which API calls actually fired and how they answered. Defaults to this session's tab, or the active tab. Use network_get_request with an id for headers
+
+        :param all_tabs: List requests from every tab (default: false)
+        :param limit: Maximum requests to return (default: 50)
+        :param status: Only requests with this status: a code like 404, a class like "4xx", or "failed" for requests that got no response
+        :param tab_id: List requests from this tab instead
+        :param type: Only requests of this resource type; fetch() and XHR calls are xmlhttprequest
+        :param url_pattern: Only requests whose URL contains this substring, e.g. /api/
+        """
+        return self.call_tool("network_list_requests", {"allTabs": all_tabs, "limit": limit, "status": status, "tabId": tab_id, "type": type, "urlPattern": url_pattern})
+
+    def network_get_request(self, *, id: Optional[str] = None, limit: Optional[float] = None, url_pattern: Optional[str] = None) -> ToolResult:
+        """Get captured HTTP requests (headers, redacted bodies when enabled) by request id or URL substring
+
+        :param id: Captured request id
+        :param limit: Maximum number of entries to return, newest first (default: 10)
+        :param url_pattern: Match requests whose URL contains this substring
+        """
+        return self.call_tool("network_get_request", {"id": id, "limit": limit, "urlPattern": url_pattern})
+
+    def session_artifacts_list(self) -> ToolResult:
+        """List files this session has written to its artifact directory (used for savePath when the client declares no roots); idle session directories are cleaned up automatically"""
+        return self.call_tool("session_artifacts_list", {})
+
+    def artifacts_search(self, *, query: str, kinds: Optional[List[str]] = None, limit: Optional[int] = None, session: Optional[str] = None, since: Optional[str] = None, until: Optional[str] = None) -> ToolResult:
+        """Full-text search across everything the server kept, from every session: page text of saved snapshots, session reports and text artifacts, and audit log events. Use it to find where something was seen before (e.g. query "invoice total" with since "7d"). Results are ranked, newest first among equals, with a snippet
+
+        :param query: Words that must all appear; "quoted phrases", prefix* and -excluded words are supported
+        :param kinds: Only search these sources (default: all)
+        :param limit: Maximum results (default 20, max 100)
+        :param session: Only results from this session label (see session_list)
+        :param since: Only results from this time on: RFC 3339, YYYY-MM-DD, or an age such as 24h, 7d or 2w
+        :param until: Only results up to this time, in the same formats as since
+        """
+        return self.call_tool("artifacts_search", {"query": query, "kinds": kinds, "limit": limit, "session": session, "since": since, "until": until})
+
+    def session_report(self, *, include_screenshots: Optional[bool] = None, title: Optional[str] = None) -> ToolResult:
+        """Write a self-contained HTML report of what this session did: every tool call with its timing and outcome, the session's screenshots, a network summary and credential/authorization audit events. Typed values and secrets are left out; returns the file path
+
+        :param include_screenshots: Embed the session's PNG/JPEG artifacts (default: true)
+        :param title: Report heading (default: Agent session report)
+        """
+        return self.call_tool("session_report", {"includeScreenshots": include_screenshots, "title": title})
+
+    def history_retry(self, *, arguments: Optional[Dict[str, Any]] = None, call_id: Optional[str] = None, session: Optional[str] = None) -> ToolResult:
+        """Re-run a failed tool call from a session's timeline, optionally with edited arguments, to nudge a stuck session forward. The retry is recorded with the id of the call it re-ran and the first call of the chain, on the timeline and in the audit log. Call ids (c1, c2, ...) appear in session_report
+
+        :param arguments: Arguments to change before re-running; each key replaces the original value, null removes it
+        :param call_id: Failed call to re-run, e.g. c12 (default: the session's most recent failure)
+        :param session: Session whose call to re-run, as listed by session_list (default: this session); the call runs in that session
+        """
+        return self.call_tool("history_retry", {"arguments": arguments, "callId": call_id, "session": session})
+
+    def session_list(self) -> ToolResult:
+        """List the MCP sessions connected on any transport (stdio agent, TCP, WebSocket, HTTP, SSE) with their client, connection time and observers, plus this session's own id"""
+        return self.call_tool("session_list", {})
+
+    def session_observe(self, *, session: str, stop: Optional[bool] = None) -> ToolResult:
+        """Receive another session's tool calls as notifications/session_activity (call id, tool, redacted arguments, duration, error) so a supervisor can follow an agent; stop with stop: true
+
+        :param session: Session to observe, from session_list
+        :param stop: Stop observing instead (default: false)
+        """
+        return self.call_tool("session_observe", {"session": session, "stop": stop})
+
+    def session_inject(self, *, session: str, tool: str, arguments: Optional[Dict[str, Any]] = None) -> ToolResult:
+        """Run a tool call as another session (e.g. the stdio agent), for a supervisor taking a step on its behalf. The call is recorded on that session's timeline and in the audit log as injected by this session, and that session receives notifications/session_injected. Session, passkey and recovery tools and playwright_evaluate can't be injected
+
+        :param session: Session to run the call as, from session_list
+        :param tool: Tool to call
+        :param arguments: The tool's arguments
+        """
+        return self.call_tool("session_inject", {"session": session, "tool": tool, "arguments": arguments})
+
+    def history_stats(self, *, compact: Optional[bool] = None, days: Optional[float] = None) -> ToolResult:
+        """Summarize authorization and credential activity per day from the audit log: event counts by kind and relying party and distinct sessions. Events older than the retention window are kept as daily summaries; optionally compact now
+
+        :param compact: Fold events older than the retention window into daily summaries before reporting (default: false)
+        :param days: Most recent days with activity to include (default: 30, max: 366)
+        """
+        return self.call_tool("history_stats", {"compact": compact, "days": days})
+
+    def alerts_test(self, *, class_: Optional[str] = None) -> ToolResult:
+        """Send a test alert through the channels configured in ~/.agent-browser/alerts.json and report each channel's delivery result, along with the configured channels and per-class routes
+
+        :param class_: Event class whose route to test (default: every class)
+        """
+        return self.call_tool("alerts_test", {"class": class_})
+
+    def storage_status(self, *, cleanup: Optional[bool] = None) -> ToolResult:
+        """Report disk usage of the ~/.agent-browser data directory against the quotas for artifacts, recordings and audit log segments; optionally run the least-recently-used cleanup now
+
+        :param cleanup: Delete least recently used files in categories over quota before reporting (default: false)
+        """
+        return self.call_tool("storage_status", {"cleanup": cleanup})
+
+    def server_metrics(self) -> ToolResult:
+        """Report the extension command queue (in-flight depth, limit, high-water mark, admitted/rejected/shed counts), pending requests, connected MCP sessions and the companion browser pool"""
+        return self.call_tool("server_metrics", {})
+
+    def telemetry_status(self, *, epsilon: Optional[float] = None, reset: Optional[bool] = None) -> ToolResult:
+        """Show the opt-in local usage telemetry (tool call and failure counts only, never URLs or content; enabled with --telemetry) and a differentially private export of it for sharing with maintainers
+
+        :param epsilon: Privacy budget for the export; smaller adds more noise (default: 1)
+        :param reset: Delete the collected counts first (default: false)
+        """
+        return self.call_tool("telemetry_status", {"epsilon": epsilon, "reset": reset})
+
+    def capabilities_diff(self, *, manifest: Optional[Dict[str, Any]] = None, path: Optional[str] = None) -> ToolResult:
+        """Developer tool: diff the server's tool registry against a saved manifest (a tools/list result) and report added, removed and changed tool schemas, flagging breaking changes
+
+        :param manifest: Saved tools/list result ({"tools": [...]})
+        :param path: Path to a saved manifest JSON file on the server host (used when manifest is omitted)
+        """
+        return self.call_tool("capabilities_diff", {"manifest": manifest, "path": path})
+
+    def preflight(self, *, url: str, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Check a URL's reachability from the server (DNS, TCP connect, TLS handshake, HTTP status) without navigating the browser; each stage is timed and the verdict says where it failed
+
+        :param url: http:// or https:// URL to check
+        :param timeout_ms: Timeout per stage in milliseconds (default: 5000, max: 30000)
+        """
+        return self.call_tool("preflight", {"url": url, "timeoutMs": timeout_ms})
+
+    def passkey_enable(self, *, enabled: bool) -> ToolResult:
+        """Enable or disable passkey automation for WebAuthn flows
+
+        :param enabled: Whether to enable passkey automation
+        """
+        return self.call_tool("passkey_enable", {"enabled": enabled})
+
+    def passkey_status(self) -> ToolResult:
+        """Get the current status of passkey automation"""
+        return self.call_tool("passkey_status", {})
+
+    def passkey_list(self) -> ToolResult:
+        """List all stored passkey credentials"""
+        return self.call_tool("passkey_list", {})
+
+    def passkey_clear(self) -> ToolResult:
+        """Clear all stored passkey credentials"""
+        return self.call_tool("passkey_clear", {})
+
+    def passkey_authorize(self, *, duration_hours: Optional[float] = None) -> ToolResult:
+        """Authorize AI agent to use passkeys for a limited time (requires Touch ID on macOS)
+
+        :param duration_hours: Number of hours to authorize access (default: 8)
+        """
+        return self.call_tool("passkey_authorize", {"duration_hours": duration_hours})
+
+    def passkey_authorization_status(self) -> ToolResult:
+        """Check if AI agent is currently authorized to use passkeys"""
+        return self.call_tool("passkey_authorization_status", {})
+
+    def passkey_grant_create(self, *, rp_id: str, duration_minutes: Optional[float] = None, operation: Optional[str] = None) -> ToolResult:
+        """Mint a signed token that lets another MCP client (e.g. a sub-agent) use one site's passkey exactly once, without authorizing that client generally. Requires an active passkey authorization; the token expires within 15 minutes and when this session's authorization ends
+
+        :param rp_id: Relying party whose credential the grant covers, e.g. github.com
+        :param duration_minutes: Minutes the grant stays valid (default: 5, max: 15)
+        :param operation: Limit the grant to signing in (get) or registering (create) (default: either)
+        """
+        return self.call_tool("passkey_grant_create", {"rp_id": rp_id, "duration_minutes": duration_minutes, "operation": operation})
+
+    def passkey_grant_redeem(self, *, token: str) -> ToolResult:
+        """Redeem a token from passkey_grant_create for this session. The session may then use that site's passkey once; the token cannot be redeemed again
+
+        :param token: Grant token (abgrant1....)
+        """
+        return self.call_tool("passkey_grant_redeem", {"token": token})
+
+    def recovery_secret_store(self, *, kind: str, rp_id: str, values: List[str], label: Optional[str] = None) -> ToolResult:
+        """Store account recovery secrets (backup codes or security question answers) encrypted at rest. Requires elevated authorization (recovery_authorize)
+
+        :param kind: Type of recovery secret
+        :param rp_id: Relying party / site the secrets belong to (e.g. github.com)
+        :param values: Secret values; each backup code is stored and consumed individually
+        :param label: Security question text (required for security_answer)
+        """
+        return self.call_tool("recovery_secret_store", {"kind": kind, "rp_id": rp_id, "values": values, "label": label})
+
+    def recovery_secret_list(self, *, rp_id: Optional[str] = None) -> ToolResult:
+        """List stored recovery secrets with values redacted. Requires an active passkey authorization
+
+        :param rp_id: Only list secrets for this relying party
+        """
+        return self.call_tool("recovery_secret_list", {"rp_id": rp_id})
+
+    def recovery_authorize(self, *, duration_minutes: Optional[float] = None) -> ToolResult:
+        """Grant short-lived elevated authorization to store, retrieve or delete recovery secrets (requires an active passkey authorization, max 15 minutes)
+
+        :param duration_minutes: Number of minutes to allow retrieval (default: 5, max: 15)
+        """
+        return self.call_tool("recovery_authorize", {"duration_minutes": duration_minutes})
+
+    def recovery_secret_get(self, *, kind: str, rp_id: str, label: Optional[str] = None) -> ToolResult:
+        """Retrieve a recovery secret under elevated authorization. Backup codes are consumed and never returned again
+
+        :param kind: Type of recovery secret
+        :param rp_id: Relying party to retrieve a secret for
+        :param label: Security question text to match (security_answer only)
+        """
+        return self.call_tool("recovery_secret_get", {"kind": kind, "rp_id": rp_id, "label": label})
+
+    def recovery_secret_delete(self, *, rp_id: str) -> ToolResult:
+        """Delete all recovery secrets stored for a relying party. Requires elevated authorization (recovery_authorize)
+
+        :param rp_id: Relying party whose secrets should be removed
+        """
+        return self.call_tool("recovery_secret_delete", {"rp_id": rp_id})
+
+    def state_set(self, *, key: str, value: Any, workspace: Optional[str] = None) -> ToolResult:
+        """Persist a small JSON value under a key so later sessions can read it back
+
+        :param key: Key to store the value under
+        :param value: Any JSON value (max 64KB)
+        :param workspace: Workspace namespace (default: "default")
+        """
+        return self.call_tool("state_set", {"key": key, "value": value, "workspace": workspace})
+
+    def state_get(self, *, key: Optional[str] = None, prefix: Optional[str] = None, workspace: Optional[str] = None) -> ToolResult:
+        """Read a stored value by key, or list all keys in a workspace when key is omitted
+
+        :param key: Key to read (omit to list the workspace)
+        :param prefix: When listing, only include keys starting with this prefix
+        :param workspace: Workspace namespace (default: "default")
+        """
+        return self.call_tool("state_get", {"key": key, "prefix": prefix, "workspace": workspace})
+
+    def state_delete(self, *, key: str, workspace: Optional[str] = None) -> ToolResult:
+        """Delete a stored key
+
+        :param key: Key to delete
+        :param workspace: Workspace namespace (default: "default")
+        """
+        return self.call_tool("state_delete", {"key": key, "workspace": workspace})
+
+    def data_transform(self, *, expression: str, data: Optional[Any] = None, key: Optional[str] = None, save_as: Optional[str] = None, workspace: Optional[str] = None) -> ToolResult:
+        """Reshape JSON with a JavaScript expression evaluated in the server (e.g. filter, map or rename fields of extracted rows) without another round trip or page evaluate. The input is bound to `data`. Supports literals, template strings, regex literals, spread, optional chaining, arrow functions and common Array/String/Number/Object/Math/JSON methods; no statements, assignments or host access, and evaluation is step-limited
+
+        :param expression: Expression over `data`, e.g. "data.filter(r => r.price > 10).map(r => ({ name: r.name.trim(), price: r.price }))"
+        :param data: Input value (omit to read it from the state store with key)
+        :param key: State store key to read the input from instead of data
+        :param save_as: Also store the result under this state store key
+        :param workspace: State store workspace for key and saveAs (default: "default")
+        """
+        return self.call_tool("data_transform", {"expression": expression, "data": data, "key": key, "saveAs": save_as, "workspace": workspace})
+
+    def snapshot_save(self, *, name: str, include: Optional[List[str]] = None, metadata: Optional[Dict[str, Any]] = None, overwrite: Optional[bool] = None) -> ToolResult:
+        """Capture the current page (DOM, accessibility tree, screenshot) and persist it under a name for later sessions to reference
+
+        :param name: Snapshot name (letters, digits, '-', '_', '.')
+        :param include: Parts to capture (default: all)
+        :param metadata: Free-form metadata stored with the snapshot
+        :param overwrite: Replace an existing snapshot with the same name (default: false)
+        """
+        return self.call_tool("snapshot_save", {"name": name, "include": include, "metadata": metadata, "overwrite": overwrite})
+
+    def snapshot_get(self, *, include: Optional[List[str]] = None, name: Optional[str] = None) -> ToolResult:
+        """Load a saved snapshot by name, or list saved snapshots when name is omitted
+
+        :param include: Parts to return (default: all stored parts)
+        :param name: Snapshot name (omit to list all snapshots)
+        """
+        return self.call_tool("snapshot_get", {"include": include, "name": name})
+
+    def baseline_set(self, *, name: str, overwrite: Optional[bool] = None) -> ToolResult:
+        """Screenshot the current page and store it as a named visual regression baseline for the current viewport size (one image per viewport under each name)
+
+        :param name: Baseline name (letters, digits, '-', '_', '.')
+        :param overwrite: Replace an existing image for this name and viewport (default: false)
+        """
+        return self.call_tool("baseline_set", {"name": name, "overwrite": overwrite})
+
+    def baseline_list(self, *, name: Optional[str] = None, url: Optional[str] = None, viewport: Optional[str] = None) -> ToolResult:
+        """List stored visual regression baselines with their URL, viewport and capture time
+
+        :param name: Only this baseline
+        :param url: Only baselines captured on URLs containing this text
+        :param viewport: Only this viewport size, e.g. 1280x720
+        """
+        return self.call_tool("baseline_list", {"name": name, "url": url, "viewport": viewport})
+
+    def baseline_delete(self, *, name: str, viewport: Optional[str] = None) -> ToolResult:
+        """Delete a visual regression baseline, or only its image for one viewport
+
+        :param name: Baseline name
+        :param viewport: Delete only this viewport's image, e.g. 390x844 (default: all viewports)
+        """
+        return self.call_tool("baseline_delete", {"name": name, "viewport": viewport})
+
+    def screenshot_diff(self, *, baseline: str, max_diff_ratio: Optional[float] = None, tolerance: Optional[int] = None) -> ToolResult:
+        """Screenshot the current page and compare it pixel by pixel with a stored baseline at the same viewport size. Reports the share of differing pixels; on failure the capture and a diff image are saved to the session's artifacts
+
+        :param baseline: Baseline name (see baseline_set)
+        :param max_diff_ratio: Largest share of differing pixels that still passes (default: 0.001)
+        :param tolerance: Per-channel difference ignored as noise (default: 16)
+        """
+        return self.call_tool("screenshot_diff", {"baseline": baseline, "maxDiffRatio": max_diff_ratio, "tolerance": tolerance})
+
+    def watch_add(self, *, url: str, attribute: Optional[str] = None, interval_seconds: Optional[float] = None, pattern: Optional[str] = None, selector: Optional[str] = None, webhook: Optional[str] = None) -> ToolResult:
+        """Watch a page for changes: re-visit the URL on an interval in a background tab, extract a value, and send notifications/watch_changed (and POST to a webhook) when it differs from the previous run
+
+        :param url: Page to watch (http or https)
+        :param attribute: Read this attribute instead of the element's text
+        :param interval_seconds: Seconds between checks (default: 3600, minimum: 60)
+        :param pattern: Regex applied to the extracted text; the first capture group (or the whole match) is compared
+        :param selector: CSS selector of the element to read (default: the page body)
+        :param webhook: URL that receives a JSON POST on every change
+        """
+        return self.call_tool("watch_add", {"url": url, "attribute": attribute, "interval_seconds": interval_seconds, "pattern": pattern, "selector": selector, "webhook": webhook})
+
+    def watch_list(self) -> ToolResult:
+        """List page watches with their last value, last check, and change count"""
+        return self.call_tool("watch_list", {})
+
+    def watch_remove(self, *, id: str) -> ToolResult:
+        """Stop watching a page
+
+        :param id: Watch ID returned by watch_add
+        """
+        return self.call_tool("watch_remove", {"id": id})
+
+    def playwright_detect_modal(self, *, include_hidden: Optional[bool] = None, max_results: Optional[float] = None, min_z_index: Optional[float] = None) -> ToolResult:
+        """Detect if a modal, popup, or overlay is present on the page
+
+        :param include_hidden: Include hidden modals (default: false)
+        :param max_results: Maximum number of modals to detect (default: 1)
+        :param min_z_index: Minimum z-index to consider (default: 100)
+        """
+        return self.call_tool("playwright_detect_modal", {"includeHidden": include_hidden, "maxResults": max_results, "minZIndex": min_z_index})
+
+    def playwright_dismiss_modal(self, *, strategy: Optional[str] = None, timeout: Optional[float] = None, wait_after: Optional[float] = None) -> ToolResult:
+        """Attempt to dismiss any detected modals on the page
+
+        :param strategy: Dismissal strategy: auto tries all methods, button clicks dismiss button, escape presses ESC, backdrop clicks overlay, remove forcibly removes from DOM (default: auto)
+        :param timeout: Timeout in milliseconds (default: 5000)
+        :param wait_after: Wait time after dismissal to verify (default: 500)
+        """
+        return self.call_tool("playwright_dismiss_modal", {"strategy": strategy, "timeout": timeout, "waitAfter": wait_after})
+
+    def browser_handle_dialogs(self, *, mode: str, prompt_text: Optional[str] = None) -> ToolResult:
+        """Choose how this session's tab answers JavaScript dialogs (alert, confirm, prompt, beforeunload). accept and dismiss answer them automatically; manual leaves them open and fails other commands on the tab with a 'JavaScript dialog open' error until browser_respond_dialog answers. Without a policy an unexpected confirm() blocks the page until the command times out
+
+        :param mode: accept clicks OK (and leaves the page on beforeunload), dismiss clicks Cancel, manual waits for browser_respond_dialog, off stops watching
+        :param prompt_text: Text accept enters into prompt() dialogs (default: the prompt's default value)
+        """
+        return self.call_tool("browser_handle_dialogs", {"mode": mode, "promptText": prompt_text})
+
+    def browser_respond_dialog(self, *, accept: Optional[bool] = None, prompt_text: Optional[str] = None) -> ToolResult:
+        """Answer the JavaScript dialog open on this session's tab, e.g. after a 'JavaScript dialog open' error under the manual policy of browser_handle_dialogs
+
+        :param accept: Click OK rather than Cancel (default: true)
+        :param prompt_text: Text to enter into a prompt() dialog before accepting
+        """
+        return self.call_tool("browser_respond_dialog", {"accept": accept, "promptText": prompt_text})
+
+    def browser_list_dialogs(self, *, limit: Optional[float] = None, open_only: Optional[bool] = None, tab_id: Optional[float] = None) -> ToolResult:
+        """List recent JavaScript dialogs reported while browser_handle_dialogs was on, newest first: type, message, page URL, whether it is still open, and how it was answered
+
+        :param limit: Maximum number of dialogs to return (default: 20)
+        :param open_only: Only include dialogs still waiting for an answer (default: false)
+        :param tab_id: Only include dialogs from this tab
+        """
+        return self.call_tool("browser_list_dialogs", {"limit": limit, "openOnly": open_only, "tabId": tab_id})
+
+    def audit_accessibility(self, *, max_nodes: Optional[float] = None, rules: Optional[List[str]] = None, selector: Optional[str] = None) -> ToolResult:
+        """Check the current page for accessibility problems (missing alt text and labels, unnamed buttons and links, low color contrast, invalid ARIA, heading order, and more) and return violations grouped by rule with impact, WCAG reference and example elements, plus a 0-100 score over the rules that applied
+
+        :param max_nodes: Example elements returned per violated rule (default: 10, max: 100)
+        :param rules: Rules to run (default: all)
+        :param selector: Only audit elements inside this CSS selector (default: whole document)
+        """
+        return self.call_tool("audit_accessibility", {"maxNodes": max_nodes, "rules": rules, "selector": selector})
+
+    def check_links(self, *, allow_private: Optional[bool] = None, concurrency: Optional[float] = None, include_resources: Optional[bool] = None, max_links: Optional[float] = None, same_origin_only: Optional[bool] = None, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Find broken links on the current page: extracts its links and checks each from the server (HEAD, falling back to GET) with bounded concurrency, following redirects. Returns each link's final status, redirect chain and link text, with a summary of ok, broken (4xx/5xx) and unreachable links. Hosts on private or loopback addresses are skipped unless the page is on one or allowPrivate is set
+
+        :param allow_private: Check links to loopback, private and link-local addresses (default: false)
+        :param concurrency: Requests in flight at once (default: 8, max: 32)
+        :param include_resources: Also check image, script, stylesheet and media URLs (default: false, anchors only)
+        :param max_links: Most distinct links to check (default: 100, max: 500)
+        :param same_origin_only: Only check links on the page's own origin (default: false)
+        :param timeout_ms: Per-link timeout including redirects (default: 8000, max: 30000)
+        """
+        return self.call_tool("check_links", {"allowPrivate": allow_private, "concurrency": concurrency, "includeResources": include_resources, "maxLinks": max_links, "sameOriginOnly": same_origin_only, "timeoutMs": timeout_ms})
+
+    def audit_seo(self, *, link_checks: Optional[float] = None, site_checks: Optional[bool] = None, timeout_ms: Optional[float] = None) -> ToolResult:
+        """Audit the current page for search-engine readiness: title, meta description, canonical, robots meta, heading structure, hreflang, Open Graph and structured data from the page, plus server-side checks of robots.txt rules for the page, sitemap availability and the HTTP status of the canonical URL and key links. Returns the collected facts and issues graded error/warning/notice
+
+        :param link_checks: How many key links (canonical, alternates, then page links) to check the status of (default: 10, max: 50)
+        :param site_checks: Fetch robots.txt, sitemaps and key links from the server (default: true)
+        :param timeout_ms: Per-request timeout for site checks (default: 5000, max: 30000)
+        """
+        return self.call_tool("audit_seo", {"linkChecks": link_checks, "siteChecks": site_checks, "timeoutMs": timeout_ms})
+
+    def dismiss_consent(self, *, auto: Optional[bool] = None, fallback_to_modal: Optional[bool] = None, mode: Optional[str] = None) -> ToolResult:
+        """Accept or reject a cookie-consent banner using rules for common consent platforms (OneTrust, Cookiebot, Didomi, Quantcast, Usercentrics, ...), or turn automatic handling after every navigation on or off
+
+        :param auto: Enable or disable running dismiss_consent automatically after each navigation (with the given mode) instead of running it now
+        :param fallback_to_modal: Fall back to generic modal dismissal when no known platform matches (default: true)
+        :param mode: Accept all or reject all non-essential cookies (default: reject)
+        """
+        return self.call_tool("dismiss_consent", {"auto": auto, "fallbackToModal": fallback_to_modal, "mode": mode})
@@ -1,15 +1,16 @@
 /**
  * Background script - WebSocket client and message routing
  *
- * - Connect WebSocket to localhost:8085
+ * - Connect WebSocket to localhost:8085 (or the port the native host reports)
  * - Route messages to correct tab
  * - Auto-inject content script if dead
  * - Reconnect if disconnected
  * - Handle WebAuthn passkey automation
  */
 
-import { WebAuthnProxy } from '../lib/webauthn/proxy';
+import { WebAuthnProxy, type CredentialUseRequest } from '../lib/webauthn/proxy';
 import { getEmailInboxAutomation } from '../lib/automation/email-inbox';
+import { COMMAND_VERSIONS, registeredTools } from '../lib/capabilities';
 
 // ============================================================================
 // Types
@@ -43,9 +44,26 @@ let reconnectTimeout: ReturnType<typeof setTimeout> | null = null;
 let reconnectAttempts = 0;
 const MAX_RECONNECT_ATTEMPTS = 10;
 const RECONNECT_BASE_DELAY = 1000;
+// Replaced by the port the native host reports when the server picked its own
+let serverUrl = 'ws://localhost:8085';
 
 const pending = new Map<string, PendingRequest>();
 
+// WebAuthn requests waiting for the server's passkey_decision, by request id
+const passkeyDecisions = new Map<number, (error: string | null) => void>();
+const PASSKEY_DECISION_TIMEOUT_MS = 10000;
+
+// llm_query events waiting for the server's llm_query_result, by query id
+const llmQueries = new Map<string, (reply: LlmQueryReply) => void>();
+const LLM_QUERY_TIMEOUT_MS = 30000;
+
+interface LlmQueryReply {
+  success: boolean;
+  text?: string;
+  model?: string;
+  error?: string;
+}
+
 // Badge state management
 type ServerStatus = 'starting' | 'connected' | 'disconnected' | 'reconnecting' | 'error';
 type CommandType = 'navigate' | 'click' | 'type' | 'wait' | 'screenshot' | null;
@@ -461,7 +479,7 @@ function updateBadge() {
         '⋯ SERVER STARTING',
         '',
         'Status: Initializing connection',
-        'Target: ws://localhost:8085',
+        `Target: ${serverUrl}`,
         '',
         'Please wait...'
       ].join('\n');
@@ -548,11 +566,14 @@ function connect() {
   }
 
   console.log('[Background] Connecting to WebSocket server...');
-  ws = new WebSocket('ws://localhost:8085');
+  ws = new WebSocket(serverUrl);
 
   ws.onopen = () => {
     console.log('[Background] WebSocket connected');
     reconnectAttempts = 0;
+    // Tell the server which commands this build handles; it lists them as MCP tools
+    sendEvent('capabilities', { version: chrome.runtime.getManifest().version, commands: COMMAND_VERSIONS });
+    sendEvent('register_tools', { tools: registeredTools() });
     setBadgeState({
       serverStatus: 'connected',
       errorType: null,
@@ -566,6 +587,24 @@ function connect() {
       const message: Message = JSON.parse(event.data);
       console.log('[Background] Received message:', message);
 
+      // Dashboard events are fire-and-forget; the server expects no response
+      if (message.method === 'dashboard_event') {
+        handleDashboardEvent(message);
+        return;
+      }
+      if (message.method === 'passkey_decision') {
+        const decide = passkeyDecisions.get(message.params?.request_id);
+        decide?.(message.params?.allowed === true ? null : message.params?.error ?? 'Passkey use refused');
+        return;
+      }
+      if (message.method === 'llm_query_result') {
+        llmQueries.get(message.params?.id)?.(message.params as LlmQueryReply);
+        return;
+      }
+
+      previousCommandAt = currentCommandAt;
+      currentCommandAt = Date.now();
+
       // Set active command badge
       if (message.method === 'navigate' || message.method === 'click' ||
           message.method === 'type' || message.method === 'wait' ||
@@ -585,6 +624,68 @@ function connect() {
         response = await handlePasskeyList(message);
       } else if (message.method === 'passkey_clear') {
         response = await handlePasskeyClear(message);
+      } else if (message.method === 'watch_extract') {
+        response = await handleWatchExtract(message);
+      } else if (message.method === 'cookies_get' || message.method === 'cookies_set' || message.method === 'cookies_clear') {
+        response = await handleCookies(message);
+      } else if (message.method === 'assert_page') {
+        response = await handleAssertPage(message);
+      } else if (message.method === 'active_tab') {
+        response = await handleActiveTab(message);
+      } else if (message.method === 'downloads_list') {
+        response = await handleDownloadsList(message);
+      } else if (message.method === 'profiling_start') {
+        response = await handleProfilingStart(message);
+      } else if (message.method === 'profiling_stop') {
+        response = await handleProfilingStop(message);
+      } else if (message.method === 'print_to_pdf') {
+        response = await handlePrintToPdf(message);
+      } else if (message.method === 'screenshot_elements') {
+        response = await handleScreenshotElements(message);
+      } else if (message.method === 'image_diff') {
+        response = await handleImageDiff(message);
+      } else if (message.method === 'evaluate') {
+        response = await handleEvaluate(message);
+      } else if (message.method === 'go_back' || message.method === 'go_forward' || message.method === 'reload') {
+        response = await handleHistoryNavigation(message);
+      } else if (message.method === 'set_geolocation') {
+        response = await handleSetGeolocation(message);
+      } else if (message.method === 'emulate_locale') {
+        response = await handleEmulateLocale(message);
+      } else if (message.method === 'throttle') {
+        response = await handleThrottle(message);
+      } else if (message.method === 'dialog_policy') {
+        response = await handleDialogPolicy(message);
+      } else if (message.method === 'dialog_respond') {
+        response = await handleDialogRespond(message);
+      } else if (message.method === 'screencast_start') {
+        response = await handleScreencastStart(message);
+      } else if (message.method === 'screencast_stop') {
+        response = await handleScreencastStop(message);
+      } else if (message.method === 'page_info') {
+        response = await handlePageInfo(message);
+      } else if (message.method === 'bring_to_front') {
+        response = await handleBringToFront(message);
+      } else if (message.method === 'wait_for_load') {
+        response = await handleWaitForLoad(message);
+      } else if (message.method === 'network_capture_config') {
+        response = await handleNetworkCaptureConfig(message);
+      } else if (message.method === 'list_tabs') {
+        response = await handleListTabs(message);
+      } else if (message.method === 'list_frames') {
+        response = await handleListFrames(message);
+      } else if (message.method === 'list_service_workers') {
+        response = await handleListServiceWorkers(message);
+      } else if (message.method === 'unregister_service_workers') {
+        response = await handleUnregisterServiceWorkers(message);
+      } else if (message.method === 'clear_site_data') {
+        response = await handleClearSiteData(message);
+      } else if (message.method === 'dismiss_consent') {
+        response = await handleDismissConsent(message);
+      } else if (message.method === 'navigate' && message.params?.bypassServiceWorker) {
+        response = await handleNavigateBypassingServiceWorker(message);
+      } else if (message.method === 'navigate' && message.params?.waitUntil) {
+        response = await handleNavigateAndWait(message);
       } else {
         // Route to content script for other commands
         response = await routeToTab(message);
@@ -639,6 +740,15 @@ function connect() {
   };
 }
 
+/**
+ * Push an unsolicited event to the server (not tied to a request id)
+ */
+function sendEvent(event: string, data: Record<string, any>) {
+  if (ws && ws.readyState === WebSocket.OPEN) {
+    ws.send(JSON.stringify({ event, data }));
+  }
+}
+
 function scheduleReconnect() {
   if (reconnectTimeout) {
     clearTimeout(reconnectTimeout);
@@ -677,8 +787,10 @@ function scheduleReconnect() {
 
 async function routeToTab(message: Message): Promise<Response> {
   try {
-    // Get active tab - try current window first, then any window
-    let tabs = await chrome.tabs.query({ active: true, currentWindow: true });
+    // The tab the server named, else the active tab - current window first, then any window
+    let tabs = typeof message.params?.tabId === 'number'
+      ? [await chrome.tabs.get(message.params.tabId)]
+      : await chrome.tabs.query({ active: true, currentWindow: true });
     if (tabs.length === 0) {
       // No active tab in current window, try any active tab
       tabs = await chrome.tabs.query({ active: true });
@@ -724,18 +836,22 @@ async function routeToTab(message: Message): Promise<Response> {
       await waitForTabReady(tab.id!);
     }
 
+    // Selector tools may target an iframe; the top frame is frameId 0
+    const frame = message.params?.frame ? await resolveFrame(tab.id!, message.params.frame) : null;
+    const frameId = frame?.frameId ?? 0;
+
     // Check if content script is loaded
-    let isLoaded = await checkContentScript(tab.id);
+    let isLoaded = await checkContentScript(tab.id, frameId);
 
     if (!isLoaded) {
       console.log('[Background] Content script not loaded, injecting...');
       try {
-        await injectContentScript(tab.id);
+        await injectContentScript(tab.id, frameId);
         // Wait a bit longer for script to initialize
         await new Promise(resolve => setTimeout(resolve, 200));
 
         // Verify it loaded
-        isLoaded = await checkContentScript(tab.id);
+        isLoaded = await checkContentScript(tab.id, frameId);
         if (!isLoaded) {
           throw new Error('Content script injected but not responding');
         }
@@ -759,15 +875,20 @@ async function routeToTab(message: Message): Promise<Response> {
       setTimeout(() => reject(new Error('Request timeout')), 30000);
     });
 
+    // Addressed to one frame, since frames used before also run the content script
     const response = await Promise.race([
-      chrome.tabs.sendMessage(tab.id, message),
+      chrome.tabs.sendMessage(tab.id, message, { frameId }),
       timeoutPromise,
     ]);
 
+    // The content script answers with its own { success, result | error } envelope
+    if (!response?.success) {
+      return { id: message.id, success: false, error: response?.error || 'Content script returned no response' };
+    }
     return {
       id: message.id,
       success: true,
-      result: response,
+      result: frame ? { ...response.result, frame: describeFrame(frame) } : response.result,
     };
   } catch (error: any) {
     console.error('[Background] Error routing message:', error);
@@ -826,19 +947,19 @@ async function waitForTabReady(tabId: number): Promise<void> {
   });
 }
 
-async function checkContentScript(tabId: number): Promise<boolean> {
+async function checkContentScript(tabId: number, frameId = 0): Promise<boolean> {
   try {
-    const response = await chrome.tabs.sendMessage(tabId, { type: 'ping' });
+    const response = await chrome.tabs.sendMessage(tabId, { type: 'ping' }, { frameId });
     return response?.pong === true;
   } catch {
     return false;
   }
 }
 
-async function injectContentScript(tabId: number): Promise<void> {
+async function injectContentScript(tabId: number, frameId = 0): Promise<void> {
   try {
     await chrome.scripting.executeScript({
-      target: { tabId },
+      target: { tabId, frameIds: [frameId] },
       files: ['content.js'],
     });
 
@@ -850,6 +971,238 @@ async function injectContentScript(tabId: number): Promise<void> {
   }
 }
 
+// ============================================================================
+// Frame Targeting
+// ============================================================================
+
+interface FrameInfo {
+  frameId: number;
+  url: string;
+  name: string;
+  // Index of each frame among its parent's frames, from the top frame down
+  path: number[];
+}
+
+/** Every frame of the tab in document order, with its window name and position */
+async function listTabFrames(tabId: number): Promise<FrameInfo[]> {
+  const injections = await chrome.scripting.executeScript({
+    target: { tabId, allFrames: true },
+    func: () => {
+      // parent, length and indexed frames are readable across origins
+      const path: number[] = [];
+      let current: Window = window;
+      while (current !== current.parent) {
+        const parent = current.parent;
+        let index = -1;
+        for (let i = 0; i < parent.length; i++) {
+          if (parent[i] === current) {
+            index = i;
+            break;
+          }
+        }
+        path.unshift(index);
+        current = parent;
+      }
+      return { url: location.href, name: window.name, path };
+    },
+  });
+  const frames = injections
+    .filter((injection) => injection.result)
+    .map((injection) => ({ frameId: injection.frameId, ...(injection.result as Omit<FrameInfo, 'frameId'>) }));
+  const comparePaths = (a: number[], b: number[]) => {
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+      if (a[i] !== b[i]) {
+        return a[i] - b[i];
+      }
+    }
+    return a.length - b.length;
+  };
+  return frames.sort((a, b) => comparePaths(a.path, b.path));
+}
+
+function describeFrame(frame: FrameInfo) {
+  return { frameId: frame.frameId, url: frame.url, name: frame.name || null };
+}
+
+/**
+ * The tab's frame tree from webNavigation, which also sees frames scripts
+ * can't run in, with window names and document order from listTabFrames
+ * where the frame could be scripted
+ */
+async function handleListFrames(message: Message): Promise<Response> {
+  try {
+    const tabId = await commandTabId(message.params);
+    const includeAboutBlank = message.params?.includeAboutBlank === true;
+    const navigationFrames = (await chrome.webNavigation.getAllFrames({ tabId })) ?? [];
+    const scripted = await listTabFrames(tabId).catch(() => [] as FrameInfo[]);
+    const order = new Map(scripted.map((frame, index) => [frame.frameId, index]));
+    const byId = new Map(scripted.map((frame) => [frame.frameId, frame]));
+
+    const depth = (frameId: number): number => {
+      let levels = 0;
+      let current = navigationFrames.find((frame) => frame.frameId === frameId);
+      while (current && current.parentFrameId !== -1) {
+        levels++;
+        const parentId = current.parentFrameId;
+        current = navigationFrames.find((frame) => frame.frameId === parentId);
+      }
+      return levels;
+    };
+
+    const frames = navigationFrames
+      .filter((frame) => includeAboutBlank || frame.frameId === 0 || !/^about:(blank|srcdoc)/.test(frame.url))
+      .sort((a, b) => (order.get(a.frameId) ?? Number.MAX_SAFE_INTEGER) - (order.get(b.frameId) ?? Number.MAX_SAFE_INTEGER)
+        || a.frameId - b.frameId)
+      .map((frame) => ({
+        frameId: frame.frameId,
+        parentFrameId: frame.parentFrameId === -1 ? null : frame.parentFrameId,
+        url: frame.url,
+        name: byId.get(frame.frameId)?.name || null,
+        // Index among the top page's iframes, which `frame: <number>` selects
+        index: byId.get(frame.frameId)?.path.length === 1 ? byId.get(frame.frameId)!.path[0] : null,
+        depth: depth(frame.frameId),
+        scriptable: byId.has(frame.frameId),
+        errorOccurred: frame.errorOccurred,
+      }));
+
+    return { id: message.id, success: true, result: { tabId, count: frames.length, frames } };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to list frames' };
+  }
+}
+
+/**
+ * Find the frame a command's `frame` parameter (normalized by the server)
+ * names: { index } among the top page's iframes, { id }, { name }, { url }
+ * substring, or { match }, a name or else a URL substring
+ */
+async function resolveFrame(
+  tabId: number,
+  spec: { index?: number; id?: number; name?: string; url?: string; match?: string }
+): Promise<FrameInfo> {
+  const frames = (await listTabFrames(tabId)).filter((frame) => frame.frameId !== 0);
+  let matches: FrameInfo[];
+  if (spec.id !== undefined) {
+    matches = frames.filter((frame) => frame.frameId === spec.id);
+  } else if (spec.index !== undefined) {
+    matches = frames.filter((frame) => frame.path.length === 1 && frame.path[0] === spec.index);
+  } else if (spec.name !== undefined) {
+    matches = frames.filter((frame) => frame.name === spec.name);
+  } else if (spec.url !== undefined) {
+    matches = frames.filter((frame) => frame.url.includes(spec.url!));
+  } else {
+    const named = frames.filter((frame) => frame.name === spec.match);
+    matches = named.length > 0 ? named : frames.filter((frame) => frame.url.includes(spec.match ?? ''));
+  }
+  if (matches.length === 0) {
+    const available = frames.map((frame) => `${frame.frameId}: ${frame.name ? `${frame.name} ` : ''}${frame.url}`);
+    throw new Error(`No frame matches ${JSON.stringify(spec)}. Frames: ${available.length ? available.join(', ') : 'none'}`);
+  }
+  return matches[0];
+}
+
+/**
+ * The frame's content box in the top frame's viewport, found by locating its
+ * iframe element in each ancestor document in turn
+ */
+async function frameViewportRect(tabId: number, frame: FrameInfo): Promise<{ x: number; y: number; width: number; height: number }> {
+  const frames = await listTabFrames(tabId);
+  const rect = { x: 0, y: 0, width: 0, height: 0 };
+  for (let depth = 0; depth < frame.path.length; depth++) {
+    const ancestorPath = frame.path.slice(0, depth);
+    const ancestor = frames.find((f) => f.path.length === ancestorPath.length && f.path.every((index, i) => index === ancestorPath[i]));
+    if (!ancestor) {
+      throw new Error('Frame is inside a frame that cannot be scripted');
+    }
+    const [injection] = await chrome.scripting.executeScript({
+      target: { tabId, frameIds: [ancestor.frameId] },
+      args: [frame.path[depth]],
+      func: (index: number) => {
+        const child = window[index];
+        const element = Array.from(document.querySelectorAll('iframe, frame'))
+          .find((el) => (el as HTMLIFrameElement).contentWindow === child) as HTMLIFrameElement | undefined;
+        if (!element) {
+          return null;
+        }
+        const box = element.getBoundingClientRect();
+        const style = getComputedStyle(element);
+        return {
+          x: box.left + element.clientLeft + parseFloat(style.paddingLeft),
+          y: box.top + element.clientTop + parseFloat(style.paddingTop),
+          width: element.clientWidth - parseFloat(style.paddingLeft) - parseFloat(style.paddingRight),
+          height: element.clientHeight - parseFloat(style.paddingTop) - parseFloat(style.paddingBottom),
+        };
+      },
+    });
+    const box = injection?.result as typeof rect | null;
+    if (!box) {
+      throw new Error('Could not locate the frame element');
+    }
+    rect.x += box.x;
+    rect.y += box.y;
+    rect.width = box.width;
+    rect.height = box.height;
+  }
+  return rect;
+}
+
+/** Crop a PNG to a rectangle given in CSS pixels of a viewport `viewportWidth` wide */
+async function cropPng(
+  base64: string,
+  rect: { x: number; y: number; width: number; height: number },
+  viewportWidth: number
+): Promise<{ data: string; width: number; height: number }> {
+  const bitmap = await decodePng(base64);
+  try {
+    const cropped = await cropBitmap(bitmap, rect, viewportWidth);
+    if (!cropped) {
+      throw new Error('Frame is outside the visible viewport; scroll it into view first');
+    }
+    return cropped;
+  } finally {
+    bitmap.close();
+  }
+}
+
+async function decodePng(base64: string): Promise<ImageBitmap> {
+  const blob = await (await fetch(`data:image/png;base64,${base64}`)).blob();
+  return createImageBitmap(blob);
+}
+
+/** Crop part of a decoded capture; null when the rectangle is outside it */
+async function cropBitmap(
+  bitmap: ImageBitmap,
+  rect: { x: number; y: number; width: number; height: number },
+  viewportWidth: number
+): Promise<{ data: string; width: number; height: number } | null> {
+  const scale = bitmap.width / viewportWidth;
+  const x = Math.max(0, Math.round(rect.x * scale));
+  const y = Math.max(0, Math.round(rect.y * scale));
+  const width = Math.min(bitmap.width - x, Math.round((rect.width + Math.min(0, rect.x)) * scale));
+  const height = Math.min(bitmap.height - y, Math.round((rect.height + Math.min(0, rect.y)) * scale));
+  if (width <= 0 || height <= 0) {
+    return null;
+  }
+  const context = new OffscreenCanvas(width, height).getContext('2d')!;
+  context.drawImage(bitmap, x, y, width, height, 0, 0, width, height);
+  return { data: await encodePng(context.getImageData(0, 0, width, height)), width, height };
+}
+
+// captureVisibleTab allows two calls per second per extension
+const CAPTURE_INTERVAL_MS = 510;
+let lastCaptureAt = 0;
+
+/** Capture the window's visible tab as base64 PNG, spacing calls to stay under Chrome's rate limit */
+async function captureTab(windowId: number): Promise<string> {
+  const wait = lastCaptureAt + CAPTURE_INTERVAL_MS - Date.now();
+  if (wait > 0) {
+    await new Promise((resolve) => setTimeout(resolve, wait));
+  }
+  lastCaptureAt = Date.now();
+  const dataUrl = await chrome.tabs.captureVisibleTab(windowId, { format: 'png' });
+  return dataUrl.split(',')[1];
+}
+
 // ============================================================================
 // Screenshot Handler
 // ============================================================================
@@ -873,13 +1226,45 @@ async function handleScreenshot(message: Message): Promise<Response> {
       throw new Error('Cannot screenshot system pages (chrome://, chrome-extension://, edge://, or about: pages)');
     }
 
-    // Capture visible tab as PNG data URL
-    const dataUrl = await chrome.tabs.captureVisibleTab(tab.windowId, {
-      format: 'png',
-    });
+    const frame = message.params?.frame ? await resolveFrame(tab.id, message.params.frame) : null;
+
+    // Outline the elements to verify, in the frame they live in, for this capture only
+    const highlightSelector: string | undefined = message.params?.highlightSelector;
+    let highlight: Record<string, any> | undefined;
+    if (highlightSelector) {
+      highlight = await drawHighlights(tab.id, frame?.frameId ?? 0, highlightSelector, message.params.highlightLabel);
+    }
+
+    // Capture visible tab as base64 PNG
+    let base64Data: string;
+    try {
+      base64Data = await captureTab(tab.windowId);
+    } finally {
+      if (highlightSelector) {
+        await removeHighlights(tab.id, frame?.frameId ?? 0);
+      }
+    }
 
-    // Extract base64 data from data URL (remove "data:image/png;base64," prefix)
-    const base64Data = dataUrl.split(',')[1];
+    // With a frame, crop to the iframe's visible area
+    if (frame) {
+      const rect = await frameViewportRect(tab.id, frame);
+      const cropped = await cropPng(base64Data, rect, tab.width!);
+      return {
+        id: message.id,
+        success: true,
+        result: {
+          success: true,
+          format: 'png',
+          data: cropped.data,
+          encoding: 'base64',
+          url: tab.url,
+          title: tab.title,
+          viewport: { width: Math.round(rect.width), height: Math.round(rect.height) },
+          frame: { ...describeFrame(frame), bounds: rect },
+          highlight,
+        },
+      };
+    }
 
     return {
       id: message.id,
@@ -889,6 +1274,11 @@ async function handleScreenshot(message: Message): Promise<Response> {
         format: 'png',
         data: base64Data,
         encoding: 'base64',
+        url: tab.url,
+        title: tab.title,
+        // Baselines are keyed by this size, not the device-pixel image size
+        viewport: tab.width && tab.height ? { width: tab.width, height: tab.height } : null,
+        highlight,
       },
     };
   } catch (error: any) {
@@ -901,10 +1291,409 @@ async function handleScreenshot(message: Message): Promise<Response> {
   }
 }
 
+// ============================================================================
+// Screenshot Highlights
+// ============================================================================
+
+const HIGHLIGHT_OVERLAY_ID = 'agent-browser-screenshot-highlight';
+const MAX_HIGHLIGHTED_ELEMENTS = 20;
+
+/**
+ * Draw an outline and label over each element matching `selector` in a
+ * fixed, click-through overlay, and resolve once it has been painted
+ */
+async function drawHighlights(tabId: number, frameId: number, selector: string, label?: string): Promise<Record<string, any>> {
+  const [injection] = await chrome.scripting.executeScript({
+    target: { tabId, frameIds: [frameId] },
+    args: [selector, label ?? null, HIGHLIGHT_OVERLAY_ID, MAX_HIGHLIGHTED_ELEMENTS],
+    func: async (selector: string, label: string | null, overlayId: string, max: number) => {
+      let matches: Element[];
+      try {
+        matches = Array.from(document.querySelectorAll(selector));
+      } catch {
+        return { error: `Invalid highlightSelector: ${selector}` };
+      }
+
+      document.getElementById(overlayId)?.remove();
+      const overlay = document.createElement('div');
+      overlay.id = overlayId;
+      overlay.style.cssText = 'position: fixed; inset: 0; pointer-events: none; z-index: 2147483647;';
+
+      const elements = matches.slice(0, max).map((element, index) => {
+        const rect = element.getBoundingClientRect();
+        const classes = typeof element.className === 'string' && element.className.trim()
+          ? `.${element.className.trim().split(/\s+/).slice(0, 2).join('.')}`
+          : '';
+        const description = `${element.tagName.toLowerCase()}${element.id ? `#${element.id}` : ''}${classes}`;
+        const text = matches.length > 1 ? `${index + 1}: ${label ?? description}` : label ?? description;
+
+        const box = document.createElement('div');
+        box.style.cssText = `position: absolute; left: ${rect.left - 3}px; top: ${rect.top - 3}px; `
+          + `width: ${rect.width}px; height: ${rect.height}px; border: 3px solid #ff1744; `
+          + 'box-shadow: 0 0 0 1px #fff, inset 0 0 0 1px #fff; box-sizing: content-box;';
+        const tag = document.createElement('div');
+        tag.textContent = text;
+        // Above the outline, or inside it when the element touches the top edge
+        const tagTop = rect.top >= 20 ? rect.top - 21 : rect.top;
+        tag.style.cssText = `position: absolute; left: ${Math.max(0, rect.left - 3)}px; top: ${Math.max(0, tagTop)}px; `
+          + 'max-width: 320px; overflow: hidden; white-space: nowrap; text-overflow: ellipsis; '
+          + 'background: #ff1744; color: #fff; font: bold 12px/18px system-ui, sans-serif; padding: 0 5px;';
+        overlay.append(box, tag);
+
+        return {
+          index,
+          element: description,
+          bounds: { x: rect.x, y: rect.y, width: rect.width, height: rect.height },
+          inViewport: rect.width > 0 && rect.bottom > 0 && rect.right > 0
+            && rect.top < window.innerHeight && rect.left < window.innerWidth,
+        };
+      });
+      (document.body ?? document.documentElement).appendChild(overlay);
+
+      // Two frames: one to lay out the overlay, one to paint it
+      await new Promise((resolve) => requestAnimationFrame(() => requestAnimationFrame(resolve)));
+      return { selector, count: matches.length, elements };
+    },
+  });
+
+  const result = injection?.result as Record<string, any> | undefined;
+  if (!result) {
+    throw new Error('Could not draw highlights on this page');
+  }
+  if (result.error) {
+    throw new Error(result.error);
+  }
+  return result;
+}
+
+async function removeHighlights(tabId: number, frameId: number): Promise<void> {
+  await chrome.scripting.executeScript({
+    target: { tabId, frameIds: [frameId] },
+    args: [HIGHLIGHT_OVERLAY_ID],
+    func: (overlayId: string) => document.getElementById(overlayId)?.remove(),
+  }).catch(() => {});
+}
+
+// ============================================================================
+// Element Screenshots
+// ============================================================================
+
+const SHOT_ATTRIBUTE = 'data-agent-browser-shot';
+
+interface ShotTarget {
+  id: number;
+  selector: string;
+  /** Index among the selector's matches */
+  index: number;
+  element: string;
+  /** Document coordinates */
+  bounds: { x: number; y: number; width: number; height: number };
+}
+
+/**
+ * Screenshot many elements in one command. Targets are taken top to
+ * bottom: each step scrolls the next one to the top of the viewport, below
+ * any sticky header, captures once, and crops every target that fits.
+ */
+async function handleScreenshotElements(message: Message): Promise<Response> {
+  const { selectors, all = false, maxElements = 20, padding = 0, budgetMs = 25000 } = message.params || {};
+  const deadline = Date.now() + budgetMs;
+  let tabId: number | undefined;
+  try {
+    tabId = await commandTabId(message.params);
+    const tab = await chrome.tabs.get(tabId);
+    if (!tab.active) {
+      throw new Error(`Tab ${tabId} is not the visible tab of its window; call browser_bring_to_front first`);
+    }
+
+    // Tag the targets so later steps can find them again
+    const [listing] = await chrome.scripting.executeScript({
+      target: { tabId },
+      args: [selectors, all, maxElements, SHOT_ATTRIBUTE],
+      func: (selectors: string[], all: boolean, max: number, attribute: string) => {
+        const targets: any[] = [];
+        const missing: string[] = [];
+        let truncated = false;
+        for (const selector of selectors) {
+          let matches: Element[];
+          try {
+            matches = Array.from(document.querySelectorAll(selector));
+          } catch {
+            return { error: `Invalid selector: ${selector}` };
+          }
+          if (matches.length === 0) {
+            missing.push(selector);
+          }
+          for (const [index, element] of (all ? matches : matches.slice(0, 1)).entries()) {
+            if (targets.length === max) {
+              truncated = true;
+              break;
+            }
+            const rect = element.getBoundingClientRect();
+            const classes = typeof element.className === 'string' && element.className.trim()
+              ? `.${element.className.trim().split(/\s+/).slice(0, 2).join('.')}`
+              : '';
+            element.setAttribute(attribute, String(targets.length));
+            targets.push({
+              id: targets.length,
+              selector,
+              index,
+              element: `${element.tagName.toLowerCase()}${element.id ? `#${element.id}` : ''}${classes}`,
+              bounds: { x: rect.x + window.scrollX, y: rect.y + window.scrollY, width: rect.width, height: rect.height },
+            });
+          }
+        }
+        return { targets, missing, truncated, scroll: { x: window.scrollX, y: window.scrollY } };
+      },
+    });
+    const listed = listing?.result as Record<string, any> | undefined;
+    if (!listed) {
+      throw new Error('Cannot read elements on this page');
+    }
+    if (listed.error) {
+      throw new Error(listed.error);
+    }
+
+    const pending: ShotTarget[] = [...listed.targets].sort((a, b) => a.bounds.y - b.bounds.y || a.bounds.x - b.bounds.x);
+    const images: Record<string, any>[] = [];
+    const skipped: Record<string, any>[] = [];
+    let captures = 0;
+
+    while (pending.length > 0) {
+      if (Date.now() + CAPTURE_INTERVAL_MS > deadline) {
+        skipped.push(...pending.map((target) => ({ ...describeShot(target), reason: 'Time budget exhausted' })));
+        break;
+      }
+
+      // Bring the first remaining target to the top and measure everything left
+      const [step] = await chrome.scripting.executeScript({
+        target: { tabId },
+        args: [pending[0].id, pending.map((target) => target.id), padding, SHOT_ATTRIBUTE],
+        func: async (firstId: number, ids: number[], padding: number, attribute: string) => {
+          const find = (id: number) => document.querySelector(`[${attribute}="${id}"]`);
+          const first = find(firstId);
+          let stickyTop = 0;
+          if (first) {
+            for (const element of Array.from(document.body?.querySelectorAll('*') ?? [])) {
+              const style = getComputedStyle(element);
+              if ((style.position !== 'fixed' && style.position !== 'sticky') || element.contains(first)) {
+                continue;
+              }
+              const rect = element.getBoundingClientRect();
+              if (rect.top <= 1 && rect.bottom > 0 && rect.width >= window.innerWidth / 2 && rect.height < window.innerHeight / 3) {
+                stickyTop = Math.max(stickyTop, rect.bottom);
+              }
+            }
+            first.scrollIntoView({ block: 'start', inline: 'nearest' });
+            window.scrollBy(0, first.getBoundingClientRect().top - stickyTop - padding);
+            // Two frames: one to lay out the scrolled page, one to paint it
+            await new Promise((resolve) => requestAnimationFrame(() => requestAnimationFrame(resolve)));
+          }
+          const rects: Record<number, { x: number; y: number; width: number; height: number } | null> = {};
+          for (const id of ids) {
+            const rect = find(id)?.getBoundingClientRect();
+            rects[id] = rect ? { x: rect.x, y: rect.y, width: rect.width, height: rect.height } : null;
+          }
+          return { rects, stickyTop, viewport: { width: window.innerWidth, height: window.innerHeight } };
+        },
+      });
+      const measured = step?.result as Record<string, any> | undefined;
+      if (!measured) {
+        throw new Error('Cannot scroll this page');
+      }
+
+      const bitmap = await decodePng(await captureTab(tab.windowId));
+      captures++;
+      const first = pending[0];
+      try {
+        for (const target of [...pending]) {
+          const rect = measured.rects[target.id];
+          if (!rect || rect.width === 0 || rect.height === 0) {
+            skipped.push({ ...describeShot(target), reason: rect ? 'Element has no size' : 'Element was removed from the page' });
+            pending.splice(pending.indexOf(target), 1);
+            continue;
+          }
+          const box = {
+            x: rect.x - padding,
+            y: rect.y - padding,
+            width: rect.width + 2 * padding,
+            height: rect.height + 2 * padding,
+          };
+          const fits = box.x >= 0 && box.y >= measured.stickyTop
+            && box.x + box.width <= measured.viewport.width && box.y + box.height <= measured.viewport.height;
+          if (!fits && target !== first) {
+            continue;
+          }
+          // The first target is taken even when it is taller or wider than the viewport
+          const visible = {
+            x: Math.max(box.x, 0),
+            y: Math.max(box.y, measured.stickyTop),
+            width: 0,
+            height: 0,
+          };
+          visible.width = Math.min(box.x + box.width, measured.viewport.width) - visible.x;
+          visible.height = Math.min(box.y + box.height, measured.viewport.height) - visible.y;
+          const cropped = visible.width > 0 && visible.height > 0
+            ? await cropBitmap(bitmap, visible, measured.viewport.width)
+            : null;
+          if (cropped) {
+            images.push({ ...describeShot(target), clipped: !fits, format: 'png', ...cropped });
+          } else {
+            skipped.push({ ...describeShot(target), reason: 'Element could not be scrolled into view' });
+          }
+          pending.splice(pending.indexOf(target), 1);
+        }
+      } finally {
+        bitmap.close();
+      }
+    }
+
+    images.sort((a, b) => a.id - b.id);
+    const scroll = listed.scroll;
+    await cleanupShots(tabId, scroll);
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        tabId,
+        url: tab.url,
+        images: images.map(({ id, ...image }) => image),
+        missing: listed.missing,
+        skipped: skipped.map(({ id, ...target }) => target),
+        truncated: listed.truncated,
+        captures,
+      },
+    };
+  } catch (error: any) {
+    if (tabId !== undefined) {
+      await cleanupShots(tabId, null);
+    }
+    console.error('[Background] screenshot_elements error:', error);
+    return { id: message.id, success: false, error: error.message || 'Element screenshots failed' };
+  }
+}
+
+function describeShot(target: ShotTarget): Record<string, any> {
+  return { id: target.id, selector: target.selector, index: target.index, element: target.element, bounds: target.bounds };
+}
+
+/** Remove the target tags and put the scroll position back */
+async function cleanupShots(tabId: number, scroll: { x: number; y: number } | null): Promise<void> {
+  await chrome.scripting.executeScript({
+    target: { tabId },
+    args: [SHOT_ATTRIBUTE, scroll],
+    func: (attribute: string, scroll: { x: number; y: number } | null) => {
+      document.querySelectorAll(`[${attribute}]`).forEach((element) => element.removeAttribute(attribute));
+      if (scroll) {
+        window.scrollTo(scroll.x, scroll.y);
+      }
+    },
+  }).catch(() => {});
+}
+
+// ============================================================================
+// Image Diff Handler
+// ============================================================================
+
+async function decodePng(base64: string): Promise<ImageData> {
+  const blob = await (await fetch(`data:image/png;base64,${base64}`)).blob();
+  const bitmap = await createImageBitmap(blob);
+  const canvas = new OffscreenCanvas(bitmap.width, bitmap.height);
+  const context = canvas.getContext('2d')!;
+  context.drawImage(bitmap, 0, 0);
+  bitmap.close();
+  return context.getImageData(0, 0, canvas.width, canvas.height);
+}
+
+async function encodePng(image: ImageData): Promise<string> {
+  const canvas = new OffscreenCanvas(image.width, image.height);
+  canvas.getContext('2d')!.putImageData(image, 0, 0);
+  const bytes = new Uint8Array(await (await canvas.convertToBlob({ type: 'image/png' })).arrayBuffer());
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+/**
+ * Compare two PNGs pixel by pixel for screenshot_diff. The diff image shows
+ * the expected image faded, with differing pixels in red.
+ */
+async function handleImageDiff(message: Message): Promise<Response> {
+  try {
+    const { expected: expectedData, actual: actualData, tolerance = 16 } = message.params ?? {};
+    const [expected, actual] = await Promise.all([decodePng(expectedData), decodePng(actualData)]);
+
+    if (expected.width !== actual.width || expected.height !== actual.height) {
+      return {
+        id: message.id,
+        success: true,
+        result: {
+          sizeMismatch: true,
+          expectedSize: { width: expected.width, height: expected.height },
+          actualSize: { width: actual.width, height: actual.height },
+          diffPixels: null,
+          diffRatio: 1,
+        },
+      };
+    }
+
+    const diff = new ImageData(expected.width, expected.height);
+    let diffPixels = 0;
+    for (let i = 0; i < expected.data.length; i += 4) {
+      const differs = Math.abs(expected.data[i] - actual.data[i]) > tolerance
+        || Math.abs(expected.data[i + 1] - actual.data[i + 1]) > tolerance
+        || Math.abs(expected.data[i + 2] - actual.data[i + 2]) > tolerance
+        || Math.abs(expected.data[i + 3] - actual.data[i + 3]) > tolerance;
+      if (differs) {
+        diffPixels++;
+        diff.data.set([255, 0, 0, 255], i);
+      } else {
+        const gray = (expected.data[i] + expected.data[i + 1] + expected.data[i + 2]) / 3;
+        diff.data.set([gray, gray, gray, 64], i);
+      }
+    }
+
+    const total = expected.width * expected.height;
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        sizeMismatch: false,
+        width: expected.width,
+        height: expected.height,
+        diffPixels,
+        diffRatio: total > 0 ? diffPixels / total : 0,
+        diff: diffPixels > 0 ? await encodePng(diff) : null,
+      },
+    };
+  } catch (error: any) {
+    console.error('[Background] Image diff error:', error);
+    return {
+      id: message.id,
+      success: false,
+      error: error.message || 'Image diff failed',
+    };
+  }
+}
+
 // ============================================================================
 // Passkey Automation Handlers
 // ============================================================================
 
+const MAX_DASHBOARD_EVENTS = 50;
+
+async function handleDashboardEvent(message: Message) {
+  console.log('[Background] Dashboard event:', message.params);
+
+  // Keep a short rolling history for the popup to display
+  const { dashboardEvents = [] } = await chrome.storage.local.get('dashboardEvents');
+  const events = [{ ...message.params, receivedAt: Date.now() }, ...dashboardEvents]
+    .slice(0, MAX_DASHBOARD_EVENTS);
+  await chrome.storage.local.set({ dashboardEvents: events });
+}
+
 async function handlePasskeyEnable(message: Message): Promise<Response> {
   try {
     if (!webAuthnProxy) {
@@ -1016,46 +1805,1949 @@ async function handlePasskeyClear(message: Message): Promise<Response> {
 }
 
 // ============================================================================
-// Native Messaging Host - Ensure Server Running
+// Watch Extraction
 // ============================================================================
 
-async function ensureServerRunning() {
-  console.log('[Background] Calling NMH to ensure server is running...');
-  setBadgeState({ serverStatus: 'starting' });
+const WATCH_LOAD_TIMEOUT_MS = 20000;
 
+// Load the page in a background tab, read the watched value, and close the tab
+async function handleWatchExtract(message: Message): Promise<Response> {
+  const { url, selector, attribute } = message.params || {};
+  let tabId: number | undefined;
   try {
-    const response = await chrome.runtime.sendNativeMessage(
-      'com.agentbrowser.native',
-      { cmd: 'ensure_server' }
-    );
+    if (!url) {
+      throw new Error('URL is required for watch_extract');
+    }
 
-    console.log('[Background] NMH response:', response);
+    const tab = await chrome.tabs.create({ url, active: false });
+    tabId = tab.id!;
+    await waitForTabComplete(tabId, WATCH_LOAD_TIMEOUT_MS);
 
-    if (response.ok) {
-      console.log('[Background] Server is running');
-      if (response.logs) {
-        console.log('[Background] Server logs:', response.logs);
-      }
-      // Give server a moment to fully start, then connect WebSocket
-      setTimeout(() => connect(), 1000);
-    } else {
-      console.error('[Background] NMH reported error:', response.error);
-      setBadgeState({
-        serverStatus: 'error',
-        errorMessage: response.error || 'NMH reported error',
-      });
-      // Fall back to trying to connect anyway (maybe server is running)
-      setTimeout(() => connect(), 2000);
-    }
-  } catch (error: any) {
-    console.error('[Background] Failed to call NMH:', error);
-    console.log('[Background] NMH may not be installed. Trying to connect to server anyway...');
-    setBadgeState({
-      serverStatus: 'error',
-      errorMessage: 'NMH not responding',
+    const [injection] = await chrome.scripting.executeScript({
+      target: { tabId },
+      args: [selector ?? null, attribute ?? null],
+      func: (selector: string | null, attribute: string | null) => {
+        const element = selector ? document.querySelector(selector) : document.body;
+        if (!element) {
+          return null;
+        }
+        return attribute ? element.getAttribute(attribute) : (element as HTMLElement).innerText;
+      },
     });
-    // Fall back to connecting (maybe server is running manually)
-    setTimeout(() => connect(), 2000);
+    const finalTab = await chrome.tabs.get(tabId);
+
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        value: injection?.result ?? null,
+        url: finalTab.url,
+        title: finalTab.title,
+      },
+    };
+  } catch (error: any) {
+    console.error('[Background] Watch extract error:', error);
+    return {
+      id: message.id,
+      success: false,
+      error: error.message || 'Failed to extract watched value',
+    };
+  } finally {
+    if (tabId !== undefined) {
+      chrome.tabs.remove(tabId).catch(() => {});
+    }
+  }
+}
+
+async function handleEvaluate(message: Message): Promise<Response> {
+  const { expression, args, frameId, awaitPromise, tabId } = message.params || {};
+  try {
+    let targetTabId: number | undefined = tabId;
+    if (targetTabId === undefined) {
+      const [tab] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+      targetTabId = tab?.id;
+    }
+    if (targetTabId === undefined) {
+      throw new Error('No active tab to evaluate in');
+    }
+
+    // Runs in the page's own world so page globals are visible; string
+    // evaluation is still subject to the page's Content-Security-Policy
+    const [injection] = await chrome.scripting.executeScript({
+      target: frameId != null ? { tabId: targetTabId, frameIds: [frameId] } : { tabId: targetTabId },
+      world: 'MAIN',
+      args: [expression, args ?? [], awaitPromise !== false],
+      func: async (expression: string, args: unknown[], awaitPromise: boolean) => {
+        let value: unknown;
+        try {
+          value = (0, eval)(`(${expression})`);
+          if (typeof value === 'function') {
+            value = value(...args);
+          }
+          if (awaitPromise) {
+            value = await value;
+          }
+        } catch (error: any) {
+          return { ok: false, error: String(error?.stack || error) };
+        }
+        try {
+          return { ok: true, type: typeof value, value: JSON.parse(JSON.stringify(value ?? null) ?? 'null') };
+        } catch {
+          return { ok: false, error: `Result of type ${typeof value} is not JSON-serializable` };
+        }
+      },
+    });
+
+    const outcome = injection?.result as { ok: boolean; type?: string; value?: unknown; error?: string } | undefined;
+    if (!outcome?.ok) {
+      throw new Error(outcome?.error || 'Evaluation returned no result');
+    }
+    return {
+      id: message.id,
+      success: true,
+      result: { value: outcome.value, type: outcome.type },
+    };
+  } catch (error: any) {
+    console.error('[Background] Evaluate error:', error);
+    return {
+      id: message.id,
+      success: false,
+      error: error.message || 'Failed to evaluate expression',
+    };
+  }
+}
+
+const HISTORY_LOAD_TIMEOUT_MS = 20000;
+
+async function handleHistoryNavigation(message: Message): Promise<Response> {
+  const { tabId, bypassCache, timeoutMs } = message.params || {};
+  try {
+    let targetTabId: number | undefined = tabId;
+    if (targetTabId === undefined) {
+      const [tab] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+      targetTabId = tab?.id;
+    }
+    if (targetTabId === undefined) {
+      throw new Error('No active tab to navigate');
+    }
+    const id = targetTabId;
+    const before = await chrome.tabs.get(id);
+
+    // Listen before acting so a fast (or back/forward cached) load isn't missed
+    const load = waitForNextLoad(id, timeoutMs ?? HISTORY_LOAD_TIMEOUT_MS);
+    try {
+      if (message.method === 'go_back') {
+        await chrome.tabs.goBack(id);
+      } else if (message.method === 'go_forward') {
+        await chrome.tabs.goForward(id);
+      } else {
+        await chrome.tabs.reload(id, { bypassCache: bypassCache === true });
+      }
+    } catch (error) {
+      load.cancel();
+      throw error;
+    }
+    await load.done;
+
+    const after = await chrome.tabs.get(id);
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        url: after.url,
+        title: after.title,
+        previousUrl: before.url,
+      },
+    };
+  } catch (error: any) {
+    console.error(`[Background] ${message.method} error:`, error);
+    return {
+      id: message.id,
+      success: false,
+      // goBack/goForward reject with "Cannot find a next page in history." at either end
+      error: error.message || `Failed to ${message.method.replace('_', ' ')}`,
+    };
+  }
+}
+
+/** Waits for the tab's next load to complete, unlike waitForTabComplete which accepts the current one */
+function waitForNextLoad(tabId: number, timeoutMs: number): { done: Promise<void>; cancel: () => void } {
+  let cancel = () => {};
+  const done = new Promise<void>((resolve, reject) => {
+    const stop = () => {
+      clearTimeout(timer);
+      chrome.tabs.onUpdated.removeListener(listener);
+    };
+    const timer = setTimeout(() => {
+      stop();
+      reject(new Error(`Page did not finish loading within ${timeoutMs} ms`));
+    }, timeoutMs);
+    const listener = (updatedId: number, info: chrome.tabs.TabChangeInfo) => {
+      if (updatedId === tabId && info.status === 'complete') {
+        stop();
+        resolve();
+      }
+    };
+    chrome.tabs.onUpdated.addListener(listener);
+    cancel = stop;
+  });
+  return { done, cancel };
+}
+
+function waitForTabComplete(tabId: number, timeoutMs: number): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const timer = setTimeout(() => {
+      chrome.tabs.onUpdated.removeListener(listener);
+      reject(new Error(`Page did not finish loading within ${timeoutMs} ms`));
+    }, timeoutMs);
+    const listener = (updatedId: number, info: chrome.tabs.TabChangeInfo) => {
+      if (updatedId === tabId && info.status === 'complete') {
+        clearTimeout(timer);
+        chrome.tabs.onUpdated.removeListener(listener);
+        resolve();
+      }
+    };
+    chrome.tabs.onUpdated.addListener(listener);
+    // The page may have finished before the listener was attached
+    chrome.tabs.get(tabId).then(tab => {
+      if (tab.status === 'complete') {
+        listener(tabId, { status: 'complete' });
+      }
+    }).catch(() => {});
+  });
+}
+
+// ============================================================================
+// Cookie Handlers
+// ============================================================================
+
+/** Cookie filter from `domain` or `url`, defaulting to the target tab's URL */
+async function cookieScope(params: Record<string, any>): Promise<{ domain?: string; url?: string }> {
+  if (params.domain) {
+    return { domain: params.domain };
+  }
+  if (params.url) {
+    return { url: params.url };
+  }
+  const tab = params.tabId !== undefined
+    ? await chrome.tabs.get(params.tabId)
+    : (await chrome.tabs.query({ active: true, lastFocusedWindow: true }))[0];
+  if (!tab?.url || !/^https?:/.test(tab.url)) {
+    throw new Error('No http(s) page in the target tab; pass domain or url');
+  }
+  return { url: tab.url };
+}
+
+/** URL chrome.cookies.set/remove needs for a cookie */
+function cookieUrl(cookie: { domain: string; path: string; secure: boolean }): string {
+  return `${cookie.secure ? 'https' : 'http'}://${cookie.domain.replace(/^\./, '')}${cookie.path}`;
+}
+
+function describeCookie(cookie: chrome.cookies.Cookie, includeValue: boolean) {
+  return {
+    name: cookie.name,
+    ...(includeValue ? { value: cookie.value } : {}),
+    domain: cookie.domain,
+    path: cookie.path,
+    secure: cookie.secure,
+    httpOnly: cookie.httpOnly,
+    sameSite: cookie.sameSite,
+    session: cookie.session,
+    expirationDate: cookie.expirationDate ?? null,
+  };
+}
+
+async function handleCookies(message: Message): Promise<Response> {
+  const params = message.params || {};
+  try {
+    let result;
+    if (message.method === 'cookies_set') {
+      const scope = params.url
+        ? { url: params.url }
+        : params.domain
+          ? { url: cookieUrl({ domain: params.domain, path: params.path ?? '/', secure: params.secure ?? true }), domain: params.domain }
+          : await cookieScope(params);
+      const cookie = await chrome.cookies.set({
+        ...scope,
+        name: params.name,
+        value: params.value,
+        path: params.path,
+        secure: params.secure,
+        httpOnly: params.httpOnly,
+        sameSite: params.sameSite,
+        expirationDate: params.expirationDate,
+      });
+      if (!cookie) {
+        // chrome.cookies.set resolves null when the browser rejects the cookie
+        throw new Error(chrome.runtime.lastError?.message || `Cookie '${params.name}' was rejected by the browser`);
+      }
+      result = { cookie: describeCookie(cookie, false) };
+    } else {
+      const scope = await cookieScope(params);
+      const cookies = await chrome.cookies.getAll({ ...scope, ...(params.name ? { name: params.name } : {}) });
+      if (message.method === 'cookies_get') {
+        result = {
+          ...scope,
+          count: cookies.length,
+          cookies: cookies.map((cookie) => describeCookie(cookie, params.includeValues === true)),
+        };
+      } else {
+        const removed = await Promise.all(cookies.map((cookie) =>
+          chrome.cookies.remove({ url: cookieUrl(cookie), name: cookie.name, storeId: cookie.storeId })));
+        result = { ...scope, removed: removed.filter(Boolean).length };
+      }
+    }
+    return { id: message.id, success: true, result };
+  } catch (error: any) {
+    console.error(`[Background] ${message.method} error:`, error);
+    return {
+      id: message.id,
+      success: false,
+      error: error.message || `Failed to ${message.method.replace('_', ' ')}`,
+    };
+  }
+}
+
+// ============================================================================
+// Network Request Capture
+// ============================================================================
+
+const CAPTURED_URLS = { urls: ['http://*/*', 'https://*/*'] };
+
+// Requests in flight, keyed by webRequest requestId (redirects keep the id)
+const requestsInFlight = new Map<string, {
+  tabId: number;
+  url: string;
+  startedAt: number;
+  requestHeaders?: Record<string, string>;
+  requestBody?: string;
+}>();
+
+interface NetworkCaptureConfig {
+  captureBodies: boolean;
+  maxBodyBytes: number;
+}
+
+// Whether bodies are collected, as the server's capture policy last said; kept
+// in storage so a restarted service worker doesn't fall back to none
+let networkCaptureConfig: NetworkCaptureConfig = { captureBodies: false, maxBodyBytes: 65536 };
+chrome.storage.local.get('networkCaptureConfig').then(({ networkCaptureConfig: stored }) => {
+  if (stored) {
+    networkCaptureConfig = stored;
+  }
+});
+
+// How long a fetch/XHR report waits for the page hooks to read its response body
+const RESPONSE_BODY_WAIT_MS = 1000;
+const MAX_UNCLAIMED_BODIES = 50;
+// Content types the page hooks read bodies of, as in network-hooks.ts
+const TEXT_BODY_TYPES = /^(text\/|application\/([\w.+-]*\+)?(json|xml|javascript|x-www-form-urlencoded|graphql))/i;
+
+// Response bodies from network-hooks, keyed by tab, method and URL, and the reports waiting on them
+const responseBodies = new Map<string, string>();
+const responseBodyWaiters = new Map<string, (body: string) => void>();
+
+function bodyKey(tabId: number, method: string, url: string): string {
+  return `${tabId} ${method.toUpperCase()} ${url}`;
+}
+
+/** A response body read by the page hooks, handed to the report waiting for it or kept briefly */
+function receiveResponseBody(tabId: number, data: { method: string; url: string; body: string }): void {
+  const key = bodyKey(tabId, data.method, data.url);
+  const waiter = responseBodyWaiters.get(key);
+  if (waiter) {
+    responseBodyWaiters.delete(key);
+    waiter(data.body);
+    return;
+  }
+  if (responseBodies.size >= MAX_UNCLAIMED_BODIES) {
+    responseBodies.delete(responseBodies.keys().next().value!);
+  }
+  responseBodies.set(key, data.body);
+}
+
+function awaitResponseBody(key: string): Promise<string | undefined> {
+  const ready = responseBodies.get(key);
+  if (ready !== undefined) {
+    responseBodies.delete(key);
+    return Promise.resolve(ready);
+  }
+  return new Promise((resolve) => {
+    const timer = setTimeout(() => {
+      responseBodyWaiters.delete(key);
+      resolve(undefined);
+    }, RESPONSE_BODY_WAIT_MS);
+    responseBodyWaiters.set(key, (body) => {
+      clearTimeout(timer);
+      resolve(body);
+    });
+  });
+}
+
+/** Form fields as a query string, raw bytes as UTF-8; uploaded files are left out */
+function decodeRequestBody(body: chrome.webRequest.WebRequestBody | null | undefined): string | undefined {
+  if (!body || body.error) {
+    return undefined;
+  }
+  if (body.formData) {
+    const params = new URLSearchParams();
+    for (const [name, values] of Object.entries(body.formData)) {
+      for (const value of values) {
+        params.append(name, String(value));
+      }
+    }
+    return params.toString();
+  }
+  const chunks = (body.raw ?? []).filter((part) => part.bytes).map((part) => new Uint8Array(part.bytes!));
+  if (chunks.length === 0) {
+    return undefined;
+  }
+  const bytes = new Uint8Array(chunks.reduce((total, chunk) => total + chunk.length, 0));
+  let offset = 0;
+  for (const chunk of chunks) {
+    bytes.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return new TextDecoder().decode(bytes);
+}
+
+/** Apply the server's capture policy here and in every tab's page hooks */
+async function handleNetworkCaptureConfig(message: Message): Promise<Response> {
+  const { captureBodies, maxBodyBytes } = message.params || {};
+  networkCaptureConfig = {
+    captureBodies: captureBodies === true,
+    maxBodyBytes: typeof maxBodyBytes === 'number' ? maxBodyBytes : networkCaptureConfig.maxBodyBytes,
+  };
+  await chrome.storage.local.set({ networkCaptureConfig });
+  const tabs = await chrome.tabs.query({});
+  for (const tab of tabs) {
+    if (tab.id !== undefined) {
+      chrome.tabs.sendMessage(tab.id, { type: 'network_capture_config', config: networkCaptureConfig }).catch(() => {});
+    }
+  }
+  return { id: message.id, success: true, result: networkCaptureConfig };
+}
+
+function headerMap(headers?: chrome.webRequest.HttpHeader[]): Record<string, string> {
+  const map: Record<string, string> = {};
+  for (const header of headers ?? []) {
+    map[header.name] = header.value ?? '';
+  }
+  return map;
+}
+
+/**
+ * Report a finished request as an `http` event; the server redacts and stores it.
+ * With bodies on, a fetch/XHR report first waits for the page hooks to read its response.
+ */
+async function reportRequest(
+  details: chrome.webRequest.WebRequestDetails,
+  outcome: { status?: number; responseHeaders?: chrome.webRequest.HttpHeader[]; error?: string }
+) {
+  const started = requestsInFlight.get(details.requestId);
+  requestsInFlight.delete(details.requestId);
+  const contentType = outcome.responseHeaders?.find((header) => header.name.toLowerCase() === 'content-type')?.value ?? '';
+  const responseBody = networkCaptureConfig.captureBodies && details.type === 'xmlhttprequest' && TEXT_BODY_TYPES.test(contentType)
+    ? await awaitResponseBody(bodyKey(details.tabId, details.method, details.url))
+    : undefined;
+  const startedAt = started?.startedAt ?? details.timeStamp;
+  const responseHeaders = headerMap(outcome.responseHeaders);
+  const contentLength = Object.entries(responseHeaders).find(([name]) => name.toLowerCase() === 'content-length')?.[1];
+  sendEvent('http', {
+    id: details.requestId,
+    url: details.url,
+    method: details.method,
+    status: outcome.status,
+    type: details.type,
+    tabId: details.tabId,
+    timestamp: Math.round(startedAt),
+    durationMs: Math.round((details.timeStamp - startedAt) * 10) / 10,
+    size: contentLength !== undefined && /^\d+$/.test(contentLength) ? Number(contentLength) : undefined,
+    requestHeaders: started?.requestHeaders ?? {},
+    responseHeaders,
+    requestBody: started?.requestBody,
+    responseBody,
+    error: outcome.error,
+  });
+}
+
+// Only page requests are captured; the extension's own (tabId -1) are skipped
+chrome.webRequest.onBeforeRequest.addListener((details) => {
+  if (details.tabId >= 0) {
+    requestsInFlight.set(details.requestId, {
+      tabId: details.tabId,
+      url: details.url,
+      startedAt: details.timeStamp,
+      requestBody: networkCaptureConfig.captureBodies ? decodeRequestBody(details.requestBody) : undefined,
+    });
+  }
+}, CAPTURED_URLS, ['requestBody']);
+
+chrome.webRequest.onSendHeaders.addListener((details) => {
+  const started = requestsInFlight.get(details.requestId);
+  if (started) {
+    started.requestHeaders = headerMap(details.requestHeaders);
+  }
+}, CAPTURED_URLS, ['requestHeaders']);
+
+chrome.webRequest.onCompleted.addListener((details) => {
+  if (details.tabId >= 0) {
+    reportRequest(details, { status: details.statusCode, responseHeaders: details.responseHeaders });
+  }
+}, CAPTURED_URLS, ['responseHeaders']);
+
+chrome.webRequest.onErrorOccurred.addListener((details) => {
+  if (details.tabId >= 0) {
+    reportRequest(details, { error: details.error });
+  }
+}, CAPTURED_URLS);
+
+/** The tab commands without a tabId go to, for server tools that filter by tab */
+async function handleActiveTab(message: Message): Promise<Response> {
+  const [tab] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+  if (tab?.id === undefined) {
+    return { id: message.id, success: false, error: 'No active tab' };
+  }
+  return { id: message.id, success: true, result: { tabId: tab.id, url: tab.url, title: tab.title } };
+}
+
+// ============================================================================
+// Load State Waits
+// ============================================================================
+
+const LOAD_STATE_TIMEOUT_MS = 20000;
+const LOAD_STATE_POLL_MS = 50;
+const DEFAULT_NETWORK_IDLE_MS = 500;
+
+type LoadState = 'domcontentloaded' | 'load' | 'networkidle';
+
+interface LoadWait {
+  state: LoadState;
+  timeoutMs?: number;
+  /** How long the network must stay quiet for networkidle */
+  idleMs?: number;
+  /** Requests allowed to stay open, such as long polls, for networkidle */
+  maxInflight?: number;
+  /** Only a document created at or after this time counts (epoch ms) */
+  since?: number;
+}
+
+// When the command before the current one arrived, for waits on the
+// navigation that command caused
+let previousCommandAt = 0;
+let currentCommandAt = 0;
+
+/** Requests the tab has open, oldest first */
+function tabRequestsInFlight(tabId: number): string[] {
+  return Array.from(requestsInFlight.values())
+    .filter((request) => request.tabId === tabId)
+    .sort((a, b) => a.startedAt - b.startedAt)
+    .map((request) => request.url);
+}
+
+/** The top document's ready state and creation time; null while none can be read */
+async function documentState(tabId: number): Promise<{ readyState: DocumentReadyState; timeOrigin: number; url: string } | null> {
+  try {
+    const [injection] = await chrome.scripting.executeScript({
+      target: { tabId },
+      func: () => ({ readyState: document.readyState, timeOrigin: performance.timeOrigin, url: location.href }),
+    });
+    return injection?.result ?? null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Poll until the tab's document reaches the state. DOMContentLoaded has
+ * fired once readyState leaves "loading"; load needs "complete" and the tab
+ * done loading; networkidle additionally needs `idleMs` without requests
+ * beyond `maxInflight`.
+ */
+async function waitForLoadState(tabId: number, wait: LoadWait): Promise<Record<string, any>> {
+  const startedAt = Date.now();
+  const timeoutMs = wait.timeoutMs ?? LOAD_STATE_TIMEOUT_MS;
+  const idleMs = wait.idleMs ?? DEFAULT_NETWORK_IDLE_MS;
+  const maxInflight = wait.maxInflight ?? 0;
+  let idleSince: number | null = null;
+  let reachedState = false;
+
+  while (true) {
+    const tab = await chrome.tabs.get(tabId);
+    const page = await documentState(tabId);
+    // Pages scripts can't run in (chrome://, the web store) only report tab status
+    const fresh = wait.since === undefined || page === null || page.timeOrigin >= wait.since;
+    const loaded = page === null ? tab.status === 'complete' : page.readyState === 'complete' && tab.status === 'complete';
+    const parsed = page !== null && page.readyState !== 'loading';
+
+    if (fresh && (wait.state === 'domcontentloaded' ? parsed || loaded : loaded)) {
+      reachedState = true;
+      if (wait.state !== 'networkidle') {
+        break;
+      }
+      const open = tabRequestsInFlight(tabId).length;
+      if (open > maxInflight) {
+        idleSince = null;
+      } else if (idleSince === null) {
+        idleSince = Date.now();
+      } else if (Date.now() - idleSince >= idleMs) {
+        break;
+      }
+    } else {
+      idleSince = null;
+    }
+
+    if (Date.now() - startedAt >= timeoutMs) {
+      const pending = tabRequestsInFlight(tabId);
+      const detail = !fresh
+        ? 'no navigation started'
+        : !reachedState
+          ? `the page is still ${page?.readyState ?? tab.status}`
+          : `${pending.length} request${pending.length === 1 ? '' : 's'} still open: ${pending.slice(0, 5).join(', ')}`;
+      throw new Error(`Timed out after ${timeoutMs} ms waiting for ${wait.state}: ${detail}`);
+    }
+    await new Promise((resolve) => setTimeout(resolve, LOAD_STATE_POLL_MS));
+  }
+
+  const tab = await chrome.tabs.get(tabId);
+  return {
+    state: wait.state,
+    url: tab.url,
+    title: tab.title,
+    waitedMs: Date.now() - startedAt,
+  };
+}
+
+/** Tabs with pages the content script can read, for the server's browser://tab resources */
+async function handleListTabs(message: Message): Promise<Response> {
+  try {
+    const tabs = await chrome.tabs.query({});
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        tabs: tabs
+          .filter((tab) => tab.id !== undefined && /^(https?|file):/.test(tab.url ?? ''))
+          .map((tab) => ({ id: tab.id, title: tab.title ?? null, url: tab.url, active: tab.active, windowId: tab.windowId })),
+      },
+    };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to list tabs' };
+  }
+}
+
+/** Tab a command without tabId goes to, as routeToTab picks it */
+async function commandTabId(params: Record<string, any> | undefined): Promise<number> {
+  if (typeof params?.tabId === 'number') {
+    return params.tabId;
+  }
+  let tabs = await chrome.tabs.query({ active: true, currentWindow: true });
+  if (tabs.length === 0) {
+    tabs = await chrome.tabs.query({ active: true });
+  }
+  if (tabs[0]?.id === undefined) {
+    throw new Error('No active tab');
+  }
+  return tabs[0].id;
+}
+
+async function handleWaitForLoad(message: Message): Promise<Response> {
+  const { state, timeoutMs, idleMs, maxInflight, expectNavigation } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    const result = await waitForLoadState(tabId, {
+      state: state ?? 'load',
+      timeoutMs,
+      idleMs,
+      maxInflight,
+      since: expectNavigation ? previousCommandAt : undefined,
+    });
+    return { id: message.id, success: true, result };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to wait for load state' };
+  }
+}
+
+/** Navigate in the content script, then block until the new document reaches `waitUntil` */
+async function handleNavigateAndWait(message: Message): Promise<Response> {
+  const { waitUntil, timeoutMs, idleMs, maxInflight } = message.params;
+  const since = Date.now();
+  const response = await routeToTab(message);
+  if (!response.success) {
+    return response;
+  }
+  try {
+    const tabId = await commandTabId(message.params);
+    const load = await waitForLoadState(tabId, { state: waitUntil, timeoutMs, idleMs, maxInflight, since });
+    return { ...response, result: { ...response.result, ...load } };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to wait for navigation' };
+  }
+}
+
+// Time a consent iframe gets to close before the dismissal is checked
+const CONSENT_VERIFY_DELAY_MS = 500;
+
+/**
+ * The content script presses consent buttons in the page and under shadow
+ * roots; a banner rendered in an iframe comes back as a frame rule, and its
+ * button is pressed here in whichever subframe has it
+ */
+async function handleDismissConsent(message: Message): Promise<Response> {
+  const response = await routeToTab(message);
+  const pending = response.success ? response.result?.pendingFrame : undefined;
+  if (!pending) {
+    return response;
+  }
+  try {
+    const tabId = await commandTabId(message.params);
+    const injections = await chrome.scripting.executeScript({
+      target: { tabId, allFrames: true },
+      args: [pending.button],
+      func: (button: string) => {
+        if (window === window.top) {
+          return false;
+        }
+        const element = document.querySelector(button);
+        if (!(element instanceof HTMLElement)) {
+          return false;
+        }
+        element.click();
+        return true;
+      },
+    });
+    const pressed = injections.find((injection) => injection.result === true);
+    if (!pressed) {
+      return { ...response, result: { ...response.result, pendingFrame: undefined, error: `No frame has ${pending.button}` } };
+    }
+    await new Promise((resolve) => setTimeout(resolve, CONSENT_VERIFY_DELAY_MS));
+    const [check] = await chrome.scripting.executeScript({
+      target: { tabId, frameIds: [0] },
+      args: [pending.selector],
+      func: (selector: string) => document.querySelector(selector) !== null,
+    });
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        dismissed: true,
+        mode: response.result.mode,
+        platform: response.result.platform,
+        button: pending.button,
+        frameId: pressed.frameId,
+        verified: check?.result === false,
+      },
+    };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to dismiss consent banner in its frame' };
+  }
+}
+
+// ============================================================================
+// Service Workers
+// ============================================================================
+
+// Tabs navigating with Network.setBypassServiceWorker on
+const serviceWorkerBypasses = new Set<number>();
+
+/** Run a function over the top frame's service worker container */
+async function inTopFrame<T>(tabId: number, func: (scope: string | null) => Promise<T>, scope: string | null = null): Promise<T> {
+  const [injection] = await chrome.scripting.executeScript({ target: { tabId }, args: [scope], func });
+  if (!injection) {
+    throw new Error('Cannot run in this page');
+  }
+  return injection.result as T;
+}
+
+async function handleListServiceWorkers(message: Message): Promise<Response> {
+  try {
+    const tabId = await commandTabId(message.params);
+    const result = await inTopFrame(tabId, async () => {
+      if (!('serviceWorker' in navigator)) {
+        return { origin: location.origin, supported: false, controlled: false, registrations: [] };
+      }
+      const registrations = await navigator.serviceWorker.getRegistrations();
+      return {
+        origin: location.origin,
+        supported: true,
+        // Whether this page's own requests go through a worker
+        controlled: navigator.serviceWorker.controller !== null,
+        registrations: registrations.map((registration) => {
+          const worker = registration.active ?? registration.waiting ?? registration.installing;
+          return {
+            scope: registration.scope,
+            scriptURL: worker?.scriptURL ?? null,
+            state: worker?.state ?? null,
+            waiting: registration.waiting !== null,
+            updateViaCache: registration.updateViaCache,
+          };
+        }),
+      };
+    });
+    return { id: message.id, success: true, result: { tabId, ...result } };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to list service workers' };
+  }
+}
+
+async function handleUnregisterServiceWorkers(message: Message): Promise<Response> {
+  try {
+    const tabId = await commandTabId(message.params);
+    const result = await inTopFrame(tabId, async (scope) => {
+      if (!('serviceWorker' in navigator)) {
+        return { origin: location.origin, unregistered: [], failed: [] };
+      }
+      const registrations = (await navigator.serviceWorker.getRegistrations())
+        .filter((registration) => scope === null || registration.scope === scope);
+      const unregistered: string[] = [];
+      const failed: string[] = [];
+      for (const registration of registrations) {
+        (await registration.unregister().catch(() => false) ? unregistered : failed).push(registration.scope);
+      }
+      return { origin: location.origin, unregistered, failed };
+    }, typeof message.params?.scope === 'string' ? message.params.scope : null);
+    if (message.params?.scope && result.unregistered.length === 0 && result.failed.length === 0) {
+      throw new Error(`No service worker registered with scope ${message.params.scope}`);
+    }
+    // An unregistered worker keeps controlling open pages until they reload
+    return { id: message.id, success: true, result: { tabId, ...result, reloadRequired: result.unregistered.length > 0 } };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to unregister service workers' };
+  }
+}
+
+/**
+ * Navigate with Network.setBypassServiceWorker on until the new document has
+ * loaded, so its subresources also come from the network
+ */
+async function handleNavigateBypassingServiceWorker(message: Message): Promise<Response> {
+  let tabId: number;
+  try {
+    tabId = await commandTabId(message.params);
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    serviceWorkerBypasses.add(tabId);
+    await debuggerCommand(tabId, 'Network.setBypassServiceWorker', { bypass: true });
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to bypass service workers' };
+  }
+
+  const since = Date.now();
+  try {
+    if (message.params?.waitUntil) {
+      return await handleNavigateAndWait(message);
+    }
+    const response = await routeToTab(message);
+    if (response.success) {
+      await waitForLoadState(tabId, { state: 'load', since }).catch(() => {});
+    }
+    return response;
+  } finally {
+    serviceWorkerBypasses.delete(tabId);
+    await debuggerCommand(tabId, 'Network.setBypassServiceWorker', { bypass: false }).catch(() => {});
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.detach({ tabId }).catch(() => {});
+    }
+  }
+}
+
+// ============================================================================
+// Site Data
+// ============================================================================
+
+// browser_clear_site_data's types, all of which chrome.browsingData can limit to origins
+const SITE_DATA_TYPES = ['cache', 'cookies', 'localStorage', 'indexedDB', 'cacheStorage', 'serviceWorkers', 'fileSystems'] as const;
+
+/**
+ * Clear an origin's data with chrome.browsingData. Cookies are cleared for
+ * the origin's whole registrable domain, which is how Chrome scopes them.
+ */
+async function handleClearSiteData(message: Message): Promise<Response> {
+  const { origin, dataTypes, reload } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    let target: string = origin;
+    if (!target) {
+      const tab = await chrome.tabs.get(tabId);
+      if (!tab.url || !/^https?:/.test(tab.url)) {
+        throw new Error(`The current tab has no web origin (${tab.url ?? 'no URL'}); pass origin`);
+      }
+      target = new URL(tab.url).origin;
+    }
+
+    const types: string[] = Array.isArray(dataTypes) && dataTypes.length > 0 ? dataTypes : [...SITE_DATA_TYPES];
+    const dataToRemove: chrome.browsingData.DataTypeSet = {};
+    for (const type of types) {
+      (dataToRemove as Record<string, boolean>)[type] = true;
+    }
+    await chrome.browsingData.remove({ origins: [target], since: 0 }, dataToRemove);
+
+    let reloaded = false;
+    if (reload) {
+      const since = Date.now();
+      await chrome.tabs.reload(tabId, { bypassCache: true });
+      await waitForLoadState(tabId, { state: 'load', since }).catch(() => {});
+      reloaded = true;
+    }
+
+    return { id: message.id, success: true, result: { tabId, origin: target, cleared: types, reloaded } };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to clear site data' };
+  }
+}
+
+// ============================================================================
+// Page Info
+// ============================================================================
+
+/** Where the tab is: URL, title, ready state, scroll position and viewport, without a screenshot */
+async function handlePageInfo(message: Message): Promise<Response> {
+  try {
+    const tabId = await commandTabId(message.params);
+    const tab = await chrome.tabs.get(tabId);
+    const info = { tabId, url: tab.url, title: tab.title, status: tab.status };
+
+    const page = await chrome.scripting.executeScript({
+      target: { tabId },
+      func: () => {
+        const root = document.scrollingElement ?? document.documentElement;
+        return {
+          url: location.href,
+          title: document.title,
+          readyState: document.readyState,
+          scroll: {
+            x: window.scrollX,
+            y: window.scrollY,
+            maxX: Math.max(0, root.scrollWidth - window.innerWidth),
+            maxY: Math.max(0, root.scrollHeight - window.innerHeight),
+          },
+          viewport: { width: window.innerWidth, height: window.innerHeight, devicePixelRatio: window.devicePixelRatio },
+          document: { width: root.scrollWidth, height: root.scrollHeight },
+          focused: document.hasFocus(),
+        };
+      },
+    }).then(([injection]) => injection?.result ?? null, () => null);
+
+    // Pages scripts can't run in (chrome://, the web store) only have the tab's view
+    return {
+      id: message.id,
+      success: true,
+      result: page ? { ...info, ...page } : { ...info, readyState: null, restricted: true },
+    };
+  } catch (error: any) {
+    return { id: message.id, success: false, error: error.message || 'Failed to read page info' };
+  }
+}
+
+// ============================================================================
+// Window Focus
+// ============================================================================
+
+// How long to wait for the page to see itself become visible
+const FOREGROUND_SETTLE_MS = 1000;
+const FOREGROUND_POLL_MS = 100;
+
+/** Whether the tab is active in an unminimized window, and what the page itself reports */
+async function foregroundState(tabId: number): Promise<Record<string, any>> {
+  const tab = await chrome.tabs.get(tabId);
+  const tabWindow = await chrome.windows.get(tab.windowId);
+  const page = await chrome.scripting.executeScript({
+    target: { tabId },
+    func: () => ({ visibilityState: document.visibilityState, hasFocus: document.hasFocus() }),
+  }).then(([injection]) => injection?.result ?? null, () => null);
+
+  return {
+    tabId,
+    windowId: tab.windowId,
+    tabActive: tab.active,
+    windowFocused: tabWindow.focused,
+    windowState: tabWindow.state,
+    // null where scripts can't run (chrome://, the web store)
+    visibilityState: page?.visibilityState ?? null,
+    hasFocus: page?.hasFocus ?? null,
+    foreground: tab.active && tabWindow.state !== 'minimized' && (page === null || page.visibilityState === 'visible'),
+  };
+}
+
+/** Activate the tab, restore and focus its window, and wait for the page to turn visible */
+async function handleBringToFront(message: Message): Promise<Response> {
+  const { focusWindow = true, checkOnly = false } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    const before = await foregroundState(tabId);
+    if (checkOnly) {
+      return { id: message.id, success: true, result: before };
+    }
+
+    if (before.windowState === 'minimized') {
+      await chrome.windows.update(before.windowId, { state: 'normal' });
+    }
+    await chrome.tabs.update(tabId, { active: true });
+    if (focusWindow) {
+      await chrome.windows.update(before.windowId, { focused: true });
+    }
+
+    // visibilitychange reaches the page a little after the tab switch
+    let after = await foregroundState(tabId);
+    for (let waited = 0; !after.foreground && waited < FOREGROUND_SETTLE_MS; waited += FOREGROUND_POLL_MS) {
+      await new Promise((resolve) => setTimeout(resolve, FOREGROUND_POLL_MS));
+      after = await foregroundState(tabId);
+    }
+
+    return { id: message.id, success: true, result: { ...after, wasForeground: before.foreground } };
+  } catch (error: any) {
+    console.error('[Background] bring_to_front error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to bring tab to front' };
+  }
+}
+
+// ============================================================================
+// Page Assertions
+// ============================================================================
+
+// Longest text quoted back as an assertion's actual value
+const ASSERT_ACTUAL_MAX_CHARS = 200;
+
+interface PageAssertion {
+  type: 'exists' | 'visible' | 'text' | 'url' | 'cookie';
+  description: string;
+  selector?: string;
+  name?: string;
+  equals?: string;
+  contains?: string;
+  matches?: string;
+  ignoreCase?: boolean;
+  not?: boolean;
+}
+
+/** Whether a value satisfies the assertion's equals/contains/matches */
+function assertionMatches(assertion: PageAssertion, value: string): boolean {
+  if (assertion.matches !== undefined) {
+    return new RegExp(assertion.matches, assertion.ignoreCase ? 'i' : '').test(value);
+  }
+  const fold = (text: string) => (assertion.ignoreCase ? text.toLowerCase() : text);
+  if (assertion.equals !== undefined) {
+    return fold(value) === fold(assertion.equals);
+  }
+  return fold(value).includes(fold(assertion.contains ?? ''));
+}
+
+function quoteActual(value: string): string {
+  return value.length > ASSERT_ACTUAL_MAX_CHARS ? `${value.slice(0, ASSERT_ACTUAL_MAX_CHARS)}…` : value;
+}
+
+/** Evaluate every assertion against the tab with one page injection */
+async function handleAssertPage(message: Message): Promise<Response> {
+  const { tabId, assertions } = message.params || {};
+  try {
+    let targetTabId: number | undefined = tabId;
+    if (targetTabId === undefined) {
+      const [tab] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+      targetTabId = tab?.id;
+    }
+    if (targetTabId === undefined) {
+      throw new Error('No active tab to check');
+    }
+    const tab = await chrome.tabs.get(targetTabId);
+    const checks: PageAssertion[] = assertions ?? [];
+
+    // Element observations for exists/visible/text, indexed like checks
+    const elementChecks = checks.map((check) => (['exists', 'visible', 'text'].includes(check.type) ? check : null));
+    const [injection] = await chrome.scripting.executeScript({
+      target: { tabId: targetTabId },
+      args: [elementChecks.map((check) => (check ? { type: check.type, selector: check.selector ?? null } : null))],
+      func: (checks: ({ type: string; selector: string | null } | null)[]) =>
+        checks.map((check) => {
+          if (!check) {
+            return null;
+          }
+          try {
+            if (check.type === 'text' && !check.selector) {
+              return { count: 1, text: document.body?.innerText ?? '' };
+            }
+            const elements = document.querySelectorAll(check.selector!);
+            const element = elements[0] as HTMLElement | undefined;
+            if (!element) {
+              return { count: 0 };
+            }
+            if (check.type === 'text') {
+              return { count: elements.length, text: element.innerText ?? element.textContent ?? '' };
+            }
+            // Visible if any match renders a box that isn't hidden by style
+            const visible = Array.from(elements).some((el) => {
+              const style = getComputedStyle(el);
+              const rect = el.getBoundingClientRect();
+              return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none' && style.opacity !== '0';
+            });
+            return { count: elements.length, visible };
+          } catch (error: any) {
+            return { error: String(error?.message || error) };
+          }
+        }),
+    });
+    const observations = (injection?.result ?? []) as ({ count?: number; text?: string; visible?: boolean; error?: string } | null)[];
+
+    const results = [];
+    for (const [index, check] of checks.entries()) {
+      let holds: boolean;
+      let actual: string;
+      try {
+        if (check.type === 'url') {
+          actual = tab.url ?? '';
+          holds = assertionMatches(check, actual);
+        } else if (check.type === 'cookie') {
+          const [cookie] = await chrome.cookies.getAll({ url: tab.url, name: check.name });
+          // Cookie values may be credentials, so only presence is reported
+          actual = cookie ? 'present' : 'absent';
+          const hasMatcher = check.equals !== undefined || check.contains !== undefined || check.matches !== undefined;
+          holds = !!cookie && (!hasMatcher || assertionMatches(check, cookie.value));
+        } else {
+          const observed = observations[index];
+          if (!observed || observed.error) {
+            throw new Error(observed?.error || 'Page did not report this element');
+          }
+          if (check.type === 'exists') {
+            actual = observed.count ? `${observed.count} matching element${observed.count === 1 ? '' : 's'}` : 'no matching element';
+            holds = !!observed.count;
+          } else if (!observed.count) {
+            throw new Error(`No element matches ${check.selector}`);
+          } else if (check.type === 'visible') {
+            actual = observed.visible ? 'visible' : 'hidden';
+            holds = !!observed.visible;
+          } else {
+            const text = (observed.text ?? '').trim();
+            actual = quoteActual(text);
+            holds = assertionMatches(check, text);
+          }
+        }
+      } catch (error: any) {
+        // An assertion that couldn't be evaluated fails even when negated
+        results.push({ index, description: check.description, passed: false, error: error.message || String(error) });
+        continue;
+      }
+      results.push({ index, description: check.description, passed: check.not ? !holds : holds, actual });
+    }
+
+    const failed = results.filter((result) => !result.passed).length;
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        passed: failed === 0,
+        summary: `${results.length - failed}/${results.length} assertions passed`,
+        failed,
+        url: tab.url,
+        title: tab.title,
+        results,
+      },
+    };
+  } catch (error: any) {
+    console.error('[Background] assert_page error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to check assertions' };
+  }
+}
+
+// ============================================================================
+// Download Handlers
+// ============================================================================
+
+function describeDownload(item: chrome.downloads.DownloadItem) {
+  return {
+    id: item.id,
+    url: item.finalUrl || item.url,
+    filename: item.filename,
+    state: item.state,
+    mime: item.mime,
+    bytesReceived: item.bytesReceived,
+    totalBytes: item.totalBytes,
+    startTime: item.startTime,
+    endTime: item.endTime,
+    error: item.error,
+    exists: item.exists,
+  };
+}
+
+/** Downloads newest first, filtered by id, state, start time, URL or file path */
+async function handleDownloadsList(message: Message): Promise<Response> {
+  const params = (message.params || {}) as Record<string, any>;
+  try {
+    const query: chrome.downloads.DownloadQuery = { orderBy: ['-startTime'] };
+    if (typeof params.id === 'number') {
+      query.id = params.id;
+    }
+    if (params.state) {
+      query.state = params.state;
+    }
+    if (params.startedAfter) {
+      query.startedAfter = params.startedAfter;
+    }
+    // Substring filters run here, so search without a limit first
+    const items = (await chrome.downloads.search(query)).filter((item) =>
+      (!params.urlContains || (item.finalUrl || item.url).includes(params.urlContains)) &&
+      (!params.filenameContains || item.filename.includes(params.filenameContains)));
+    const downloads = items.slice(0, params.limit ?? 20).map(describeDownload);
+    return { id: message.id, success: true, result: { count: items.length, downloads } };
+  } catch (error: any) {
+    console.error('[Background] downloads_list error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to list downloads' };
+  }
+}
+
+// ============================================================================
+// DevTools Profiling
+// ============================================================================
+
+const DEBUGGER_PROTOCOL_VERSION = '1.3';
+const TRACE_COMPLETE_TIMEOUT_MS = 60000;
+const IO_READ_CHUNK_BYTES = 1 << 20;
+
+interface ProfilingSession {
+  js: boolean;
+  css: boolean;
+  trace: boolean;
+  startedAt: number;
+  // Script lengths and stylesheet headers seen while attached, for coverage totals
+  scripts: Map<string, { url: string; length?: number }>;
+  styleSheets: Map<string, { sourceURL: string; length: number }>;
+  onTraceComplete?: (stream: string) => void;
+}
+
+// Tabs the debugger is attached to for profiling_start
+const profilingSessions = new Map<number, ProfilingSession>();
+
+function debuggerCommand(tabId: number, method: string, params?: Record<string, any>): Promise<any> {
+  return chrome.debugger.sendCommand({ tabId }, method, params);
+}
+
+/**
+ * Whether profiling, a geolocation or locale override, throttling, dialog
+ * handling, a screencast or a service-worker-bypassing navigation holds the
+ * tab's debugger session
+ */
+function debuggerHeld(tabId: number): boolean {
+  return profilingSessions.has(tabId) || geolocationOverrides.has(tabId) || dialogPolicies.has(tabId) || screencasts.has(tabId)
+    || serviceWorkerBypasses.has(tabId) || localeOverrides.has(tabId) || throttles.has(tabId);
+}
+
+chrome.debugger.onEvent.addListener((source, method, params: any) => {
+  const session = source.tabId !== undefined ? profilingSessions.get(source.tabId) : undefined;
+  if (!session) {
+    return;
+  }
+  if (method === 'Debugger.scriptParsed') {
+    session.scripts.set(params.scriptId, { url: params.url, length: params.length });
+  } else if (method === 'CSS.styleSheetAdded') {
+    const { styleSheetId, sourceURL, length } = params.header;
+    session.styleSheets.set(styleSheetId, { sourceURL, length });
+  } else if (method === 'Tracing.tracingComplete') {
+    session.onTraceComplete?.(params.stream);
+  }
+});
+
+// Closing the tab, or the user cancelling the debugging banner, ends profiling,
+// any geolocation or locale override, throttling, dialog handling and screencast
+chrome.debugger.onDetach.addListener((source, reason) => {
+  if (source.tabId !== undefined && screencasts.delete(source.tabId)) {
+    // The server keeps the frames it has; browser_recording_stop still saves them
+    console.log(`[Background] Screencast of tab ${source.tabId} ended: ${reason}`);
+  }
+  if (source.tabId !== undefined && profilingSessions.delete(source.tabId)) {
+    console.log(`[Background] Profiling of tab ${source.tabId} ended: ${reason}`);
+  }
+  if (source.tabId !== undefined && geolocationOverrides.delete(source.tabId)) {
+    console.log(`[Background] Geolocation override of tab ${source.tabId} ended: ${reason}`);
+  }
+  if (source.tabId !== undefined && localeOverrides.delete(source.tabId)) {
+    console.log(`[Background] Locale override of tab ${source.tabId} ended: ${reason}`);
+  }
+  if (source.tabId !== undefined && throttles.delete(source.tabId)) {
+    console.log(`[Background] Throttling of tab ${source.tabId} ended: ${reason}`);
+  }
+  if (source.tabId !== undefined && dialogPolicies.delete(source.tabId)) {
+    console.log(`[Background] Dialog handling of tab ${source.tabId} ended: ${reason}`);
+    // The server would otherwise keep failing commands for a dialog nobody reports on
+    if (openDialogs.delete(source.tabId)) {
+      sendEvent('dialog', { phase: 'closed', tabId: source.tabId, handledBy: 'detached' });
+    }
+  }
+});
+
+async function profilingTabId(tabId: number | undefined): Promise<number> {
+  if (tabId !== undefined) {
+    return tabId;
+  }
+  const [tab] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+  if (tab?.id === undefined) {
+    throw new Error('No active tab to profile');
+  }
+  return tab.id;
+}
+
+/** Attach the debugger and start coverage and/or tracing */
+async function handleProfilingStart(message: Message): Promise<Response> {
+  const { tabId, js, css, trace, categories } = message.params || {};
+  let started: number | undefined;
+  try {
+    const targetTabId = await profilingTabId(tabId);
+    if (profilingSessions.has(targetTabId)) {
+      throw new Error(`Tab ${targetTabId} is already being profiled; call browser_profiling_stop first`);
+    }
+    // Another feature may already hold the tab's debugger session
+    const attached = debuggerHeld(targetTabId);
+    // Registered before attaching so the events replayed on enable are kept
+    profilingSessions.set(targetTabId, {
+      js: !!js,
+      css: !!css,
+      trace: !!trace,
+      startedAt: Date.now(),
+      scripts: new Map(),
+      styleSheets: new Map(),
+    });
+    started = targetTabId;
+    if (!attached) {
+      await chrome.debugger.attach({ tabId: targetTabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+
+    if (js) {
+      await debuggerCommand(targetTabId, 'Debugger.enable');
+      await debuggerCommand(targetTabId, 'Profiler.enable');
+      await debuggerCommand(targetTabId, 'Profiler.startPreciseCoverage', { callCount: true, detailed: true });
+    }
+    if (css) {
+      await debuggerCommand(targetTabId, 'DOM.enable');
+      await debuggerCommand(targetTabId, 'CSS.enable');
+      await debuggerCommand(targetTabId, 'CSS.startRuleUsageTracking');
+    }
+    if (trace) {
+      await debuggerCommand(targetTabId, 'Tracing.start', {
+        transferMode: 'ReturnAsStream',
+        traceConfig: { recordMode: 'recordAsMuchAsPossible', includedCategories: categories ?? [] },
+      });
+    }
+
+    const tab = await chrome.tabs.get(targetTabId);
+    return { id: message.id, success: true, result: { tabId: targetTabId, url: tab.url, js: !!js, css: !!css, trace: !!trace } };
+  } catch (error: any) {
+    console.error('[Background] profiling_start error:', error);
+    if (started !== undefined) {
+      profilingSessions.delete(started);
+      if (!debuggerHeld(started)) {
+        chrome.debugger.detach({ tabId: started }).catch(() => {});
+      }
+    }
+    return { id: message.id, success: false, error: error.message || 'Failed to start profiling' };
+  }
+}
+
+async function readDebuggerStream(tabId: number, handle: string): Promise<string> {
+  const chunks: string[] = [];
+  for (;;) {
+    const { data, eof, base64Encoded } = await debuggerCommand(tabId, 'IO.read', { handle, size: IO_READ_CHUNK_BYTES });
+    chunks.push(base64Encoded ? atob(data) : data);
+    if (eof) {
+      break;
+    }
+  }
+  await debuggerCommand(tabId, 'IO.close', { handle });
+  return chunks.join('');
+}
+
+/** Collect coverage and the trace, then detach */
+async function handleProfilingStop(message: Message): Promise<Response> {
+  const { tabId } = message.params || {};
+  try {
+    // With one recording in progress, stop it whichever tab is active now
+    const targetTabId = tabId ?? (profilingSessions.size === 1 ? [...profilingSessions.keys()][0] : await profilingTabId(undefined));
+    const session = profilingSessions.get(targetTabId);
+    if (!session) {
+      throw new Error(`Tab ${targetTabId} is not being profiled; call browser_profiling_start first`);
+    }
+
+    const tab = await chrome.tabs.get(targetTabId);
+    const result: Record<string, any> = { tabId: targetTabId, url: tab.url, durationMs: Date.now() - session.startedAt };
+    try {
+      if (session.js) {
+        const { result: scripts } = await debuggerCommand(targetTabId, 'Profiler.takePreciseCoverage');
+        await debuggerCommand(targetTabId, 'Profiler.stopPreciseCoverage');
+        result.jsCoverage = scripts.map((script: any) => {
+          const parsed = session.scripts.get(script.scriptId);
+          return { ...script, url: script.url || parsed?.url || '', length: parsed?.length };
+        });
+      }
+      if (session.css) {
+        const { ruleUsage } = await debuggerCommand(targetTabId, 'CSS.stopRuleUsageTracking');
+        const styleSheets = [...session.styleSheets].map(([styleSheetId, sheet]) => ({ styleSheetId, ...sheet }));
+        result.cssCoverage = { ruleUsage, styleSheets };
+      }
+      if (session.trace) {
+        const stream = await new Promise<string>((resolve, reject) => {
+          const timer = setTimeout(() => reject(new Error('Trace did not finish in time')), TRACE_COMPLETE_TIMEOUT_MS);
+          session.onTraceComplete = (stream) => {
+            clearTimeout(timer);
+            resolve(stream);
+          };
+          debuggerCommand(targetTabId, 'Tracing.end').catch((error) => {
+            clearTimeout(timer);
+            reject(error);
+          });
+        });
+        result.trace = await readDebuggerStream(targetTabId, stream);
+      }
+    } finally {
+      profilingSessions.delete(targetTabId);
+      if (!debuggerHeld(targetTabId)) {
+        await chrome.debugger.detach({ tabId: targetTabId }).catch(() => {});
+      }
+    }
+    return { id: message.id, success: true, result };
+  } catch (error: any) {
+    console.error('[Background] profiling_stop error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to stop profiling' };
+  }
+}
+
+// ============================================================================
+// PDF Export
+// ============================================================================
+
+/** Print the tab through Page.printToPDF, attaching the debugger only for the call */
+async function handlePrintToPdf(message: Message): Promise<Response> {
+  const { landscape = false, printBackground = true, scale = 1 } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    const attached = debuggerHeld(tabId);
+    if (!attached) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    try {
+      const { data } = await debuggerCommand(tabId, 'Page.printToPDF', { landscape, printBackground, scale });
+      return { id: message.id, success: true, result: { tabId, data, encoding: 'base64', format: 'pdf' } };
+    } finally {
+      if (!attached && !debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+    }
+  } catch (error: any) {
+    console.error('[Background] print_to_pdf error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to print to PDF' };
+  }
+}
+
+// ============================================================================
+// Geolocation Override
+// ============================================================================
+
+interface GeolocationOverride {
+  latitude: number;
+  longitude: number;
+  accuracy: number;
+}
+
+// Tabs the debugger stays attached to so their override keeps applying
+const geolocationOverrides = new Map<number, GeolocationOverride>();
+
+/**
+ * Spoof navigator.geolocation for the tab through the debugger, and allow
+ * the location permission for the origin so the page isn't left prompting
+ */
+async function handleSetGeolocation(message: Message): Promise<Response> {
+  const { reset, latitude, longitude, accuracy, origin, grantPermission } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+
+    if (reset) {
+      if (geolocationOverrides.delete(tabId)) {
+        await debuggerCommand(tabId, 'Emulation.clearGeolocationOverride').catch(() => {});
+        if (!debuggerHeld(tabId)) {
+          await chrome.debugger.detach({ tabId }).catch(() => {});
+        }
+      }
+      // Only settings this extension made are cleared
+      await chrome.contentSettings.location.clear({});
+      return { id: message.id, success: true, result: { tabId, reset: true } };
+    }
+
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    const override = { latitude, longitude, accuracy };
+    try {
+      await debuggerCommand(tabId, 'Emulation.setGeolocationOverride', override);
+    } catch (error) {
+      if (!debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+      throw error;
+    }
+    geolocationOverrides.set(tabId, override);
+
+    let grantedOrigin: string | null = null;
+    if (grantPermission) {
+      const tab = await chrome.tabs.get(tabId);
+      const pageOrigin = tab.url && /^https?:/.test(tab.url) ? new URL(tab.url).origin : null;
+      grantedOrigin = origin ?? pageOrigin;
+      if (grantedOrigin) {
+        await chrome.contentSettings.location.set({ primaryPattern: `${grantedOrigin}/*`, setting: 'allow' });
+      }
+    }
+
+    return {
+      id: message.id,
+      success: true,
+      result: { tabId, ...override, permissionGrantedTo: grantedOrigin },
+    };
+  } catch (error: any) {
+    console.error('[Background] set_geolocation error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to set geolocation' };
+  }
+}
+
+// ============================================================================
+// Locale Emulation
+// ============================================================================
+
+interface LocaleOverride {
+  locale: string | null;
+  timezone: string | null;
+  acceptLanguage: string | null;
+}
+
+// Tabs the debugger stays attached to so their locale keeps applying
+const localeOverrides = new Map<number, LocaleOverride>();
+
+/**
+ * Override the tab's ICU locale, timezone and Accept-Language through the
+ * debugger. Accept-Language also drives navigator.language(s); leaving a
+ * field unset keeps the one already in force.
+ */
+async function handleEmulateLocale(message: Message): Promise<Response> {
+  const { reset, locale, timezone, acceptLanguage } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+
+    if (reset) {
+      if (localeOverrides.delete(tabId)) {
+        if (debuggerHeld(tabId)) {
+          // Empty values clear each override without detaching from other features
+          await debuggerCommand(tabId, 'Emulation.setLocaleOverride', {}).catch(() => {});
+          await debuggerCommand(tabId, 'Emulation.setTimezoneOverride', { timezoneId: '' }).catch(() => {});
+          await debuggerCommand(tabId, 'Emulation.setUserAgentOverride', { userAgent: '' }).catch(() => {});
+        } else {
+          await chrome.debugger.detach({ tabId }).catch(() => {});
+        }
+      }
+      return { id: message.id, success: true, result: { tabId, reset: true } };
+    }
+
+    const previous = localeOverrides.get(tabId);
+    const override: LocaleOverride = {
+      locale: locale ?? previous?.locale ?? null,
+      timezone: timezone ?? previous?.timezone ?? null,
+      acceptLanguage: acceptLanguage ?? previous?.acceptLanguage ?? null,
+    };
+
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    try {
+      if (locale) {
+        await debuggerCommand(tabId, 'Emulation.setLocaleOverride', { locale });
+      }
+      if (timezone) {
+        await debuggerCommand(tabId, 'Emulation.setTimezoneOverride', { timezoneId: timezone });
+      }
+      if (acceptLanguage) {
+        await debuggerCommand(tabId, 'Emulation.setUserAgentOverride', { userAgent: navigator.userAgent, acceptLanguage });
+      }
+    } catch (error) {
+      if (!debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+      throw error;
+    }
+    localeOverrides.set(tabId, override);
+
+    return { id: message.id, success: true, result: { tabId, ...override } };
+  } catch (error: any) {
+    console.error('[Background] emulate_locale error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to emulate locale' };
+  }
+}
+
+// ============================================================================
+// Network and CPU Throttling
+// ============================================================================
+
+interface NetworkConditions {
+  offline: boolean;
+  latencyMs: number;
+  /** Bytes per second, -1 for unthrottled */
+  downloadThroughput: number;
+  uploadThroughput: number;
+}
+
+interface ThrottleState {
+  network: NetworkConditions | null;
+  cpuRate: number;
+}
+
+// Tabs the debugger stays attached to so their throttling keeps applying
+const throttles = new Map<number, ThrottleState>();
+
+function unthrottled(state: ThrottleState): boolean {
+  const network = state.network;
+  const networkOff = !network || (!network.offline && network.latencyMs === 0
+    && network.downloadThroughput < 0 && network.uploadThroughput < 0);
+  return networkOff && state.cpuRate === 1;
+}
+
+/**
+ * Throttle the tab's network through Network.emulateNetworkConditions and
+ * its main thread through Emulation.setCPUThrottlingRate. Whichever of the
+ * two is left out keeps its current setting; once both are back to normal
+ * the debugger is released.
+ */
+async function handleThrottle(message: Message): Promise<Response> {
+  const { network, cpuRate } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    const previous = throttles.get(tabId);
+    const state: ThrottleState = {
+      network: network ?? previous?.network ?? null,
+      cpuRate: cpuRate ?? previous?.cpuRate ?? 1,
+    };
+
+    if (!previous && unthrottled(state)) {
+      return { id: message.id, success: true, result: { tabId, ...state, throttled: false } };
+    }
+
+    const attached = debuggerHeld(tabId);
+    if (!attached) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    try {
+      if (network) {
+        await debuggerCommand(tabId, 'Network.enable');
+        await debuggerCommand(tabId, 'Network.emulateNetworkConditions', {
+          offline: network.offline,
+          latency: network.latencyMs,
+          downloadThroughput: network.downloadThroughput,
+          uploadThroughput: network.uploadThroughput,
+        });
+      }
+      if (cpuRate !== undefined && cpuRate !== null) {
+        await debuggerCommand(tabId, 'Emulation.setCPUThrottlingRate', { rate: cpuRate });
+      }
+    } catch (error) {
+      if (!attached) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+      throw error;
+    }
+
+    if (unthrottled(state)) {
+      throttles.delete(tabId);
+      if (!debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+    } else {
+      throttles.set(tabId, state);
+    }
+    return { id: message.id, success: true, result: { tabId, ...state, throttled: !unthrottled(state) } };
+  } catch (error: any) {
+    console.error('[Background] throttle error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to throttle' };
+  }
+}
+
+// ============================================================================
+// JavaScript Dialogs
+// ============================================================================
+
+interface DialogPolicy {
+  mode: 'accept' | 'dismiss' | 'manual';
+  promptText?: string;
+}
+
+// Tabs the debugger stays attached to so their dialogs are reported
+const dialogPolicies = new Map<number, DialogPolicy>();
+// Dialogs left open for the agent, with who answered them once someone has
+const openDialogs = new Map<number, { type: string; message: string; handledBy?: 'agent' | 'policy' }>();
+
+chrome.debugger.onEvent.addListener(async (source, method, params: any) => {
+  const tabId = source.tabId;
+  const policy = tabId !== undefined ? dialogPolicies.get(tabId) : undefined;
+  if (tabId === undefined || !policy) {
+    return;
+  }
+
+  if (method === 'Page.javascriptDialogOpening') {
+    const tab = await chrome.tabs.get(tabId).catch(() => undefined);
+    const dialog = {
+      phase: 'opened',
+      tabId,
+      type: params.type,
+      message: params.message,
+      defaultPrompt: params.defaultPrompt,
+      url: params.url,
+      active: tab?.active ?? false,
+    };
+    if (policy.mode !== 'manual') {
+      const accept = policy.mode === 'accept';
+      const promptText = accept && params.type === 'prompt' ? policy.promptText ?? params.defaultPrompt : undefined;
+      try {
+        await debuggerCommand(tabId, 'Page.handleJavaScriptDialog', { accept, promptText });
+        sendEvent('dialog', { ...dialog, handledBy: 'policy', accepted: accept, promptText });
+        return;
+      } catch (error) {
+        // Fall through and leave it to the agent rather than block unreported
+        console.error('[Background] Failed to answer dialog:', error);
+      }
+    }
+    openDialogs.set(tabId, { type: params.type, message: params.message });
+    sendEvent('dialog', dialog);
+  } else if (method === 'Page.javascriptDialogClosed') {
+    // Dialogs the policy answered were reported closed when they opened
+    const open = openDialogs.get(tabId);
+    if (open) {
+      openDialogs.delete(tabId);
+      sendEvent('dialog', {
+        phase: 'closed',
+        tabId,
+        accepted: params.result,
+        promptText: params.userInput,
+        handledBy: open.handledBy ?? 'user',
+      });
+    }
+  }
+});
+
+/**
+ * Watch the tab's alert/confirm/prompt/beforeunload dialogs through the
+ * debugger and answer them per the policy, or report them for the agent
+ */
+async function handleDialogPolicy(message: Message): Promise<Response> {
+  const { mode, promptText } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+
+    if (mode === 'off') {
+      const open = openDialogs.get(tabId);
+      if (open) {
+        // Don't leave the page blocked on a dialog nobody is watching any more
+        open.handledBy = 'agent';
+        await debuggerCommand(tabId, 'Page.handleJavaScriptDialog', { accept: false }).catch(() => {});
+        openDialogs.delete(tabId);
+        sendEvent('dialog', { phase: 'closed', tabId, accepted: false, handledBy: 'agent' });
+      }
+      if (dialogPolicies.delete(tabId) && !debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+      return { id: message.id, success: true, result: { tabId, mode } };
+    }
+
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    try {
+      await debuggerCommand(tabId, 'Page.enable');
+    } catch (error) {
+      if (!debuggerHeld(tabId)) {
+        await chrome.debugger.detach({ tabId }).catch(() => {});
+      }
+      throw error;
+    }
+    dialogPolicies.set(tabId, { mode, promptText });
+
+    // A dialog left for the agent is answered now if the new policy does so
+    const open = openDialogs.get(tabId);
+    if (open && !open.handledBy && mode !== 'manual') {
+      const accept = mode === 'accept';
+      open.handledBy = 'policy';
+      await debuggerCommand(tabId, 'Page.handleJavaScriptDialog', {
+        accept,
+        promptText: accept && open.type === 'prompt' ? promptText : undefined,
+      });
+    }
+
+    return {
+      id: message.id,
+      success: true,
+      result: { tabId, mode, promptText: promptText ?? null, dialogOpen: openDialogs.has(tabId) },
+    };
+  } catch (error: any) {
+    console.error('[Background] dialog_policy error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to set dialog policy' };
+  }
+}
+
+/** Answer the dialog open on the tab */
+async function handleDialogRespond(message: Message): Promise<Response> {
+  const { accept = true, promptText } = message.params || {};
+  try {
+    const tabId = await commandTabId(message.params);
+    const open = openDialogs.get(tabId);
+    if (!open) {
+      throw new Error(dialogPolicies.has(tabId)
+        ? `No JavaScript dialog is open on tab ${tabId}`
+        : `Dialogs on tab ${tabId} are not being watched; call browser_handle_dialogs first`);
+    }
+
+    open.handledBy = 'agent';
+    try {
+      await debuggerCommand(tabId, 'Page.handleJavaScriptDialog', { accept, promptText });
+    } catch (error) {
+      open.handledBy = undefined;
+      throw error;
+    }
+
+    return {
+      id: message.id,
+      success: true,
+      result: {
+        tabId,
+        type: open.type,
+        message: open.message,
+        accepted: accept,
+        promptText: accept && open.type === 'prompt' ? promptText ?? null : null,
+      },
+    };
+  } catch (error: any) {
+    console.error('[Background] dialog_respond error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to answer dialog' };
+  }
+}
+
+// ============================================================================
+// Screencast Recording
+// ============================================================================
+
+interface Screencast {
+  startedAt: number;
+  frames: number;
+}
+
+// Tabs streaming screencast frames to the server
+const screencasts = new Map<number, Screencast>();
+
+// Chrome sends the next frame only once the previous one is acknowledged
+chrome.debugger.onEvent.addListener((source, method, params: any) => {
+  const tabId = source.tabId;
+  const screencast = tabId !== undefined ? screencasts.get(tabId) : undefined;
+  if (tabId === undefined || !screencast || method !== 'Page.screencastFrame') {
+    return;
+  }
+  screencast.frames++;
+  debuggerCommand(tabId, 'Page.screencastFrameAck', { sessionId: params.sessionId }).catch(() => {});
+  sendEvent('screencast_frame', {
+    tabId,
+    data: params.data,
+    // Seconds since the epoch, when the frame was painted
+    timestamp: params.metadata?.timestamp ?? Date.now() / 1000,
+  });
+});
+
+/** Attach the debugger and stream JPEG frames of the tab as `screencast_frame` events */
+async function handleScreencastStart(message: Message): Promise<Response> {
+  const { maxWidth, maxHeight, quality } = message.params || {};
+  let started: number | undefined;
+  try {
+    const tabId = await commandTabId(message.params);
+    if (screencasts.has(tabId)) {
+      throw new Error(`Tab ${tabId} is already being recorded; call browser_recording_stop first`);
+    }
+    const attached = debuggerHeld(tabId);
+    screencasts.set(tabId, { startedAt: Date.now(), frames: 0 });
+    started = tabId;
+    if (!attached) {
+      await chrome.debugger.attach({ tabId }, DEBUGGER_PROTOCOL_VERSION);
+    }
+    await debuggerCommand(tabId, 'Page.startScreencast', { format: 'jpeg', quality, maxWidth, maxHeight, everyNthFrame: 1 });
+
+    const tab = await chrome.tabs.get(tabId);
+    return { id: message.id, success: true, result: { tabId, url: tab.url, visible: tab.active } };
+  } catch (error: any) {
+    console.error('[Background] screencast_start error:', error);
+    if (started !== undefined) {
+      screencasts.delete(started);
+      if (!debuggerHeld(started)) {
+        chrome.debugger.detach({ tabId: started }).catch(() => {});
+      }
+    }
+    return { id: message.id, success: false, error: error.message || 'Failed to start recording' };
+  }
+}
+
+/** Stop the tab's screencast, detaching unless something else holds the debugger */
+async function handleScreencastStop(message: Message): Promise<Response> {
+  try {
+    const tabId = await commandTabId(message.params);
+    const screencast = screencasts.get(tabId);
+    if (!screencast) {
+      throw new Error(`Tab ${tabId} is not being recorded`);
+    }
+    screencasts.delete(tabId);
+    await debuggerCommand(tabId, 'Page.stopScreencast').catch(() => {});
+    if (!debuggerHeld(tabId)) {
+      await chrome.debugger.detach({ tabId }).catch(() => {});
+    }
+    return {
+      id: message.id,
+      success: true,
+      result: { tabId, frames: screencast.frames, durationMs: Date.now() - screencast.startedAt },
+    };
+  } catch (error: any) {
+    console.error('[Background] screencast_stop error:', error);
+    return { id: message.id, success: false, error: error.message || 'Failed to stop recording' };
+  }
+}
+
+// ============================================================================
+// Native Messaging Host - Ensure Server Running
+// ============================================================================
+
+async function ensureServerRunning() {
+  console.log('[Background] Calling NMH to ensure server is running...');
+  setBadgeState({ serverStatus: 'starting' });
+
+  try {
+    const response = await chrome.runtime.sendNativeMessage(
+      'com.agentbrowser.native',
+      { cmd: 'ensure_server' }
+    );
+
+    console.log('[Background] NMH response:', response);
+
+    if (typeof response.wsPort === 'number') {
+      serverUrl = `ws://localhost:${response.wsPort}`;
+    }
+
+    if (response.ok) {
+      console.log('[Background] Server is running at', serverUrl);
+      if (response.logs) {
+        console.log('[Background] Server logs:', response.logs);
+      }
+      // Give server a moment to fully start, then connect WebSocket
+      setTimeout(() => connect(), 1000);
+    } else {
+      console.error('[Background] NMH reported error:', response.error);
+      setBadgeState({
+        serverStatus: 'error',
+        errorMessage: response.error || 'NMH reported error',
+      });
+      // Fall back to trying to connect anyway (maybe server is running)
+      setTimeout(() => connect(), 2000);
+    }
+  } catch (error: any) {
+    console.error('[Background] Failed to call NMH:', error);
+    console.log('[Background] NMH may not be installed. Trying to connect to server anyway...');
+    setBadgeState({
+      serverStatus: 'error',
+      errorMessage: 'NMH not responding',
+    });
+    // Fall back to connecting (maybe server is running manually)
+    setTimeout(() => connect(), 2000);
   }
 }
 
@@ -1233,6 +3925,25 @@ chrome.runtime.onMessage.addListener((message, sender, sendResponse) => {
   }
 
   // Handle messages from content scripts
+  if (message.type === 'llm_query_request') {
+    queryClientModel(message).then(sendResponse);
+    return true; // Keep channel open for async response
+  }
+
+  if (message.type === 'network_capture') {
+    if (sender.tab?.id !== undefined && (message.event === 'websocket' || message.event === 'webrtc')) {
+      sendEvent(message.event, { ...message.data, tabId: sender.tab.id });
+    } else if (sender.tab?.id !== undefined && message.event === 'http_body' && networkCaptureConfig.captureBodies) {
+      receiveResponseBody(sender.tab.id, message.data);
+    }
+    return;
+  }
+
+  if (message.type === 'network_capture_config_get') {
+    sendResponse(networkCaptureConfig);
+    return;
+  }
+
   if (message.type === 'magic_link_detected') {
     handleMagicLinkDetection(message).catch(error => {
       console.error('[Background] Error handling magic link:', error);
@@ -1343,10 +4054,96 @@ checkEmailProviderStatus();
 // Set initial badge state
 updateBadge();
 
+/**
+ * Ask the server whether the driving session may use this credential.
+ * Resolves to the refusal reason, or null when approved.
+ */
+function requestPasskeyApproval(details: CredentialUseRequest): Promise<string | null> {
+  if (!ws || ws.readyState !== WebSocket.OPEN) {
+    return Promise.resolve('Not connected to the agent-browser server');
+  }
+  return new Promise((resolve) => {
+    const timer = setTimeout(() => decide('No passkey decision from the server'), PASSKEY_DECISION_TIMEOUT_MS);
+    const decide = (error: string | null) => {
+      clearTimeout(timer);
+      passkeyDecisions.delete(details.requestId);
+      resolve(error);
+    };
+    passkeyDecisions.set(details.requestId, decide);
+    requestingTabs(details).then((tabs) => {
+      sendEvent('credential_request', {
+        request_id: details.requestId,
+        rp_id: details.rpId,
+        operation: details.operation,
+        origin: details.origin,
+        tabs,
+      });
+    });
+  });
+}
+
+/**
+ * Tabs the WebAuthn request may come from: webAuthenticationProxy doesn't
+ * say, so these are the tabs showing its origin (or, without one, a host
+ * under the rpId). The server approves only if one session owns them all.
+ */
+async function requestingTabs(details: CredentialUseRequest): Promise<Array<{ tabId: number; active: boolean }>> {
+  const tabs = await chrome.tabs.query({}).catch(() => [] as chrome.tabs.Tab[]);
+  return tabs
+    .filter((tab) => {
+      if (tab.id === undefined || !tab.url || !/^https?:/.test(tab.url)) {
+        return false;
+      }
+      const url = new URL(tab.url);
+      return details.origin
+        ? url.origin === details.origin
+        : url.hostname === details.rpId || url.hostname.endsWith(`.${details.rpId}`);
+    })
+    .map((tab) => ({ tabId: tab.id!, active: tab.active }));
+}
+
+/**
+ * Ask the MCP client's model through the server's sampling support: an
+ * llm_query event answered by an llm_query_result command
+ */
+function queryClientModel(request: { id?: string; prompt?: string; systemPrompt?: string; maxTokens?: number }): Promise<LlmQueryReply> {
+  if (typeof request.prompt !== 'string' || request.prompt.length === 0) {
+    return Promise.resolve({ success: false, error: 'Missing prompt' });
+  }
+  if (!ws || ws.readyState !== WebSocket.OPEN) {
+    return Promise.resolve({ success: false, error: 'Not connected to the agent-browser server' });
+  }
+  const id = request.id ?? `llm_${Date.now()}_${Math.random().toString(36).slice(2, 11)}`;
+  return new Promise((resolve) => {
+    const timer = setTimeout(() => answer({ success: false, error: 'No answer from the MCP client' }), LLM_QUERY_TIMEOUT_MS);
+    const answer = (reply: LlmQueryReply) => {
+      clearTimeout(timer);
+      llmQueries.delete(id);
+      resolve(reply);
+    };
+    llmQueries.set(id, answer);
+    sendEvent('llm_query', {
+      id,
+      prompt: request.prompt,
+      systemPrompt: request.systemPrompt,
+      maxTokens: request.maxTokens,
+    });
+  });
+}
+
 // Initialize WebAuthn proxy
 async function initializeWebAuthnProxy() {
   try {
     webAuthnProxy = new WebAuthnProxy();
+    webAuthnProxy.approveUse = requestPasskeyApproval;
+    webAuthnProxy.onCredentialUsed = (details) => {
+      sendEvent('credential_used', {
+        credential_id: details.credentialId,
+        rp_id: details.rpId,
+        operation: details.operation,
+        origin: details.origin,
+      });
+    };
     await webAuthnProxy.initialize();
     console.log('[Background] WebAuthn proxy initialized');
   } catch (error) {
@@ -1389,6 +4186,49 @@ chrome.action.onClicked.addListener(async () => {
   }
 });
 
+// ============================================================================
+// Tab Lifecycle Events
+// ============================================================================
+
+// Tabs already reported to the server with the tab that opened them
+const tabsWithOpener = new Set<number>();
+
+/** Report a new tab as a tab_opened event; sessions in follow mode switch to popups */
+function reportTabOpened(tabId: number, openerTabId: number | undefined, url: string | undefined) {
+  if (openerTabId !== undefined) {
+    if (tabsWithOpener.has(tabId)) {
+      return;
+    }
+    tabsWithOpener.add(tabId);
+  }
+  sendEvent('tab_opened', { tabId, openerTabId: openerTabId ?? null, url: url || null });
+}
+
+chrome.tabs.onCreated.addListener((tab) => {
+  if (tab.id !== undefined) {
+    reportTabOpened(tab.id, tab.openerTabId, tab.pendingUrl ?? tab.url);
+  }
+});
+
+// window.open() popups in their own window may lack openerTabId; the navigation knows its source
+chrome.webNavigation.onCreatedNavigationTarget.addListener((details) => {
+  reportTabOpened(details.tabId, details.sourceTabId, details.url);
+});
+
+// A committed top-frame navigation replaces the document element commands were aimed at
+chrome.webNavigation.onCommitted.addListener(async (details) => {
+  if (details.frameId !== 0) {
+    return;
+  }
+  const [active] = await chrome.tabs.query({ active: true, lastFocusedWindow: true });
+  sendEvent('navigation', { tabId: details.tabId, url: details.url, active: active?.id === details.tabId });
+});
+
+chrome.tabs.onRemoved.addListener((tabId, removeInfo) => {
+  tabsWithOpener.delete(tabId);
+  sendEvent('tab_closed', { tabId, windowId: removeInfo.windowId, windowClosing: removeInfo.isWindowClosing });
+});
+
 // Handle tab updates (re-inject if needed)
 chrome.tabs.onUpdated.addListener(async (tabId, changeInfo, tab) => {
   if (changeInfo.status === 'complete' && tab.url && !tab.url.startsWith('chrome://') && !tab.url.startsWith('chrome-extension://')) {
@@ -2,17 +2,43 @@
  * Content script - Command execution in web pages
  *
  * - Listen for commands from background
- * - Execute: navigate, click, type, wait
+ * - Execute: navigate, click, type, type_text, select, fill_form, scroll_into_view, press_key, wait, get_text, get_html, extract_content, find_text, collect_items, extract_table, list_forms, dismiss_consent, focus, blur, get_focused_element, hover, wait_for_selector, query_elements, find_similar_elements, set_date, canvas_read_pixels, media_control, media_get_state, media_capture_frame
  * - Return result
  * - Monitor for magic link authentication flows
+ * - Relay WebSocket and WebRTC reports from network-hooks
  */
 
 import { clickCommand } from '../lib/automation/click';
 import { typeCommand } from '../lib/automation/type';
-import { waitCommand } from '../lib/automation/wait';
+import { typeTextCommand } from '../lib/automation/type-text';
+import { selectCommand } from '../lib/automation/select';
+import { fillFormCommand } from '../lib/automation/form';
+import { getTextCommand } from '../lib/automation/text';
+import { getHtmlCommand } from '../lib/automation/html';
+import { extractContentCommand } from '../lib/automation/readability';
+import { findTextCommand } from '../lib/automation/find';
+import { collectItemsCommand } from '../lib/automation/collect';
+import { extractTableCommand } from '../lib/automation/table';
+import { listFormsCommand } from '../lib/automation/forms';
+import { pressKeyCommand } from '../lib/automation/keyboard';
+import { auditAccessibilityCommand } from '../lib/automation/accessibility';
+import { accessibilitySnapshotCommand } from '../lib/automation/a11y-tree';
+import { auditSeoCommand } from '../lib/automation/seo';
+import { listLinksCommand } from '../lib/automation/links';
+import { waitCommand, waitForSelectorCommand } from '../lib/automation/wait';
+import { queryElementsCommand } from '../lib/automation/query';
+import { findSimilarElementsCommand } from '../lib/automation/similar';
+import { hoverCommand } from '../lib/automation/hover';
+import { setDateCommand } from '../lib/automation/date';
+import { canvasReadPixelsCommand } from '../lib/automation/canvas';
+import { mediaCaptureFrameCommand, mediaControlCommand, mediaGetStateCommand } from '../lib/automation/media';
+import { blurCommand, focusCommand, getFocusedElementCommand } from '../lib/automation/focus';
+import { describeElement, scrollIntoViewCommand } from '../lib/automation/scroll';
+import { applyLocator, describeLocator } from '../lib/automation/locator';
 import { getModeConfig } from '../lib/automation/mode-config';
 import { detectModal, detectAllModals } from '../lib/automation/modal-detector';
 import { dismissModal } from '../lib/automation/modal-dismiss';
+import { dismissConsentCommand } from '../lib/automation/consent';
 import type { Command, CommandAction } from '../lib/automation/types';
 import { getMagicLinkDetector } from '../lib/automation/magic-link-detector';
 import type { MagicLinkDetection } from '../lib/automation/magic-link-detector';
@@ -61,6 +87,24 @@ chrome.runtime.onMessage.addListener((message, sender, sendResponse) => {
 async function handleCommand(command: Command): Promise<any> {
   console.log('[Content] Executing command:', command);
 
+  if (!command.params?.locator) {
+    return await runCommand(command);
+  }
+
+  // Address the located element through a temporary attribute selector
+  const located = applyLocator(command.params);
+  try {
+    const result = await runCommand(command);
+    if (result && typeof result === 'object' && result.selector === located.selector) {
+      result.selector = located.path;
+    }
+    return { ...result, locator: describeLocator(command.params.locator) };
+  } finally {
+    located.release();
+  }
+}
+
+async function runCommand(command: Command): Promise<any> {
   const config = getModeConfig(command.mode);
   const action = command.method as CommandAction;
 
@@ -74,6 +118,18 @@ async function handleCommand(command: Command): Promise<any> {
     case 'type':
       return await typeCommand(command, config);
 
+    case 'type_text':
+      return await typeTextCommand(command, config);
+
+    case 'select':
+      return await selectCommand(command, config);
+
+    case 'fill_form':
+      return await fillFormCommand(command, config);
+
+    case 'scroll_into_view':
+      return await scrollIntoViewCommand(command, config);
+
     case 'wait':
       return await waitCommand(command, config);
 
@@ -83,12 +139,87 @@ async function handleCommand(command: Command): Promise<any> {
     case 'get_element':
       return await handleGetElement(command);
 
+    case 'get_text':
+      return await getTextCommand(command, config);
+
+    case 'press_key':
+      return await pressKeyCommand(command, config);
+
+    case 'get_html':
+      return await getHtmlCommand(command, config);
+
+    case 'extract_content':
+      return await extractContentCommand(command, config);
+
+    case 'find_text':
+      return await findTextCommand(command, config);
+
+    case 'collect_items':
+      return await collectItemsCommand(command, config);
+
+    case 'extract_table':
+      return await extractTableCommand(command, config);
+
+    case 'list_forms':
+      return await listFormsCommand(command, config);
+
+    case 'audit_accessibility':
+      return await auditAccessibilityCommand(command, config);
+
+    case 'accessibility_snapshot':
+      return await accessibilitySnapshotCommand(command, config);
+
+    case 'audit_seo':
+      return await auditSeoCommand(command, config);
+
+    case 'list_links':
+      return await listLinksCommand(command, config);
+
     case 'detect_modal':
       return await handleDetectModal(command);
 
     case 'dismiss_modal':
       return await handleDismissModal(command, config);
 
+    case 'dismiss_consent':
+      return await dismissConsentCommand(command, config);
+
+    case 'find_similar_elements':
+      return await findSimilarElementsCommand(command, config);
+
+    case 'focus':
+      return await focusCommand(command, config);
+
+    case 'blur':
+      return await blurCommand(command, config);
+
+    case 'get_focused_element':
+      return await getFocusedElementCommand(command, config);
+
+    case 'hover':
+      return await hoverCommand(command, config);
+
+    case 'wait_for_selector':
+      return await waitForSelectorCommand(command, config);
+
+    case 'query_elements':
+      return await queryElementsCommand(command, config);
+
+    case 'set_date':
+      return await setDateCommand(command, config);
+
+    case 'canvas_read_pixels':
+      return await canvasReadPixelsCommand(command, config);
+
+    case 'media_control':
+      return await mediaControlCommand(command, config);
+
+    case 'media_get_state':
+      return await mediaGetStateCommand(command, config);
+
+    case 'media_capture_frame':
+      return await mediaCaptureFrameCommand(command, config);
+
     default:
       throw new Error(`Unknown command: ${action}`);
   }
@@ -126,9 +257,12 @@ async function handleGetElement(command: Command): Promise<any> {
   const { selector, xpath } = command.params;
 
   let element: Element | null = null;
+  let count = 0;
 
   if (selector) {
-    element = document.querySelector(selector);
+    const matches = document.querySelectorAll(selector);
+    element = matches[0] ?? null;
+    count = matches.length;
   } else if (xpath) {
     const result = document.evaluate(
       xpath,
@@ -138,6 +272,7 @@ async function handleGetElement(command: Command): Promise<any> {
       null
     );
     element = result.singleNodeValue as Element | null;
+    count = element ? 1 : 0;
   }
 
   if (!element) {
@@ -149,25 +284,74 @@ async function handleGetElement(command: Command): Promise<any> {
 
   const rect = element.getBoundingClientRect();
   const style = window.getComputedStyle(element);
+  const viewport = { width: window.innerWidth, height: window.innerHeight };
+
+  const visible =
+    rect.width > 0 &&
+    rect.height > 0 &&
+    style.visibility !== 'hidden' &&
+    style.display !== 'none' &&
+    style.opacity !== '0';
+  const hiddenBy = !visible
+    ? style.display === 'none' ? 'display: none'
+      : style.visibility === 'hidden' ? 'visibility: hidden'
+      : style.opacity === '0' ? 'opacity: 0'
+      : 'zero size'
+    : null;
+
+  // Visible area clipped to the viewport
+  const clipped = {
+    width: Math.max(0, Math.min(rect.right, viewport.width) - Math.max(rect.left, 0)),
+    height: Math.max(0, Math.min(rect.bottom, viewport.height) - Math.max(rect.top, 0)),
+  };
+  const area = rect.width * rect.height;
+  const inViewportRatio = area > 0 ? (clipped.width * clipped.height) / area : 0;
+
+  // :disabled covers disabled fieldsets and optgroups; aria-disabled covers custom widgets
+  const enabled = !element.matches(':disabled') && element.closest('[aria-disabled="true"]') === null;
+
+  // A click at the element's center lands on whatever is topmost there
+  let clickable = false;
+  let obscuredBy: string | null = null;
+  if (visible && inViewportRatio > 0) {
+    const x = Math.max(rect.left, 0) + clipped.width / 2;
+    const y = Math.max(rect.top, 0) + clipped.height / 2;
+    const topmost = document.elementFromPoint(x, y);
+    clickable = topmost !== null && (element === topmost || element.contains(topmost));
+    if (topmost && !clickable) {
+      obscuredBy = describeElement(topmost);
+    }
+  }
 
   return {
     found: true,
     selector: selector || xpath,
+    count,
+    tagName: element.tagName.toLowerCase(),
+    id: element.id,
+    className: element.className,
     bounds: {
       x: rect.x,
       y: rect.y,
       width: rect.width,
       height: rect.height,
     },
-    visible:
-      rect.width > 0 &&
-      rect.height > 0 &&
-      style.visibility !== 'hidden' &&
-      style.display !== 'none' &&
-      style.opacity !== '0',
-    tagName: element.tagName.toLowerCase(),
-    id: element.id,
-    className: element.className,
+    // Document coordinates, stable across scrolling
+    pageBounds: {
+      x: rect.x + window.scrollX,
+      y: rect.y + window.scrollY,
+      width: rect.width,
+      height: rect.height,
+    },
+    visible,
+    hiddenBy,
+    enabled,
+    inViewport: inViewportRatio > 0,
+    fullyInViewport: area > 0 && inViewportRatio >= 0.999,
+    inViewportRatio: Math.round(inViewportRatio * 1000) / 1000,
+    clickable,
+    obscuredBy,
+    viewport: { ...viewport, scrollX: window.scrollX, scrollY: window.scrollY, devicePixelRatio: window.devicePixelRatio },
   };
 }
 
@@ -300,6 +484,33 @@ try {
   console.error('[Content] Failed to initialize auto-modal handler:', error);
 }
 
+// ============================================================================
+// Network Capture Relay
+// ============================================================================
+
+// network-hooks.ts reports WebSocket and WebRTC activity, and response bodies, from the page's world
+window.addEventListener('message', (event) => {
+  const report = event.source === window ? event.data?.__agentBrowserNetwork : undefined;
+  if (report && (report.event === 'websocket' || report.event === 'webrtc' || report.event === 'http_body')) {
+    chrome.runtime.sendMessage({ type: 'network_capture', event: report.event, data: report.data }).catch(() => {});
+  }
+});
+window.postMessage({ __agentBrowserNetworkReady: true }, '*');
+
+// The hooks only read response bodies while the server's capture policy keeps them
+function configureNetworkHooks(config: { captureBodies: boolean; maxBodyBytes: number } | undefined): void {
+  if (config) {
+    window.postMessage({ __agentBrowserNetworkConfig: config }, '*');
+  }
+}
+chrome.runtime.sendMessage({ type: 'network_capture_config_get' }).then(configureNetworkHooks).catch(() => {});
+chrome.runtime.onMessage.addListener((message) => {
+  if (message.type === 'network_capture_config') {
+    configureNetworkHooks(message.config);
+  }
+  return false;
+});
+
 // ============================================================================
 // Initialization
 // ============================================================================
@@ -0,0 +1,210 @@
+/**
+ * Network hooks - WebSocket, WebRTC and response body capture in the page's own world
+ *
+ * - Runs at document_start in the MAIN world, before page scripts
+ * - Wraps WebSocket and RTCPeerConnection and reports their lifecycle and messages
+ * - While the capture policy keeps bodies, wraps fetch and XMLHttpRequest and
+ *   reports their text responses
+ * - Posts reports to the content script, which relays them to the background
+ *   script as `websocket` and `webrtc` events, or response bodies for its
+ *   `http` events
+ *
+ * webRequest never sees WebSocket frames, peer connections or response
+ * bodies, and watching them through chrome.debugger would put the debugging
+ * banner on every tab. Reports are queued until the content script, which
+ * loads at document_idle, says it is listening.
+ */
+
+export {};
+
+const CHANNEL = '__agentBrowserNetwork';
+const READY = '__agentBrowserNetworkReady';
+const CONFIG = '__agentBrowserNetworkConfig';
+
+// Message text kept per frame; the server previews and redacts it
+const MAX_PAYLOAD_CHARS = 4096;
+// Reports held while no content script listens
+const MAX_QUEUED = 500;
+
+type Report = { event: 'websocket' | 'webrtc' | 'http_body'; data: Record<string, any> };
+
+let relayReady = false;
+// Set by the content script from the server's capture policy
+let bodies = { captureBodies: false, maxBodyBytes: 0 };
+const queue: Report[] = [];
+let counter = 0;
+
+function nextId(prefix: string): string {
+  counter += 1;
+  return `${prefix}-${Date.now().toString(36)}-${counter}`;
+}
+
+function post(report: Report): void {
+  window.postMessage({ [CHANNEL]: report }, location.origin === 'null' ? '*' : location.origin);
+}
+
+function emit(event: Report['event'], data: Record<string, any>): void {
+  const report: Report = { event, data: { ...data, timestamp: Date.now() } };
+  if (relayReady) {
+    post(report);
+  } else if (queue.length < MAX_QUEUED) {
+    queue.push(report);
+  }
+}
+
+window.addEventListener('message', (event) => {
+  if (event.source !== window) {
+    return;
+  }
+  if (event.data?.[READY] === true && !relayReady) {
+    relayReady = true;
+    for (const report of queue.splice(0)) {
+      post(report);
+    }
+  }
+  if (event.data?.[CONFIG]) {
+    bodies = event.data[CONFIG];
+  }
+});
+
+// ============================================================================
+// WebSocket
+// ============================================================================
+
+function describePayload(data: unknown): { size: number; binary: boolean; payload?: string } {
+  if (typeof data === 'string') {
+    return { size: new Blob([data]).size, binary: false, payload: data.slice(0, MAX_PAYLOAD_CHARS) };
+  }
+  if (data instanceof Blob) {
+    return { size: data.size, binary: true };
+  }
+  if (data instanceof ArrayBuffer || ArrayBuffer.isView(data)) {
+    return { size: (data as ArrayBuffer).byteLength, binary: true };
+  }
+  return { size: 0, binary: true };
+}
+
+const NativeWebSocket = window.WebSocket;
+
+if (NativeWebSocket) {
+  class CapturedWebSocket extends NativeWebSocket {
+    constructor(url: string | URL, protocols?: string | string[]) {
+      super(url, protocols);
+      const id = nextId('ws');
+      emit('websocket', { id, phase: 'connecting', url: this.url });
+      this.addEventListener('open', () => emit('websocket', { id, phase: 'open', url: this.url }));
+      this.addEventListener('message', (event) => {
+        emit('websocket', { id, phase: 'message', direction: 'received', ...describePayload(event.data) });
+      });
+      this.addEventListener('error', () => emit('websocket', { id, phase: 'error', reason: 'WebSocket error' }));
+      this.addEventListener('close', (event) => {
+        emit('websocket', { id, phase: 'close', code: event.code, reason: event.reason || undefined });
+      });
+      (this as any)[CHANNEL] = id;
+    }
+
+    send(data: string | ArrayBufferLike | Blob | ArrayBufferView): void {
+      super.send(data);
+      emit('websocket', { id: (this as any)[CHANNEL], phase: 'message', direction: 'sent', ...describePayload(data) });
+    }
+  }
+
+  window.WebSocket = CapturedWebSocket as typeof WebSocket;
+}
+
+// ============================================================================
+// WebRTC
+// ============================================================================
+
+const NativePeerConnection = window.RTCPeerConnection;
+
+if (NativePeerConnection) {
+  class CapturedPeerConnection extends NativePeerConnection {
+    constructor(configuration?: RTCConfiguration) {
+      super(configuration);
+      const connectionId = nextId('rtc');
+      (this as any)[CHANNEL] = connectionId;
+      const report = (event: string, detail: Record<string, any> = {}, state?: string) => {
+        emit('webrtc', { connection_id: connectionId, event, state, detail });
+      };
+
+      // STUN/TURN hosts only; credentials stay in the page
+      const iceServers = (configuration?.iceServers ?? [])
+        .flatMap((server) => (Array.isArray(server.urls) ? server.urls : [server.urls]));
+      report('created', { iceServers, iceTransportPolicy: configuration?.iceTransportPolicy ?? 'all' });
+
+      this.addEventListener('connectionstatechange', () => report('connectionstatechange', {}, this.connectionState));
+      this.addEventListener('iceconnectionstatechange', () => report('iceconnectionstatechange', {}, this.iceConnectionState));
+      this.addEventListener('signalingstatechange', () => report('signalingstatechange', {}, this.signalingState));
+      this.addEventListener('icegatheringstatechange', () => report('icegatheringstatechange', {}, this.iceGatheringState));
+      this.addEventListener('icecandidateerror', (event: Event) => {
+        const error = event as RTCPeerConnectionIceErrorEvent;
+        report('icecandidateerror', { url: error.url, errorCode: error.errorCode, errorText: error.errorText });
+      });
+      this.addEventListener('datachannel', (event) => {
+        report('datachannel', { label: event.channel.label, protocol: event.channel.protocol, remote: true });
+      });
+      this.addEventListener('track', (event) => report('track', { kind: event.track.kind }));
+    }
+
+    createDataChannel(label: string, options?: RTCDataChannelInit): RTCDataChannel {
+      const channel = super.createDataChannel(label, options);
+      emit('webrtc', {
+        connection_id: (this as any)[CHANNEL],
+        event: 'datachannel',
+        detail: { label, protocol: channel.protocol, remote: false },
+      });
+      return channel;
+    }
+
+    close(): void {
+      super.close();
+      emit('webrtc', { connection_id: (this as any)[CHANNEL], event: 'closed', state: 'closed', detail: {} });
+    }
+  }
+
+  window.RTCPeerConnection = CapturedPeerConnection as typeof RTCPeerConnection;
+}
+
+// ============================================================================
+// Response Bodies
+// ============================================================================
+
+const TEXT_TYPES = /^(text\/|application\/([\w.+-]*\+)?(json|xml|javascript|x-www-form-urlencoded|graphql))/i;
+
+function reportBody(method: string, url: string, contentType: string | null, body: string): void {
+  if (!contentType || !TEXT_TYPES.test(contentType)) {
+    return;
+  }
+  emit('http_body', { method: method.toUpperCase(), url, body: body.slice(0, bodies.maxBodyBytes) });
+}
+
+const nativeFetch = window.fetch;
+
+window.fetch = async function (input: RequestInfo | URL, init?: RequestInit): Promise<Response> {
+  const response = await nativeFetch.call(this, input, init);
+  if (bodies.captureBodies) {
+    const method = init?.method ?? (input instanceof Request ? input.method : 'GET');
+    // A clone leaves the page's own stream unread
+    response.clone().text()
+      .then((body) => reportBody(method, response.url, response.headers.get('content-type'), body))
+      .catch(() => {});
+  }
+  return response;
+};
+
+const nativeOpen = XMLHttpRequest.prototype.open;
+
+XMLHttpRequest.prototype.open = function (this: XMLHttpRequest, method: string, ...rest: any[]) {
+  if (bodies.captureBodies) {
+    this.addEventListener('load', () => {
+      // Only text is readable without changing what the page gets back
+      if (this.responseType === '' || this.responseType === 'text') {
+        reportBody(method, this.responseURL, this.getResponseHeader('content-type'), this.responseText);
+      } else if (this.responseType === 'json') {
+        reportBody(method, this.responseURL, this.getResponseHeader('content-type'), JSON.stringify(this.response));
+      }
+    }, { once: true });
+  }
+  return (nativeOpen as any).call(this, method, ...rest);
+} as typeof XMLHttpRequest.prototype.open;
@@ -0,0 +1,288 @@
+/**
+ * Accessibility snapshot command: the page's accessibility tree as JSON
+ * (role, name, value, states, children), computed from implicit HTML roles
+ * and ARIA attributes. Each node carries a CSS selector so callers can act
+ * on it. Hidden subtrees are skipped; open shadow roots are included.
+ */
+
+import { accessibleName, cssPath, isHidden } from './accessibility';
+import type { AccessibilitySnapshotParams, Command, CommandHandler } from './types';
+
+export interface AxNode {
+  role: string;
+  name?: string;
+  value?: string;
+  level?: number;
+  states?: Record<string, boolean | string>;
+  selector?: string;
+  children?: AxNode[];
+}
+
+// Roles kept when interestingOnly drops generic containers
+const INTERESTING_ROLES = new Set([
+  'article', 'banner', 'button', 'cell', 'checkbox', 'columnheader', 'combobox', 'complementary',
+  'contentinfo', 'dialog', 'alertdialog', 'form', 'grid', 'heading', 'img', 'link', 'list', 'listbox',
+  'listitem', 'main', 'menu', 'menubar', 'menuitem', 'menuitemcheckbox', 'menuitemradio', 'navigation',
+  'option', 'progressbar', 'radio', 'radiogroup', 'region', 'row', 'rowheader', 'search', 'searchbox',
+  'separator', 'slider', 'spinbutton', 'switch', 'tab', 'table', 'tablist', 'tabpanel', 'textbox',
+  'tree', 'treeitem', 'alert', 'status', 'group', 'figure', 'iframe',
+]);
+
+const TAG_ROLES: Record<string, string> = {
+  article: 'article', aside: 'complementary', button: 'button', dialog: 'dialog', fieldset: 'group',
+  figure: 'figure', hr: 'separator', iframe: 'iframe', li: 'listitem', main: 'main', nav: 'navigation',
+  ol: 'list', ul: 'list', option: 'option', progress: 'progressbar', summary: 'button', table: 'table',
+  tr: 'row', td: 'cell', textarea: 'textbox', p: 'paragraph', details: 'group', search: 'search',
+};
+
+const INPUT_ROLES: Record<string, string> = {
+  button: 'button', submit: 'button', reset: 'button', image: 'button', checkbox: 'checkbox',
+  radio: 'radio', range: 'slider', number: 'spinbutton', search: 'searchbox',
+};
+
+function implicitRole(element: Element): string {
+  const tag = element.tagName.toLowerCase();
+  if (/^h[1-6]$/.test(tag)) {
+    return 'heading';
+  }
+  switch (tag) {
+    case 'a':
+    case 'area':
+      return element.hasAttribute('href') ? 'link' : 'generic';
+    case 'img':
+      return element.getAttribute('alt') === '' ? 'presentation' : 'img';
+    case 'input': {
+      const type = (element as HTMLInputElement).type;
+      if (type === 'hidden') {
+        return 'none';
+      }
+      return INPUT_ROLES[type] ?? (element.hasAttribute('list') ? 'combobox' : 'textbox');
+    }
+    case 'select': {
+      const select = element as HTMLSelectElement;
+      return select.multiple || select.size > 1 ? 'listbox' : 'combobox';
+    }
+    case 'th':
+      return element.closest('thead') || element.getAttribute('scope') === 'col' ? 'columnheader' : 'rowheader';
+    case 'header':
+    case 'footer':
+      // Only page-level headers and footers are landmarks
+      if (element.closest('article, aside, main, nav, section')) {
+        return 'generic';
+      }
+      return tag === 'header' ? 'banner' : 'contentinfo';
+    case 'section':
+    case 'form':
+      // Unnamed sections and forms aren't landmarks
+      return element.hasAttribute('aria-label') || element.hasAttribute('aria-labelledby')
+        ? (tag === 'form' ? 'form' : 'region')
+        : 'generic';
+    default:
+      return TAG_ROLES[tag] ?? 'generic';
+  }
+}
+
+export function roleOf(element: Element): string {
+  const explicit = element.getAttribute('role')?.trim().split(/\s+/)[0];
+  return explicit || implicitRole(element);
+}
+
+/** Names of form controls: ARIA, then associated labels, placeholder and title */
+function controlName(element: HTMLInputElement | HTMLSelectElement | HTMLTextAreaElement): string {
+  const aria = element.getAttribute('aria-label')?.trim();
+  if (aria) {
+    return aria;
+  }
+  const labels = Array.from(element.labels ?? []).map((label) => label.innerText.trim()).join(' ').trim();
+  if (labels) {
+    return labels;
+  }
+  if (element instanceof HTMLInputElement && ['button', 'submit', 'reset'].includes(element.type)) {
+    return element.value || (element.type === 'submit' ? 'Submit' : element.type === 'reset' ? 'Reset' : '');
+  }
+  return element.getAttribute('placeholder')?.trim() || element.getAttribute('title')?.trim() || '';
+}
+
+export function nameOf(element: Element, role: string): string {
+  const isControl = element instanceof HTMLInputElement || element instanceof HTMLSelectElement
+    || element instanceof HTMLTextAreaElement;
+  let name: string;
+  if (isControl && !element.hasAttribute('aria-labelledby')) {
+    name = controlName(element);
+  } else if (role === 'img' && element.hasAttribute('alt')) {
+    name = element.getAttribute('alt')!.trim();
+  } else if (['list', 'listbox', 'table', 'navigation', 'main', 'region', 'form', 'group', 'dialog', 'menu',
+    'tablist', 'banner', 'contentinfo', 'complementary', 'article', 'radiogroup', 'grid', 'tree'].includes(role)) {
+    // Containers are named by ARIA only, not by their whole contents
+    name = element.getAttribute('aria-label')?.trim()
+      || (element.getAttribute('aria-labelledby') ? accessibleName(element) : '')
+      || (element.tagName === 'FIELDSET' ? element.querySelector('legend')?.textContent?.trim() ?? '' : '');
+  } else {
+    name = accessibleName(element);
+  }
+  name = name.replace(/\s+/g, ' ');
+  return name.length > 200 ? `${name.slice(0, 200)}...` : name;
+}
+
+function statesOf(element: Element, role: string): Record<string, boolean | string> | undefined {
+  const states: Record<string, boolean | string> = {};
+  const aria = (attribute: string) => element.getAttribute(`aria-${attribute}`);
+
+  if ((element as HTMLInputElement).disabled || aria('disabled') === 'true') {
+    states.disabled = true;
+  }
+  if (element instanceof HTMLInputElement && (element.type === 'checkbox' || element.type === 'radio')) {
+    states.checked = element.indeterminate ? 'mixed' : element.checked;
+  } else if (aria('checked') !== null) {
+    states.checked = aria('checked') === 'mixed' ? 'mixed' : aria('checked') === 'true';
+  }
+  if (element instanceof HTMLOptionElement) {
+    states.selected = element.selected;
+  } else if (aria('selected') !== null) {
+    states.selected = aria('selected') === 'true';
+  }
+  if (aria('expanded') !== null) {
+    states.expanded = aria('expanded') === 'true';
+  } else if (element instanceof HTMLDetailsElement) {
+    states.expanded = element.open;
+  } else if (role === 'button' && element.tagName === 'SUMMARY') {
+    states.expanded = (element.parentElement as HTMLDetailsElement | null)?.open ?? false;
+  }
+  if (aria('pressed') !== null) {
+    states.pressed = aria('pressed') === 'mixed' ? 'mixed' : aria('pressed') === 'true';
+  }
+  if ((element as HTMLInputElement).required || aria('required') === 'true') {
+    states.required = true;
+  }
+  if ((element as HTMLInputElement).readOnly || aria('readonly') === 'true') {
+    states.readonly = true;
+  }
+  if (aria('invalid') && aria('invalid') !== 'false') {
+    states.invalid = true;
+  }
+  if (aria('current') && aria('current') !== 'false') {
+    states.current = aria('current')!;
+  }
+  if (document.activeElement === element) {
+    states.focused = true;
+  }
+  return Object.keys(states).length ? states : undefined;
+}
+
+function valueOf(element: Element, role: string): string | undefined {
+  if (element instanceof HTMLInputElement && element.type === 'password') {
+    return undefined;
+  }
+  if (['textbox', 'searchbox', 'combobox', 'spinbutton', 'slider'].includes(role)) {
+    if (element instanceof HTMLSelectElement) {
+      return element.selectedOptions[0]?.text;
+    }
+    const value = (element as HTMLInputElement).value ?? element.getAttribute('aria-valuenow') ?? undefined;
+    return value || undefined;
+  }
+  if (role === 'progressbar') {
+    return (element as HTMLProgressElement).value?.toString() ?? element.getAttribute('aria-valuenow') ?? undefined;
+  }
+  return undefined;
+}
+
+function childElements(element: Element): Element[] {
+  const children = Array.from(element.children);
+  return element.shadowRoot ? [...Array.from(element.shadowRoot.children), ...children] : children;
+}
+
+export const accessibilitySnapshotCommand: CommandHandler = async (command: Command) => {
+  // snapshot_save sends no parameters and gets the full tree
+  const params: AccessibilitySnapshotParams = {
+    interestingOnly: false,
+    maxDepth: 100,
+    maxNodes: 10000,
+    ...(command.params as Partial<AccessibilitySnapshotParams>),
+  };
+  const root = params.selector ? document.querySelector(params.selector) : document.body;
+  if (!root) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+
+  let count = 0;
+  let truncated = false;
+
+  /** Nodes for `element`: itself, or its children's nodes when it's dropped */
+  const build = (element: Element, depth: number): AxNode[] => {
+    if (count >= params.maxNodes) {
+      truncated = true;
+      return [];
+    }
+    if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE'].includes(element.tagName) || isHidden(element)) {
+      return [];
+    }
+    const role = roleOf(element);
+    if (role === 'none' && element.tagName === 'INPUT') {
+      return [];
+    }
+    const leaf = ['button', 'link', 'heading', 'textbox', 'searchbox', 'img', 'option', 'checkbox', 'radio',
+      'slider', 'spinbutton', 'combobox', 'menuitem', 'tab', 'switch', 'progressbar', 'separator'].includes(role)
+      && !element.querySelector('input, select, textarea, button, a[href], [role]');
+    const kept = params.interestingOnly
+      ? INTERESTING_ROLES.has(role)
+      : !['generic', 'none', 'presentation'].includes(role);
+
+    const descend = (nextDepth: number): AxNode[] => {
+      if (leaf || nextDepth > params.maxDepth) {
+        return [];
+      }
+      const nodes = childElements(element).flatMap((child) => build(child, nextDepth));
+      if (!params.interestingOnly) {
+        // Text directly inside this element, between child elements
+        const text = Array.from(element.childNodes)
+          .filter((node) => node.nodeType === Node.TEXT_NODE)
+          .map((node) => node.textContent?.trim() ?? '')
+          .filter(Boolean)
+          .join(' ');
+        if (text && !kept) {
+          count += 1;
+          nodes.unshift({ role: 'text', name: text.length > 200 ? `${text.slice(0, 200)}...` : text });
+        }
+      }
+      return nodes;
+    };
+
+    if (!kept) {
+      return descend(depth);
+    }
+
+    count += 1;
+    const node: AxNode = { role };
+    const name = nameOf(element, role);
+    if (name) {
+      node.name = name;
+    }
+    const value = valueOf(element, role);
+    if (value !== undefined) {
+      node.value = value.length > 200 ? `${value.slice(0, 200)}...` : value;
+    }
+    if (role === 'heading') {
+      node.level = Number(element.getAttribute('aria-level') ?? element.tagName.slice(1)) || 2;
+    }
+    const states = statesOf(element, role);
+    if (states) {
+      node.states = states;
+    }
+    node.selector = cssPath(element);
+    const children = descend(depth + 1);
+    if (children.length) {
+      node.children = children;
+    }
+    return [node];
+  };
+
+  const tree: AxNode = { role: 'document', name: document.title, children: build(root, 1) };
+  return {
+    success: true,
+    url: location.href,
+    title: document.title,
+    nodeCount: count,
+    truncated,
+    tree,
+  };
+};
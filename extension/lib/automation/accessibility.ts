@@ -0,0 +1,346 @@
+/**
+ * Accessibility audit command: run axe-core-style rules against the DOM and
+ * report raw findings (elements examined, elements failing) per rule.
+ * Impact, WCAG mapping and scoring are applied by the server.
+ */
+
+import type { AccessibilityAuditParams, Command, CommandHandler } from './types';
+
+interface FailedNode {
+  selector: string;
+  html: string;
+  message?: string;
+}
+
+interface RuleFinding {
+  rule: string;
+  checked: number;
+  nodes: FailedNode[];
+}
+
+type RuleCheck = (root: Element) => RuleFinding;
+
+// Elements sampled for color-contrast, which needs computed styles per element
+const MAX_CONTRAST_ELEMENTS = 2000;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+export function cssPath(element: Element): string {
+  if (element.id && document.querySelectorAll(`#${CSS.escape(element.id)}`).length === 1) {
+    return `#${CSS.escape(element.id)}`;
+  }
+  const parts: string[] = [];
+  let current: Element | null = element;
+  while (current && current !== document.documentElement && parts.length < 6) {
+    let part = current.tagName.toLowerCase();
+    const parent: Element | null = current.parentElement;
+    if (parent) {
+      const siblings = Array.from(parent.children).filter((child) => child.tagName === current!.tagName);
+      if (siblings.length > 1) {
+        part += `:nth-of-type(${siblings.indexOf(current) + 1})`;
+      }
+    }
+    parts.unshift(part);
+    if (parent?.id) {
+      parts.unshift(`#${CSS.escape(parent.id)}`);
+      break;
+    }
+    current = parent;
+  }
+  return parts.join(' > ');
+}
+
+function snippet(element: Element): string {
+  const html = element.outerHTML;
+  return html.length > 200 ? `${html.slice(0, 200)}...` : html;
+}
+
+function failure(element: Element, message?: string): FailedNode {
+  return { selector: cssPath(element), html: snippet(element), message };
+}
+
+function within(root: Element, selector: string): Element[] {
+  const matches = Array.from(root.querySelectorAll(selector));
+  return root.matches(selector) ? [root, ...matches] : matches;
+}
+
+export function isHidden(element: Element): boolean {
+  if (element.closest('[aria-hidden="true"], [hidden]')) {
+    return true;
+  }
+  const style = getComputedStyle(element);
+  return style.display === 'none' || style.visibility === 'hidden';
+}
+
+function labelledByText(element: Element): string {
+  const ids = element.getAttribute('aria-labelledby')?.split(/\s+/) ?? [];
+  return ids.map((id) => document.getElementById(id)?.textContent ?? '').join(' ').trim();
+}
+
+/** Accessible name from ARIA, contents, and image alt text (a subset of accname) */
+export function accessibleName(element: Element): string {
+  const aria = element.getAttribute('aria-label')?.trim() || labelledByText(element);
+  if (aria) {
+    return aria;
+  }
+  const text = (element as HTMLElement).innerText?.trim() ?? element.textContent?.trim() ?? '';
+  if (text) {
+    return text;
+  }
+  const alt = Array.from(element.querySelectorAll('img[alt], [role="img"][aria-label]'))
+    .map((image) => image.getAttribute('alt') ?? image.getAttribute('aria-label') ?? '')
+    .join(' ')
+    .trim();
+  return alt || element.getAttribute('title')?.trim() || element.querySelector('svg title')?.textContent?.trim() || '';
+}
+
+function check(rule: string, elements: Element[], fails: (element: Element) => string | false | undefined): RuleFinding {
+  const nodes: FailedNode[] = [];
+  for (const element of elements) {
+    const message = fails(element);
+    if (message !== false && message !== undefined) {
+      nodes.push(failure(element, message || undefined));
+    }
+  }
+  return { rule, checked: elements.length, nodes };
+}
+
+// ============================================================================
+// Color Contrast
+// ============================================================================
+
+type Rgba = [number, number, number, number];
+
+function parseColor(value: string): Rgba | null {
+  const match = value.match(/rgba?\(([^)]+)\)/);
+  if (!match) {
+    return null;
+  }
+  const [r, g, b, a = '1'] = match[1].split(/[\s,/]+/).filter(Boolean);
+  return [Number(r), Number(g), Number(b), Number(a)];
+}
+
+function blend(top: Rgba, bottom: Rgba): Rgba {
+  const alpha = top[3];
+  return [
+    top[0] * alpha + bottom[0] * (1 - alpha),
+    top[1] * alpha + bottom[1] * (1 - alpha),
+    top[2] * alpha + bottom[2] * (1 - alpha),
+    1,
+  ];
+}
+
+/** Opaque background behind an element, or null when an image makes it unknowable */
+function effectiveBackground(element: Element): Rgba | null {
+  const layers: Rgba[] = [];
+  for (let current: Element | null = element; current; current = current.parentElement) {
+    const style = getComputedStyle(current);
+    if (style.backgroundImage !== 'none') {
+      return null;
+    }
+    const color = parseColor(style.backgroundColor);
+    if (color && color[3] > 0) {
+      layers.push(color);
+      if (color[3] >= 1) {
+        break;
+      }
+    }
+  }
+  return layers.reduceRight<Rgba>((below, layer) => blend(layer, below), [255, 255, 255, 1]);
+}
+
+function luminance([r, g, b]: Rgba): number {
+  const channel = (c: number) => {
+    const s = c / 255;
+    return s <= 0.03928 ? s / 12.92 : ((s + 0.055) / 1.055) ** 2.4;
+  };
+  return 0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b);
+}
+
+function contrastRatio(a: Rgba, b: Rgba): number {
+  const [light, dark] = [luminance(a), luminance(b)].sort((x, y) => y - x);
+  return (light + 0.05) / (dark + 0.05);
+}
+
+function hasOwnText(element: Element): boolean {
+  return Array.from(element.childNodes).some(
+    (node) => node.nodeType === Node.TEXT_NODE && (node.textContent ?? '').trim().length > 0
+  );
+}
+
+const colorContrast: RuleCheck = (root) => {
+  const elements = within(root, 'body *')
+    .filter((element) => hasOwnText(element) && !isHidden(element))
+    .slice(0, MAX_CONTRAST_ELEMENTS);
+  const examined: Element[] = [];
+  const nodes: FailedNode[] = [];
+  for (const element of elements) {
+    const style = getComputedStyle(element);
+    const foreground = parseColor(style.color);
+    const background = effectiveBackground(element);
+    if (!foreground || !background) {
+      continue;
+    }
+    examined.push(element);
+    const ratio = contrastRatio(blend(foreground, background), background);
+    const size = parseFloat(style.fontSize);
+    const bold = Number(style.fontWeight) >= 700;
+    const large = size >= 24 || (bold && size >= 18.66);
+    const required = large ? 3 : 4.5;
+    if (ratio < required) {
+      nodes.push(failure(element, `Contrast ${ratio.toFixed(2)}:1, needs ${required}:1`));
+    }
+  }
+  return { rule: 'color-contrast', checked: examined.length, nodes };
+};
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+const ARIA_ROLES = new Set([
+  'alert', 'alertdialog', 'application', 'article', 'banner', 'blockquote', 'button', 'caption', 'cell',
+  'checkbox', 'code', 'columnheader', 'combobox', 'complementary', 'contentinfo', 'definition', 'deletion',
+  'dialog', 'directory', 'document', 'emphasis', 'feed', 'figure', 'form', 'generic', 'grid', 'gridcell',
+  'group', 'heading', 'img', 'insertion', 'link', 'list', 'listbox', 'listitem', 'log', 'main', 'marquee',
+  'math', 'menu', 'menubar', 'menuitem', 'menuitemcheckbox', 'menuitemradio', 'meter', 'navigation', 'none',
+  'note', 'option', 'paragraph', 'presentation', 'progressbar', 'radio', 'radiogroup', 'region', 'row',
+  'rowgroup', 'rowheader', 'scrollbar', 'search', 'searchbox', 'separator', 'slider', 'spinbutton', 'status',
+  'strong', 'subscript', 'superscript', 'switch', 'tab', 'table', 'tablist', 'tabpanel', 'term', 'textbox',
+  'time', 'timer', 'toolbar', 'tooltip', 'tree', 'treegrid', 'treeitem',
+]);
+
+const FOCUSABLE = 'a[href], button, input, select, textarea, iframe, [tabindex], [contenteditable="true"]';
+
+const RULE_CHECKS: Record<string, RuleCheck> = {
+  'image-alt': (root) =>
+    check('image-alt', within(root, 'img, [role="img"]').filter((e) => !isHidden(e)), (image) => {
+      const role = image.getAttribute('role');
+      if (role === 'presentation' || role === 'none') {
+        return false;
+      }
+      return !image.hasAttribute('alt') && !image.getAttribute('aria-label') && !labelledByText(image) && '';
+    }),
+
+  label: (root) => {
+    const fields = within(root, 'input, select, textarea').filter((field) => {
+      const type = (field.getAttribute('type') ?? 'text').toLowerCase();
+      return !['hidden', 'submit', 'reset', 'button', 'image'].includes(type) && !isHidden(field);
+    });
+    return check('label', fields, (field) => {
+      const labels = (field as HTMLInputElement).labels;
+      const labelled = Array.from(labels ?? []).some((label) => label.textContent?.trim());
+      return !labelled && !field.getAttribute('aria-label')?.trim() && !labelledByText(field)
+        && !field.getAttribute('title')?.trim() && '';
+    });
+  },
+
+  'button-name': (root) =>
+    check(
+      'button-name',
+      within(root, 'button, [role="button"], input[type="submit"], input[type="button"], input[type="reset"]')
+        .filter((e) => !isHidden(e)),
+      (button) => {
+        if (button instanceof HTMLInputElement) {
+          return !button.value.trim() && !button.getAttribute('aria-label')?.trim() && '';
+        }
+        return !accessibleName(button) && '';
+      }
+    ),
+
+  'link-name': (root) =>
+    check('link-name', within(root, 'a[href]').filter((e) => !isHidden(e)), (link) => !accessibleName(link) && ''),
+
+  'color-contrast': colorContrast,
+
+  'html-has-lang': () =>
+    check('html-has-lang', [document.documentElement], (html) => !html.getAttribute('lang')?.trim() && ''),
+
+  'document-title': () => ({
+    rule: 'document-title',
+    checked: 1,
+    nodes: document.title.trim() ? [] : [failure(document.head ?? document.documentElement, 'Missing or empty <title>')],
+  }),
+
+  'aria-valid-role': (root) =>
+    check('aria-valid-role', within(root, '[role]'), (element) => {
+      const invalid = (element.getAttribute('role') ?? '').split(/\s+/).filter((role) => role && !ARIA_ROLES.has(role));
+      return invalid.length > 0 && `Unknown role: ${invalid.join(', ')}`;
+    }),
+
+  'aria-hidden-focus': (root) =>
+    check('aria-hidden-focus', within(root, '[aria-hidden="true"]'), (hidden) => {
+      const focusable = [hidden, ...Array.from(hidden.querySelectorAll(FOCUSABLE))].filter(
+        (element) => element.matches(FOCUSABLE) && element.getAttribute('tabindex') !== '-1'
+          && !(element as HTMLButtonElement).disabled
+      );
+      return focusable.length > 0 && `Contains ${focusable.length} focusable element(s)`;
+    }),
+
+  'aria-labelledby-target': (root) =>
+    check('aria-labelledby-target', within(root, '[aria-labelledby], [aria-describedby]'), (element) => {
+      const ids = ['aria-labelledby', 'aria-describedby']
+        .flatMap((attribute) => element.getAttribute(attribute)?.split(/\s+/) ?? [])
+        .filter(Boolean);
+      const missing = ids.filter((id) => !document.getElementById(id));
+      return missing.length > 0 && `Missing id: ${missing.join(', ')}`;
+    }),
+
+  'duplicate-id': (root) => {
+    const elements = within(root, '[id]');
+    const counts = new Map<string, number>();
+    for (const element of elements) {
+      counts.set(element.id, (counts.get(element.id) ?? 0) + 1);
+    }
+    const seen = new Set<string>();
+    return check('duplicate-id', elements, (element) => {
+      // Report each duplicated id once
+      if ((counts.get(element.id) ?? 0) < 2 || seen.has(element.id)) {
+        return false;
+      }
+      seen.add(element.id);
+      return `id "${element.id}" is used ${counts.get(element.id)} times`;
+    });
+  },
+
+  'heading-order': (root) => {
+    const headings = within(root, 'h1, h2, h3, h4, h5, h6').filter((e) => !isHidden(e));
+    let previous = 0;
+    return check('heading-order', headings, (heading) => {
+      const level = Number(heading.tagName[1]);
+      const skipped = previous > 0 && level > previous + 1;
+      const message = skipped && `h${previous} followed by h${level}`;
+      previous = level;
+      return message;
+    });
+  },
+
+  'frame-title': (root) =>
+    check('frame-title', within(root, 'iframe, frame').filter((e) => !isHidden(e)), (frame) =>
+      !frame.getAttribute('title')?.trim() && !frame.getAttribute('aria-label')?.trim() && ''),
+};
+
+// ============================================================================
+// Audit Command Handler
+// ============================================================================
+
+export const auditAccessibilityCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as AccessibilityAuditParams;
+  const root = params.selector ? document.querySelector(params.selector) : document.documentElement;
+  if (!root) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+
+  const findings = params.rules
+    .filter((rule) => rule in RULE_CHECKS)
+    .map((rule) => RULE_CHECKS[rule](root));
+
+  return {
+    success: true,
+    url: location.href,
+    title: document.title,
+    findings,
+  };
+};
@@ -0,0 +1,113 @@
+/**
+ * Canvas-read-pixels command implementation
+ *
+ * The region is drawn onto a scratch 2D canvas, which works for 2D and
+ * WebGL canvases alike, and read back with getImageData. A canvas that has
+ * drawn cross-origin images without CORS is tainted and can't be read; a
+ * WebGL canvas without preserveDrawingBuffer reads as transparent between
+ * frames.
+ */
+
+import type { CanvasReadPixelsParams, Command, CommandHandler, ModeConfig } from './types';
+
+/** Mean RGBA of each cell when the region is split into grid x grid cells */
+export function averageCells(
+  data: Uint8ClampedArray,
+  width: number,
+  height: number,
+  grid: number,
+): Array<{ row: number; col: number; x: number; y: number; width: number; height: number; rgba: number[]; hex: string }> {
+  const cells = [];
+  for (let row = 0; row < grid; row++) {
+    for (let col = 0; col < grid; col++) {
+      const x0 = Math.floor((col * width) / grid);
+      const x1 = Math.floor(((col + 1) * width) / grid);
+      const y0 = Math.floor((row * height) / grid);
+      const y1 = Math.floor(((row + 1) * height) / grid);
+      const sums = [0, 0, 0, 0];
+      for (let y = y0; y < y1; y++) {
+        for (let x = x0; x < x1; x++) {
+          const offset = (y * width + x) * 4;
+          for (let channel = 0; channel < 4; channel++) {
+            sums[channel] += data[offset + channel];
+          }
+        }
+      }
+      const count = Math.max(1, (x1 - x0) * (y1 - y0));
+      const rgba = sums.map((sum) => Math.round(sum / count));
+      const hex = `#${rgba.slice(0, 3).map((value) => value.toString(16).padStart(2, '0')).join('')}`;
+      cells.push({ row, col, x: x0, y: y0, width: x1 - x0, height: y1 - y0, rgba, hex });
+    }
+  }
+  return cells;
+}
+
+export const canvasReadPixelsCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const params = command.params as CanvasReadPixelsParams;
+  const canvas = document.querySelector(params.selector);
+  if (!canvas) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+  if (!(canvas instanceof HTMLCanvasElement)) {
+    throw new Error(`Element is a <${canvas.tagName.toLowerCase()}>, not a <canvas>: ${params.selector}`);
+  }
+  if (canvas.width === 0 || canvas.height === 0) {
+    throw new Error(`Canvas has no pixels (${canvas.width}x${canvas.height})`);
+  }
+  if (params.x >= canvas.width || params.y >= canvas.height) {
+    throw new Error(`Region starts at ${params.x},${params.y}, outside the ${canvas.width}x${canvas.height} canvas`);
+  }
+
+  const width = Math.min(params.width ?? canvas.width - params.x, canvas.width - params.x);
+  const height = Math.min(params.height ?? canvas.height - params.y, canvas.height - params.y);
+  if (width * height > params.maxPixels) {
+    throw new Error(`Region of ${width}x${height} exceeds the ${params.maxPixels} pixel limit; pass width and height`);
+  }
+
+  const scratch = document.createElement('canvas');
+  scratch.width = width;
+  scratch.height = height;
+  const context = scratch.getContext('2d')!;
+  context.drawImage(canvas, params.x, params.y, width, height, 0, 0, width, height);
+
+  let pixels: ImageData;
+  try {
+    pixels = context.getImageData(0, 0, width, height);
+  } catch (error) {
+    if (error instanceof DOMException && error.name === 'SecurityError') {
+      throw new Error('Canvas is tainted by cross-origin content drawn without CORS, so its pixels cannot be read; use playwright_screenshot with a selector instead');
+    }
+    throw error;
+  }
+
+  let transparent = true;
+  for (let i = 3; i < pixels.data.length; i += 4) {
+    if (pixels.data[i] !== 0) {
+      transparent = false;
+      break;
+    }
+  }
+
+  const base = {
+    success: true,
+    selector: params.selector,
+    canvas: { width: canvas.width, height: canvas.height },
+    region: { x: params.x, y: params.y, width, height },
+    transparent,
+    // Asking for the context type would create one on a canvas the page hasn't used yet
+    ...(transparent
+      ? { hint: 'The region is fully transparent; WebGL canvases read that way between frames unless created with preserveDrawingBuffer: true' }
+      : {}),
+  };
+
+  if (params.mode === 'average') {
+    return { ...base, mode: 'average', grid: params.grid, cells: averageCells(pixels.data, width, height, params.grid) };
+  }
+  return {
+    ...base,
+    mode: 'image',
+    data: scratch.toDataURL('image/png').split(',')[1],
+    encoding: 'base64',
+    format: 'png',
+  };
+};
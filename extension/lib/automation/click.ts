@@ -4,6 +4,7 @@
 
 import type { Command, CommandHandler, ModeConfig, ClickParams } from './types';
 import { getRandomDelay } from './mode-config';
+import { bringIntoView } from './scroll';
 
 // ============================================================================
 // Element Finding Utilities
@@ -39,10 +40,6 @@ function isElementVisible(element: Element): boolean {
   );
 }
 
-function scrollIntoView(element: Element, behavior: ScrollBehavior = 'auto'): void {
-  element.scrollIntoView({ behavior, block: 'center', inline: 'center' });
-}
-
 // ============================================================================
 // Click Simulation
 // ============================================================================
@@ -69,34 +66,47 @@ async function simulateHumanClick(
   }
 
   const mouseButton = button === 'left' ? 0 : button === 'right' ? 2 : 1;
+  // The buttons bitmask orders right before middle, unlike button
+  const pressed = button === 'left' ? 1 : button === 'right' ? 2 : 4;
 
-  // Simulate mouse events
-  const eventOptions = {
+  const eventOptions = (detail: number, buttons: number) => ({
     bubbles: true,
     cancelable: true,
+    composed: true,
     clientX,
     clientY,
     button: mouseButton,
-    buttons: 1 << mouseButton,
-  };
-
-  // Mouse down
-  element.dispatchEvent(new MouseEvent('mousedown', eventOptions));
-
-  // Wait for stealth mode
-  if (config.mode === 'stealth') {
-    const delay = getRandomDelay([20, 80]);
-    await new Promise((resolve) => setTimeout(resolve, delay));
-  }
+    buttons,
+    detail,
+    view: window,
+  });
+
+  // Each click is a full press; detail counts clicks in the sequence, as browsers do
+  for (let i = 1; i <= clickCount; i++) {
+    element.dispatchEvent(new MouseEvent('mousedown', eventOptions(i, pressed)));
+
+    // Wait for stealth mode
+    if (config.mode === 'stealth') {
+      const delay = getRandomDelay([20, 80]);
+      await new Promise((resolve) => setTimeout(resolve, delay));
+    }
 
-  // Mouse up
-  element.dispatchEvent(new MouseEvent('mouseup', eventOptions));
+    element.dispatchEvent(new MouseEvent('mouseup', eventOptions(i, 0)));
+
+    if (button === 'left') {
+      element.dispatchEvent(new MouseEvent('click', eventOptions(i, 0)));
+      if (i === 2) {
+        element.dispatchEvent(new MouseEvent('dblclick', eventOptions(2, 0)));
+      }
+    } else if (button === 'middle') {
+      element.dispatchEvent(new MouseEvent('auxclick', eventOptions(i, 0)));
+    } else {
+      // Opens the page's context menu, if it has one; the native menu can't be driven
+      element.dispatchEvent(new MouseEvent('contextmenu', eventOptions(i, pressed)));
+    }
 
-  // Click
-  for (let i = 0; i < clickCount; i++) {
-    element.dispatchEvent(new MouseEvent('click', eventOptions));
-    if (i < clickCount - 1 && config.mode === 'stealth') {
-      await new Promise((resolve) => setTimeout(resolve, 100));
+    if (i < clickCount && config.mode === 'stealth') {
+      await new Promise((resolve) => setTimeout(resolve, getRandomDelay([60, 120])));
     }
   }
 }
@@ -150,9 +160,12 @@ export const clickCommand: CommandHandler = async (command: Command, config: Mod
     throw new Error('Element not visible');
   }
 
-  // Scroll into view
+  // Scroll into view; a target still off-screen would get a click it can't receive
   const behavior = config.speed.skipAnimations ? 'auto' : 'smooth';
-  scrollIntoView(element, behavior);
+  const view = await bringIntoView(element, { behavior, detectSticky: params.avoidStickyChrome });
+  if (view.visibleRatio === 0) {
+    throw new Error(`Element could not be scrolled into view: ${view.reason}`);
+  }
 
   // Wait for scroll if in stealth mode
   if (config.mode === 'stealth') {
@@ -170,6 +183,9 @@ export const clickCommand: CommandHandler = async (command: Command, config: Mod
   return {
     success: true,
     selector: params.selector || params.xpath,
+    button: params.button ?? 'left',
+    clickCount: params.clickCount ?? 1,
+    obscuredBy: view.obscuredBy,
     bounds: element.getBoundingClientRect(),
   };
 };
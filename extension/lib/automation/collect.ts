@@ -0,0 +1,194 @@
+/**
+ * Collect-items command: harvest an infinite-scroll feed. Scrolls the feed's
+ * container a screen at a time, waits for new items to render, and keeps
+ * every distinct item seen until a count, scroll or time budget runs out.
+ * Items are remembered by content rather than by element, because
+ * virtualized lists recycle their elements as they scroll.
+ */
+
+import type { CollectItemsParams, Command, CommandHandler } from './types';
+import { describeElement } from './scroll';
+
+// How often the page is checked for new items while waiting after a scroll
+const POLL_INTERVAL_MS = 100;
+
+// Fraction of the container's height scrolled per step, so no item is skipped
+const SCROLL_STEP = 0.9;
+
+interface Field {
+  /** Sub-selector inside the item; null reads the item itself */
+  selector: string | null;
+  /** Attribute to read; null reads the text */
+  attribute: string | null;
+}
+
+// ============================================================================
+// Item Values
+// ============================================================================
+
+/** Parse "h2", "a@href" or "@data-id" */
+function parseField(spec: string): Field {
+  const at = spec.lastIndexOf('@');
+  if (at === -1) {
+    return { selector: spec.trim(), attribute: null };
+  }
+  return { selector: spec.slice(0, at).trim() || null, attribute: spec.slice(at + 1).trim() };
+}
+
+function readField(item: Element, field: Field, maxTextLength: number): string | null {
+  const element = field.selector ? item.querySelector(field.selector) : item;
+  if (!element) {
+    return null;
+  }
+  if (field.attribute) {
+    // Properties such as href and src are already resolved against the base URL
+    const property = (element as unknown as Record<string, unknown>)[field.attribute];
+    if ((field.attribute === 'href' || field.attribute === 'src') && typeof property === 'string' && property) {
+      return property;
+    }
+    return element.getAttribute(field.attribute);
+  }
+  const text = ((element as HTMLElement).innerText ?? element.textContent ?? '').replace(/\s+/g, ' ').trim();
+  return text.slice(0, maxTextLength);
+}
+
+/** The item's values: the named fields, or its text and first link */
+function readItem(item: Element, fields: Record<string, Field> | null, maxTextLength: number): Record<string, string | null> {
+  if (fields) {
+    return Object.fromEntries(Object.entries(fields).map(([name, field]) => [name, readField(item, field, maxTextLength)]));
+  }
+  const link = item.closest('a[href]') ?? item.querySelector('a[href]');
+  return {
+    text: readField(item, { selector: null, attribute: null }, maxTextLength),
+    link: link ? (link as HTMLAnchorElement).href : null,
+  };
+}
+
+// ============================================================================
+// Scrolling
+// ============================================================================
+
+function isScrollable(element: Element): boolean {
+  const overflow = getComputedStyle(element).overflowY;
+  return (overflow === 'auto' || overflow === 'scroll') && element.scrollHeight > element.clientHeight;
+}
+
+/** The container to scroll: the given one, else the first item's scrolling ancestor, else the page */
+function scrollContainer(params: CollectItemsParams): Element {
+  const page = document.scrollingElement ?? document.documentElement;
+  if (params.container) {
+    const container = document.querySelector(params.container);
+    if (!container) {
+      throw new Error(`Element not found: ${params.container}`);
+    }
+    return container;
+  }
+  for (let node = document.querySelector(params.itemSelector)?.parentElement; node && node !== page; node = node.parentElement) {
+    if (node !== document.body && isScrollable(node)) {
+      return node;
+    }
+  }
+  return page;
+}
+
+function atBottom(container: Element): boolean {
+  return container.scrollTop + container.clientHeight >= container.scrollHeight - 2;
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// ============================================================================
+// Collect Items Command Handler
+// ============================================================================
+
+export const collectItemsCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as CollectItemsParams;
+  const started = Date.now();
+  const deadline = started + params.timeoutMs;
+  const fields = params.fields
+    ? Object.fromEntries(Object.entries(params.fields).map(([name, spec]) => [name, parseField(spec)]))
+    : null;
+
+  const container = scrollContainer(params);
+  const seen = new Set<string>();
+  const items: Record<string, any>[] = [];
+
+  /** Add the items currently rendered; returns how many were new */
+  const collect = (): number => {
+    let added = 0;
+    for (const element of Array.from(document.querySelectorAll(params.itemSelector))) {
+      if (items.length === params.maxItems) {
+        break;
+      }
+      const values = readItem(element, fields, params.maxTextLength);
+      // Skeleton placeholders have no content yet; they'll be read once filled in
+      if (Object.values(values).every((value) => !value)) {
+        continue;
+      }
+      const key = JSON.stringify(values);
+      if (seen.has(key)) {
+        continue;
+      }
+      seen.add(key);
+      items.push({ index: items.length, ...values });
+      added++;
+    }
+    return added;
+  };
+
+  collect();
+  let scrolls = 0;
+  let loadMoreClicks = 0;
+  let idleScrolls = 0;
+  let stopReason = 'maxItems';
+
+  while (items.length < params.maxItems) {
+    if (scrolls === params.maxScrolls) {
+      stopReason = 'maxScrolls';
+      break;
+    }
+    if (Date.now() >= deadline) {
+      stopReason = 'timeout';
+      break;
+    }
+
+    const loadMore = params.loadMoreSelector ? document.querySelector<HTMLElement>(params.loadMoreSelector) : null;
+    if (atBottom(container) && loadMore && !(loadMore as HTMLButtonElement).disabled) {
+      loadMore.click();
+      loadMoreClicks++;
+    } else {
+      container.scrollTop += Math.max(1, container.clientHeight * SCROLL_STEP);
+    }
+    scrolls++;
+
+    // Wait for new items, or until the wait (or the whole budget) is over
+    let added = 0;
+    const waitUntil = Math.min(Date.now() + params.waitMs, deadline);
+    while (Date.now() < waitUntil) {
+      await sleep(POLL_INTERVAL_MS);
+      added += collect();
+      if (added > 0 || items.length === params.maxItems) {
+        break;
+      }
+    }
+
+    // Scrolling through already-seen items is progress; waiting at the bottom is not
+    idleScrolls = added === 0 && atBottom(container) ? idleScrolls + 1 : 0;
+    if (idleScrolls === params.idleScrolls) {
+      stopReason = 'end';
+      break;
+    }
+  }
+
+  return {
+    success: true,
+    items,
+    count: items.length,
+    stopReason,
+    reachedEnd: stopReason === 'end',
+    scrolls,
+    loadMoreClicks,
+    durationMs: Date.now() - started,
+    container: container === document.scrollingElement ? 'page' : describeElement(container),
+  };
+};
@@ -0,0 +1,77 @@
+/**
+ * Dismiss-consent command implementation
+ *
+ * The server sends its ruleset of consent-management platforms with the
+ * button to press resolved for the mode. The first rule whose banner is
+ * showing wins; its button is found in the document or under the rule's
+ * shadow host (closed roots included) and clicked. Banners rendered in an
+ * iframe can't be reached from here, so the rule is handed back for the
+ * background script to press in the frame.
+ */
+
+import type { Command, CommandHandler, ConsentRule, DismissConsentParams, ModeConfig } from './types';
+
+// Time a banner gets to animate away before the dismissal is checked
+const VERIFY_DELAY_MS = 500;
+
+function isShown(element: Element): boolean {
+  const rect = element.getBoundingClientRect();
+  const style = window.getComputedStyle(element);
+  return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+}
+
+function shadowRootOf(host: Element): ShadowRoot | null {
+  // chrome.dom reaches closed roots, which some platforms use
+  return (chrome as any).dom?.openOrClosedShadowRoot?.(host) ?? host.shadowRoot;
+}
+
+/** The rule's button when its banner is on screen */
+function findButton(rule: ConsentRule): HTMLElement | null {
+  let root: ParentNode = document;
+  if (rule.shadowHost) {
+    const host = document.querySelector(rule.shadowHost);
+    const shadow = host ? shadowRootOf(host) : null;
+    if (!shadow) {
+      return null;
+    }
+    root = shadow;
+  } else {
+    const banner = document.querySelector(rule.detect);
+    if (!banner || !isShown(banner)) {
+      return null;
+    }
+  }
+  const button = root.querySelector(rule.button);
+  return button instanceof HTMLElement && isShown(button) ? button : null;
+}
+
+function bannerShowing(rule: ConsentRule): boolean {
+  if (rule.shadowHost) {
+    return findButton(rule) !== null;
+  }
+  const banner = document.querySelector(rule.detect);
+  return banner !== null && isShown(banner);
+}
+
+export const dismissConsentCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { mode, rules } = command.params as DismissConsentParams;
+
+  for (const rule of rules) {
+    if (rule.frame) {
+      if (bannerShowing(rule) && document.querySelector(rule.frame)) {
+        return { success: true, dismissed: false, mode, platform: rule.platform, pendingFrame: { selector: rule.frame, button: rule.button } };
+      }
+      continue;
+    }
+
+    const button = findButton(rule);
+    if (!button) {
+      continue;
+    }
+    button.click();
+    await new Promise((resolve) => setTimeout(resolve, VERIFY_DELAY_MS));
+    return { success: true, dismissed: true, mode, platform: rule.platform, button: rule.button, verified: !bannerShowing(rule) };
+  }
+
+  return { success: true, dismissed: false, mode, platform: null };
+};
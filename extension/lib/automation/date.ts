@@ -0,0 +1,228 @@
+/**
+ * Set-date command implementation
+ *
+ * The server parses the date and orders the strategies:
+ * - inject writes the value through the native setter and fires input and
+ *   change, in the format the input expects (native date/time types) or the
+ *   one its placeholder or current value shows (text inputs behind pickers)
+ * - widget opens the picker, steps its calendar to the month, and clicks the day
+ */
+
+import type { Command, CommandHandler, ModeConfig, SetDateParams } from './types';
+import { setNativeValue } from './type-text';
+
+// Months the widget strategy steps through before giving up
+const MAX_MONTH_STEPS = 120;
+const WIDGET_SETTLE_MS = 150;
+
+const pad = (value: number, width = 2) => String(value).padStart(width, '0');
+const settle = () => new Promise((resolve) => setTimeout(resolve, WIDGET_SETTLE_MS));
+
+// ============================================================================
+// Formats
+// ============================================================================
+
+/** ISO week (YYYY-Www) of a date, for week inputs */
+export function isoWeek(year: number, month: number, day: number): string {
+  const date = new Date(Date.UTC(year, month - 1, day));
+  // Thursday of this week decides the week-numbering year
+  const weekday = date.getUTCDay() || 7;
+  date.setUTCDate(date.getUTCDate() + 4 - weekday);
+  const weekYear = date.getUTCFullYear();
+  const week = Math.ceil(((date.getTime() - Date.UTC(weekYear, 0, 1)) / 86400000 + 1) / 7);
+  return `${weekYear}-W${pad(week)}`;
+}
+
+/**
+ * Render the date in a text field's format, read from a pattern such as
+ * MM/DD/YYYY or DD.MM.YYYY in its placeholder, or from the shape of its value
+ */
+export function formatForTextField(params: Pick<SetDateParams, 'date' | 'year' | 'month' | 'day'>, hint: string): string {
+  const pattern = /\b(YYYY|MM|DD)([-/. ])(YYYY|MM|DD)\2(YYYY|MM|DD)\b/i.exec(hint);
+  if (pattern) {
+    const parts: Record<string, string> = { YYYY: String(params.year), MM: pad(params.month), DD: pad(params.day) };
+    return [pattern[1], pattern[3], pattern[4]].map((part) => parts[part.toUpperCase()]).join(pattern[2]);
+  }
+  const shaped = /^(\d{1,2})([/.])(\d{1,2})\2(\d{4})$/.exec(hint.trim());
+  if (shaped) {
+    // A value like 31/12/2024 can only be day-first; otherwise assume month-first
+    const dayFirst = Number(shaped[1]) > 12;
+    const [first, second] = dayFirst ? [params.day, params.month] : [params.month, params.day];
+    return `${pad(first)}${shaped[2]}${pad(second)}${shaped[2]}${params.year}`;
+  }
+  return params.date;
+}
+
+function nativeValue(input: HTMLInputElement, params: SetDateParams): string | null {
+  switch (input.type) {
+    case 'date':
+      return params.date;
+    case 'datetime-local':
+      return `${params.date}T${params.time ?? '00:00'}`;
+    case 'month':
+      return `${params.year}-${pad(params.month)}`;
+    case 'week':
+      return isoWeek(params.year, params.month, params.day);
+    case 'time':
+      return params.time;
+    default:
+      return null;
+  }
+}
+
+// ============================================================================
+// Strategies
+// ============================================================================
+
+function injectDate(element: Element, params: SetDateParams): string {
+  if (!(element instanceof HTMLInputElement)) {
+    throw new Error('inject needs an <input>');
+  }
+  if (element.disabled || element.readOnly) {
+    // Read-only inputs are usually filled by their picker; let the widget strategy try
+    throw new Error(`Input is ${element.disabled ? 'disabled' : 'read-only'}`);
+  }
+  const native = nativeValue(element, params);
+  if (element.type === 'time' && native === null) {
+    throw new Error('A time input needs YYYY-MM-DDTHH:MM');
+  }
+  const value = native ?? formatForTextField(params, element.placeholder || element.value);
+
+  element.focus();
+  setNativeValue(element, value);
+  element.dispatchEvent(new Event('input', { bubbles: true, composed: true }));
+  element.dispatchEvent(new Event('change', { bubbles: true }));
+  element.blur();
+
+  // Native inputs drop values outside min/max or in the wrong format
+  if (element.value !== value) {
+    throw new Error(`The input rejected ${value}${element.min || element.max ? ` (allowed ${element.min || '…'} to ${element.max || '…'})` : ''}`);
+  }
+  return value;
+}
+
+function isShown(element: Element): boolean {
+  const rect = element.getBoundingClientRect();
+  const style = getComputedStyle(element);
+  return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+}
+
+function press(element: Element): void {
+  const rect = element.getBoundingClientRect();
+  const init = { bubbles: true, cancelable: true, composed: true, clientX: rect.left + rect.width / 2, clientY: rect.top + rect.height / 2, button: 0 };
+  element.dispatchEvent(new PointerEvent('pointerdown', { ...init, pointerType: 'mouse', isPrimary: true }));
+  element.dispatchEvent(new MouseEvent('mousedown', { ...init, buttons: 1 }));
+  element.dispatchEvent(new PointerEvent('pointerup', { ...init, pointerType: 'mouse', isPrimary: true }));
+  element.dispatchEvent(new MouseEvent('mouseup', init));
+  element.dispatchEvent(new MouseEvent('click', { ...init, detail: 1 }));
+}
+
+const PICKER_SELECTOR = [
+  '[role="dialog"]', '[role="grid"]', '[class*="datepicker" i]', '[class*="date-picker" i]',
+  '[class*="calendar" i]', '.flatpickr-calendar', '.ui-datepicker', '.react-datepicker', '.pika-single',
+].join(', ');
+
+/** The open calendar: a visible picker that mentions a month name */
+function findPicker(): Element | null {
+  const pickers = Array.from(document.querySelectorAll(PICKER_SELECTOR)).filter(isShown);
+  // The outermost match, so navigation buttons next to the grid are inside it
+  return pickers.find((picker) => !pickers.some((other) => other !== picker && other.contains(picker))) ?? null;
+}
+
+/** Which month the picker shows, read from the first "Month YYYY" it mentions */
+function shownMonth(picker: Element): { year: number; month: number } | null {
+  const text = (picker as HTMLElement).innerText ?? picker.textContent ?? '';
+  const names = Array.from({ length: 12 }, (_, i) => new Date(2000, i, 1).toLocaleString('en', { month: 'long' }));
+  const match = new RegExp(`\\b(${names.join('|')}|${names.map((n) => n.slice(0, 3)).join('|')})\\w*\\s+(\\d{4})`, 'i').exec(text);
+  if (!match) {
+    return null;
+  }
+  const month = names.findIndex((name) => name.slice(0, 3).toLowerCase() === match[1].slice(0, 3).toLowerCase()) + 1;
+  return { year: Number(match[2]), month };
+}
+
+function navigationButton(picker: Element, direction: 'next' | 'prev'): Element | null {
+  const words = direction === 'next' ? /next|forward|›|»|→|>/i : /prev|back|‹|«|←|</i;
+  return Array.from(picker.querySelectorAll('button, [role="button"], a, span[class*="arrow" i], [class*="' + direction + '" i]'))
+    .filter(isShown)
+    .find((candidate) => words.test(`${candidate.getAttribute('aria-label') ?? ''} ${candidate.getAttribute('title') ?? ''} ${candidate.className} ${candidate.textContent ?? ''}`.trim()))
+    ?? null;
+}
+
+function dayCell(picker: Element, params: SetDateParams): Element | null {
+  const cells = Array.from(picker.querySelectorAll('[role="gridcell"], td, button, [class*="day" i]'))
+    .filter((cell) => isShown(cell) && cell.children.length <= 1 && cell.textContent?.trim() === String(params.day));
+  const usable = cells.filter((cell) => {
+    const marks = `${cell.className} ${cell.parentElement?.className ?? ''}`;
+    return cell.getAttribute('aria-disabled') !== 'true' && !cell.matches(':disabled')
+      && !/outside|other-month|prev-month|next-month|disabled|muted/i.test(marks);
+  });
+  // Labels like "Monday, March 4, 2024" settle ambiguity when present
+  const labelled = usable.find((cell) => (cell.getAttribute('aria-label') ?? '').includes(params.monthName));
+  return labelled ?? usable[0] ?? null;
+}
+
+async function pickDate(element: Element, params: SetDateParams): Promise<string> {
+  press(element);
+  await settle();
+  const picker = findPicker();
+  if (!picker) {
+    throw new Error('No calendar opened');
+  }
+
+  for (let step = 0; step < MAX_MONTH_STEPS; step++) {
+    const shown = shownMonth(picker);
+    if (!shown) {
+      throw new Error('Could not read the month the calendar shows');
+    }
+    const offset = (params.year - shown.year) * 12 + (params.month - shown.month);
+    if (offset === 0) {
+      break;
+    }
+    const button = navigationButton(picker, offset > 0 ? 'next' : 'prev');
+    if (!button) {
+      throw new Error(`No ${offset > 0 ? 'next' : 'previous'} month button in the calendar`);
+    }
+    press(button);
+    await settle();
+  }
+
+  const cell = dayCell(picker, params);
+  if (!cell) {
+    throw new Error(`Day ${params.day} is not selectable in ${params.monthName} ${params.year}`);
+  }
+  press(cell);
+  await settle();
+  return element instanceof HTMLInputElement ? element.value : (element.textContent ?? '').trim();
+}
+
+// ============================================================================
+// Set-Date Command Handler
+// ============================================================================
+
+export const setDateCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const params = command.params as SetDateParams;
+  const element = document.querySelector(params.selector);
+  if (!element) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+
+  const failures: string[] = [];
+  for (const strategy of params.strategies) {
+    try {
+      const value = strategy === 'inject' ? injectDate(element, params) : await pickDate(element, params);
+      return {
+        success: true,
+        selector: params.selector,
+        date: params.date,
+        strategy,
+        value,
+        inputType: element instanceof HTMLInputElement ? element.type : null,
+        failed: failures,
+      };
+    } catch (error) {
+      failures.push(`${strategy}: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  }
+  throw new Error(`Could not set ${params.date} on ${params.selector} (${failures.join('; ')})`);
+};
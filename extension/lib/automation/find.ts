@@ -0,0 +1,250 @@
+/**
+ * Find-text command: Ctrl+F over the page's rendered text. Matches may span
+ * elements; each is reported with context and its element, and the matches
+ * are marked with the CSS Custom Highlight API so the DOM is left untouched.
+ */
+
+import type { Command, CommandHandler, FindTextParams, ModeConfig } from './types';
+import { cssPath } from './accessibility';
+import { bringIntoView, describeElement } from './scroll';
+
+const HIGHLIGHT = 'agent-browser-find';
+const CURRENT_HIGHLIGHT = 'agent-browser-find-current';
+const HIGHLIGHT_STYLE_ID = 'agent-browser-find-style';
+
+// Matches counted before giving up on an exact total
+const MAX_COUNTED_MATCHES = 10000;
+
+const SKIPPED_TAGS = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'HEAD', 'TITLE']);
+
+interface Segment {
+  node: Text;
+  /** Offset of the node's first character in the joined text */
+  start: number;
+}
+
+// ============================================================================
+// Page Text
+// ============================================================================
+
+/**
+ * The visible text under `root` joined into one string, with a newline
+ * between blocks so matches don't run across them
+ */
+function pageText(root: Element): { text: string; segments: Segment[] } {
+  const displayed = new Map<Element, boolean>();
+  const isDisplayed = (element: Element): boolean => {
+    let result = displayed.get(element);
+    if (result === undefined) {
+      result = !SKIPPED_TAGS.has(element.tagName)
+        && getComputedStyle(element).display !== 'none'
+        && (element.parentElement === null || isDisplayed(element.parentElement));
+      displayed.set(element, result);
+    }
+    return result;
+  };
+  const blocks = new Map<Element, Element>();
+  const blockOf = (element: Element): Element => {
+    let block = blocks.get(element);
+    if (block === undefined) {
+      const display = getComputedStyle(element).display;
+      block = element.parentElement && (display.startsWith('inline') || display === 'contents')
+        ? blockOf(element.parentElement)
+        : element;
+      blocks.set(element, block);
+    }
+    return block;
+  };
+
+  const segments: Segment[] = [];
+  let text = '';
+  let previousBlock: Element | null = null;
+  const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT);
+  for (let node = walker.nextNode() as Text | null; node; node = walker.nextNode() as Text | null) {
+    const parent = node.parentElement;
+    if (!parent || !node.data || !isDisplayed(parent) || getComputedStyle(parent).visibility === 'hidden') {
+      continue;
+    }
+    const block = blockOf(parent);
+    if (previousBlock && block !== previousBlock) {
+      text += '\n';
+    }
+    previousBlock = block;
+    segments.push({ node, start: text.length });
+    text += node.data;
+  }
+  return { text, segments };
+}
+
+/** Text node and offset for a position in the joined text; block separators snap forward (start) or back (end) */
+function locate(segments: Segment[], offset: number, isEnd: boolean): { node: Text; offset: number } {
+  let low = 0;
+  let high = segments.length - 1;
+  while (low < high) {
+    const mid = Math.ceil((low + high) / 2);
+    if (segments[mid].start <= offset) {
+      low = mid;
+    } else {
+      high = mid - 1;
+    }
+  }
+  let segment = segments[low];
+  if (offset - segment.start > segment.node.length && !isEnd && low + 1 < segments.length) {
+    segment = segments[low + 1];
+  }
+  if (isEnd && offset === segment.start && low > 0) {
+    // An end at a node's first character belongs to the node before
+    segment = segments[low - 1];
+  }
+  return { node: segment.node, offset: Math.max(0, Math.min(offset - segment.start, segment.node.length)) };
+}
+
+function buildPattern(params: FindTextParams): RegExp {
+  let source = params.regex
+    ? params.query!
+    // Any run of whitespace matches a space, as in the browser's find bar
+    : params.query!.replace(/[.*+?^${}()|[\]\\]/g, '\\$&').replace(/\s+/g, '\\s+');
+  if (params.wholeWord) {
+    source = `\\b(?:${source})\\b`;
+  }
+  return new RegExp(source, params.caseSensitive ? 'g' : 'gi');
+}
+
+// ============================================================================
+// Highlights
+// ============================================================================
+
+function clearHighlights(): void {
+  if (typeof CSS !== 'undefined' && 'highlights' in CSS) {
+    CSS.highlights.delete(HIGHLIGHT);
+    CSS.highlights.delete(CURRENT_HIGHLIGHT);
+  }
+}
+
+/** Mark the ranges on the page; false where the Highlight API is missing */
+function highlight(ranges: Range[], current: Range | null): boolean {
+  if (typeof Highlight === 'undefined' || !('highlights' in CSS)) {
+    return false;
+  }
+  if (!document.getElementById(HIGHLIGHT_STYLE_ID)) {
+    const style = document.createElement('style');
+    style.id = HIGHLIGHT_STYLE_ID;
+    style.textContent = `::highlight(${HIGHLIGHT}) { background-color: #ffeb3b; color: #000; }\n`
+      + `::highlight(${CURRENT_HIGHLIGHT}) { background-color: #ff9632; color: #000; }`;
+    (document.head ?? document.documentElement).appendChild(style);
+  }
+  CSS.highlights.set(HIGHLIGHT, new Highlight(...ranges));
+  if (current) {
+    CSS.highlights.set(CURRENT_HIGHLIGHT, new Highlight(current));
+  }
+  return true;
+}
+
+function inViewport(rect: DOMRect): boolean {
+  return rect.width > 0 && rect.bottom > 0 && rect.right > 0 && rect.top < window.innerHeight && rect.left < window.innerWidth;
+}
+
+// ============================================================================
+// Find Text Command Handler
+// ============================================================================
+
+export const findTextCommand: CommandHandler = async (command: Command, config: ModeConfig) => {
+  const params = command.params as FindTextParams;
+
+  clearHighlights();
+  if (params.clear) {
+    return { success: true, cleared: true };
+  }
+
+  let root: Element = document.body ?? document.documentElement;
+  if (params.selector) {
+    const element = document.querySelector(params.selector);
+    if (!element) {
+      throw new Error(`Element not found: ${params.selector}`);
+    }
+    root = element;
+  }
+
+  const pattern = buildPattern(params);
+  const { text, segments } = pageText(root);
+  const ranges: Range[] = [];
+  const matches: Record<string, any>[] = [];
+  let countCapped = false;
+
+  for (let match = pattern.exec(text); match && segments.length; match = pattern.exec(text)) {
+    if (match[0].length === 0) {
+      // Empty matches (e.g. /x*/) would never advance
+      pattern.lastIndex++;
+      continue;
+    }
+    if (ranges.length === MAX_COUNTED_MATCHES) {
+      countCapped = true;
+      break;
+    }
+    const start = match.index;
+    const end = start + match[0].length;
+    const from = locate(segments, start, false);
+    const to = locate(segments, end, true);
+    const range = document.createRange();
+    range.setStart(from.node, from.offset);
+    range.setEnd(to.node, to.offset);
+    ranges.push(range);
+
+    if (matches.length < params.maxResults) {
+      const element = from.node.parentElement!;
+      const squash = (s: string) => s.replace(/\s+/g, ' ');
+      matches.push({
+        index: ranges.length - 1,
+        text: squash(match[0]),
+        before: squash(text.slice(Math.max(0, start - params.contextChars), start)).trimStart(),
+        after: squash(text.slice(end, end + params.contextChars)).trimEnd(),
+        element: describeElement(element),
+        selector: cssPath(element),
+        inViewport: inViewport(range.getBoundingClientRect()),
+      });
+    }
+  }
+
+  let current: Record<string, any> | null = null;
+  const scrollTo = params.scrollTo ?? null;
+  if (scrollTo !== null) {
+    const range = ranges[scrollTo];
+    if (!range) {
+      throw new Error(`scrollTo ${scrollTo} is out of range: ${ranges.length} match${ranges.length === 1 ? '' : 'es'} for ${JSON.stringify(params.query)}`);
+    }
+    const element = range.startContainer.parentElement!;
+    await bringIntoView(element, {
+      block: 'nearest',
+      behavior: config.speed.skipAnimations ? 'auto' : 'smooth',
+      detectSticky: true,
+    });
+    // A long paragraph can be in view while the match itself is not
+    let rect = range.getBoundingClientRect();
+    if (rect.top < 0 || rect.bottom > window.innerHeight) {
+      window.scrollBy({ top: rect.top - (window.innerHeight - rect.height) / 2 });
+      rect = range.getBoundingClientRect();
+    }
+    current = {
+      index: scrollTo,
+      element: describeElement(element),
+      bounds: { x: rect.x, y: rect.y, width: rect.width, height: rect.height },
+      inViewport: inViewport(rect),
+    };
+  }
+
+  const highlighted = params.highlight && ranges.length > 0
+    ? highlight(ranges, scrollTo !== null ? ranges[scrollTo] : null)
+    : false;
+
+  return {
+    success: true,
+    query: params.query,
+    total: ranges.length,
+    countCapped,
+    returned: matches.length,
+    truncated: ranges.length > matches.length,
+    matches,
+    current,
+    highlighted,
+  };
+};
@@ -0,0 +1,141 @@
+/**
+ * Focus, blur and get-focused-element commands
+ *
+ * element.focus() and blur() only fire focus events while the page's window
+ * has focus, which it doesn't when the tab is in the background or DevTools
+ * is focused. When the browser fires none, the events are dispatched here so
+ * focus-triggered widgets and on-blur validation still run.
+ */
+
+import type { BlurParams, Command, CommandHandler, FocusParams, ModeConfig } from './types';
+import { cssPath } from './accessibility';
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+function findElement(selector: string): HTMLElement {
+  const element = document.querySelector(selector);
+  if (!element) {
+    throw new Error(`Element not found: ${selector}`);
+  }
+  if (!(element instanceof HTMLElement || element instanceof SVGElement)) {
+    throw new Error(`Element can't take focus: ${selector}`);
+  }
+  return element as HTMLElement;
+}
+
+/** Run a focus change; dispatch `type` and `bubblingType` if the browser didn't */
+function withFocusEvents(
+  element: HTMLElement,
+  type: 'focus' | 'blur',
+  bubblingType: 'focusin' | 'focusout',
+  relatedTarget: Element | null,
+  change: () => void,
+): boolean {
+  let fired = false;
+  const observe = () => { fired = true; };
+  element.addEventListener(type, observe, { once: true });
+  try {
+    change();
+  } finally {
+    element.removeEventListener(type, observe);
+  }
+  if (!fired) {
+    element.dispatchEvent(new FocusEvent(type, { relatedTarget }));
+    element.dispatchEvent(new FocusEvent(bubblingType, { bubbles: true, composed: true, relatedTarget }));
+  }
+  return fired;
+}
+
+export function describeFocused(element: Element | null): Record<string, any> | null {
+  if (!element || element === document.body || element === document.documentElement) {
+    return null;
+  }
+  const field = element as HTMLInputElement;
+  const isPassword = element instanceof HTMLInputElement && element.type === 'password';
+  return {
+    tag: element.tagName.toLowerCase(),
+    id: element.id || null,
+    name: element.getAttribute('name'),
+    type: element.getAttribute('type'),
+    value: isPassword ? null : (typeof field.value === 'string' ? field.value : null),
+    contentEditable: (element as HTMLElement).isContentEditable ?? false,
+    selector: cssPath(element),
+  };
+}
+
+/** The focused element, descending into open shadow roots and same-origin iframes */
+function deepActiveElement(): Element | null {
+  let active: Element | null = document.activeElement;
+  while (active) {
+    const inner: Element | null | undefined = active.shadowRoot?.activeElement
+      ?? (active instanceof HTMLIFrameElement ? safeFrameActive(active) : null);
+    if (!inner) {
+      break;
+    }
+    active = inner;
+  }
+  return active;
+}
+
+function safeFrameActive(frame: HTMLIFrameElement): Element | null {
+  try {
+    const inner = frame.contentDocument?.activeElement ?? null;
+    return inner === frame.contentDocument?.body ? null : inner;
+  } catch {
+    // Cross-origin frame
+    return null;
+  }
+}
+
+// ============================================================================
+// Command Handlers
+// ============================================================================
+
+export const focusCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector } = command.params as FocusParams;
+  const element = findElement(selector);
+  const previous = document.activeElement === element ? null : document.activeElement;
+
+  const native = withFocusEvents(element, 'focus', 'focusin', previous, () => element.focus());
+  if (document.activeElement !== element) {
+    throw new Error(`Element is not focusable: ${selector}`);
+  }
+
+  return {
+    success: true,
+    selector,
+    nativeEvents: native,
+    element: describeFocused(element),
+  };
+};
+
+export const blurCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector } = command.params as BlurParams;
+  const element = selector ? findElement(selector) : (document.activeElement as HTMLElement | null);
+
+  if (!element || element === document.body) {
+    return { success: true, blurred: null };
+  }
+  const wasFocused = document.activeElement === element;
+  const described = describeFocused(element);
+  const native = withFocusEvents(element, 'blur', 'focusout', null, () => element.blur());
+
+  return {
+    success: true,
+    blurred: described,
+    wasFocused,
+    nativeEvents: native,
+  };
+};
+
+export const getFocusedElementCommand: CommandHandler = async (_command: Command, _config: ModeConfig) => {
+  const focused = describeFocused(deepActiveElement());
+  return {
+    success: true,
+    focused: focused !== null,
+    element: focused,
+    documentHasFocus: document.hasFocus(),
+  };
+};
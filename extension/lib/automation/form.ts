@@ -0,0 +1,169 @@
+/**
+ * Fill form command: fills several fields in one round trip, reporting each
+ */
+
+import type { Command, CommandHandler, FillFormField, FillFormParams, ModeConfig } from './types';
+import { typeCommand } from './type';
+import { selectCommand } from './select';
+import { applyLocator, describeLocator } from './locator';
+
+type FieldKind = 'text' | 'checkbox' | 'radio' | 'select';
+
+// ============================================================================
+// Field Detection
+// ============================================================================
+
+function fieldKind(element: Element): FieldKind | null {
+  if (element instanceof HTMLSelectElement) {
+    return 'select';
+  }
+  if (element instanceof HTMLInputElement) {
+    if (element.type === 'checkbox') {
+      return 'checkbox';
+    }
+    if (element.type === 'radio') {
+      return 'radio';
+    }
+    // Buttons, files and the like can't be filled with a value
+    const fillable = ['text', 'email', 'password', 'search', 'tel', 'url', 'number', 'date', 'datetime-local', 'month', 'week', 'time', 'color', 'range'];
+    return fillable.includes(element.type) ? 'text' : null;
+  }
+  if (element instanceof HTMLTextAreaElement || (element instanceof HTMLElement && element.isContentEditable)) {
+    return 'text';
+  }
+  return null;
+}
+
+/** The radio in `element`'s group whose value or label is `wanted` */
+function findRadio(element: HTMLInputElement, wanted: string): HTMLInputElement {
+  const group = element.name
+    ? Array.from(document.querySelectorAll<HTMLInputElement>('input[type="radio"]'))
+        .filter((radio) => radio.name === element.name && radio.form === element.form)
+    : [element];
+  const match = group.find((radio) => radio.value === wanted)
+    ?? group.find((radio) => Array.from(radio.labels ?? []).some((label) => label.innerText.trim() === wanted.trim()));
+  if (!match) {
+    const values = group.map((radio) => JSON.stringify(radio.value)).join(', ');
+    throw new Error(`No radio button with value or label ${JSON.stringify(wanted)}. Available: ${values}`);
+  }
+  return match;
+}
+
+/** Flip a checkbox or radio by clicking it, as a user would, so page handlers run */
+function setChecked(input: HTMLInputElement, checked: boolean): void {
+  if (input.checked === checked) {
+    return;
+  }
+  input.scrollIntoView({ behavior: 'auto', block: 'center' });
+  input.click();
+  if (input.checked !== checked) {
+    // The page cancelled the click; set the state directly
+    input.checked = checked;
+    input.dispatchEvent(new Event('input', { bubbles: true }));
+    input.dispatchEvent(new Event('change', { bubbles: true }));
+  }
+}
+
+// ============================================================================
+// Filling
+// ============================================================================
+
+async function fillField(field: FillFormField & { selector: string }, command: Command, config: ModeConfig): Promise<{ type: FieldKind; value: unknown }> {
+  const element = document.querySelector(field.selector);
+  if (!element) {
+    throw new Error(`Element not found: ${field.selector}`);
+  }
+  const kind = fieldKind(element);
+  if (!kind) {
+    throw new Error(`<${element.tagName.toLowerCase()}> is not a fillable form field`);
+  }
+  if (field.type !== 'auto' && field.type !== kind) {
+    throw new Error(`Expected a ${field.type} field, found a ${kind} field`);
+  }
+  if ((element as HTMLInputElement).disabled) {
+    throw new Error('Field is disabled');
+  }
+
+  const { value } = field;
+  switch (kind) {
+    case 'text': {
+      if (typeof value !== 'string') {
+        throw new Error('A text field needs a string value');
+      }
+      if ((element as HTMLInputElement).readOnly) {
+        throw new Error('Field is read-only');
+      }
+      await typeCommand({ ...command, params: { selector: field.selector, text: value, clear: field.clear ?? true } }, config);
+      const filled = element instanceof HTMLElement && element.isContentEditable ? element.innerText : (element as HTMLInputElement).value;
+      return { type: kind, value: filled };
+    }
+
+    case 'checkbox': {
+      if (typeof value !== 'boolean') {
+        throw new Error('A checkbox needs true or false');
+      }
+      setChecked(element as HTMLInputElement, value);
+      return { type: kind, value: (element as HTMLInputElement).checked };
+    }
+
+    case 'radio': {
+      if (value === false || (typeof value !== 'string' && value !== true)) {
+        throw new Error("A radio button needs the value to pick, or true; it can't be unchecked");
+      }
+      const radio = value === true ? (element as HTMLInputElement) : findRadio(element as HTMLInputElement, value);
+      setChecked(radio, true);
+      return { type: kind, value: radio.value };
+    }
+
+    case 'select': {
+      if (typeof value === 'boolean') {
+        throw new Error('A select needs an option or a list of options');
+      }
+      const wanted = Array.isArray(value) ? value : [value];
+      // Match option values when all of them are values, visible labels otherwise
+      const values = new Set(Array.from((element as HTMLSelectElement).options).map((option) => option.value));
+      const by = wanted.every((option) => values.has(option)) ? 'value' : 'label';
+      const result = await selectCommand({ ...command, params: { selector: field.selector, by, options: wanted } }, config);
+      return { type: kind, value: result.selected.map((option: { value: string }) => option.value) };
+    }
+  }
+}
+
+// ============================================================================
+// Fill Form Command Handler
+// ============================================================================
+
+export const fillFormCommand: CommandHandler = async (command: Command, config: ModeConfig) => {
+  const params = command.params as FillFormParams;
+
+  const fields = [];
+  for (const field of params.fields) {
+    const target = field.locator ? describeLocator(field.locator) : field.selector;
+    let release = () => {};
+    try {
+      // A located field is addressed through a temporary attribute selector
+      const resolved = { ...field, selector: field.selector ?? '' };
+      if (field.locator) {
+        ({ release } = applyLocator(resolved));
+      }
+      const filled = await fillField(resolved, command, config);
+      fields.push({ selector: target, success: true, ...filled });
+    } catch (error: any) {
+      fields.push({ selector: target, success: false, error: error.message || String(error) });
+      if (params.stopOnError) {
+        break;
+      }
+    } finally {
+      release();
+    }
+  }
+
+  const failed = fields.filter((field) => !field.success).length;
+  return {
+    success: failed === 0,
+    filled: fields.length - failed,
+    failed,
+    skipped: params.fields.length - fields.length,
+    fields,
+  };
+};
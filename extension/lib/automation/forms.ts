@@ -0,0 +1,282 @@
+/**
+ * List-forms command: every form on the page with its fields and submit
+ * controls, described so they can be passed to fill_form as they are:
+ * selectors that address one element, the fill type, labels and options.
+ * Fields outside any <form>, common in single-page apps, are listed as
+ * one extra form with `formless` set.
+ */
+
+import type { Command, CommandHandler, ListFormsParams } from './types';
+import { cssPath, isHidden } from './accessibility';
+import { labelText } from './locator';
+
+// Fields listed per form
+const MAX_FIELDS = 200;
+
+// Longest label or button text returned
+const MAX_TEXT_LENGTH = 200;
+
+const FIELD_SELECTOR = 'input, select, textarea, [contenteditable=""], [contenteditable="true"]';
+const SUBMIT_SELECTOR = 'button, input[type="submit"], input[type="image"]';
+
+// Input types fill_form types into; the rest aren't fillable with a value
+const TEXT_TYPES = new Set(['text', 'email', 'password', 'search', 'tel', 'url', 'number', 'date', 'datetime-local', 'month', 'week', 'time', 'color', 'range']);
+
+type FieldElement = HTMLInputElement | HTMLSelectElement | HTMLTextAreaElement | HTMLElement;
+
+const squash = (text: string | null | undefined) => (text ?? '').replace(/\s+/g, ' ').trim().slice(0, MAX_TEXT_LENGTH);
+
+// ============================================================================
+// Field Description
+// ============================================================================
+
+/** A selector for exactly this element: its id, its name, or its CSS path */
+function uniqueSelector(element: Element): string {
+  const tag = element.tagName.toLowerCase();
+  if (element.id && document.querySelectorAll(`#${CSS.escape(element.id)}`).length === 1) {
+    return `#${CSS.escape(element.id)}`;
+  }
+  const name = element.getAttribute('name');
+  if (name) {
+    const selector = `${tag}[name="${name.replace(/["\\]/g, '\\$&')}"]`;
+    if (document.querySelectorAll(selector).length === 1) {
+      return selector;
+    }
+  }
+  return cssPath(element);
+}
+
+function isShown(element: Element): boolean {
+  if (isHidden(element)) {
+    return false;
+  }
+  const rect = element.getBoundingClientRect();
+  return rect.width > 0 || rect.height > 0;
+}
+
+/** The field's label, falling back to a wrapping label, its placeholder or title */
+function fieldLabel(element: Element): string {
+  return squash(labelText(element))
+    || squash(element.closest('label')?.innerText)
+    || squash(element.getAttribute('placeholder'))
+    || squash(element.getAttribute('title'));
+}
+
+/** The fill_form type for the element, or null when fill_form can't set it */
+function fillType(element: FieldElement): 'text' | 'checkbox' | 'radio' | 'select' | null {
+  if (element instanceof HTMLSelectElement) {
+    return 'select';
+  }
+  if (element instanceof HTMLInputElement) {
+    if (element.type === 'checkbox' || element.type === 'radio') {
+      return element.type;
+    }
+    return TEXT_TYPES.has(element.type) ? 'text' : null;
+  }
+  return 'text';
+}
+
+function describeField(element: FieldElement, params: ListFormsParams): Record<string, any> {
+  const input = element instanceof HTMLInputElement ? element : null;
+  const type = input ? input.type
+    : element instanceof HTMLSelectElement ? (element.multiple ? 'select-multiple' : 'select')
+    : element instanceof HTMLTextAreaElement ? 'textarea'
+    : 'contenteditable';
+  const field: Record<string, any> = {
+    selector: uniqueSelector(element),
+    name: element.getAttribute('name'),
+    id: element.id || null,
+    type,
+    fillType: fillType(element),
+    label: fieldLabel(element),
+    required: 'required' in element ? (element as HTMLInputElement).required : element.getAttribute('aria-required') === 'true',
+    disabled: 'disabled' in element ? (element as HTMLInputElement).disabled : element.getAttribute('aria-disabled') === 'true',
+  };
+  if (input || element instanceof HTMLTextAreaElement) {
+    const control = element as HTMLInputElement | HTMLTextAreaElement;
+    field.readOnly = control.readOnly;
+    field.placeholder = control.placeholder || null;
+    field.autocomplete = control.autocomplete || null;
+  }
+  if (input && (input.type === 'checkbox' || input.type === 'radio')) {
+    field.value = input.value;
+    field.checked = input.checked;
+  } else if (element instanceof HTMLSelectElement) {
+    const options = Array.from(element.options);
+    field.value = element.multiple ? options.filter((option) => option.selected).map((option) => option.value) : element.value;
+    field.options = options.slice(0, params.maxOptions).map((option) => ({
+      value: option.value,
+      text: squash(option.text),
+      selected: option.selected,
+      disabled: option.disabled,
+    }));
+    field.optionCount = options.length;
+  } else if (input?.type === 'password') {
+    // Report whether a password is filled in, never the password
+    field.value = null;
+    field.filled = input.value !== '';
+  } else if (input?.type === 'file') {
+    field.value = Array.from(input.files ?? []).map((file) => file.name);
+  } else {
+    field.value = input || element instanceof HTMLTextAreaElement
+      ? (element as HTMLInputElement).value
+      : squash(element.innerText);
+  }
+  if (input) {
+    const constraints: Record<string, string | number> = {};
+    for (const key of ['min', 'max', 'step', 'pattern']) {
+      const value = input.getAttribute(key);
+      if (value !== null) {
+        constraints[key] = value;
+      }
+    }
+    if (input.maxLength >= 0) {
+      constraints.maxLength = input.maxLength;
+    }
+    if (Object.keys(constraints).length > 0) {
+      field.constraints = constraints;
+    }
+  }
+  if (params.includeHidden) {
+    field.visible = isShown(element);
+  }
+  return field;
+}
+
+/** Radio buttons sharing a name, as one field whose options are the buttons */
+function describeRadioGroup(radios: HTMLInputElement[], params: ListFormsParams): Record<string, any> {
+  const field = describeField(radios[0], params);
+  const checked = radios.find((radio) => radio.checked);
+  field.label = squash(radios[0].closest('fieldset')?.querySelector('legend')?.innerText) || field.label;
+  field.value = checked?.value ?? null;
+  delete field.checked;
+  field.required = radios.some((radio) => radio.required);
+  field.options = radios.map((radio) => ({
+    value: radio.value,
+    text: fieldLabel(radio),
+    selected: radio.checked,
+    disabled: radio.disabled,
+    selector: uniqueSelector(radio),
+  }));
+  field.optionCount = radios.length;
+  return field;
+}
+
+// ============================================================================
+// Forms
+// ============================================================================
+
+function isSubmitControl(element: Element): boolean {
+  if (element instanceof HTMLButtonElement) {
+    // A button's type defaults to submit
+    return element.type === 'submit';
+  }
+  return element instanceof HTMLInputElement && (element.type === 'submit' || element.type === 'image');
+}
+
+function describeFields(elements: FieldElement[], params: ListFormsParams): { fields: Record<string, any>[]; truncated: boolean } {
+  const entries: FieldElement[] = [];
+  const radioGroups = new Map<string, HTMLInputElement[]>();
+  const kept = elements.filter((element) => {
+    if (element instanceof HTMLInputElement && ['submit', 'image', 'button', 'reset'].includes(element.type)) {
+      return false;
+    }
+    if (params.includeHidden) {
+      return true;
+    }
+    return !(element instanceof HTMLInputElement && element.type === 'hidden') && isShown(element);
+  });
+  for (const element of kept) {
+    if (element instanceof HTMLInputElement && element.type === 'radio' && element.name) {
+      const group = radioGroups.get(element.name);
+      if (group) {
+        group.push(element);
+        continue;
+      }
+      radioGroups.set(element.name, [element]);
+    }
+    entries.push(element);
+  }
+  const fields = entries.slice(0, MAX_FIELDS).map((element) =>
+    element instanceof HTMLInputElement && element.type === 'radio' && element.name
+      ? describeRadioGroup(radioGroups.get(element.name)!, params)
+      : describeField(element, params));
+  return { fields, truncated: entries.length > MAX_FIELDS };
+}
+
+function describeSubmit(element: HTMLButtonElement | HTMLInputElement): Record<string, any> {
+  return {
+    selector: uniqueSelector(element),
+    text: squash(element instanceof HTMLInputElement ? element.value || element.alt : element.innerText) || squash(element.getAttribute('aria-label')),
+    type: element instanceof HTMLInputElement ? element.type : 'button',
+    name: element.getAttribute('name'),
+    disabled: element.disabled,
+  };
+}
+
+// ============================================================================
+// List Forms Command Handler
+// ============================================================================
+
+export const listFormsCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as ListFormsParams;
+  let root: Element = document.body ?? document.documentElement;
+  if (params.selector) {
+    const element = document.querySelector(params.selector);
+    if (!element) {
+      throw new Error(`Element not found: ${params.selector}`);
+    }
+    root = element;
+  }
+
+  const forms = root instanceof HTMLFormElement ? [root] : Array.from(root.querySelectorAll('form'));
+  const described = forms
+    .filter((form) => params.includeHidden || isShown(form))
+    .map((form) => {
+      // form.elements includes controls outside the form that name it with form="id"
+      const elements = Array.from(form.elements).filter((element) => element.matches(FIELD_SELECTOR)) as FieldElement[];
+      const editable = Array.from(form.querySelectorAll<HTMLElement>('[contenteditable=""], [contenteditable="true"]'));
+      const { fields, truncated } = describeFields([...elements, ...editable], params);
+      const submits = (Array.from(form.elements) as Element[]).filter(isSubmitControl) as Array<HTMLButtonElement | HTMLInputElement>;
+      return {
+        selector: uniqueSelector(form),
+        id: form.id || null,
+        name: form.getAttribute('name'),
+        action: form.getAttribute('action') !== null ? form.action : null,
+        method: form.method,
+        formless: false,
+        fields,
+        fieldsTruncated: truncated,
+        submit: submits.filter((submit) => params.includeHidden || isShown(submit)).map(describeSubmit),
+      };
+    });
+
+  if (!(root instanceof HTMLFormElement)) {
+    const formless = (Array.from(root.querySelectorAll(FIELD_SELECTOR)) as FieldElement[])
+      .filter((element) => element.closest('form') === null && !('form' in element && (element as HTMLInputElement).form));
+    const { fields, truncated } = describeFields(formless, params);
+    if (fields.length > 0) {
+      // Without a form, any button next to the fields may be the one that sends them
+      const buttons = Array.from(root.querySelectorAll<HTMLButtonElement | HTMLInputElement>(SUBMIT_SELECTOR))
+        .filter((button) => button.closest('form') === null && button.form === null && isShown(button)
+          && !(button instanceof HTMLInputElement && button.type !== 'submit' && button.type !== 'image'));
+      described.push({
+        selector: root === document.body ? 'body' : uniqueSelector(root),
+        id: null,
+        name: null,
+        action: null,
+        method: 'get',
+        formless: true,
+        fields,
+        fieldsTruncated: truncated,
+        submit: buttons.slice(0, 20).map(describeSubmit),
+      });
+    }
+  }
+
+  return {
+    success: true,
+    forms: described.map((form, index) => ({ index, ...form })),
+    count: described.length,
+  };
+};
@@ -0,0 +1,91 @@
+/**
+ * Hover command implementation
+ *
+ * Moves a synthetic pointer onto an element: the element the pointer left
+ * gets pointerout/pointerleave and mouseout/mouseleave, the new one gets
+ * pointerover/pointerenter, mouseover/mouseenter and a move. CSS :hover
+ * doesn't follow synthetic events, so menus that open on :hover alone won't
+ * open; those built on mouse events (most JavaScript menus) do.
+ */
+
+import type { Command, CommandHandler, HoverParams, ModeConfig } from './types';
+import { bringIntoView } from './scroll';
+
+// The element the synthetic pointer is over, so the next hover can leave it
+let hovered: Element | null = null;
+
+function pointerInit(x: number, y: number, relatedTarget: Element | null): PointerEventInit {
+  return {
+    bubbles: true,
+    cancelable: true,
+    composed: true,
+    clientX: x,
+    clientY: y,
+    screenX: window.screenX + x,
+    screenY: window.screenY + y,
+    relatedTarget,
+    pointerId: 1,
+    pointerType: 'mouse',
+    isPrimary: true,
+  };
+}
+
+/** Dispatch a pointer event and its mouse counterpart */
+function dispatchPair(target: Element, pointerType: string, mouseType: string, init: PointerEventInit, bubbles = true): void {
+  target.dispatchEvent(new PointerEvent(pointerType, { ...init, bubbles, cancelable: bubbles }));
+  target.dispatchEvent(new MouseEvent(mouseType, { ...init, bubbles, cancelable: bubbles }));
+}
+
+export const hoverCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector, position } = command.params as HoverParams;
+  if (!selector) {
+    throw new Error('Selector is required for hover');
+  }
+  const element = document.querySelector(selector);
+  if (!element) {
+    throw new Error(`Element not found: ${selector}`);
+  }
+
+  if (!position) {
+    const view = await bringIntoView(element);
+    if (view.visibleRatio === 0) {
+      throw new Error(`Element could not be scrolled into view: ${view.reason}`);
+    }
+  }
+  const rect = element.getBoundingClientRect();
+  const x = rect.left + (position ? position.x : rect.width / 2);
+  const y = rect.top + (position ? position.y : rect.height / 2);
+
+  // Events go to what is on top at that point, as a real pointer's would
+  const target = document.elementFromPoint(x, y) ?? element;
+  const previous = hovered && hovered.isConnected ? hovered : null;
+
+  if (previous !== target) {
+    if (previous) {
+      dispatchPair(previous, 'pointerout', 'mouseout', pointerInit(x, y, target));
+      // leave and enter don't bubble; each ancestor not shared with the new target gets one
+      for (let node: Element | null = previous; node && !node.contains(target); node = node.parentElement) {
+        dispatchPair(node, 'pointerleave', 'mouseleave', pointerInit(x, y, target), false);
+      }
+    }
+    dispatchPair(target, 'pointerover', 'mouseover', pointerInit(x, y, previous));
+    const entered: Element[] = [];
+    for (let node: Element | null = target; node && !(previous && node.contains(previous)); node = node.parentElement) {
+      entered.unshift(node);
+    }
+    for (const node of entered) {
+      dispatchPair(node, 'pointerenter', 'mouseenter', pointerInit(x, y, previous), false);
+    }
+  }
+  dispatchPair(target, 'pointermove', 'mousemove', pointerInit(x, y, null));
+  hovered = target;
+
+  return {
+    success: true,
+    selector,
+    x,
+    y,
+    target: target.tagName.toLowerCase(),
+    coveredBy: element.contains(target) ? null : target.tagName.toLowerCase(),
+  };
+};
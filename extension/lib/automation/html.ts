@@ -0,0 +1,98 @@
+/**
+ * Get-HTML command implementation: outerHTML of an element or the whole
+ * document, optionally stripped of scripts and styles
+ */
+
+import type { Command, CommandHandler, GetHtmlParams } from './types';
+
+// ============================================================================
+// Sanitizing
+// ============================================================================
+
+function strip(root: Element, params: GetHtmlParams): void {
+  const remove = (selector: string) => {
+    root.querySelectorAll(selector).forEach((element) => element.remove());
+  };
+
+  if (params.stripScripts) {
+    remove('script, noscript, template');
+    for (const element of [root, ...Array.from(root.querySelectorAll('*'))]) {
+      for (const attribute of Array.from(element.attributes)) {
+        const name = attribute.name.toLowerCase();
+        if (name.startsWith('on') || (name === 'href' && /^\s*javascript:/i.test(attribute.value))) {
+          element.removeAttribute(attribute.name);
+        }
+      }
+    }
+  }
+
+  if (params.stripStyles) {
+    remove('style, link[rel~="stylesheet"]');
+    root.querySelectorAll('[style]').forEach((element) => element.removeAttribute('style'));
+  }
+
+  if (params.stripComments) {
+    const walker = document.createTreeWalker(root, NodeFilter.SHOW_COMMENT);
+    const comments: Node[] = [];
+    while (walker.nextNode()) {
+      comments.push(walker.currentNode);
+    }
+    comments.forEach((comment) => comment.parentNode?.removeChild(comment));
+  }
+}
+
+/** Live form state isn't in the markup; copy it in, except passwords */
+function syncFormState(source: Element, copy: Element): void {
+  const sources = source.querySelectorAll('input, textarea, select');
+  const copies = copy.querySelectorAll('input, textarea, select');
+  sources.forEach((field, index) => {
+    const target = copies[index];
+    if (field instanceof HTMLInputElement && target instanceof HTMLInputElement) {
+      if (field.type === 'password') {
+        target.removeAttribute('value');
+      } else if (field.type === 'checkbox' || field.type === 'radio') {
+        target.toggleAttribute('checked', field.checked);
+      } else {
+        target.setAttribute('value', field.value);
+      }
+    } else if (field instanceof HTMLTextAreaElement && target instanceof HTMLTextAreaElement) {
+      target.textContent = field.value;
+    } else if (field instanceof HTMLSelectElement && target instanceof HTMLSelectElement) {
+      Array.from(target.options).forEach((option, i) => option.toggleAttribute('selected', field.options[i]?.selected ?? false));
+    }
+  });
+}
+
+// ============================================================================
+// Get-HTML Command Handler
+// ============================================================================
+
+export const getHtmlCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as GetHtmlParams;
+  const source = params.selector ? document.querySelector(params.selector) : document.documentElement;
+  if (!source) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+
+  // Work on a copy so stripping never touches the live page
+  const copy = source.cloneNode(true) as Element;
+  syncFormState(source, copy);
+  strip(copy, params);
+
+  let html = copy.outerHTML;
+  if (!params.selector && document.doctype) {
+    html = `<!DOCTYPE ${document.doctype.name}>\n${html}`;
+  }
+  const length = html.length;
+  const truncated = params.maxLength !== undefined && length > params.maxLength;
+
+  return {
+    success: true,
+    url: location.href,
+    title: document.title,
+    selector: params.selector ?? null,
+    html: truncated ? html.slice(0, params.maxLength) : html,
+    length,
+    truncated,
+  };
+};
@@ -0,0 +1,254 @@
+/**
+ * Press-key command implementation
+ *
+ * Synthetic key events are untrusted, so the browser performs none of a key's
+ * default actions for them. After dispatching keydown/keypress/keyup, the
+ * common defaults (submitting a form on Enter, moving focus on Tab, toggling
+ * on Space, stepping through a <select>, scrolling) are reproduced here unless
+ * a page handler called preventDefault().
+ */
+
+import type { Command, CommandHandler, ModeConfig, PressKeyParams } from './types';
+import { getRandomDelay } from './mode-config';
+
+// ============================================================================
+// Key Definitions
+// ============================================================================
+
+const NAMED_KEYS: Record<string, { code: string; keyCode: number }> = {
+  Enter: { code: 'Enter', keyCode: 13 },
+  Tab: { code: 'Tab', keyCode: 9 },
+  Escape: { code: 'Escape', keyCode: 27 },
+  Backspace: { code: 'Backspace', keyCode: 8 },
+  Delete: { code: 'Delete', keyCode: 46 },
+  ' ': { code: 'Space', keyCode: 32 },
+  ArrowUp: { code: 'ArrowUp', keyCode: 38 },
+  ArrowDown: { code: 'ArrowDown', keyCode: 40 },
+  ArrowLeft: { code: 'ArrowLeft', keyCode: 37 },
+  ArrowRight: { code: 'ArrowRight', keyCode: 39 },
+  Home: { code: 'Home', keyCode: 36 },
+  End: { code: 'End', keyCode: 35 },
+  PageUp: { code: 'PageUp', keyCode: 33 },
+  PageDown: { code: 'PageDown', keyCode: 34 },
+};
+
+function keyInit(key: string, modifiers: PressKeyParams['modifiers']): KeyboardEventInit {
+  const named = NAMED_KEYS[key];
+  const fKey = /^F(\d{1,2})$/.exec(key);
+  const upper = key.toUpperCase();
+  const code = named?.code
+    ?? (fKey ? key : /^[A-Z]$/.test(upper) ? `Key${upper}` : /^\d$/.test(key) ? `Digit${key}` : '');
+  const keyCode = named?.keyCode ?? (fKey ? 111 + Number(fKey[1]) : upper.charCodeAt(0));
+  return {
+    key,
+    code,
+    keyCode,
+    which: keyCode,
+    altKey: modifiers.includes('Alt'),
+    ctrlKey: modifiers.includes('Control'),
+    metaKey: modifiers.includes('Meta'),
+    shiftKey: modifiers.includes('Shift'),
+    bubbles: true,
+    cancelable: true,
+    composed: true,
+  };
+}
+
+// ============================================================================
+// Default Actions
+// ============================================================================
+
+const TABBABLE = 'a[href], button:not([disabled]), input:not([disabled]):not([type="hidden"]), select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex="-1"]), [contenteditable="true"]';
+
+function isTextField(element: Element): element is HTMLInputElement | HTMLTextAreaElement {
+  if (element instanceof HTMLTextAreaElement) {
+    return true;
+  }
+  return element instanceof HTMLInputElement
+    && ['text', 'search', 'email', 'url', 'tel', 'password', 'number', ''].includes(element.type);
+}
+
+function moveFocus(from: Element, backwards: boolean): string {
+  const tabbable = Array.from(document.querySelectorAll<HTMLElement>(TABBABLE)).filter((element) => {
+    const rect = element.getBoundingClientRect();
+    return rect.width > 0 && rect.height > 0 && getComputedStyle(element).visibility !== 'hidden';
+  });
+  if (tabbable.length === 0) {
+    return 'none';
+  }
+  const index = tabbable.indexOf(from as HTMLElement);
+  const next = index === -1
+    ? tabbable[backwards ? tabbable.length - 1 : 0]
+    : tabbable[(index + (backwards ? -1 : 1) + tabbable.length) % tabbable.length];
+  next.focus();
+  return 'focus_moved';
+}
+
+function editText(field: HTMLInputElement | HTMLTextAreaElement, key: string): string {
+  const start = field.selectionStart ?? field.value.length;
+  const end = field.selectionEnd ?? field.value.length;
+  let value = field.value;
+  let caret = start;
+  if (key === 'Backspace') {
+    const from = start === end ? Math.max(0, start - 1) : start;
+    value = value.slice(0, from) + value.slice(end);
+    caret = from;
+  } else if (key === 'Delete') {
+    const to = start === end ? Math.min(value.length, end + 1) : end;
+    value = value.slice(0, start) + value.slice(to);
+  } else {
+    value = value.slice(0, start) + key + value.slice(end);
+    caret = start + key.length;
+  }
+  field.value = value;
+  try {
+    field.setSelectionRange(caret, caret);
+  } catch {
+    // email and number inputs don't expose a selection
+  }
+  field.dispatchEvent(new InputEvent('input', {
+    bubbles: true,
+    inputType: key === 'Backspace' ? 'deleteContentBackward' : key === 'Delete' ? 'deleteContentForward' : 'insertText',
+    data: key.length === 1 ? key : null,
+  }));
+  return 'text_edited';
+}
+
+function stepSelect(select: HTMLSelectElement, key: string): string {
+  const last = select.options.length - 1;
+  const steps: Record<string, number> = { ArrowUp: -1, ArrowLeft: -1, ArrowDown: 1, ArrowRight: 1 };
+  const index = key === 'Home' ? 0 : key === 'End' ? last : select.selectedIndex + (steps[key] ?? 0);
+  const clamped = Math.max(0, Math.min(last, index));
+  if (clamped === select.selectedIndex) {
+    return 'none';
+  }
+  select.selectedIndex = clamped;
+  select.dispatchEvent(new Event('input', { bubbles: true }));
+  select.dispatchEvent(new Event('change', { bubbles: true }));
+  return 'option_changed';
+}
+
+function scrollPage(key: string): string {
+  const page = window.innerHeight * 0.9;
+  const by: Record<string, [number, number]> = {
+    ArrowUp: [0, -40],
+    ArrowDown: [0, 40],
+    ArrowLeft: [-40, 0],
+    ArrowRight: [40, 0],
+    PageUp: [0, -page],
+    PageDown: [0, page],
+    ' ': [0, page],
+  };
+  if (key === 'Home' || key === 'End') {
+    window.scrollTo({ top: key === 'Home' ? 0 : document.documentElement.scrollHeight, behavior: 'auto' });
+  } else {
+    window.scrollBy({ left: by[key][0], top: by[key][1], behavior: 'auto' });
+  }
+  return 'scrolled';
+}
+
+function defaultAction(target: Element, key: string, params: PressKeyParams): string {
+  if (params.modifiers.some((modifier) => modifier !== 'Shift')) {
+    // Shortcuts are left to page handlers
+    return 'none';
+  }
+  switch (key) {
+    case 'Enter':
+      if (target instanceof HTMLInputElement && target.form && isTextField(target)) {
+        target.form.requestSubmit();
+        return 'form_submitted';
+      }
+      if (target instanceof HTMLButtonElement || target instanceof HTMLAnchorElement || target.getAttribute('role') === 'button') {
+        (target as HTMLElement).click();
+        return 'clicked';
+      }
+      return 'none';
+    case 'Tab':
+      return moveFocus(target, params.modifiers.includes('Shift'));
+    case ' ':
+      if (target instanceof HTMLButtonElement
+        || (target instanceof HTMLInputElement && ['checkbox', 'radio', 'button', 'submit'].includes(target.type))
+        || ['button', 'checkbox', 'switch'].includes(target.getAttribute('role') ?? '')) {
+        (target as HTMLElement).click();
+        return 'clicked';
+      }
+      return isTextField(target) ? editText(target, key) : scrollPage(key);
+    case 'Backspace':
+    case 'Delete':
+      return isTextField(target) ? editText(target, key) : 'none';
+    case 'ArrowUp':
+    case 'ArrowDown':
+    case 'ArrowLeft':
+    case 'ArrowRight':
+    case 'Home':
+    case 'End':
+    case 'PageUp':
+    case 'PageDown':
+      if (target instanceof HTMLSelectElement) {
+        return stepSelect(target, key);
+      }
+      return isTextField(target) || (target as HTMLElement).isContentEditable ? 'none' : scrollPage(key);
+    default:
+      if (key.length === 1 && isTextField(target)) {
+        return editText(target, params.modifiers.includes('Shift') ? key.toUpperCase() : key);
+      }
+      return 'none';
+  }
+}
+
+// ============================================================================
+// Press-Key Command Handler
+// ============================================================================
+
+function describe(element: Element): string {
+  const id = element.id ? `#${element.id}` : '';
+  return `${element.tagName.toLowerCase()}${id}`;
+}
+
+export const pressKeyCommand: CommandHandler = async (command: Command, config: ModeConfig) => {
+  const params = command.params as PressKeyParams;
+
+  if (params.selector) {
+    const element = document.querySelector<HTMLElement>(params.selector);
+    if (!element) {
+      throw new Error(`Element not found: ${params.selector}`);
+    }
+    element.scrollIntoView({ behavior: 'auto', block: 'center' });
+    element.focus();
+  }
+
+  const actions: string[] = [];
+  let prevented = 0;
+  for (let i = 0; i < params.count; i++) {
+    // The focused element can change between presses (Tab)
+    const target = document.activeElement ?? document.body;
+    const init = keyInit(params.key, params.modifiers);
+
+    const allowed = target.dispatchEvent(new KeyboardEvent('keydown', init));
+    if (allowed && params.key.length === 1) {
+      target.dispatchEvent(new KeyboardEvent('keypress', init));
+    }
+    const action = allowed ? defaultAction(target, params.key, params) : 'prevented';
+    target.dispatchEvent(new KeyboardEvent('keyup', init));
+
+    if (!allowed) {
+      prevented++;
+    }
+    actions.push(action);
+
+    if (config.mode === 'stealth' && config.stealth.humanizeTiming && i < params.count - 1) {
+      await new Promise((resolve) => setTimeout(resolve, getRandomDelay(config.stealth.typingDelayRange)));
+    }
+  }
+
+  const focused = document.activeElement;
+  return {
+    success: true,
+    key: params.key,
+    modifiers: params.modifiers,
+    count: params.count,
+    actions: [...new Set(actions)],
+    prevented,
+    focused: focused && focused !== document.body ? describe(focused) : null,
+  };
+};
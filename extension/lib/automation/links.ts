@@ -0,0 +1,51 @@
+/**
+ * List-links command: every link (and optionally resource URL) on the page,
+ * resolved to absolute URLs, for the server's link checker
+ */
+
+import type { Command, CommandHandler, ListLinksParams } from './types';
+
+const RESOURCE_SELECTORS: Array<[string, string]> = [
+  ['img[src]', 'src'],
+  ['script[src]', 'src'],
+  ['link[rel~="stylesheet"][href]', 'href'],
+  ['source[src]', 'src'],
+  ['video[src]', 'src'],
+  ['audio[src]', 'src'],
+];
+
+function linkText(element: Element): string {
+  const text = (element.textContent ?? '').trim().replace(/\s+/g, ' ')
+    || element.getAttribute('aria-label')
+    || element.querySelector('img[alt]')?.getAttribute('alt')
+    || element.getAttribute('title')
+    || '';
+  return text.slice(0, 100);
+}
+
+export const listLinksCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as ListLinksParams;
+  const links: Array<{ href: string; text: string; tag: string }> = [];
+
+  document.querySelectorAll<HTMLAnchorElement | HTMLAreaElement>('a[href], area[href]').forEach((anchor) => {
+    links.push({ href: anchor.href, text: linkText(anchor), tag: anchor.tagName.toLowerCase() });
+  });
+
+  if (params.includeResources) {
+    for (const [selector, attribute] of RESOURCE_SELECTORS) {
+      document.querySelectorAll(selector).forEach((element) => {
+        // The property (not the attribute) is already resolved against the base URL
+        const href = (element as unknown as Record<string, unknown>)[attribute];
+        if (typeof href === 'string' && href) {
+          links.push({ href, text: element.getAttribute('alt') ?? '', tag: element.tagName.toLowerCase() });
+        }
+      });
+    }
+  }
+
+  return {
+    success: true,
+    url: location.href,
+    links,
+  };
+};
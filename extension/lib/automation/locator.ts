@@ -0,0 +1,121 @@
+/**
+ * Playwright-style locators: find elements by ARIA role and accessible name,
+ * visible text, label or placeholder instead of CSS. The selector-based
+ * commands address the resolved element through a temporary attribute.
+ */
+
+import { nameOf, roleOf } from './a11y-tree';
+import { cssPath, isHidden } from './accessibility';
+import type { Locator } from './types';
+
+const LOCATOR_ATTRIBUTE = 'data-agent-browser-locator';
+
+let nextToken = 0;
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+function normalize(text: string): string {
+  return text.replace(/\s+/g, ' ').trim();
+}
+
+/** Substring match ignoring case by default; exact compares whole, case-sensitive text */
+function textMatches(actual: string, wanted: string, exact: boolean): boolean {
+  const [a, b] = [normalize(actual), normalize(wanted)];
+  return exact ? a === b : a.toLowerCase().includes(b.toLowerCase());
+}
+
+export function labelText(element: Element): string {
+  const aria = element.getAttribute('aria-label') ?? '';
+  const labelledBy = (element.getAttribute('aria-labelledby')?.split(/\s+/) ?? [])
+    .map((id) => document.getElementById(id)?.textContent ?? '')
+    .join(' ');
+  const labels = 'labels' in element
+    ? Array.from((element as HTMLInputElement).labels ?? []).map((label) => label.innerText).join(' ')
+    : '';
+  return [aria, labelledBy, labels].filter((text) => text.trim()).join(' ');
+}
+
+/** Drop matches that contain another match, keeping the innermost elements */
+function innermost(elements: Element[]): Element[] {
+  return elements.filter((element) => !elements.some((other) => other !== element && element.contains(other)));
+}
+
+/** Visible elements the locator matches, in document order */
+export function findByLocator(locator: Locator): Element[] {
+  const exact = locator.exact === true;
+  const candidates = Array.from(document.body?.querySelectorAll('*') ?? []).filter((element) => !isHidden(element));
+
+  if (locator.role !== undefined) {
+    return candidates.filter((element) => {
+      const role = roleOf(element);
+      return role === locator.role && (locator.name === undefined || textMatches(nameOf(element, role), locator.name, exact));
+    });
+  }
+  if (locator.label !== undefined) {
+    const label = locator.label;
+    return candidates.filter((element) => {
+      const text = labelText(element);
+      return text !== '' && textMatches(text, label, exact);
+    });
+  }
+  if (locator.placeholder !== undefined) {
+    const placeholder = locator.placeholder;
+    return candidates.filter((element) =>
+      element.hasAttribute('placeholder') && textMatches(element.getAttribute('placeholder')!, placeholder, exact));
+  }
+  const text = locator.text ?? '';
+  const matches = candidates.filter((element) => {
+    if (element instanceof HTMLInputElement && ['button', 'submit', 'reset'].includes(element.type)) {
+      return textMatches(element.value, text, exact);
+    }
+    return textMatches((element as HTMLElement).innerText ?? element.textContent ?? '', text, exact);
+  });
+  return innermost(matches);
+}
+
+/** The locator as Playwright would write it, for messages */
+export function describeLocator(locator: Locator): string {
+  const options = [
+    locator.name !== undefined ? `name: ${JSON.stringify(locator.name)}` : '',
+    locator.exact ? 'exact: true' : '',
+  ].filter(Boolean);
+  const suffix = options.length ? `, { ${options.join(', ')} }` : '';
+  const nth = locator.nth ? `.nth(${locator.nth})` : '';
+  if (locator.role !== undefined) {
+    return `getByRole(${JSON.stringify(locator.role)}${suffix})${nth}`;
+  }
+  const [method, value] = locator.label !== undefined
+    ? ['getByLabel', locator.label]
+    : locator.placeholder !== undefined
+      ? ['getByPlaceholder', locator.placeholder]
+      : ['getByText', locator.text];
+  return `${method}(${JSON.stringify(value)}${suffix})${nth}`;
+}
+
+// ============================================================================
+// Applying Locators
+// ============================================================================
+
+/**
+ * Resolve `params.locator` and point `params.selector` at the element.
+ * Returns the element's CSS path and a function removing the temporary
+ * attribute, to call once the command is done.
+ */
+export function applyLocator(params: Record<string, any>): { selector: string; path: string; release: () => void } {
+  const locator = params.locator as Locator;
+  const matches = findByLocator(locator);
+  const element = matches[locator.nth ?? 0];
+  if (!element) {
+    throw new Error(matches.length
+      ? `${describeLocator(locator)}: only ${matches.length} element${matches.length === 1 ? '' : 's'} match`
+      : `No element matches ${describeLocator(locator)}`);
+  }
+
+  const token = `${Date.now().toString(36)}-${nextToken++}`;
+  element.setAttribute(LOCATOR_ATTRIBUTE, token);
+  const selector = `[${LOCATOR_ATTRIBUTE}="${token}"]`;
+  params.selector = selector;
+  return { selector, path: cssPath(element), release: () => element.removeAttribute(LOCATOR_ATTRIBUTE) };
+}
@@ -0,0 +1,192 @@
+/**
+ * Media commands: control, state and frame capture for <audio> and <video>
+ *
+ * play() is subject to the autoplay policy: an unmuted play without a user
+ * gesture is refused, which is reported rather than retried muted. Frames
+ * are read through a scratch canvas, so cross-origin video served without
+ * CORS can't be captured.
+ */
+
+import type { Command, CommandHandler, MediaParams, ModeConfig } from './types';
+import { cssPath } from './accessibility';
+
+const SEEK_TIMEOUT_MS = 5000;
+const READY_STATES = ['HAVE_NOTHING', 'HAVE_METADATA', 'HAVE_CURRENT_DATA', 'HAVE_FUTURE_DATA', 'HAVE_ENOUGH_DATA'];
+const MEDIA_ERRORS: Record<number, string> = {
+  1: 'MEDIA_ERR_ABORTED',
+  2: 'MEDIA_ERR_NETWORK',
+  3: 'MEDIA_ERR_DECODE',
+  4: 'MEDIA_ERR_SRC_NOT_SUPPORTED',
+};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+function findMedia(selector: string | undefined, videoOnly = false): HTMLMediaElement {
+  const fallback = videoOnly ? 'video' : 'video, audio';
+  const element = document.querySelector(selector ?? fallback);
+  if (!element) {
+    throw new Error(selector ? `Element not found: ${selector}` : `No ${videoOnly ? 'video' : 'audio or video'} element on the page`);
+  }
+  if (videoOnly ? !(element instanceof HTMLVideoElement) : !(element instanceof HTMLMediaElement)) {
+    throw new Error(`Element is a <${element.tagName.toLowerCase()}>, not ${videoOnly ? 'a <video>' : 'an <audio> or <video>'}: ${selector}`);
+  }
+  return element as HTMLMediaElement;
+}
+
+/** Duration is NaN before metadata and Infinity for live streams; neither survives JSON */
+function finite(value: number): number | null {
+  return Number.isFinite(value) ? Math.round(value * 1000) / 1000 : null;
+}
+
+export function describeMedia(media: HTMLMediaElement): Record<string, any> {
+  const buffered = media.buffered.length > 0 ? media.buffered.end(media.buffered.length - 1) : 0;
+  return {
+    selector: cssPath(media),
+    tag: media.tagName.toLowerCase(),
+    src: media.currentSrc || media.getAttribute('src') || null,
+    paused: media.paused,
+    ended: media.ended,
+    currentTime: finite(media.currentTime),
+    duration: finite(media.duration),
+    live: media.duration === Infinity,
+    bufferedUntil: finite(buffered),
+    muted: media.muted,
+    volume: media.volume,
+    playbackRate: media.playbackRate,
+    loop: media.loop,
+    readyState: READY_STATES[media.readyState] ?? media.readyState,
+    seeking: media.seeking,
+    error: media.error ? { code: MEDIA_ERRORS[media.error.code] ?? media.error.code, message: media.error.message || null } : null,
+    ...(media instanceof HTMLVideoElement ? { videoWidth: media.videoWidth, videoHeight: media.videoHeight, poster: media.poster || null } : {}),
+  };
+}
+
+function seek(media: HTMLMediaElement, time: number): Promise<void> {
+  return new Promise((resolve, reject) => {
+    if (media.readyState === 0) {
+      reject(new Error('Media has no metadata yet, so it cannot seek'));
+      return;
+    }
+    const target = Number.isFinite(media.duration) ? Math.min(time, media.duration) : time;
+    const timer = setTimeout(() => {
+      media.removeEventListener('seeked', done);
+      reject(new Error(`Seek to ${target}s did not finish within ${SEEK_TIMEOUT_MS}ms`));
+    }, SEEK_TIMEOUT_MS);
+    const done = () => {
+      clearTimeout(timer);
+      resolve();
+    };
+    media.addEventListener('seeked', done, { once: true });
+    media.currentTime = target;
+  });
+}
+
+function toPng(source: CanvasImageSource, width: number, height: number): string {
+  const canvas = document.createElement('canvas');
+  canvas.width = width;
+  canvas.height = height;
+  canvas.getContext('2d')!.drawImage(source, 0, 0, width, height);
+  try {
+    return canvas.toDataURL('image/png').split(',')[1];
+  } catch (error) {
+    if (error instanceof DOMException && error.name === 'SecurityError') {
+      throw new Error('The video is cross-origin without CORS, so its frames cannot be read; use playwright_screenshot with a selector instead');
+    }
+    throw error;
+  }
+}
+
+function loadImage(url: string): Promise<HTMLImageElement> {
+  return new Promise((resolve, reject) => {
+    const image = new Image();
+    image.onload = () => resolve(image);
+    image.onerror = () => reject(new Error(`Poster image failed to load: ${url}`));
+    image.src = url;
+  });
+}
+
+// ============================================================================
+// Command Handlers
+// ============================================================================
+
+export const mediaControlCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector, action, time } = command.params as MediaParams;
+  const media = findMedia(selector);
+
+  switch (action) {
+    case 'play':
+      try {
+        await media.play();
+      } catch (error) {
+        if (error instanceof DOMException && error.name === 'NotAllowedError') {
+          throw new Error('Playback was blocked by the autoplay policy; mute the element first or click it');
+        }
+        throw error;
+      }
+      break;
+    case 'pause':
+      media.pause();
+      break;
+    case 'seek':
+      await seek(media, time!);
+      break;
+    case 'mute':
+      media.muted = true;
+      break;
+    case 'unmute':
+      media.muted = false;
+      break;
+    default:
+      throw new Error(`Unknown media action: ${action}`);
+  }
+
+  return { success: true, action, state: describeMedia(media) };
+};
+
+export const mediaGetStateCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector } = command.params as MediaParams;
+  const elements = selector
+    ? [findMedia(selector)]
+    : Array.from(document.querySelectorAll<HTMLMediaElement>('video, audio'));
+  return { success: true, count: elements.length, media: elements.map(describeMedia) };
+};
+
+export const mediaCaptureFrameCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector, time } = command.params as MediaParams;
+  const video = findMedia(selector, true) as HTMLVideoElement;
+  if (time !== undefined) {
+    await seek(video, time);
+  }
+
+  // Before the first frame decodes only the poster is on screen
+  if (video.readyState < HTMLMediaElement.HAVE_CURRENT_DATA || video.videoWidth === 0) {
+    if (!video.poster) {
+      throw new Error(`The video has no frame to capture yet (${READY_STATES[video.readyState]}) and no poster`);
+    }
+    const poster = await loadImage(video.poster);
+    return {
+      success: true,
+      source: 'poster',
+      width: poster.naturalWidth,
+      height: poster.naturalHeight,
+      data: toPng(poster, poster.naturalWidth, poster.naturalHeight),
+      encoding: 'base64',
+      format: 'png',
+      state: describeMedia(video),
+    };
+  }
+
+  return {
+    success: true,
+    source: 'frame',
+    currentTime: finite(video.currentTime),
+    width: video.videoWidth,
+    height: video.videoHeight,
+    data: toPng(video, video.videoWidth, video.videoHeight),
+    encoding: 'base64',
+    format: 'png',
+    state: describeMedia(video),
+  };
+};
@@ -0,0 +1,74 @@
+/**
+ * Query-elements command: describe every element matching a selector,
+ * optionally inside a container, with a selector that addresses each one.
+ * explore_menu reads a menu's items with it.
+ */
+
+import type { Command, CommandHandler, ModeConfig, QueryElementsParams } from './types';
+import { accessibleName, cssPath } from './accessibility';
+import { roleOf } from './a11y-tree';
+
+const MAX_TEXT_LENGTH = 200;
+
+function isVisible(element: Element): boolean {
+  const rect = element.getBoundingClientRect();
+  const style = window.getComputedStyle(element);
+  return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+}
+
+function isDisabled(element: Element): boolean {
+  return element.matches(':disabled') || element.closest('[aria-disabled="true"]') !== null;
+}
+
+function describe(element: Element): Record<string, any> {
+  const rect = element.getBoundingClientRect();
+  return {
+    tag: element.tagName.toLowerCase(),
+    text: accessibleName(element).replace(/\s+/g, ' ').trim().slice(0, MAX_TEXT_LENGTH),
+    selector: cssPath(element),
+    role: roleOf(element),
+    href: element instanceof HTMLAnchorElement && element.getAttribute('href') ? element.href : null,
+    disabled: isDisabled(element),
+    visible: isVisible(element),
+    bounds: { x: rect.x, y: rect.y, width: rect.width, height: rect.height },
+  };
+}
+
+export const queryElementsCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector, within, visibleOnly = false, limit = 100 } = command.params as QueryElementsParams;
+  if (!selector) {
+    throw new Error('Selector is required for query_elements');
+  }
+
+  let roots: ParentNode[] = [document];
+  if (within) {
+    roots = Array.from(document.querySelectorAll(within));
+    if (visibleOnly) {
+      roots = (roots as Element[]).filter(isVisible);
+    }
+    if (roots.length === 0) {
+      throw new Error(`No ${visibleOnly ? 'visible ' : ''}container matches ${within}`);
+    }
+  }
+
+  // Containers can nest (a submenu inside a menu), so each element is listed once
+  const seen = new Set<Element>();
+  const matches: Element[] = [];
+  for (const root of roots) {
+    for (const element of Array.from(root.querySelectorAll(selector))) {
+      if (!seen.has(element) && (!visibleOnly || isVisible(element))) {
+        seen.add(element);
+        matches.push(element);
+      }
+    }
+  }
+
+  return {
+    success: true,
+    selector,
+    within: within ?? null,
+    count: matches.length,
+    truncated: matches.length > limit,
+    elements: matches.slice(0, limit).map(describe),
+  };
+};
@@ -0,0 +1,362 @@
+/**
+ * Extract-content command: a readability pass that finds the page's main
+ * content, drops navigation and boilerplate, and renders it as Markdown
+ */
+
+import type { Command, CommandHandler, ExtractContentParams } from './types';
+import { isHidden } from './accessibility';
+import { describeElement } from './scroll';
+
+// Elements never part of an article body
+const SKIPPED_TAGS = new Set([
+  'SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'NAV', 'ASIDE', 'FOOTER', 'FORM', 'BUTTON',
+  'INPUT', 'SELECT', 'TEXTAREA', 'IFRAME', 'SVG', 'CANVAS', 'DIALOG', 'OBJECT', 'EMBED',
+]);
+
+const POSITIVE_HINTS = /article|body|content|entry|hentry|main|page|post|text|blog|story|prose/i;
+const NEGATIVE_HINTS = /comment|meta|footer|footnote|masthead|sidebar|sponsor|\bad-|advert|share|social|nav|menu|promo|related|recommend|banner|cookie|consent|subscribe|newsletter|popup|modal|breadcrumb|pagination/i;
+
+const MAX_LINKS = 200;
+
+// ============================================================================
+// Scoring
+// ============================================================================
+
+function normalize(text: string): string {
+  return text.replace(/\s+/g, ' ').trim();
+}
+
+/** +25 for class/id names that suggest content, -25 for chrome */
+function classWeight(element: Element): number {
+  const names = `${typeof element.className === 'string' ? element.className : ''} ${element.id}`;
+  return (POSITIVE_HINTS.test(names) ? 25 : 0) - (NEGATIVE_HINTS.test(names) ? 25 : 0);
+}
+
+/** Share of an element's text that sits inside links */
+function linkDensity(element: Element): number {
+  const length = normalize(element.textContent ?? '').length;
+  if (length === 0) {
+    return 0;
+  }
+  const linked = Array.from(element.querySelectorAll('a'))
+    .reduce((sum, link) => sum + normalize(link.textContent ?? '').length, 0);
+  return linked / length;
+}
+
+/**
+ * The element holding the main content: paragraphs score their parent and,
+ * at half weight, their grandparent by length and commas; link-heavy
+ * containers are discounted
+ */
+function findMainContent(): Element {
+  const scores = new Map<Element, number>();
+  const add = (element: Element | null, score: number) => {
+    if (element && element !== document.documentElement) {
+      scores.set(element, (scores.get(element) ?? classWeight(element)) + score);
+    }
+  };
+
+  for (const block of Array.from(document.body.querySelectorAll('p, pre, td, blockquote, li, h2, h3'))) {
+    const text = normalize(block.textContent ?? '');
+    if (text.length < 25 || block.closest('nav, aside, footer, header, form') || isHidden(block)) {
+      continue;
+    }
+    // One point, plus one per comma and per 100 characters (up to three)
+    const score = text.split(/[,，、]/).length + Math.min(Math.floor(text.length / 100), 3);
+    add(block.parentElement, score);
+    add(block.parentElement?.parentElement ?? null, score / 2);
+  }
+
+  let best: Element | null = null;
+  let bestScore = 0;
+  for (const [element, score] of scores) {
+    const adjusted = score * (1 - linkDensity(element));
+    if (adjusted > bestScore) {
+      best = element;
+      bestScore = adjusted;
+    }
+  }
+
+  // Semantic containers win when they hold the best candidate
+  const semantic = best?.closest('article, main, [role="main"]');
+  if (semantic && normalize(semantic.textContent ?? '').length < normalize(best!.textContent ?? '').length * 3) {
+    return semantic;
+  }
+  return best ?? document.querySelector('article, main, [role="main"]') ?? document.body;
+}
+
+/** Boilerplate inside the main content: hidden, chrome, or mostly links with little text */
+function isBoilerplate(element: Element): boolean {
+  if (SKIPPED_TAGS.has(element.tagName.toUpperCase()) || isHidden(element)) {
+    return true;
+  }
+  if (/^(P|PRE|CODE|TABLE|BLOCKQUOTE|H[1-6]|IMG|PICTURE|FIGURE|A|STRONG|EM|B|I)$/.test(element.tagName)) {
+    return false;
+  }
+  const text = normalize(element.textContent ?? '');
+  if (classWeight(element) < 0 && text.length < 500) {
+    return true;
+  }
+  return text.length < 200 && linkDensity(element) > 0.5 && element.querySelectorAll('a').length > 2;
+}
+
+// ============================================================================
+// Markdown Rendering
+// ============================================================================
+
+class MarkdownWriter {
+  links: Array<{ text: string; url: string }> = [];
+  private seenLinks = new Set<string>();
+
+  constructor(private options: { includeLinks: boolean; includeImages: boolean }) {}
+
+  render(root: Element): string {
+    return this.blocks(root).replace(/\n{3,}/g, '\n\n').trim();
+  }
+
+  /** Block-level Markdown for an element's children */
+  private blocks(element: Element, listDepth = 0): string {
+    let out = '';
+    let inline = '';
+    const flush = () => {
+      const text = normalize(inline);
+      if (text) {
+        out += `${text}\n\n`;
+      }
+      inline = '';
+    };
+
+    for (const node of Array.from(element.childNodes)) {
+      if (node.nodeType === Node.TEXT_NODE) {
+        inline += node.textContent ?? '';
+        continue;
+      }
+      if (!(node instanceof Element) || isBoilerplate(node)) {
+        continue;
+      }
+      const block = this.block(node, listDepth);
+      if (block === null) {
+        inline += this.inline(node);
+      } else {
+        flush();
+        out += block;
+      }
+    }
+    flush();
+    return out;
+  }
+
+  /** Markdown for a block element, or null for inline ones */
+  private block(element: Element, listDepth: number): string | null {
+    const tag = element.tagName.toUpperCase();
+    const heading = /^H([1-6])$/.exec(tag);
+    if (heading) {
+      const text = normalize(this.inlineChildren(element));
+      return text ? `${'#'.repeat(Number(heading[1]))} ${text}\n\n` : '';
+    }
+    switch (tag) {
+      case 'P':
+        return `${normalize(this.inlineChildren(element))}\n\n`;
+      case 'BR':
+        return '\n';
+      case 'HR':
+        return '---\n\n';
+      case 'PRE': {
+        const code = (element.textContent ?? '').replace(/\n+$/, '');
+        const language = /language-([\w+-]+)/.exec(element.querySelector('code')?.className ?? '')?.[1] ?? '';
+        return `\`\`\`${language}\n${code}\n\`\`\`\n\n`;
+      }
+      case 'BLOCKQUOTE': {
+        const quoted = this.blocks(element, listDepth).trim();
+        return `${quoted.split('\n').map((line) => (line ? `> ${line}` : '>')).join('\n')}\n\n`;
+      }
+      case 'UL':
+      case 'OL':
+        return `${this.list(element, tag === 'OL', listDepth)}\n`;
+      case 'TABLE':
+        return this.table(element as HTMLTableElement);
+      case 'FIGURE':
+      case 'DIV':
+      case 'SECTION':
+      case 'ARTICLE':
+      case 'MAIN':
+      case 'HEADER':
+      case 'DL':
+      case 'DD':
+      case 'DT':
+      case 'FIGCAPTION':
+      case 'DETAILS':
+      case 'SUMMARY':
+        return this.blocks(element, listDepth);
+      default:
+        return null;
+    }
+  }
+
+  private list(element: Element, ordered: boolean, depth: number): string {
+    const indent = '  '.repeat(depth);
+    let index = Number(element.getAttribute('start') ?? 1);
+    let out = '';
+    for (const item of Array.from(element.children)) {
+      if (item.tagName.toUpperCase() !== 'LI' || isBoilerplate(item)) {
+        continue;
+      }
+      const marker = ordered ? `${index++}.` : '-';
+      const body = this.blocks(item, depth + 1).trim().replace(/\n\n+/g, '\n');
+      const [first, ...rest] = body.split('\n');
+      out += `${indent}${marker} ${first ?? ''}\n`;
+      for (const line of rest) {
+        out += line.startsWith(`${indent}  `) ? `${line}\n` : `${indent}  ${line}\n`;
+      }
+    }
+    return out;
+  }
+
+  private table(table: HTMLTableElement): string {
+    const rows = Array.from(table.rows).map((row) =>
+      Array.from(row.cells).map((cell) => normalize(this.inlineChildren(cell)).replace(/\|/g, '\\|')));
+    if (rows.length === 0) {
+      return '';
+    }
+    const width = Math.max(...rows.map((row) => row.length));
+    const line = (cells: string[]) => `| ${Array.from({ length: width }, (_, i) => cells[i] ?? '').join(' | ')} |`;
+    const [head, ...body] = rows;
+    return `${[line(head), line(Array(width).fill('---')), ...body.map(line)].join('\n')}\n\n`;
+  }
+
+  private inlineChildren(element: Element): string {
+    return Array.from(element.childNodes).map((node) => this.inline(node)).join('');
+  }
+
+  /** Inline Markdown; block elements nested in inline ones are flattened */
+  private inline(node: Node): string {
+    if (node.nodeType === Node.TEXT_NODE) {
+      return node.textContent ?? '';
+    }
+    if (!(node instanceof Element) || isBoilerplate(node)) {
+      return '';
+    }
+    const wrap = (marker: string) => {
+      const text = normalize(this.inlineChildren(node));
+      return text ? ` ${marker}${text}${marker} ` : '';
+    };
+    switch (node.tagName.toUpperCase()) {
+      case 'STRONG':
+      case 'B':
+        return wrap('**');
+      case 'EM':
+      case 'I':
+        return wrap('_');
+      case 'CODE':
+        return ` \`${normalize(node.textContent ?? '')}\` `;
+      case 'BR':
+        return '\n';
+      case 'A': {
+        const text = normalize(this.inlineChildren(node));
+        const href = (node as HTMLAnchorElement).href;
+        if (!text || !href || href.startsWith('javascript:')) {
+          return text;
+        }
+        if (!this.seenLinks.has(href) && this.links.length < MAX_LINKS) {
+          this.seenLinks.add(href);
+          this.links.push({ text, url: href });
+        }
+        return this.options.includeLinks ? ` [${text}](${href}) ` : text;
+      }
+      case 'IMG': {
+        const image = node as HTMLImageElement;
+        return this.options.includeImages && image.currentSrc
+          ? ` ![${normalize(image.alt)}](${image.currentSrc}) `
+          : '';
+      }
+      default:
+        return this.inlineChildren(node);
+    }
+  }
+}
+
+// ============================================================================
+// Metadata
+// ============================================================================
+
+function meta(...names: string[]): string | null {
+  for (const name of names) {
+    const content = document.querySelector(`meta[property="${name}"], meta[name="${name}"]`)?.getAttribute('content');
+    if (content?.trim()) {
+      return normalize(content);
+    }
+  }
+  return null;
+}
+
+function articleMetadata(root: Element) {
+  const heading = root.querySelector('h1') ?? document.querySelector('h1');
+  const byline = meta('author', 'article:author', 'twitter:creator')
+    ?? document.querySelector('[rel="author"], [itemprop="author"], .byline, .author')?.textContent
+    ?? null;
+  return {
+    title: meta('og:title', 'twitter:title') ?? (heading ? normalize(heading.textContent ?? '') : null) ?? document.title,
+    byline: byline ? normalize(byline).slice(0, 200) : null,
+    siteName: meta('og:site_name', 'application-name'),
+    publishedTime: meta('article:published_time', 'date', 'pubdate')
+      ?? root.querySelector('time[datetime]')?.getAttribute('datetime')
+      ?? null,
+    excerpt: meta('description', 'og:description', 'twitter:description'),
+    lang: document.documentElement.lang || null,
+  };
+}
+
+// ============================================================================
+// Extract Content Command Handler
+// ============================================================================
+
+export const extractContentCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as ExtractContentParams;
+
+  let root: Element;
+  if (params.selector) {
+    const element = document.querySelector(params.selector);
+    if (!element) {
+      throw new Error(`Element not found: ${params.selector}`);
+    }
+    root = element;
+  } else {
+    root = findMainContent();
+  }
+
+  const metadata = articleMetadata(root);
+  const writer = new MarkdownWriter({ includeLinks: params.includeLinks, includeImages: params.includeImages });
+  let body = writer.render(root);
+  // The title is rendered once, above the body
+  if (metadata.title && body.startsWith(`# ${metadata.title}\n`)) {
+    body = body.slice(metadata.title.length + 3).trimStart();
+  }
+
+  const header = [
+    metadata.title ? `# ${metadata.title}` : '',
+    metadata.byline ? `_${metadata.byline}_` : '',
+  ].filter(Boolean).join('\n\n');
+  const markdown = header ? `${header}\n\n${body}` : body;
+
+  // Cut at a paragraph break when one is close to the limit
+  let clipped = markdown;
+  if (markdown.length > params.maxLength) {
+    clipped = markdown.slice(0, params.maxLength);
+    const paragraph = clipped.lastIndexOf('\n\n');
+    if (paragraph > params.maxLength * 0.8) {
+      clipped = clipped.slice(0, paragraph);
+    }
+  }
+
+  return {
+    success: true,
+    url: location.href,
+    ...metadata,
+    source: params.selector ?? describeElement(root),
+    markdown: clipped,
+    length: markdown.length,
+    truncated: clipped.length < markdown.length,
+    links: writer.links,
+  };
+};
+
@@ -0,0 +1,220 @@
+/**
+ * Scroll into view command, and the scroll-then-verify pre-step click and
+ * type run before touching an element
+ */
+
+import type { Command, CommandHandler, ModeConfig, ScrollIntoViewParams } from './types';
+
+export interface ViewOptions {
+  block?: 'start' | 'center' | 'end' | 'nearest';
+  behavior?: ScrollBehavior;
+  /** Detect fixed/sticky headers and footers at the viewport edges */
+  detectSticky?: boolean;
+  /** Pixels obscured at the top or bottom; override detection */
+  offsetTop?: number | null;
+  offsetBottom?: number | null;
+}
+
+export interface ViewStatus {
+  inView: boolean;
+  /** Fraction of the element inside the unobscured viewport and its scroll containers */
+  visibleRatio: number;
+  obscuredBy: string | null;
+  /** Why the element is not in view */
+  reason: string | null;
+  offsetTop: number;
+  offsetBottom: number;
+}
+
+// ============================================================================
+// Geometry
+// ============================================================================
+
+/** Short description of an element for reports, e.g. div#banner.cookie-bar */
+export function describeElement(element: Element): string {
+  const id = element.id ? `#${element.id}` : '';
+  const classes = typeof element.className === 'string' && element.className.trim()
+    ? `.${element.className.trim().split(/\s+/).slice(0, 2).join('.')}`
+    : '';
+  return `${element.tagName.toLowerCase()}${id}${classes}`;
+}
+
+/** Height of fixed/sticky bars spanning most of the viewport width at its top and bottom edges */
+function stickyInsets(target: Element): { top: number; bottom: number } {
+  const viewport = { width: window.innerWidth, height: window.innerHeight };
+  let top = 0;
+  let bottom = 0;
+  for (const element of Array.from(document.body?.querySelectorAll('*') ?? [])) {
+    const style = window.getComputedStyle(element);
+    if (style.position !== 'fixed' && style.position !== 'sticky') {
+      continue;
+    }
+    if (element.contains(target) || style.visibility === 'hidden' || style.display === 'none') {
+      continue;
+    }
+    const rect = element.getBoundingClientRect();
+    if (rect.width < viewport.width / 2 || rect.height === 0 || rect.height > viewport.height / 3) {
+      continue;
+    }
+    if (rect.top <= 1 && rect.bottom > 0) {
+      top = Math.max(top, rect.bottom);
+    } else if (rect.bottom >= viewport.height - 1 && rect.top < viewport.height) {
+      bottom = Math.max(bottom, viewport.height - rect.top);
+    }
+  }
+  return { top, bottom };
+}
+
+/** Nearest ancestor that scrolls vertically, or the document */
+function scrollParent(element: Element): Element {
+  for (let node = element.parentElement; node; node = node.parentElement) {
+    const overflow = window.getComputedStyle(node).overflowY;
+    if ((overflow === 'auto' || overflow === 'scroll') && node.scrollHeight > node.clientHeight) {
+      return node;
+    }
+  }
+  return document.scrollingElement ?? document.documentElement;
+}
+
+/** Resolve once the scroll position stops changing, or after a second */
+async function scrollSettled(): Promise<void> {
+  const frame = () => new Promise((resolve) => requestAnimationFrame(resolve));
+  let last = '';
+  for (let i = 0; i < 60; i++) {
+    await frame();
+    const position = `${window.scrollX},${window.scrollY}`;
+    if (position === last) {
+      return;
+    }
+    last = position;
+  }
+}
+
+// ============================================================================
+// Scrolling
+// ============================================================================
+
+/** Measure how much of `element` can be seen below the given insets */
+export function viewStatus(element: Element, insets: { top: number; bottom: number }): ViewStatus {
+  const status = (visibleRatio: number, reason: string | null, obscuredBy: string | null = null): ViewStatus => ({
+    inView: visibleRatio > 0 && obscuredBy === null,
+    visibleRatio: Math.round(visibleRatio * 1000) / 1000,
+    obscuredBy,
+    reason,
+    offsetTop: insets.top,
+    offsetBottom: insets.bottom,
+  });
+
+  const rect = element.getBoundingClientRect();
+  const style = window.getComputedStyle(element);
+  if (rect.width === 0 || rect.height === 0) {
+    return status(0, style.display === 'none' ? 'Element is display: none' : 'Element has no size');
+  }
+  if (style.visibility === 'hidden') {
+    return status(0, 'Element is visibility: hidden');
+  }
+
+  // Clip by every ancestor that hides overflow, then by the unobscured viewport
+  let clip = { left: rect.left, top: rect.top, right: rect.right, bottom: rect.bottom };
+  for (let node = element.parentElement; node && node !== document.body; node = node.parentElement) {
+    const overflow = window.getComputedStyle(node);
+    if (overflow.overflowX === 'visible' && overflow.overflowY === 'visible') {
+      continue;
+    }
+    const box = node.getBoundingClientRect();
+    clip = {
+      left: Math.max(clip.left, box.left),
+      top: Math.max(clip.top, box.top),
+      right: Math.min(clip.right, box.right),
+      bottom: Math.min(clip.bottom, box.bottom),
+    };
+    if (clip.right <= clip.left || clip.bottom <= clip.top) {
+      return status(0, `Element is clipped by ${describeElement(node)}, which could not be scrolled to it`);
+    }
+  }
+  clip = {
+    left: Math.max(clip.left, 0),
+    top: Math.max(clip.top, insets.top),
+    right: Math.min(clip.right, window.innerWidth),
+    bottom: Math.min(clip.bottom, window.innerHeight - insets.bottom),
+  };
+  if (clip.right <= clip.left || clip.bottom <= clip.top) {
+    return status(0, 'Element is outside the viewport after scrolling (fixed position or transformed?)');
+  }
+  const ratio = ((clip.right - clip.left) * (clip.bottom - clip.top)) / (rect.width * rect.height);
+
+  const topmost = document.elementFromPoint((clip.left + clip.right) / 2, (clip.top + clip.bottom) / 2);
+  if (topmost && topmost !== element && !element.contains(topmost)) {
+    return status(ratio, `Element is covered by ${describeElement(topmost)}`, describeElement(topmost));
+  }
+  return status(ratio, null);
+}
+
+/**
+ * Scroll `element` into the part of the viewport not covered by fixed or
+ * sticky chrome, then report whether it can be seen
+ */
+export async function bringIntoView(element: Element, options: ViewOptions = {}): Promise<ViewStatus> {
+  const block = options.block ?? 'center';
+  const behavior = options.behavior ?? 'auto';
+  element.scrollIntoView({ behavior, block: block === 'nearest' ? 'nearest' : 'center', inline: 'nearest' });
+  await scrollSettled();
+
+  const detected = options.detectSticky ? stickyInsets(element) : { top: 0, bottom: 0 };
+  const insets = {
+    top: options.offsetTop ?? detected.top,
+    bottom: options.offsetBottom ?? detected.bottom,
+  };
+
+  // Place the element within the unobscured band according to block
+  const rect = element.getBoundingClientRect();
+  const bandTop = insets.top;
+  const bandBottom = window.innerHeight - insets.bottom;
+  const fits = rect.height <= bandBottom - bandTop;
+  let wantedTop: number | null;
+  if (!fits || block === 'start') {
+    wantedTop = bandTop;
+  } else if (block === 'end') {
+    wantedTop = bandBottom - rect.height;
+  } else if (block === 'center') {
+    wantedTop = bandTop + (bandBottom - bandTop - rect.height) / 2;
+  } else {
+    wantedTop = rect.top < bandTop ? bandTop : rect.bottom > bandBottom ? bandBottom - rect.height : null;
+  }
+  if (wantedTop !== null && Math.abs(rect.top - wantedTop) >= 1) {
+    scrollParent(element).scrollBy({ top: rect.top - wantedTop, behavior });
+    await scrollSettled();
+  }
+
+  return viewStatus(element, insets);
+}
+
+// ============================================================================
+// Scroll Into View Command Handler
+// ============================================================================
+
+export const scrollIntoViewCommand: CommandHandler = async (command: Command, config: ModeConfig) => {
+  const params = command.params as ScrollIntoViewParams;
+
+  const element = document.querySelector(params.selector);
+  if (!element) {
+    throw new Error(`Element not found: ${params.selector}`);
+  }
+
+  const before = { x: window.scrollX, y: window.scrollY };
+  const status = await bringIntoView(element, {
+    block: params.block,
+    behavior: config.speed.skipAnimations ? 'auto' : 'smooth',
+    detectSticky: params.detectSticky,
+    offsetTop: params.offsetTop,
+    offsetBottom: params.offsetBottom,
+  });
+
+  return {
+    success: true,
+    selector: params.selector,
+    ...status,
+    scrolledBy: { x: window.scrollX - before.x, y: window.scrollY - before.y },
+    bounds: element.getBoundingClientRect(),
+  };
+};
@@ -0,0 +1,101 @@
+/**
+ * Select command implementation for <select> elements
+ */
+
+import type { Command, CommandHandler, ModeConfig, SelectParams } from './types';
+import { getRandomDelay } from './mode-config';
+
+// ============================================================================
+// Option Matching
+// ============================================================================
+
+function findSelect(params: SelectParams): HTMLSelectElement {
+  let element: Element | null = null;
+  if (params.selector) {
+    element = document.querySelector(params.selector);
+  } else if (params.xpath) {
+    const result = document.evaluate(
+      params.xpath,
+      document,
+      null,
+      XPathResult.FIRST_ORDERED_NODE_TYPE,
+      null
+    );
+    element = result.singleNodeValue as Element | null;
+  }
+
+  if (!element) {
+    throw new Error(`Element not found: ${params.selector || params.xpath || 'unknown'}`);
+  }
+  if (!(element instanceof HTMLSelectElement)) {
+    throw new Error(`Element is a <${element.tagName.toLowerCase()}>, not a <select>`);
+  }
+  return element;
+}
+
+function matchOption(select: HTMLSelectElement, by: SelectParams['by'], wanted: string | number): HTMLOptionElement | undefined {
+  const options = Array.from(select.options);
+  switch (by) {
+    case 'index':
+      return options[wanted as number];
+    case 'label':
+      return options.find((option) => option.label.trim() === String(wanted).trim())
+        ?? options.find((option) => option.text.trim() === String(wanted).trim());
+    default:
+      return options.find((option) => option.value === String(wanted));
+  }
+}
+
+function describeOptions(select: HTMLSelectElement): string {
+  const options = Array.from(select.options).slice(0, 20);
+  const listed = options.map((option) => `${JSON.stringify(option.value)} (${option.label.trim()})`).join(', ');
+  return select.options.length > options.length ? `${listed}, ...` : listed;
+}
+
+// ============================================================================
+// Select Command Handler
+// ============================================================================
+
+export const selectCommand: CommandHandler = async (command: Command, config: ModeConfig) => {
+  const params = command.params as SelectParams;
+  const select = findSelect(params);
+
+  if (params.options.length > 1 && !select.multiple) {
+    throw new Error('Element does not allow multiple selections; pass a single option');
+  }
+
+  const matched = params.options.map((wanted) => {
+    const option = matchOption(select, params.by, wanted);
+    if (!option) {
+      throw new Error(`No option with ${params.by} ${JSON.stringify(wanted)}. Available: ${describeOptions(select)}`);
+    }
+    if (option.disabled) {
+      throw new Error(`Option ${JSON.stringify(wanted)} is disabled`);
+    }
+    return option;
+  });
+
+  select.scrollIntoView({ behavior: 'auto', block: 'center' });
+  select.focus();
+  if (config.mode === 'stealth' && config.stealth.humanizeTiming) {
+    await new Promise((resolve) => setTimeout(resolve, getRandomDelay(config.stealth.typingDelayRange)));
+  }
+
+  // Replace the whole selection, as a user picking these options would
+  for (const option of Array.from(select.options)) {
+    option.selected = matched.includes(option);
+  }
+  select.dispatchEvent(new Event('input', { bubbles: true }));
+  select.dispatchEvent(new Event('change', { bubbles: true }));
+
+  return {
+    success: true,
+    selector: params.selector || params.xpath,
+    multiple: select.multiple,
+    selected: Array.from(select.selectedOptions).map((option) => ({
+      value: option.value,
+      label: option.label.trim(),
+      index: option.index,
+    })),
+  };
+};
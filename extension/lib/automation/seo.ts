@@ -0,0 +1,72 @@
+/**
+ * SEO audit command: collect the facts a page states about itself (title,
+ * meta tags, canonical, headings, alternates, Open Graph, links). Grading
+ * and site-level checks happen on the server.
+ */
+
+import type { Command, CommandHandler } from './types';
+
+// Page links sent for status checks; the server samples from the front
+const MAX_LINKS = 200;
+
+function meta(name: string): string | null {
+  const element = document.querySelector(`meta[name="${name}" i], meta[property="${name}" i]`);
+  return element?.getAttribute('content') ?? null;
+}
+
+function linkHref(rel: string): string | null {
+  const link = document.querySelector<HTMLLinkElement>(`link[rel~="${rel}" i]`);
+  return link ? link.href : null;
+}
+
+export const auditSeoCommand: CommandHandler = async (_command: Command) => {
+  const headings = Array.from(document.querySelectorAll('h1, h2, h3, h4, h5, h6')).map((heading) => ({
+    level: Number(heading.tagName[1]),
+    text: (heading.textContent ?? '').trim().replace(/\s+/g, ' ').slice(0, 120),
+  }));
+
+  const hreflang = Array.from(document.querySelectorAll<HTMLLinkElement>('link[rel~="alternate" i][hreflang]')).map(
+    (link) => ({ lang: link.hreflang, href: link.href })
+  );
+
+  const openGraph: Record<string, string> = {};
+  document.querySelectorAll('meta[property^="og:" i], meta[name^="twitter:" i]').forEach((element) => {
+    const key = (element.getAttribute('property') ?? element.getAttribute('name') ?? '').toLowerCase();
+    const content = element.getAttribute('content');
+    if (key && content && !(key in openGraph)) {
+      openGraph[key] = content;
+    }
+  });
+
+  const links: string[] = [];
+  const seen = new Set<string>();
+  for (const anchor of Array.from(document.querySelectorAll<HTMLAnchorElement>('a[href]'))) {
+    if (links.length >= MAX_LINKS) {
+      break;
+    }
+    const href = anchor.href.split('#')[0];
+    if (/^https?:/.test(href) && !seen.has(href) && !/\bnofollow\b/i.test(anchor.rel)) {
+      seen.add(href);
+      links.push(href);
+    }
+  }
+
+  const text = document.body?.innerText ?? '';
+
+  return {
+    url: location.href,
+    title: document.title || null,
+    metaDescription: meta('description'),
+    canonical: linkHref('canonical'),
+    robots: meta('robots'),
+    lang: document.documentElement.getAttribute('lang'),
+    viewport: meta('viewport') !== null,
+    headings,
+    hreflang,
+    openGraph,
+    structuredData: document.querySelectorAll('script[type="application/ld+json"], [itemscope]').length,
+    wordCount: text.split(/\s+/).filter(Boolean).length,
+    imagesMissingAlt: document.querySelectorAll('img:not([alt])').length,
+    links,
+  };
+};
@@ -0,0 +1,77 @@
+/**
+ * Find-similar-elements command: candidates for a selector that matched
+ * nothing. Visible elements sharing the wanted tag, an id or class
+ * fragment, or a word of the wanted text are gathered and roughly ordered
+ * here; the server does the real ranking.
+ */
+
+import type { Command, CommandHandler, FindSimilarElementsParams, ModeConfig } from './types';
+import { accessibleName, cssPath } from './accessibility';
+
+const MAX_TEXT_LENGTH = 100;
+// Elements looked at before giving up, so huge pages stay fast
+const MAX_SCANNED = 5000;
+const CANDIDATE_SELECTOR = 'a, button, input, select, textarea, label, summary, [role], [onclick], [tabindex], [id], [class]';
+
+function isVisible(element: Element): boolean {
+  const rect = element.getBoundingClientRect();
+  const style = window.getComputedStyle(element);
+  return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+}
+
+function words(value: string): string[] {
+  return value.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter((word) => word.length > 1);
+}
+
+/** Overlap between the hint and an element; 0 means it isn't a candidate */
+function roughScore(element: Element, text: string, params: FindSimilarElementsParams, wantedWords: string[]): number {
+  let score = 0;
+  if (params.tag && element.tagName.toLowerCase() === params.tag) {
+    score += 1;
+  }
+  const id = element.id.toLowerCase();
+  if (params.id && id && (id.includes(params.id.toLowerCase()) || params.id.toLowerCase().includes(id))) {
+    score += 1.5;
+  }
+  const classes = Array.from(element.classList).map((name) => name.toLowerCase());
+  for (const wanted of params.classes ?? []) {
+    if (classes.some((name) => name.includes(wanted.toLowerCase()) || wanted.toLowerCase().includes(name))) {
+      score += 1 / params.classes!.length;
+    }
+  }
+  if (wantedWords.length > 0) {
+    const have = new Set(words(text));
+    score += (2 * wantedWords.filter((word) => have.has(word)).length) / wantedWords.length;
+  }
+  return score;
+}
+
+export const findSimilarElementsCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const params = command.params as FindSimilarElementsParams;
+  const limit = params.limit ?? 25;
+  const wantedWords = params.text ? words(params.text) : [];
+
+  const scored: Array<{ element: Element; text: string; score: number }> = [];
+  const elements = Array.from(document.querySelectorAll(CANDIDATE_SELECTOR)).slice(0, MAX_SCANNED);
+  for (const element of elements) {
+    const text = accessibleName(element).replace(/\s+/g, ' ').trim().slice(0, MAX_TEXT_LENGTH);
+    const score = roughScore(element, text, params, wantedWords);
+    if (score > 0 && isVisible(element)) {
+      scored.push({ element, text, score });
+    }
+  }
+  // Deeper matches win ties, so a button beats the form wrapping its text
+  scored.sort((a, b) => b.score - a.score || Number(a.element.contains(b.element)) - Number(b.element.contains(a.element)));
+
+  return {
+    success: true,
+    selector: params.selector,
+    candidates: scored.slice(0, limit).map(({ element, text }) => ({
+      selector: cssPath(element),
+      tag: element.tagName.toLowerCase(),
+      text,
+      id: element.id || null,
+      className: typeof element.className === 'string' ? element.className : element.getAttribute('class') ?? '',
+    })),
+  };
+};
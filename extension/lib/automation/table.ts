@@ -0,0 +1,161 @@
+/**
+ * Extract-table command: lay an HTML table out as a grid, repeating
+ * colspan/rowspan cells into every slot they cover, and split off the
+ * header rows. The server turns the grid into row objects or CSV.
+ */
+
+import type { Command, CommandHandler, ExtractTableParams } from './types';
+import { cssPath } from './accessibility';
+
+// Limits for spans, which pages sometimes set to absurd values
+const MAX_COLSPAN = 1000;
+const MAX_COLUMNS = 1000;
+
+// Rows laid out beyond maxRows, to leave room for the header rows
+const MAX_HEADER_ROWS = 10;
+
+interface Slot {
+  text: string;
+  isHeader: boolean;
+}
+
+function cellText(cell: HTMLElement): string {
+  return (cell.innerText ?? cell.textContent ?? '').replace(/\s+/g, ' ').trim();
+}
+
+function isVisible(table: HTMLTableElement): boolean {
+  const rect = table.getBoundingClientRect();
+  return rect.width > 0 && rect.height > 0 && getComputedStyle(table).visibility !== 'hidden';
+}
+
+// ============================================================================
+// Finding the Table
+// ============================================================================
+
+/** The table by selector (or the one inside the match), by index, or else the page's largest */
+function findTable(params: ExtractTableParams, tables: HTMLTableElement[]): HTMLTableElement {
+  if (params.selector) {
+    const element = document.querySelector(params.selector);
+    if (!element) {
+      throw new Error(`Element not found: ${params.selector}`);
+    }
+    const table = element instanceof HTMLTableElement ? element : element.querySelector('table');
+    if (!table) {
+      throw new Error(`No table at or inside ${params.selector}`);
+    }
+    return table;
+  }
+  if (tables.length === 0) {
+    throw new Error('No visible tables on the page');
+  }
+  if (params.index !== undefined && params.index !== null) {
+    if (params.index >= tables.length) {
+      throw new Error(`index ${params.index} is out of range: the page has ${tables.length} visible table${tables.length === 1 ? '' : 's'}`);
+    }
+    return tables[params.index];
+  }
+  // Layout tables are usually small; data tables have the most cells
+  return tables.reduce((largest, table) =>
+    table.querySelectorAll('td, th').length > largest.querySelectorAll('td, th').length ? table : largest);
+}
+
+// ============================================================================
+// Grid Layout
+// ============================================================================
+
+/** The table's rows with spanned cells repeated into each slot they cover */
+function layOut(table: HTMLTableElement, maxRows: number): Slot[][] {
+  const rows = Array.from(table.rows);
+  const limit = Math.min(rows.length, maxRows + MAX_HEADER_ROWS);
+  const grid: Slot[][] = [];
+  for (let r = 0; r < limit; r++) {
+    grid[r] ??= [];
+    let column = 0;
+    for (const cell of Array.from(rows[r].cells)) {
+      while (grid[r][column]) {
+        column++;
+      }
+      const text = cellText(cell);
+      const isHeader = cell.tagName === 'TH';
+      const colspan = Math.min(Math.max(cell.colSpan, 1), MAX_COLSPAN);
+      // rowspan="0" spans the rest of the table section
+      const rowspan = cell.rowSpan === 0
+        ? (cell.parentElement?.parentElement as HTMLTableSectionElement | null)?.rows.length ?? 1
+        : Math.max(cell.rowSpan, 1);
+      for (let dr = 0; dr < rowspan && r + dr < limit; dr++) {
+        grid[r + dr] ??= [];
+        for (let dc = 0; dc < colspan && column + dc < MAX_COLUMNS; dc++) {
+          grid[r + dr][column + dc] = { text, isHeader };
+        }
+      }
+      column += colspan;
+    }
+  }
+  return grid;
+}
+
+/** Leading rows in <thead>, or made only of <th> cells */
+function detectHeaderRows(table: HTMLTableElement, grid: Slot[][]): number {
+  if (table.tHead && table.tHead.rows.length > 0) {
+    return table.tHead.rows.length;
+  }
+  let count = 0;
+  while (count < Math.min(grid.length - 1, MAX_HEADER_ROWS) && grid[count].length > 0 && Array.from(grid[count]).every((slot) => slot?.isHeader)) {
+    count++;
+  }
+  return count;
+}
+
+/** One label per column from the header rows, joining stacked labels with " / " */
+function columnHeaders(grid: Slot[][], headerRows: number, columns: number): string[] {
+  const headers: string[] = [];
+  for (let c = 0; c < columns; c++) {
+    const labels: string[] = [];
+    for (let r = 0; r < headerRows; r++) {
+      const text = grid[r][c]?.text;
+      if (text && labels[labels.length - 1] !== text) {
+        labels.push(text);
+      }
+    }
+    headers.push(labels.join(' / ') || `Column ${c + 1}`);
+  }
+  return headers;
+}
+
+// ============================================================================
+// Extract Table Command Handler
+// ============================================================================
+
+export const extractTableCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as ExtractTableParams;
+  const tables = Array.from(document.querySelectorAll('table')).filter(isVisible);
+  const table = findTable(params, tables);
+
+  const grid = layOut(table, params.maxRows);
+  const columns = Math.min(Math.max(0, ...grid.map((row) => row.length)), MAX_COLUMNS);
+  const detected = detectHeaderRows(table, grid);
+  const headerRows = Math.min(params.headerRows ?? detected, grid.length);
+  const headers = columnHeaders(grid, headerRows, columns);
+
+  const bodyRows = table.rows.length - headerRows;
+  const rows = grid
+    .slice(headerRows, headerRows + params.maxRows)
+    .map((row) => Array.from({ length: columns }, (_, c) => row[c]?.text ?? ''))
+    // Spacer rows carry no data
+    .filter((row) => row.some((text) => text !== ''));
+
+  return {
+    success: true,
+    selector: cssPath(table),
+    tableIndex: tables.indexOf(table),
+    visibleTables: tables.length,
+    caption: table.caption ? cellText(table.caption) : null,
+    headerRows,
+    headers,
+    rows,
+    rowCount: rows.length,
+    columnCount: columns,
+    totalRows: bodyRows,
+    truncated: bodyRows > params.maxRows,
+  };
+};
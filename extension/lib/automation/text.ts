@@ -0,0 +1,60 @@
+/**
+ * Get-text command implementation: read rendered or raw text from elements
+ */
+
+import type { Command, CommandHandler, GetTextParams } from './types';
+
+// ============================================================================
+// Text Reading
+// ============================================================================
+
+function readText(element: Element, property: GetTextParams['property']): string {
+  if (property === 'textContent') {
+    return element.textContent ?? '';
+  }
+  // innerText only exists on HTML elements; fall back for SVG and friends
+  return element instanceof HTMLElement ? element.innerText : element.textContent ?? '';
+}
+
+function clip(text: string, maxLength: number): { text: string; truncated: boolean } {
+  return text.length > maxLength
+    ? { text: text.slice(0, maxLength), truncated: true }
+    : { text, truncated: false };
+}
+
+// ============================================================================
+// Get-Text Command Handler
+// ============================================================================
+
+export const getTextCommand: CommandHandler = async (command: Command) => {
+  const params = command.params as GetTextParams;
+  const selector = params.selector ?? 'body';
+
+  if (!params.all) {
+    const element = document.querySelector(selector);
+    if (!element) {
+      throw new Error(`Element not found: ${selector}`);
+    }
+    return {
+      success: true,
+      selector,
+      property: params.property,
+      ...clip(readText(element, params.property), params.maxLength),
+    };
+  }
+
+  const elements = Array.from(document.querySelectorAll(selector));
+  const matches = elements.slice(0, params.limit).map((element, index) => ({
+    index,
+    ...clip(readText(element, params.property), params.maxLength),
+  }));
+
+  return {
+    success: true,
+    selector,
+    property: params.property,
+    count: elements.length,
+    matches,
+    truncated: elements.length > matches.length,
+  };
+};
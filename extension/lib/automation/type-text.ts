@@ -0,0 +1,303 @@
+/**
+ * Type-text command: keystroke-level typing for fields whose frameworks
+ * ignore programmatic value changes. Each character gets keydown, keypress,
+ * beforeinput, input and keyup, with the value written through the native
+ * setter so React-style value tracking sees the change. Runs of CJK text
+ * are typed as an IME would: compositionstart, a compositionupdate and
+ * input per keystroke, then compositionend with the committed text.
+ *
+ * The server plans each field (selector, text, clear, composition) and
+ * checks that the whole run fits in the command timeout.
+ */
+
+import type { Command, CommandHandler, ModeConfig, TypeTextParams } from './types';
+import { bringIntoView } from './scroll';
+
+// keyCode browsers report for keys an IME is processing
+const IME_KEY_CODE = 229;
+
+type EditableField = HTMLInputElement | HTMLTextAreaElement | HTMLElement;
+
+// ============================================================================
+// Keystroke Planning
+// ============================================================================
+
+/** Whether an IME composes this character (kana, CJK ideographs, hangul) */
+export function isComposable(char: string): boolean {
+  const code = char.codePointAt(0) ?? 0;
+  return (code >= 0x3040 && code <= 0x30ff)
+    || (code >= 0x3400 && code <= 0x4dbf)
+    || (code >= 0x4e00 && code <= 0x9fff)
+    || (code >= 0xac00 && code <= 0xd7af)
+    || (code >= 0xf900 && code <= 0xfaff);
+}
+
+/** Split text into keystrokes, grouping composable runs when composition is on */
+export function planKeystrokes(text: string, composition: boolean): Array<{ text: string; composed: boolean }> {
+  const strokes: Array<{ text: string; composed: boolean }> = [];
+  for (const char of Array.from(text)) {
+    const composed = composition && isComposable(char);
+    const last = strokes[strokes.length - 1];
+    if (composed && last?.composed) {
+      last.text += char;
+    } else {
+      strokes.push({ text: char, composed });
+    }
+  }
+  return strokes;
+}
+
+const SHIFTED_DIGITS = ')!@#$%^&*(';
+const PUNCTUATION: Record<string, [string, number, boolean]> = {
+  ' ': ['Space', 32, false],
+  '-': ['Minus', 189, false], '_': ['Minus', 189, true],
+  '=': ['Equal', 187, false], '+': ['Equal', 187, true],
+  '[': ['BracketLeft', 219, false], '{': ['BracketLeft', 219, true],
+  ']': ['BracketRight', 221, false], '}': ['BracketRight', 221, true],
+  '\\': ['Backslash', 220, false], '|': ['Backslash', 220, true],
+  ';': ['Semicolon', 186, false], ':': ['Semicolon', 186, true],
+  "'": ['Quote', 222, false], '"': ['Quote', 222, true],
+  ',': ['Comma', 188, false], '<': ['Comma', 188, true],
+  '.': ['Period', 190, false], '>': ['Period', 190, true],
+  '/': ['Slash', 191, false], '?': ['Slash', 191, true],
+  '`': ['Backquote', 192, false], '~': ['Backquote', 192, true],
+};
+
+/** The KeyboardEvent fields a US keyboard reports for one character */
+export function keyFor(char: string): { key: string; code: string; keyCode: number; shiftKey: boolean } {
+  if (char === '\n') {
+    return { key: 'Enter', code: 'Enter', keyCode: 13, shiftKey: false };
+  }
+  if (char === '\t') {
+    return { key: 'Tab', code: 'Tab', keyCode: 9, shiftKey: false };
+  }
+  if (/^[a-z]$/i.test(char)) {
+    const upper = char.toUpperCase();
+    return { key: char, code: `Key${upper}`, keyCode: upper.charCodeAt(0), shiftKey: char === upper };
+  }
+  if (/^\d$/.test(char)) {
+    return { key: char, code: `Digit${char}`, keyCode: char.charCodeAt(0), shiftKey: false };
+  }
+  const shiftedDigit = SHIFTED_DIGITS.indexOf(char);
+  if (shiftedDigit !== -1) {
+    return { key: char, code: `Digit${shiftedDigit}`, keyCode: 48 + shiftedDigit, shiftKey: true };
+  }
+  const punctuation = PUNCTUATION[char];
+  if (punctuation) {
+    return { key: char, code: punctuation[0], keyCode: punctuation[1], shiftKey: punctuation[2] };
+  }
+  // Characters without a key on the layout (accents, emoji) carry no code
+  return { key: char, code: '', keyCode: 0, shiftKey: false };
+}
+
+/** Delay before a keystroke: delayMs plus up to jitterMs */
+export function keystrokeDelay(delayMs: number, jitterMs: number, random = Math.random): number {
+  return delayMs + (jitterMs > 0 ? Math.floor(random() * (jitterMs + 1)) : 0);
+}
+
+// ============================================================================
+// Editing
+// ============================================================================
+
+function isTextControl(element: Element): element is HTMLInputElement | HTMLTextAreaElement {
+  return element instanceof HTMLInputElement || element instanceof HTMLTextAreaElement;
+}
+
+/** Set a control's value through the prototype setter frameworks don't intercept */
+export function setNativeValue(field: HTMLInputElement | HTMLTextAreaElement, value: string): void {
+  const setter = Object.getOwnPropertyDescriptor(Object.getPrototypeOf(field), 'value')?.set;
+  if (setter) {
+    setter.call(field, value);
+  } else {
+    field.value = value;
+  }
+}
+
+function selectionRange(field: HTMLInputElement | HTMLTextAreaElement): [number, number] {
+  try {
+    return [field.selectionStart ?? field.value.length, field.selectionEnd ?? field.value.length];
+  } catch {
+    // email and number inputs don't expose a selection
+    return [field.value.length, field.value.length];
+  }
+}
+
+function setCaret(field: HTMLInputElement | HTMLTextAreaElement, caret: number): void {
+  try {
+    field.setSelectionRange(caret, caret);
+  } catch {
+    // See selectionRange
+  }
+}
+
+/** Replace `replaced` characters before the caret (and any selection) with text */
+function insertText(element: EditableField, text: string, replaced = 0): void {
+  if (isTextControl(element)) {
+    const [start, end] = selectionRange(element);
+    const from = Math.max(0, start - replaced);
+    setNativeValue(element, element.value.slice(0, from) + text + element.value.slice(end));
+    setCaret(element, from + text.length);
+    return;
+  }
+  const selection = window.getSelection();
+  if (!selection || selection.rangeCount === 0 || !element.contains(selection.anchorNode)) {
+    const range = document.createRange();
+    range.selectNodeContents(element);
+    range.collapse(false);
+    selection?.removeAllRanges();
+    selection?.addRange(range);
+  }
+  for (let i = 0; i < replaced; i++) {
+    selection?.modify('extend', 'backward', 'character');
+  }
+  const range = selection!.getRangeAt(0);
+  range.deleteContents();
+  const node = document.createTextNode(text);
+  range.insertNode(node);
+  range.setStartAfter(node);
+  range.collapse(true);
+  selection!.removeAllRanges();
+  selection!.addRange(range);
+}
+
+function clearField(element: EditableField): void {
+  const init = { key: 'Backspace', code: 'Backspace', keyCode: 8, which: 8, bubbles: true, cancelable: true, composed: true };
+  element.dispatchEvent(new KeyboardEvent('keydown', init));
+  if (isTextControl(element)) {
+    setNativeValue(element, '');
+  } else {
+    element.textContent = '';
+  }
+  element.dispatchEvent(new InputEvent('input', { bubbles: true, inputType: 'deleteContentBackward' }));
+  element.dispatchEvent(new KeyboardEvent('keyup', init));
+}
+
+// ============================================================================
+// Typing
+// ============================================================================
+
+/** Type one character; returns false when a page handler prevented it */
+function typeCharacter(element: EditableField, char: string): boolean {
+  const key = keyFor(char);
+  const init: KeyboardEventInit = {
+    ...key,
+    which: key.keyCode,
+    bubbles: true,
+    cancelable: true,
+    composed: true,
+  };
+  let allowed = element.dispatchEvent(new KeyboardEvent('keydown', init))
+    && (char === '\t' || element.dispatchEvent(new KeyboardEvent('keypress', { ...init, charCode: char.codePointAt(0) })));
+
+  // Tab isn't text, and a single-line input drops line breaks
+  if (allowed && char !== '\t' && !(char === '\n' && element instanceof HTMLInputElement)) {
+    const inputType = char === '\n' ? (element.isContentEditable ? 'insertParagraph' : 'insertLineBreak') : 'insertText';
+    const data = char === '\n' ? null : char;
+    allowed = element.dispatchEvent(new InputEvent('beforeinput', { bubbles: true, cancelable: true, composed: true, inputType, data }));
+    if (allowed) {
+      insertText(element, char);
+      element.dispatchEvent(new InputEvent('input', { bubbles: true, composed: true, inputType, data }));
+    }
+  }
+  element.dispatchEvent(new KeyboardEvent('keyup', init));
+  return allowed;
+}
+
+/** Type a run of composable characters the way an IME commits it */
+async function typeComposition(element: EditableField, run: string, pause: () => Promise<void>): Promise<void> {
+  element.dispatchEvent(new CompositionEvent('compositionstart', { bubbles: true, data: '' }));
+  const chars = Array.from(run);
+  let composed = '';
+  for (let i = 0; i < chars.length; i++) {
+    if (i > 0) {
+      await pause();
+    }
+    const init: KeyboardEventInit = { key: 'Process', code: '', keyCode: IME_KEY_CODE, which: IME_KEY_CODE, isComposing: true, bubbles: true, cancelable: true, composed: true };
+    element.dispatchEvent(new KeyboardEvent('keydown', init));
+    const previous = composed;
+    composed += chars[i];
+    element.dispatchEvent(new CompositionEvent('compositionupdate', { bubbles: true, data: composed }));
+    element.dispatchEvent(new InputEvent('beforeinput', { bubbles: true, composed: true, inputType: 'insertCompositionText', data: composed, isComposing: true }));
+    insertText(element, composed, Array.from(previous).length);
+    element.dispatchEvent(new InputEvent('input', { bubbles: true, composed: true, inputType: 'insertCompositionText', data: composed, isComposing: true }));
+    element.dispatchEvent(new KeyboardEvent('keyup', init));
+  }
+  element.dispatchEvent(new CompositionEvent('compositionend', { bubbles: true, data: composed }));
+}
+
+function findField(selector: string): EditableField {
+  const element = document.querySelector<HTMLElement>(selector);
+  if (!element) {
+    throw new Error(`Element not found: ${selector}`);
+  }
+  if (!isTextControl(element) && !element.isContentEditable) {
+    throw new Error(`Element is not an input, textarea, or contentEditable element: ${selector}`);
+  }
+  if (isTextControl(element) && (element.disabled || element.readOnly)) {
+    throw new Error(`Field is ${element.disabled ? 'disabled' : 'read-only'}: ${selector}`);
+  }
+  return element;
+}
+
+// ============================================================================
+// Type-Text Command Handler
+// ============================================================================
+
+export const typeTextCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const params = command.params as TypeTextParams;
+  const started = Date.now();
+  const pause = () => new Promise<void>((resolve) => setTimeout(resolve, keystrokeDelay(params.delayMs, params.jitterMs)));
+
+  // Check every field first so a bad selector doesn't leave the form half typed
+  const elements = params.fields.map((field) => findField(field.selector));
+
+  const fields: Record<string, any>[] = [];
+  for (let f = 0; f < params.fields.length; f++) {
+    const field = params.fields[f];
+    const element = elements[f];
+    const view = await bringIntoView(element);
+    if (view.visibleRatio === 0) {
+      throw new Error(`Element could not be scrolled into view: ${view.reason}`);
+    }
+    element.focus();
+    if (field.clear) {
+      clearField(element);
+    }
+
+    let typed = 0;
+    let prevented = 0;
+    const strokes = planKeystrokes(field.text, field.composition);
+    for (let i = 0; i < strokes.length; i++) {
+      if (i > 0 || f > 0) {
+        await pause();
+      }
+      const stroke = strokes[i];
+      if (stroke.composed) {
+        await typeComposition(element, stroke.text, pause);
+        typed += Array.from(stroke.text).length;
+      } else if (typeCharacter(element, stroke.text)) {
+        typed++;
+      } else {
+        prevented++;
+      }
+    }
+    element.dispatchEvent(new Event('change', { bubbles: true }));
+
+    fields.push({
+      selector: field.selector,
+      characters: Array.from(field.text).length,
+      typed,
+      prevented,
+      composition: field.composition,
+      value: isTextControl(element)
+        ? (element instanceof HTMLInputElement && element.type === 'password' ? null : element.value)
+        : element.innerText,
+    });
+  }
+
+  return {
+    success: true,
+    fields,
+    durationMs: Date.now() - started,
+  };
+};
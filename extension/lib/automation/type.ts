@@ -4,6 +4,7 @@
 
 import type { Command, CommandHandler, ModeConfig, TypeParams } from './types';
 import { getRandomDelay } from './mode-config';
+import { bringIntoView } from './scroll';
 
 // ============================================================================
 // Element Finding Utilities
@@ -206,7 +207,10 @@ export const typeCommand: CommandHandler = async (command: Command, config: Mode
   }
 
   // Scroll into view
-  element.scrollIntoView({ behavior: 'auto', block: 'center' });
+  const view = await bringIntoView(element);
+  if (view.visibleRatio === 0) {
+    throw new Error(`Element could not be scrolled into view: ${view.reason}`);
+  }
 
   // Wait for custom delay if specified
   if (params.delay) {
@@ -38,13 +38,42 @@ export interface ModeConfig {
 export type CommandAction =
   | 'click'
   | 'type'
+  | 'type_text'
+  | 'select'
+  | 'fill_form'
   | 'scroll'
+  | 'scroll_into_view'
   | 'wait'
   | 'screenshot'
   | 'navigate'
   | 'get_element'
+  | 'get_text'
+  | 'get_html'
+  | 'extract_content'
+  | 'find_text'
+  | 'collect_items'
+  | 'extract_table'
+  | 'list_forms'
+  | 'press_key'
+  | 'audit_accessibility'
+  | 'accessibility_snapshot'
+  | 'audit_seo'
+  | 'list_links'
   | 'detect_modal'
-  | 'dismiss_modal';
+  | 'dismiss_modal'
+  | 'dismiss_consent'
+  | 'find_similar_elements'
+  | 'focus'
+  | 'blur'
+  | 'get_focused_element'
+  | 'hover'
+  | 'wait_for_selector'
+  | 'query_elements'
+  | 'set_date'
+  | 'canvas_read_pixels'
+  | 'media_control'
+  | 'media_get_state'
+  | 'media_capture_frame';
 
 export interface Command {
   commandId: string;
@@ -76,6 +105,8 @@ export interface ClickParams {
   button?: 'left' | 'right' | 'middle';
   clickCount?: number;
   delay?: number;
+  /** Keep the element clear of fixed/sticky headers and footers */
+  avoidStickyChrome?: boolean;
 }
 
 export interface TypeParams {
@@ -86,6 +117,176 @@ export interface TypeParams {
   delay?: number;
 }
 
+export interface TypeTextParams {
+  /** Typed in order; the server decides clear and composition per field */
+  fields: Array<{ selector: string; text: string; clear: boolean; composition: boolean }>;
+  delayMs: number;
+  jitterMs: number;
+}
+
+export interface SelectParams {
+  selector?: string;
+  xpath?: string;
+  /** How entries in `options` identify an option */
+  by: 'value' | 'label' | 'index';
+  options: Array<string | number>;
+}
+
+/** Playwright-style locator; exactly one of role, text, label or placeholder is set */
+export interface Locator {
+  role?: string;
+  /** Accessible name, with role */
+  name?: string;
+  text?: string;
+  label?: string;
+  placeholder?: string;
+  /** Match the whole text, case-sensitively (default: substring, ignoring case) */
+  exact?: boolean;
+  /** Which match to use, from 0 (default: 0) */
+  nth?: number;
+}
+
+export interface FillFormField {
+  /** CSS selector; set either this or locator */
+  selector?: string;
+  locator?: Locator;
+  /** Expected field type; 'auto' goes by the element found */
+  type: 'auto' | 'text' | 'checkbox' | 'radio' | 'select';
+  value: string | boolean | string[];
+  /** Clear a text field before typing (default: true) */
+  clear?: boolean;
+}
+
+export interface FillFormParams {
+  /** Filled in order */
+  fields: FillFormField[];
+  stopOnError: boolean;
+}
+
+export interface GetTextParams {
+  /** CSS selector; defaults to the whole body */
+  selector?: string;
+  /** Return every match instead of the first */
+  all: boolean;
+  property: 'innerText' | 'textContent';
+  /** Characters kept per element */
+  maxLength: number;
+  /** Matches returned when `all` is set */
+  limit: number;
+}
+
+export interface PressKeyParams {
+  /** Focus this element before pressing; otherwise the focused element receives the key */
+  selector?: string;
+  /** KeyboardEvent.key value: a named key (Enter, Tab, ArrowDown, ...) or one character */
+  key: string;
+  modifiers: Array<'Alt' | 'Control' | 'Meta' | 'Shift'>;
+  count: number;
+}
+
+export interface GetHtmlParams {
+  /** CSS selector; the whole document when absent */
+  selector?: string;
+  stripScripts?: boolean;
+  stripStyles?: boolean;
+  stripComments?: boolean;
+  /** Characters returned before truncating; unlimited when absent */
+  maxLength?: number;
+}
+
+export interface ExtractContentParams {
+  /** Element to convert; defaults to the detected main content */
+  selector?: string;
+  maxLength: number;
+  /** Render links inline as [text](url) */
+  includeLinks: boolean;
+  includeImages: boolean;
+}
+
+export interface FindTextParams {
+  query?: string;
+  regex?: boolean;
+  caseSensitive?: boolean;
+  wholeWord?: boolean;
+  /** Only search inside this element */
+  selector?: string;
+  contextChars: number;
+  maxResults: number;
+  /** Zero-based index of the match to scroll to */
+  scrollTo?: number | null;
+  highlight: boolean;
+  /** Only remove the previous search's highlights */
+  clear?: boolean;
+}
+
+export interface CollectItemsParams {
+  /** Selector matching each item of the feed */
+  itemSelector: string;
+  /** Values read per item: name -> "selector", "selector@attribute" or "@attribute" */
+  fields?: Record<string, string> | null;
+  /** Element to scroll (default: the items' scrolling ancestor, or the page) */
+  container?: string | null;
+  /** Clicked instead of scrolling once the container is at the bottom */
+  loadMoreSelector?: string | null;
+  maxItems: number;
+  maxScrolls: number;
+  timeoutMs: number;
+  /** How long to wait for new items after each scroll */
+  waitMs: number;
+  /** Scrolls at the bottom without new items before giving up */
+  idleScrolls: number;
+  maxTextLength: number;
+}
+
+export interface ExtractTableParams {
+  /** The table, or an element containing it */
+  selector?: string | null;
+  /** Zero-based index among the page's visible tables */
+  index?: number | null;
+  /** Leading rows used as column headers (default: detected from thead/th) */
+  headerRows?: number | null;
+  maxRows: number;
+}
+
+export interface ListFormsParams {
+  /** Only list forms inside this element (or this form) */
+  selector?: string | null;
+  /** Also list hidden inputs and fields that aren't displayed */
+  includeHidden: boolean;
+  /** Options listed per select */
+  maxOptions: number;
+}
+
+export interface ListLinksParams {
+  /** Also list img/script/stylesheet/media URLs */
+  includeResources?: boolean;
+}
+
+export interface AccessibilityAuditParams {
+  /** Limit the audit to this subtree */
+  selector?: string;
+  /** Rule ids to run */
+  rules: string[];
+}
+
+export interface AccessibilitySnapshotParams {
+  /** Limit the tree to this subtree */
+  selector?: string;
+  /** Drop generic containers and plain text, hoisting their children */
+  interestingOnly: boolean;
+  maxDepth: number;
+  maxNodes: number;
+}
+
+export interface FocusParams {
+  selector: string;
+}
+
+export interface BlurParams {
+  /** Defaults to document.activeElement */
+  selector?: string;
+}
+
 export interface ScrollParams {
   x?: number;
   y?: number;
@@ -93,6 +294,15 @@ export interface ScrollParams {
   behavior?: 'auto' | 'smooth';
 }
 
+export interface ScrollIntoViewParams {
+  selector: string;
+  block: 'start' | 'center' | 'end' | 'nearest';
+  detectSticky: boolean;
+  /** Pixels obscured at the top or bottom; null to detect */
+  offsetTop: number | null;
+  offsetBottom: number | null;
+}
+
 export interface WaitParams {
   type: 'time' | 'selector' | 'navigation' | 'load';
   duration?: number;
@@ -100,6 +310,94 @@ export interface WaitParams {
   timeout?: number;
 }
 
+export interface WaitForSelectorParams {
+  selector: string;
+  state?: 'attached' | 'visible' | 'hidden' | 'detached';
+  timeout?: number;
+}
+
+export interface QueryElementsParams {
+  selector: string;
+  /** Only search inside elements matching this selector */
+  within?: string;
+  /** Skip hidden elements, and hidden containers */
+  visibleOnly?: boolean;
+  limit?: number;
+}
+
+export interface HoverParams {
+  selector: string;
+  /** Point relative to the element's top-left corner (default: its center) */
+  position?: { x: number; y: number };
+}
+
+/** set_date as prepared by the server */
+export interface SetDateParams {
+  selector: string;
+  /** YYYY-MM-DD */
+  date: string;
+  /** HH:MM, for datetime-local and time inputs */
+  time: string | null;
+  year: number;
+  month: number;
+  day: number;
+  /** English month name, e.g. "March" */
+  monthName: string;
+  strategies: Array<'inject' | 'widget'>;
+}
+
+/** canvas_read_pixels as prepared by the server */
+export interface CanvasReadPixelsParams {
+  selector: string;
+  x: number;
+  y: number;
+  /** Default: the rest of the canvas */
+  width: number | null;
+  height: number | null;
+  mode: 'image' | 'average';
+  /** Cells per side in average mode */
+  grid: number;
+  maxPixels: number;
+}
+
+export interface MediaParams {
+  /** Default: the first video (or audio, except for frame capture) */
+  selector?: string;
+  action?: 'play' | 'pause' | 'seek' | 'mute' | 'unmute';
+  /** Seconds, for seek and for frame capture */
+  time?: number;
+}
+
+/** What a selector that matched nothing was after, as the server parsed it */
+export interface FindSimilarElementsParams {
+  selector: string;
+  tag?: string | null;
+  id?: string | null;
+  classes?: string[];
+  text?: string | null;
+  limit?: number;
+}
+
+/** One consent-management platform, as the server's ruleset sends it */
+export interface ConsentRule {
+  platform: string;
+  /** Present while the banner is showing */
+  detect: string;
+  accept: string;
+  reject?: string;
+  /** Shadow root host the buttons live under */
+  shadowHost?: string;
+  /** Iframe the buttons are rendered in */
+  frame?: string;
+  /** accept or reject, resolved for the mode */
+  button: string;
+}
+
+export interface DismissConsentParams {
+  mode: 'accept' | 'reject';
+  rules: ConsentRule[];
+}
+
 export interface ScreenshotParams {
   fullPage?: boolean;
   selector?: string;
@@ -2,7 +2,7 @@
  * Wait command implementation with multiple wait types
  */
 
-import type { Command, CommandHandler, ModeConfig, WaitParams } from './types';
+import type { Command, CommandHandler, ModeConfig, WaitForSelectorParams, WaitParams } from './types';
 
 // ============================================================================
 // Wait Utilities
@@ -12,6 +12,12 @@ async function waitForTime(duration: number): Promise<void> {
   await new Promise((resolve) => setTimeout(resolve, duration));
 }
 
+function isVisible(element: Element): boolean {
+  const rect = element.getBoundingClientRect();
+  const style = window.getComputedStyle(element);
+  return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none' && style.opacity !== '0';
+}
+
 async function waitForSelector(
   selector: string,
   timeout: number = 5000
@@ -20,21 +26,8 @@ async function waitForSelector(
 
   while (Date.now() - startTime < timeout) {
     const element = document.querySelector(selector);
-    if (element) {
-      // Check if element is visible
-      const rect = element.getBoundingClientRect();
-      const style = window.getComputedStyle(element);
-
-      const isVisible =
-        rect.width > 0 &&
-        rect.height > 0 &&
-        style.visibility !== 'hidden' &&
-        style.display !== 'none' &&
-        style.opacity !== '0';
-
-      if (isVisible) {
-        return element;
-      }
+    if (element && isVisible(element)) {
+      return element;
     }
 
     // Wait before checking again
@@ -183,3 +176,46 @@ export const waitCommand: CommandHandler = async (command: Command, config: Mode
       throw new Error(`Unknown wait type: ${params.type}`);
   }
 };
+
+// ============================================================================
+// Wait-For-Selector Command Handler
+// ============================================================================
+
+const SELECTOR_POLL_MS = 50;
+
+/**
+ * Wait until a selector is attached, visible, hidden or detached. Used by
+ * server-side tools such as explore_menu that wait for a menu to open.
+ */
+export const waitForSelectorCommand: CommandHandler = async (command: Command, _config: ModeConfig) => {
+  const { selector, state = 'visible', timeout = 5000 } = command.params as WaitForSelectorParams;
+  if (!selector) {
+    throw new Error('Selector is required for wait_for_selector');
+  }
+  const startTime = Date.now();
+
+  while (true) {
+    const matches = Array.from(document.querySelectorAll(selector));
+    const visible = matches.find(isVisible);
+    const reached = state === 'attached' ? matches.length > 0
+      : state === 'detached' ? matches.length === 0
+      : state === 'hidden' ? visible === undefined
+      : visible !== undefined;
+    if (reached) {
+      const element = state === 'visible' ? visible : matches[0];
+      return {
+        success: true,
+        selector,
+        state,
+        count: matches.length,
+        bounds: element ? element.getBoundingClientRect() : null,
+        duration: Date.now() - startTime,
+      };
+    }
+    if (Date.now() - startTime >= timeout) {
+      const found = matches.length === 0 ? 'no element matches' : `${matches.length} match, ${visible ? 'some' : 'none'} visible`;
+      throw new Error(`Timeout after ${timeout}ms waiting for ${selector} to be ${state} (${found})`);
+    }
+    await waitForTime(SELECTOR_POLL_MS);
+  }
+};
@@ -0,0 +1,100 @@
+import { FORWARDED_TOOLS, type ForwardedTool } from './forwarded-tools';
+
+/**
+ * Commands this build handles, with the version of each command's behavior,
+ * sent to the server in `capabilities` on connect. The server hides tools
+ * whose commands are missing here (or older than they need) instead of
+ * forwarding calls that would fail.
+ *
+ * Bump a command's version when its parameters or result change in a way
+ * server tools depend on, and update `SERVER_TOOL_COMMANDS` in
+ * server/src/mcp/tools.rs to match.
+ */
+export const COMMAND_VERSIONS: Record<string, number> = {
+  // Handled by the background script
+  // v2: result includes url, title and viewport
+  screenshot: 2,
+  image_diff: 1,
+  screenshot_elements: 1,
+  print_to_pdf: 1,
+  passkey_enable: 1,
+  passkey_status: 1,
+  passkey_list: 1,
+  passkey_clear: 1,
+  watch_extract: 1,
+  evaluate: 1,
+  go_back: 1,
+  go_forward: 1,
+  reload: 1,
+  cookies_get: 1,
+  cookies_set: 1,
+  cookies_clear: 1,
+  downloads_list: 1,
+  assert_page: 1,
+  active_tab: 1,
+  profiling_start: 1,
+  profiling_stop: 1,
+  wait_for_load: 1,
+  page_info: 1,
+  bring_to_front: 1,
+  set_geolocation: 1,
+  emulate_locale: 1,
+  throttle: 1,
+  dialog_policy: 1,
+  dialog_respond: 1,
+  screencast_start: 1,
+  screencast_stop: 1,
+  list_tabs: 1,
+  network_capture_config: 1,
+  list_frames: 1,
+  list_service_workers: 1,
+  unregister_service_workers: 1,
+  clear_site_data: 1,
+
+  // Routed to the content script
+  navigate: 1,
+  click: 1,
+  type: 1,
+  type_text: 1,
+  select: 1,
+  fill_form: 1,
+  scroll_into_view: 1,
+  wait: 1,
+  get_element: 1,
+  get_text: 1,
+  press_key: 1,
+  get_html: 1,
+  extract_content: 1,
+  find_text: 1,
+  collect_items: 1,
+  extract_table: 1,
+  list_forms: 1,
+  audit_accessibility: 1,
+  accessibility_snapshot: 1,
+  audit_seo: 1,
+  list_links: 1,
+  detect_modal: 1,
+  dismiss_modal: 1,
+  dismiss_consent: 1,
+  focus: 1,
+  blur: 1,
+  get_focused_element: 1,
+  hover: 1,
+  wait_for_selector: 1,
+  query_elements: 1,
+  find_similar_elements: 1,
+  set_date: 1,
+  canvas_read_pixels: 1,
+  media_control: 1,
+  media_get_state: 1,
+  media_capture_frame: 1,
+};
+
+/**
+ * Tools sent to the server in `register_tools`: the forwarded tools whose
+ * command this build handles. `cargo xtask handlers` checks that every
+ * command listed above has a handler in the background or content script.
+ */
+export function registeredTools(): ForwardedTool[] {
+  return FORWARDED_TOOLS.filter((tool) => tool.command in COMMAND_VERSIONS);
+}
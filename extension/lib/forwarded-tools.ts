@@ -0,0 +1,2369 @@
+// @generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit
+
+export interface ForwardedTool {
+  name: string;
+  command: string;
+  description: string;
+  inputSchema: Record<string, unknown>;
+}
+
+/**
+ * Definitions of the tools the server forwards to an extension command. The
+ * extension registers the ones whose command is in COMMAND_VERSIONS
+ * (lib/capabilities.ts), so a build lacking a handler doesn't offer its tool.
+ */
+export const FORWARDED_TOOLS: ForwardedTool[] = [
+  {
+    "command": "navigate",
+    "description": "Navigate to a URL in the browser",
+    "inputSchema": {
+      "properties": {
+        "bypassServiceWorker": {
+          "description": "Bypass registered service workers for this navigation so the network response is used instead of a cached app shell (default: false)",
+          "type": "boolean"
+        },
+        "timeoutMs": {
+          "description": "How long to wait for waitUntil (default: 20000)",
+          "type": "number"
+        },
+        "url": {
+          "description": "The URL to navigate to",
+          "type": "string"
+        },
+        "waitUntil": {
+          "description": "Block until the new page reaches this state: DOMContentLoaded fired, load fired, or load plus 500 ms without network requests (default: don't wait)",
+          "enum": [
+            "domcontentloaded",
+            "load",
+            "networkidle"
+          ],
+          "type": "string"
+        }
+      },
+      "required": [
+        "url"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_navigate"
+  },
+  {
+    "command": "page_info",
+    "description": "Answer \"where am I?\" in one cheap call: the current URL, title, ready state, scroll position, viewport and document size",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "browser_page_info"
+  },
+  {
+    "command": "bring_to_front",
+    "description": "Make the automated tab the active tab of a focused, unminimized window and report whether the page is now visible. Background tabs throttle timers and pause animations, media and requestAnimationFrame, which silently stalls some pages",
+    "inputSchema": {
+      "properties": {
+        "checkOnly": {
+          "description": "Only report whether the tab is in the foreground, without changing anything (default: false)",
+          "type": "boolean"
+        },
+        "focusWindow": {
+          "description": "Also focus the tab's window above other windows (default: true)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_bring_to_front"
+  },
+  {
+    "command": "wait_for_load",
+    "description": "Wait until the page reaches a load state, instead of sleeping after an action. With expectNavigation, waits for the page the previous action navigated to",
+    "inputSchema": {
+      "properties": {
+        "expectNavigation": {
+          "description": "Only accept a document created after the previous command started, e.g. the page a click submitted a form to; without it the current page counts (default: false)",
+          "type": "boolean"
+        },
+        "idleMs": {
+          "description": "Quiet period networkidle needs (default: 500)",
+          "type": "number"
+        },
+        "maxInflight": {
+          "description": "Requests allowed to stay open during the quiet period, for pages with long polls or streams (default: 0)",
+          "minimum": 0,
+          "type": "integer"
+        },
+        "state": {
+          "description": "domcontentloaded: the HTML is parsed; load: the page and its subresources finished loading; networkidle: load, then idleMs without network requests (default: load)",
+          "enum": [
+            "domcontentloaded",
+            "load",
+            "networkidle"
+          ],
+          "type": "string"
+        },
+        "timeoutMs": {
+          "description": "How long to wait (default: 20000)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_wait_for_load"
+  },
+  {
+    "command": "go_back",
+    "description": "Go back one page in the tab's history and wait for it to load; returns the resulting URL and title",
+    "inputSchema": {
+      "properties": {
+        "timeoutMs": {
+          "description": "How long to wait for the page to load (default: 20000)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_go_back"
+  },
+  {
+    "command": "go_forward",
+    "description": "Go forward one page in the tab's history and wait for it to load; returns the resulting URL and title",
+    "inputSchema": {
+      "properties": {
+        "timeoutMs": {
+          "description": "How long to wait for the page to load (default: 20000)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_go_forward"
+  },
+  {
+    "command": "reload",
+    "description": "Reload the current page and wait for it to load; returns the resulting URL and title",
+    "inputSchema": {
+      "properties": {
+        "bypassCache": {
+          "description": "Ignore the HTTP cache, like a hard reload (default: false)",
+          "type": "boolean"
+        },
+        "timeoutMs": {
+          "description": "How long to wait for the page to load (default: 20000)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_reload"
+  },
+  {
+    "command": "click",
+    "description": "Click an element on the page",
+    "inputSchema": {
+      "properties": {
+        "avoidStickyChrome": {
+          "description": "Scroll the element clear of fixed/sticky headers and footers before clicking (default: false)",
+          "type": "boolean"
+        },
+        "button": {
+          "description": "Mouse button (default: left)",
+          "enum": [
+            "left",
+            "right",
+            "middle"
+          ],
+          "type": "string"
+        },
+        "captureReceipt": {
+          "description": "After the click, save a screenshot, the final URL, and confirmation text to the session's receipts as evidence of the submission (default: false)",
+          "type": "boolean"
+        },
+        "clickCount": {
+          "description": "Number of clicks; 2 also fires dblclick (default: 1)",
+          "maximum": 3,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector for the element to click",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_click"
+  },
+  {
+    "command": "click",
+    "description": "Double-click an element, e.g. to edit a cell or select a word",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector for the element to double-click",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_dblclick"
+  },
+  {
+    "command": "click",
+    "description": "Right-click an element to open its context menu",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector for the element to right-click",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_context_click"
+  },
+  {
+    "command": "scroll_into_view",
+    "description": "Scroll an element into view, compensating for fixed/sticky headers and footers so it is not left underneath them. Reports whether the element ended up visible, and why not if it didn't",
+    "inputSchema": {
+      "properties": {
+        "block": {
+          "description": "Vertical alignment within the unobscured viewport (default: center)",
+          "enum": [
+            "start",
+            "center",
+            "end",
+            "nearest"
+          ],
+          "type": "string"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "offsetBottom": {
+          "description": "Pixels obscured at the bottom of the viewport; overrides detection",
+          "type": "number"
+        },
+        "offsetTop": {
+          "description": "Pixels obscured at the top of the viewport; overrides detection",
+          "type": "number"
+        },
+        "selector": {
+          "description": "CSS selector for the element",
+          "type": "string"
+        },
+        "stickyCompensation": {
+          "description": "auto detects fixed/sticky chrome at the viewport edges, none scrolls like element.scrollIntoView (default: auto)",
+          "enum": [
+            "auto",
+            "none"
+          ],
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "scroll_into_view"
+  },
+  {
+    "command": "type",
+    "description": "Fill out an input field",
+    "inputSchema": {
+      "properties": {
+        "captureReceipt": {
+          "description": "After filling, save a screenshot, the final URL, and confirmation text to the session's receipts (the typed value is not recorded; default: false)",
+          "type": "boolean"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector for the input element",
+          "type": "string"
+        },
+        "value": {
+          "description": "The text to type into the input",
+          "type": "string"
+        }
+      },
+      "required": [
+        "value"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_fill"
+  },
+  {
+    "command": "select",
+    "description": "Choose options in a <select> dropdown by value, visible label, or index (playwright_fill does not work on selects). Pass a list to choose several options in a multi-select; the previous selection is replaced",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "index": {
+          "description": "Zero-based option position(s) to select",
+          "oneOf": [
+            {
+              "type": "number"
+            },
+            {
+              "items": {
+                "type": "number"
+              },
+              "type": "array"
+            }
+          ]
+        },
+        "label": {
+          "description": "Visible option text(s) to select",
+          "oneOf": [
+            {
+              "type": "string"
+            },
+            {
+              "items": {
+                "type": "string"
+              },
+              "type": "array"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector for the <select> element",
+          "type": "string"
+        },
+        "value": {
+          "description": "Option value attribute(s) to select",
+          "oneOf": [
+            {
+              "type": "string"
+            },
+            {
+              "items": {
+                "type": "string"
+              },
+              "type": "array"
+            }
+          ]
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_select"
+  },
+  {
+    "command": "list_forms",
+    "description": "List the forms on the page with their fields and submit buttons, to plan a playwright_fill_form call instead of guessing selectors. Each field has a selector addressing only it, its fillType for playwright_fill_form, label, name, required flag, current value (never passwords), and the options of selects and radio groups. Fields outside any <form> are listed as one extra form marked formless",
+    "inputSchema": {
+      "properties": {
+        "includeHidden": {
+          "description": "Also list hidden inputs and fields that aren't displayed, each with a visible flag (default: false)",
+          "type": "boolean"
+        },
+        "maxOptions": {
+          "description": "Options listed per select; optionCount has the total (default: 50)",
+          "maximum": 1000,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "selector": {
+          "description": "Only list forms inside this CSS selector, or just this form (default: the whole page)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_list_forms"
+  },
+  {
+    "command": "fill_form",
+    "description": "Fill several form fields in one call, in order, and report success per field. Each field's value decides how it is filled unless type is given: a string or number is typed into a text input, textarea or contenteditable (replacing its content) or chooses a <select> option by value or label; true/false sets a checkbox; a string or true picks a radio button (the selector may match any radio in the group); a list chooses options in a multi-select. Fields that fail are reported and the rest are still filled unless stopOnError is set",
+    "inputSchema": {
+      "properties": {
+        "fields": {
+          "items": {
+            "properties": {
+              "clear": {
+                "description": "Clear a text field before typing (default: true)",
+                "type": "boolean"
+              },
+              "locator": {
+                "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+                "properties": {
+                  "exact": {
+                    "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+                    "type": "boolean"
+                  },
+                  "label": {
+                    "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+                    "type": "string"
+                  },
+                  "name": {
+                    "description": "Accessible name the role match must have (with role only)",
+                    "type": "string"
+                  },
+                  "nth": {
+                    "description": "Which match to use when several match (default: 0, the first in document order)",
+                    "minimum": 0,
+                    "type": "integer"
+                  },
+                  "placeholder": {
+                    "description": "Placeholder of an input or textarea",
+                    "type": "string"
+                  },
+                  "role": {
+                    "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+                    "type": "string"
+                  },
+                  "text": {
+                    "description": "Visible text; matches the innermost element containing it",
+                    "type": "string"
+                  }
+                },
+                "type": "object"
+              },
+              "selector": {
+                "description": "CSS selector for the field",
+                "type": "string"
+              },
+              "type": {
+                "description": "Expected field type; the call fails for this field if the element is something else (default: auto)",
+                "enum": [
+                  "auto",
+                  "text",
+                  "checkbox",
+                  "radio",
+                  "select"
+                ],
+                "type": "string"
+              },
+              "value": {
+                "description": "Text to enter, checkbox state, radio value, or option(s) to select",
+                "oneOf": [
+                  {
+                    "type": "string"
+                  },
+                  {
+                    "type": "number"
+                  },
+                  {
+                    "type": "boolean"
+                  },
+                  {
+                    "items": {
+                      "type": "string"
+                    },
+                    "type": "array"
+                  }
+                ]
+              }
+            },
+            "required": [
+              "value"
+            ],
+            "type": "object"
+          },
+          "maxItems": 100,
+          "minItems": 1,
+          "type": "array"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "stopOnError": {
+          "description": "Stop at the first field that fails (default: false)",
+          "type": "boolean"
+        }
+      },
+      "required": [
+        "fields"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_fill_form"
+  },
+  {
+    "command": "press_key",
+    "description": "Press a key, optionally focusing an element first: Enter to submit a search box, Tab/Shift+Tab to move focus, Escape to close a popup, arrows to move through menus, lists and selects. Key events go to the focused element, and their usual effect (submit, focus change, toggle, scroll) is applied unless the page cancels it",
+    "inputSchema": {
+      "properties": {
+        "count": {
+          "description": "Number of times to press the key (default: 1, max: 50)",
+          "type": "number"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "key": {
+          "description": "Enter, Tab, Escape, Backspace, Delete, Space, ArrowUp/Down/Left/Right, Home, End, PageUp, PageDown, F1-F12 or a single character; a combo like \"Shift+Tab\" or \"Control+a\" is also accepted",
+          "type": "string"
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "modifiers": {
+          "description": "Modifier keys held during the press",
+          "items": {
+            "enum": [
+              "Alt",
+              "Control",
+              "Meta",
+              "Shift"
+            ],
+            "type": "string"
+          },
+          "type": "array"
+        },
+        "selector": {
+          "description": "CSS selector of an element to focus before pressing (default: the currently focused element)",
+          "type": "string"
+        }
+      },
+      "required": [
+        "key"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_press_key"
+  },
+  {
+    "command": "get_text",
+    "description": "Read the text of an element (or the whole page body) without a screenshot. Returns the rendered innerText by default, or the raw textContent including hidden nodes; with all: true, returns the text of every match",
+    "inputSchema": {
+      "properties": {
+        "all": {
+          "description": "Return every matching element's text instead of the first (default: false)",
+          "type": "boolean"
+        },
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "limit": {
+          "description": "Matches returned with all: true (default: 100, max: 1000)",
+          "type": "number"
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "maxLength": {
+          "description": "Characters returned per element before truncating (default: 50000, max: 1000000)",
+          "type": "number"
+        },
+        "property": {
+          "description": "innerText (as rendered, hidden elements skipped) or textContent (all text nodes) (default: innerText)",
+          "enum": [
+            "innerText",
+            "textContent"
+          ],
+          "type": "string"
+        },
+        "selector": {
+          "description": "CSS selector (default: body)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_get_text"
+  },
+  {
+    "command": "get_html",
+    "description": "Return the outerHTML of an element, or of the whole document, to inspect page structure and build selectors. Current form values are included (passwords never are); scripts and inline event handlers are stripped by default",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "maxLength": {
+          "description": "Characters returned before truncating (default: 200000, max: 5000000)",
+          "type": "number"
+        },
+        "selector": {
+          "description": "CSS selector of the element (default: the whole document)",
+          "type": "string"
+        },
+        "stripComments": {
+          "description": "Remove HTML comments (default: true)",
+          "type": "boolean"
+        },
+        "stripScripts": {
+          "description": "Remove <script>, <noscript>, <template>, on* attributes and javascript: links (default: true)",
+          "type": "boolean"
+        },
+        "stripStyles": {
+          "description": "Remove <style>, stylesheet links and style attributes (default: false)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_get_html"
+  },
+  {
+    "command": "extract_content",
+    "description": "Extract the page's main content as clean Markdown (title, byline, body, links), leaving out navigation, ads and other boilerplate. Far smaller than the HTML or a screenshot; use it to read articles, docs and search results",
+    "inputSchema": {
+      "properties": {
+        "includeImages": {
+          "description": "Render images as ![alt](src) (default: false)",
+          "type": "boolean"
+        },
+        "includeLinks": {
+          "description": "Render links inline as [text](url); they are listed in links either way (default: true)",
+          "type": "boolean"
+        },
+        "maxLength": {
+          "description": "Characters of Markdown returned before truncating at a paragraph break (default: 20000, max: 1000000)",
+          "type": "number"
+        },
+        "selector": {
+          "description": "CSS selector of the element to convert, skipping main-content detection",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_extract_content"
+  },
+  {
+    "command": "find_text",
+    "description": "Find a string or regular expression in the page's visible text, like Ctrl+F: returns the match count and each match with surrounding context and its element, and can scroll to and highlight one match. Use it to locate a phrase instead of reading the whole page",
+    "inputSchema": {
+      "properties": {
+        "caseSensitive": {
+          "description": "Match case exactly (default: false)",
+          "type": "boolean"
+        },
+        "clear": {
+          "description": "Only remove the highlights of the previous search",
+          "type": "boolean"
+        },
+        "contextChars": {
+          "description": "Characters of context returned on each side of a match (default: 40, max: 500)",
+          "type": "number"
+        },
+        "highlight": {
+          "description": "Highlight the matches on the page until the next search (default: true)",
+          "type": "boolean"
+        },
+        "maxResults": {
+          "description": "Maximum matches returned; the count covers all of them (default: 20, max: 500)",
+          "type": "number"
+        },
+        "query": {
+          "description": "Text to find, or a JavaScript regular expression with regex",
+          "type": "string"
+        },
+        "regex": {
+          "description": "Treat query as a regular expression (default: false)",
+          "type": "boolean"
+        },
+        "scrollTo": {
+          "description": "Zero-based index of the match to scroll into view and mark as current",
+          "minimum": 0,
+          "type": "integer"
+        },
+        "selector": {
+          "description": "Only search inside this CSS selector (default: the whole page)",
+          "type": "string"
+        },
+        "wholeWord": {
+          "description": "Only match at word boundaries (default: false)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_find_text"
+  },
+  {
+    "command": "collect_items",
+    "description": "Harvest an infinite-scroll feed in one call: scrolls repeatedly, waits for new items to load, and returns every distinct item matching itemSelector until maxItems, maxScrolls or timeoutMs is reached or the feed stops growing. Each item has its text and first link, or the named fields. The page is left scrolled where collection stopped",
+    "inputSchema": {
+      "properties": {
+        "container": {
+          "description": "CSS selector of the element that scrolls (default: the items' scrolling ancestor, or the page)",
+          "type": "string"
+        },
+        "fields": {
+          "additionalProperties": {
+            "type": "string"
+          },
+          "description": "Values to read from each item: name -> \"selector\" (text), \"selector@attribute\" or \"@attribute\" (of the item itself), e.g. {\"title\": \"h2\", \"url\": \"a@href\"}. Default: text and link",
+          "type": "object"
+        },
+        "idleScrolls": {
+          "description": "Scrolls at the bottom without new items before the feed counts as ended (default: 3)",
+          "maximum": 10,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "itemSelector": {
+          "description": "CSS selector matching each item of the feed, e.g. \"article.post\"",
+          "type": "string"
+        },
+        "loadMoreSelector": {
+          "description": "Button to click when the bottom is reached, for feeds with a \"Load more\" button",
+          "type": "string"
+        },
+        "maxItems": {
+          "description": "Stop after this many items (default: 100)",
+          "maximum": 2000,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "maxScrolls": {
+          "description": "Stop after this many scrolls (default: 50)",
+          "maximum": 500,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "maxTextLength": {
+          "description": "Longest text returned per value (default: 300)",
+          "maximum": 5000,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "timeoutMs": {
+          "description": "Stop after this long (default: 30000, at most the command timeout less 2 s)",
+          "minimum": 1,
+          "type": "integer"
+        },
+        "waitMs": {
+          "description": "How long to wait for new items after each scroll (default: 1500)",
+          "maximum": 10000,
+          "minimum": 100,
+          "type": "integer"
+        }
+      },
+      "required": [
+        "itemSelector"
+      ],
+      "type": "object"
+    },
+    "name": "browser_collect_items"
+  },
+  {
+    "command": "assert_page",
+    "description": "Check a list of assertions against the current page in one call and return a pass/fail report. Types: exists/visible (selector), text (selector, default the whole page), url, and cookie (name; present, or with a matcher on its value). text and url need one of equals, contains or matches (a JavaScript regular expression); not inverts an assertion",
+    "inputSchema": {
+      "properties": {
+        "assertions": {
+          "items": {
+            "properties": {
+              "contains": {
+                "description": "Expected substring",
+                "type": "string"
+              },
+              "equals": {
+                "description": "Expected exact value (text is trimmed)",
+                "type": "string"
+              },
+              "ignoreCase": {
+                "description": "Compare case-insensitively (default: false)",
+                "type": "boolean"
+              },
+              "label": {
+                "description": "Name for this assertion in the report",
+                "type": "string"
+              },
+              "matches": {
+                "description": "Regular expression the value must match",
+                "type": "string"
+              },
+              "name": {
+                "description": "Cookie name, for cookie",
+                "type": "string"
+              },
+              "not": {
+                "description": "Pass when the assertion does not hold (default: false)",
+                "type": "boolean"
+              },
+              "selector": {
+                "description": "CSS selector, for exists, visible and text",
+                "type": "string"
+              },
+              "type": {
+                "enum": [
+                  "exists",
+                  "visible",
+                  "text",
+                  "url",
+                  "cookie"
+                ],
+                "type": "string"
+              }
+            },
+            "required": [
+              "type"
+            ],
+            "type": "object"
+          },
+          "maxItems": 50,
+          "minItems": 1,
+          "type": "array"
+        }
+      },
+      "required": [
+        "assertions"
+      ],
+      "type": "object"
+    },
+    "name": "assert_page"
+  },
+  {
+    "command": "accessibility_snapshot",
+    "description": "Return the page's accessibility tree as JSON: each node's role, accessible name, value, heading level and states (checked, expanded, disabled, focused, ...) plus a CSS selector to act on it. A cheaper, more reliable page representation than screenshots for text-only models",
+    "inputSchema": {
+      "properties": {
+        "interestingOnly": {
+          "description": "Only landmarks, headings, widgets, lists, tables and images; generic containers and plain text are dropped and their children hoisted (default: true)",
+          "type": "boolean"
+        },
+        "maxDepth": {
+          "description": "Deepest level of nodes returned (default: 100)",
+          "maximum": 100,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "maxNodes": {
+          "description": "Nodes returned before the tree is truncated (default: 2000)",
+          "maximum": 10000,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "selector": {
+          "description": "CSS selector of the subtree to return (default: the whole page)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_accessibility_tree"
+  },
+  {
+    "command": "focus",
+    "description": "Focus an element, firing focus/focusin events so focus-triggered validation and widgets run",
+    "inputSchema": {
+      "properties": {
+        "selector": {
+          "description": "CSS selector for the element to focus",
+          "type": "string"
+        }
+      },
+      "required": [
+        "selector"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_focus"
+  },
+  {
+    "command": "blur",
+    "description": "Remove focus from an element (default: the focused element), firing blur/focusout so on-blur validation runs",
+    "inputSchema": {
+      "properties": {
+        "selector": {
+          "description": "CSS selector for the element to blur (default: document.activeElement)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_blur"
+  },
+  {
+    "command": "get_focused_element",
+    "description": "Describe the currently focused element (tag, id, name, type, value, unique selector)",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "playwright_get_focused_element"
+  },
+  {
+    "command": "get_element",
+    "description": "Describe an element's geometry and state: bounding box (viewport and page coordinates), whether it is visible (and what hides it), enabled, how much of it is in the viewport, and whether a click at its center would reach it or what covers it. Use it to decide whether to scroll first, whether a click can work, or where to crop a screenshot",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "locator": {
+          "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector",
+          "properties": {
+            "exact": {
+              "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)",
+              "type": "boolean"
+            },
+            "label": {
+              "description": "Text of the form control's <label>, aria-label or aria-labelledby",
+              "type": "string"
+            },
+            "name": {
+              "description": "Accessible name the role match must have (with role only)",
+              "type": "string"
+            },
+            "nth": {
+              "description": "Which match to use when several match (default: 0, the first in document order)",
+              "minimum": 0,
+              "type": "integer"
+            },
+            "placeholder": {
+              "description": "Placeholder of an input or textarea",
+              "type": "string"
+            },
+            "role": {
+              "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading",
+              "type": "string"
+            },
+            "text": {
+              "description": "Visible text; matches the innermost element containing it",
+              "type": "string"
+            }
+          },
+          "type": "object"
+        },
+        "selector": {
+          "description": "CSS selector; the first match is described and count reports how many matched",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_get_element"
+  },
+  {
+    "command": "evaluate",
+    "description": "Run a JavaScript expression or function in the page and return its JSON-serializable result. Disabled unless the server runs with --allow-evaluate or the session is inside a passkey_authorize window",
+    "inputSchema": {
+      "properties": {
+        "args": {
+          "description": "JSON arguments passed to the function (ignored for plain expressions)",
+          "type": "array"
+        },
+        "awaitPromise": {
+          "description": "Wait for a returned promise and return its value (default: true)",
+          "type": "boolean"
+        },
+        "expression": {
+          "description": "Expression to evaluate, e.g. document.title, or a function such as (sel) => document.querySelectorAll(sel).length, which is called with args",
+          "type": "string"
+        },
+        "frameId": {
+          "description": "Frame to run in, from browser_list_frames (default: the top frame)",
+          "type": "number"
+        }
+      },
+      "required": [
+        "expression"
+      ],
+      "type": "object"
+    },
+    "name": "playwright_evaluate"
+  },
+  {
+    "command": "type_text",
+    "description": "Type into one or more fields with realistic keyboard events (keydown/keypress/input/keyup per character, IME composition events for CJK) instead of setting values directly, for frameworks that ignore programmatic value changes",
+    "inputSchema": {
+      "properties": {
+        "clear": {
+          "description": "Clear existing field contents before typing (default: false)",
+          "type": "boolean"
+        },
+        "composition": {
+          "description": "Emit compositionstart/update/end events: auto enables them for CJK text (default: auto)",
+          "enum": [
+            "auto",
+            "always",
+            "never"
+          ],
+          "type": "string"
+        },
+        "delayMs": {
+          "description": "Delay between keystrokes in milliseconds (default: 50)",
+          "type": "number"
+        },
+        "fields": {
+          "description": "Fields typed in order within one command",
+          "items": {
+            "properties": {
+              "clear": {
+                "type": "boolean"
+              },
+              "selector": {
+                "type": "string"
+              },
+              "text": {
+                "type": "string"
+              }
+            },
+            "required": [
+              "selector",
+              "text"
+            ],
+            "type": "object"
+          },
+          "type": "array"
+        },
+        "jitterMs": {
+          "description": "Random extra delay added to each keystroke, 0..jitterMs (default: 0)",
+          "type": "number"
+        },
+        "selector": {
+          "description": "CSS selector of the field to type into (single-field form)",
+          "type": "string"
+        },
+        "text": {
+          "description": "Text to type (single-field form)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "type_text"
+  },
+  {
+    "command": "set_date",
+    "description": "Set a date on a native date input or JS date-picker widget, injecting the value first and falling back to navigating the calendar",
+    "inputSchema": {
+      "properties": {
+        "date": {
+          "description": "Date as YYYY-MM-DD, or YYYY-MM-DDTHH:MM for datetime inputs",
+          "type": "string"
+        },
+        "selector": {
+          "description": "CSS selector for the date input or the element that opens the picker",
+          "type": "string"
+        },
+        "strategy": {
+          "description": "inject sets the value and fires input/change events, widget clicks through the calendar, auto tries inject then widget (default: auto)",
+          "enum": [
+            "auto",
+            "inject",
+            "widget"
+          ],
+          "type": "string"
+        }
+      },
+      "required": [
+        "selector",
+        "date"
+      ],
+      "type": "object"
+    },
+    "name": "set_date"
+  },
+  {
+    "command": "emulate_locale",
+    "description": "Override Accept-Language, the JavaScript locale (navigator.language, Intl) and the timezone for this session's tab, to reproduce region-specific prices and formats",
+    "inputSchema": {
+      "properties": {
+        "acceptLanguage": {
+          "description": "Accept-Language header value (default: derived from locale, e.g. de-DE,de;q=0.9)",
+          "type": "string"
+        },
+        "locale": {
+          "description": "BCP 47 locale, e.g. de-DE or ja-JP",
+          "type": "string"
+        },
+        "reset": {
+          "description": "Remove all overrides and return to the browser's settings",
+          "type": "boolean"
+        },
+        "timezone": {
+          "description": "IANA timezone, e.g. Europe/Berlin or America/New_York",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_emulate_locale"
+  },
+  {
+    "command": "set_geolocation",
+    "description": "Spoof the position navigator.geolocation reports for this session's tab and allow the location permission for the site, to exercise store locators and delivery flows. The override lasts until reset or the tab closes",
+    "inputSchema": {
+      "properties": {
+        "accuracy": {
+          "description": "Accuracy radius in meters (default: 100)",
+          "type": "number"
+        },
+        "grantPermission": {
+          "description": "Allow the location permission for the origin so no prompt appears (default: true)",
+          "type": "boolean"
+        },
+        "latitude": {
+          "description": "Latitude in degrees",
+          "maximum": 90,
+          "minimum": -90,
+          "type": "number"
+        },
+        "longitude": {
+          "description": "Longitude in degrees",
+          "maximum": 180,
+          "minimum": -180,
+          "type": "number"
+        },
+        "origin": {
+          "description": "Origin to allow location access for, e.g. https://shop.example (default: the current page's)",
+          "type": "string"
+        },
+        "reset": {
+          "description": "Remove the override and the permissions this tool granted",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_set_geolocation"
+  },
+  {
+    "command": "throttle",
+    "description": "Emulate a slow network (3G, 4G, offline) and/or CPU slowdown for this session's tab, to measure pages under realistic constraints",
+    "inputSchema": {
+      "properties": {
+        "cpuRate": {
+          "description": "CPU slowdown factor, 1 (none) to 20; 4 approximates a mid-range phone",
+          "type": "number"
+        },
+        "downloadKbps": {
+          "description": "Download bandwidth in kilobits per second (overrides the profile's)",
+          "type": "number"
+        },
+        "latencyMs": {
+          "description": "Added round-trip latency in milliseconds (overrides the profile's)",
+          "type": "number"
+        },
+        "network": {
+          "description": "Network profile; none removes network throttling",
+          "enum": [
+            "slow-3g",
+            "3g",
+            "4g",
+            "offline",
+            "none"
+          ],
+          "type": "string"
+        },
+        "reset": {
+          "description": "Remove all network and CPU throttling",
+          "type": "boolean"
+        },
+        "uploadKbps": {
+          "description": "Upload bandwidth in kilobits per second (overrides the profile's)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_throttle"
+  },
+  {
+    "command": "screenshot",
+    "description": "Take a screenshot of the current page or a specific element. With highlightSelector, the matched elements are outlined and labeled in the image, to check what an action targeted",
+    "inputSchema": {
+      "properties": {
+        "frame": {
+          "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames",
+          "oneOf": [
+            {
+              "minimum": 0,
+              "type": "integer"
+            },
+            {
+              "type": "string"
+            },
+            {
+              "properties": {
+                "id": {
+                  "minimum": 1,
+                  "type": "integer"
+                },
+                "index": {
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "name": {
+                  "type": "string"
+                },
+                "url": {
+                  "type": "string"
+                }
+              },
+              "type": "object"
+            }
+          ]
+        },
+        "fullPage": {
+          "description": "Whether to take a full page screenshot",
+          "type": "boolean"
+        },
+        "highlightLabel": {
+          "description": "Label drawn on the highlighted elements, numbered when several match (default: each element's tag, id and classes)",
+          "maxLength": 80,
+          "type": "string"
+        },
+        "highlightSelector": {
+          "description": "CSS selector of elements to outline and label in the screenshot; the overlay is removed after capture",
+          "type": "string"
+        },
+        "savePath": {
+          "description": "Write the PNG to this file instead of returning it; must be inside the client's declared roots (relative paths use the first root)",
+          "type": "string"
+        },
+        "selector": {
+          "description": "Optional CSS selector to screenshot a specific element",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_screenshot"
+  },
+  {
+    "command": "print_to_pdf",
+    "description": "Export the current page as a PDF, returned as base64 or written to a file inside the client's declared roots",
+    "inputSchema": {
+      "properties": {
+        "landscape": {
+          "description": "Landscape orientation (default: false)",
+          "type": "boolean"
+        },
+        "printBackground": {
+          "description": "Include background graphics (default: true)",
+          "type": "boolean"
+        },
+        "savePath": {
+          "description": "Write the PDF to this file; must be inside the client's declared roots (relative paths use the first root)",
+          "type": "string"
+        },
+        "scale": {
+          "description": "Rendering scale between 0.1 and 2 (default: 1)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_export_pdf"
+  },
+  {
+    "command": "canvas_read_pixels",
+    "description": "Read a region of a canvas (2D or WebGL) as a PNG image or as average colors over a grid, to verify chart or canvas rendering",
+    "inputSchema": {
+      "properties": {
+        "grid": {
+          "description": "Cells per side for average mode, 1-16 (default: 1)",
+          "type": "number"
+        },
+        "height": {
+          "description": "Region height (default: rest of the canvas)",
+          "type": "number"
+        },
+        "mode": {
+          "description": "image returns a base64 PNG, average returns mean RGBA per grid cell (default: image)",
+          "enum": [
+            "image",
+            "average"
+          ],
+          "type": "string"
+        },
+        "selector": {
+          "description": "CSS selector for the canvas element",
+          "type": "string"
+        },
+        "width": {
+          "description": "Region width (default: rest of the canvas)",
+          "type": "number"
+        },
+        "x": {
+          "description": "Left edge of the region in canvas pixels (default: 0)",
+          "type": "number"
+        },
+        "y": {
+          "description": "Top edge of the region in canvas pixels (default: 0)",
+          "type": "number"
+        }
+      },
+      "required": [
+        "selector"
+      ],
+      "type": "object"
+    },
+    "name": "canvas_read_pixels"
+  },
+  {
+    "command": "media_control",
+    "description": "Play, pause, seek, mute, or unmute an audio/video element",
+    "inputSchema": {
+      "properties": {
+        "action": {
+          "description": "Operation to perform",
+          "enum": [
+            "play",
+            "pause",
+            "seek",
+            "mute",
+            "unmute"
+          ],
+          "type": "string"
+        },
+        "selector": {
+          "description": "CSS selector for the media element (default: first audio/video on the page)",
+          "type": "string"
+        },
+        "time": {
+          "description": "Target position in seconds (required for seek)",
+          "type": "number"
+        }
+      },
+      "required": [
+        "action"
+      ],
+      "type": "object"
+    },
+    "name": "media_control"
+  },
+  {
+    "command": "media_get_state",
+    "description": "Read playback state of audio/video elements: paused, currentTime, duration, muted, volume, readyState, and errors",
+    "inputSchema": {
+      "properties": {
+        "selector": {
+          "description": "CSS selector for the media element (default: every audio/video on the page)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "media_get_state"
+  },
+  {
+    "command": "media_capture_frame",
+    "description": "Capture the current frame of a video (or its poster image before playback) as a PNG",
+    "inputSchema": {
+      "properties": {
+        "selector": {
+          "description": "CSS selector for the video element (default: first video on the page)",
+          "type": "string"
+        },
+        "time": {
+          "description": "Seek to this position in seconds before capturing",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "media_capture_frame"
+  },
+  {
+    "command": "list_frames",
+    "description": "List the frame hierarchy of the current tab (frame id, URL, name, parent frame id) to find which frame contains a widget",
+    "inputSchema": {
+      "properties": {
+        "includeAboutBlank": {
+          "description": "Include about:blank and srcdoc frames (default: false)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_list_frames"
+  },
+  {
+    "command": "list_service_workers",
+    "description": "List service worker registrations for the current origin (scope, script URL, state)",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "browser_list_service_workers"
+  },
+  {
+    "command": "unregister_service_workers",
+    "description": "Unregister service workers for the current origin so the next load fetches a fresh app shell",
+    "inputSchema": {
+      "properties": {
+        "scope": {
+          "description": "Only unregister the registration with this scope URL (default: all for the origin)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_unregister_service_workers"
+  },
+  {
+    "command": "clear_site_data",
+    "description": "Clear cache, cookies, and storage for an origin (default: current tab's origin) to reproduce a first visit or recover from corrupted client state",
+    "inputSchema": {
+      "properties": {
+        "dataTypes": {
+          "description": "Data types to clear (default: all)",
+          "items": {
+            "enum": [
+              "cache",
+              "cookies",
+              "localStorage",
+              "indexedDB",
+              "cacheStorage",
+              "serviceWorkers",
+              "fileSystems"
+            ],
+            "type": "string"
+          },
+          "type": "array"
+        },
+        "origin": {
+          "description": "Origin to clear, e.g. https://example.com (default: current tab's origin)",
+          "type": "string"
+        },
+        "reload": {
+          "description": "Reload the tab after clearing (default: false)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_clear_site_data"
+  },
+  {
+    "command": "cookies_get",
+    "description": "List cookies for a domain or URL (default: the current tab's URL) to check login state or carry a session into another flow. Values are omitted unless includeValues is true",
+    "inputSchema": {
+      "properties": {
+        "domain": {
+          "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)",
+          "type": "string"
+        },
+        "includeValues": {
+          "description": "Include cookie values (default: false)",
+          "type": "boolean"
+        },
+        "name": {
+          "description": "Only cookies with this name",
+          "type": "string"
+        },
+        "url": {
+          "description": "Only cookies the browser would send to this URL (instead of domain)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_get_cookies"
+  },
+  {
+    "command": "cookies_set",
+    "description": "Set a cookie for a domain or URL (default: the current tab's URL)",
+    "inputSchema": {
+      "properties": {
+        "domain": {
+          "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)",
+          "type": "string"
+        },
+        "expires": {
+          "description": "Expiry as a Unix timestamp in seconds (default: session cookie)",
+          "type": "number"
+        },
+        "httpOnly": {
+          "description": "Hide the cookie from page scripts",
+          "type": "boolean"
+        },
+        "maxAgeSeconds": {
+          "description": "Expiry in seconds from now (instead of expires)",
+          "minimum": 0,
+          "type": "integer"
+        },
+        "name": {
+          "description": "Cookie name",
+          "type": "string"
+        },
+        "path": {
+          "description": "Cookie path (default: /)",
+          "type": "string"
+        },
+        "sameSite": {
+          "description": "SameSite policy; none implies secure",
+          "enum": [
+            "strict",
+            "lax",
+            "none"
+          ],
+          "type": "string"
+        },
+        "secure": {
+          "description": "Only send over HTTPS",
+          "type": "boolean"
+        },
+        "url": {
+          "description": "Only cookies the browser would send to this URL (instead of domain)",
+          "type": "string"
+        },
+        "value": {
+          "description": "Cookie value",
+          "type": "string"
+        }
+      },
+      "required": [
+        "name",
+        "value"
+      ],
+      "type": "object"
+    },
+    "name": "browser_set_cookie"
+  },
+  {
+    "command": "cookies_clear",
+    "description": "Delete cookies for a domain or URL (default: the current tab's URL), optionally only those with a given name; leaves other site data alone",
+    "inputSchema": {
+      "properties": {
+        "domain": {
+          "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)",
+          "type": "string"
+        },
+        "name": {
+          "description": "Only delete cookies with this name",
+          "type": "string"
+        },
+        "url": {
+          "description": "Only cookies the browser would send to this URL (instead of domain)",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_clear_cookies"
+  },
+  {
+    "command": "downloads_list",
+    "description": "List the browser's downloads, newest first, with their state (in_progress, complete, interrupted), progress and file path on disk",
+    "inputSchema": {
+      "properties": {
+        "filenameContains": {
+          "description": "Only downloads whose file path contains this text, e.g. .csv",
+          "type": "string"
+        },
+        "limit": {
+          "description": "Maximum downloads to return (default: 20)",
+          "maximum": 100,
+          "minimum": 1,
+          "type": "integer"
+        },
+        "state": {
+          "description": "Only downloads in this state",
+          "enum": [
+            "in_progress",
+            "complete",
+            "interrupted"
+          ],
+          "type": "string"
+        },
+        "urlContains": {
+          "description": "Only downloads whose URL contains this text",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_list_downloads"
+  },
+  {
+    "command": "profiling_start",
+    "description": "Start recording JS/CSS code coverage and a performance trace in the current tab over the DevTools Protocol (Chrome shows a debugging banner while recording). Interact with the page, then call browser_profiling_stop",
+    "inputSchema": {
+      "properties": {
+        "categories": {
+          "description": "Trace categories (default: those the DevTools Performance panel records)",
+          "items": {
+            "type": "string"
+          },
+          "type": "array"
+        },
+        "coverage": {
+          "description": "Coverage to record (default: both; [] for none)",
+          "items": {
+            "enum": [
+              "js",
+              "css"
+            ],
+            "type": "string"
+          },
+          "type": "array"
+        },
+        "trace": {
+          "description": "Record a performance trace (default: true)",
+          "type": "boolean"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_profiling_start"
+  },
+  {
+    "command": "passkey_enable",
+    "description": "Enable or disable passkey automation for WebAuthn flows",
+    "inputSchema": {
+      "properties": {
+        "enabled": {
+          "description": "Whether to enable passkey automation",
+          "type": "boolean"
+        }
+      },
+      "required": [
+        "enabled"
+      ],
+      "type": "object"
+    },
+    "name": "passkey_enable"
+  },
+  {
+    "command": "passkey_status",
+    "description": "Get the current status of passkey automation",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "passkey_status"
+  },
+  {
+    "command": "passkey_list",
+    "description": "List all stored passkey credentials",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "passkey_list"
+  },
+  {
+    "command": "passkey_clear",
+    "description": "Clear all stored passkey credentials",
+    "inputSchema": {
+      "properties": {},
+      "type": "object"
+    },
+    "name": "passkey_clear"
+  },
+  {
+    "command": "detect_modal",
+    "description": "Detect if a modal, popup, or overlay is present on the page",
+    "inputSchema": {
+      "properties": {
+        "includeHidden": {
+          "description": "Include hidden modals (default: false)",
+          "type": "boolean"
+        },
+        "maxResults": {
+          "description": "Maximum number of modals to detect (default: 1)",
+          "type": "number"
+        },
+        "minZIndex": {
+          "description": "Minimum z-index to consider (default: 100)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_detect_modal"
+  },
+  {
+    "command": "dismiss_modal",
+    "description": "Attempt to dismiss any detected modals on the page",
+    "inputSchema": {
+      "properties": {
+        "strategy": {
+          "description": "Dismissal strategy: auto tries all methods, button clicks dismiss button, escape presses ESC, backdrop clicks overlay, remove forcibly removes from DOM (default: auto)",
+          "enum": [
+            "auto",
+            "button",
+            "escape",
+            "backdrop",
+            "remove"
+          ],
+          "type": "string"
+        },
+        "timeout": {
+          "description": "Timeout in milliseconds (default: 5000)",
+          "type": "number"
+        },
+        "waitAfter": {
+          "description": "Wait time after dismissal to verify (default: 500)",
+          "type": "number"
+        }
+      },
+      "type": "object"
+    },
+    "name": "playwright_dismiss_modal"
+  },
+  {
+    "command": "dialog_policy",
+    "description": "Choose how this session's tab answers JavaScript dialogs (alert, confirm, prompt, beforeunload). accept and dismiss answer them automatically; manual leaves them open and fails other commands on the tab with a 'JavaScript dialog open' error until browser_respond_dialog answers. Without a policy an unexpected confirm() blocks the page until the command times out",
+    "inputSchema": {
+      "properties": {
+        "mode": {
+          "description": "accept clicks OK (and leaves the page on beforeunload), dismiss clicks Cancel, manual waits for browser_respond_dialog, off stops watching",
+          "enum": [
+            "accept",
+            "dismiss",
+            "manual",
+            "off"
+          ],
+          "type": "string"
+        },
+        "promptText": {
+          "description": "Text accept enters into prompt() dialogs (default: the prompt's default value)",
+          "type": "string"
+        }
+      },
+      "required": [
+        "mode"
+      ],
+      "type": "object"
+    },
+    "name": "browser_handle_dialogs"
+  },
+  {
+    "command": "dialog_respond",
+    "description": "Answer the JavaScript dialog open on this session's tab, e.g. after a 'JavaScript dialog open' error under the manual policy of browser_handle_dialogs",
+    "inputSchema": {
+      "properties": {
+        "accept": {
+          "description": "Click OK rather than Cancel (default: true)",
+          "type": "boolean"
+        },
+        "promptText": {
+          "description": "Text to enter into a prompt() dialog before accepting",
+          "type": "string"
+        }
+      },
+      "type": "object"
+    },
+    "name": "browser_respond_dialog"
+  }
+];
@@ -1,8 +1,10 @@
 /**
  * LLM Query Service
  *
- * Provides LLM query capabilities to content scripts and Rust server
- * for agent assistance when automation tasks fail or agents get stuck.
+ * Provides LLM query capabilities to content scripts for agent assistance
+ * when automation tasks fail or agents get stuck. The background script
+ * forwards each query to the server as an `llm_query` event, and the server
+ * asks the MCP client's model through sampling.
  */
 
 // ============================================================================
@@ -71,19 +73,20 @@ export class LlmQueryManager {
 
       this.activeQueries.set(queryId, query);
 
-      // Send request to background script
+      // The background script answers once the MCP client's model has replied
       chrome.runtime.sendMessage({
         type: 'llm_query_request',
         id: queryId,
         prompt,
       }).then(response => {
-        if (!response.success) {
-          this.activeQueries.delete(queryId);
-          reject(new Error(response.error || 'LLM query failed'));
+        if (response?.success) {
+          this.handleChunk(queryId, response.text ?? '');
+          this.handleComplete(queryId);
+        } else {
+          this.handleError(queryId, response?.error || 'LLM query failed');
         }
       }).catch(error => {
-        this.activeQueries.delete(queryId);
-        reject(error);
+        this.handleError(queryId, error instanceof Error ? error.message : String(error));
       });
     });
   }
@@ -21,11 +21,34 @@ export interface WebAuthnProxyStatus {
   error?: string;
 }
 
+export interface CredentialUseDetails {
+  credentialId: string;
+  rpId: string;
+  operation: 'create' | 'get';
+  origin?: string;
+}
+
+export interface CredentialUseRequest {
+  requestId: number;
+  rpId: string;
+  operation: 'create' | 'get';
+  origin?: string;
+}
+
 export class WebAuthnProxy {
   private isAttached = false;
   private storedCredentials = new Map<string, StoredCredential>();
   private automationMode = true; // Default to enabled
 
+  /** Called whenever a stored credential is created or used for an assertion */
+  onCredentialUsed: ((details: CredentialUseDetails) => void) | null = null;
+
+  /**
+   * Asked before a credential is created or used; resolves to an error
+   * message when the use is refused. Without it every use is refused.
+   */
+  approveUse: ((details: CredentialUseRequest) => Promise<string | null>) | null = null;
+
   async initialize(): Promise<void> {
     try {
       // @ts-ignore - webAuthenticationProxy is a Chrome extension API
@@ -123,6 +146,16 @@ export class WebAuthnProxy {
       }
 
       if (this.automationMode) {
+        const refused = await this.checkApproval({ requestId, rpId: request.rp.id, operation: 'create', origin: request.origin });
+        if (refused) {
+          // @ts-ignore
+          chrome.webAuthenticationProxy.completeCreateRequest({
+            requestId,
+            error: { name: "NotAllowedError", message: refused }
+          });
+          return;
+        }
+
         // Simulate credential creation
         const credential = await this.simulateCredentialCreation(request);
 
@@ -163,6 +196,13 @@ export class WebAuthnProxy {
           responseJson: JSON.stringify(response)
         });
 
+        this.onCredentialUsed?.({
+          credentialId: response.id,
+          rpId: request.rp.id,
+          operation: 'create',
+          origin: request.origin,
+        });
+
         return;
       }
 
@@ -207,6 +247,16 @@ export class WebAuthnProxy {
         console.log('[WebAuthnProxy] Found credential:', credential ? 'yes' : 'no');
 
         if (credential) {
+          const refused = await this.checkApproval({ requestId, rpId: credential.rpId, operation: 'get', origin: request.origin });
+          if (refused) {
+            // @ts-ignore
+            chrome.webAuthenticationProxy.completeGetRequest({
+              requestId,
+              error: { name: "NotAllowedError", message: refused }
+            });
+            return;
+          }
+
           // Simulate authentication
           const authResponse = await this.simulateAuthentication(credential, request);
 
@@ -231,6 +281,13 @@ export class WebAuthnProxy {
             responseJson: JSON.stringify(response)
           });
 
+          this.onCredentialUsed?.({
+            credentialId: response.id,
+            rpId: credential.rpId,
+            operation: 'get',
+            origin: request.origin,
+          });
+
           return;
         }
       }
@@ -252,6 +309,18 @@ export class WebAuthnProxy {
     }
   }
 
+  /** The reason the use is refused, or null when the server approved it */
+  private async checkApproval(details: CredentialUseRequest): Promise<string | null> {
+    if (!this.approveUse) {
+      return 'Passkey use needs the agent-browser server';
+    }
+    const refused = await this.approveUse(details);
+    if (refused) {
+      console.log('[WebAuthnProxy] Use refused:', refused);
+    }
+    return refused;
+  }
+
   private findMatchingCredential(request: any): StoredCredential | null {
     console.log('[WebAuthnProxy] Finding credential for rpId:', request.rpId);
     console.log('[WebAuthnProxy] allowCredentials:', request.allowCredentials);
@@ -0,0 +1,36 @@
+/**
+ * Tests for canvas_read_pixels averaging
+ */
+
+import { test, expect, describe } from 'bun:test';
+import { averageCells } from '../lib/automation/canvas';
+
+/** A width x height RGBA buffer with the left half red and the right half opaque blue */
+function halves(width: number, height: number): Uint8ClampedArray {
+  const data = new Uint8ClampedArray(width * height * 4);
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      data.set(x < width / 2 ? [255, 0, 0, 255] : [0, 0, 255, 255], (y * width + x) * 4);
+    }
+  }
+  return data;
+}
+
+describe('Canvas Read Pixels', () => {
+  test('averages the whole region with one cell', () => {
+    const [cell] = averageCells(halves(4, 2), 4, 2, 1);
+    expect(cell.rgba).toEqual([128, 0, 128, 255]);
+    expect(cell).toMatchObject({ row: 0, col: 0, x: 0, y: 0, width: 4, height: 2, hex: '#800080' });
+  });
+
+  test('splits the region into grid cells', () => {
+    const cells = averageCells(halves(4, 4), 4, 4, 2);
+    expect(cells.map((cell) => cell.hex)).toEqual(['#ff0000', '#0000ff', '#ff0000', '#0000ff']);
+    expect(cells[3]).toMatchObject({ row: 1, col: 1, x: 2, y: 2, width: 2, height: 2 });
+  });
+
+  test('covers every pixel when the size does not divide evenly', () => {
+    const cells = averageCells(halves(5, 3), 5, 3, 2);
+    expect(cells.reduce((sum, cell) => sum + cell.width * cell.height, 0)).toBe(15);
+  });
+});
@@ -0,0 +1,32 @@
+/**
+ * Tests for set_date value formatting
+ */
+
+import { test, expect, describe } from 'bun:test';
+import { formatForTextField, isoWeek } from '../lib/automation/date';
+
+const march4 = { date: '2024-03-04', year: 2024, month: 3, day: 4 };
+
+describe('Set Date', () => {
+  test('computes ISO weeks across year boundaries', () => {
+    expect(isoWeek(2024, 3, 4)).toBe('2024-W10');
+    // Thursday decides the week-numbering year
+    expect(isoWeek(2021, 1, 1)).toBe('2020-W53');
+    expect(isoWeek(2024, 12, 30)).toBe('2025-W01');
+  });
+
+  test('follows the placeholder pattern', () => {
+    expect(formatForTextField(march4, 'MM/DD/YYYY')).toBe('03/04/2024');
+    expect(formatForTextField(march4, 'dd.mm.yyyy')).toBe('04.03.2024');
+    expect(formatForTextField(march4, 'YYYY-MM-DD')).toBe('2024-03-04');
+  });
+
+  test('infers day-first order from an existing value', () => {
+    expect(formatForTextField(march4, '31/12/2023')).toBe('04/03/2024');
+    expect(formatForTextField(march4, '12/31/2023')).toBe('03/04/2024');
+  });
+
+  test('falls back to ISO without a hint', () => {
+    expect(formatForTextField(march4, 'Pick a date')).toBe('2024-03-04');
+  });
+});
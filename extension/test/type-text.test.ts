@@ -0,0 +1,42 @@
+/**
+ * Tests for type_text keystroke planning
+ */
+
+import { test, expect, describe } from 'bun:test';
+import { isComposable, keyFor, keystrokeDelay, planKeystrokes } from '../lib/automation/type-text';
+
+describe('Type Text', () => {
+  test('groups CJK runs into one composition', () => {
+    expect(planKeystrokes('ab日本語c', true)).toEqual([
+      { text: 'a', composed: false },
+      { text: 'b', composed: false },
+      { text: '日本語', composed: true },
+      { text: 'c', composed: false },
+    ]);
+    expect(planKeystrokes('日本', false)).toEqual([
+      { text: '日', composed: false },
+      { text: '本', composed: false },
+    ]);
+  });
+
+  test('keeps astral characters whole', () => {
+    expect(planKeystrokes('a😀', true).map((stroke) => stroke.text)).toEqual(['a', '😀']);
+    expect(isComposable('한')).toBe(true);
+    expect(isComposable('é')).toBe(false);
+  });
+
+  test('reports US layout keys with shift state', () => {
+    expect(keyFor('a')).toEqual({ key: 'a', code: 'KeyA', keyCode: 65, shiftKey: false });
+    expect(keyFor('A')).toEqual({ key: 'A', code: 'KeyA', keyCode: 65, shiftKey: true });
+    expect(keyFor('!')).toEqual({ key: '!', code: 'Digit1', keyCode: 49, shiftKey: true });
+    expect(keyFor('?')).toEqual({ key: '?', code: 'Slash', keyCode: 191, shiftKey: true });
+    expect(keyFor('\n').key).toBe('Enter');
+    expect(keyFor('é').code).toBe('');
+  });
+
+  test('adds jitter within bounds', () => {
+    expect(keystrokeDelay(50, 0, () => 0.99)).toBe(50);
+    expect(keystrokeDelay(50, 20, () => 0)).toBe(50);
+    expect(keystrokeDelay(50, 20, () => 0.999)).toBe(70);
+  });
+});
@@ -157,5 +157,6 @@ echo "  - Show ✓ badge when connected"
 echo
 echo "To test MCP:"
 echo "  nc localhost 8084"
+echo '  {"jsonrpc":"2.0","id":0,"method":"authenticate","params":{"token":"<contents of ~/.agent-browser/token>"}}'
 echo '  {"jsonrpc":"2.0","id":1,"method":"navigate","params":{"url":"https://example.com"}}'
 echo
@@ -208,6 +208,7 @@ echo "  ⚠ (red)     = Error (hover for details)"
 echo
 echo -e "${BLUE}To test the MCP server:${NC}"
 echo "  nc localhost 8084"
+echo '  {"jsonrpc":"2.0","id":0,"method":"authenticate","params":{"token":"<contents of ~/.agent-browser/token>"}}'
 echo '  {"jsonrpc":"2.0","id":1,"method":"tools/list"}'
 echo
 echo -e "${BLUE}For convenience, you can use:${NC}"
@@ -102,5 +102,6 @@ echo "  3. Show ✓ badge when ready"
 echo
 echo "To test MCP connection:"
 echo "  nc localhost 8084"
+echo '  {"jsonrpc":"2.0","id":0,"method":"authenticate","params":{"token":"<contents of ~/.agent-browser/token>"}}'
 echo '  {"jsonrpc":"2.0","id":1,"method":"tools/list"}'
 echo
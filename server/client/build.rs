@@ -0,0 +1,134 @@
+//! Generates the typed tool API from the server's tool registry
+//!
+//! `src/mcp/tools.rs` is compiled into this build script as-is, so every
+//! tool in `builtin_tools()` gets an argument struct and a `Client` method
+//! and the client can never drift from what the server lists.
+
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+#[allow(dead_code)]
+#[path = "../src/mcp/tools.rs"]
+mod tools;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else {
+            out.push('_');
+        }
+    }
+    if RUST_KEYWORDS.contains(&out.as_str()) {
+        format!("r#{}", out)
+    } else {
+        out
+    }
+}
+
+/// Rust type for a JSON Schema property; anything not a plain scalar stays a `Value`
+fn rust_type(schema: &Value) -> &'static str {
+    match schema.get("type").and_then(|t| t.as_str()) {
+        Some("string") => "String",
+        Some("boolean") => "bool",
+        Some("integer") => "i64",
+        Some("number") => "f64",
+        Some("array") => match schema.pointer("/items/type").and_then(|t| t.as_str()) {
+            Some("string") => "Vec<String>",
+            Some("number") => "Vec<f64>",
+            Some("integer") => "Vec<i64>",
+            _ => "Vec<serde_json::Value>",
+        },
+        _ => "serde_json::Value",
+    }
+}
+
+fn generate(tools: &[Value]) -> String {
+    let mut structs = String::new();
+    let mut methods = String::new();
+    let mut names = Vec::new();
+
+    for tool in tools {
+        let name = tool["name"].as_str().expect("tool without a name");
+        let description = tool["description"].as_str().unwrap_or_default();
+        let args = format!("{}Args", pascal_case(name));
+        let required: Vec<&str> = tool
+            .pointer("/inputSchema/required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        names.push(name);
+
+        writeln!(structs, "/// Arguments for `{}`", name).unwrap();
+        writeln!(structs, "#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]").unwrap();
+        writeln!(structs, "pub struct {} {{", args).unwrap();
+        if let Some(properties) = tool.pointer("/inputSchema/properties").and_then(|p| p.as_object()) {
+            for (property, schema) in properties {
+                let field = snake_case(property);
+                let ty = rust_type(schema);
+                if let Some(doc) = schema.get("description").and_then(|d| d.as_str()) {
+                    writeln!(structs, "    #[doc = {:?}]", doc).unwrap();
+                }
+                if field.trim_start_matches("r#") != property {
+                    writeln!(structs, "    #[serde(rename = {:?})]", property).unwrap();
+                }
+                if required.contains(&property.as_str()) {
+                    writeln!(structs, "    pub {}: {},", field, ty).unwrap();
+                } else {
+                    writeln!(structs, "    #[serde(skip_serializing_if = \"Option::is_none\")]").unwrap();
+                    writeln!(structs, "    pub {}: Option<{}>,", field, ty).unwrap();
+                }
+            }
+        }
+        writeln!(structs, "}}\n").unwrap();
+
+        writeln!(methods, "    #[doc = {:?}]", description).unwrap();
+        writeln!(
+            methods,
+            "    pub async fn {}(&self, args: {}) -> Result<ToolResult> {{\n        self.call_tool({:?}, &args).await\n    }}\n",
+            name, args, name
+        )
+        .unwrap();
+    }
+
+    let mut out = String::from("// @generated by build.rs from src/mcp/tools.rs; do not edit\n\n");
+    writeln!(out, "/// Every tool in the server's registry, in `tools/list` order").unwrap();
+    writeln!(out, "pub const TOOL_NAMES: &[&str] = &{:?};\n", names).unwrap();
+    out.push_str(&structs);
+    writeln!(out, "impl Client {{\n{}}}", methods.trim_end()).unwrap();
+    out
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=../src/mcp/tools.rs");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("tools.rs");
+    std::fs::write(out, generate(&tools::builtin_tools())).unwrap();
+}
@@ -0,0 +1,570 @@
+/*!
+ * Agent Browser Client
+ *
+ * Typed async client for the MCP TCP interface (`localhost:8084`). Requests
+ * are newline-delimited JSON-RPC 2.0 and may be issued concurrently from
+ * clones of one `Client`; responses are matched back by id.
+ *
+ * Every tool in the server's registry has an argument struct in [`tools`]
+ * and a method on `Client`, both generated at build time from
+ * `src/mcp/tools.rs`:
+ *
+ * ```no_run
+ * # async fn demo() -> agent_browser_client::Result<()> {
+ * use agent_browser_client::{tools::PlaywrightNavigateArgs, Client};
+ *
+ * let client = Client::connect("127.0.0.1:8084").await?;
+ * let result = client
+ *     .playwright_navigate(PlaywrightNavigateArgs { url: "https://example.com".into(), ..Default::default() })
+ *     .await?;
+ * println!("{}", result.text());
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * If the connection drops, calls in flight fail with [`Error::Disconnected`]
+ * and the next call reconnects and re-initializes. Calls are never retried
+ * automatically since most tools have side effects in the browser. A new
+ * connection is a new MCP session: passkey authorization, popup following
+ * and other per-session state must be set up again.
+ *
+ * Each connection starts with `authenticate`, using the token the server
+ * writes to `~/.agent-browser/token` unless one is given in
+ * [`ClientOptions`].
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+/// Typed tool arguments generated from the server's tool registry
+pub mod tools {
+    use crate::{Client, Result, ToolResult};
+
+    include!(concat!(env!("OUT_DIR"), "/tools.rs"));
+}
+
+/// Protocol revision requested in `initialize`
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8084";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// JSON-RPC error returned by the server
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+    /// The connection closed before the response arrived
+    Disconnected,
+    Timeout,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Json(e) => write!(f, "Invalid JSON: {}", e),
+            Error::Rpc { code, message, .. } => write!(f, "{} ({})", message, code),
+            Error::Disconnected => write!(f, "Connection to agent-browser closed"),
+            Error::Timeout => write!(f, "Request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Token written by a server on this machine, if there is one
+fn local_token() -> Option<String> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    let token = std::fs::read_to_string(std::path::Path::new(&home).join(".agent-browser").join("token")).ok()?;
+    Some(token.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Reported as `clientInfo.name` in `initialize`
+    pub client_name: String,
+    pub request_timeout: Duration,
+    /// Connection attempts after the first before a call gives up
+    pub reconnect_attempts: u32,
+    /// Delay before the first retry; doubles with each attempt
+    pub reconnect_delay: Duration,
+    /// Auth token; when unset, `~/.agent-browser/token` is read on every
+    /// connect so reconnects after a server restart pick up the new token
+    pub token: Option<String>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            client_name: "agent-browser-client".to_string(),
+            request_timeout: Duration::from_secs(60),
+            reconnect_attempts: 3,
+            reconnect_delay: Duration::from_millis(250),
+            token: None,
+        }
+    }
+}
+
+/// One content block of a tool result
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: Option<String>,
+    pub data: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+/// Result of `tools/call`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResult {
+    #[serde(default)]
+    pub content: Vec<Content>,
+    /// Present when the negotiated protocol supports structured output, and
+    /// always for tools the server answers without the extension
+    pub structured_content: Option<Value>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    /// Text blocks joined with newlines
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|c| c.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The tool's JSON result: `structuredContent`, or the text block parsed as JSON
+    pub fn json(&self) -> Result<Value> {
+        match &self.structured_content {
+            Some(value) => Ok(value.clone()),
+            None => Ok(serde_json::from_str(&self.text())?),
+        }
+    }
+
+    /// Decode the tool's JSON result into a caller-defined type
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.json()?)?)
+    }
+}
+
+/// Server-sent notification (progress, list changes, credential use, ...)
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub method: String,
+    pub params: Value,
+}
+
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
+
+struct Connection {
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+    reader: JoinHandle<()>,
+}
+
+impl Connection {
+    async fn open(addr: &str, notifications: broadcast::Sender<Notification>) -> Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let (reader, writer) = stream.into_split();
+        let writer = Arc::new(Mutex::new(writer));
+        let pending: Pending = Arc::default();
+        let alive = Arc::new(AtomicBool::new(true));
+
+        let reader = tokio::spawn(read_loop(
+            BufReader::new(reader),
+            Arc::clone(&writer),
+            Arc::clone(&pending),
+            Arc::clone(&alive),
+            notifications,
+        ));
+        Ok(Self {
+            writer,
+            pending,
+            alive,
+            reader,
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    async fn request(&self, id: u64, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = write_line(&self.writer, &message).await {
+            self.pending.lock().unwrap().remove(&id);
+            self.alive.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(Error::Disconnected),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(Error::Timeout)
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        write_line(&self.writer, &json!({ "jsonrpc": "2.0", "method": method, "params": params })).await
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+async fn write_line(writer: &Mutex<OwnedWriteHalf>, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    let mut writer = writer.lock().await;
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+async fn read_loop(
+    mut reader: BufReader<tokio::net::tcp::OwnedReadHalf>,
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+    notifications: broadcast::Sender<Notification>,
+) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+
+        match (message.get("id"), message.get("method").and_then(|m| m.as_str())) {
+            // Response to one of our requests
+            (Some(id), None) => {
+                let Some(tx) = id.as_u64().and_then(|id| pending.lock().unwrap().remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(Error::Rpc {
+                        code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(-32000),
+                        message: error.get("message").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+                        data: error.get("data").cloned(),
+                    }),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = tx.send(result);
+            }
+            // Server keep-alive
+            (Some(id), Some("ping")) => {
+                let _ = write_line(&writer, &json!({ "jsonrpc": "2.0", "id": id, "result": {} })).await;
+            }
+            // Other server-initiated requests: no client capabilities are declared, so none are supported
+            (Some(id), Some(method)) => {
+                let reply = json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Method not found: {}", method) }
+                });
+                let _ = write_line(&writer, &reply).await;
+            }
+            (None, Some(method)) => {
+                let _ = notifications.send(Notification {
+                    method: method.to_string(),
+                    params: message.get("params").cloned().unwrap_or(Value::Null),
+                });
+            }
+            (None, None) => {}
+        }
+    }
+
+    alive.store(false, Ordering::SeqCst);
+    // Dropping the senders fails every waiting call with Disconnected
+    pending.lock().unwrap().clear();
+}
+
+/// Client for one agent-browser server; cheap to clone, clones share the connection
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    addr: String,
+    options: ClientOptions,
+    connection: Mutex<Option<Arc<Connection>>>,
+    next_id: AtomicU64,
+    notifications: broadcast::Sender<Notification>,
+    protocol_version: std::sync::Mutex<Option<String>>,
+}
+
+impl Client {
+    /// Connect and initialize with default options
+    pub async fn connect(addr: &str) -> Result<Self> {
+        Self::connect_with_options(addr, ClientOptions::default()).await
+    }
+
+    pub async fn connect_with_options(addr: &str, options: ClientOptions) -> Result<Self> {
+        let client = Self {
+            inner: Arc::new(Inner {
+                addr: addr.to_string(),
+                options,
+                connection: Mutex::new(None),
+                next_id: AtomicU64::new(1),
+                notifications: broadcast::channel(256).0,
+                protocol_version: std::sync::Mutex::new(None),
+            }),
+        };
+        client.connection().await?;
+        Ok(client)
+    }
+
+    /// Protocol version the server agreed to on the current connection
+    pub fn protocol_version(&self) -> Option<String> {
+        self.inner.protocol_version.lock().unwrap().clone()
+    }
+
+    /// Subscribe to server notifications; only those sent after subscribing are received
+    pub fn notifications(&self) -> broadcast::Receiver<Notification> {
+        self.inner.notifications.subscribe()
+    }
+
+    /// The live connection, reconnecting (and re-initializing) if it dropped
+    async fn connection(&self) -> Result<Arc<Connection>> {
+        let mut current = self.inner.connection.lock().await;
+        if let Some(conn) = current.as_ref().filter(|c| c.is_alive()) {
+            return Ok(Arc::clone(conn));
+        }
+        *current = None;
+
+        let options = &self.inner.options;
+        let mut delay = options.reconnect_delay;
+        let mut attempt = 0;
+        loop {
+            match self.open().await {
+                Ok(conn) => {
+                    *current = Some(Arc::clone(&conn));
+                    return Ok(conn);
+                }
+                Err(e @ (Error::Io(_) | Error::Disconnected)) => {
+                    if attempt >= options.reconnect_attempts {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn open(&self) -> Result<Arc<Connection>> {
+        let conn = Arc::new(Connection::open(&self.inner.addr, self.inner.notifications.clone()).await?);
+        let timeout = self.inner.options.request_timeout;
+        if let Some(token) = self.inner.options.token.clone().or_else(local_token) {
+            conn.request(self.next_id(), "authenticate", json!({ "token": token }), timeout)
+                .await?;
+        }
+        let params = json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": self.inner.options.client_name, "version": env!("CARGO_PKG_VERSION") }
+        });
+        let result = conn.request(self.next_id(), "initialize", params, timeout).await?;
+        *self.inner.protocol_version.lock().unwrap() =
+            result.get("protocolVersion").and_then(|v| v.as_str()).map(String::from);
+        conn.notify("notifications/initialized", json!({})).await?;
+        Ok(conn)
+    }
+
+    fn next_id(&self) -> u64 {
+        self.inner.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Send a raw JSON-RPC request and return its `result`
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let conn = self.connection().await?;
+        conn.request(self.next_id(), method, params, self.inner.options.request_timeout)
+            .await
+    }
+
+    /// Call any tool by name, including ones registered after this crate was built
+    pub async fn call_tool<A: Serialize + ?Sized>(&self, name: &str, args: &A) -> Result<ToolResult> {
+        let params = json!({ "name": name, "arguments": serde_json::to_value(args)? });
+        let result = self.request("tools/call", params).await?;
+        // Tools the server answers itself return their JSON without a content wrapper
+        if result.get("content").is_none() {
+            return Ok(ToolResult {
+                content: Vec::new(),
+                structured_content: Some(result),
+                is_error: false,
+            });
+        }
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Tool definitions currently available on the server
+    pub async fn list_tools(&self) -> Result<Vec<Value>> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(result
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        self.request("ping", json!({})).await.map(|_| ())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::tools::*;
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Expects authenticate first, answers initialize and echoes tool arguments back; closes after `limit` tool calls
+    async fn serve_one(listener: &TcpListener, limit: usize) {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut calls = 0;
+        let mut first = true;
+        while let Ok(Some(line)) = lines.next_line().await {
+            let request: Value = serde_json::from_str(&line).unwrap();
+            if std::mem::take(&mut first) {
+                assert_eq!(request["method"], "authenticate");
+                assert_eq!(request["params"]["token"], "secret");
+            }
+            let Some(id) = request.get("id") else {
+                continue;
+            };
+            let result = match request["method"].as_str().unwrap() {
+                "initialize" => json!({ "protocolVersion": "2025-06-18" }),
+                "tools/call" if request["params"]["arguments"]["raw"] == true => {
+                    calls += 1;
+                    json!({ "deleted": true })
+                }
+                "tools/call" => {
+                    calls += 1;
+                    let text = serde_json::to_string(&request["params"]).unwrap();
+                    json!({ "content": [{ "type": "text", "text": text }] })
+                }
+                _ => json!({}),
+            };
+            let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+            writer.write_all(format!("{}\n", response).as_bytes()).await.unwrap();
+            if calls == limit {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_args_serialize_like_the_schema() {
+        assert!(TOOL_NAMES.contains(&"playwright_navigate"));
+
+        let args = PlaywrightNavigateArgs {
+            url: "https://example.com".into(),
+            ..Default::default()
+        };
+        assert_eq!(serde_json::to_value(&args).unwrap(), json!({ "url": "https://example.com" }));
+
+        let args = PlaywrightNavigateArgs {
+            url: "https://example.com".into(),
+            bypass_service_worker: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(serde_json::to_value(&args).unwrap()["bypassServiceWorker"], true);
+    }
+
+    #[tokio::test]
+    async fn test_typed_call_and_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            serve_one(&listener, 1).await;
+            serve_one(&listener, 2).await;
+        });
+
+        let options = ClientOptions {
+            token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let client = Client::connect_with_options(&addr, options).await.unwrap();
+        assert_eq!(client.protocol_version().as_deref(), Some("2025-06-18"));
+
+        let result = client
+            .playwright_click(PlaywrightClickArgs {
+                selector: Some("#go".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let echoed = result.json().unwrap();
+        assert_eq!(echoed["name"], "playwright_click");
+        assert_eq!(echoed["arguments"], json!({ "selector": "#go" }));
+
+        // The server hung up after one call; the next call reconnects
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let result = client.call_tool("state_get", &json!({ "key": "k" })).await.unwrap();
+        assert_eq!(result.json().unwrap()["name"], "state_get");
+        // Unwrapped results (server-answered tools) come back as structured content
+        assert_eq!(result.structured_content, None);
+        let result = client.call_tool("state_delete", &json!({ "raw": true })).await.unwrap();
+        assert_eq!(result.structured_content, Some(json!({ "deleted": true })));
+
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+/*!
+ * Accessibility Audit
+ *
+ * `audit_accessibility` runs a fixed set of axe-core-style checks in the
+ * page through the extension's `audit_accessibility` command. The extension
+ * only reports raw findings: for each rule, how many elements it examined
+ * and which of them failed. Impact, WCAG references and scoring live here,
+ * so the rule catalog can change without an extension release.
+ *
+ * The score follows Lighthouse: each rule that applied to the page (examined
+ * at least one element) is weighted by its impact and counts as passed only
+ * with zero failures, giving 0–100. Rules that did not apply are listed but
+ * left out of the score.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Impact {
+    Minor,
+    Moderate,
+    Serious,
+    Critical,
+}
+
+impl Impact {
+    fn weight(self) -> u32 {
+        match self {
+            Impact::Critical => 10,
+            Impact::Serious => 7,
+            Impact::Moderate => 3,
+            Impact::Minor => 1,
+        }
+    }
+}
+
+pub struct Rule {
+    pub id: &'static str,
+    pub impact: Impact,
+    pub wcag: &'static str,
+    pub help: &'static str,
+}
+
+/// Rules the extension knows how to check
+pub const RULES: &[Rule] = &[
+    Rule { id: "image-alt", impact: Impact::Critical, wcag: "1.1.1", help: "Images must have alternative text (alt, aria-label or role=\"presentation\")" },
+    Rule { id: "label", impact: Impact::Critical, wcag: "1.3.1, 4.1.2", help: "Form fields must have a label, aria-label or aria-labelledby" },
+    Rule { id: "button-name", impact: Impact::Critical, wcag: "4.1.2", help: "Buttons must have discernible text" },
+    Rule { id: "link-name", impact: Impact::Serious, wcag: "2.4.4, 4.1.2", help: "Links must have discernible text" },
+    Rule { id: "color-contrast", impact: Impact::Serious, wcag: "1.4.3", help: "Text must have a contrast ratio of at least 4.5:1 (3:1 for large text)" },
+    Rule { id: "html-has-lang", impact: Impact::Serious, wcag: "3.1.1", help: "The <html> element must have a lang attribute" },
+    Rule { id: "document-title", impact: Impact::Serious, wcag: "2.4.2", help: "Documents must have a non-empty <title>" },
+    Rule { id: "aria-valid-role", impact: Impact::Critical, wcag: "4.1.2", help: "role attributes must use valid ARIA roles" },
+    Rule { id: "aria-hidden-focus", impact: Impact::Serious, wcag: "4.1.2", help: "aria-hidden elements must not contain focusable elements" },
+    Rule { id: "aria-labelledby-target", impact: Impact::Serious, wcag: "4.1.2", help: "aria-labelledby and aria-describedby must reference existing ids" },
+    Rule { id: "duplicate-id", impact: Impact::Minor, wcag: "4.1.1", help: "id attributes must be unique" },
+    Rule { id: "heading-order", impact: Impact::Moderate, wcag: "1.3.1", help: "Heading levels should only increase by one" },
+    Rule { id: "frame-title", impact: Impact::Serious, wcag: "4.1.2", help: "Frames must have a title" },
+];
+
+pub fn rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+/// One rule's raw outcome as collected by the extension
+#[derive(Debug, Deserialize)]
+pub struct RuleFinding {
+    pub rule: String,
+    #[serde(default)]
+    pub checked: u64,
+    #[serde(default)]
+    pub nodes: Vec<FailedNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedNode {
+    pub selector: String,
+    #[serde(default)]
+    pub html: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    pub rule: &'static str,
+    pub impact: Impact,
+    pub wcag: &'static str,
+    pub help: &'static str,
+    pub count: usize,
+    pub nodes: Vec<FailedNode>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub score: u32,
+    pub violations: Vec<Violation>,
+    pub passed: Vec<&'static str>,
+    pub inapplicable: Vec<&'static str>,
+    pub by_impact: BTreeMap<&'static str, usize>,
+}
+
+/// Aggregate the extension's findings into a scored report, keeping at most
+/// `max_nodes` example elements per violated rule
+pub fn aggregate(findings: Vec<RuleFinding>, max_nodes: usize) -> AuditReport {
+    let mut violations = Vec::new();
+    let mut passed = Vec::new();
+    let mut inapplicable = Vec::new();
+    let (mut earned, mut possible) = (0, 0);
+
+    for finding in findings {
+        // Findings for rules this server doesn't know are ignored rather than guessed at
+        let Some(rule) = rule(&finding.rule) else {
+            continue;
+        };
+        if finding.checked == 0 && finding.nodes.is_empty() {
+            inapplicable.push(rule.id);
+            continue;
+        }
+        possible += rule.impact.weight();
+        if finding.nodes.is_empty() {
+            earned += rule.impact.weight();
+            passed.push(rule.id);
+            continue;
+        }
+        let count = finding.nodes.len();
+        let mut nodes = finding.nodes;
+        nodes.truncate(max_nodes);
+        violations.push(Violation { rule: rule.id, impact: rule.impact, wcag: rule.wcag, help: rule.help, count, nodes });
+    }
+
+    // Most severe and most widespread first
+    violations.sort_by(|a, b| b.impact.cmp(&a.impact).then(b.count.cmp(&a.count)));
+    let mut by_impact = BTreeMap::new();
+    for violation in &violations {
+        let key = match violation.impact {
+            Impact::Critical => "critical",
+            Impact::Serious => "serious",
+            Impact::Moderate => "moderate",
+            Impact::Minor => "minor",
+        };
+        *by_impact.entry(key).or_insert(0) += violation.count;
+    }
+
+    AuditReport {
+        score: (earned * 100 + possible / 2).checked_div(possible).unwrap_or(100),
+        violations,
+        passed,
+        inapplicable,
+        by_impact,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule: &str, checked: u64, failed: usize) -> RuleFinding {
+        RuleFinding {
+            rule: rule.to_string(),
+            checked,
+            nodes: (0..failed)
+                .map(|i| FailedNode { selector: format!("#n{}", i), html: String::new(), message: None })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_scores_applicable_rules_by_impact() {
+        let report = aggregate(
+            vec![
+                finding("image-alt", 4, 3),
+                finding("heading-order", 6, 1),
+                finding("link-name", 10, 0),
+                finding("frame-title", 0, 0),
+                finding("made-up-rule", 5, 5),
+            ],
+            2,
+        );
+        // link-name (7) passed out of image-alt (10) + heading-order (3) + link-name (7)
+        assert_eq!(report.score, 35);
+        assert_eq!(report.violations[0].rule, "image-alt");
+        assert_eq!(report.violations[0].count, 3);
+        assert_eq!(report.violations[0].nodes.len(), 2);
+        assert_eq!(report.passed, ["link-name"]);
+        assert_eq!(report.inapplicable, ["frame-title"]);
+        assert_eq!(report.by_impact["critical"], 3);
+        assert_eq!(report.by_impact["moderate"], 1);
+
+        assert_eq!(aggregate(Vec::new(), 10).score, 100);
+    }
+
+    #[test]
+    fn test_tool_schema_lists_every_rule() {
+        let tools = crate::mcp::tools::builtin_tools();
+        let tool = tools.iter().find(|t| t["name"] == "audit_accessibility").unwrap();
+        let listed = &tool["inputSchema"]["properties"]["rules"]["items"]["enum"];
+        let ids: Vec<&str> = RULES.iter().map(|rule| rule.id).collect();
+        assert_eq!(listed, &serde_json::json!(ids));
+    }
+}
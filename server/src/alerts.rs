@@ -0,0 +1,542 @@
+/*!
+ * Operator Alerts
+ *
+ * Events an operator may want to hear about outside the MCP session are
+ * raised as alerts in one of three classes: `authorization` (passkey and
+ * recovery authorizations, delegated grants, credential use),
+ * `policy_violation` (rejected tokens and origins, refused privileged
+ * calls) and `task_completion` (watch changes, finished reports). Each
+ * class is routed to any number of channels configured in
+ * `~/.agent-browser/alerts.json`:
+ *
+ * ```json
+ * {
+ *   "channels": {
+ *     "desktop": { "type": "desktop" },
+ *     "ops": { "type": "webhook", "url": "https://hooks.example.com/agent-browser" },
+ *     "mail": {
+ *       "type": "smtp",
+ *       "server": "smtps://smtp.example.com:465",
+ *       "from": "agent-browser@example.com",
+ *       "to": ["oncall@example.com"],
+ *       "username": "agent-browser@example.com",
+ *       "passwordEnv": "AGENT_BROWSER_SMTP_PASSWORD"
+ *     }
+ *   },
+ *   "routes": {
+ *     "authorization": ["desktop"],
+ *     "policy_violation": ["ops", "mail"],
+ *     "task_completion": ["ops"]
+ *   }
+ * }
+ * ```
+ *
+ * Channels implement `Sink`, so new kinds plug in without touching the
+ * places that raise alerts. Delivery happens in the background and a failing
+ * channel is only logged. Identical alerts within `REPEAT_SUPPRESS_SECS` are
+ * sent once, so a client retrying a bad token doesn't flood the inbox.
+ */
+
+use anyhow::{anyhow, bail, Result};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tracing::{debug, warn};
+
+use crate::preflight::{self, Target};
+use crate::storage;
+use crate::watch;
+
+pub const CONFIG_FILE: &str = "alerts.json";
+const REPEAT_SUPPRESS_SECS: u64 = 60;
+const SMTP_TIMEOUT_SECS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventClass {
+    Authorization,
+    PolicyViolation,
+    TaskCompletion,
+}
+
+impl EventClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            EventClass::Authorization => "authorization",
+            EventClass::PolicyViolation => "policy_violation",
+            EventClass::TaskCompletion => "task_completion",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub class: EventClass,
+    pub title: String,
+    pub body: String,
+    pub session: Option<String>,
+    pub timestamp: String,
+}
+
+impl Alert {
+    pub fn new(class: EventClass, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            class,
+            title: title.into(),
+            body: body.into(),
+            session: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn for_session(mut self, session: &str) -> Self {
+        self.session = Some(session.to_string());
+        self
+    }
+}
+
+// ============================================================================
+// Sinks
+// ============================================================================
+
+/// A place alerts can be delivered to
+pub trait Sink: Send + Sync {
+    fn kind(&self) -> &'static str;
+    fn deliver<'a>(&'a self, alert: &'a Alert) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Native desktop notification (notify-send on Linux, osascript on macOS)
+pub struct DesktopSink;
+
+impl Sink for DesktopSink {
+    fn kind(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn deliver<'a>(&'a self, alert: &'a Alert) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut command = if cfg!(target_os = "macos") {
+                let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+                let mut command = tokio::process::Command::new("osascript");
+                command.arg("-e").arg(format!(
+                    "display notification {} with title {}",
+                    quote(&alert.body),
+                    quote(&format!("Agent Browser: {}", alert.title))
+                ));
+                command
+            } else if cfg!(unix) {
+                let mut command = tokio::process::Command::new("notify-send");
+                command.args(["--app-name", "Agent Browser", &alert.title, &alert.body]);
+                command
+            } else {
+                bail!("Desktop notifications are not supported on this platform");
+            };
+            let status = command.status().await?;
+            if !status.success() {
+                bail!("notifier exited with {}", status);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// JSON POST of the alert to an HTTP(S) endpoint
+pub struct WebhookSink {
+    pub url: String,
+}
+
+impl Sink for WebhookSink {
+    fn kind(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn deliver<'a>(&'a self, alert: &'a Alert) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let payload = serde_json::to_value(alert)?;
+            match watch::post_webhook(&self.url, &payload).await {
+                Ok(status) if status < 400 => Ok(()),
+                Ok(status) => Err(anyhow!("webhook answered {}", status)),
+                Err(e) => Err(anyhow!(e)),
+            }
+        })
+    }
+}
+
+/// Plain-text mail over SMTP: implicit TLS for `smtps://`, STARTTLS when
+/// offered for `smtp://`
+pub struct SmtpSink {
+    pub implicit_tls: bool,
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    pub to: Vec<String>,
+    pub username: Option<String>,
+    pub password_env: Option<String>,
+}
+
+impl SmtpSink {
+    fn parse_server(server: &str) -> Result<(bool, String, u16)> {
+        let (implicit_tls, rest, default_port) = if let Some(rest) = server.strip_prefix("smtps://") {
+            (true, rest, 465)
+        } else if let Some(rest) = server.strip_prefix("smtp://") {
+            (false, rest, 587)
+        } else {
+            bail!("Invalid SMTP server '{}': expected smtps://host[:port] or smtp://host[:port]", server);
+        };
+        let rest = rest.trim_end_matches('/');
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| anyhow!("Invalid SMTP port in '{}'", server))?),
+            None => (rest, default_port),
+        };
+        if host.is_empty() {
+            bail!("Missing SMTP host in '{}'", server);
+        }
+        Ok((implicit_tls, host.to_string(), port))
+    }
+
+    fn credentials(&self) -> Result<Option<(String, String)>> {
+        let Some(username) = &self.username else {
+            return Ok(None);
+        };
+        let var = self
+            .password_env
+            .as_deref()
+            .ok_or_else(|| anyhow!("SMTP username set without passwordEnv"))?;
+        let password = std::env::var(var).map_err(|_| anyhow!("SMTP password variable {} is not set", var))?;
+        Ok(Some((username.clone(), password)))
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let credentials = self.credentials()?;
+        let message = format_message(&self.from, &self.to, alert);
+        let tcp = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        let server_name = ServerName::try_from(self.host.clone())?;
+
+        if self.implicit_tls {
+            let mut tls = BufReader::new(preflight::tls_connector().connect(server_name, tcp).await?);
+            expect(&mut tls, None, 220).await?;
+            expect(&mut tls, Some("EHLO agent-browser"), 250).await?;
+            return self.transact(&mut tls, credentials, &message).await;
+        }
+
+        let mut plain = BufReader::new(tcp);
+        expect(&mut plain, None, 220).await?;
+        let features = expect(&mut plain, Some("EHLO agent-browser"), 250).await?;
+        if features.to_ascii_uppercase().contains("STARTTLS") {
+            expect(&mut plain, Some("STARTTLS"), 220).await?;
+            let tls = preflight::tls_connector().connect(server_name, plain.into_inner()).await?;
+            let mut tls = BufReader::new(tls);
+            expect(&mut tls, Some("EHLO agent-browser"), 250).await?;
+            return self.transact(&mut tls, credentials, &message).await;
+        }
+        if credentials.is_some() {
+            bail!("{} does not offer STARTTLS; refusing to send credentials in the clear", self.host);
+        }
+        self.transact(&mut plain, None, &message).await
+    }
+
+    async fn transact<S>(&self, stream: &mut BufReader<S>, credentials: Option<(String, String)>, message: &str) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+
+        if let Some((username, password)) = credentials {
+            let token = BASE64.encode(format!("\0{}\0{}", username, password));
+            expect(stream, Some(&format!("AUTH PLAIN {}", token)), 235).await?;
+        }
+        expect(stream, Some(&format!("MAIL FROM:<{}>", self.from)), 250).await?;
+        for to in &self.to {
+            expect(stream, Some(&format!("RCPT TO:<{}>", to)), 250).await?;
+        }
+        expect(stream, Some("DATA"), 354).await?;
+        expect(stream, Some(&format!("{}\r\n.", message)), 250).await?;
+        let _ = expect(stream, Some("QUIT"), 221).await;
+        Ok(())
+    }
+}
+
+impl Sink for SmtpSink {
+    fn kind(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn deliver<'a>(&'a self, alert: &'a Alert) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            timeout(Duration::from_secs(SMTP_TIMEOUT_SECS), self.send(alert))
+                .await
+                .map_err(|_| anyhow!("SMTP exchange with {} timed out", self.host))?
+        })
+    }
+}
+
+/// Send a command (or just read, for the greeting) and require the reply code;
+/// returns the reply text
+async fn expect<S>(stream: &mut BufReader<S>, command: Option<&str>, code: u16) -> Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Some(command) = command {
+        stream.get_mut().write_all(format!("{}\r\n", command).as_bytes()).await?;
+        stream.get_mut().flush().await?;
+    }
+    // Multi-line replies continue with "250-" and end with "250 "
+    let mut text = String::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            bail!("SMTP server closed the connection");
+        }
+        text.push_str(&line);
+        if line.len() < 4 || line.as_bytes()[3] != b'-' {
+            let got: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+            if got != code {
+                let verb = command.and_then(|c| c.split_whitespace().next()).unwrap_or("greeting");
+                bail!("SMTP {} failed: {}", verb, text.trim());
+            }
+            return Ok(text);
+        }
+    }
+}
+
+/// RFC 5322 message with dot-stuffed body lines
+fn format_message(from: &str, to: &[String], alert: &Alert) -> String {
+    let mut body = alert.body.clone();
+    if let Some(session) = &alert.session {
+        body.push_str(&format!("\n\nSession: {}", session));
+    }
+    body.push_str(&format!("\nClass: {}\nTime: {}", alert.class.name(), alert.timestamp));
+    let body: Vec<String> = body
+        .lines()
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect();
+    let subject: String = alert.title.chars().filter(|c| !c.is_control()).collect();
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: [Agent Browser] {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+        from,
+        to.join(", "),
+        subject,
+        chrono::Utc::now().to_rfc2822(),
+        body.join("\r\n")
+    )
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", rename_all_fields = "camelCase", deny_unknown_fields)]
+enum ChannelConfig {
+    Desktop,
+    Webhook {
+        url: String,
+    },
+    Smtp {
+        server: String,
+        from: String,
+        to: Vec<String>,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password_env: Option<String>,
+    },
+}
+
+impl ChannelConfig {
+    fn build(self) -> Result<Arc<dyn Sink>> {
+        Ok(match self {
+            ChannelConfig::Desktop => Arc::new(DesktopSink),
+            ChannelConfig::Webhook { url } => {
+                Target::parse(&url).map_err(|e| anyhow!(e))?;
+                Arc::new(WebhookSink { url })
+            }
+            ChannelConfig::Smtp { server, from, to, username, password_env } => {
+                let (implicit_tls, host, port) = SmtpSink::parse_server(&server)?;
+                if to.is_empty() {
+                    bail!("SMTP channel needs at least one recipient");
+                }
+                Arc::new(SmtpSink { implicit_tls, host, port, from, to, username, password_env })
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlertsConfig {
+    #[serde(default)]
+    channels: BTreeMap<String, ChannelConfig>,
+    #[serde(default)]
+    routes: HashMap<EventClass, Vec<String>>,
+}
+
+// ============================================================================
+// Router
+// ============================================================================
+
+#[derive(Default)]
+pub struct AlertRouter {
+    channels: BTreeMap<String, Arc<dyn Sink>>,
+    routes: HashMap<EventClass, Vec<String>>,
+    recent: Mutex<HashMap<(EventClass, String, String), Instant>>,
+}
+
+impl AlertRouter {
+    /// Load channels and routes from ~/.agent-browser/alerts.json (no file = no alerts)
+    pub fn load() -> Result<Self> {
+        let path = storage::data_dir()?.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config: AlertsConfig = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))?;
+        Self::from_config(config)
+    }
+
+    fn from_config(config: AlertsConfig) -> Result<Self> {
+        for (class, names) in &config.routes {
+            if let Some(missing) = names.iter().find(|name| !config.channels.contains_key(*name)) {
+                bail!("Route {} names unknown channel '{}'", class.name(), missing);
+            }
+        }
+        let channels = config
+            .channels
+            .into_iter()
+            .map(|(name, channel)| {
+                let sink = channel.build().map_err(|e| anyhow!("Channel '{}': {}", name, e))?;
+                Ok((name, sink))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            channels,
+            routes: config.routes,
+            recent: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn sinks_for(&self, class: EventClass) -> Vec<(String, Arc<dyn Sink>)> {
+        self.routes
+            .get(&class)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.channels.get(name).map(|sink| (name.clone(), Arc::clone(sink))))
+            .collect()
+    }
+
+    /// Whether the same alert went out within the suppression window
+    fn is_repeat(&self, alert: &Alert) -> bool {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        let window = Duration::from_secs(REPEAT_SUPPRESS_SECS);
+        recent.retain(|_, sent| sent.elapsed() < window);
+        let key = (alert.class, alert.title.clone(), alert.body.clone());
+        if recent.contains_key(&key) {
+            return true;
+        }
+        recent.insert(key, Instant::now());
+        false
+    }
+
+    /// Deliver in the background to every channel routed for the alert's class
+    pub fn send(&self, alert: Alert) {
+        let sinks = self.sinks_for(alert.class);
+        if sinks.is_empty() || self.is_repeat(&alert) {
+            return;
+        }
+        let alert = Arc::new(alert);
+        for (name, sink) in sinks {
+            let alert = Arc::clone(&alert);
+            tokio::spawn(async move {
+                match sink.deliver(&alert).await {
+                    Ok(()) => debug!("Alert '{}' delivered to {}", alert.title, name),
+                    Err(e) => warn!("Alert channel {} failed: {}", name, e),
+                }
+            });
+        }
+    }
+
+    /// Deliver now and report each channel's outcome (for alerts_test)
+    pub async fn deliver(&self, alert: &Alert) -> Vec<serde_json::Value> {
+        let mut results = Vec::new();
+        for (name, sink) in self.sinks_for(alert.class) {
+            let outcome = sink.deliver(alert).await;
+            results.push(serde_json::json!({
+                "channel": name,
+                "type": sink.kind(),
+                "delivered": outcome.is_ok(),
+                "error": outcome.err().map(|e| e.to_string()),
+            }));
+        }
+        results
+    }
+
+    /// Configured channels and routes
+    pub fn describe(&self) -> serde_json::Value {
+        let channels: BTreeMap<&str, &str> = self.channels.iter().map(|(name, sink)| (name.as_str(), sink.kind())).collect();
+        let routes: BTreeMap<&str, &Vec<String>> = self.routes.iter().map(|(class, names)| (class.name(), names)).collect();
+        serde_json::json!({ "channels": channels, "routes": routes })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: serde_json::Value) -> Result<AlertRouter> {
+        AlertRouter::from_config(serde_json::from_value(json)?)
+    }
+
+    #[test]
+    fn test_config_routes_classes_to_channels() {
+        let router = config(serde_json::json!({
+            "channels": {
+                "desk": { "type": "desktop" },
+                "hook": { "type": "webhook", "url": "https://hooks.example.com/x" },
+                "mail": { "type": "smtp", "server": "smtp://mail.example.com", "from": "a@example.com", "to": ["b@example.com"] }
+            },
+            "routes": { "policy_violation": ["hook", "mail"], "authorization": ["desk"] }
+        }))
+        .unwrap();
+        let names: Vec<String> = router.sinks_for(EventClass::PolicyViolation).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["hook", "mail"]);
+        assert!(router.sinks_for(EventClass::TaskCompletion).is_empty());
+        assert_eq!(router.describe()["channels"]["mail"], "smtp");
+
+        assert!(config(serde_json::json!({ "routes": { "authorization": ["missing"] } })).is_err());
+        assert!(config(serde_json::json!({ "channels": { "x": { "type": "webhook", "url": "ftp://x" } } })).is_err());
+        assert!(config(serde_json::json!({ "channels": { "x": { "type": "pager" } } })).is_err());
+    }
+
+    #[test]
+    fn test_smtp_server_and_message() {
+        assert_eq!(SmtpSink::parse_server("smtps://mail.example.com").unwrap(), (true, "mail.example.com".to_string(), 465));
+        assert_eq!(SmtpSink::parse_server("smtp://localhost:2525").unwrap(), (false, "localhost".to_string(), 2525));
+        assert!(SmtpSink::parse_server("mail.example.com").is_err());
+
+        let alert = Alert::new(EventClass::PolicyViolation, "Invalid token\r\nBcc: x", ".hidden\nline").for_session("tcp:1");
+        let message = format_message("a@example.com", &["b@example.com".to_string()], &alert);
+        assert!(message.contains("Subject: [Agent Browser] Invalid tokenBcc: x\r\n"));
+        assert!(message.contains("\r\n..hidden\r\nline"));
+        assert!(message.contains("Session: tcp:1"));
+    }
+
+    #[test]
+    fn test_repeats_are_suppressed() {
+        let router = AlertRouter::default();
+        let alert = Alert::new(EventClass::PolicyViolation, "Invalid MCP token", "from 10.0.0.2");
+        assert!(!router.is_repeat(&alert));
+        assert!(router.is_repeat(&alert));
+        assert!(!router.is_repeat(&Alert::new(EventClass::PolicyViolation, "Invalid MCP token", "from 10.0.0.3")));
+    }
+}
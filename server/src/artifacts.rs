@@ -0,0 +1,159 @@
+/*!
+ * Per-Session Artifacts
+ *
+ * Files written by tools (screenshots, PDFs, exports) for sessions that
+ * declared no MCP roots go to `~/.agent-browser/artifacts/<session>/`, so
+ * concurrent sessions never overwrite each other's outputs. Session
+ * directories untouched for longer than the retention period are removed
+ * by a periodic sweep.
+ */
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::storage;
+
+/// Default retention for session directories, overridable with
+/// `AGENT_BROWSER_ARTIFACT_TTL_HOURS`
+pub const DEFAULT_RETENTION_HOURS: u64 = 24;
+pub const SWEEP_INTERVAL_SECS: u64 = 3600;
+
+pub fn retention() -> Duration {
+    let hours = std::env::var("AGENT_BROWSER_ARTIFACT_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_HOURS);
+    Duration::from_secs(hours * 3600)
+}
+
+/// Session labels ("tcp:127.0.0.1:5123") as directory names ("tcp_127.0.0.1_5123")
+pub fn session_dir_name(session: &str) -> String {
+    session
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+fn artifacts_dir() -> Result<PathBuf> {
+    storage::data_subdir("artifacts")
+}
+
+/// The session's artifact directory, created on first use
+pub fn session_dir(session: &str) -> Result<PathBuf> {
+    storage::ensure_private_dir(&artifacts_dir()?.join(session_dir_name(session)))
+}
+
+/// Files in the session's directory (recursively), newest first
+pub fn list(session: &str) -> Result<Vec<serde_json::Value>> {
+    let dir = artifacts_dir()?.join(session_dir_name(session));
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        collect_files(&dir, &dir, &mut files)?;
+    }
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(files.into_iter().map(|(_, entry)| entry).collect())
+}
+
+fn collect_files(base: &Path, dir: &Path, out: &mut Vec<(SystemTime, serde_json::Value)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_files(base, &entry.path(), out)?;
+            continue;
+        }
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let path = entry.path();
+        out.push((
+            modified,
+            serde_json::json!({
+                "name": path.strip_prefix(base).unwrap_or(&path).display().to_string(),
+                "path": path.display().to_string(),
+                "bytes": metadata.len(),
+                "modified": chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339(),
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Newest modification time of a directory or anything in it
+fn last_activity(dir: &Path) -> SystemTime {
+    let mut newest = fs::metadata(dir).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let modified = if path.is_dir() {
+                last_activity(&path)
+            } else {
+                entry.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH)
+            };
+            newest = newest.max(modified);
+        }
+    }
+    newest
+}
+
+/// Remove session directories under `root` idle for longer than `max_age`
+/// (except those of sessions still connected); returns how many were removed
+pub fn sweep_dir(root: &Path, max_age: Duration, active: &[String]) -> Result<usize> {
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if active.iter().any(|session| session_dir_name(session) == name) {
+            continue;
+        }
+        let idle = now.duration_since(last_activity(&path)).unwrap_or_default();
+        if idle > max_age {
+            fs::remove_dir_all(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+pub fn sweep(max_age: Duration, active: &[String]) -> Result<usize> {
+    sweep_dir(&artifacts_dir()?, max_age, active)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_dir_name() {
+        assert_eq!(session_dir_name("tcp:127.0.0.1:5123"), "tcp_127.0.0.1_5123");
+        assert_eq!(session_dir_name("http:9f1c-22"), "http_9f1c-22");
+        assert_eq!(session_dir_name("../x"), ".._x");
+    }
+
+    #[test]
+    fn test_sweep_removes_idle_sessions() {
+        let root = std::env::temp_dir().join(format!("ab-artifacts-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("tcp_a")).unwrap();
+        fs::create_dir_all(root.join("stdio")).unwrap();
+        fs::write(root.join("tcp_a").join("shot.png"), b"png").unwrap();
+
+        // Nothing is old enough yet
+        assert_eq!(sweep_dir(&root, Duration::from_secs(60), &[]).unwrap(), 0);
+
+        // Everything is idle with a zero retention, but connected sessions are kept
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(sweep_dir(&root, Duration::ZERO, &["stdio".to_string()]).unwrap(), 1);
+        assert!(!root.join("tcp_a").exists());
+        assert!(root.join("stdio").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+/*!
+ * MCP Client Authentication
+ *
+ * A fresh random token is written to `~/.agent-browser/token` (owner-only)
+ * at startup. TCP and WebSocket clients must send `authenticate` with it
+ * before any other method; HTTP clients send `Authorization: Bearer <token>`
+ * on every request. stdio is trusted, since only the parent process can
+ * reach it. `MCP_AUTH=off` disables the check for local development.
+ */
+
+use anyhow::Result;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::path::PathBuf;
+
+use crate::mcp::{self, JsonRpcIncoming, JsonRpcRes};
+use crate::storage;
+
+pub const TOKEN_FILE: &str = "token";
+
+/// Method a connection uses to present the token
+pub const AUTHENTICATE_METHOD: &str = "authenticate";
+
+/// JSON-RPC error code for requests on a connection that hasn't authenticated
+pub const AUTH_REQUIRED_CODE: i64 = -32012;
+
+/// Error message for an `authenticate` carrying the wrong token
+pub const INVALID_TOKEN: &str = "Invalid token";
+
+const TOKEN_BYTES: usize = 32;
+
+pub fn token_path() -> Result<PathBuf> {
+    Ok(storage::data_dir()?.join(TOKEN_FILE))
+}
+
+fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow::anyhow!("Failed to generate auth token"))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Comparison time doesn't depend on where the strings differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub struct ClientAuth {
+    /// None when authentication is disabled
+    token: Option<String>,
+}
+
+impl ClientAuth {
+    /// Generate this run's token, unless `MCP_AUTH=off`
+    pub fn from_env() -> Result<Self> {
+        if std::env::var("MCP_AUTH").is_ok_and(|v| v == "off") {
+            return Ok(Self::disabled());
+        }
+        Ok(Self::with_token(generate_token()?))
+    }
+
+    /// Publish the token for local clients. Called once the MCP listener is
+    /// bound, so a second instance that fails to start can't replace the
+    /// token of the one that is running.
+    pub fn write_token_file(&self) -> Result<()> {
+        match &self.token {
+            Some(token) => storage::write_private_file(&token_path()?, token),
+            None => Ok(()),
+        }
+    }
+
+    pub fn with_token(token: String) -> Self {
+        Self { token: Some(token) }
+    }
+
+    pub fn disabled() -> Self {
+        Self { token: None }
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.token.is_some()
+    }
+
+    pub fn verify(&self, presented: &str) -> bool {
+        match &self.token {
+            Some(token) => constant_time_eq(token.as_bytes(), presented.as_bytes()),
+            None => true,
+        }
+    }
+
+    /// Check an HTTP `Authorization` header value
+    pub fn verify_bearer(&self, header: Option<&str>) -> bool {
+        if !self.is_required() {
+            return true;
+        }
+        header
+            .and_then(|h| h.strip_prefix("Bearer ").or_else(|| h.strip_prefix("bearer ")))
+            .is_some_and(|token| self.verify(token.trim()))
+    }
+
+    /// Answer a message received before the connection authenticated. Returns
+    /// the response to send (None for notifications) and whether the
+    /// connection is now authenticated.
+    pub fn handle_unauthenticated(&self, message: &str) -> (Option<JsonRpcRes>, bool) {
+        let req = match mcp::parse_message(message) {
+            Ok(JsonRpcIncoming::Single(req)) => req,
+            Ok(JsonRpcIncoming::Batch(_)) => return (Some(auth_required(None)), false),
+            Ok(JsonRpcIncoming::Response(_)) => return (None, false),
+            Err(e) => return (Some(JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None)), false),
+        };
+        if req.method != AUTHENTICATE_METHOD {
+            return (req.id.is_some().then(|| auth_required(req.id)), false);
+        }
+
+        let token = req
+            .params
+            .as_ref()
+            .and_then(|p| p.get("token"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if self.verify(token) {
+            (Some(authenticated(req.id)), true)
+        } else {
+            (Some(JsonRpcRes::err(req.id, AUTH_REQUIRED_CODE, INVALID_TOKEN, None)), false)
+        }
+    }
+}
+
+/// Response to a successful (or redundant) `authenticate`
+pub fn authenticated(id: Option<serde_json::Value>) -> JsonRpcRes {
+    JsonRpcRes::ok(id, serde_json::json!({ "authenticated": true }))
+}
+
+fn auth_required(id: Option<serde_json::Value>) -> JsonRpcRes {
+    JsonRpcRes::err(
+        id,
+        AUTH_REQUIRED_CODE,
+        "Authentication required: send 'authenticate' with the token from ~/.agent-browser/token",
+        None,
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(res: &JsonRpcRes) -> Option<i64> {
+        serde_json::to_value(res).ok()?.pointer("/error/code")?.as_i64()
+    }
+
+    #[test]
+    fn test_unauthenticated_connection_must_authenticate_first() {
+        let auth = ClientAuth::with_token("secret".to_string());
+
+        let (res, ok) = auth.handle_unauthenticated(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#);
+        assert!(!ok);
+        assert_eq!(error_code(&res.unwrap()), Some(AUTH_REQUIRED_CODE));
+
+        // Notifications are dropped without a response
+        let (res, ok) = auth.handle_unauthenticated(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+        assert!(res.is_none() && !ok);
+
+        let (res, ok) =
+            auth.handle_unauthenticated(r#"{"jsonrpc":"2.0","id":2,"method":"authenticate","params":{"token":"nope"}}"#);
+        assert!(!ok);
+        assert_eq!(error_code(&res.unwrap()), Some(AUTH_REQUIRED_CODE));
+
+        let (res, ok) =
+            auth.handle_unauthenticated(r#"{"jsonrpc":"2.0","id":3,"method":"authenticate","params":{"token":"secret"}}"#);
+        assert!(ok);
+        assert_eq!(error_code(&res.unwrap()), None);
+    }
+
+    #[test]
+    fn test_verify_bearer() {
+        let auth = ClientAuth::with_token("secret".to_string());
+        assert!(auth.verify_bearer(Some("Bearer secret")));
+        assert!(!auth.verify_bearer(Some("Bearer secre")));
+        assert!(!auth.verify_bearer(Some("secret")));
+        assert!(!auth.verify_bearer(None));
+        assert!(ClientAuth::disabled().verify_bearer(None));
+    }
+
+    #[test]
+    fn test_generated_tokens_are_unique_hex() {
+        let (a, b) = (generate_token().unwrap(), generate_token().unwrap());
+        assert_eq!(a.len(), TOKEN_BYTES * 2);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+}
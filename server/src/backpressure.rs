@@ -0,0 +1,156 @@
+/*!
+ * Extension Command Backpressure
+ *
+ * The extension works through commands one tab at a time, so a burst of
+ * requests used to sit in the command channel until all of them timed out
+ * together. Commands are now admitted only while fewer than the limit are
+ * in flight (sent and not yet answered); beyond that they are rejected at
+ * once with a structured "Server busy" error carrying the queue depth and
+ * a retry hint. Fire-and-forget dashboard messages are shed instead.
+ *
+ * The limit is set with `AGENT_BROWSER_MAX_PENDING_COMMANDS`.
+ */
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub const DEFAULT_MAX_PENDING_COMMANDS: usize = 64;
+
+/// Prefix of the error returned when a command is rejected for load
+pub const SERVER_BUSY_ERROR: &str = "Server busy";
+
+/// JSON-RPC error code for commands rejected for load
+pub const SERVER_BUSY_CODE: i64 = -32011;
+
+/// Suggested delay before a client retries a rejected command
+pub const RETRY_AFTER_MS: u64 = 500;
+
+pub fn max_pending_commands() -> usize {
+    std::env::var("AGENT_BROWSER_MAX_PENDING_COMMANDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_PENDING_COMMANDS)
+}
+
+pub struct CommandQueue {
+    limit: usize,
+    depth: AtomicUsize,
+    high_water: AtomicUsize,
+    admitted: AtomicU64,
+    rejected: AtomicU64,
+    shed: AtomicU64,
+}
+
+/// A slot in the queue, released when the command is answered or abandoned
+pub struct Permit {
+    queue: Arc<CommandQueue>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.queue.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CommandQueue {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            depth: AtomicUsize::new(0),
+            high_water: AtomicUsize::new(0),
+            admitted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    /// Take a slot for a command, or the busy error if every slot is taken
+    pub fn try_admit(self: &Arc<Self>) -> Result<Permit, String> {
+        let admitted = self
+            .depth
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |depth| (depth < self.limit).then_some(depth + 1));
+        match admitted {
+            Ok(previous) => {
+                self.high_water.fetch_max(previous + 1, Ordering::SeqCst);
+                self.admitted.fetch_add(1, Ordering::SeqCst);
+                Ok(Permit {
+                    queue: Arc::clone(self),
+                })
+            }
+            Err(depth) => {
+                self.rejected.fetch_add(1, Ordering::SeqCst);
+                Err(self.busy_message(depth))
+            }
+        }
+    }
+
+    /// A command was dropped because the extension channel was full
+    pub fn record_shed(&self) {
+        self.shed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn busy_message(&self, depth: usize) -> String {
+        format!(
+            "{}: {} of {} extension commands in flight, retry after {} ms",
+            SERVER_BUSY_ERROR, depth, self.limit, RETRY_AFTER_MS
+        )
+    }
+
+    /// Error `data` attached to busy rejections
+    pub fn busy_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "queueDepth": self.depth(),
+            "limit": self.limit,
+            "retryAfterMs": RETRY_AFTER_MS,
+        })
+    }
+
+    pub fn metrics(&self) -> serde_json::Value {
+        serde_json::json!({
+            "depth": self.depth(),
+            "limit": self.limit,
+            "highWater": self.high_water.load(Ordering::SeqCst),
+            "admitted": self.admitted.load(Ordering::SeqCst),
+            "rejected": self.rejected.load(Ordering::SeqCst),
+            "shed": self.shed.load(Ordering::SeqCst),
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admission_is_bounded_and_released_on_drop() {
+        let queue = Arc::new(CommandQueue::new(2));
+        let first = queue.try_admit().unwrap();
+        let _second = queue.try_admit().unwrap();
+
+        let busy = queue.try_admit().err().unwrap();
+        assert!(busy.starts_with(SERVER_BUSY_ERROR));
+        assert_eq!(queue.busy_data()["queueDepth"], 2);
+
+        drop(first);
+        assert_eq!(queue.depth(), 1);
+        assert!(queue.try_admit().is_ok());
+
+        let metrics = queue.metrics();
+        assert_eq!(metrics["admitted"], 3);
+        assert_eq!(metrics["rejected"], 1);
+        assert_eq!(metrics["highWater"], 2);
+    }
+}
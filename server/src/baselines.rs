@@ -0,0 +1,311 @@
+/*!
+ * Visual Regression Baselines
+ *
+ * Named reference screenshots that `screenshot_diff` compares the current
+ * page against. A baseline name holds one image per viewport size, so the
+ * same suite can run at desktop and mobile widths:
+ *
+ * Layout: ~/.agent-browser/baselines/<name>/
+ *   1280x720.png, 1280x720.json, 390x844.png, 390x844.json, ...
+ *
+ * Identical captures are detected by hash on the server; anything else is
+ * decoded and compared pixel by pixel in the extension (the server carries
+ * no image codecs). Diff images and the failing capture are written to the
+ * session's artifact directory under `diffs/`.
+ */
+
+use anyhow::{anyhow, Result};
+use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::{artifacts, storage};
+
+/// Per-channel difference (0-255) below which two pixels count as equal;
+/// absorbs anti-aliasing and color-profile noise
+pub const DEFAULT_TOLERANCE: u64 = 16;
+/// Share of differing pixels above which a comparison fails
+pub const DEFAULT_MAX_DIFF_RATIO: f64 = 0.001;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Parse "1280x720"
+    pub fn parse(value: &str) -> Option<Self> {
+        let (width, height) = value.split_once('x')?;
+        Some(Self {
+            width: width.parse().ok().filter(|w| *w > 0)?,
+            height: height.parse().ok().filter(|h| *h > 0)?,
+        })
+    }
+
+    /// The viewport the extension reported alongside a screenshot
+    pub fn from_result(result: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(result.get("viewport")?.clone()).ok()
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineMetadata {
+    pub name: String,
+    pub viewport: Viewport,
+    pub url: Option<String>,
+    pub created: i64,
+    pub session: Option<String>,
+    /// Hex SHA-256 of the PNG, for the identical-capture fast path
+    pub sha256: String,
+    pub bytes: usize,
+}
+
+// ============================================================================
+// Baseline Store
+// ============================================================================
+
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl BaselineStore {
+    pub fn new() -> Result<Self> {
+        let dir = storage::data_subdir("baselines")?;
+        info!("Baseline store initialized at {:?}", dir);
+        Ok(Self { dir })
+    }
+
+    fn baseline_dir(&self, name: &str) -> Result<PathBuf> {
+        storage::validate_name("Baseline", name)?;
+        Ok(self.dir.join(name))
+    }
+
+    /// Store a base64 PNG as the baseline for `name` at `viewport`
+    pub fn set(
+        &self,
+        name: &str,
+        viewport: Viewport,
+        url: Option<String>,
+        png_b64: &str,
+        session: Option<&str>,
+        overwrite: bool,
+    ) -> Result<BaselineMetadata> {
+        let dir = self.baseline_dir(name)?;
+        let png = BASE64.decode(png_b64).map_err(|e| anyhow!("Invalid screenshot data: {}", e))?;
+        let label = viewport.label();
+        let meta_path = dir.join(format!("{}.json", label));
+        if meta_path.exists() && !overwrite {
+            return Err(anyhow!(
+                "Baseline '{}' already has a {} image (pass overwrite: true to replace it)",
+                name,
+                label
+            ));
+        }
+        storage::ensure_private_dir(&dir)?;
+
+        let meta = BaselineMetadata {
+            name: name.to_string(),
+            viewport,
+            url,
+            created: chrono::Utc::now().timestamp(),
+            session: session.map(str::to_string),
+            sha256: sha256_hex(&png),
+            bytes: png.len(),
+        };
+        storage::write_private_file(&dir.join(format!("{}.png", label)), &png)?;
+        storage::write_private_file(&meta_path, serde_json::to_string_pretty(&meta)?)?;
+
+        info!("Saved baseline '{}' at {}", name, label);
+        Ok(meta)
+    }
+
+    /// Load a baseline's metadata and PNG bytes
+    pub fn get(&self, name: &str, viewport: Viewport) -> Result<(BaselineMetadata, Vec<u8>)> {
+        let dir = self.baseline_dir(name)?;
+        let label = viewport.label();
+        let Ok(data) = fs::read_to_string(dir.join(format!("{}.json", label))) else {
+            let available: Vec<String> = self.viewports(&dir).iter().map(Viewport::label).collect();
+            return Err(if available.is_empty() {
+                anyhow!("Baseline '{}' not found", name)
+            } else {
+                anyhow!(
+                    "Baseline '{}' has no {} image (available: {})",
+                    name,
+                    label,
+                    available.join(", ")
+                )
+            });
+        };
+        let meta: BaselineMetadata = serde_json::from_str(&data)?;
+        let png = fs::read(dir.join(format!("{}.png", label)))?;
+        Ok((meta, png))
+    }
+
+    fn viewports(&self, dir: &Path) -> Vec<Viewport> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut viewports: Vec<Viewport> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                Viewport::parse(file_name.strip_suffix(".json")?)
+            })
+            .collect();
+        viewports.sort_by_key(|v| (v.width, v.height));
+        viewports
+    }
+
+    /// Metadata for every stored baseline image, optionally limited to one
+    /// name or to baselines captured on URLs containing `url`
+    pub fn list(&self, name: Option<&str>, url: Option<&str>) -> Result<Vec<BaselineMetadata>> {
+        let dirs = match name {
+            Some(name) => vec![self.baseline_dir(name)?],
+            None => fs::read_dir(&self.dir)?.flatten().map(|entry| entry.path()).collect(),
+        };
+        let mut baselines = Vec::new();
+        for dir in dirs {
+            for viewport in self.viewports(&dir) {
+                let Ok(data) = fs::read_to_string(dir.join(format!("{}.json", viewport.label()))) else {
+                    continue;
+                };
+                let Ok(meta) = serde_json::from_str::<BaselineMetadata>(&data) else {
+                    continue;
+                };
+                if url.is_none_or(|url| meta.url.as_deref().is_some_and(|u| u.contains(url))) {
+                    baselines.push(meta);
+                }
+            }
+        }
+        baselines.sort_by(|a, b| a.name.cmp(&b.name).then(a.viewport.width.cmp(&b.viewport.width)));
+        Ok(baselines)
+    }
+
+    /// Remove one viewport's image, or the whole baseline; returns the
+    /// viewports removed
+    pub fn delete(&self, name: &str, viewport: Option<Viewport>) -> Result<Vec<Viewport>> {
+        let dir = self.baseline_dir(name)?;
+        let removed = match viewport {
+            Some(viewport) => {
+                let meta_path = dir.join(format!("{}.json", viewport.label()));
+                if !meta_path.exists() {
+                    return Ok(Vec::new());
+                }
+                fs::remove_file(meta_path)?;
+                let _ = fs::remove_file(dir.join(format!("{}.png", viewport.label())));
+                if self.viewports(&dir).is_empty() {
+                    fs::remove_dir_all(&dir)?;
+                }
+                vec![viewport]
+            }
+            None => {
+                let viewports = self.viewports(&dir);
+                if dir.exists() {
+                    fs::remove_dir_all(&dir)?;
+                }
+                viewports
+            }
+        };
+        if !removed.is_empty() {
+            info!("Deleted baseline '{}' ({} images)", name, removed.len());
+        }
+        Ok(removed)
+    }
+}
+
+// ============================================================================
+// Diff Artifacts
+// ============================================================================
+
+/// Write a failed comparison's capture and diff image to
+/// `diffs/<time>-<name>-<viewport>/` in the session's artifact directory
+pub fn write_diff(
+    session: &str,
+    name: &str,
+    viewport: Viewport,
+    actual_png: &[u8],
+    diff_png: Option<&[u8]>,
+) -> Result<PathBuf> {
+    let dir = artifacts::session_dir(session)?.join("diffs").join(format!(
+        "{}-{}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+        name,
+        viewport.label()
+    ));
+    storage::ensure_private_dir(&dir)?;
+    storage::write_private_file(&dir.join("actual.png"), actual_png)?;
+    if let Some(diff) = diff_png {
+        storage::write_private_file(&dir.join("diff.png"), diff)?;
+    }
+    Ok(dir)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> BaselineStore {
+        let dir = std::env::temp_dir().join(format!("agent-browser-baselines-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        BaselineStore { dir }
+    }
+
+    #[test]
+    fn test_viewport_parse() {
+        assert_eq!(Viewport::parse("1280x720"), Some(Viewport { width: 1280, height: 720 }));
+        assert_eq!(Viewport::parse("0x720"), None);
+        assert_eq!(Viewport::parse("1280"), None);
+        assert_eq!(Viewport { width: 390, height: 844 }.label(), "390x844");
+    }
+
+    #[test]
+    fn test_set_list_delete_per_viewport() {
+        let store = temp_store();
+        let desktop = Viewport { width: 1280, height: 720 };
+        let mobile = Viewport { width: 390, height: 844 };
+        let png = BASE64.encode(b"not really a png");
+
+        let meta = store.set("home", desktop, Some("https://example.com/".into()), &png, None, false).unwrap();
+        assert_eq!(meta.sha256, sha256_hex(b"not really a png"));
+        store.set("home", mobile, Some("https://example.com/".into()), &png, None, false).unwrap();
+        assert!(store.set("home", mobile, None, &png, None, false).is_err());
+
+        assert_eq!(store.list(None, None).unwrap().len(), 2);
+        assert_eq!(store.list(None, Some("example.com")).unwrap().len(), 2);
+        assert!(store.list(None, Some("other.org")).unwrap().is_empty());
+
+        let (_, bytes) = store.get("home", mobile).unwrap();
+        assert_eq!(bytes, b"not really a png");
+        let missing = store.get("home", Viewport { width: 800, height: 600 }).unwrap_err();
+        assert!(missing.to_string().contains("390x844, 1280x720"));
+
+        assert_eq!(store.delete("home", Some(mobile)).unwrap(), vec![mobile]);
+        assert_eq!(store.list(Some("home"), None).unwrap().len(), 1);
+        assert_eq!(store.delete("home", None).unwrap(), vec![desktop]);
+        assert!(store.list(None, None).unwrap().is_empty());
+        assert!(store.delete("../etc", None).is_err());
+    }
+}
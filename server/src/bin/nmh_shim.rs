@@ -1,10 +1,21 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr};
 use std::process::{Command, Stdio};
+use std::sync::mpsc;
 use std::time::Duration;
 use std::thread;
 
+/// Where the server publishes the ports it bound (see config.rs)
+const PORTS_FILE: &str = ".agent-browser/ports.json";
+
+const DEFAULT_TCP_PORT: u16 = 8084;
+const DEFAULT_WS_ADDR: &str = "127.0.0.1:8085";
+
+/// How long to wait for a spawned server to report its ports
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Deserialize)]
 struct NmhRequest {
     #[allow(dead_code)]
@@ -22,6 +33,31 @@ struct NmhResponse {
     host: String,
     port: u16,
     scheme: String,
+    #[serde(rename = "wsPort")]
+    ws_port: u16,
+    #[serde(rename = "httpPort", skip_serializing_if = "Option::is_none")]
+    http_port: Option<u16>,
+}
+
+/// Bound addresses as reported by the server
+#[derive(Debug, Deserialize)]
+struct Ports {
+    tcp: Endpoint,
+    ws: Endpoint,
+    http: Option<Endpoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+impl Endpoint {
+    fn addr(&self) -> Option<SocketAddr> {
+        let ip: IpAddr = self.host.parse().ok()?;
+        Some(SocketAddr::new(ip, self.port))
+    }
 }
 
 fn write_native_message<T: Serialize>(value: &T) -> Result<()> {
@@ -45,12 +81,20 @@ fn read_native_message() -> Result<serde_json::Value> {
     Ok(serde_json::from_slice(&buf)?)
 }
 
-fn is_server_running() -> bool {
+fn read_ports_file() -> Option<Ports> {
+    let path = dirs::home_dir()?.join(PORTS_FILE);
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn is_server_running(ports: Option<&Ports>) -> bool {
     // Check if WebSocket port is accessible (server is running)
-    std::net::TcpStream::connect("127.0.0.1:8085").is_ok()
+    let addr = ports
+        .and_then(|p| p.ws.addr())
+        .unwrap_or_else(|| DEFAULT_WS_ADDR.parse().unwrap());
+    std::net::TcpStream::connect(addr).is_ok()
 }
 
-fn spawn_server() -> Result<String> {
+fn spawn_server() -> Result<(Ports, String)> {
     // Find the agent-browser-server binary in the same directory
     let server_path = std::env::current_exe()
         .ok()
@@ -59,29 +103,44 @@ fn spawn_server() -> Result<String> {
 
     let mut logs = format!("Starting server: {:?}\n", server_path);
 
-    // Spawn the server detached with MCP_TCP enabled
-    let child = Command::new(&server_path)
+    // Spawn the server detached with MCP_TCP enabled; it prints the bound
+    // ports as one JSON line once every listener is up
+    let mut child = Command::new(&server_path)
+        .arg("--print-ports")
         .env("RUST_LOG", "info")
         .env("MCP_TCP", "1")
         .stdin(Stdio::null())
-        .stdout(Stdio::null())
+        .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .spawn()
         .context("Failed to spawn agent-browser-server")?;
 
     logs.push_str(&format!("Server started with PID: {}\n", child.id()));
 
-    // Give it time to start
-    thread::sleep(Duration::from_millis(1000));
-
-    // Verify it started
-    if is_server_running() {
-        logs.push_str("Server is now running\n");
-    } else {
-        logs.push_str("Warning: Server may not have started properly\n");
-    }
+    let stdout = child.stdout.take().context("Server stdout not captured")?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        let _ = BufReader::new(stdout).read_line(&mut line);
+        let _ = tx.send(line);
+    });
+
+    let line = match rx.recv_timeout(STARTUP_TIMEOUT) {
+        Ok(line) if !line.trim().is_empty() => line,
+        // EOF: the server exited before binding (e.g. a port is taken)
+        Ok(_) => match child.try_wait() {
+            Ok(Some(status)) => anyhow::bail!("Server exited during startup ({})", status),
+            _ => anyhow::bail!("Server closed stdout without reporting its ports"),
+        },
+        Err(_) => anyhow::bail!("Server did not report its ports within {:?}", STARTUP_TIMEOUT),
+    };
+    let ports: Ports = serde_json::from_str(line.trim()).context("Invalid port report from server")?;
+    logs.push_str(&format!(
+        "Server is now running (MCP TCP {}, WebSocket {})\n",
+        ports.tcp.port, ports.ws.port
+    ));
 
-    Ok(logs)
+    Ok((ports, logs))
 }
 
 fn main() -> Result<()> {
@@ -96,27 +155,35 @@ fn main() -> Result<()> {
     let mut logs = String::new();
     let mut error = None;
 
-    if is_server_running() {
+    let mut ports = read_ports_file();
+    if is_server_running(ports.as_ref()) {
         logs.push_str("Server already running\n");
     } else {
         logs.push_str("Server not running, starting it...\n");
         match spawn_server() {
-            Ok(spawn_logs) => logs.push_str(&spawn_logs),
+            Ok((spawned, spawn_logs)) => {
+                ports = Some(spawned);
+                logs.push_str(&spawn_logs);
+            }
             Err(e) => {
+                ports = None;
                 error = Some(format!("Failed to start server: {}", e));
                 logs.push_str(&format!("Error: {}\n", e));
             }
         }
     }
 
-    // Send response (using localhost:8084 for MCP, server also listens on 8085 for WebSocket)
+    // Report where MCP clients (TCP) and the extension (WebSocket) should connect
+    let ws_default: SocketAddr = DEFAULT_WS_ADDR.parse()?;
     let response = NmhResponse {
         ok: error.is_none(),
         error,
         logs: if logs.is_empty() { None } else { Some(logs) },
         host: "localhost".into(),
-        port: 8084, // MCP TCP port
+        port: ports.as_ref().map_or(DEFAULT_TCP_PORT, |p| p.tcp.port),
         scheme: "http".into(),
+        ws_port: ports.as_ref().map_or(ws_default.port(), |p| p.ws.port),
+        http_port: ports.as_ref().and_then(|p| p.http.as_ref()).map(|h| h.port),
     };
 
     write_native_message(&response)?;
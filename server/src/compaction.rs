@@ -0,0 +1,406 @@
+/*!
+ * Audit Log Compaction
+ *
+ * `audit.log` and its rotated `audit-<timestamp>.log` segments keep one line
+ * per authorization or credential event, which adds up over months of agent
+ * activity. Lines older than the retention window
+ * (`AGENT_BROWSER_AUDIT_RETENTION_DAYS`, default 30) are folded into daily
+ * summaries in `audit-summary.json`: event counts per kind and per RP, and
+ * the number of distinct sessions. The folded lines are then removed from
+ * the logs, and segments left empty are deleted. Compaction runs with the
+ * hourly sweep. `history_stats` reads the summaries plus the recent
+ * detailed lines, so its cost depends on the retention window rather than
+ * on the total history.
+ */
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::quota::AUDIT_LOG_NAME;
+
+pub const DEFAULT_RETENTION_DAYS: u64 = 30;
+pub const SUMMARY_FILE: &str = "audit-summary.json";
+
+pub fn retention_days() -> u64 {
+    std::env::var("AGENT_BROWSER_AUDIT_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// First day whose events are still kept line by line
+pub fn cutoff(now: DateTime<Utc>, retention_days: u64) -> NaiveDate {
+    now.date_naive() - chrono::Days::new(retention_days)
+}
+
+// ============================================================================
+// Event Classification
+// ============================================================================
+
+/// Split "[2026-01-02T03:04:05+00:00] message" into its day and message
+fn parse_line(line: &str) -> Option<(NaiveDate, &str)> {
+    let (timestamp, message) = line.strip_prefix('[')?.split_once("] ")?;
+    let day = DateTime::parse_from_rfc3339(timestamp).ok()?.with_timezone(&Utc).date_naive();
+    Some((day, message))
+}
+
+/// Coarse event kind of an audit message, as written by the credential store
+fn kind(message: &str) -> &'static str {
+    if message.starts_with("Credential grant") || message.contains("issued credential grant") {
+        "grant"
+    } else if message.starts_with("Credential ") && message.contains(" used for ") {
+        "credential_used"
+    } else if message.starts_with("Stored credential") {
+        "credential_stored"
+    } else if message.starts_with("Cleared ") {
+        "credentials_cleared"
+    } else if message.contains("authorization revoked") {
+        "authorization_revoked"
+    } else if message.starts_with("Session ") && message.contains(", injected by session ") {
+        "tool_injection"
+    } else if message.starts_with("Session ") && message.contains(" retried ") {
+        "tool_retry"
+    } else if message.starts_with("Session ") && message.contains(" authorized for ") {
+        "authorization"
+    } else if message.starts_with("Elevated recovery") {
+        "recovery_authorization"
+    } else if message.contains("recovery secret") {
+        "recovery_secret"
+    } else {
+        "other"
+    }
+}
+
+fn word_after<'a>(message: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = &message[message.find(marker)? + marker.len()..];
+    let word = rest.split(|c: char| c.is_whitespace() || c == ')').next()?;
+    (!word.is_empty()).then_some(word)
+}
+
+fn rp_id(message: &str) -> Option<&str> {
+    word_after(message, "rpId: ")
+}
+
+fn session(message: &str) -> Option<&str> {
+    word_after(message, "(session: ")
+        .or_else(|| word_after(message, "Session "))
+        .or_else(|| word_after(message, "session "))
+        .filter(|session| *session != "unknown")
+}
+
+// ============================================================================
+// Summaries
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaySummary {
+    pub events: u64,
+    pub kinds: BTreeMap<String, u64>,
+    pub rp_ids: BTreeMap<String, u64>,
+    /// Distinct sessions seen that day
+    pub sessions: u64,
+}
+
+impl DaySummary {
+    fn merge(&mut self, other: &DaySummary) {
+        self.events += other.events;
+        for (kind, count) in &other.kinds {
+            *self.kinds.entry(kind.clone()).or_default() += count;
+        }
+        for (rp_id, count) in &other.rp_ids {
+            *self.rp_ids.entry(rp_id.clone()).or_default() += count;
+        }
+        self.sessions += other.sessions;
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summaries {
+    /// By day (YYYY-MM-DD)
+    pub days: BTreeMap<String, DaySummary>,
+    pub last_compacted: Option<String>,
+}
+
+impl Summaries {
+    fn load(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(SUMMARY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self, data_dir: &Path) -> Result<()> {
+        let tmp = data_dir.join(format!("{}.tmp", SUMMARY_FILE));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, data_dir.join(SUMMARY_FILE))?;
+        Ok(())
+    }
+}
+
+/// Per-day summaries of audit lines, counting distinct sessions within the batch
+#[derive(Default)]
+struct Tally {
+    days: BTreeMap<NaiveDate, DaySummary>,
+    sessions: HashMap<NaiveDate, HashSet<String>>,
+}
+
+impl Tally {
+    fn add(&mut self, day: NaiveDate, message: &str) {
+        let summary = self.days.entry(day).or_default();
+        summary.events += 1;
+        *summary.kinds.entry(kind(message).to_string()).or_default() += 1;
+        if let Some(rp_id) = rp_id(message) {
+            *summary.rp_ids.entry(rp_id.to_string()).or_default() += 1;
+        }
+        if let Some(session) = session(message) {
+            self.sessions.entry(day).or_default().insert(session.to_string());
+        }
+    }
+
+    fn finish(mut self) -> BTreeMap<String, DaySummary> {
+        for (day, sessions) in self.sessions {
+            if let Some(summary) = self.days.get_mut(&day) {
+                summary.sessions = sessions.len() as u64;
+            }
+        }
+        self.days.into_iter().map(|(day, summary)| (day.to_string(), summary)).collect()
+    }
+}
+
+/// Rotated segments (oldest first) followed by the live log
+fn audit_files(data_dir: &Path) -> Vec<PathBuf> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(data_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|path| {
+                    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    name.starts_with("audit-") && name.ends_with(".log")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    segments.sort();
+    let live = data_dir.join(AUDIT_LOG_NAME);
+    if live.exists() {
+        segments.push(live);
+    }
+    segments
+}
+
+// ============================================================================
+// Compaction
+// ============================================================================
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub events: u64,
+    pub days: usize,
+    pub files_removed: usize,
+}
+
+/// Fold audit lines dated before `cutoff` into the daily summaries. Callers
+/// must keep the credential store from appending while this runs.
+pub fn compact(data_dir: &Path, cutoff: NaiveDate) -> Result<CompactionReport> {
+    let mut tally = Tally::default();
+    // (file, lines to keep) for every file holding something to fold
+    let mut rewrites: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for path in audit_files(data_dir) {
+        let content = fs::read_to_string(&path)?;
+        let mut keep = Vec::new();
+        let mut folded = false;
+        for line in content.lines() {
+            match parse_line(line) {
+                Some((day, message)) if day < cutoff => {
+                    tally.add(day, message);
+                    folded = true;
+                }
+                _ => keep.push(line.to_string()),
+            }
+        }
+        if folded {
+            rewrites.push((path, keep));
+        }
+    }
+
+    let mut report = CompactionReport::default();
+    if rewrites.is_empty() {
+        return Ok(report);
+    }
+
+    // Summaries are saved first: a crash before the rewrite double counts rather than loses events
+    let mut summaries = Summaries::load(data_dir)?;
+    for (day, summary) in tally.finish() {
+        report.events += summary.events;
+        report.days += 1;
+        summaries.days.entry(day).or_default().merge(&summary);
+    }
+    summaries.last_compacted = Some(Utc::now().to_rfc3339());
+    summaries.save(data_dir)?;
+
+    for (path, keep) in rewrites {
+        let is_live = path.file_name().is_some_and(|n| n == AUDIT_LOG_NAME);
+        if keep.is_empty() && !is_live {
+            fs::remove_file(&path)?;
+            report.files_removed += 1;
+            continue;
+        }
+        let tmp = path.with_extension("log.tmp");
+        let mut content = keep.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        fs::write(&tmp, content)?;
+        fs::rename(tmp, &path)?;
+    }
+    Ok(report)
+}
+
+/// Activity per day for the last `days` days: summaries for compacted days,
+/// counted from the detailed lines for the rest
+pub fn stats(data_dir: &Path, days: usize) -> Result<serde_json::Value> {
+    let summaries = Summaries::load(data_dir)?;
+
+    let mut tally = Tally::default();
+    let (mut detailed_bytes, mut detailed_files) = (0, 0);
+    for path in audit_files(data_dir) {
+        let content = fs::read_to_string(&path)?;
+        detailed_bytes += content.len() as u64;
+        detailed_files += 1;
+        for (day, message) in content.lines().filter_map(parse_line) {
+            tally.add(day, message);
+        }
+    }
+    let detailed = tally.finish();
+
+    let mut by_kind: BTreeMap<String, u64> = BTreeMap::new();
+    let mut merged: BTreeMap<String, (DaySummary, bool)> = BTreeMap::new();
+    for (day, summary, compacted) in summaries
+        .days
+        .iter()
+        .map(|(day, s)| (day, s, true))
+        .chain(detailed.iter().map(|(day, s)| (day, s, false)))
+    {
+        for (kind, count) in &summary.kinds {
+            *by_kind.entry(kind.clone()).or_default() += count;
+        }
+        let entry = merged.entry(day.clone()).or_default();
+        entry.0.merge(summary);
+        entry.1 |= compacted;
+    }
+
+    let daily: Vec<serde_json::Value> = merged
+        .iter()
+        .rev()
+        .take(days)
+        .map(|(day, (summary, compacted))| {
+            let mut value = serde_json::to_value(summary).unwrap_or_default();
+            value["date"] = serde_json::json!(day);
+            value["compacted"] = serde_json::json!(compacted);
+            value
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "retentionDays": retention_days(),
+        "lastCompacted": summaries.last_compacted,
+        "oldestDay": merged.keys().next(),
+        "newestDay": merged.keys().next_back(),
+        "compacted": {
+            "days": summaries.days.len(),
+            "events": summaries.days.values().map(|s| s.events).sum::<u64>(),
+        },
+        "detailed": {
+            "days": detailed.len(),
+            "events": detailed.values().map(|s| s.events).sum::<u64>(),
+            "files": detailed_files,
+            "bytes": detailed_bytes,
+        },
+        "byKind": by_kind,
+        "daily": daily,
+    }))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_audit_messages() {
+        let used = "Credential abc used for get on rpId: github.com (session: tcp:127.0.0.1:5000)";
+        assert_eq!(kind(used), "credential_used");
+        assert_eq!(rp_id(used), Some("github.com"));
+        assert_eq!(session(used), Some("tcp:127.0.0.1:5000"));
+
+        let authorized = "Session stdio authorized for 8 hours";
+        assert_eq!(kind(authorized), "authorization");
+        assert_eq!(session(authorized), Some("stdio"));
+        assert_eq!(kind("Session stdio closed; authorization revoked"), "authorization_revoked");
+        assert_eq!(kind("Session stdio ran playwright_click as call c9, injected by session tcp:127.0.0.1:5000"), "tool_injection");
+        assert_eq!(kind("Session stdio retried playwright_click call c2 as c5 (first call c2, requested by session stdio)"), "tool_retry");
+        assert_eq!(kind("Credential grant g1 for rpId: a.com redeemed by session http:1"), "grant");
+        assert_eq!(kind("Released recovery secret r1 (BackupCode) for rpId: a.com"), "recovery_secret");
+        assert_eq!(session("Credential x used for get on rpId: a.com (session: unknown)"), None);
+
+        let (day, message) = parse_line("[2026-03-04T05:06:07.123+00:00] Cleared 2 credentials").unwrap();
+        assert_eq!(day.to_string(), "2026-03-04");
+        assert_eq!(message, "Cleared 2 credentials");
+        assert!(parse_line("garbage").is_none());
+    }
+
+    #[test]
+    fn test_compaction_folds_old_lines() {
+        let dir = std::env::temp_dir().join(format!("ab-compaction-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let used = |day: &str, session: &str| {
+            format!("[{}T10:00:00+00:00] Credential c used for get on rpId: a.com (session: {})\n", day, session)
+        };
+        fs::write(
+            dir.join("audit-20260101T000000.000.log"),
+            used("2026-01-01", "s1") + &used("2026-01-01", "s2") + &used("2026-01-01", "s1"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join(AUDIT_LOG_NAME),
+            used("2026-01-02", "s1") + "[2026-02-01T10:00:00+00:00] Session s3 authorized for 8 hours\n",
+        )
+        .unwrap();
+
+        let cutoff = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let report = compact(&dir, cutoff).unwrap();
+        assert_eq!(report, CompactionReport { events: 4, days: 2, files_removed: 1 });
+        assert!(!dir.join("audit-20260101T000000.000.log").exists());
+        assert_eq!(fs::read_to_string(dir.join(AUDIT_LOG_NAME)).unwrap().lines().count(), 1);
+
+        let summaries = Summaries::load(&dir).unwrap();
+        let first = &summaries.days["2026-01-01"];
+        assert_eq!((first.events, first.sessions, first.rp_ids["a.com"]), (3, 2, 3));
+
+        // Nothing left to fold
+        assert_eq!(compact(&dir, cutoff).unwrap().events, 0);
+
+        let stats = stats(&dir, 10).unwrap();
+        assert_eq!(stats["compacted"]["events"], 4);
+        assert_eq!(stats["detailed"]["events"], 1);
+        assert_eq!(stats["byKind"]["credential_used"], 4);
+        assert_eq!(stats["daily"][0]["date"], "2026-02-01");
+        assert_eq!(stats["daily"][0]["compacted"], false);
+        assert_eq!(stats["daily"].as_array().unwrap().len(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
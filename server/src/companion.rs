@@ -0,0 +1,447 @@
+/*!
+ * Headless Companion Browser
+ *
+ * Background work (page watches) otherwise opens tabs in the user's own
+ * browser through the extension. With `AGENT_BROWSER_COMPANION_POOL=n` the
+ * server instead starts a headless Chrome of its own on first use and
+ * drives it over the Chrome DevTools Protocol:
+ *
+ * - Every job gets a fresh browser context (its own cookies and storage),
+ *   disposed when the job finishes
+ * - At most n jobs run at once; further jobs wait for a free slot
+ * - The browser uses a dedicated profile in ~/.agent-browser/companion/,
+ *   is restarted if it dies, and exits with the server
+ *
+ * The Chrome binary comes from `AGENT_BROWSER_CHROME`, else the first of
+ * the usual install locations that exists.
+ */
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
+use tokio::time::{timeout, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info};
+
+use crate::storage;
+
+/// Profile directory of the companion browser, under the data directory
+pub const PROFILE_DIR: &str = "companion";
+
+const LAUNCH_TIMEOUT_SECS: u64 = 15;
+const CDP_TIMEOUT_SECS: u64 = 30;
+const LOAD_POLL_MS: u64 = 250;
+
+const CHROME_CANDIDATES: &[&str] = &[
+    "google-chrome",
+    "google-chrome-stable",
+    "chromium",
+    "chromium-browser",
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+];
+
+/// Concurrent companion jobs allowed; 0 (the default) leaves background work to the extension
+pub fn pool_size() -> usize {
+    std::env::var("AGENT_BROWSER_COMPANION_POOL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Chrome binary to launch headless
+pub fn chrome_binary() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("AGENT_BROWSER_CHROME") {
+        return Some(PathBuf::from(path));
+    }
+    CHROME_CANDIDATES.iter().find_map(|candidate| {
+        let path = Path::new(candidate);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+        std::env::split_paths(&std::env::var_os("PATH")?)
+            .map(|dir| dir.join(candidate))
+            .find(|p| p.is_file())
+    })
+}
+
+/// Browser WebSocket URL from Chrome's `DevToolsActivePort` file (port, then path)
+fn devtools_url(contents: &str) -> Option<String> {
+    let mut lines = contents.lines();
+    let port: u16 = lines.next()?.trim().parse().ok()?;
+    let path = lines.next()?.trim();
+    path.starts_with('/').then(|| format!("ws://127.0.0.1:{}{}", port, path))
+}
+
+/// Expression returning `{value, url, title}`, the same shape as the
+/// extension's `watch_extract` answer
+pub fn extract_expression(selector: Option<&str>, attribute: Option<&str>) -> String {
+    let json = |v: Option<&str>| serde_json::to_string(&v).unwrap_or_else(|_| "null".to_string());
+    format!(
+        "(() => {{ \
+            const selector = {}, attribute = {}; \
+            const element = selector ? document.querySelector(selector) : document.body; \
+            const value = element ? (attribute ? element.getAttribute(attribute) : element.innerText) : null; \
+            return {{ value, url: location.href, title: document.title }}; \
+        }})()",
+        json(selector),
+        json(attribute)
+    )
+}
+
+// ============================================================================
+// CDP Connection
+// ============================================================================
+
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// Browser-level CDP connection; page commands are multiplexed onto it with
+/// flattened session ids
+struct Cdp {
+    tx: mpsc::Sender<String>,
+    pending: Pending,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+}
+
+impl Cdp {
+    async fn connect(url: &str) -> Result<Self> {
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .with_context(|| format!("Failed to connect to {}", url))?;
+        let (mut sink, mut stream) = ws.split();
+
+        let (tx, mut rx) = mpsc::channel::<String>(32);
+        tokio::spawn(async move {
+            while let Some(text) = rx.recv().await {
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let pending: Pending = Arc::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        let (reader_pending, reader_closed) = (Arc::clone(&pending), Arc::clone(&closed));
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                if let Message::Text(text) = message {
+                    route_reply(&reader_pending, &text);
+                }
+            }
+            reader_closed.store(true, Ordering::SeqCst);
+            // Dropping the senders fails every waiting command
+            reader_pending.lock().unwrap().clear();
+        });
+
+        Ok(Self {
+            tx,
+            pending,
+            next_id: AtomicU64::new(0),
+            closed,
+        })
+    }
+
+    async fn send(&self, session: Option<&str>, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut message = serde_json::json!({ "id": id, "method": method, "params": params });
+        if let Some(session) = session {
+            message["sessionId"] = serde_json::json!(session);
+        }
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, reply_tx);
+        if self.tx.send(message.to_string()).await.is_err() {
+            self.pending.lock().unwrap().remove(&id);
+            return Err("Companion browser connection closed".to_string());
+        }
+
+        match timeout(Duration::from_secs(CDP_TIMEOUT_SECS), reply_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("Companion browser connection closed".to_string()),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(format!("{} timed out after {}s", method, CDP_TIMEOUT_SECS))
+            }
+        }
+    }
+}
+
+/// Hand a CDP reply to the command waiting for it; events carry no id and are ignored
+fn route_reply(pending: &Pending, text: &str) -> bool {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return false;
+    };
+    let Some(id) = message.get("id").and_then(|v| v.as_u64()) else {
+        return false;
+    };
+    let Some(reply) = pending.lock().unwrap().remove(&id) else {
+        return false;
+    };
+    let result = match message.get("error") {
+        Some(error) => Err(error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("CDP error")
+            .to_string()),
+        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+    };
+    let _ = reply.send(result);
+    true
+}
+
+fn string_field<'a>(value: &'a Value, key: &str) -> Result<&'a str, String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("CDP reply without {}", key))
+}
+
+// ============================================================================
+// Pool
+// ============================================================================
+
+struct Browser {
+    cdp: Cdp,
+    // Killed when the last job holding the browser lets go of it
+    _child: Child,
+}
+
+pub struct CompanionPool {
+    size: usize,
+    slots: Semaphore,
+    browser: Mutex<Option<Arc<Browser>>>,
+    launches: AtomicU64,
+    jobs: AtomicU64,
+}
+
+impl CompanionPool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            slots: Semaphore::new(size),
+            browser: Mutex::new(None),
+            launches: AtomicU64::new(0),
+            jobs: AtomicU64::new(0),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub async fn metrics(&self) -> Value {
+        let running = self
+            .browser
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|b| !b.cdp.closed.load(Ordering::SeqCst));
+        serde_json::json!({
+            "size": self.size,
+            "inUse": self.size - self.slots.available_permits(),
+            "running": running,
+            "launches": self.launches.load(Ordering::SeqCst),
+            "jobs": self.jobs.load(Ordering::SeqCst),
+        })
+    }
+
+    /// The running browser, started (or restarted after a crash) on demand
+    async fn browser(&self) -> Result<Arc<Browser>, String> {
+        let mut browser = self.browser.lock().await;
+        if let Some(running) = browser.as_ref().filter(|b| !b.cdp.closed.load(Ordering::SeqCst)) {
+            return Ok(Arc::clone(running));
+        }
+        let launched = Arc::new(launch().await.map_err(|e| format!("Failed to start companion browser: {:#}", e))?);
+        self.launches.fetch_add(1, Ordering::SeqCst);
+        *browser = Some(Arc::clone(&launched));
+        Ok(launched)
+    }
+
+    /// Load `url` in a fresh context and extract `{value, url, title}`
+    pub async fn extract(
+        &self,
+        url: &str,
+        selector: Option<&str>,
+        attribute: Option<&str>,
+        load_timeout: Duration,
+    ) -> Result<Value, String> {
+        let _slot = self
+            .slots
+            .acquire()
+            .await
+            .map_err(|_| "Companion pool closed".to_string())?;
+        let browser = self.browser().await?;
+        let cdp = &browser.cdp;
+        self.jobs.fetch_add(1, Ordering::SeqCst);
+
+        let created = cdp
+            .send(None, "Target.createBrowserContext", serde_json::json!({ "disposeOnDetach": true }))
+            .await?;
+        let context = string_field(&created, "browserContextId")?;
+        let expression = extract_expression(selector, attribute);
+        let result = run_in_context(cdp, context, url, &expression, load_timeout).await;
+
+        if let Err(e) = cdp
+            .send(None, "Target.disposeBrowserContext", serde_json::json!({ "browserContextId": context }))
+            .await
+        {
+            debug!("Failed to dispose companion context {}: {}", context, e);
+        }
+        result
+    }
+}
+
+/// Open a page in the context, wait for it to load, and evaluate `expression`
+async fn run_in_context(
+    cdp: &Cdp,
+    context: &str,
+    url: &str,
+    expression: &str,
+    load_timeout: Duration,
+) -> Result<Value, String> {
+    let target = cdp
+        .send(
+            None,
+            "Target.createTarget",
+            serde_json::json!({ "url": "about:blank", "browserContextId": context }),
+        )
+        .await?;
+    let attached = cdp
+        .send(
+            None,
+            "Target.attachToTarget",
+            serde_json::json!({ "targetId": string_field(&target, "targetId")?, "flatten": true }),
+        )
+        .await?;
+    let session = Some(string_field(&attached, "sessionId")?);
+
+    let navigated = cdp.send(session, "Page.navigate", serde_json::json!({ "url": url })).await?;
+    if let Some(error) = navigated.get("errorText").and_then(|v| v.as_str()) {
+        return Err(format!("Navigation to {} failed: {}", url, error));
+    }
+
+    let evaluate = |expression: &str| {
+        cdp.send(
+            session,
+            "Runtime.evaluate",
+            serde_json::json!({ "expression": expression, "returnByValue": true }),
+        )
+    };
+    let deadline = Instant::now() + load_timeout;
+    loop {
+        let state = evaluate("document.readyState").await?;
+        if state.pointer("/result/value").and_then(|v| v.as_str()) == Some("complete") {
+            break;
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("Page did not finish loading within {} ms", load_timeout.as_millis()));
+        }
+        tokio::time::sleep(Duration::from_millis(LOAD_POLL_MS)).await;
+    }
+
+    let evaluated = evaluate(expression).await?;
+    if let Some(exception) = evaluated.get("exceptionDetails") {
+        let text = exception
+            .pointer("/exception/description")
+            .or_else(|| exception.get("text"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("exception");
+        return Err(format!("Extraction failed: {}", text));
+    }
+    Ok(evaluated.pointer("/result/value").cloned().unwrap_or(Value::Null))
+}
+
+/// Start headless Chrome on a free debugging port and connect to it
+async fn launch() -> Result<Browser> {
+    let binary = chrome_binary().ok_or_else(|| anyhow!("No Chrome binary found; set AGENT_BROWSER_CHROME"))?;
+    let profile = storage::data_subdir(PROFILE_DIR)?;
+    // A port file left by an earlier run would point at a browser that is gone
+    let port_file = profile.join("DevToolsActivePort");
+    let _ = std::fs::remove_file(&port_file);
+
+    let child = Command::new(&binary)
+        .args([
+            "--headless=new",
+            "--remote-debugging-port=0",
+            "--no-first-run",
+            "--no-default-browser-check",
+            "--disable-extensions",
+            "--disable-background-networking",
+            "--mute-audio",
+        ])
+        .arg(format!("--user-data-dir={}", profile.display()))
+        .arg("about:blank")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", binary.display()))?;
+
+    let deadline = Instant::now() + Duration::from_secs(LAUNCH_TIMEOUT_SECS);
+    let url = loop {
+        if let Some(url) = std::fs::read_to_string(&port_file).ok().and_then(|c| devtools_url(&c)) {
+            break url;
+        }
+        if Instant::now() >= deadline {
+            bail!("{} did not open a DevTools port within {}s", binary.display(), LAUNCH_TIMEOUT_SECS);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    let cdp = Cdp::connect(&url).await?;
+    info!("Companion browser started: {} ({})", binary.display(), url);
+    Ok(Browser { cdp, _child: child })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_devtools_url() {
+        assert_eq!(
+            devtools_url("41235\n/devtools/browser/8b2f\n").as_deref(),
+            Some("ws://127.0.0.1:41235/devtools/browser/8b2f")
+        );
+        // Chrome writes the file in two steps; a half-written file is not ready yet
+        assert_eq!(devtools_url("41235\n"), None);
+        assert_eq!(devtools_url("port\n/devtools/browser/8b2f"), None);
+    }
+
+    #[test]
+    fn test_extract_expression_quotes_arguments() {
+        let expression = extract_expression(Some("a[title=\"x\"]"), None);
+        assert!(expression.contains(r#"const selector = "a[title=\"x\"]", attribute = null;"#));
+        assert!(extract_expression(None, Some("href")).contains(r#"const selector = null, attribute = "href";"#));
+    }
+
+    #[tokio::test]
+    async fn test_route_reply() {
+        let pending: Pending = Arc::default();
+        let (ok_tx, ok_rx) = oneshot::channel();
+        let (err_tx, err_rx) = oneshot::channel();
+        pending.lock().unwrap().insert(1, ok_tx);
+        pending.lock().unwrap().insert(2, err_tx);
+
+        assert!(!route_reply(&pending, r#"{"method":"Page.loadEventFired","params":{}}"#));
+        assert!(route_reply(&pending, r#"{"id":1,"result":{"targetId":"T1"}}"#));
+        assert!(route_reply(&pending, r#"{"id":2,"error":{"code":-32000,"message":"No target"}}"#));
+        assert!(!route_reply(&pending, r#"{"id":1,"result":{}}"#));
+
+        assert_eq!(ok_rx.await.unwrap().unwrap()["targetId"], "T1");
+        assert_eq!(err_rx.await.unwrap().unwrap_err(), "No target");
+    }
+}
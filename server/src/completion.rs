@@ -0,0 +1,187 @@
+/*!
+ * Argument Completion
+ *
+ * Answers `completion/complete` so clients can autocomplete arguments:
+ * - `rp_id` of passkey and recovery tools, from stored credentials and
+ *   recovery secrets
+ * - `tabId` of tools and of the `browser://tab/{tabId}/...` resource
+ *   templates, from tabs seen navigating (plus "active" for resources)
+ * - `selector` of element tools, from selectors this session used recently
+ * - `url` of the fill_login_form prompt, from stored credential RP IDs
+ *
+ * The spec defines `ref/prompt` and `ref/resource` references; tool
+ * arguments are completed through a `ref/tool` reference naming the tool.
+ */
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+use crate::mcp::tools;
+use crate::prompts;
+use crate::resources;
+
+/// Most values returned by one completion/complete (the spec's limit)
+pub const MAX_COMPLETION_VALUES: usize = 100;
+
+/// Selectors remembered per session for completion
+const MAX_RECENT_SELECTORS: usize = 50;
+
+/// Where completion values for an argument come from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    RpId,
+    TabId,
+    /// Tab IDs plus the "active" alias accepted by tab resources
+    ResourceTabId,
+    Selector,
+    LoginUrl,
+    /// Nothing to suggest for this argument
+    None,
+}
+
+/// Resolve `ref` and `argument.name` to a value source
+pub fn source(reference: &Value, argument: &str) -> Result<Source, String> {
+    let kind = reference.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    match kind {
+        "ref/tool" => {
+            let name = reference.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            if !tools::builtin_tools().iter().any(|tool| tool["name"] == name) {
+                return Err(format!("Unknown tool: {}", name));
+            }
+            Ok(match argument {
+                "rp_id" => Source::RpId,
+                "tabId" => Source::TabId,
+                "selector" => Source::Selector,
+                _ => Source::None,
+            })
+        }
+        "ref/prompt" => {
+            let name = reference.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            if !prompts::exists(name) {
+                return Err(format!("Unknown prompt: {}", name));
+            }
+            Ok(match (name, argument) {
+                ("fill_login_form", "url") => Source::LoginUrl,
+                _ => Source::None,
+            })
+        }
+        "ref/resource" => {
+            let uri = reference.get("uri").and_then(|u| u.as_str()).unwrap_or_default();
+            let known = resources::templates()
+                .as_array()
+                .is_some_and(|templates| templates.iter().any(|t| t["uriTemplate"] == uri));
+            if !known {
+                return Err(format!("Unknown resource template: {}", uri));
+            }
+            Ok(match argument {
+                "tabId" => Source::ResourceTabId,
+                _ => Source::None,
+            })
+        }
+        other => Err(format!("Unsupported completion reference type: {}", other)),
+    }
+}
+
+/// Candidates starting with `prefix` (case-insensitive), first occurrence kept,
+/// shaped as the `completion` result object
+pub fn complete<I: IntoIterator<Item = String>>(candidates: I, prefix: &str) -> Value {
+    let prefix = prefix.to_lowercase();
+    let mut values: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate.to_lowercase().starts_with(&prefix) && !values.contains(&candidate) {
+            values.push(candidate);
+        }
+    }
+    let total = values.len();
+    values.truncate(MAX_COMPLETION_VALUES);
+    json!({
+        "values": values,
+        "total": total,
+        "hasMore": total > MAX_COMPLETION_VALUES,
+    })
+}
+
+/// Selectors each session passed to tools, most recent first
+#[derive(Default)]
+pub struct RecentSelectors {
+    sessions: RwLock<HashMap<String, VecDeque<String>>>,
+}
+
+impl RecentSelectors {
+    pub async fn record(&self, session: &str, selector: &str) {
+        if selector.is_empty() {
+            return;
+        }
+        let mut sessions = self.sessions.write().await;
+        let recent = sessions.entry(session.to_string()).or_default();
+        recent.retain(|s| s != selector);
+        recent.push_front(selector.to_string());
+        recent.truncate(MAX_RECENT_SELECTORS);
+    }
+
+    pub async fn for_session(&self, session: &str) -> Vec<String> {
+        self.sessions
+            .read()
+            .await
+            .get(session)
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub async fn forget(&self, session: &str) {
+        self.sessions.write().await.remove(session);
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_resolution() {
+        let tool = json!({ "type": "ref/tool", "name": "recovery_secret_get" });
+        assert_eq!(source(&tool, "rp_id"), Ok(Source::RpId));
+        assert_eq!(source(&tool, "kind"), Ok(Source::None));
+        let click = json!({ "type": "ref/tool", "name": "playwright_click" });
+        assert_eq!(source(&click, "selector"), Ok(Source::Selector));
+
+        let prompt = json!({ "type": "ref/prompt", "name": "fill_login_form" });
+        assert_eq!(source(&prompt, "url"), Ok(Source::LoginUrl));
+        let resource = json!({ "type": "ref/resource", "uri": "browser://tab/{tabId}/dom" });
+        assert_eq!(source(&resource, "tabId"), Ok(Source::ResourceTabId));
+
+        assert!(source(&json!({ "type": "ref/tool", "name": "nope" }), "selector").is_err());
+        assert!(source(&json!({ "type": "ref/resource", "uri": "browser://nope" }), "tabId").is_err());
+        assert!(source(&json!({ "type": "ref/other" }), "x").is_err());
+    }
+
+    #[test]
+    fn test_complete_filters_dedupes_and_caps() {
+        let result = complete(["Example.com", "accounts.example", "example.com", "example.com"].map(String::from), "ex");
+        assert_eq!(result["values"], json!(["Example.com", "example.com"]));
+        assert_eq!(result["total"], 2);
+
+        let many = complete((0..150).map(|i| i.to_string()), "");
+        assert_eq!(many["values"].as_array().unwrap().len(), MAX_COMPLETION_VALUES);
+        assert_eq!(many["total"], 150);
+        assert_eq!(many["hasMore"], true);
+    }
+
+    #[tokio::test]
+    async fn test_recent_selectors_are_per_session_and_most_recent_first() {
+        let recent = RecentSelectors::default();
+        recent.record("a", "#login").await;
+        recent.record("a", "#submit").await;
+        recent.record("a", "#login").await;
+        recent.record("b", ".other").await;
+        assert_eq!(recent.for_session("a").await, ["#login", "#submit"]);
+        recent.forget("a").await;
+        assert!(recent.for_session("a").await.is_empty());
+        assert_eq!(recent.for_session("b").await, [".other"]);
+    }
+}
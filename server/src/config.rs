@@ -0,0 +1,328 @@
+/*!
+ * Listener and Timeout Configuration
+ *
+ * Bind addresses and the extension command timeout come from command-line
+ * flags, then environment variables, then the defaults (MCP TCP on
+ * 127.0.0.1:8084, WebSocket on 127.0.0.1:8085, HTTP on 127.0.0.1:8086, 30s).
+ * A port of 0 lets the OS choose; the ports actually bound are written to
+ * `~/.agent-browser/ports.json` and, with `--print-ports`, to stdout.
+ * Origin allowlists and the MCP TCP keep-alive interval are configured here
+ * too (see origin.rs and keepalive.rs).
+ */
+
+use anyhow::{bail, Context, Result};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::http_transport;
+use crate::keepalive;
+use crate::origin;
+use crate::storage;
+use crate::tls;
+
+pub const DEFAULT_WS_ADDR: &str = "127.0.0.1:8085";
+
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+pub const PORTS_FILE: &str = "ports.json";
+
+pub const USAGE: &str = "\
+Usage: agent-browser-server [doctor] [OPTIONS]
+
+Commands:
+  doctor                  Check ports, data directory, native messaging host, extension and
+                          credential store, printing a fix for each problem
+
+Options:
+  --tcp-addr <ip:port>    MCP TCP listener [env MCP_TCP_ADDR, default 127.0.0.1:8084]
+  --ws-addr <ip:port>     Extension and /mcp WebSocket listener [env MCP_WS_ADDR, default 127.0.0.1:8085]
+  --http-addr <ip:port>   MCP HTTP listener, enables HTTP [env MCP_HTTP_ADDR, default 127.0.0.1:8086]
+  --tcp-port <port>       Override only the port of the TCP address
+  --ws-port <port>        Override only the port of the WebSocket address
+  --http-port <port>      Override only the port of the HTTP address, enables HTTP
+  --http                  Serve MCP over HTTP [env MCP_HTTP]
+  --timeout <secs>        Extension command timeout [env AGENT_BROWSER_REQUEST_TIMEOUT_SECS, default 30]
+  --allow-evaluate        Let any session run playwright_evaluate, not just authorized ones [env AGENT_BROWSER_ALLOW_EVALUATE]
+  --telemetry             Count tool calls and failures locally for telemetry_status [env AGENT_BROWSER_TELEMETRY]
+  --keepalive <secs>      Ping MCP TCP clients idle this long, closing silent ones [env MCP_KEEPALIVE_SECS, default off]
+  --extension-id <id>     Extension allowed on the WebSocket, repeatable [env AGENT_BROWSER_EXTENSION_IDS]
+  --cors-origin <origin>  Browser origin allowed on MCP HTTP and /mcp, repeatable [env MCP_CORS_ORIGINS]
+  --print-ports           Print the bound addresses as one JSON line on stdout and skip stdio MCP
+  -h, --help              Show this help
+
+Port 0 binds any free port; the chosen ports are saved to ~/.agent-browser/ports.json.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub tcp_addr: String,
+    pub ws_addr: String,
+    pub http_addr: String,
+    pub http: bool,
+    pub request_timeout: Duration,
+    /// Idle time after which MCP TCP clients are pinged (None = never)
+    pub keepalive: Option<Duration>,
+    /// Allow playwright_evaluate without a passkey_authorize window
+    pub allow_evaluate: bool,
+    /// Extension IDs whose origin may open the extension WebSocket
+    pub extension_ids: Vec<String>,
+    /// Browser origins allowed to use the MCP HTTP and WebSocket surfaces
+    pub cors_origins: Vec<String>,
+    /// Keep local, opt-in usage counts (see telemetry.rs)
+    pub telemetry: bool,
+    /// Report bound addresses on stdout instead of serving MCP over stdio
+    pub print_ports: bool,
+}
+
+impl ServerConfig {
+    /// Defaults overridden by environment variables
+    pub fn from_env() -> Result<Self> {
+        let request_timeout = match std::env::var("AGENT_BROWSER_REQUEST_TIMEOUT_SECS") {
+            Ok(secs) => parse_timeout(&secs)?,
+            Err(_) => Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+        };
+        let keepalive = match std::env::var("MCP_KEEPALIVE_SECS") {
+            Ok(secs) => parse_keepalive(&secs)?,
+            Err(_) => None,
+        };
+        let extension_ids = match std::env::var("AGENT_BROWSER_EXTENSION_IDS") {
+            Ok(ids) => parse_list(&ids, origin::parse_extension_id)?,
+            Err(_) => vec![origin::DEFAULT_EXTENSION_ID.to_string()],
+        };
+        let cors_origins = match std::env::var("MCP_CORS_ORIGINS") {
+            Ok(origins) => parse_list(&origins, origin::parse_cors_origin)?,
+            Err(_) => Vec::new(),
+        };
+        Ok(Self {
+            tcp_addr: std::env::var("MCP_TCP_ADDR").unwrap_or_else(|_| tls::DEFAULT_TCP_ADDR.to_string()),
+            ws_addr: std::env::var("MCP_WS_ADDR").unwrap_or_else(|_| DEFAULT_WS_ADDR.to_string()),
+            http_addr: std::env::var("MCP_HTTP_ADDR")
+                .unwrap_or_else(|_| http_transport::DEFAULT_HTTP_ADDR.to_string()),
+            http: std::env::var("MCP_HTTP").is_ok(),
+            request_timeout,
+            keepalive,
+            allow_evaluate: std::env::var("AGENT_BROWSER_ALLOW_EVALUATE").is_ok(),
+            extension_ids,
+            cors_origins,
+            telemetry: std::env::var("AGENT_BROWSER_TELEMETRY").is_ok(),
+            print_ports: false,
+        })
+    }
+
+    /// Apply command-line flags (without the program name) on top of `self`.
+    /// Returns None when `--help` was given.
+    pub fn with_args<I: IntoIterator<Item = String>>(mut self, args: I) -> Result<Option<Self>> {
+        let mut args = args.into_iter();
+        // The first occurrence of a repeatable flag replaces the env/default list
+        let (mut extension_ids_given, mut cors_origins_given) = (false, false);
+        while let Some(flag) = args.next() {
+            let (flag, inline) = match flag.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (flag, None),
+            };
+            let mut value = || -> Result<String> {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .with_context(|| format!("{} needs a value", flag))
+            };
+            match flag.as_str() {
+                "--tcp-addr" => self.tcp_addr = parse_addr(&value()?)?,
+                "--ws-addr" => self.ws_addr = parse_addr(&value()?)?,
+                "--http-addr" => {
+                    self.http_addr = parse_addr(&value()?)?;
+                    self.http = true;
+                }
+                "--tcp-port" => self.tcp_addr = with_port(&self.tcp_addr, &value()?)?,
+                "--ws-port" => self.ws_addr = with_port(&self.ws_addr, &value()?)?,
+                "--http-port" => {
+                    self.http_addr = with_port(&self.http_addr, &value()?)?;
+                    self.http = true;
+                }
+                "--http" => self.http = true,
+                "--timeout" => self.request_timeout = parse_timeout(&value()?)?,
+                "--keepalive" => self.keepalive = parse_keepalive(&value()?)?,
+                "--allow-evaluate" => self.allow_evaluate = true,
+                "--telemetry" => self.telemetry = true,
+                "--extension-id" => {
+                    let id = origin::parse_extension_id(&value()?)?;
+                    if !std::mem::replace(&mut extension_ids_given, true) {
+                        self.extension_ids.clear();
+                    }
+                    self.extension_ids.push(id);
+                }
+                "--cors-origin" => {
+                    let allowed = origin::parse_cors_origin(&value()?)?;
+                    if !std::mem::replace(&mut cors_origins_given, true) {
+                        self.cors_origins.clear();
+                    }
+                    self.cors_origins.push(allowed);
+                }
+                "--print-ports" => self.print_ports = true,
+                "-h" | "--help" => return Ok(None),
+                other => bail!("Unknown option '{}'\n\n{}", other, USAGE),
+            }
+        }
+        Ok(Some(self))
+    }
+}
+
+fn parse_addr(addr: &str) -> Result<String> {
+    addr.parse::<SocketAddr>()
+        .with_context(|| format!("Invalid address '{}' (expected ip:port)", addr))?;
+    Ok(addr.to_string())
+}
+
+/// Replace the port of an `ip:port` address
+fn with_port(addr: &str, port: &str) -> Result<String> {
+    let port: u16 = port.parse().with_context(|| format!("Invalid port '{}'", port))?;
+    let mut parsed: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Invalid address '{}' (expected ip:port)", addr))?;
+    parsed.set_port(port);
+    Ok(parsed.to_string())
+}
+
+/// Parse a comma-separated list, skipping empty entries
+fn parse_list(list: &str, parse: fn(&str) -> Result<String>) -> Result<Vec<String>> {
+    list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(parse).collect()
+}
+
+fn parse_timeout(secs: &str) -> Result<Duration> {
+    match secs.parse::<u64>() {
+        Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+        _ => bail!("Invalid request timeout '{}' (expected whole seconds > 0)", secs),
+    }
+}
+
+/// Keep-alive interval in whole seconds; 0 turns it off
+fn parse_keepalive(secs: &str) -> Result<Option<Duration>> {
+    match secs.parse::<u64>() {
+        Ok(0) => Ok(None),
+        Ok(secs) if secs >= keepalive::PING_TIMEOUT_SECS => Ok(Some(Duration::from_secs(secs))),
+        _ => bail!(
+            "Invalid keep-alive interval '{}' (expected 0 or whole seconds >= {})",
+            secs,
+            keepalive::PING_TIMEOUT_SECS
+        ),
+    }
+}
+
+/// Addresses the listeners actually bound, after any port 0 was resolved
+#[derive(Debug, Clone)]
+pub struct BoundAddrs {
+    pub tcp: SocketAddr,
+    pub ws: SocketAddr,
+    pub http: Option<SocketAddr>,
+}
+
+impl BoundAddrs {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "pid": std::process::id(),
+            "tcp": { "host": self.tcp.ip().to_string(), "port": self.tcp.port() },
+            "ws": { "host": self.ws.ip().to_string(), "port": self.ws.port() },
+            "http": self.http.map(|a| serde_json::json!({ "host": a.ip().to_string(), "port": a.port() })),
+        })
+    }
+
+    /// Publish the bound ports for the native messaging host and local clients
+    pub fn write_ports_file(&self) -> Result<()> {
+        storage::write_private_file(&ports_path()?, self.to_json().to_string())
+    }
+}
+
+pub fn ports_path() -> Result<PathBuf> {
+    Ok(storage::data_dir()?.join(PORTS_FILE))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> ServerConfig {
+        ServerConfig {
+            tcp_addr: tls::DEFAULT_TCP_ADDR.to_string(),
+            ws_addr: DEFAULT_WS_ADDR.to_string(),
+            http_addr: http_transport::DEFAULT_HTTP_ADDR.to_string(),
+            http: false,
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            keepalive: None,
+            allow_evaluate: false,
+            extension_ids: vec![origin::DEFAULT_EXTENSION_ID.to_string()],
+            cors_origins: Vec::new(),
+            telemetry: false,
+            print_ports: false,
+        }
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flags_override_defaults() {
+        let config = defaults()
+            .with_args(args(&[
+                "--ws-port",
+                "0",
+                "--tcp-addr=127.0.0.1:9000",
+                "--timeout",
+                "90",
+                "--print-ports",
+                "--allow-evaluate",
+                "--telemetry",
+            ]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.ws_addr, "127.0.0.1:0");
+        assert_eq!(config.tcp_addr, "127.0.0.1:9000");
+        assert_eq!(config.request_timeout, Duration::from_secs(90));
+        assert!(config.print_ports && config.allow_evaluate && config.telemetry && !config.http);
+
+        let config = defaults().with_args(args(&["--http-port", "0"])).unwrap().unwrap();
+        assert!(config.http);
+        assert_eq!(config.http_addr, "127.0.0.1:0");
+
+        let config = defaults().with_args(args(&["--keepalive", "120"])).unwrap().unwrap();
+        assert_eq!(config.keepalive, Some(Duration::from_secs(120)));
+        let config = config.with_args(args(&["--keepalive=0"])).unwrap().unwrap();
+        assert_eq!(config.keepalive, None);
+    }
+
+    #[test]
+    fn test_repeatable_origin_flags_replace_defaults() {
+        let config = defaults()
+            .with_args(args(&[
+                "--extension-id",
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "--extension-id=bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                "--cors-origin",
+                "http://localhost:5173/",
+            ]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            config.extension_ids,
+            ["aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"]
+        );
+        assert_eq!(config.cors_origins, ["http://localhost:5173"]);
+        assert!(defaults().with_args(args(&["--cors-origin", "*"])).is_err());
+        assert_eq!(parse_list(" , ", origin::parse_cors_origin).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_invalid_flags_are_rejected() {
+        assert!(defaults().with_args(args(&["--timeout", "0"])).is_err());
+        assert!(defaults().with_args(args(&["--keepalive", "5"])).is_err());
+        assert!(defaults().with_args(args(&["--ws-port", "70000"])).is_err());
+        assert!(defaults().with_args(args(&["--ws-addr", "localhost:1"])).is_err());
+        assert!(defaults().with_args(args(&["--tcp-addr"])).is_err());
+        assert!(defaults().with_args(args(&["--bogus"])).is_err());
+        assert!(defaults().with_args(args(&["--help"])).unwrap().is_none());
+    }
+}
@@ -0,0 +1,135 @@
+/*!
+ * Cookie Consent Rules
+ *
+ * Curated selectors for common consent-management platforms (CMPs), sent
+ * to the extension by `dismiss_consent`. Each rule names the banner to
+ * detect and the buttons that accept or reject all non-essential cookies.
+ *
+ * Rules without a reject button are skipped in reject mode so a banner is
+ * never accepted by accident; the generic modal dismissal is the fallback.
+ */
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentMode {
+    Accept,
+    Reject,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentRule {
+    pub platform: &'static str,
+    /// Present while the banner is showing
+    pub detect: &'static str,
+    pub accept: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject: Option<&'static str>,
+    /// Shadow root host the buttons live under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_host: Option<&'static str>,
+    /// Iframe the banner is rendered in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<&'static str>,
+}
+
+const fn rule(platform: &'static str, detect: &'static str, accept: &'static str, reject: Option<&'static str>) -> ConsentRule {
+    ConsentRule {
+        platform,
+        detect,
+        accept,
+        reject,
+        shadow_host: None,
+        frame: None,
+    }
+}
+
+pub const CONSENT_RULES: &[ConsentRule] = &[
+    rule("onetrust", "#onetrust-banner-sdk", "#onetrust-accept-btn-handler", Some("#onetrust-reject-all-handler")),
+    rule(
+        "cookiebot",
+        "#CybotCookiebotDialog",
+        "#CybotCookiebotDialogBodyLevelButtonLevelOptinAllowAll, #CybotCookiebotDialogBodyButtonAccept",
+        Some("#CybotCookiebotDialogBodyButtonDecline"),
+    ),
+    rule(
+        "didomi",
+        "#didomi-notice",
+        "#didomi-notice-agree-button",
+        Some("#didomi-notice-disagree-button, .didomi-continue-without-agreeing"),
+    ),
+    rule(
+        "quantcast",
+        ".qc-cmp2-container",
+        ".qc-cmp2-summary-buttons button[mode='primary']",
+        Some(".qc-cmp2-summary-buttons button[mode='secondary']"),
+    ),
+    rule("trustarc", "#truste-consent-track", "#truste-consent-button", Some("#truste-consent-required")),
+    ConsentRule {
+        shadow_host: Some("#usercentrics-root"),
+        ..rule(
+            "usercentrics",
+            "#usercentrics-root",
+            "[data-testid='uc-accept-all-button']",
+            Some("[data-testid='uc-deny-all-button']"),
+        )
+    },
+    ConsentRule {
+        frame: Some("iframe[id^='sp_message_iframe']"),
+        ..rule(
+            "sourcepoint",
+            "[id^='sp_message_container']",
+            "button.sp_choice_type_11",
+            Some("button.sp_choice_type_13"),
+        )
+    },
+    rule("google_funding_choices", ".fc-consent-root", ".fc-cta-consent", Some(".fc-cta-do-not-consent")),
+    rule("cookieyes", ".cky-consent-container", ".cky-btn-accept", Some(".cky-btn-reject")),
+    rule("osano", ".osano-cm-dialog", ".osano-cm-accept-all", Some(".osano-cm-denyAll")),
+    rule("complianz", "#cmplz-cookiebanner-container", ".cmplz-accept", Some(".cmplz-deny")),
+    rule("klaro", ".klaro .cookie-notice", ".cm-btn-success", Some(".cn-decline")),
+    rule("cookie_notice", "#cookie-notice", "#cn-accept-cookie", Some("#cn-refuse-cookie")),
+    rule("iubenda", "#iubenda-cs-banner", ".iubenda-cs-accept-btn", Some(".iubenda-cs-reject-btn")),
+    rule("cookie_script", "#cookiescript_injected", "#cookiescript_accept", Some("#cookiescript_reject")),
+];
+
+/// Rules usable for a mode, with the button to click resolved
+pub fn rules_for(mode: ConsentMode) -> Vec<serde_json::Value> {
+    CONSENT_RULES
+        .iter()
+        .filter_map(|rule| {
+            let button = match mode {
+                ConsentMode::Accept => rule.accept,
+                ConsentMode::Reject => rule.reject?,
+            };
+            let mut value = serde_json::to_value(rule).ok()?;
+            value["button"] = serde_json::json!(button);
+            Some(value)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_resolve_button_for_mode() {
+        let reject = rules_for(ConsentMode::Reject);
+        let onetrust = reject.iter().find(|r| r["platform"] == "onetrust").unwrap();
+        assert_eq!(onetrust["button"], "#onetrust-reject-all-handler");
+
+        let usercentrics = reject.iter().find(|r| r["platform"] == "usercentrics").unwrap();
+        assert_eq!(usercentrics["shadowHost"], "#usercentrics-root");
+
+        // Every rule currently has a reject button
+        assert_eq!(reject.len(), CONSENT_RULES.len());
+        assert_eq!(rules_for(ConsentMode::Accept).len(), CONSENT_RULES.len());
+    }
+}
@@ -5,13 +5,15 @@
  * - Credentials encrypted at rest with master key
  * - Master key stored encrypted (requires OS authentication once per session)
  * - Time-limited authorization grants (human approves AI for X hours)
+ * - Single-use, RP-scoped grants an authorized session can hand to a sub-agent
  * - All credential usage audited
  * - Memory protection for sensitive data
  */
 
 use anyhow::{anyhow, Result};
-use base64::engine::{Engine as _, general_purpose::STANDARD as BASE64};
-use ring::aead::{Aad, BoundKey, Nonce, NonceSequence, SealingKey, UnboundKey, AES_256_GCM, NONCE_LEN};
+use base64::engine::{Engine as _, general_purpose::STANDARD as BASE64, general_purpose::URL_SAFE_NO_PAD as BASE64_URL};
+use ring::hmac;
+use ring::aead::{Aad, BoundKey, LessSafeKey, Nonce, NonceSequence, SealingKey, UnboundKey, AES_256_GCM, NONCE_LEN};
 use ring::error::Unspecified;
 use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
@@ -56,6 +58,142 @@ pub struct CredentialMetadata {
     pub use_count: u64,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialUsage {
+    pub last_used: Option<i64>,
+    pub use_count: u64,
+}
+
+/// A single exercise of a credential, reported to supervising clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialUseEvent {
+    pub credential_id: String,
+    pub rp_id: String,
+    pub operation: String,
+    pub session: Option<String>,
+    pub timestamp: i64,
+    pub use_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoverySecretKind {
+    /// One-time backup code; consumed on retrieval
+    BackupCode,
+    /// Security question answer; reusable, keyed by its question label
+    SecurityAnswer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySecret {
+    pub id: String,
+    pub rp_id: String,
+    pub kind: RecoverySecretKind,
+    pub label: Option<String>,
+    pub encrypted_value: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub created: i64,
+    pub used_at: Option<i64>,
+}
+
+/// Recovery secret as shown to agents: the value itself is never included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySecretMetadata {
+    pub id: String,
+    pub rp_id: String,
+    pub kind: RecoverySecretKind,
+    pub label: Option<String>,
+    pub hint: String,
+    pub created: i64,
+    pub used_at: Option<i64>,
+}
+
+/// Longest elevated window a single approval can grant
+pub const MAX_ELEVATED_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// Longest a delegated credential grant stays redeemable or usable
+pub const MAX_GRANT_DURATION: Duration = Duration::from_secs(15 * 60);
+
+const GRANT_TOKEN_PREFIX: &str = "abgrant1.";
+
+/// Permission to use one RP's credential once, minted by an authorized
+/// session for another MCP client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialGrant {
+    pub id: String,
+    pub rp_id: String,
+    /// "get" (sign in) or "create" (register); None allows either
+    pub operation: Option<String>,
+    pub issued_by: String,
+    pub expires_at: u64,
+}
+
+impl CredentialGrant {
+    fn is_expired(&self) -> bool {
+        unix_now() >= self.expires_at
+    }
+
+    fn covers(&self, rp_id: &str, operation: &str) -> bool {
+        self.rp_id == rp_id && self.operation.as_deref().is_none_or(|op| op == operation)
+    }
+}
+
+/// Why a credential use was allowed
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialApproval {
+    /// The session has an active authorization window
+    Authorized,
+    /// The session's redeemed grant (by id) covered the use and is now spent
+    Grant(String),
+}
+
+/// Remove and return the session's grant if it is unexpired and covers the use
+///
+/// An expired grant is dropped; one for another RP or operation is kept.
+fn take_grant(
+    grants: &mut HashMap<String, CredentialGrant>,
+    session: &str,
+    rp_id: &str,
+    operation: &str,
+) -> Option<CredentialGrant> {
+    match grants.get(session) {
+        Some(grant) if grant.is_expired() => {
+            grants.remove(session);
+            None
+        }
+        Some(grant) if grant.covers(rp_id, operation) => grants.remove(session),
+        _ => None,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Encode a grant as `abgrant1.<payload>.<signature>` (base64url, HMAC-SHA256)
+fn sign_grant(key: &hmac::Key, grant: &CredentialGrant) -> Result<String> {
+    let payload = BASE64_URL.encode(serde_json::to_vec(grant)?);
+    let tag = hmac::sign(key, payload.as_bytes());
+    Ok(format!("{}{}.{}", GRANT_TOKEN_PREFIX, payload, BASE64_URL.encode(tag.as_ref())))
+}
+
+/// Check a token's signature and decode its grant (expiry and single use are checked by the store)
+fn verify_grant(key: &hmac::Key, token: &str) -> Result<CredentialGrant> {
+    let invalid = || anyhow!("Invalid credential grant token");
+    let (payload, signature) = token
+        .trim()
+        .strip_prefix(GRANT_TOKEN_PREFIX)
+        .and_then(|rest| rest.split_once('.'))
+        .ok_or_else(invalid)?;
+    let signature = BASE64_URL.decode(signature).map_err(|_| invalid())?;
+    hmac::verify(key, payload.as_bytes(), &signature).map_err(|_| invalid())?;
+    let payload = BASE64_URL.decode(payload).map_err(|_| invalid())?;
+    serde_json::from_slice(&payload).map_err(|_| invalid())
+}
+
 // ============================================================================
 // Nonce Generator
 // ============================================================================
@@ -89,14 +227,25 @@ pub struct CredentialStore {
     credentials: Arc<RwLock<HashMap<String, EncryptedCredential>>>,
     #[allow(dead_code)]
     authorizations: Arc<RwLock<HashMap<String, CredentialAuthorization>>>,
+    usage: Arc<RwLock<HashMap<String, CredentialUsage>>>,
     #[allow(dead_code)]
     master_key: Vec<u8>,
     #[allow(dead_code)]
     rng: SystemRandom,
     db_path: PathBuf,
+    usage_db_path: PathBuf,
+    recovery_db_path: PathBuf,
     audit_log_path: PathBuf,
-    session_authorized: Arc<RwLock<bool>>, // Global session authorization
-    session_authorized_until: Arc<RwLock<Option<u64>>>,
+    // Authorization expiry per MCP session, so one agent's grant never covers another
+    session_authorized_until: Arc<RwLock<HashMap<String, u64>>>,
+    recovery_secrets: Arc<RwLock<HashMap<String, RecoverySecret>>>,
+    elevated_until: Arc<RwLock<HashMap<String, u64>>>, // Recovery secret access window per session
+    // Delegated grants minted but not yet redeemed, by grant id
+    issued_grants: Arc<RwLock<HashMap<String, CredentialGrant>>>,
+    // Redeemed grants not yet used, by the redeeming session
+    session_grants: Arc<RwLock<HashMap<String, CredentialGrant>>>,
+    // Held while appending to or compacting the audit log
+    audit_lock: tokio::sync::Mutex<()>,
 }
 
 impl CredentialStore {
@@ -104,6 +253,8 @@ impl CredentialStore {
     pub async fn new() -> Result<Self> {
         let db_dir = Self::get_db_dir()?;
         let db_path = db_dir.join("credentials.json");
+        let usage_db_path = db_dir.join("credential_usage.json");
+        let recovery_db_path = db_dir.join("recovery_secrets.json");
         let audit_log_path = db_dir.join("audit.log");
 
         // Load or generate master key
@@ -112,20 +263,31 @@ impl CredentialStore {
         // Set restrictive permissions on database
         #[cfg(unix)]
         Self::set_secure_permissions(&db_path)?;
+        #[cfg(unix)]
+        Self::set_secure_permissions(&recovery_db_path)?;
 
         let mut store = Self {
             credentials: Arc::new(RwLock::new(HashMap::new())),
             authorizations: Arc::new(RwLock::new(HashMap::new())),
+            usage: Arc::new(RwLock::new(HashMap::new())),
             master_key,
             rng: SystemRandom::new(),
             db_path: db_path.clone(),
+            usage_db_path,
+            recovery_db_path,
             audit_log_path,
-            session_authorized: Arc::new(RwLock::new(false)),
-            session_authorized_until: Arc::new(RwLock::new(None)),
+            session_authorized_until: Arc::new(RwLock::new(HashMap::new())),
+            recovery_secrets: Arc::new(RwLock::new(HashMap::new())),
+            elevated_until: Arc::new(RwLock::new(HashMap::new())),
+            issued_grants: Arc::new(RwLock::new(HashMap::new())),
+            session_grants: Arc::new(RwLock::new(HashMap::new())),
+            audit_lock: tokio::sync::Mutex::new(()),
         };
 
         // Load existing credentials
         store.load_credentials().await?;
+        store.load_usage().await?;
+        store.load_recovery_secrets().await?;
 
         info!("Credential store initialized at {:?}", db_path);
 
@@ -134,22 +296,7 @@ impl CredentialStore {
 
     /// Get database directory
     fn get_db_dir() -> Result<PathBuf> {
-        let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
-        let db_dir = home.join(".agent-browser");
-
-        if !db_dir.exists() {
-            fs::create_dir_all(&db_dir)?;
-
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perms = fs::metadata(&db_dir)?.permissions();
-                perms.set_mode(0o700); // Only owner can access
-                fs::set_permissions(&db_dir, perms)?;
-            }
-        }
-
-        Ok(db_dir)
+        crate::storage::data_dir()
     }
 
     /// Load or generate master encryption key
@@ -227,11 +374,35 @@ impl CredentialStore {
         Ok(())
     }
 
+    /// Load credential usage counters from disk
+    async fn load_usage(&mut self) -> Result<()> {
+        if !self.usage_db_path.exists() {
+            return Ok(());
+        }
+
+        let data = fs::read_to_string(&self.usage_db_path)?;
+        let loaded: HashMap<String, CredentialUsage> = serde_json::from_str(&data)?;
+        *self.usage.write().await = loaded;
+        Ok(())
+    }
+
+    /// Save credential usage counters to disk
+    async fn save_usage(&self) -> Result<()> {
+        let usage = self.usage.read().await;
+        let data = serde_json::to_string_pretty(&*usage)?;
+        fs::write(&self.usage_db_path, data)?;
+
+        #[cfg(unix)]
+        Self::set_secure_permissions(&self.usage_db_path)?;
+
+        Ok(())
+    }
+
     /// Authorize session for duration
     ///
     /// FUTURE: Touch ID verification on macOS (see SECURITY.md roadmap)
     /// Currently grants time-bound authorization without biometric check
-    pub async fn authorize_session(&self, duration: Duration) -> Result<()> {
+    pub async fn authorize_session(&self, session: &str, duration: Duration) -> Result<()> {
         // Grant time-bound authorization
         // Touch ID integration planned for Q1 2025 (see SECURITY.md)
 
@@ -241,49 +412,77 @@ impl CredentialStore {
             .duration_since(UNIX_EPOCH)?
             .as_secs();
 
-        *self.session_authorized.write().await = true;
-        *self.session_authorized_until.write().await = Some(authorized_until);
+        self.session_authorized_until
+            .write()
+            .await
+            .insert(session.to_string(), authorized_until);
 
         self.audit_log(&format!(
-            "Session authorized for {} hours",
+            "Session {} authorized for {} hours",
+            session,
             duration.as_secs() / 3600
         )).await;
 
-        info!("Session authorized until timestamp {}", authorized_until);
+        info!("Session {} authorized until timestamp {}", session, authorized_until);
 
         Ok(())
     }
 
     /// Check if session is currently authorized
-    pub async fn is_session_authorized(&self) -> bool {
-        let authorized = *self.session_authorized.read().await;
-
-        if !authorized {
-            return false;
-        }
-
+    pub async fn is_session_authorized(&self, session: &str) -> bool {
         let authorized_until = self.session_authorized_until.read().await;
 
-        if let Some(until) = *authorized_until {
+        if let Some(until) = authorized_until.get(session) {
             let now = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs();
 
-            now < until
+            now < *until
         } else {
             false
         }
     }
 
+    /// Drop a session's authorization when its connection closes
+    pub async fn revoke_session(&self, session: &str) {
+        let had_grant = self.session_authorized_until.write().await.remove(session).is_some();
+        self.elevated_until.write().await.remove(session);
+        if had_grant {
+            self.audit_log(&format!("Session {} closed; authorization revoked", session)).await;
+        }
+
+        // Unredeemed grants die with the session that minted them
+        self.session_grants.write().await.remove(session);
+        let revoked: Vec<String> = {
+            let mut issued = self.issued_grants.write().await;
+            let ids: Vec<String> = issued
+                .values()
+                .filter(|grant| grant.issued_by == session)
+                .map(|grant| grant.id.clone())
+                .collect();
+            ids.into_iter().filter(|id| issued.remove(id).is_some()).collect()
+        };
+        for id in revoked {
+            self.audit_log(&format!("Credential grant {} revoked; issuing session {} closed", id, session)).await;
+        }
+    }
+
     /// Get authorization status
-    pub async fn get_authorization_status(&self) -> serde_json::Value {
-        let authorized = self.is_session_authorized().await;
-        let until = *self.session_authorized_until.read().await;
+    pub async fn get_authorization_status(&self, session: &str) -> serde_json::Value {
+        let authorized = self.is_session_authorized(session).await;
+        let until = self.session_authorized_until.read().await.get(session).copied();
+        let elevated = self.is_elevated(session).await;
+        let elevated_until = self.elevated_until.read().await.get(session).copied();
+
+        let grant = self.active_grant(session).await;
 
         serde_json::json!({
             "authorized": authorized,
+            "grant": grant,
             "expires_at": until,
+            "elevated": elevated,
+            "elevated_expires_at": if elevated { elevated_until } else { None },
             "remaining_seconds": until.map(|u| {
                 let now = SystemTime::now()
                     .duration_since(UNIX_EPOCH)
@@ -340,14 +539,15 @@ impl CredentialStore {
         let cred = credentials
             .get(id)
             .ok_or_else(|| anyhow!("Credential not found"))?;
+        let usage = self.usage.read().await.get(id).cloned().unwrap_or_default();
 
         Ok(CredentialMetadata {
             id: cred.id.clone(),
             rp_id: cred.rp_id.clone(),
             user_handle_b64: BASE64.encode(&cred.user_handle),
             created: cred.created,
-            last_used: None,
-            use_count: 0,
+            last_used: usage.last_used,
+            use_count: usage.use_count,
         })
     }
 
@@ -355,14 +555,18 @@ impl CredentialStore {
     #[allow(dead_code)]
     pub async fn list_credentials(&self) -> Result<Vec<CredentialMetadata>> {
         let credentials = self.credentials.read().await;
-
-        Ok(credentials.values().map(|cred| CredentialMetadata {
-            id: cred.id.clone(),
-            rp_id: cred.rp_id.clone(),
-            user_handle_b64: BASE64.encode(&cred.user_handle),
-            created: cred.created,
-            last_used: None,
-            use_count: 0,
+        let usage = self.usage.read().await;
+
+        Ok(credentials.values().map(|cred| {
+            let cred_usage = usage.get(&cred.id).cloned().unwrap_or_default();
+            CredentialMetadata {
+                id: cred.id.clone(),
+                rp_id: cred.rp_id.clone(),
+                user_handle_b64: BASE64.encode(&cred.user_handle),
+                created: cred.created,
+                last_used: cred_usage.last_used,
+                use_count: cred_usage.use_count,
+            }
         }).collect())
     }
 
@@ -373,19 +577,552 @@ impl CredentialStore {
         let count = credentials.len();
         credentials.clear();
         drop(credentials);
+        self.usage.write().await.clear();
 
         self.save_credentials().await?;
+        self.save_usage().await?;
 
         self.audit_log(&format!("Cleared {} credentials", count)).await;
 
         Ok(())
     }
 
+    /// Record that a credential was exercised (assertion, registration, ...)
+    ///
+    /// The rp_id falls back to the stored credential when the caller doesn't
+    /// supply one. Returns the event so callers can notify supervisors.
+    pub async fn record_credential_use(
+        &self,
+        credential_id: &str,
+        rp_id: Option<&str>,
+        operation: &str,
+        session: Option<&str>,
+    ) -> CredentialUseEvent {
+        let rp_id = match rp_id {
+            Some(rp_id) => rp_id.to_string(),
+            None => self
+                .credentials
+                .read()
+                .await
+                .get(credential_id)
+                .map(|c| c.rp_id.clone())
+                .unwrap_or_default(),
+        };
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let use_count = {
+            let mut usage = self.usage.write().await;
+            let entry = usage.entry(credential_id.to_string()).or_default();
+            entry.last_used = Some(timestamp);
+            entry.use_count += 1;
+            entry.use_count
+        };
+        // The use already happened, so a failed save only costs the counter
+        if let Err(e) = self.save_usage().await {
+            warn!("Failed to save credential usage: {}", e);
+        }
+
+        self.audit_log(&format!(
+            "Credential {} used for {} on rpId: {} (session: {})",
+            credential_id,
+            operation,
+            rp_id,
+            session.unwrap_or("unknown")
+        )).await;
+
+        CredentialUseEvent {
+            credential_id: credential_id.to_string(),
+            rp_id,
+            operation: operation.to_string(),
+            session: session.map(str::to_string),
+            timestamp,
+            use_count,
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Recovery secrets
+    // ------------------------------------------------------------------------
+
+    /// Load recovery secrets from disk
+    async fn load_recovery_secrets(&mut self) -> Result<()> {
+        if !self.recovery_db_path.exists() {
+            return Ok(());
+        }
+
+        let data = fs::read_to_string(&self.recovery_db_path)?;
+        let secrets: HashMap<String, RecoverySecret> = serde_json::from_str(&data)?;
+
+        let mut recovery_secrets = self.recovery_secrets.write().await;
+        *recovery_secrets = secrets;
+
+        info!("Loaded {} recovery secrets from disk", recovery_secrets.len());
+        Ok(())
+    }
+
+    /// Save recovery secrets to disk
+    async fn save_recovery_secrets(&self) -> Result<()> {
+        let secrets = self.recovery_secrets.read().await;
+        self.write_recovery_secrets(&secrets)
+    }
+
+    /// Write a recovery secret table to disk without touching the in-memory copy
+    fn write_recovery_secrets(&self, secrets: &HashMap<String, RecoverySecret>) -> Result<()> {
+        let data = serde_json::to_string_pretty(secrets)?;
+        fs::write(&self.recovery_db_path, data)?;
+
+        #[cfg(unix)]
+        Self::set_secure_permissions(&self.recovery_db_path)?;
+
+        Ok(())
+    }
+
+    /// Encrypt a secret value with a fresh random nonce
+    fn seal_secret(&self, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let key = LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, &self.master_key)
+                .map_err(|_| anyhow!("Failed to create encryption key"))?,
+        );
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce_bytes).map_err(|_| anyhow!("Failed to generate nonce"))?;
+
+        let mut ciphertext = plaintext.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::empty(),
+            &mut ciphertext,
+        )
+        .map_err(|_| anyhow!("Encryption failed"))?;
+
+        Ok((ciphertext, nonce_bytes.to_vec()))
+    }
+
+    /// Decrypt a secret value sealed by `seal_secret`
+    fn open_secret(&self, ciphertext: &[u8], nonce: &[u8]) -> Result<String> {
+        let key = LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, &self.master_key)
+                .map_err(|_| anyhow!("Failed to create encryption key"))?,
+        );
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Invalid nonce"))?;
+
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = key
+            .open_in_place(nonce, Aad::empty(), &mut buffer)
+            .map_err(|_| anyhow!("Decryption failed"))?;
+
+        Ok(String::from_utf8(plaintext.to_vec())?)
+    }
+
+    /// Store one or more recovery secrets for an RP
+    ///
+    /// Each value becomes its own entry so backup codes can be consumed individually.
+    pub async fn store_recovery_secrets(
+        &self,
+        rp_id: &str,
+        kind: RecoverySecretKind,
+        label: Option<&str>,
+        values: &[String],
+    ) -> Result<Vec<RecoverySecretMetadata>> {
+        if values.is_empty() {
+            return Err(anyhow!("No recovery secret values provided"));
+        }
+        if kind == RecoverySecretKind::SecurityAnswer && label.is_none() {
+            return Err(anyhow!("Security answers require a question label"));
+        }
+
+        let created = chrono::Utc::now().timestamp();
+        let mut stored = Vec::with_capacity(values.len());
+        {
+            let mut secrets = self.recovery_secrets.write().await;
+            for value in values {
+                let (encrypted_value, nonce) = self.seal_secret(value.as_bytes())?;
+                let secret = RecoverySecret {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    rp_id: rp_id.to_string(),
+                    kind,
+                    label: label.map(str::to_string),
+                    encrypted_value,
+                    nonce,
+                    created,
+                    used_at: None,
+                };
+                stored.push(redact_recovery_secret(&secret, value));
+                secrets.insert(secret.id.clone(), secret);
+            }
+        }
+
+        self.save_recovery_secrets().await?;
+
+        self.audit_log(&format!(
+            "Stored {} recovery secret(s) of kind {:?} for rpId: {}",
+            values.len(),
+            kind,
+            rp_id
+        )).await;
+
+        Ok(stored)
+    }
+
+    /// List recovery secrets with values redacted
+    ///
+    /// An entry that no longer decrypts is still listed, with an
+    /// `UNREADABLE_HINT` hint, so one bad entry doesn't hide the rest.
+    pub async fn list_recovery_secrets(&self, rp_id: Option<&str>) -> Result<Vec<RecoverySecretMetadata>> {
+        let secrets = self.recovery_secrets.read().await;
+
+        let mut listed = Vec::new();
+        for secret in secrets.values() {
+            if rp_id.is_some_and(|rp| rp != secret.rp_id) {
+                continue;
+            }
+            let opened = self.open_secret(&secret.encrypted_value, &secret.nonce);
+            if let Err(e) = &opened {
+                warn!("Recovery secret {} for {} does not decrypt: {}", secret.id, secret.rp_id, e);
+            }
+            listed.push(describe_recovery_secret(secret, opened.ok().as_deref()));
+        }
+        listed.sort_by(|a, b| (&a.rp_id, a.created, &a.id).cmp(&(&b.rp_id, b.created, &b.id)));
+
+        Ok(listed)
+    }
+
+    /// Check that every stored recovery secret opens with the master key.
+    /// Returns the number of credentials and recovery secrets on disk.
+    pub async fn verify_decryption(&self) -> Result<(usize, usize)> {
+        let secrets = self.recovery_secrets.read().await;
+        for secret in secrets.values() {
+            self.open_secret(&secret.encrypted_value, &secret.nonce)
+                .map_err(|e| anyhow!("Recovery secret {} for {}: {}", secret.id, secret.rp_id, e))?;
+        }
+        Ok((self.credentials.read().await.len(), secrets.len()))
+    }
+
+    /// Grant elevated authorization for recovery secret retrieval
+    ///
+    /// Requires an active session authorization and is capped at
+    /// `MAX_ELEVATED_DURATION` regardless of the requested duration.
+    pub async fn authorize_elevated(&self, session: &str, duration: Duration) -> Result<u64> {
+        if !self.is_session_authorized(session).await {
+            return Err(anyhow!("Session not authorized; call passkey_authorize first"));
+        }
+
+        let duration = duration.min(MAX_ELEVATED_DURATION);
+        let elevated_until = SystemTime::now()
+            .checked_add(duration)
+            .ok_or_else(|| anyhow!("Time overflow"))?
+            .duration_since(UNIX_EPOCH)?
+            .as_secs();
+
+        self.elevated_until
+            .write()
+            .await
+            .insert(session.to_string(), elevated_until);
+
+        self.audit_log(&format!(
+            "Elevated recovery access authorized for session {} for {} minutes",
+            session,
+            duration.as_secs() / 60
+        )).await;
+
+        Ok(elevated_until)
+    }
+
+    /// Check if elevated authorization is currently active
+    pub async fn is_elevated(&self, session: &str) -> bool {
+        if !self.is_session_authorized(session).await {
+            return false;
+        }
+
+        match self.elevated_until.read().await.get(session).copied() {
+            Some(until) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                now < until
+            }
+            None => false,
+        }
+    }
+
+    /// Refuse a recovery secret action unless `session` is elevated, auditing the denial
+    pub async fn require_elevated(&self, session: &str, action: &str, rp_id: &str) -> Result<()> {
+        if self.is_elevated(session).await {
+            return Ok(());
+        }
+        self.audit_log(&format!(
+            "Denied recovery secret {} for rpId: {} (session {} not elevated)",
+            action, rp_id, session
+        )).await;
+        Err(anyhow!("Elevated authorization required; call recovery_authorize first"))
+    }
+
+    /// Refuse a recovery secret action unless `session` is authorized, auditing the denial
+    pub async fn require_authorized(&self, session: &str, action: &str, rp_id: &str) -> Result<()> {
+        if self.is_session_authorized(session).await {
+            return Ok(());
+        }
+        self.audit_log(&format!(
+            "Denied recovery secret {} for rpId: {} (session {} not authorized)",
+            action, rp_id, session
+        )).await;
+        Err(anyhow!("Session not authorized; call passkey_authorize first"))
+    }
+
+    /// Retrieve a recovery secret under elevated authorization
+    ///
+    /// Backup codes are marked used and never returned again. Security
+    /// answers are matched by label (case-insensitive) and stay reusable.
+    pub async fn take_recovery_secret(
+        &self,
+        session: &str,
+        rp_id: &str,
+        kind: RecoverySecretKind,
+        label: Option<&str>,
+    ) -> Result<(RecoverySecretMetadata, String)> {
+        self.require_elevated(session, "retrieval", rp_id).await?;
+
+        // The write lock is held across the save so two retrievals can't
+        // release the same backup code
+        let mut secrets = self.recovery_secrets.write().await;
+        let secret = pick_recovery_secret(&secrets, rp_id, kind, label)
+            .ok_or_else(|| anyhow!("No matching recovery secret for {}", rp_id))?
+            .clone();
+        let value = self.open_secret(&secret.encrypted_value, &secret.nonce)?;
+
+        let metadata = if kind == RecoverySecretKind::BackupCode {
+            // Mark the code used on a copy; memory only changes once the
+            // copy is on disk, so a failed save leaves the code available
+            let used = RecoverySecret { used_at: Some(chrono::Utc::now().timestamp()), ..secret };
+            let mut updated = secrets.clone();
+            updated.insert(used.id.clone(), used.clone());
+            self.write_recovery_secrets(&updated)?;
+            *secrets = updated;
+            redact_recovery_secret(&used, &value)
+        } else {
+            redact_recovery_secret(&secret, &value)
+        };
+        drop(secrets);
+
+        self.audit_log(&format!(
+            "Released recovery secret {} ({:?}) for rpId: {}",
+            metadata.id, kind, rp_id
+        )).await;
+
+        Ok((metadata, value))
+    }
+
+    /// Delete recovery secrets for an RP (all kinds)
+    pub async fn delete_recovery_secrets(&self, rp_id: &str) -> Result<usize> {
+        let removed = {
+            let mut secrets = self.recovery_secrets.write().await;
+            let before = secrets.len();
+            secrets.retain(|_, s| s.rp_id != rp_id);
+            before - secrets.len()
+        };
+
+        self.save_recovery_secrets().await?;
+
+        self.audit_log(&format!(
+            "Deleted {} recovery secret(s) for rpId: {}",
+            removed, rp_id
+        )).await;
+
+        Ok(removed)
+    }
+
+    // ------------------------------------------------------------------------
+    // Delegated grants
+    // ------------------------------------------------------------------------
+
+    fn grant_key(&self) -> hmac::Key {
+        // Derived from the master key so tokens don't outlive a key rotation
+        let derived = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &self.master_key), b"agent-browser credential grant");
+        hmac::Key::new(hmac::HMAC_SHA256, derived.as_ref())
+    }
+
+    /// Mint a signed, single-use grant for one RP's credential
+    ///
+    /// Requires an active session authorization; the grant expires after
+    /// `duration` (capped at `MAX_GRANT_DURATION`) or when the minting
+    /// session's own window ends, whichever is first.
+    pub async fn mint_grant(
+        &self,
+        session: &str,
+        rp_id: &str,
+        operation: Option<&str>,
+        duration: Duration,
+    ) -> Result<(String, CredentialGrant)> {
+        if !self.is_session_authorized(session).await {
+            return Err(anyhow!("Session not authorized; call passkey_authorize first"));
+        }
+        let window_end = self
+            .session_authorized_until
+            .read()
+            .await
+            .get(session)
+            .copied()
+            .unwrap_or_default();
+
+        let grant = CredentialGrant {
+            id: uuid::Uuid::new_v4().to_string(),
+            rp_id: rp_id.to_string(),
+            operation: operation.map(str::to_string),
+            issued_by: session.to_string(),
+            expires_at: (unix_now() + duration.min(MAX_GRANT_DURATION).as_secs()).min(window_end),
+        };
+        let token = sign_grant(&self.grant_key(), &grant)?;
+        self.issued_grants.write().await.insert(grant.id.clone(), grant.clone());
+
+        self.audit_log(&format!(
+            "Session {} issued credential grant {} for {} on rpId: {}",
+            session,
+            grant.id,
+            operation.unwrap_or("any operation"),
+            rp_id
+        )).await;
+
+        Ok((token, grant))
+    }
+
+    /// Redeem a grant token for `session`, which may then use the RP's credential once
+    pub async fn redeem_grant(&self, session: &str, token: &str) -> Result<CredentialGrant> {
+        let grant = verify_grant(&self.grant_key(), token)?;
+        let issued = self.issued_grants.write().await.remove(&grant.id);
+        let Some(grant) = issued.filter(|issued| *issued == grant) else {
+            return Err(anyhow!("Credential grant was already redeemed or has been revoked"));
+        };
+        if grant.is_expired() {
+            return Err(anyhow!("Credential grant has expired"));
+        }
+
+        self.session_grants
+            .write()
+            .await
+            .insert(session.to_string(), grant.clone());
+
+        self.audit_log(&format!(
+            "Credential grant {} for rpId: {} redeemed by session {}",
+            grant.id, grant.rp_id, session
+        )).await;
+
+        Ok(grant)
+    }
+
+    /// The redeemed, unused and unexpired grant held by `session`
+    pub async fn active_grant(&self, session: &str) -> Option<CredentialGrant> {
+        self.session_grants
+            .read()
+            .await
+            .get(session)
+            .filter(|grant| !grant.is_expired())
+            .cloned()
+    }
+
+    /// Decide whether `session` may use the RP's credential, before the extension answers
+    ///
+    /// An authorized session may; otherwise a redeemed grant covering the use
+    /// is used up. Anything else is refused and logged.
+    pub async fn approve_credential_use(
+        &self,
+        session: Option<&str>,
+        rp_id: &str,
+        operation: &str,
+    ) -> Result<CredentialApproval> {
+        let Some(session) = session else {
+            self.audit_log(&format!("Refused {} on rpId: {}; no session is driving the browser", operation, rp_id)).await;
+            return Err(anyhow!("No MCP session is driving the browser"));
+        };
+        if self.is_session_authorized(session).await {
+            return Ok(CredentialApproval::Authorized);
+        }
+
+        let taken = take_grant(&mut *self.session_grants.write().await, session, rp_id, operation);
+        if let Some(grant) = taken {
+            self.audit_log(&format!(
+                "Credential grant {} used by session {} for {} on rpId: {}",
+                grant.id, session, operation, rp_id
+            )).await;
+            return Ok(CredentialApproval::Grant(grant.id));
+        }
+
+        self.audit_log(&format!(
+            "Refused {} on rpId: {} for session {}; not authorized and no matching grant",
+            operation, rp_id, session
+        )).await;
+        Err(anyhow!(
+            "Session {} is not authorized for {} on {}; call passkey_authorize or redeem a grant",
+            session, operation, rp_id
+        ))
+    }
+
+    /// Audit a use refused before any session could be asked, e.g. because
+    /// the requesting tab has no single owning session
+    pub async fn refuse_credential_use(&self, rp_id: &str, operation: &str, reason: &str) {
+        self.audit_log(&format!("Refused {} on rpId: {}; {}", operation, rp_id, reason)).await;
+    }
+
+    /// Fold audit events older than the retention window into daily summaries
+    pub async fn compact_audit_log(&self, retention_days: u64) -> Result<crate::compaction::CompactionReport> {
+        let _guard = self.audit_lock.lock().await;
+        let data_dir = self
+            .audit_log_path
+            .parent()
+            .ok_or_else(|| anyhow!("Audit log has no parent directory"))?;
+        crate::compaction::compact(data_dir, crate::compaction::cutoff(chrono::Utc::now(), retention_days))
+    }
+
+    /// Record a history_retry re-run with its call lineage
+    pub async fn record_tool_retry(&self, session: &str, tool: &str, call_id: &str, retry_of: &str, root: &str, requested_by: &str) {
+        self.audit_log(&format!(
+            "Session {} retried {} call {} as {} (first call {}, requested by session {})",
+            session, tool, retry_of, call_id, root, requested_by
+        ))
+        .await;
+    }
+
+    /// Record a call session_inject ran as another session
+    pub async fn record_tool_injection(&self, session: &str, tool: &str, call_id: &str, injected_by: &str) {
+        self.audit_log(&format!(
+            "Session {} ran {} as call {}, injected by session {}",
+            session, tool, call_id, injected_by
+        ))
+        .await;
+    }
+
+    /// Lines of the current audit log that mention `session`
+    pub fn session_audit_events(&self, session: &str) -> Vec<String> {
+        let markers = [
+            format!("Session {} ", session),
+            format!("session {} ", session),
+            format!("(session: {})", session),
+        ];
+        fs::read_to_string(&self.audit_log_path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| markers.iter().any(|marker| line.contains(marker.as_str())))
+            .map(str::to_string)
+            .collect()
+    }
+
     /// Audit log
     async fn audit_log(&self, message: &str) {
+        let _guard = self.audit_lock.lock().await;
         let timestamp = chrono::Utc::now().to_rfc3339();
         let log_entry = format!("[{}] {}\n", timestamp, message);
 
+        match crate::quota::rotate_audit_log(&self.audit_log_path) {
+            Ok(Some(segment)) => {
+                info!("Rotated audit log to {:?}", segment);
+                if let Err(e) = crate::quota::enforce(crate::quota::Category::Audit) {
+                    warn!("Failed to enforce audit quota: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to rotate audit log: {}", e),
+        }
+
         if let Err(e) = fs::OpenOptions::new()
             .create(true)
             .append(true)
@@ -399,3 +1136,189 @@ impl CredentialStore {
         }
     }
 }
+
+// ============================================================================
+// Redaction
+// ============================================================================
+
+/// Mask a secret for display: backup codes keep their last two characters,
+/// security answers are fully masked.
+fn redact_value(kind: RecoverySecretKind, value: &str) -> String {
+    match kind {
+        RecoverySecretKind::BackupCode => {
+            let chars: Vec<char> = value.chars().collect();
+            if chars.len() <= 4 {
+                "*".repeat(chars.len())
+            } else {
+                let tail: String = chars[chars.len() - 2..].iter().collect();
+                format!("{}{}", "*".repeat(chars.len() - 2), tail)
+            }
+        }
+        RecoverySecretKind::SecurityAnswer => "********".to_string(),
+    }
+}
+
+/// Oldest recovery secret `take_recovery_secret` may release: an unused
+/// backup code, or a security answer whose label matches (case-insensitive)
+fn pick_recovery_secret<'a>(
+    secrets: &'a HashMap<String, RecoverySecret>,
+    rp_id: &str,
+    kind: RecoverySecretKind,
+    label: Option<&str>,
+) -> Option<&'a RecoverySecret> {
+    secrets
+        .values()
+        .filter(|s| s.rp_id == rp_id && s.kind == kind)
+        .filter(|s| match kind {
+            RecoverySecretKind::BackupCode => s.used_at.is_none(),
+            RecoverySecretKind::SecurityAnswer => label.is_none_or(|l| {
+                s.label.as_deref().is_some_and(|sl| sl.eq_ignore_ascii_case(l))
+            }),
+        })
+        .min_by(|a, b| (a.created, &a.id).cmp(&(b.created, &b.id)))
+}
+
+/// Hint listed for a recovery secret that no longer decrypts
+const UNREADABLE_HINT: &str = "(unreadable)";
+
+/// Listing entry for a secret; `value` is None when it failed to decrypt
+fn describe_recovery_secret(secret: &RecoverySecret, value: Option<&str>) -> RecoverySecretMetadata {
+    match value {
+        Some(value) => redact_recovery_secret(secret, value),
+        None => RecoverySecretMetadata {
+            hint: UNREADABLE_HINT.to_string(),
+            ..redact_recovery_secret(secret, "")
+        },
+    }
+}
+
+fn redact_recovery_secret(secret: &RecoverySecret, value: &str) -> RecoverySecretMetadata {
+    RecoverySecretMetadata {
+        id: secret.id.clone(),
+        rp_id: secret.rp_id.clone(),
+        kind: secret.kind,
+        label: secret.label.clone(),
+        hint: redact_value(secret.kind, value),
+        created: secret.created,
+        used_at: secret.used_at,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_code_redaction_keeps_tail() {
+        assert_eq!(redact_value(RecoverySecretKind::BackupCode, "abcd-1234"), "*******34");
+        assert_eq!(redact_value(RecoverySecretKind::BackupCode, "1234"), "****");
+    }
+
+    #[test]
+    fn test_grant_tokens_are_signed() {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"test key");
+        let grant = CredentialGrant {
+            id: "g1".to_string(),
+            rp_id: "example.com".to_string(),
+            operation: Some("get".to_string()),
+            issued_by: "tcp:127.0.0.1:5000".to_string(),
+            expires_at: unix_now() + 60,
+        };
+        let token = sign_grant(&key, &grant).unwrap();
+        assert_eq!(verify_grant(&key, &token).unwrap(), grant);
+
+        // A payload rewritten to another RP no longer matches its signature
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let forged = CredentialGrant { rp_id: "bank.example".to_string(), ..grant.clone() };
+        let forged_payload = BASE64_URL.encode(serde_json::to_vec(&forged).unwrap());
+        assert!(verify_grant(&key, &format!("{}{}.{}", GRANT_TOKEN_PREFIX, forged_payload, signature)).is_err());
+
+        let other_key = hmac::Key::new(hmac::HMAC_SHA256, b"other key");
+        assert!(verify_grant(&other_key, &token).is_err());
+        assert!(verify_grant(&key, "not a token").is_err());
+
+        assert!(grant.covers("example.com", "get"));
+        assert!(!grant.covers("example.com", "create"));
+        assert!(!grant.covers("bank.example", "get"));
+    }
+
+    #[test]
+    fn test_grant_is_taken_once_and_only_for_its_rp() {
+        let grant = CredentialGrant {
+            id: "g1".to_string(),
+            rp_id: "example.com".to_string(),
+            operation: Some("get".to_string()),
+            issued_by: "tcp:127.0.0.1:5000".to_string(),
+            expires_at: unix_now() + 60,
+        };
+        let mut grants = HashMap::from([("sub".to_string(), grant.clone())]);
+
+        assert_eq!(take_grant(&mut grants, "sub", "bank.example", "get"), None);
+        assert_eq!(take_grant(&mut grants, "sub", "example.com", "create"), None);
+        assert_eq!(take_grant(&mut grants, "other", "example.com", "get"), None);
+        assert_eq!(take_grant(&mut grants, "sub", "example.com", "get"), Some(grant.clone()));
+        assert_eq!(take_grant(&mut grants, "sub", "example.com", "get"), None);
+
+        let expired = CredentialGrant { expires_at: unix_now() - 1, ..grant };
+        let mut grants = HashMap::from([("sub".to_string(), expired)]);
+        assert_eq!(take_grant(&mut grants, "sub", "example.com", "get"), None);
+        assert!(grants.is_empty());
+    }
+
+    fn recovery_secret(id: &str, kind: RecoverySecretKind, label: Option<&str>, created: i64) -> RecoverySecret {
+        RecoverySecret {
+            id: id.to_string(),
+            rp_id: "example.com".to_string(),
+            kind,
+            label: label.map(str::to_string),
+            encrypted_value: Vec::new(),
+            nonce: Vec::new(),
+            created,
+            used_at: None,
+        }
+    }
+
+    #[test]
+    fn test_pick_recovery_secret_skips_used_codes() {
+        let mut first = recovery_secret("a", RecoverySecretKind::BackupCode, None, 1);
+        let second = recovery_secret("b", RecoverySecretKind::BackupCode, None, 2);
+        let answer = recovery_secret("c", RecoverySecretKind::SecurityAnswer, Some("First pet"), 0);
+        let mut secrets = HashMap::from([
+            ("a".to_string(), first.clone()),
+            ("b".to_string(), second),
+            ("c".to_string(), answer),
+        ]);
+
+        let pick = |secrets: &HashMap<String, RecoverySecret>, kind, label| {
+            pick_recovery_secret(secrets, "example.com", kind, label).map(|s| s.id.clone())
+        };
+        assert_eq!(pick(&secrets, RecoverySecretKind::BackupCode, None).as_deref(), Some("a"));
+        assert_eq!(pick(&secrets, RecoverySecretKind::SecurityAnswer, Some("first PET")).as_deref(), Some("c"));
+        assert_eq!(pick(&secrets, RecoverySecretKind::SecurityAnswer, Some("Street")), None);
+
+        first.used_at = Some(3);
+        secrets.insert("a".to_string(), first);
+        assert_eq!(pick(&secrets, RecoverySecretKind::BackupCode, None).as_deref(), Some("b"));
+        assert!(pick_recovery_secret(&secrets, "bank.example", RecoverySecretKind::BackupCode, None).is_none());
+    }
+
+    #[test]
+    fn test_undecryptable_secret_is_listed_as_unreadable() {
+        let secret = recovery_secret("a", RecoverySecretKind::BackupCode, None, 1);
+        assert_eq!(describe_recovery_secret(&secret, Some("abcd-1234")).hint, "*******34");
+        let unreadable = describe_recovery_secret(&secret, None);
+        assert_eq!(unreadable.hint, UNREADABLE_HINT);
+        assert_eq!(unreadable.id, "a");
+    }
+
+    #[test]
+    fn test_security_answer_fully_redacted() {
+        let hint = redact_value(RecoverySecretKind::SecurityAnswer, "Springfield");
+        assert!(!hint.contains("Spring"));
+        assert_eq!(hint, "********");
+    }
+}
@@ -0,0 +1,266 @@
+/*!
+ * JavaScript Dialogs
+ *
+ * Once `browser_handle_dialogs` is on for a tab, the extension reports
+ * alert/confirm/prompt/beforeunload dialogs as `dialog` events. Dialogs the
+ * policy answers are only logged. A dialog left open for the agent blocks
+ * the page's script thread, so commands for that tab fail fast with a
+ * "JavaScript dialog open" error until `browser_respond_dialog` answers it,
+ * instead of hanging until the command timeout.
+ */
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::sync::RwLock;
+
+/// Prefix of the error returned for commands blocked by an open dialog
+pub const DIALOG_OPEN_ERROR: &str = "JavaScript dialog open";
+
+/// JSON-RPC error code for commands blocked by an open dialog
+pub const DIALOG_OPEN_CODE: i64 = -32014;
+
+/// Dialogs kept for browser_list_dialogs
+const MAX_DIALOGS: usize = 100;
+
+/// Policies browser_handle_dialogs accepts
+pub const DIALOG_MODES: &[&str] = &["accept", "dismiss", "manual", "off"];
+
+/// Commands that never wait on the page's script thread and still run while
+/// a dialog is open
+const DIALOG_SAFE_COMMANDS: &[&str] = &[
+    "dialog_policy",
+    "dialog_respond",
+    "active_tab",
+    "cookies_get",
+    "cookies_set",
+    "cookies_clear",
+    "clear_site_data",
+    "downloads_list",
+    "throttle",
+    "set_geolocation",
+    "screencast_start",
+    "screencast_stop",
+];
+
+pub fn blocked_by_dialog(method: &str) -> bool {
+    !DIALOG_SAFE_COMMANDS.contains(&method)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dialog {
+    pub id: u64,
+    pub tab_id: i64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Unix milliseconds
+    pub opened_at: u64,
+    /// Whether the tab was the one commands without a tabId go to
+    pub active: bool,
+    /// "policy" when answered automatically, otherwise who closed it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handled_by: Option<String>,
+    /// Whether the dialog was accepted (None while open)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<u64>,
+}
+
+impl Dialog {
+    pub fn is_open(&self) -> bool {
+        self.closed_at.is_none()
+    }
+
+    /// Whether a command for `tab_id` (None = the active tab) would hit this dialog
+    pub fn blocks(&self, tab_id: Option<i64>) -> bool {
+        self.is_open()
+            && match tab_id {
+                Some(tab_id) => tab_id == self.tab_id,
+                None => self.active,
+            }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    dialogs: VecDeque<Dialog>,
+    next_id: u64,
+}
+
+#[derive(Default)]
+pub struct DialogLog {
+    inner: RwLock<Inner>,
+}
+
+impl DialogLog {
+    /// Apply a `dialog` event from the extension. Returns the dialog when one
+    /// opened and was left for the agent to answer.
+    pub async fn record(&self, data: &serde_json::Value) -> Result<Option<Dialog>, String> {
+        let tab_id = data
+            .get("tabId")
+            .and_then(|v| v.as_i64())
+            .ok_or("dialog event without tabId")?;
+        let str_field = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let mut inner = self.inner.write().await;
+
+        match data.get("phase").and_then(|v| v.as_str()) {
+            Some("opened") => {
+                // A tab shows one dialog at a time; an unreported close was lost
+                for dialog in inner.dialogs.iter_mut().filter(|d| d.tab_id == tab_id && d.is_open()) {
+                    dialog.closed_at = Some(now);
+                }
+                inner.next_id += 1;
+                let handled_by = str_field("handledBy");
+                let dialog = Dialog {
+                    id: inner.next_id,
+                    tab_id,
+                    kind: str_field("type").unwrap_or_else(|| "alert".to_string()),
+                    message: str_field("message").unwrap_or_default(),
+                    default_prompt: str_field("defaultPrompt").filter(|s| !s.is_empty()),
+                    url: str_field("url"),
+                    opened_at: now,
+                    active: data.get("active").and_then(|v| v.as_bool()).unwrap_or(true),
+                    accepted: handled_by.as_ref().and(data.get("accepted").and_then(|v| v.as_bool())),
+                    prompt_text: handled_by.as_ref().and(str_field("promptText")),
+                    closed_at: handled_by.as_ref().map(|_| now),
+                    handled_by,
+                };
+                if inner.dialogs.len() == MAX_DIALOGS {
+                    inner.dialogs.pop_front();
+                }
+                inner.dialogs.push_back(dialog.clone());
+                Ok(dialog.is_open().then_some(dialog))
+            }
+            Some("closed") => {
+                if let Some(dialog) = inner.dialogs.iter_mut().rev().find(|d| d.tab_id == tab_id && d.is_open()) {
+                    dialog.closed_at = Some(now);
+                    dialog.accepted = data.get("accepted").and_then(|v| v.as_bool());
+                    dialog.prompt_text = str_field("promptText").filter(|_| dialog.kind == "prompt");
+                    dialog.handled_by = Some(str_field("handledBy").unwrap_or_else(|| "user".to_string()));
+                }
+                Ok(None)
+            }
+            other => Err(format!("dialog event with unknown phase {:?}", other)),
+        }
+    }
+
+    /// The open dialog a command for `tab_id` (None = the active tab) would hit
+    pub async fn blocking(&self, tab_id: Option<i64>) -> Option<Dialog> {
+        self.inner.read().await.dialogs.iter().rev().find(|d| d.blocks(tab_id)).cloned()
+    }
+
+    /// Most recent dialogs first, optionally for one tab
+    pub async fn list(&self, tab_id: Option<i64>, limit: usize) -> Vec<Dialog> {
+        self.inner
+            .read()
+            .await
+            .dialogs
+            .iter()
+            .rev()
+            .filter(|d| tab_id.is_none_or(|tab_id| d.tab_id == tab_id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Close whatever was open on a tab that went away
+    pub async fn forget(&self, tab_id: i64) {
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        for dialog in self.inner.write().await.dialogs.iter_mut().filter(|d| d.tab_id == tab_id && d.is_open()) {
+            dialog.closed_at = Some(now);
+            dialog.handled_by = Some("tab_closed".to_string());
+        }
+    }
+}
+
+pub fn dialog_open_error(dialog: &Dialog) -> String {
+    format!(
+        "{}: {}({}) on tab {} is waiting for an answer; reply with browser_respond_dialog",
+        DIALOG_OPEN_ERROR,
+        dialog.kind,
+        serde_json::Value::String(dialog.message.chars().take(200).collect()),
+        dialog.tab_id
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_manual_dialog_blocks_until_closed() {
+        let log = DialogLog::default();
+        let opened = log
+            .record(&json!({ "phase": "opened", "tabId": 3, "type": "confirm", "message": "Delete?", "active": true }))
+            .await
+            .unwrap()
+            .expect("manual dialog stays open");
+        assert_eq!(opened.kind, "confirm");
+        assert!(log.blocking(None).await.is_some());
+        assert!(log.blocking(Some(3)).await.is_some());
+        assert!(log.blocking(Some(4)).await.is_none());
+        assert!(dialog_open_error(&opened).starts_with(DIALOG_OPEN_ERROR));
+
+        log.record(&json!({ "phase": "closed", "tabId": 3, "accepted": true, "handledBy": "agent" }))
+            .await
+            .unwrap();
+        assert!(log.blocking(None).await.is_none());
+        let listed = log.list(None, 10).await;
+        assert_eq!(listed[0].accepted, Some(true));
+        assert_eq!(listed[0].handled_by.as_deref(), Some("agent"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_dialogs_are_logged_closed() {
+        let log = DialogLog::default();
+        let open = log
+            .record(&json!({
+                "phase": "opened", "tabId": 1, "type": "prompt", "message": "Name?",
+                "handledBy": "policy", "accepted": true, "promptText": "Ada"
+            }))
+            .await
+            .unwrap();
+        assert!(open.is_none());
+        assert!(log.blocking(Some(1)).await.is_none());
+        assert_eq!(log.list(Some(1), 10).await[0].prompt_text.as_deref(), Some("Ada"));
+        assert!(log.list(Some(2), 10).await.is_empty());
+
+        assert!(log.record(&json!({ "phase": "bogus", "tabId": 1 })).await.is_err());
+        assert!(log.record(&json!({ "phase": "opened" })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_background_tab_dialog_and_forget() {
+        let log = DialogLog::default();
+        log.record(&json!({ "phase": "opened", "tabId": 7, "type": "alert", "message": "Hi", "active": false }))
+            .await
+            .unwrap();
+        assert!(log.blocking(None).await.is_none());
+        assert!(log.blocking(Some(7)).await.is_some());
+        log.forget(7).await;
+        assert!(log.blocking(Some(7)).await.is_none());
+        assert_eq!(log.list(None, 1).await[0].handled_by.as_deref(), Some("tab_closed"));
+    }
+
+    #[test]
+    fn test_blocked_commands() {
+        assert!(blocked_by_dialog("click"));
+        assert!(blocked_by_dialog("get_text"));
+        assert!(!blocked_by_dialog("dialog_respond"));
+        assert!(!blocked_by_dialog("cookies_get"));
+    }
+}
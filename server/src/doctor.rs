@@ -0,0 +1,559 @@
+/*!
+ * Environment Doctor
+ *
+ * `agent-browser-server doctor` checks what usually breaks a first run —
+ * ports already taken, a data directory with the wrong owner or mode, a
+ * native messaging manifest pointing at a missing binary, an extension that
+ * never connects, data written by a newer build, a master key that no longer
+ * opens the stored secrets — and prints a fix for each problem. It exits
+ * non-zero when any check fails.
+ */
+
+use anyhow::{anyhow, Context, Result};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use crate::auth;
+use crate::config::{self, ServerConfig};
+use crate::credential_store::CredentialStore;
+use crate::migrations;
+use crate::storage;
+
+pub const NMH_NAME: &str = "com.agentbrowser.native";
+
+/// How long to wait on a running server before reporting it unresponsive
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Files under the data directory that must stay owner-only
+const SECRET_FILES: &[&str] = &[
+    "master.key",
+    "credentials.json",
+    "recovery_secrets.json",
+    auth::TOKEN_FILE,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check, print the report, and return whether none failed
+pub async fn run(config: &ServerConfig) -> bool {
+    let mut checks = check_data_dir();
+    let running = config::ports_path()
+        .ok()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok());
+
+    let mut listeners = vec![
+        ("MCP TCP", "tcp", &config.tcp_addr, "--tcp-port"),
+        ("WebSocket", "ws", &config.ws_addr, "--ws-port"),
+    ];
+    if config.http {
+        listeners.push(("MCP HTTP", "http", &config.http_addr, "--http-port"));
+    }
+    for (label, key, addr, flag) in listeners {
+        let running_port = running
+            .as_ref()
+            .and_then(|r| r.pointer(&format!("/{}/port", key)))
+            .and_then(|p| p.as_u64());
+        checks.push(check_port(label, addr, flag, running_port));
+    }
+
+    checks.extend(check_nmh_manifests(&nmh_manifest_dirs()));
+    checks.push(check_extension(running.as_ref()).await);
+    checks.push(check_data_formats());
+    checks.push(check_credential_store().await);
+
+    print_report(&checks);
+    checks.iter().all(|c| c.status != Status::Fail)
+}
+
+fn print_report(checks: &[Check]) {
+    for check in checks {
+        let tag = match check.status {
+            Status::Ok => "[ok]  ",
+            Status::Warn => "[warn]",
+            Status::Fail => "[FAIL]",
+        };
+        println!("{} {}: {}", tag, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("       fix: {}", fix);
+        }
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    println!("{} checks, {} failed, {} warnings", checks.len(), failed, warned);
+}
+
+// ============================================================================
+// Data directory
+// ============================================================================
+
+fn check_data_dir() -> Vec<Check> {
+    const NAME: &str = "Data directory";
+    let dir = match storage::data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            return vec![Check::fail(
+                NAME,
+                format!("cannot create ~/.agent-browser: {}", e),
+                "make sure $HOME is set and writable by this user",
+            )]
+        }
+    };
+
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    if let Err(e) = std::fs::write(&probe, b"ok").and_then(|_| std::fs::remove_file(&probe)) {
+        return vec![Check::fail(
+            NAME,
+            format!("{} is not writable: {}", dir.display(), e),
+            format!("chown -R $(id -un) {}", dir.display()),
+        )];
+    }
+
+    let mut checks = vec![check_mode(NAME, &dir, 0o700)];
+    for file in SECRET_FILES {
+        let path = dir.join(file);
+        if path.exists() {
+            checks.push(check_mode(&format!("Secret file {}", file), &path, 0o600));
+        }
+    }
+    checks
+}
+
+/// Fail when group or others have any access beyond `expected`
+#[cfg(unix)]
+fn check_mode(name: &str, path: &Path, expected: u32) -> Check {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = match std::fs::metadata(path) {
+        Ok(meta) => meta.permissions().mode() & 0o777,
+        Err(e) => {
+            return Check::fail(
+                name,
+                format!("cannot stat {}: {}", path.display(), e),
+                "check the file exists",
+            )
+        }
+    };
+    if mode & 0o077 == 0 {
+        Check::ok(name, format!("{} is owner-only ({:o})", path.display(), mode))
+    } else {
+        Check::fail(
+            name,
+            format!("{} is readable by other users ({:o})", path.display(), mode),
+            format!("chmod {:o} {}", expected, path.display()),
+        )
+    }
+}
+
+#[cfg(not(unix))]
+fn check_mode(name: &str, path: &Path, _expected: u32) -> Check {
+    Check::ok(name, format!("{} exists", path.display()))
+}
+
+// ============================================================================
+// Ports
+// ============================================================================
+
+/// A port is fine when it's free, or when it's held by the server recorded in ports.json
+fn check_port(label: &str, addr: &str, flag: &str, running_port: Option<u64>) -> Check {
+    let name = format!("{} port", label);
+    let parsed: SocketAddr = match addr.parse() {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            return Check::fail(
+                name,
+                format!("'{}' is not an ip:port address", addr),
+                format!("pass {} or a valid address", flag),
+            )
+        }
+    };
+    if parsed.port() == 0 {
+        return Check::ok(name, "port 0, the OS will pick a free port");
+    }
+    match std::net::TcpListener::bind(parsed) {
+        Ok(_) => Check::ok(name, format!("{} is free", addr)),
+        Err(_) if running_port == Some(parsed.port() as u64) => {
+            Check::ok(name, format!("{} is in use by the running agent-browser-server", addr))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => Check::fail(
+            name,
+            format!("{} is in use by another process", addr),
+            format!(
+                "stop it (`lsof -i :{}` shows which) or use another port with {} <port> (0 picks a free one)",
+                parsed.port(),
+                flag
+            ),
+        ),
+        Err(e) => Check::fail(
+            name,
+            format!("cannot bind {}: {}", addr, e),
+            "bind a loopback address you're allowed to use",
+        ),
+    }
+}
+
+// ============================================================================
+// Native messaging host
+// ============================================================================
+
+/// Chrome's per-user NativeMessagingHosts directories, plus the dev profile the install script uses
+fn nmh_manifest_dirs() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let mut dirs = vec![home.join(".chrome-profiles/agent-browser-dev/NativeMessagingHosts")];
+    if cfg!(target_os = "macos") {
+        for browser in ["Chrome", "Chrome Canary", "Chrome Dev", "Chrome Beta"] {
+            dirs.push(
+                home.join("Library/Application Support/Google")
+                    .join(browser)
+                    .join("NativeMessagingHosts"),
+            );
+        }
+    } else {
+        for browser in [
+            "google-chrome",
+            "google-chrome-beta",
+            "google-chrome-unstable",
+            "chromium",
+        ] {
+            dirs.push(home.join(".config").join(browser).join("NativeMessagingHosts"));
+        }
+    }
+    dirs
+}
+
+fn check_nmh_manifests(dirs: &[PathBuf]) -> Vec<Check> {
+    let manifests: Vec<PathBuf> = dirs
+        .iter()
+        .map(|d| d.join(format!("{}.json", NMH_NAME)))
+        .filter(|p| p.exists())
+        .collect();
+    if manifests.is_empty() {
+        return vec![Check::fail(
+            "Native messaging host",
+            format!("no {}.json manifest found", NMH_NAME),
+            "run ./scripts/install-nmh.sh from the repository root",
+        )];
+    }
+    manifests.iter().map(|path| check_nmh_manifest(path)).collect()
+}
+
+fn check_nmh_manifest(path: &Path) -> Check {
+    let name = format!("NMH manifest {}", path.display());
+    let manifest: serde_json::Value = match std::fs::read(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| serde_json::from_slice(&data).map_err(anyhow::Error::from))
+    {
+        Ok(manifest) => manifest,
+        Err(e) => return Check::fail(name, format!("unreadable: {}", e), "re-run ./scripts/install-nmh.sh"),
+    };
+
+    if manifest["name"] != NMH_NAME {
+        return Check::fail(
+            name,
+            format!("name is {} instead of {}", manifest["name"], NMH_NAME),
+            "re-run ./scripts/install-nmh.sh",
+        );
+    }
+    let Some(binary) = manifest["path"].as_str().map(PathBuf::from) else {
+        return Check::fail(name, "has no \"path\"", "re-run ./scripts/install-nmh.sh");
+    };
+    if !is_executable(&binary) {
+        return Check::fail(
+            name,
+            format!("points at {}, which is missing or not executable", binary.display()),
+            "re-run ./scripts/install-nmh.sh to install agent-browser-nmh",
+        );
+    }
+    // The shim launches the server binary that sits next to it
+    let server = binary.with_file_name("agent-browser-server");
+    if !is_executable(&server) {
+        return Check::fail(
+            name,
+            format!("{} has no agent-browser-server next to it", binary.display()),
+            format!("install the server binary at {}", server.display()),
+        );
+    }
+    if manifest["allowed_origins"].as_array().is_none_or(|o| o.is_empty()) {
+        return Check::fail(
+            name,
+            "allows no extension origins",
+            "re-run ./scripts/install-nmh.sh to add the extension ID",
+        );
+    }
+    Check::ok(name, format!("points at {}", binary.display()))
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+// ============================================================================
+// Extension connection
+// ============================================================================
+
+/// Ask the running server (found through ports.json) whether the extension is connected
+async fn check_extension(running: Option<&serde_json::Value>) -> Check {
+    const NAME: &str = "Extension";
+    let Some(port) = running.and_then(|r| r.pointer("/tcp/port")).and_then(|p| p.as_u64()) else {
+        return Check::warn(
+            NAME,
+            "no server has run yet, so the extension connection can't be checked",
+            "start Chrome with the extension loaded (or agent-browser-server), then re-run doctor",
+        );
+    };
+    let host = running
+        .and_then(|r| r.pointer("/tcp/host"))
+        .and_then(|h| h.as_str())
+        .unwrap_or("127.0.0.1");
+
+    match timeout(PROBE_TIMEOUT, server_metrics(host, port as u16)).await {
+        Ok(Ok(metrics)) if metrics["extensionConnected"] == true => Check::ok(NAME, "connected to the running server"),
+        Ok(Ok(_)) => Check::fail(
+            NAME,
+            "the server is running but the extension is not connected",
+            "open chrome://extensions, make sure Agent Browser is enabled, and reload it",
+        ),
+        Ok(Err(e)) => Check::warn(
+            NAME,
+            format!("no server answering on {}:{} ({})", host, port, e),
+            "start Chrome with the extension loaded (or agent-browser-server), then re-run doctor",
+        ),
+        Err(_) => Check::fail(
+            NAME,
+            format!(
+                "the server on {}:{} did not answer within {:?}",
+                host, port, PROBE_TIMEOUT
+            ),
+            "restart agent-browser-server",
+        ),
+    }
+}
+
+/// Call `server_metrics` over MCP TCP, authenticating with the token file
+async fn server_metrics(host: &str, port: u16) -> Result<serde_json::Value> {
+    let stream = TcpStream::connect((host, port)).await?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let token = std::fs::read_to_string(auth::token_path()?).unwrap_or_default();
+    let requests = [
+        serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": auth::AUTHENTICATE_METHOD, "params": {"token": token.trim()}}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": {"name": "server_metrics", "arguments": {}}}),
+    ];
+    for request in &requests {
+        writer.write_all(format!("{}\n", request).as_bytes()).await?;
+    }
+
+    while let Some(line) = lines.next_line().await? {
+        let response: serde_json::Value = serde_json::from_str(&line).context("Invalid response from server")?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{}", error["message"].as_str().unwrap_or("request failed")));
+        }
+        if response["id"] == 2 {
+            return Ok(response["result"].clone());
+        }
+    }
+    Err(anyhow!("connection closed"))
+}
+
+// ============================================================================
+// Credential store
+// ============================================================================
+
+fn check_data_formats() -> Check {
+    const NAME: &str = "Data formats";
+    let Ok(data_dir) = storage::data_dir() else {
+        return Check::ok(NAME, "no data directory yet");
+    };
+    match migrations::pending(&data_dir, migrations::FORMATS) {
+        Ok(pending) if pending.is_empty() => Check::ok(NAME, "every store is in this build's format"),
+        Ok(pending) => Check::warn(
+            NAME,
+            format!(
+                "will migrate on next start: {}",
+                pending
+                    .iter()
+                    .map(|p| format!("{} (v{} -> v{})", p.path, p.from, p.to))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            format!("nothing to do; the current copies are saved to {}/ first", migrations::BACKUP_DIR),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            e.to_string(),
+            format!("run a newer agent-browser, or restore the store from {}/", migrations::BACKUP_DIR),
+        ),
+    }
+}
+
+async fn check_credential_store() -> Check {
+    const NAME: &str = "Credential store";
+    // Opening the store would generate a master key; don't create one from doctor
+    if !storage::data_dir().is_ok_and(|d| d.join("master.key").exists()) {
+        return Check::ok(NAME, "no master key yet; one is created on first start");
+    }
+    let store = match CredentialStore::new().await {
+        Ok(store) => store,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("cannot be opened: {}", e),
+                "restore master.key and credentials.json from backup; moving both aside starts an empty store",
+            )
+        }
+    };
+    match store.verify_decryption().await {
+        Ok((credentials, secrets)) => Check::ok(
+            NAME,
+            format!(
+                "{} credentials and {} recovery secrets, all decrypt",
+                credentials, secrets
+            ),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("master.key does not decrypt the stored secrets: {}", e),
+            "restore the master.key these secrets were written with",
+        ),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ab-doctor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_port_check_accepts_free_and_own_ports() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = taken.local_addr().unwrap().to_string();
+        let port = taken.local_addr().unwrap().port() as u64;
+
+        assert_eq!(check_port("WebSocket", &addr, "--ws-port", None).status, Status::Fail);
+        assert_eq!(
+            check_port("WebSocket", &addr, "--ws-port", Some(port)).status,
+            Status::Ok
+        );
+        assert_eq!(
+            check_port("WebSocket", "127.0.0.1:0", "--ws-port", None).status,
+            Status::Ok
+        );
+        drop(taken);
+        assert_eq!(check_port("WebSocket", &addr, "--ws-port", None).status, Status::Ok);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nmh_manifest_must_point_at_installed_binaries() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir();
+        let manifest_path = dir.join(format!("{}.json", NMH_NAME));
+        let write_manifest = |path: &Path| {
+            let manifest = serde_json::json!({
+                "name": NMH_NAME,
+                "path": path,
+                "type": "stdio",
+                "allowed_origins": ["chrome-extension://abc/"],
+            });
+            std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+        };
+
+        assert_eq!(check_nmh_manifests(std::slice::from_ref(&dir))[0].status, Status::Fail);
+
+        let shim = dir.join("agent-browser-nmh");
+        write_manifest(&shim);
+        assert_eq!(check_nmh_manifest(&manifest_path).status, Status::Fail);
+
+        for binary in [&shim, &dir.join("agent-browser-server")] {
+            std::fs::write(binary, "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert_eq!(check_nmh_manifest(&manifest_path).status, Status::Ok);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mode_check_flags_group_access() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir();
+        let file = dir.join("master.key");
+        std::fs::write(&file, [0u8; 32]).unwrap();
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(check_mode("key", &file, 0o600).status, Status::Ok);
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let check = check_mode("key", &file, 0o600);
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.fix.unwrap().starts_with("chmod 600"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
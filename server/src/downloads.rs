@@ -0,0 +1,212 @@
+/*!
+ * Browser Downloads
+ *
+ * `browser_list_downloads` is forwarded to the extension, which reads
+ * `chrome.downloads`. `browser_wait_for_download` runs here: it polls the
+ * same `downloads_list` command until the newest matching download
+ * finishes, then optionally moves the file into the session's artifact
+ * directory (`downloads/`) or to a path inside the session's roots. The
+ * browser runs on the server's machine, so the path Chrome reports can be
+ * moved directly.
+ */
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{artifacts, storage};
+
+pub const DEFAULT_WAIT_MS: u64 = 30_000;
+pub const MAX_WAIT_MS: u64 = 600_000;
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Without an id, downloads started this long before the call still match,
+/// so a wait issued just after the click that started one finds it
+pub const DEFAULT_LOOKBACK_SECS: u64 = 60;
+pub const DOWNLOAD_STATES: &[&str] = &["in_progress", "complete", "interrupted"];
+
+/// A download as reported by the extension's `downloads_list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Download {
+    pub id: u64,
+    pub url: String,
+    /// Absolute path on disk (empty until Chrome has picked one)
+    pub filename: String,
+    pub state: String,
+    #[serde(default)]
+    pub mime: Option<String>,
+    #[serde(default)]
+    pub bytes_received: u64,
+    /// -1 when the size is unknown
+    #[serde(default)]
+    pub total_bytes: i64,
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub end_time: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Which download `browser_wait_for_download` waits for
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaitQuery {
+    pub id: Option<u64>,
+    pub url_contains: Option<String>,
+    pub filename_contains: Option<String>,
+    /// RFC 3339; ignored when waiting for a specific id
+    pub started_after: String,
+    pub timeout: Duration,
+}
+
+impl WaitQuery {
+    pub fn from_arguments(arguments: &serde_json::Value) -> Result<Self, String> {
+        let text = |key: &str| -> Result<Option<String>, String> {
+            match arguments.get(key) {
+                None => Ok(None),
+                Some(v) => v
+                    .as_str()
+                    .filter(|s| !s.is_empty())
+                    .map(|s| Some(s.to_string()))
+                    .ok_or(format!("{} must be a non-empty string", key)),
+            }
+        };
+        let id = match arguments.get("id") {
+            None => None,
+            Some(v) => Some(v.as_u64().ok_or("id must be a download id from browser_list_downloads")?),
+        };
+        let timeout_ms = arguments.get("timeoutMs").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_WAIT_MS);
+        if timeout_ms == 0 || timeout_ms > MAX_WAIT_MS {
+            return Err(format!("timeoutMs must be between 1 and {}", MAX_WAIT_MS));
+        }
+        let lookback = arguments
+            .get("sinceSeconds")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_LOOKBACK_SECS);
+        let started_after = chrono::Utc::now() - chrono::Duration::seconds(lookback as i64);
+        Ok(Self {
+            id,
+            url_contains: text("urlContains")?,
+            filename_contains: text("filenameContains")?,
+            started_after: started_after.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+
+    /// `downloads_list` parameters selecting the newest matching download
+    pub fn list_params(&self) -> serde_json::Value {
+        let mut params = serde_json::json!({ "limit": 1 });
+        match self.id {
+            Some(id) => params["id"] = id.into(),
+            None => params["startedAfter"] = self.started_after.clone().into(),
+        }
+        if let Some(url) = &self.url_contains {
+            params["urlContains"] = url.clone().into();
+        }
+        if let Some(filename) = &self.filename_contains {
+            params["filenameContains"] = filename.clone().into();
+        }
+        params
+    }
+}
+
+/// The newest download in a `downloads_list` result, or None when nothing
+/// matched yet
+pub fn newest(result: &serde_json::Value) -> Result<Option<Download>, String> {
+    let Some(first) = result.get("downloads").and_then(|d| d.as_array()).and_then(|d| d.first()) else {
+        return Ok(None);
+    };
+    serde_json::from_value(first.clone())
+        .map(Some)
+        .map_err(|e| format!("Extension returned an invalid download: {}", e))
+}
+
+fn file_name(download: &Download) -> Result<String> {
+    Path::new(&download.filename)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Download {} has no file name", download.id))
+}
+
+/// `downloads/<file>` in the session's artifact directory; the download id is
+/// added to the name when that file already exists
+pub fn artifact_path(session: &str, download: &Download) -> Result<PathBuf> {
+    let dir = storage::ensure_private_dir(&artifacts::session_dir(session)?.join("downloads"))?;
+    let name = file_name(download)?;
+    let path = dir.join(&name);
+    if !path.exists() {
+        return Ok(path);
+    }
+    Ok(dir.join(format!("{}-{}", download.id, name)))
+}
+
+/// Move a finished download, copying across filesystems; never overwrites
+pub fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        return Err(anyhow!("{} already exists", to.display()));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).map_err(|e| anyhow!("Failed to move {}: {}", from.display(), e))?;
+        std::fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_query() {
+        let query = WaitQuery::from_arguments(&serde_json::json!({ "filenameContains": ".csv" })).unwrap();
+        let params = query.list_params();
+        assert_eq!(params["filenameContains"], ".csv");
+        assert_eq!(params["limit"], 1);
+        assert!(params.get("startedAfter").is_some() && params.get("id").is_none());
+        assert_eq!(query.timeout, Duration::from_millis(DEFAULT_WAIT_MS));
+
+        // A specific download is found however long ago it started
+        let params = WaitQuery::from_arguments(&serde_json::json!({ "id": 7 })).unwrap().list_params();
+        assert_eq!(params["id"], 7);
+        assert!(params.get("startedAfter").is_none());
+
+        assert!(WaitQuery::from_arguments(&serde_json::json!({ "id": "7" })).is_err());
+        assert!(WaitQuery::from_arguments(&serde_json::json!({ "urlContains": "" })).is_err());
+        assert!(WaitQuery::from_arguments(&serde_json::json!({ "timeoutMs": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_newest_and_move() {
+        assert!(newest(&serde_json::json!({ "downloads": [] })).unwrap().is_none());
+
+        let dir = std::env::temp_dir().join(format!("agent-browser-downloads-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("report.csv");
+        std::fs::write(&source, "a,b\n").unwrap();
+
+        let result = serde_json::json!({ "downloads": [{
+            "id": 3, "url": "https://example.com/export", "filename": source.display().to_string(),
+            "state": "complete", "bytesReceived": 4, "totalBytes": 4
+        }] });
+        let download = newest(&result).unwrap().unwrap();
+        assert_eq!(file_name(&download).unwrap(), "report.csv");
+
+        let target = dir.join("kept").join("report.csv");
+        move_file(&source, &target).unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "a,b\n");
+        assert!(!source.exists());
+
+        // Never overwrites
+        std::fs::write(&source, "new").unwrap();
+        assert!(move_file(&source, &target).is_err());
+        assert!(source.exists());
+    }
+}
@@ -0,0 +1,82 @@
+/*!
+ * Batch Element Screenshots
+ *
+ * `browser_screenshot_elements` captures many elements in one extension
+ * command instead of one round trip each. The background script works down
+ * the page, capturing once per viewport and cropping every element that
+ * fits, within a time budget derived from the command timeout. With `save`,
+ * the server writes the crops to `elements/<time>/` in the session's
+ * artifacts and returns their paths instead of base64 data.
+ */
+
+use anyhow::Result;
+use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+
+use crate::{artifacts, storage};
+
+pub const MAX_SELECTORS: usize = 50;
+pub const DEFAULT_MAX_ELEMENTS: u64 = 20;
+pub const MAX_ELEMENTS: u64 = 100;
+pub const MAX_PADDING: u64 = 100;
+
+/// Part of the command timeout kept back for the last crop and the reply
+pub const BUDGET_HEADROOM_MS: u64 = 3000;
+
+/// "03-div.product-card-2.png": position in the batch, the element, and its
+/// index among the selector's matches
+pub fn file_name(position: usize, element: &str, index: u64) -> String {
+    let mut slug: String = element
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    slug.truncate(40);
+    let slug = slug.trim_matches(|c| c == '-' || c == '.');
+    format!("{:02}-{}-{}.png", position + 1, if slug.is_empty() { "element" } else { slug }, index)
+}
+
+/// Write each image's data to `elements/<time>/`, replacing it with `path`
+/// and `bytes`; returns the directory
+pub fn save(session: &str, result: &mut serde_json::Value) -> Result<String> {
+    let dir = storage::ensure_private_dir(
+        &artifacts::session_dir(session)?
+            .join("elements")
+            .join(chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string()),
+    )?;
+    let images = result
+        .get_mut("images")
+        .and_then(|v| v.as_array_mut())
+        .map(std::mem::take)
+        .unwrap_or_default();
+    let mut saved = Vec::with_capacity(images.len());
+    for (position, mut image) in images.into_iter().enumerate() {
+        let Some(obj) = image.as_object_mut() else { continue };
+        let Some(data) = obj.remove("data") else { continue };
+        let bytes = BASE64.decode(data.as_str().unwrap_or_default())?;
+        let element = obj.get("element").and_then(|v| v.as_str()).unwrap_or_default();
+        let index = obj.get("index").and_then(|v| v.as_u64()).unwrap_or(0);
+        let path = dir.join(file_name(position, element, index));
+        storage::write_private_file(&path, &bytes)?;
+        obj.insert("path".to_string(), path.display().to_string().into());
+        obj.insert("bytes".to_string(), bytes.len().into());
+        saved.push(image);
+    }
+    result["images"] = saved.into();
+    Ok(dir.display().to_string())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name(2, "div.product-card", 4), "03-div.product-card-4.png");
+        assert_eq!(file_name(0, "a#Buy Now", 0), "01-a-buy-now-0.png");
+        assert_eq!(file_name(0, "", 0), "01-element-0.png");
+        assert_eq!(file_name(9, &"x".repeat(80), 1).len(), "10--1.png".len() + 40);
+    }
+}
@@ -0,0 +1,254 @@
+/*!
+ * Extension-Registered Tools
+ *
+ * On connect the extension sends a `register_tools` event listing the
+ * commands it handles, each as an MCP tool definition plus the extension
+ * `command` it maps to. That set replaces the built-in forwarded tools in
+ * tools/list and in tools/call routing, so the server lists what the
+ * connected extension actually supports. Server-implemented tools can't be
+ * overridden. Until an extension registers, the built-in mapping is used.
+ *
+ * It also sends a `capabilities` event: its version and every command it
+ * handles with that command's version. Tools whose commands (forwarded, or
+ * sent by a server-implemented tool) are missing or older than required are
+ * dropped from tools/list and refused by tools/call with a message naming
+ * the command, instead of failing inside an older extension. Without the
+ * event (extensions predating it) every tool is assumed to work.
+ */
+
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::mcp::tools;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredTool {
+    pub name: String,
+    pub command: String,
+    /// Definition served by tools/list (without the `command` field)
+    pub definition: Value,
+}
+
+/// Validate a `register_tools` payload: `{"tools": [{name, command?, description?, inputSchema}]}`.
+/// Entries that are malformed or shadow a server tool are skipped and reported.
+pub fn parse_registration(data: &Value) -> Result<(Vec<RegisteredTool>, Vec<String>), String> {
+    let entries = data
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .ok_or("register_tools needs a tools array")?;
+
+    let mut registered: Vec<RegisteredTool> = Vec::with_capacity(entries.len());
+    let mut skipped = Vec::new();
+    for entry in entries {
+        let Some(name) = entry.get("name").and_then(|n| n.as_str()).filter(|n| !n.is_empty()) else {
+            skipped.push(format!("tool without a name: {}", entry));
+            continue;
+        };
+        if tools::is_server_tool(name) {
+            skipped.push(format!("{} is implemented by the server", name));
+            continue;
+        }
+        if registered.iter().any(|tool| tool.name == name) {
+            skipped.push(format!("{} is registered twice", name));
+            continue;
+        }
+        if entry.pointer("/inputSchema/type").and_then(|t| t.as_str()) != Some("object") {
+            skipped.push(format!("{} needs an object inputSchema", name));
+            continue;
+        }
+
+        let command = entry.get("command").and_then(|c| c.as_str()).unwrap_or(name);
+        let mut definition = serde_json::json!({
+            "name": name,
+            "description": entry.get("description").and_then(|d| d.as_str()).unwrap_or_default(),
+            "inputSchema": entry["inputSchema"],
+        });
+        for optional in ["title", "outputSchema", "annotations"] {
+            if let Some(value) = entry.get(optional) {
+                definition[optional] = value.clone();
+            }
+        }
+        registered.push(RegisteredTool {
+            name: name.to_string(),
+            command: command.to_string(),
+            definition,
+        });
+    }
+    Ok((registered, skipped))
+}
+
+/// What the connected extension build can do
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub version: String,
+    /// Command name to the version of its behavior
+    pub commands: HashMap<String, u32>,
+}
+
+impl Capabilities {
+    /// Required commands that are missing or too old, as "command" or "command v2"
+    pub fn missing(&self, required: &[(String, u32)]) -> Vec<String> {
+        required
+            .iter()
+            .filter(|(command, version)| self.commands.get(command).is_none_or(|have| have < version))
+            .map(|(command, version)| if *version > 1 { format!("{} v{}", command, version) } else { command.clone() })
+            .collect()
+    }
+}
+
+/// Validate a `capabilities` payload: `{"version": "0.1.0", "commands": {"click": 1, ...}}`
+pub fn parse_capabilities(data: &Value) -> Result<Capabilities, String> {
+    let version = data.get("version").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+    let commands = data
+        .get("commands")
+        .and_then(|c| c.as_object())
+        .ok_or("capabilities needs a commands object")?
+        .iter()
+        .map(|(command, v)| {
+            v.as_u64()
+                .filter(|v| *v >= 1)
+                .map(|v| (command.clone(), v as u32))
+                .ok_or(format!("version of {} must be a positive integer", command))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Capabilities { version, commands })
+}
+
+#[derive(Default)]
+pub struct ExtensionTools {
+    /// Last set the extension registered; kept across reconnects so a
+    /// reconnect with the same set doesn't announce a change
+    registered: RwLock<Option<Vec<RegisteredTool>>>,
+    /// Commands the extension advertised, None until it does
+    capabilities: RwLock<Option<Capabilities>>,
+}
+
+impl ExtensionTools {
+    /// Replace the registered set; returns whether it differs from the previous one
+    pub async fn register(&self, tools: Vec<RegisteredTool>) -> bool {
+        let mut registered = self.registered.write().await;
+        let changed = registered.as_ref() != Some(&tools);
+        *registered = Some(tools);
+        changed
+    }
+
+    /// Definitions for tools/list, or None if the extension never registered
+    pub async fn definitions(&self) -> Option<Vec<Value>> {
+        let registered = self.registered.read().await;
+        registered
+            .as_ref()
+            .map(|tools| tools.iter().map(|tool| tool.definition.clone()).collect())
+    }
+
+    /// Replace the advertised capabilities; returns whether they changed
+    pub async fn set_capabilities(&self, capabilities: Capabilities) -> bool {
+        let mut current = self.capabilities.write().await;
+        let changed = current.as_ref() != Some(&capabilities);
+        *current = Some(capabilities);
+        changed
+    }
+
+    /// Why `tool` can't run on the connected extension, or None when it can
+    /// (or the extension never advertised its commands)
+    pub async fn unsupported(&self, tool: &str) -> Option<String> {
+        let mut required: Vec<(String, u32)> = tools::server_tool_commands(tool)
+            .iter()
+            .map(|(command, version)| (command.to_string(), *version))
+            .collect();
+        if let Some(command) = self.command_for(tool).await {
+            required.push((command, 1));
+        }
+        let capabilities = self.capabilities.read().await;
+        let capabilities = capabilities.as_ref()?;
+        let missing = capabilities.missing(&required);
+        (!missing.is_empty()).then(|| {
+            format!(
+                "{} needs extension command {}, which the connected extension ({}) doesn't support; update the extension",
+                tool,
+                missing.join(", "),
+                capabilities.version
+            )
+        })
+    }
+
+    /// Extension command for a forwarded tool, from the registered set when
+    /// there is one and the built-in mapping otherwise
+    pub async fn command_for(&self, name: &str) -> Option<String> {
+        match self.registered.read().await.as_ref() {
+            Some(tools) => tools.iter().find(|tool| tool.name == name).map(|tool| tool.command.clone()),
+            None => tools::extension_command(name).map(str::to_string),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_skips_invalid_and_server_tools() {
+        let (tools, skipped) = parse_registration(&serde_json::json!({
+            "tools": [
+                { "name": "playwright_click", "command": "click", "description": "Click", "inputSchema": { "type": "object" } },
+                { "name": "tab_mute", "inputSchema": { "type": "object" } },
+                { "name": "state_get", "inputSchema": { "type": "object" } },
+                { "name": "tab_mute", "inputSchema": { "type": "object" } },
+                { "name": "no_schema" },
+                { "description": "nameless" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].command, "click");
+        assert_eq!(tools[1].command, "tab_mute");
+        assert!(tools[0].definition.get("command").is_none());
+        assert_eq!(skipped.len(), 4);
+        assert!(parse_registration(&serde_json::json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_reports_changes_and_routes_commands() {
+        let registry = ExtensionTools::default();
+        assert_eq!(registry.command_for("playwright_click").await.as_deref(), Some("click"));
+        assert!(registry.definitions().await.is_none());
+
+        let (tools, _) = parse_registration(&serde_json::json!({
+            "tools": [{ "name": "tab_mute", "inputSchema": { "type": "object" } }]
+        }))
+        .unwrap();
+        assert!(registry.register(tools.clone()).await);
+        assert!(!registry.register(tools).await);
+        assert_eq!(registry.command_for("tab_mute").await.as_deref(), Some("tab_mute"));
+        // Built-in forwarded tools the extension didn't register are no longer routed
+        assert_eq!(registry.command_for("playwright_click").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_gate_forwarded_and_server_tools() {
+        let registry = ExtensionTools::default();
+        assert!(registry.unsupported("screenshot_diff").await.is_none());
+
+        let capabilities = parse_capabilities(&serde_json::json!({
+            "version": "0.1.0",
+            "commands": { "click": 1, "screenshot": 1 }
+        }))
+        .unwrap();
+        assert!(registry.set_capabilities(capabilities.clone()).await);
+        assert!(!registry.set_capabilities(capabilities).await);
+
+        assert!(registry.unsupported("playwright_click").await.is_none());
+        assert!(registry.unsupported("state_get").await.is_none());
+        let reason = registry.unsupported("playwright_fill").await.unwrap();
+        assert!(reason.contains("command type,") && reason.contains("(0.1.0)"), "{}", reason);
+        let reason = registry.unsupported("screenshot_diff").await.unwrap();
+        assert!(reason.contains("screenshot v2, image_diff"), "{}", reason);
+
+        assert!(parse_capabilities(&serde_json::json!({ "version": "0.1.0" })).is_err());
+        assert!(parse_capabilities(&serde_json::json!({ "commands": { "click": 0 } })).is_err());
+    }
+}
@@ -0,0 +1,539 @@
+/*!
+ * MCP Streamable HTTP Transport
+ *
+ * Serves the MCP Streamable HTTP spec on localhost:8086 (enable with MCP_HTTP=1):
+ * - POST /mcp - one JSON-RPC message per request; plain JSON response, or an
+ *   SSE stream (notifications, then the response) if the client accepts it
+ * - GET /mcp - SSE stream of server-initiated notifications
+ * - DELETE /mcp - terminate the session named by Mcp-Session-Id
+ *
+ * The same listener also serves the legacy HTTP+SSE transport
+ * (GET /sse, POST /messages) for hosts that predate Streamable HTTP.
+ *
+ * Requests share handle_mcp_request with the TCP and stdio transports.
+ * Browser requests must come from an allowed origin (see origin.rs) and get
+ * CORS headers on every response.
+ */
+
+use anyhow::Result;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::{interval, timeout, timeout_at, Duration, Instant};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::mcp::{protocol, JsonRpcReq, JsonRpcRes};
+use crate::origin;
+use crate::sse_transport::{self, SseSessions};
+use crate::{handle_mcp_notification, handle_mcp_request, ServerState};
+
+pub const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:8086";
+const MCP_PATH: &str = "/mcp";
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+const PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// How long a connection may sit idle before (or between) requests
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a client has to finish the headers once a request has begun, then the body
+const HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+const BODY_TIMEOUT: Duration = Duration::from_secs(30);
+pub(crate) const SSE_KEEPALIVE_SECS: u64 = 15;
+
+type Sessions = Arc<RwLock<HashSet<String>>>;
+
+tokio::task_local! {
+    /// Allowed `Origin` of the request being answered on this connection, so
+    /// every response writer (here and in sse_transport) adds CORS headers
+    static CORS_ORIGIN: RefCell<Option<String>>;
+}
+
+fn current_cors_headers() -> Vec<(&'static str, String)> {
+    CORS_ORIGIN
+        .try_with(|o| o.borrow().as_deref().map(origin::cors_headers))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// HTTP Primitives
+// ============================================================================
+
+/// Parsed HTTP/1.1 request (headers lower-cased)
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the client accepts a given media type
+    pub fn accepts(&self, media_type: &str) -> bool {
+        self.header("accept")
+            .is_some_and(|accept| accept.split(',').any(|t| t.trim().starts_with(media_type)))
+    }
+
+    /// Look up a (non-decoded) query string parameter
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query.as_deref()?.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key == name).then_some(value)
+        })
+    }
+
+    pub(crate) fn keep_alive(&self) -> bool {
+        !self
+            .header("connection")
+            .is_some_and(|c| c.eq_ignore_ascii_case("close"))
+    }
+}
+
+/// Read one request from the stream; Ok(None) on clean EOF or idle timeout
+/// between requests. A client too slow with its headers or body fails with
+/// `Elapsed`, so a stalled connection can't hold its task forever.
+pub async fn read_request(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Result<Option<HttpRequest>> {
+    // Read until the header block is complete; pipelined bytes have already begun a request
+    let mut header_deadline = (!buf.is_empty()).then(|| Instant::now() + HEADER_TIMEOUT);
+    let header_len = loop {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut req = httparse::Request::new(&mut headers);
+        match req.parse(buf)? {
+            httparse::Status::Complete(len) => break len,
+            httparse::Status::Partial => {
+                if buf.len() > MAX_HEADER_BYTES {
+                    anyhow::bail!("Request headers too large");
+                }
+                let mut chunk = [0u8; 4096];
+                let read = stream.read(&mut chunk);
+                let n = match header_deadline {
+                    Some(deadline) => timeout_at(deadline, read).await??,
+                    None => match timeout(IDLE_TIMEOUT, read).await {
+                        Ok(n) => n?,
+                        Err(_) => return Ok(None),
+                    },
+                };
+                if n == 0 {
+                    if buf.is_empty() {
+                        return Ok(None);
+                    }
+                    anyhow::bail!("Connection closed mid-request");
+                }
+                buf.extend_from_slice(&chunk[..n]);
+                header_deadline.get_or_insert_with(|| Instant::now() + HEADER_TIMEOUT);
+            }
+        }
+    };
+
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut req = httparse::Request::new(&mut headers);
+    req.parse(buf)?;
+
+    let method = req.method.unwrap_or_default().to_string();
+    let (path, query) = match req.path.unwrap_or("/").split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (req.path.unwrap_or("/").to_string(), None),
+    };
+    let headers: Vec<(String, String)> = req
+        .headers
+        .iter()
+        .map(|h| {
+            (
+                h.name.to_ascii_lowercase(),
+                String::from_utf8_lossy(h.value).to_string(),
+            )
+        })
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k == "content-length")
+        .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        anyhow::bail!("Request body too large");
+    }
+
+    // Read the rest of the body
+    let total = header_len + content_length;
+    let body_deadline = Instant::now() + BODY_TIMEOUT;
+    while buf.len() < total {
+        let mut chunk = vec![0u8; (total - buf.len()).min(64 * 1024)];
+        let n = timeout_at(body_deadline, stream.read(&mut chunk)).await??;
+        if n == 0 {
+            anyhow::bail!("Connection closed mid-body");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let body = buf[header_len..total].to_vec();
+    // Keep any pipelined bytes for the next request
+    buf.drain(..total);
+
+    Ok(Some(HttpRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    }))
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Write a complete response with a Content-Length body
+pub async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(&str, String)],
+    body: &[u8],
+) -> std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", status, reason_phrase(status));
+    for (name, value) in headers.iter().cloned().chain(current_cors_headers()) {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await
+}
+
+/// Start an SSE response; the connection is closed when the stream ends
+pub async fn write_sse_head(stream: &mut TcpStream, headers: &[(&str, String)]) -> std::io::Result<()> {
+    let mut head = String::from(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n",
+    );
+    for (name, value) in headers.iter().cloned().chain(current_cors_headers()) {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.flush().await
+}
+
+/// Write one SSE `message` event carrying a JSON payload
+pub async fn write_sse_event(stream: &mut TcpStream, json: &str) -> std::io::Result<()> {
+    write_sse_named_event(stream, "message", json).await
+}
+
+/// Write one SSE event with an explicit event name
+pub async fn write_sse_named_event(stream: &mut TcpStream, event: &str, data: &str) -> std::io::Result<()> {
+    stream
+        .write_all(format!("event: {}\ndata: {}\n\n", event, data).as_bytes())
+        .await?;
+    stream.flush().await
+}
+
+/// Write an SSE comment line so proxies don't idle the stream out
+pub async fn write_sse_keepalive(stream: &mut TcpStream) -> std::io::Result<()> {
+    stream.write_all(b": keepalive\n\n").await?;
+    stream.flush().await
+}
+
+fn json_headers(extra: &[(&str, String)]) -> Vec<(&'static str, String)> {
+    let mut headers = vec![("Content-Type", "application/json".to_string())];
+    for (name, value) in extra {
+        if name.eq_ignore_ascii_case(SESSION_HEADER) {
+            headers.push((SESSION_HEADER, value.clone()));
+        }
+    }
+    headers
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+pub async fn run_mcp_http(state: Arc<ServerState>, listener: TcpListener) -> Result<()> {
+    let addr = listener.local_addr()?;
+    info!("MCP HTTP server listening on http://{}{}", addr, MCP_PATH);
+
+    info!(
+        "MCP SSE endpoint available at http://{}{}",
+        addr,
+        sse_transport::SSE_PATH
+    );
+
+    let sessions: Sessions = Arc::new(RwLock::new(HashSet::new()));
+    let sse_sessions = SseSessions::default();
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        let sessions = Arc::clone(&sessions);
+        let sse_sessions = sse_sessions.clone();
+        tokio::spawn(async move {
+            let connection = handle_http_connection(stream, state, sessions, sse_sessions);
+            if let Err(e) = CORS_ORIGIN.scope(RefCell::new(None), connection).await {
+                debug!("MCP HTTP connection {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_http_connection(
+    mut stream: TcpStream,
+    state: Arc<ServerState>,
+    sessions: Sessions,
+    sse_sessions: SseSessions,
+) -> Result<()> {
+    let mut buf = Vec::new();
+
+    loop {
+        let request = match read_request(&mut stream, &mut buf).await {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                let status = if e.is::<tokio::time::error::Elapsed>() { 408 } else { 400 };
+                let _ = write_response(&mut stream, status, &[], e.to_string().as_bytes()).await;
+                return Err(e);
+            }
+        };
+
+        let keep_alive = request.keep_alive();
+
+        // Pages in the browser may only reach MCP from an allowed origin
+        let request_origin = request.header("origin");
+        if !state.origins.allows_client(request_origin) {
+            CORS_ORIGIN.with(|o| o.borrow_mut().take());
+            warn!("Rejected MCP HTTP request from origin {:?}", request_origin);
+            write_response(&mut stream, 403, &[], b"Origin not allowed").await?;
+            if !keep_alive {
+                return Ok(());
+            }
+            continue;
+        }
+        CORS_ORIGIN.with(|o| *o.borrow_mut() = request_origin.map(str::to_string));
+
+        // Preflights carry no credentials, so they are answered before the token check
+        if request.method == "OPTIONS" {
+            write_response(&mut stream, 204, &origin::preflight_headers(), b"").await?;
+            if !keep_alive {
+                return Ok(());
+            }
+            continue;
+        }
+
+        if !state.auth.verify_bearer(request.header("authorization")) {
+            let headers = [("WWW-Authenticate", "Bearer".to_string())];
+            write_response(&mut stream, 401, &headers, b"Missing or invalid bearer token").await?;
+            if !keep_alive {
+                return Ok(());
+            }
+            continue;
+        }
+
+        if request.path == sse_transport::SSE_PATH || request.path == sse_transport::MESSAGES_PATH {
+            let keep_open = sse_transport::handle_request(&mut stream, &request, &state, &sse_sessions).await?;
+            if !keep_open || !keep_alive {
+                return Ok(());
+            }
+            continue;
+        }
+
+        if request.path != MCP_PATH {
+            write_response(&mut stream, 404, &[], b"Not Found").await?;
+            if !keep_alive {
+                return Ok(());
+            }
+            continue;
+        }
+
+        let keep_open = match request.method.as_str() {
+            "POST" => handle_post(&mut stream, &request, &state, &sessions).await?,
+            "GET" => {
+                handle_get(&mut stream, &request, &state, &sessions).await?;
+                false
+            }
+            "DELETE" => {
+                let removed = match request.header(SESSION_HEADER) {
+                    Some(session) => {
+                        state.forget_session(&format!("http:{}", session)).await;
+                        sessions.write().await.remove(session)
+                    }
+                    None => false,
+                };
+                let status = if removed { 200 } else { 404 };
+                write_response(&mut stream, status, &[], b"").await?;
+                true
+            }
+            _ => {
+                write_response(&mut stream, 405, &[("Allow", "GET, POST, DELETE".to_string())], b"").await?;
+                true
+            }
+        };
+
+        if !keep_open || !keep_alive {
+            return Ok(());
+        }
+    }
+}
+
+/// Handle POST /mcp; returns whether the connection can serve another request
+async fn handle_post(
+    stream: &mut TcpStream,
+    request: &HttpRequest,
+    state: &Arc<ServerState>,
+    sessions: &Sessions,
+) -> Result<bool> {
+    let req: JsonRpcReq = match serde_json::from_slice(&request.body) {
+        Ok(req) => req,
+        Err(e) => {
+            let res = JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None);
+            write_response(stream, 400, &json_headers(&[]), &serde_json::to_vec(&res)?).await?;
+            return Ok(true);
+        }
+    };
+
+    // initialize starts a new session; everything else must present one we issued
+    let session_id = if req.method == "initialize" {
+        let session_id = Uuid::new_v4().to_string();
+        sessions.write().await.insert(session_id.clone());
+        session_id
+    } else {
+        match request.header(SESSION_HEADER) {
+            Some(id) if sessions.read().await.contains(id) => id.to_string(),
+            Some(_) => {
+                write_response(stream, 404, &[], b"Unknown session").await?;
+                return Ok(true);
+            }
+            None => {
+                write_response(stream, 400, &[], b"Missing Mcp-Session-Id header").await?;
+                return Ok(true);
+            }
+        }
+    };
+    let session_header = [(SESSION_HEADER, session_id.clone())];
+
+    // Clients on 2025-06-18+ repeat the negotiated version on every request
+    if req.method != "initialize" {
+        if let Some(version) = request.header(PROTOCOL_VERSION_HEADER) {
+            if !protocol::is_supported(version) {
+                let message = format!("Unsupported {}: {}", PROTOCOL_VERSION_HEADER, version);
+                write_response(stream, 400, &[], message.as_bytes()).await?;
+                return Ok(true);
+            }
+        }
+    }
+
+    // Notifications get no JSON-RPC response
+    if req.id.is_none() {
+        tokio::spawn(handle_mcp_notification(req, Arc::clone(state), format!("http:{}", session_id)));
+        write_response(stream, 202, &json_headers(&session_header), b"").await?;
+        return Ok(true);
+    }
+
+    let session = format!("http:{}", session_id);
+
+    // Streamed responses are Streamable HTTP semantics; older clients get plain JSON
+    let streamable = protocol::supports_streamable_http(state.protocol_versions.get(&session).await);
+    if !streamable || !request.accepts("text/event-stream") {
+        let res = handle_mcp_request(req, Arc::clone(state), &session).await;
+        write_response(stream, 200, &json_headers(&session_header), &serde_json::to_vec(&res)?).await?;
+        return Ok(true);
+    }
+
+    // Streamed: relay notifications while the request runs, then the response
+    let mut notifications = state.notifications.subscribe();
+    let task_state = Arc::clone(state);
+    let task_session = session.clone();
+    let mut task = tokio::spawn(async move { handle_mcp_request(req, task_state, &task_session).await });
+
+    write_sse_head(stream, &session_header).await?;
+    loop {
+        tokio::select! {
+            res = &mut task => {
+                let res = match res {
+                    Ok(res) => res,
+                    Err(e) => {
+                        error!("MCP HTTP request task failed: {}", e);
+                        JsonRpcRes::err(None, -32603, "Internal error", None)
+                    }
+                };
+                write_sse_event(stream, &serde_json::to_string(&res)?).await?;
+                break;
+            }
+            notification = notifications.recv() => {
+                if let Some(notification) = notification.ok().filter(|n| n.is_visible_to(&session)) {
+                    write_sse_event(stream, &serde_json::to_string(&notification)?).await?;
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Handle GET /mcp: stream server notifications until the client goes away
+async fn handle_get(
+    stream: &mut TcpStream,
+    request: &HttpRequest,
+    state: &Arc<ServerState>,
+    sessions: &Sessions,
+) -> Result<()> {
+    if !request.accepts("text/event-stream") {
+        write_response(stream, 406, &[], b"GET requires Accept: text/event-stream").await?;
+        return Ok(());
+    }
+    let session = match request.header(SESSION_HEADER) {
+        Some(id) if sessions.read().await.contains(id) => format!("http:{}", id),
+        _ => {
+            write_response(stream, 404, &[], b"Unknown session").await?;
+            return Ok(());
+        }
+    };
+
+    let mut notifications = state.notifications.subscribe();
+    let mut keepalive = interval(Duration::from_secs(SSE_KEEPALIVE_SECS));
+    write_sse_head(stream, &[]).await?;
+
+    loop {
+        tokio::select! {
+            notification = notifications.recv() => match notification {
+                Ok(notification) if !notification.is_visible_to(&session) => {}
+                Ok(notification) => {
+                    write_sse_event(stream, &serde_json::to_string(&notification)?).await?;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("MCP HTTP stream lagged, dropped {} notifications", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = keepalive.tick() => {
+                // Keepalives also reveal dead clients
+                write_sse_keepalive(stream).await?;
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/*!
+ * MCP TCP Keep-Alive
+ *
+ * NATs and proxies silently drop long-idle TCP connections, leaving the
+ * server holding sessions nobody can reach. With a keep-alive interval
+ * configured (`--keepalive` / `MCP_KEEPALIVE_SECS`), a connection that has
+ * sent nothing for that long is sent a JSON-RPC `ping` over the same channel
+ * as other server-initiated requests. Any message from the client within
+ * `PING_TIMEOUT_SECS` counts as an answer, so clients that reject the
+ * request still stay connected; silence closes the connection.
+ */
+
+use serde_json::Value;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::time::{Duration, Instant};
+
+/// How long a pinged client has to send anything back
+pub const PING_TIMEOUT_SECS: u64 = 10;
+
+/// When a connection last received a message from its client
+pub struct Activity {
+    start: Instant,
+    last_ns: AtomicU64,
+}
+
+impl Default for Activity {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            last_ns: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Activity {
+    /// Record that the client sent something
+    pub fn touch(&self) {
+        let now = self.start.elapsed().as_nanos() as u64;
+        self.last_ns.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn last(&self) -> Instant {
+        self.start + Duration::from_nanos(self.last_ns.load(Ordering::Relaxed))
+    }
+
+    /// Time since the client last sent something
+    pub fn idle(&self) -> Duration {
+        self.last().elapsed()
+    }
+}
+
+/// Ping the client whenever it has been idle for `interval`; returns the
+/// reason once a ping goes unanswered
+pub async fn monitor<F, Fut>(interval: Duration, activity: &Activity, mut ping: F) -> String
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let mut last_ping: Option<Instant> = None;
+    loop {
+        // A ping that was answered restarts the wait like any other traffic
+        let idle = activity.idle();
+        let quiet = last_ping.map_or(idle, |at| idle.min(at.elapsed()));
+        if quiet < interval {
+            tokio::time::sleep(interval - quiet).await;
+            continue;
+        }
+
+        let sent = Instant::now();
+        last_ping = Some(sent);
+        match ping().await {
+            Ok(_) => {}
+            // An error reply, or any other message, still shows the peer is there
+            Err(_) if activity.last() >= sent => {}
+            Err(e) => return format!("no reply after {}s idle ({})", idle.as_secs(), e),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn test_pings_only_idle_clients() {
+        let activity = Activity::default();
+        let pings = AtomicUsize::new(0);
+        let monitor = monitor(Duration::from_millis(200), &activity, || async {
+            // The first ping is answered, the second goes unanswered
+            match pings.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(serde_json::json!({})),
+                _ => {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Err("ping request timed out".to_string())
+                }
+            }
+        });
+        tokio::pin!(monitor);
+
+        // Traffic keeps resetting the idle clock
+        for _ in 0..3 {
+            tokio::select! {
+                _ = &mut monitor => panic!("active client was closed"),
+                _ = tokio::time::sleep(Duration::from_millis(100)) => activity.touch(),
+            }
+        }
+        assert_eq!(pings.load(Ordering::SeqCst), 0);
+
+        let reason = monitor.await;
+        assert_eq!(pings.load(Ordering::SeqCst), 2);
+        assert!(reason.starts_with("no reply after"), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn test_error_reply_counts_as_alive() {
+        let activity = Activity::default();
+        let pings = AtomicUsize::new(0);
+        let result = tokio::time::timeout(
+            Duration::from_millis(300),
+            monitor(Duration::from_millis(50), &activity, || {
+                pings.fetch_add(1, Ordering::SeqCst);
+                // The client answered "Method not found", which was read off the socket
+                activity.touch();
+                async { Err("Method not found: ping".to_string()) }
+            }),
+        )
+        .await;
+        assert!(result.is_err(), "client answering with an error was closed");
+        assert!(pings.load(Ordering::SeqCst) >= 2);
+    }
+}
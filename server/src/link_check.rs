@@ -0,0 +1,319 @@
+/*!
+ * Broken Link Checker
+ *
+ * `check_links` asks the extension for the links on the current page and
+ * verifies them from the server, a bounded number at a time. Each link gets a
+ * HEAD request, retried as GET when the server refuses HEAD, and redirects are
+ * followed by hand (up to `MAX_REDIRECTS`) so the whole chain is reported.
+ *
+ * Policy: only http(s) links are requested, optionally only those on the
+ * page's own origin, and hosts resolving to loopback, private or link-local
+ * addresses are skipped unless the page itself is served from one. The
+ * connection goes to the address that passed the check, so a DNS answer
+ * can't switch to an internal host between check and connect.
+ */
+
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::pki_types::ServerName;
+
+use crate::preflight::{self, Target};
+
+pub const DEFAULT_CONCURRENCY: usize = 8;
+pub const MAX_CONCURRENCY: usize = 32;
+pub const DEFAULT_MAX_LINKS: usize = 100;
+pub const MAX_LINKS: usize = 500;
+pub const DEFAULT_TIMEOUT_MS: u64 = 8000;
+const MAX_REDIRECTS: usize = 5;
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const USER_AGENT: &str = "agent-browser-linkcheck/0.1";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    pub same_origin_only: bool,
+    pub allow_private: bool,
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hop {
+    pub url: String,
+    pub status: u16,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkResult {
+    pub url: String,
+    /// Final status after redirects (None when the request failed)
+    pub status: Option<u16>,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<Hop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    pub method: &'static str,
+    pub ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Why a link was not requested, or None to check it
+pub fn skip_reason(url: &str, page: &Target, policy: &Policy) -> Option<&'static str> {
+    let Ok(target) = Target::parse(url) else {
+        return Some("not_http");
+    };
+    if policy.same_origin_only && (target.tls, &target.host, target.port) != (page.tls, &page.host, page.port) {
+        return Some("other_origin");
+    }
+    None
+}
+
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast(),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_internal(IpAddr::V4(v4));
+            }
+            v6.is_loopback() || v6.is_unspecified() || v6.is_unique_local() || v6.is_unicast_link_local()
+        }
+    }
+}
+
+/// Whether the page being checked is itself served from an internal address
+pub async fn is_internal_host(target: &Target) -> bool {
+    match tokio::net::lookup_host((target.host.as_str(), target.port)).await {
+        Ok(mut addrs) => addrs.any(|addr| is_internal(addr.ip())),
+        Err(_) => false,
+    }
+}
+
+/// Resolve a Location header against the URL that returned it
+pub fn resolve(base: &str, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return location.to_string();
+    }
+    let scheme_end = base.find("://").map_or(0, |i| i + 3);
+    if let Some(rest) = location.strip_prefix("//") {
+        return format!("{}{}", &base[..scheme_end], rest);
+    }
+    let origin_end = base[scheme_end..].find(['/', '?', '#']).map_or(base.len(), |i| scheme_end + i);
+    let origin = &base[..origin_end];
+    if location.starts_with('/') {
+        return format!("{}{}", origin, location);
+    }
+    // Relative path: replace everything after the last '/' of the base path
+    let path = base[origin_end..].split(['?', '#']).next().unwrap_or_default();
+    let dir = path.rfind('/').map_or("/", |i| &path[..=i]);
+    if location.starts_with('?') {
+        return format!("{}{}{}", origin, path, location);
+    }
+    format!("{}{}{}", origin, dir, location)
+}
+
+async fn connect(target: &Target, policy: &Policy) -> Result<TcpStream, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+        .await
+        .map_err(|e| format!("DNS lookup failed: {}", e))?
+        .collect();
+    let addr = addrs
+        .iter()
+        .find(|addr| policy.allow_private || !is_internal(addr.ip()))
+        .ok_or_else(|| {
+            if addrs.is_empty() {
+                "DNS lookup returned no addresses".to_string()
+            } else {
+                format!("{} resolves to an internal address; skipped by policy", target.host)
+            }
+        })?;
+    TcpStream::connect(addr).await.map_err(|e| format!("connect failed: {}", e))
+}
+
+/// One request, returning the status and Location header
+async fn request(target: &Target, method: &str, policy: &Policy) -> Result<(u16, Option<String>), String> {
+    let stream = connect(target, policy).await?;
+    if target.tls {
+        let server_name = ServerName::try_from(target.host.clone()).map_err(|e| e.to_string())?;
+        let tls_stream = preflight::tls_connector()
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| format!("TLS handshake failed: {}", e))?;
+        read_head(tls_stream, target, method).await
+    } else {
+        read_head(stream, target, method).await
+    }
+}
+
+async fn read_head<S>(mut stream: S, target: &Target, method: &str) -> Result<(u16, Option<String>), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        method,
+        target.path,
+        target.host_header(),
+        USER_AGENT
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < MAX_HEAD_BYTES {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut response = httparse::Response::new(&mut headers);
+    response.parse(&buf).map_err(|e| format!("Invalid HTTP response: {}", e))?;
+    let status = response.code.ok_or("Incomplete HTTP response")?;
+    let location = response
+        .headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case("location"))
+        .map(|h| String::from_utf8_lossy(h.value).trim().to_string());
+    Ok((status, location))
+}
+
+/// Check one link, following redirects
+pub async fn check(url: &str, policy: Policy) -> LinkResult {
+    let start = Instant::now();
+    let mut result = LinkResult {
+        url: url.to_string(),
+        status: None,
+        ok: false,
+        redirects: Vec::new(),
+        final_url: None,
+        method: "HEAD",
+        ms: 0,
+        error: None,
+    };
+    let limit = Duration::from_millis(policy.timeout_ms);
+
+    let outcome = timeout(limit, async {
+        let mut current = url.split('#').next().unwrap_or_default().to_string();
+        loop {
+            let target = Target::parse(&current)?;
+            let (mut status, mut location) = request(&target, result.method, &policy).await?;
+            // Some servers reject or mishandle HEAD; ask again with GET
+            if matches!(status, 403 | 405 | 501) && result.method == "HEAD" {
+                result.method = "GET";
+                (status, location) = request(&target, "GET", &policy).await?;
+            }
+            match location.filter(|_| (300..400).contains(&status)) {
+                Some(location) if result.redirects.len() < MAX_REDIRECTS => {
+                    result.redirects.push(Hop { url: current.clone(), status });
+                    current = resolve(&current, &location);
+                }
+                Some(_) => return Err::<_, String>(format!("More than {} redirects", MAX_REDIRECTS)),
+                None => {
+                    if !result.redirects.is_empty() {
+                        result.final_url = Some(current);
+                    }
+                    return Ok(status);
+                }
+            }
+        }
+    })
+    .await;
+
+    match outcome {
+        Ok(Ok(status)) => {
+            result.status = Some(status);
+            result.ok = status < 400;
+        }
+        Ok(Err(e)) => result.error = Some(e),
+        Err(_) => result.error = Some("timed out".to_string()),
+    }
+    result.ms = start.elapsed().as_millis() as u64;
+    result
+}
+
+/// Check links with at most `concurrency` requests in flight, in input order
+pub async fn check_all(urls: Vec<String>, policy: Policy, concurrency: usize) -> Vec<LinkResult> {
+    stream::iter(urls)
+        .map(|url| async move { check(&url, policy).await })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_location() {
+        let base = "https://example.com/docs/guide/intro?x=1";
+        assert_eq!(resolve(base, "https://other.org/"), "https://other.org/");
+        assert_eq!(resolve(base, "//cdn.example.com/a"), "https://cdn.example.com/a");
+        assert_eq!(resolve(base, "/login"), "https://example.com/login");
+        assert_eq!(resolve(base, "setup"), "https://example.com/docs/guide/setup");
+        assert_eq!(resolve(base, "?page=2"), "https://example.com/docs/guide/intro?page=2");
+        assert_eq!(resolve("http://example.com", "a"), "http://example.com/a");
+    }
+
+    #[test]
+    fn test_policy() {
+        let page = Target::parse("https://example.com/").unwrap();
+        let strict = Policy { same_origin_only: true, allow_private: false, timeout_ms: 1000 };
+        assert_eq!(skip_reason("mailto:a@example.com", &page, &strict), Some("not_http"));
+        assert_eq!(skip_reason("https://other.org/", &page, &strict), Some("other_origin"));
+        assert_eq!(skip_reason("https://example.com/about", &page, &strict), None);
+
+        assert!(is_internal("127.0.0.1".parse().unwrap()));
+        assert!(is_internal("10.1.2.3".parse().unwrap()));
+        assert!(is_internal("::ffff:192.168.1.1".parse().unwrap()));
+        assert!(is_internal("fd00::1".parse().unwrap()));
+        assert!(!is_internal("93.184.216.34".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_check_follows_redirects_and_retries_head_as_get() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1024];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let response = if request.starts_with("HEAD /old") {
+                    "HTTP/1.1 301 Moved\r\nLocation: /new\r\nContent-Length: 0\r\n\r\n"
+                } else if request.starts_with("HEAD") {
+                    "HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\n\r\n"
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n"
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let url = format!("http://127.0.0.1:{}/old", port);
+        let open = Policy { same_origin_only: false, allow_private: true, timeout_ms: 2000 };
+        let result = check(&url, open).await;
+        assert_eq!(result.status, Some(404));
+        assert!(!result.ok);
+        assert_eq!(result.method, "GET");
+        assert_eq!(result.redirects.len(), 1);
+        assert_eq!(result.redirects[0].status, 301);
+        assert_eq!(result.final_url, Some(format!("http://127.0.0.1:{}/new", port)));
+
+        let guarded = Policy { allow_private: false, ..open };
+        let result = check(&url, guarded).await;
+        assert!(result.error.unwrap().contains("internal address"));
+    }
+}
@@ -4,7 +4,11 @@
  * Runs three servers simultaneously:
  * - MCP stdio - Read from stdin, write to stdout (for Claude Code integration)
  * - MCP TCP on localhost:8084 - MCP clients can connect here
- * - WebSocket on localhost:8085 - Extension connects here
+ * - WebSocket on localhost:8085 - Extension connects here; MCP clients use the /mcp path
+ *
+ * Optionally (MCP_HTTP=1) also serves MCP Streamable HTTP and the legacy
+ * HTTP+SSE transport on localhost:8086. Addresses and the command timeout
+ * are configurable; see `config.rs` or `--help`.
  *
  * Flow: MCP client → server → extension via WebSocket → response back
  */
@@ -15,19 +19,152 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::task::{JoinHandle, JoinSet};
 use tokio::time::{timeout, Duration};
-use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse as WsErrorResponse, Request as WsRequest, Response as WsResponse,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message as WsMessage};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+mod http_transport;
+
 mod mcp;
-use mcp::{JsonRpcReq, JsonRpcRes};
+use mcp::protocol::{self, NegotiatedVersions};
+use mcp::{JsonRpcIncoming, JsonRpcNotification, JsonRpcReq, JsonRpcRes};
+
+mod accessibility;
+
+mod alerts;
+use alerts::{Alert, AlertRouter, EventClass};
+
+mod artifacts;
+
+mod auth;
+use auth::ClientAuth;
+
+mod backpressure;
+use backpressure::CommandQueue;
+
+mod baselines;
+use baselines::{BaselineStore, Viewport};
+
+mod compaction;
+
+mod companion;
+use companion::CompanionPool;
+
+mod completion;
+use completion::RecentSelectors;
+
+mod config;
+use config::{BoundAddrs, ServerConfig};
+
+mod consent;
+use consent::ConsentMode;
 
 mod credential_store;
-use credential_store::CredentialStore;
+use credential_store::{CredentialStore, RecoverySecretKind};
+
+mod dialogs;
+use dialogs::DialogLog;
+
+mod doctor;
+
+mod downloads;
+
+mod element_screenshots;
+
+mod extension_tools;
+use extension_tools::ExtensionTools;
+
+mod keepalive;
+
+mod link_check;
+
+mod migrations;
+
+mod navigation;
+use navigation::{CommandScope, NavigationEvent, NavigationTracker};
+
+mod network_capture;
+use network_capture::{CapturePolicy, HttpFilter, NetworkCapture, StatusFilter, WebSocketFilter};
+
+mod origin;
+use origin::OriginPolicy;
+
+mod popup;
+use popup::SessionTargets;
+
+mod preflight;
+
+mod profiling;
+
+mod prompts;
+
+mod quota;
+
+mod receipts;
+
+mod recordings;
+use recordings::RecordingStore;
+
+mod redaction;
+
+mod report;
+use report::{PendingCall, SessionHistory};
+
+mod resources;
+use resources::{BrowserResource, RecentScreenshots};
+
+mod roots;
+use roots::ClientRoots;
+
+mod sampling;
+
+mod search;
+use search::ArtifactSearch;
+
+mod seo;
+use sampling::SamplingClients;
+
+mod sessions;
+use sessions::SessionRegistry;
+
+mod snapshot_store;
+use snapshot_store::{SnapshotCapture, SnapshotPart, SnapshotStore};
+
+mod sse_transport;
+
+mod state_store;
+use state_store::StateStore;
+
+mod suggestions;
+use suggestions::SelectorHint;
+
+mod tab_locks;
+
+mod tables;
+
+mod telemetry;
+use telemetry::Telemetry;
+use tab_locks::{TabKey, TabLocks};
+
+mod watch;
+use watch::WatchStore;
+
+mod storage;
+
+mod tls;
+
+mod transform;
+
+mod ws_transport;
 
 // ============================================================================
 // Message Types
@@ -38,6 +175,9 @@ struct ExtensionCommand {
     id: String,
     method: String,
     params: serde_json::Value,
+    /// Ask the extension to emit `progress` events for this command
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    progress: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +188,14 @@ struct ExtensionResponse {
     error: Option<String>,
 }
 
+/// Unsolicited event pushed by the extension (not tied to a request id)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExtensionEvent {
+    event: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
 // ============================================================================
 // Server State
 // ============================================================================
@@ -56,6 +204,12 @@ type RequestId = String;
 
 struct PendingRequest {
     tx: mpsc::Sender<ExtensionResponse>,
+    // MCP progressToken to relay extension progress events to, with the last value sent
+    progress: Option<(serde_json::Value, f64)>,
+    // Set for element-scoped commands that a navigation of their tab invalidates
+    scope: Option<CommandScope>,
+    // MCP session that issued the command (None for server-internal commands)
+    session: Option<String>,
 }
 
 struct ServerState {
@@ -63,19 +217,587 @@ struct ServerState {
     pending_requests: Arc<RwLock<HashMap<RequestId, PendingRequest>>>,
     // Connected extension WebSocket sender
     extension_tx: Arc<RwLock<Option<mpsc::Sender<ExtensionCommand>>>>,
+    // Admission control for commands in flight to the extension
+    commands: Arc<CommandQueue>,
     // Credential store with time-window authorization
     credential_store: Arc<CredentialStore>,
+    // Per-workspace key/value state for agents
+    state_store: Arc<StateStore>,
+    // Named DOM/accessibility/screenshot snapshots
+    snapshot_store: Arc<SnapshotStore>,
+    // Visual regression baselines compared by screenshot_diff
+    baselines: Arc<BaselineStore>,
+    // Network activity reported by the extension
+    network_capture: Arc<NetworkCapture>,
+    // Notifications fanned out to every connected MCP client
+    notifications: broadcast::Sender<JsonRpcNotification>,
+    // MCP session that most recently drove the browser
+    active_session: Arc<RwLock<Option<String>>>,
+    // Last passkey automation setting sent to the extension (None until toggled)
+    passkey_automation: Arc<RwLock<Option<bool>>>,
+    // Recent screenshots exposed as MCP resources
+    screenshots: Arc<RecentScreenshots>,
+    // Per-tab navigation generation counters
+    navigation: Arc<NavigationTracker>,
+    // JavaScript dialogs reported by the extension, and which are still open
+    dialogs: Arc<DialogLog>,
+    recordings: Arc<RecordingStore>,
+    // Consent mode applied automatically after each navigation (None = off)
+    consent_auto: Arc<RwLock<Option<ConsentMode>>>,
+    // MCP connections that can receive server-initiated requests (sampling)
+    sampling: Arc<SamplingClients>,
+    // Per-session tab attachment for popup follow mode
+    targets: Arc<SessionTargets>,
+    // Protocol version negotiated by each MCP session's initialize
+    protocol_versions: Arc<NegotiatedVersions>,
+    // Filesystem roots declared by each MCP session
+    roots: Arc<ClientRoots>,
+    // Token MCP network clients must present
+    auth: Arc<ClientAuth>,
+    // How long an extension command may take before it fails
+    request_timeout: Duration,
+    // Idle time after which MCP TCP clients are pinged (None = never)
+    keepalive: Option<Duration>,
+    // Whether playwright_evaluate is open to sessions without an authorization window
+    allow_evaluate: bool,
+    // Browser origins allowed on the WebSocket and HTTP listeners
+    origins: Arc<OriginPolicy>,
+    // Tools the extension registered, replacing the built-in forwarded set
+    extension_tools: Arc<ExtensionTools>,
+    // Selectors each session used, offered by completion/complete
+    recent_selectors: Arc<RecentSelectors>,
+    // Per-tab locks serializing page-changing commands across sessions
+    tab_locks: Arc<TabLocks>,
+    // Pages re-checked on an interval for changes
+    watches: Arc<WatchStore>,
+    // Headless browser for background work (None = use the extension)
+    companion: Option<Arc<CompanionPool>>,
+    // Tool calls each session made, for session_report
+    history: Arc<SessionHistory>,
+    // Connected MCP sessions across transports, and who observes them
+    sessions: Arc<SessionRegistry>,
+    // Operator alert channels routed by event class
+    alerts: Arc<AlertRouter>,
+    // Opt-in local tool usage counts
+    telemetry: Arc<Telemetry>,
+    // Full-text index over snapshots, artifacts and the audit log
+    search: Arc<ArtifactSearch>,
 }
 
 impl ServerState {
-    async fn new() -> Self {
+    async fn new(config: &ServerConfig) -> Self {
         let credential_store = CredentialStore::new().await
             .expect("Failed to initialize credential store");
+        let state_store = StateStore::new()
+            .expect("Failed to initialize state store");
+        let snapshot_store = SnapshotStore::new()
+            .expect("Failed to initialize snapshot store");
+        let baselines = BaselineStore::new()
+            .expect("Failed to initialize baseline store");
+        let network_capture = NetworkCapture::new()
+            .expect("Failed to initialize network capture");
+        let auth = ClientAuth::from_env()
+            .expect("Failed to generate MCP auth token");
+        let watches = WatchStore::new()
+            .expect("Failed to initialize watch store");
+        let alerts = AlertRouter::load()
+            .expect("Failed to load alert configuration");
+        let telemetry = Telemetry::new(config.telemetry)
+            .expect("Failed to initialize telemetry");
+        let search = ArtifactSearch::new()
+            .expect("Failed to initialize artifact search");
+
+        let (notifications, _) = broadcast::channel(100);
 
         Self {
             pending_requests: Arc::new(RwLock::new(HashMap::new())),
             extension_tx: Arc::new(RwLock::new(None)),
+            commands: Arc::new(CommandQueue::new(backpressure::max_pending_commands())),
             credential_store: Arc::new(credential_store),
+            state_store: Arc::new(state_store),
+            snapshot_store: Arc::new(snapshot_store),
+            baselines: Arc::new(baselines),
+            network_capture: Arc::new(network_capture),
+            notifications,
+            active_session: Arc::new(RwLock::new(None)),
+            passkey_automation: Arc::new(RwLock::new(None)),
+            screenshots: Arc::new(RecentScreenshots::default()),
+            navigation: Arc::new(NavigationTracker::default()),
+            dialogs: Arc::new(DialogLog::default()),
+            recordings: Arc::new(RecordingStore::default()),
+            consent_auto: Arc::new(RwLock::new(None)),
+            sampling: Arc::new(SamplingClients::default()),
+            targets: Arc::new(SessionTargets::default()),
+            protocol_versions: Arc::new(NegotiatedVersions::default()),
+            roots: Arc::new(ClientRoots::default()),
+            auth: Arc::new(auth),
+            request_timeout: config.request_timeout,
+            keepalive: config.keepalive,
+            allow_evaluate: config.allow_evaluate,
+            origins: Arc::new(OriginPolicy::new(&config.extension_ids, &config.cors_origins)),
+            extension_tools: Arc::new(ExtensionTools::default()),
+            recent_selectors: Arc::new(RecentSelectors::default()),
+            tab_locks: Arc::new(TabLocks::default()),
+            watches: Arc::new(watches),
+            companion: match companion::pool_size() {
+                0 => None,
+                size => Some(Arc::new(CompanionPool::new(size))),
+            },
+            history: Arc::new(SessionHistory::default()),
+            sessions: Arc::new(SessionRegistry::default()),
+            alerts: Arc::new(alerts),
+            telemetry: Arc::new(telemetry),
+            search: Arc::new(search),
+        }
+    }
+
+    /// Broadcast a notification to all connected MCP clients
+    fn notify(&self, method: &str, params: serde_json::Value) {
+        // Err only means nobody is subscribed right now
+        let _ = self.notifications.send(JsonRpcNotification::new(method, params));
+    }
+
+    /// Send a notification to one MCP session only
+    fn notify_session(&self, session: &str, method: &str, params: serde_json::Value) {
+        let _ = self
+            .notifications
+            .send(JsonRpcNotification::new(method, params).for_session(session));
+    }
+
+    /// Raise a policy alert when a connection presented the wrong MCP token
+    fn alert_on_rejected_token(&self, session: &str, response: Option<&JsonRpcRes>) {
+        if response.and_then(|r| r.error.as_ref()).is_some_and(|e| e.message == auth::INVALID_TOKEN) {
+            self.alerts.send(
+                Alert::new(EventClass::PolicyViolation, "Invalid MCP token", "A network client failed to authenticate")
+                    .for_session(session),
+            );
+        }
+    }
+
+    /// Tools usable right now; clients are told to re-fetch via tools_changed()
+    async fn available_tools(&self) -> Vec<serde_json::Value> {
+        let extension_connected = self.extension_tx.read().await.is_some();
+        let passkey_automation = *self.passkey_automation.read().await != Some(false);
+        let registered = self.extension_tools.definitions().await;
+        let mut tools = Vec::new();
+        for tool in mcp::tools::available_tools(extension_connected, passkey_automation, registered.as_deref()) {
+            // Tools the extension lacks commands for are hidden rather than failing when called
+            if self.extension_tools.unsupported(tool["name"].as_str().unwrap_or_default()).await.is_none() {
+                tools.push(tool);
+            }
+        }
+        tools
+    }
+
+    /// Drop per-session state when an MCP connection or HTTP session ends
+    async fn forget_session(&self, session: &str) {
+        self.protocol_versions.forget(session).await;
+        self.roots.forget(session).await;
+        self.credential_store.revoke_session(session).await;
+        self.recent_selectors.forget(session).await;
+        self.history.forget(session).await;
+        self.sessions.forget(session).await;
+        self.targets.forget(session).await;
+
+        // Nobody is left to receive answers to this session's commands
+        let orphaned: Vec<(RequestId, mpsc::Sender<ExtensionResponse>)> = {
+            let mut pending = self.pending_requests.write().await;
+            let ids: Vec<RequestId> = pending
+                .iter()
+                .filter(|(_, p)| p.session.as_deref() == Some(session))
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| pending.remove(&id).map(|p| (id, p.tx)))
+                .collect()
+        };
+        for (id, tx) in orphaned {
+            debug!("Dropping request {} of closed session {}", id, session);
+            let _ = tx
+                .send(ExtensionResponse {
+                    id,
+                    success: false,
+                    result: None,
+                    error: Some(format!("MCP session {} closed", session)),
+                })
+                .await;
+        }
+    }
+
+    /// Ask a session for roots/list and remember the result
+    async fn refresh_roots(&self, session: &str) {
+        match self
+            .sampling
+            .request(session, "roots/list", serde_json::json!({}), roots::ROOTS_LIST_TIMEOUT_SECS)
+            .await
+        {
+            Ok(result) => {
+                let roots = self.roots.set_roots(session, &result).await;
+                info!("Session {} declared {} root(s): {:?}", session, roots.len(), roots);
+            }
+            Err(e) => warn!("roots/list failed for {}: {}", session, e),
+        }
+    }
+
+    /// Count, record and publish a finished tool call; returns its call id
+    async fn finish_call(&self, session: &str, call: report::ToolCall, res: &JsonRpcRes) -> String {
+        self.telemetry.record(&call.tool, res);
+        let observers = self.sessions.observers(session).await;
+        let activity = (!observers.is_empty()).then(|| call.clone());
+        let call_id = self.history.record(session, call).await;
+        if let Some(call) = activity {
+            let params = serde_json::json!({
+                "session": session,
+                "callId": call_id,
+                "tool": call.tool,
+                "arguments": call.arguments,
+                "durationMs": call.duration_ms,
+                "error": call.error,
+                "retryOf": call.retry_of,
+                "injectedBy": call.injected_by,
+            });
+            for observer in observers {
+                self.notify_session(&observer, "notifications/session_activity", params.clone());
+            }
+        }
+        call_id
+    }
+
+    fn tools_changed(&self) {
+        self.notify("notifications/tools/list_changed", serde_json::json!({}));
+    }
+
+    /// Push a fire-and-forget message to the extension dashboard
+    async fn notify_extension(&self, method: &str, params: serde_json::Value) {
+        let command = ExtensionCommand {
+            id: Uuid::new_v4().to_string(),
+            method: method.to_string(),
+            params,
+            progress: false,
+        };
+
+        if let Some(tx) = self.extension_tx.read().await.as_ref() {
+            match tx.try_send(command) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    self.commands.record_shed();
+                    debug!("Extension channel full, shedding {}", method);
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    debug!("Extension disconnected, dropping {}", method);
+                }
+            }
+        }
+    }
+
+    /// Turn an extension progress event into notifications/progress for the requesting client
+    async fn relay_progress(&self, data: &serde_json::Value) {
+        let Some(request_id) = data.get("id").and_then(|v| v.as_str()) else {
+            warn!("Progress event without request id");
+            return;
+        };
+
+        let (session, params) = {
+            let mut pending = self.pending_requests.write().await;
+            let Some(request) = pending.get_mut(request_id) else {
+                // Request already finished
+                return;
+            };
+            let session = request.session.clone();
+            let Some((token, last)) = request.progress.as_mut() else {
+                // The client did not ask for progress
+                return;
+            };
+            // MCP requires progress to increase with every notification
+            let reported = data.get("progress").and_then(|v| v.as_f64());
+            *last = reported.filter(|p| *p > *last).unwrap_or(*last + 1.0);
+
+            let mut params = serde_json::json!({ "progressToken": token.clone(), "progress": *last });
+            if let Some(total) = data.get("total").and_then(|v| v.as_f64()) {
+                params["total"] = serde_json::json!(total);
+            }
+            if let Some(message) = data.get("message").and_then(|v| v.as_str()) {
+                params["message"] = serde_json::json!(message);
+            }
+            (session, params)
+        };
+
+        // Progress tokens are only meaningful to the session that issued them
+        match session {
+            Some(session) => self.notify_session(&session, "notifications/progress", params),
+            None => self.notify("notifications/progress", params),
+        }
+    }
+
+    /// Answer an extension LLM query through the MCP client's model (sampling)
+    async fn answer_llm_query(&self, data: serde_json::Value) {
+        let query_id = data.get("id").cloned().unwrap_or(serde_json::Value::Null);
+        let Some(prompt) = data.get("prompt").and_then(|v| v.as_str()) else {
+            warn!("llm_query event without prompt");
+            return;
+        };
+
+        let params = sampling::text_request(
+            prompt,
+            data.get("screenshot").and_then(|v| v.as_str()),
+            data.get("systemPrompt").and_then(|v| v.as_str()),
+            data.get("maxTokens").and_then(|v| v.as_u64()),
+        );
+        let preferred = self.active_session.read().await.clone();
+
+        let reply = match self.sampling.create_message(preferred.as_deref(), params).await {
+            Ok(result) => serde_json::json!({
+                "id": query_id,
+                "success": true,
+                "text": sampling::result_text(&result),
+                "model": result.get("model"),
+            }),
+            Err(e) => serde_json::json!({ "id": query_id, "success": false, "error": e }),
+        };
+        self.notify_extension("llm_query_result", reply).await;
+    }
+
+    /// Bump the tab's generation and fail element-scoped commands still waiting on the old page
+    async fn handle_navigation(&self, nav: NavigationEvent) {
+        let generation = self.navigation.record(&nav).await;
+        debug!("Tab {} navigated (generation {})", nav.tab_id, generation);
+
+        let error = navigation::page_navigated_error(&nav, generation);
+        self.fail_scoped_requests(|scope| scope.is_invalidated_by(&nav), error).await;
+    }
+
+    /// Fail element-scoped commands stuck behind a dialog the agent has to answer
+    async fn handle_dialog_opened(&self, dialog: dialogs::Dialog) {
+        info!("{} dialog open on tab {}: {}", dialog.kind, dialog.tab_id, dialog.message);
+        let error = dialogs::dialog_open_error(&dialog);
+        self.fail_scoped_requests(|scope| scope.targets(dialog.tab_id, dialog.active), error).await;
+    }
+
+    /// Fail in-flight element-scoped commands whose scope matches
+    async fn fail_scoped_requests(&self, matches: impl Fn(&CommandScope) -> bool, error: String) {
+        let interrupted: Vec<(RequestId, mpsc::Sender<ExtensionResponse>)> = {
+            let mut pending = self.pending_requests.write().await;
+            let ids: Vec<RequestId> = pending
+                .iter()
+                .filter(|(_, p)| p.scope.as_ref().is_some_and(&matches))
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| pending.remove(&id).map(|p| (id, p.tx)))
+                .collect()
+        };
+
+        for (id, tx) in interrupted {
+            info!("Failing request {}: {}", id, error);
+            let _ = tx
+                .send(ExtensionResponse {
+                    id,
+                    success: false,
+                    result: None,
+                    error: Some(error.clone()),
+                })
+                .await;
+        }
+    }
+
+    async fn handle_extension_event(self: &Arc<Self>, event: ExtensionEvent) {
+        match event.event.as_str() {
+            // The extension holds each WebAuthn request until the server approves it
+            "credential_request" => {
+                let data = &event.data;
+                let request_id = data.get("request_id").cloned().unwrap_or_default();
+                let rp_id = data.get("rp_id").and_then(|v| v.as_str()).unwrap_or_default();
+                let operation = data
+                    .get("operation")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("get");
+                // The session owning the requesting tab; none when no session, or several, drive it
+                let tabs: Vec<(i64, bool)> = data
+                    .get("tabs")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tab| {
+                        let tab_id = tab.get("tabId").and_then(|v| v.as_i64())?;
+                        Some((tab_id, tab.get("active").and_then(|v| v.as_bool()).unwrap_or(false)))
+                    })
+                    .collect();
+                let active_session = self.active_session.read().await.clone();
+                let owner = self.targets.owner_of(&tabs, active_session.as_deref()).await;
+                let session = owner.as_ref().ok().cloned();
+
+                let approval = match owner {
+                    Ok(session) => self.credential_store.approve_credential_use(Some(&session), rp_id, operation).await,
+                    Err(e) => {
+                        self.credential_store.refuse_credential_use(rp_id, operation, &e).await;
+                        Err(anyhow::anyhow!(e))
+                    }
+                };
+                let decision = match approval {
+                    Ok(approval) => {
+                        info!("Passkey {} on {} approved for {:?}: {:?}", operation, rp_id, session, approval);
+                        serde_json::json!({ "request_id": request_id, "allowed": true })
+                    }
+                    Err(e) => {
+                        let mut alert = Alert::new(
+                            EventClass::PolicyViolation,
+                            "Passkey use refused",
+                            format!("{} ({}): {}", rp_id, operation, e),
+                        );
+                        if let Some(session) = &session {
+                            alert = alert.for_session(session);
+                        }
+                        self.alerts.send(alert);
+                        serde_json::json!({ "request_id": request_id, "allowed": false, "error": e.to_string() })
+                    }
+                };
+                self.notify_extension("passkey_decision", decision).await;
+            }
+            "credential_used" => {
+                let data = &event.data;
+                let credential_id = data
+                    .get("credential_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                let rp_id = data.get("rp_id").and_then(|v| v.as_str());
+                let operation = data
+                    .get("operation")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("get");
+
+                // Attribute to the session that last drove the browser unless told otherwise
+                let session = match data.get("session").and_then(|v| v.as_str()) {
+                    Some(session) => Some(session.to_string()),
+                    None => self.active_session.read().await.clone(),
+                };
+
+                let usage = self
+                    .credential_store
+                    .record_credential_use(credential_id, rp_id, operation, session.as_deref())
+                    .await;
+
+                info!(
+                    "Credential used: rp_id={} operation={} session={:?}",
+                    usage.rp_id, usage.operation, usage.session
+                );
+
+                let mut alert = Alert::new(
+                    EventClass::Authorization,
+                    "Passkey used",
+                    format!("{} ({})", usage.rp_id, usage.operation),
+                );
+                if let Some(session) = &usage.session {
+                    alert = alert.for_session(session);
+                }
+                self.alerts.send(alert);
+
+                let params = serde_json::to_value(&usage).unwrap_or_default();
+                self.notify("notifications/credential_used", params.clone());
+                self.notify_extension("dashboard_event", serde_json::json!({
+                    "type": "credential_used",
+                    "data": params
+                })).await;
+            }
+            "http" => {
+                if let Err(e) = self.network_capture.record_http(&event.data).await {
+                    warn!("{}", e);
+                }
+            }
+            "websocket" => {
+                if let Err(e) = self.network_capture.record_websocket(&event.data).await {
+                    warn!("{}", e);
+                }
+            }
+            "progress" => self.relay_progress(&event.data).await,
+            "register_tools" => match extension_tools::parse_registration(&event.data) {
+                Ok((tools, skipped)) => {
+                    for reason in skipped {
+                        warn!("Ignoring registered extension tool: {}", reason);
+                    }
+                    info!("Extension registered {} tools", tools.len());
+                    if self.extension_tools.register(tools).await {
+                        self.tools_changed();
+                    }
+                }
+                Err(e) => warn!("Invalid register_tools event: {}", e),
+            },
+            "capabilities" => match extension_tools::parse_capabilities(&event.data) {
+                Ok(capabilities) => {
+                    info!(
+                        "Extension {} supports {} commands",
+                        capabilities.version,
+                        capabilities.commands.len()
+                    );
+                    if self.extension_tools.set_capabilities(capabilities).await {
+                        self.tools_changed();
+                    }
+                }
+                Err(e) => warn!("Invalid capabilities event: {}", e),
+            },
+            "tab_opened" => {
+                let data = &event.data;
+                let Some(tab_id) = data.get("tabId").and_then(|v| v.as_i64()) else {
+                    warn!("tab_opened event without tabId");
+                    return;
+                };
+                let active_session = self.active_session.read().await.clone();
+                self.targets
+                    .on_tab_opened(
+                        tab_id,
+                        data.get("openerTabId").and_then(|v| v.as_i64()),
+                        data.get("url").and_then(|v| v.as_str()),
+                        active_session.as_deref(),
+                    )
+                    .await;
+            }
+            "tab_closed" => {
+                if let Some(tab_id) = event.data.get("tabId").and_then(|v| v.as_i64()) {
+                    self.targets.on_tab_closed(tab_id).await;
+                    self.navigation.forget(tab_id).await;
+                    self.dialogs.forget(tab_id).await;
+                    self.tab_locks.forget(tab_id);
+                }
+            }
+            "llm_query" => {
+                // Sampling can take a while; answer the extension asynchronously
+                let state = Arc::clone(self);
+                tokio::spawn(async move { state.answer_llm_query(event.data).await });
+            }
+            "navigation" => match NavigationEvent::from_extension(&event.data) {
+                Some(nav) => {
+                    self.handle_navigation(nav.clone()).await;
+                    if let Some(mode) = *self.consent_auto.read().await {
+                        // Runs off the WebSocket read loop, which must stay free to deliver the response
+                        let state = Arc::clone(self);
+                        tokio::spawn(async move {
+                            tokio::time::sleep(Duration::from_millis(CONSENT_AUTO_DELAY_MS)).await;
+                            match run_consent_dismissal(&state, mode, Some(nav.tab_id), true).await {
+                                Ok(result) => debug!("Auto consent on tab {}: {}", nav.tab_id, result),
+                                Err(e) => debug!("Auto consent on tab {} failed: {}", nav.tab_id, e),
+                            }
+                        });
+                    }
+                }
+                None => warn!("Invalid navigation event: {}", event.data),
+            },
+            "dialog" => match self.dialogs.record(&event.data).await {
+                Ok(Some(dialog)) => self.handle_dialog_opened(dialog).await,
+                Ok(None) => {}
+                Err(e) => warn!("Invalid dialog event: {}", e),
+            },
+            "screencast_frame" => {
+                if let Err(e) = self.recordings.add_frame(&event.data).await {
+                    debug!("Dropped screencast frame: {}", e);
+                }
+            }
+            "webrtc" => {
+                if let Err(e) = self.network_capture.record_webrtc(&event.data).await {
+                    warn!("{}", e);
+                }
+            }
+            other => {
+                warn!("Unknown extension event: {}", other);
+            }
         }
     }
 
@@ -84,16 +806,83 @@ impl ServerState {
         method: &str,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, String> {
+        self.send_to_extension_with_progress(method, params, None, None).await
+    }
+
+    /// Like send_to_extension, owned by an MCP session and relaying extension
+    /// progress events to it as notifications/progress when it supplied a progressToken.
+    /// Page-changing commands from a session wait for the tab's lock first.
+    async fn send_to_extension_with_progress(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        progress_token: Option<serde_json::Value>,
+        session: Option<&str>,
+    ) -> Result<serde_json::Value, String> {
+        // Don't queue for a tab when nothing could run the command
+        if self.extension_tx.read().await.is_none() {
+            return Err("No extension connected".to_string());
+        }
+
+        // The page can't run anything until its dialog is answered
+        if dialogs::blocked_by_dialog(method) {
+            let tab_id = params.get("tabId").and_then(|v| v.as_i64());
+            if let Some(dialog) = self.dialogs.blocking(tab_id).await {
+                return Err(dialogs::dialog_open_error(&dialog));
+            }
+        }
+
+        if let Some(session) = session {
+            self.targets.record_command(session, params.get("tabId").and_then(|v| v.as_i64())).await;
+        }
+
+        // Queue position is reported as progress before the extension's own
+        let mut progress_sent = 0.0;
+        let _tab = match session {
+            Some(session) if tab_locks::needs_lock(method) => {
+                let key = TabKey::for_params(&params);
+                let guard = self
+                    .tab_locks
+                    .acquire(key, session, self.request_timeout, |status| {
+                        if let Some(token) = &progress_token {
+                            progress_sent += 1.0;
+                            let params = serde_json::json!({
+                                "progressToken": token,
+                                "progress": progress_sent,
+                                "message": status.message(key),
+                            });
+                            self.notify_session(session, "notifications/progress", params);
+                        }
+                    })
+                    .await?;
+                Some(guard)
+            }
+            _ => None,
+        };
+
+        // Reject up front rather than queue behind commands that will time out
+        let _permit = self.commands.try_admit()?;
+
         // Generate request ID
         let id = Uuid::new_v4().to_string();
 
         // Create response channel
         let (tx, mut rx) = mpsc::channel::<ExtensionResponse>(1);
+        let wants_progress = progress_token.is_some();
+        let scope = CommandScope::for_command(method, &params);
 
         // Store pending request
         {
             let mut pending = self.pending_requests.write().await;
-            pending.insert(id.clone(), PendingRequest { tx });
+            pending.insert(
+                id.clone(),
+                PendingRequest {
+                    tx,
+                    progress: progress_token.map(|token| (token, progress_sent)),
+                    scope,
+                    session: session.map(str::to_string),
+                },
+            );
         }
 
         // Send to extension
@@ -101,14 +890,23 @@ impl ServerState {
             id: id.clone(),
             method: method.to_string(),
             params,
+            progress: wants_progress,
         };
 
         let extension_tx = self.extension_tx.read().await;
         if let Some(tx) = extension_tx.as_ref() {
-            if tx.send(command).await.is_err() {
+            let sent = tx.try_send(command);
+            if sent.is_err() {
                 // Clean up
                 self.pending_requests.write().await.remove(&id);
-                return Err("Extension disconnected".to_string());
+            }
+            match sent {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    self.commands.record_shed();
+                    return Err(self.commands.busy_message(self.commands.depth()));
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => return Err("Extension disconnected".to_string()),
             }
         } else {
             // Clean up
@@ -117,7 +915,7 @@ impl ServerState {
         }
 
         // Wait for response with timeout
-        match timeout(Duration::from_secs(30), rx.recv()).await {
+        match timeout(self.request_timeout, rx.recv()).await {
             Ok(Some(response)) => {
                 // Clean up
                 self.pending_requests.write().await.remove(&id);
@@ -155,22 +953,69 @@ impl ServerState {
 // MCP Request Handler
 // ============================================================================
 
-async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpcRes {
+/// Client notifications (no id): nothing is sent back
+async fn handle_mcp_notification(req: JsonRpcReq, state: Arc<ServerState>, session: String) {
+    debug!("MCP notification: method={} session={}", req.method, session);
+    let roots_changed = matches!(
+        req.method.as_str(),
+        "notifications/initialized" | "notifications/roots/list_changed"
+    );
+    if roots_changed && state.roots.supports_roots(&session).await {
+        state.refresh_roots(&session).await;
+    }
+}
+
+async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>, session: &str) -> JsonRpcRes {
+    if req.method != "tools/call" {
+        return dispatch_mcp_request(req, state, session).await;
+    }
+    // Tool calls go on the session's timeline for session_report
+    let call = PendingCall::begin(req.params.as_ref());
+    let res = dispatch_mcp_request(req, Arc::clone(&state), session).await;
+    state.finish_call(session, call.finish(&res), &res).await;
+    res
+}
+
+async fn dispatch_mcp_request(req: JsonRpcReq, state: Arc<ServerState>, session: &str) -> JsonRpcRes {
     let id = req.id.clone();
-    info!("MCP request: method={}", req.method);
+    info!("MCP request: method={} session={}", req.method, session);
 
     // Handle built-in methods
     match req.method.as_str() {
+        // Connections that must authenticate are gated before reaching here
+        auth::AUTHENTICATE_METHOD => auth::authenticated(id),
         "ping" => {
             JsonRpcRes::ok(id, serde_json::json!({"ok": true}))
         }
         "initialize" => {
+            let capabilities = req.params.as_ref().and_then(|p| p.get("capabilities"));
+            state.sampling.set_capabilities(session, capabilities).await;
+            state.roots.set_capabilities(session, capabilities).await;
+            let requested = req
+                .params
+                .as_ref()
+                .and_then(|p| p.get("protocolVersion"))
+                .and_then(|v| v.as_str());
+            let version = protocol::negotiate(requested);
+            if requested != Some(version) {
+                info!("Client requested protocol {:?}, using {}", requested, version);
+            }
+            state.protocol_versions.set(session, version).await;
+            let client_info = req.params.as_ref().and_then(|p| p.get("clientInfo"));
+            state.sessions.register(session, client_info).await;
             JsonRpcRes::ok(
                 id,
                 serde_json::json!({
-                    "protocolVersion": "2024-11-05",
+                    "protocolVersion": version,
                     "capabilities": {
-                        "tools": {}
+                        "tools": {
+                            "listChanged": true
+                        },
+                        "resources": {
+                            "listChanged": true
+                        },
+                        "prompts": {},
+                        "completions": {}
                     },
                     "serverInfo": {
                         "name": "agent-browser",
@@ -183,177 +1028,34 @@ async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpc
             JsonRpcRes::ok(
                 id,
                 serde_json::json!({
-                    "tools": [
-                        {
-                            "name": "playwright_navigate",
-                            "description": "Navigate to a URL in the browser",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "url": {
-                                        "type": "string",
-                                        "description": "The URL to navigate to"
-                                    }
-                                },
-                                "required": ["url"]
-                            }
-                        },
-                        {
-                            "name": "playwright_click",
-                            "description": "Click an element on the page",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "selector": {
-                                        "type": "string",
-                                        "description": "CSS selector for the element to click"
-                                    }
-                                },
-                                "required": ["selector"]
-                            }
-                        },
-                        {
-                            "name": "playwright_fill",
-                            "description": "Fill out an input field",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "selector": {
-                                        "type": "string",
-                                        "description": "CSS selector for the input element"
-                                    },
-                                    "value": {
-                                        "type": "string",
-                                        "description": "The text to type into the input"
-                                    }
-                                },
-                                "required": ["selector", "value"]
-                            }
-                        },
-                        {
-                            "name": "playwright_screenshot",
-                            "description": "Take a screenshot of the current page or a specific element",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "selector": {
-                                        "type": "string",
-                                        "description": "Optional CSS selector to screenshot a specific element"
-                                    },
-                                    "fullPage": {
-                                        "type": "boolean",
-                                        "description": "Whether to take a full page screenshot"
-                                    }
-                                }
-                            }
-                        },
-                        {
-                            "name": "passkey_enable",
-                            "description": "Enable or disable passkey automation for WebAuthn flows",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "enabled": {
-                                        "type": "boolean",
-                                        "description": "Whether to enable passkey automation"
-                                    }
-                                },
-                                "required": ["enabled"]
-                            }
-                        },
-                        {
-                            "name": "passkey_status",
-                            "description": "Get the current status of passkey automation",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {}
-                            }
-                        },
-                        {
-                            "name": "passkey_list",
-                            "description": "List all stored passkey credentials",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {}
-                            }
-                        },
-                        {
-                            "name": "passkey_clear",
-                            "description": "Clear all stored passkey credentials",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {}
-                            }
-                        },
-                        {
-                            "name": "passkey_authorize",
-                            "description": "Authorize AI agent to use passkeys for a limited time (requires Touch ID on macOS)",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "duration_hours": {
-                                        "type": "number",
-                                        "description": "Number of hours to authorize access (default: 8)"
-                                    }
-                                },
-                                "required": []
-                            }
-                        },
-                        {
-                            "name": "passkey_authorization_status",
-                            "description": "Check if AI agent is currently authorized to use passkeys",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {}
-                            }
-                        },
-                        {
-                            "name": "playwright_detect_modal",
-                            "description": "Detect if a modal, popup, or overlay is present on the page",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "minZIndex": {
-                                        "type": "number",
-                                        "description": "Minimum z-index to consider (default: 100)"
-                                    },
-                                    "includeHidden": {
-                                        "type": "boolean",
-                                        "description": "Include hidden modals (default: false)"
-                                    },
-                                    "maxResults": {
-                                        "type": "number",
-                                        "description": "Maximum number of modals to detect (default: 1)"
-                                    }
-                                }
-                            }
-                        },
-                        {
-                            "name": "playwright_dismiss_modal",
-                            "description": "Attempt to dismiss any detected modals on the page",
-                            "inputSchema": {
-                                "type": "object",
-                                "properties": {
-                                    "strategy": {
-                                        "type": "string",
-                                        "enum": ["auto", "button", "escape", "backdrop", "remove"],
-                                        "description": "Dismissal strategy: auto tries all methods, button clicks dismiss button, escape presses ESC, backdrop clicks overlay, remove forcibly removes from DOM (default: auto)"
-                                    },
-                                    "timeout": {
-                                        "type": "number",
-                                        "description": "Timeout in milliseconds (default: 5000)"
-                                    },
-                                    "waitAfter": {
-                                        "type": "number",
-                                        "description": "Wait time after dismissal to verify (default: 500)"
-                                    }
-                                }
-                            }
-                        }
-                    ]
+                    "tools": state.available_tools().await
                 }),
             )
         }
+        "resources/list" => handle_resources_list(&state, id).await,
+        "resources/templates/list" => JsonRpcRes::ok(
+            id,
+            serde_json::json!({ "resourceTemplates": resources::templates() }),
+        ),
+        "resources/read" => {
+            let uri = req
+                .params
+                .as_ref()
+                .and_then(|p| p.get("uri"))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            handle_resources_read(&uri, &state, session, id).await
+        }
+        "prompts/list" => JsonRpcRes::ok(id, serde_json::json!({ "prompts": prompts::list() })),
+        "prompts/get" => {
+            let params = req.params.unwrap_or(serde_json::Value::Null);
+            handle_prompts_get(&params, &state, session, id).await
+        }
+        "completion/complete" => {
+            let params = req.params.unwrap_or(serde_json::Value::Null);
+            handle_completion(&params, &state, session, id).await
+        }
         "tools/call" => {
             // Extract tool name and arguments from MCP format
             let params = req.params.unwrap_or(serde_json::Value::Null);
@@ -366,9 +1068,19 @@ async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpc
                 .get("arguments")
                 .cloned()
                 .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
+            let progress_token = params
+                .get("_meta")
+                .and_then(|m| m.get("progressToken"))
+                .filter(|t| t.is_string() || t.is_number())
+                .cloned();
 
             match tool_name {
                 Ok(name) => {
+                    // Refuse tools the connected extension advertised it can't back
+                    if let Some(reason) = state.extension_tools.unsupported(name).await {
+                        return JsonRpcRes::err(id, -32601, reason, None);
+                    }
+
                     // Handle server-side tools (don't forward to extension)
                     match name {
                         "passkey_authorize" => {
@@ -379,49 +1091,725 @@ async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpc
 
                             let duration = std::time::Duration::from_secs((duration_hours * 3600.0) as u64);
 
-                            return match state.credential_store.authorize_session(duration).await {
-                                Ok(_) => JsonRpcRes::ok(
-                                    id,
-                                    serde_json::json!({
-                                        "authorized": true,
-                                        "duration_hours": duration_hours,
-                                        "message": format!("Authorized for {} hours", duration_hours)
-                                    }),
-                                ),
+                            return match state.credential_store.authorize_session(session, duration).await {
+                                Ok(_) => {
+                                    state.alerts.send(
+                                        Alert::new(
+                                            EventClass::Authorization,
+                                            "Passkey use authorized",
+                                            format!("Authorized for {} hours", duration_hours),
+                                        )
+                                        .for_session(session),
+                                    );
+                                    JsonRpcRes::ok(
+                                        id,
+                                        serde_json::json!({
+                                            "authorized": true,
+                                            "duration_hours": duration_hours,
+                                            "message": format!("Authorized for {} hours", duration_hours)
+                                        }),
+                                    )
+                                }
                                 Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
                             };
                         }
                         "passkey_authorization_status" => {
-                            let status = state.credential_store.get_authorization_status().await;
+                            let status = state.credential_store.get_authorization_status(session).await;
                             return JsonRpcRes::ok(id, status);
                         }
-                        _ => {}
-                    }
+                        "passkey_grant_create" => {
+                            let Some(rp_id) = arguments.get("rp_id").and_then(|v| v.as_str()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing rp_id", None);
+                            };
+                            let operation = arguments.get("operation").and_then(|v| v.as_str());
+                            if operation.is_some_and(|op| op != "get" && op != "create") {
+                                return JsonRpcRes::err(id, -32602, "operation must be get or create", None);
+                            }
+                            let duration_minutes = arguments
+                                .get("duration_minutes")
+                                .and_then(|v| v.as_f64())
+                                .unwrap_or(5.0);
+                            let duration = std::time::Duration::from_secs((duration_minutes * 60.0) as u64);
 
-                    // Map MCP tool names to internal command names
-                    let internal_method = match name {
-                        "playwright_navigate" => "navigate",
-                        "playwright_click" => "click",
-                        "playwright_fill" => "type",
-                        "playwright_screenshot" => "screenshot",
-                        "playwright_detect_modal" => "detect_modal",
-                        "playwright_dismiss_modal" => "dismiss_modal",
-                        "passkey_enable" => "passkey_enable",
-                        "passkey_status" => "passkey_status",
-                        "passkey_list" => "passkey_list",
-                        "passkey_clear" => "passkey_clear",
-                        _ => {
-                            return JsonRpcRes::err(
+                            return match state.credential_store.mint_grant(session, rp_id, operation, duration).await {
+                                Ok((token, grant)) => {
+                                    state.alerts.send(
+                                        Alert::new(
+                                            EventClass::Authorization,
+                                            "Passkey grant issued",
+                                            format!("Grant {} for {} until {}", grant.id, grant.rp_id, grant.expires_at),
+                                        )
+                                        .for_session(session),
+                                    );
+                                    JsonRpcRes::ok(
+                                        id,
+                                        serde_json::json!({
+                                            "token": token,
+                                            "grant_id": grant.id,
+                                            "rp_id": grant.rp_id,
+                                            "operation": grant.operation,
+                                            "expires_at": grant.expires_at,
+                                        }),
+                                    )
+                                }
+                                Err(e) => JsonRpcRes::err(id, -32001, e.to_string(), None),
+                            };
+                        }
+                        "passkey_grant_redeem" => {
+                            let Some(token) = arguments.get("token").and_then(|v| v.as_str()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing token", None);
+                            };
+                            return match state.credential_store.redeem_grant(session, token).await {
+                                Ok(grant) => {
+                                    state.alerts.send(
+                                        Alert::new(
+                                            EventClass::Authorization,
+                                            "Passkey grant redeemed",
+                                            format!("Grant {} for {} issued by {}", grant.id, grant.rp_id, grant.issued_by),
+                                        )
+                                        .for_session(session),
+                                    );
+                                    JsonRpcRes::ok(
+                                        id,
+                                        serde_json::json!({
+                                            "redeemed": true,
+                                            "rp_id": grant.rp_id,
+                                            "operation": grant.operation,
+                                            "expires_at": grant.expires_at,
+                                        }),
+                                    )
+                                }
+                                Err(e) => JsonRpcRes::err(id, -32001, e.to_string(), None),
+                            };
+                        }
+                        "state_set" | "state_get" | "state_delete" => {
+                            return handle_state_tool(name, &arguments, &state, id).await;
+                        }
+                        "data_transform" => {
+                            return handle_data_transform(&arguments, &state, id).await;
+                        }
+                        "snapshot_save" => {
+                            return handle_snapshot_save(&arguments, &state, session, id).await;
+                        }
+                        "baseline_set" => {
+                            return handle_baseline_set(&arguments, &state, session, id).await;
+                        }
+                        "baseline_list" | "baseline_delete" => {
+                            return handle_baseline_tool(name, &arguments, &state, id);
+                        }
+                        "screenshot_diff" => {
+                            return handle_screenshot_diff(&arguments, &state, session, id).await;
+                        }
+                        "browser_wait_for_download" => {
+                            return handle_wait_for_download(&arguments, &state, session, id).await;
+                        }
+                        "browser_profiling_stop" => {
+                            return handle_profiling_stop(&state, session, id).await;
+                        }
+                        "browser_screenshot_elements" => {
+                            return handle_screenshot_elements(&arguments, &state, session, id).await;
+                        }
+                        "browser_extract_table" => {
+                            return handle_extract_table(&arguments, &state, session, id).await;
+                        }
+                        "browser_recording_start" => {
+                            return handle_recording_start(&arguments, &state, session, id).await;
+                        }
+                        "browser_recording_stop" => {
+                            return handle_recording_stop(&state, session, id).await;
+                        }
+                        "audit_accessibility" => {
+                            return handle_audit_accessibility(&arguments, &state, session, id).await;
+                        }
+                        "audit_seo" => {
+                            return handle_audit_seo(&arguments, &state, session, id).await;
+                        }
+                        "check_links" => {
+                            return handle_check_links(&arguments, &state, session, id).await;
+                        }
+                        "dismiss_consent" => {
+                            return handle_dismiss_consent(&arguments, &state, session, id).await;
+                        }
+                        "browser_follow_popups" => {
+                            let Some(enabled) = arguments.get("enabled").and_then(|v| v.as_bool()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing enabled", None);
+                            };
+                            state.targets.set_follow(session, enabled).await;
+                            return JsonRpcRes::ok(
+                                id,
+                                serde_json::json!({
+                                    "enabled": enabled,
+                                    "tabId": state.targets.current_tab(session).await,
+                                }),
+                            );
+                        }
+                        "explore_menu" => {
+                            return handle_explore_menu(&arguments, &state, session, id).await;
+                        }
+                        "watch_add" | "watch_list" | "watch_remove" => {
+                            return handle_watch_tool(name, &arguments, &state, id).await;
+                        }
+                        "snapshot_get" => {
+                            let include = parse_snapshot_parts(&arguments);
+                            let result = match arguments.get("name").and_then(|v| v.as_str()) {
+                                Some(name) => state
+                                    .snapshot_store
+                                    .get(name, &include)
+                                    .and_then(|snapshot| Ok(serde_json::to_value(snapshot)?)),
+                                None => state.snapshot_store.list().map(|snapshots| {
+                                    serde_json::json!({ "snapshots": snapshots, "count": snapshots.len() })
+                                }),
+                            };
+                            return match result {
+                                Ok(result) => JsonRpcRes::ok(id, result),
+                                Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+                            };
+                        }
+                        "browser_list_dialogs" => {
+                            let tab_id = arguments.get("tabId").and_then(|v| v.as_i64());
+                            let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+                            let mut dialogs = state.dialogs.list(tab_id, usize::MAX).await;
+                            if arguments.get("openOnly").and_then(|v| v.as_bool()) == Some(true) {
+                                dialogs.retain(|dialog| dialog.is_open());
+                            }
+                            dialogs.truncate(limit);
+                            return JsonRpcRes::ok(
+                                id,
+                                serde_json::json!({ "count": dialogs.len(), "dialogs": dialogs }),
+                            );
+                        }
+                        "network_list_websockets" => {
+                            let limit = arguments
+                                .get("limit")
+                                .and_then(|v| v.as_u64())
+                                .unwrap_or(50) as usize;
+                            let filter = WebSocketFilter {
+                                url_pattern: arguments.get("urlPattern").and_then(|v| v.as_str()),
+                                include_messages: arguments
+                                    .get("includeMessages")
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(true),
+                                limit: Some(limit),
+                            };
+                            let websockets = state.network_capture.list_websockets(&filter).await;
+                            let include_webrtc = arguments
+                                .get("includeWebRTC")
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(true);
+                            let webrtc = if include_webrtc {
+                                state.network_capture.list_webrtc_events(Some(limit)).await
+                            } else {
+                                Vec::new()
+                            };
+                            return JsonRpcRes::ok(
+                                id,
+                                serde_json::json!({
+                                    "websockets": websockets,
+                                    "webrtc": webrtc
+                                }),
+                            );
+                        }
+                        "telemetry_status" => {
+                            let epsilon = match arguments.get("epsilon") {
+                                None => telemetry::DEFAULT_EPSILON,
+                                Some(v) => match v.as_f64().filter(|e| *e > 0.0 && *e <= telemetry::MAX_EPSILON) {
+                                    Some(epsilon) => epsilon,
+                                    None => {
+                                        let message = format!("epsilon must be a number above 0 and at most {}", telemetry::MAX_EPSILON);
+                                        return JsonRpcRes::err(id, -32602, message, None);
+                                    }
+                                },
+                            };
+                            if arguments.get("reset").and_then(|v| v.as_bool()) == Some(true) {
+                                if let Err(e) = state.telemetry.reset() {
+                                    return JsonRpcRes::err(id, -32000, e.to_string(), None);
+                                }
+                            }
+                            return JsonRpcRes::ok(id, state.telemetry.status(epsilon));
+                        }
+                        "server_metrics" => {
+                            let sessions = state.protocol_versions.sessions().await.len();
+                            let pending = state.pending_requests.read().await.len();
+                            return JsonRpcRes::ok(
+                                id,
+                                serde_json::json!({
+                                    "extensionConnected": state.extension_tx.read().await.is_some(),
+                                    "commandQueue": state.commands.metrics(),
+                                    "pendingRequests": pending,
+                                    "mcpSessions": sessions,
+                                    "companion": match &state.companion {
+                                        Some(pool) => pool.metrics().await,
+                                        None => serde_json::Value::Null,
+                                    },
+                                }),
+                            );
+                        }
+                        "storage_status" => {
+                            let cleanup = arguments.get("cleanup").and_then(|v| v.as_bool()).unwrap_or(false);
+                            let cleaned = if cleanup {
+                                match quota::enforce_all() {
+                                    Ok(reports) => Some(reports),
+                                    Err(e) => return JsonRpcRes::err(id, -32000, format!("Cleanup failed: {}", e), None),
+                                }
+                            } else {
+                                None
+                            };
+                            return match quota::status() {
+                                Ok(mut status) => {
+                                    if let Some(cleaned) = cleaned {
+                                        status["cleanup"] = serde_json::json!(cleaned);
+                                    }
+                                    JsonRpcRes::ok(id, status)
+                                }
+                                Err(e) => JsonRpcRes::err(id, -32000, format!("Failed to read storage status: {}", e), None),
+                            };
+                        }
+                        "history_stats" => {
+                            let days = arguments.get("days").and_then(|v| v.as_u64()).unwrap_or(30).min(366) as usize;
+                            let compact = arguments.get("compact").and_then(|v| v.as_bool()).unwrap_or(false);
+                            let compacted = if compact {
+                                match state.credential_store.compact_audit_log(compaction::retention_days()).await {
+                                    Ok(report) => Some(report),
+                                    Err(e) => return JsonRpcRes::err(id, -32000, format!("Compaction failed: {}", e), None),
+                                }
+                            } else {
+                                None
+                            };
+                            return match storage::data_dir().and_then(|dir| compaction::stats(&dir, days)) {
+                                Ok(mut stats) => {
+                                    if let Some(report) = compacted {
+                                        stats["compaction"] = serde_json::json!(report);
+                                    }
+                                    JsonRpcRes::ok(id, stats)
+                                }
+                                Err(e) => JsonRpcRes::err(id, -32000, format!("Failed to read audit history: {}", e), None),
+                            };
+                        }
+                        "alerts_test" => {
+                            let classes = match arguments.get("class") {
+                                Some(class) => match serde_json::from_value::<EventClass>(class.clone()) {
+                                    Ok(class) => vec![class],
+                                    Err(_) => return JsonRpcRes::err(id, -32602, "Invalid class", None),
+                                },
+                                None => vec![EventClass::Authorization, EventClass::PolicyViolation, EventClass::TaskCompletion],
+                            };
+                            let mut results = Vec::new();
+                            for class in classes {
+                                let alert = Alert::new(class, "Test alert", format!("Test of the {} route", class.name()))
+                                    .for_session(session);
+                                for mut result in state.alerts.deliver(&alert).await {
+                                    result["class"] = serde_json::json!(class);
+                                    results.push(result);
+                                }
+                            }
+                            let mut status = state.alerts.describe();
+                            status["results"] = serde_json::json!(results);
+                            return JsonRpcRes::ok(id, status);
+                        }
+                        "session_artifacts_list" => {
+                            return match artifacts::list(session) {
+                                Ok(files) => JsonRpcRes::ok(
+                                    id,
+                                    serde_json::json!({
+                                        "directory": artifacts::session_dir(session).ok().map(|d| d.display().to_string()),
+                                        "files": files,
+                                        "count": files.len(),
+                                        "retentionHours": artifacts::retention().as_secs() / 3600,
+                                    }),
+                                ),
+                                Err(e) => JsonRpcRes::err(id, -32000, format!("Failed to list artifacts: {}", e), None),
+                            };
+                        }
+                        "artifacts_search" => {
+                            return handle_artifacts_search(&arguments, &state, id).await;
+                        }
+                        "session_report" => {
+                            return handle_session_report(&arguments, &state, session, id).await;
+                        }
+                        "history_retry" => {
+                            return handle_history_retry(&arguments, &state, session, id).await;
+                        }
+                        "session_list" => {
+                            return JsonRpcRes::ok(
+                                id,
+                                serde_json::json!({ "session": session, "sessions": state.sessions.list().await }),
+                            );
+                        }
+                        "session_observe" => {
+                            let Some(target) = arguments.get("session").and_then(|v| v.as_str()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing session", None);
+                            };
+                            let observe = !arguments.get("stop").and_then(|v| v.as_bool()).unwrap_or(false);
+                            return match state.sessions.observe(session, target, observe).await {
+                                Ok(()) => JsonRpcRes::ok(id, serde_json::json!({ "session": target, "observing": observe })),
+                                Err(e) => JsonRpcRes::err(id, -32602, e, None),
+                            };
+                        }
+                        "session_inject" => {
+                            return handle_session_inject(&arguments, &state, session, id).await;
+                        }
+                        "capabilities_diff" => {
+                            return handle_capabilities_diff(&arguments, &state, session, id).await;
+                        }
+                        "preflight" => {
+                            let Some(url) = arguments.get("url").and_then(|v| v.as_str()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing url", None);
+                            };
+                            let target = match preflight::Target::parse(url) {
+                                Ok(target) => target,
+                                Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                            };
+                            let timeout_ms = arguments
+                                .get("timeoutMs")
+                                .and_then(|v| v.as_u64())
+                                .unwrap_or(preflight::DEFAULT_TIMEOUT_MS)
+                                .min(preflight::MAX_TIMEOUT_MS);
+                            let mut report = preflight::check(&target, timeout_ms).await;
+                            report["url"] = serde_json::json!(url);
+                            return JsonRpcRes::ok(id, report);
+                        }
+                        "network_capture_configure" => {
+                            return handle_network_capture_configure(&arguments, &state, id).await;
+                        }
+                        "network_list_requests" => {
+                            return handle_network_list_requests(&arguments, &state, session, id).await;
+                        }
+                        "network_get_request" => {
+                            let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+                            let entries = state
+                                .network_capture
+                                .find_http(
+                                    arguments.get("id").and_then(|v| v.as_str()),
+                                    arguments.get("urlPattern").and_then(|v| v.as_str()),
+                                    limit,
+                                )
+                                .await;
+                            return JsonRpcRes::ok(
                                 id,
-                                -32601,
-                                format!("Unknown tool: {}", name),
-                                None,
+                                serde_json::json!({ "requests": entries, "count": entries.len() }),
                             );
                         }
+                        "recovery_secret_store" => {
+                            let rp_id = arguments.get("rp_id").and_then(|v| v.as_str());
+                            let kind = arguments
+                                .get("kind")
+                                .cloned()
+                                .and_then(|v| serde_json::from_value::<RecoverySecretKind>(v).ok());
+                            let values: Vec<String> = arguments
+                                .get("values")
+                                .and_then(|v| v.as_array())
+                                .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+                                .unwrap_or_default();
+                            let label = arguments.get("label").and_then(|v| v.as_str());
+
+                            let (Some(rp_id), Some(kind)) = (rp_id, kind) else {
+                                return JsonRpcRes::err(id, -32602, "Missing or invalid rp_id/kind", None);
+                            };
+                            if let Err(e) = state.credential_store.require_elevated(session, "storage", rp_id).await {
+                                return refuse_recovery_secret(&state, session, id, rp_id, e);
+                            }
+
+                            return match state
+                                .credential_store
+                                .store_recovery_secrets(rp_id, kind, label, &values)
+                                .await
+                            {
+                                Ok(stored) => JsonRpcRes::ok(
+                                    id,
+                                    serde_json::json!({ "stored": stored, "count": stored.len() }),
+                                ),
+                                Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+                            };
+                        }
+                        "recovery_secret_list" => {
+                            let rp_id = arguments.get("rp_id").and_then(|v| v.as_str());
+                            if let Err(e) = state
+                                .credential_store
+                                .require_authorized(session, "listing", rp_id.unwrap_or("any"))
+                                .await
+                            {
+                                return refuse_recovery_secret(&state, session, id, rp_id.unwrap_or("any"), e);
+                            }
+                            return match state.credential_store.list_recovery_secrets(rp_id).await {
+                                Ok(secrets) => JsonRpcRes::ok(
+                                    id,
+                                    serde_json::json!({ "secrets": secrets, "count": secrets.len() }),
+                                ),
+                                Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+                            };
+                        }
+                        "recovery_authorize" => {
+                            let duration_minutes = arguments
+                                .get("duration_minutes")
+                                .and_then(|v| v.as_f64())
+                                .unwrap_or(5.0);
+
+                            let duration = std::time::Duration::from_secs((duration_minutes * 60.0) as u64);
+
+                            return match state.credential_store.authorize_elevated(session, duration).await {
+                                Ok(until) => {
+                                    state.alerts.send(
+                                        Alert::new(
+                                            EventClass::Authorization,
+                                            "Recovery access authorized",
+                                            format!("Elevated until {}", until),
+                                        )
+                                        .for_session(session),
+                                    );
+                                    JsonRpcRes::ok(
+                                        id,
+                                        serde_json::json!({
+                                            "elevated": true,
+                                            "expires_at": until
+                                        }),
+                                    )
+                                }
+                                Err(e) => JsonRpcRes::err(id, -32001, e.to_string(), None),
+                            };
+                        }
+                        "recovery_secret_get" => {
+                            let rp_id = arguments.get("rp_id").and_then(|v| v.as_str());
+                            let kind = arguments
+                                .get("kind")
+                                .cloned()
+                                .and_then(|v| serde_json::from_value::<RecoverySecretKind>(v).ok());
+                            let label = arguments.get("label").and_then(|v| v.as_str());
+
+                            let (Some(rp_id), Some(kind)) = (rp_id, kind) else {
+                                return JsonRpcRes::err(id, -32602, "Missing or invalid rp_id/kind", None);
+                            };
+
+                            return match state.credential_store.take_recovery_secret(session, rp_id, kind, label).await {
+                                Ok((secret, value)) => JsonRpcRes::ok(
+                                    id,
+                                    serde_json::json!({ "secret": secret, "value": value }),
+                                ),
+                                Err(e) => refuse_recovery_secret(&state, session, id, rp_id, e),
+                            };
+                        }
+                        "recovery_secret_delete" => {
+                            let Some(rp_id) = arguments.get("rp_id").and_then(|v| v.as_str()) else {
+                                return JsonRpcRes::err(id, -32602, "Missing rp_id", None);
+                            };
+                            if let Err(e) = state.credential_store.require_elevated(session, "deletion", rp_id).await {
+                                return refuse_recovery_secret(&state, session, id, rp_id, e);
+                            }
+                            return match state.credential_store.delete_recovery_secrets(rp_id).await {
+                                Ok(removed) => JsonRpcRes::ok(id, serde_json::json!({ "deleted": removed })),
+                                Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+                            };
+                        }
+                        _ => {}
+                    }
+
+                    // Map MCP tool names to extension commands
+                    let Some(internal_method) = state.extension_tools.command_for(name).await else {
+                        return JsonRpcRes::err(
+                            id,
+                            -32601,
+                            format!("Unknown tool: {}", name),
+                            None,
+                        );
+                    };
+
+                    if name == "browser_clear_site_data" {
+                        if let Err(e) = validate_clear_site_data(&arguments) {
+                            return JsonRpcRes::err(id, -32602, e, None);
+                        }
+                    }
+
+                    // Arbitrary page scripts need an explicit opt-in, server-wide or per session
+                    if name == "playwright_evaluate"
+                        && !state.allow_evaluate
+                        && !state.credential_store.is_session_authorized(session).await
+                    {
+                        state.alerts.send(
+                            Alert::new(EventClass::PolicyViolation, "Page evaluation refused", "playwright_evaluate called without authorization")
+                                .for_session(session),
+                        );
+                        return JsonRpcRes::err(id, -32001, EVALUATE_DISABLED_ERROR, None);
+                    }
+
+                    if name == "media_control" || name == "media_capture_frame" {
+                        if let Err(e) = validate_media_arguments(name, &arguments) {
+                            return JsonRpcRes::err(id, -32602, e, None);
+                        }
+                    }
+
+                    // Files are written by the server, inside the session's roots
+                    let save_path = match arguments.get("savePath").and_then(|v| v.as_str()) {
+                        Some(path) if FILE_OUTPUT_TOOLS.contains(&name) => match state.roots.resolve(session, path).await {
+                            Ok(resolved) => Some(resolved),
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        },
+                        _ => None,
+                    };
+
+                    // Iframe targeting; checked before the per-tool validators rebuild the params
+                    let frame = match arguments.get("frame").filter(|_| mcp::tools::FRAME_TOOLS.contains(&name)) {
+                        Some(frame) => match prepare_frame(frame) {
+                            Ok(frame) => Some(frame),
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        },
+                        None => None,
+                    };
+                    let locator = match arguments.get("locator").filter(|_| mcp::tools::LOCATOR_TOOLS.contains(&name)) {
+                        Some(_) if arguments.get("selector").is_some() => {
+                            return JsonRpcRes::err(id, -32602, "Pass selector or locator, not both", None);
+                        }
+                        Some(locator) => match prepare_locator(locator) {
+                            Ok(locator) => Some(locator),
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        },
+                        None => None,
                     };
 
-                    // Special handling for playwright_fill -> type
-                    let internal_params = if name == "playwright_fill" {
+                    let internal_params = if name == "type_text" {
+                        match prepare_type_text(&arguments, state.request_timeout) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "canvas_read_pixels" {
+                        match prepare_canvas_read_pixels(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_navigate" {
+                        match prepare_navigate(&arguments, state.request_timeout) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_wait_for_load" {
+                        match prepare_wait_for_load(&arguments, state.request_timeout) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "scroll_into_view" {
+                        match prepare_scroll_into_view(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_evaluate" {
+                        match prepare_evaluate(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "set_date" {
+                        match prepare_set_date(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_emulate_locale" {
+                        match prepare_emulate_locale(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_set_geolocation" {
+                        match prepare_set_geolocation(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_handle_dialogs" {
+                        match prepare_dialog_policy(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_throttle" {
+                        match prepare_throttle(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if matches!(name, "playwright_click" | "playwright_dblclick" | "playwright_context_click") {
+                        match prepare_click(name, &arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_select" {
+                        match prepare_select(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_get_text" {
+                        match prepare_get_text(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_fill_form" {
+                        match prepare_fill_form(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_press_key" {
+                        match prepare_press_key(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_get_element" {
+                        match prepare_get_element(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_get_html" {
+                        match prepare_get_html(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_screenshot" {
+                        match prepare_screenshot(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_list_forms" {
+                        match prepare_list_forms(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_collect_items" {
+                        match prepare_collect_items(&arguments, state.request_timeout) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_find_text" {
+                        match prepare_find_text(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_extract_content" {
+                        match prepare_extract_content(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "assert_page" {
+                        match prepare_assert_page(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_accessibility_tree" {
+                        match prepare_accessibility_tree(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_get_cookies" || name == "browser_clear_cookies" {
+                        match prepare_cookie_query(name, &arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_set_cookie" {
+                        match prepare_set_cookie(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_list_downloads" {
+                        match prepare_list_downloads(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "browser_profiling_start" {
+                        match prepare_profiling_start(&arguments) {
+                            Ok(params) => params,
+                            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+                        }
+                    } else if name == "playwright_fill" {
                         // Rename "value" to "text" for internal type command
                         let mut params_map = match arguments {
                             serde_json::Value::Object(map) => map,
@@ -435,19 +1823,122 @@ async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpc
                         arguments
                     };
 
+                    let passkey_toggle = if name == "passkey_enable" {
+                        internal_params.get("enabled").and_then(|v| v.as_bool())
+                    } else {
+                        None
+                    };
+
+                    // Receipts are captured by the server after the command succeeds
+                    let mut internal_params = internal_params;
+                    let wants_receipt = receipts::RECEIPT_TOOLS.contains(&name)
+                        && internal_params
+                            .as_object_mut()
+                            .and_then(|params| params.remove("captureReceipt"))
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false);
+
+                    if let (Some(frame), Some(params)) = (frame, internal_params.as_object_mut()) {
+                        params.insert("frame".to_string(), frame);
+                    }
+                    if let (Some(locator), Some(params)) = (locator, internal_params.as_object_mut()) {
+                        params.insert("locator".to_string(), locator);
+                    }
+
+                    // Follow mode: commands go to the tab this session is attached to
+                    if let Some(tab_id) = state.targets.current_tab(session).await {
+                        if let Some(params) = internal_params.as_object_mut() {
+                            params.entry("tabId").or_insert(serde_json::json!(tab_id));
+                        }
+                    }
+
+                    if let Some(selector) = internal_params.get("selector").and_then(|v| v.as_str()) {
+                        state.recent_selectors.record(session, selector).await;
+                    }
+
+                    // Kept for suggestions if the selector matches nothing
+                    let failed_target = internal_params
+                        .get("selector")
+                        .and_then(|v| v.as_str())
+                        .map(|selector| (selector.to_string(), internal_params.get("tabId").cloned()));
+                    let receipt_target = wants_receipt.then(|| failed_target.clone()).flatten();
+
                     // Forward to extension
-                    match state.send_to_extension(internal_method, internal_params).await {
-                        Ok(result) => JsonRpcRes::ok(
-                            id,
-                            serde_json::json!({
+                    *state.active_session.write().await = Some(session.to_string());
+                    match state
+                        .send_to_extension_with_progress(&internal_method, internal_params, progress_token, Some(session))
+                        .await
+                    {
+                        Ok(mut result) => {
+                            if state.targets.is_following(session).await {
+                                if name == "playwright_click" || name == "playwright_dblclick" {
+                                    // Popups open just after the click resolves
+                                    tokio::time::sleep(Duration::from_millis(POPUP_SETTLE_MS)).await;
+                                }
+                                let switches = state.targets.take_switches(session).await;
+                                if let (false, Some(obj)) = (switches.is_empty(), result.as_object_mut()) {
+                                    obj.insert("targetSwitches".to_string(), serde_json::json!(switches));
+                                }
+                            }
+                            if name == "playwright_screenshot" {
+                                if let Some(data) = result.get("data").and_then(|v| v.as_str()) {
+                                    let url = result.get("url").and_then(|v| v.as_str()).map(str::to_string);
+                                    state.screenshots.record(data.to_string(), url).await;
+                                    state.notify("notifications/resources/list_changed", serde_json::json!({}));
+                                }
+                            }
+                            if let Some((selector, tab_id)) = receipt_target {
+                                let receipt = capture_receipt(&state, name, &selector, tab_id, session).await;
+                                if let Some(obj) = result.as_object_mut() {
+                                    obj.insert("receipt".to_string(), receipt);
+                                }
+                            }
+                            if let Some(path) = save_path {
+                                if let Err(e) = save_artifact(&path, &mut result).await {
+                                    return JsonRpcRes::err(id, -32000, e, None);
+                                }
+                            }
+                            if let Some(enabled) = passkey_toggle {
+                                let previous = state.passkey_automation.write().await.replace(enabled);
+                                if previous.unwrap_or(true) != enabled {
+                                    state.tools_changed();
+                                }
+                            }
+                            let mut response = serde_json::json!({
                                 "content": [
                                     {
                                         "type": "text",
                                         "text": serde_json::to_string_pretty(&result).unwrap_or_else(|_| "{}".to_string())
                                     }
                                 ]
-                            }),
-                        ),
+                            });
+                            let version = state.protocol_versions.get(session).await;
+                            if protocol::supports_structured_output(version) && result.is_object() {
+                                response["structuredContent"] = result;
+                            }
+                            JsonRpcRes::ok(id, response)
+                        }
+                        Err(e) if e.starts_with(navigation::PAGE_NAVIGATED_ERROR) => {
+                            JsonRpcRes::err(id, navigation::PAGE_NAVIGATED_CODE, e, None)
+                        }
+                        Err(e) if e.starts_with(dialogs::DIALOG_OPEN_ERROR) => {
+                            JsonRpcRes::err(id, dialogs::DIALOG_OPEN_CODE, e, None)
+                        }
+                        Err(e) if e.starts_with(backpressure::SERVER_BUSY_ERROR) => {
+                            JsonRpcRes::err(id, backpressure::SERVER_BUSY_CODE, e, Some(state.commands.busy_data()))
+                        }
+                        Err(e) if e.starts_with(tab_locks::TAB_BUSY_ERROR) => {
+                            JsonRpcRes::err(id, tab_locks::TAB_BUSY_CODE, e, None)
+                        }
+                        Err(e) if suggestions::is_element_not_found(&e) => {
+                            let data = match failed_target {
+                                Some((selector, tab_id)) => {
+                                    Some(suggest_selectors(&state, &selector, tab_id, session).await)
+                                }
+                                None => None,
+                            };
+                            JsonRpcRes::err(id, -32000, e, data)
+                        }
                         Err(e) => JsonRpcRes::err(id, -32000, e, None),
                     }
                 }
@@ -457,84 +1948,3731 @@ async fn handle_mcp_request(req: JsonRpcReq, state: Arc<ServerState>) -> JsonRpc
         _ => {
             // Forward to extension (for raw method calls)
             let params = req.params.unwrap_or(serde_json::Value::Null);
-            match state.send_to_extension(&req.method, params).await {
+            *state.active_session.write().await = Some(session.to_string());
+            match state.send_to_extension_with_progress(&req.method, params, None, Some(session)).await {
                 Ok(result) => JsonRpcRes::ok(id, result),
+                Err(e) if e.starts_with(backpressure::SERVER_BUSY_ERROR) => {
+                    JsonRpcRes::err(id, backpressure::SERVER_BUSY_CODE, e, Some(state.commands.busy_data()))
+                }
+                Err(e) if e.starts_with(tab_locks::TAB_BUSY_ERROR) => {
+                    JsonRpcRes::err(id, tab_locks::TAB_BUSY_CODE, e, None)
+                }
                 Err(e) => JsonRpcRes::err(id, -32000, e, None),
             }
         }
     }
 }
 
-// ============================================================================
-// MCP TCP Server
-// ============================================================================
-
-async fn run_mcp_tcp(state: Arc<ServerState>) -> Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:8084").await?;
-    info!("MCP TCP server listening on 127.0.0.1:8084");
-
-    loop {
-        let (socket, peer) = listener.accept().await?;
-        let state = Arc::clone(&state);
-        tokio::spawn(handle_mcp_tcp_connection(socket, peer, state));
-    }
+/// Headroom a typing run leaves inside the command timeout for focus and events
+const TYPE_TEXT_HEADROOM_MS: u64 = 5000;
+
+/// Whether text needs IME composition events (CJK ideographs, kana, hangul)
+fn needs_composition(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c as u32,
+            0x3040..=0x30FF     // Hiragana, Katakana
+            | 0x3400..=0x4DBF   // CJK Extension A
+            | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF   // Hangul syllables
+            | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        )
+    })
+}
+
+/// Normalize type_text arguments into an explicit per-field plan for the extension
+fn prepare_type_text(arguments: &serde_json::Value, command_timeout: Duration) -> Result<serde_json::Value, String> {
+    let delay_ms = arguments.get("delayMs").and_then(|v| v.as_u64()).unwrap_or(50);
+    let jitter_ms = arguments.get("jitterMs").and_then(|v| v.as_u64()).unwrap_or(0);
+    let composition = arguments.get("composition").and_then(|v| v.as_str()).unwrap_or("auto");
+    let clear = arguments.get("clear").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    if !matches!(composition, "auto" | "always" | "never") {
+        return Err(format!("Invalid composition mode: {}", composition));
+    }
+
+    let raw_fields: Vec<serde_json::Value> = match arguments.get("fields").and_then(|v| v.as_array()) {
+        Some(fields) => fields.clone(),
+        None => vec![serde_json::json!({
+            "selector": arguments.get("selector"),
+            "text": arguments.get("text"),
+        })],
+    };
+
+    let mut fields = Vec::with_capacity(raw_fields.len());
+    let mut total_chars: u64 = 0;
+    for field in &raw_fields {
+        let selector = field
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .ok_or("Each field needs a selector")?;
+        let text = field
+            .get("text")
+            .and_then(|v| v.as_str())
+            .ok_or("Each field needs text")?;
+
+        total_chars += text.chars().count() as u64;
+        let use_composition = match composition {
+            "always" => true,
+            "never" => false,
+            _ => needs_composition(text),
+        };
+        fields.push(serde_json::json!({
+            "selector": selector,
+            "text": text,
+            "clear": field.get("clear").and_then(|v| v.as_bool()).unwrap_or(clear),
+            "composition": use_composition,
+        }));
+    }
+
+    if fields.is_empty() {
+        return Err("No fields to type into".to_string());
+    }
+
+    // Worst case per key is delay + jitter; leave headroom for focus/events
+    let worst_case_ms = total_chars * (delay_ms + jitter_ms);
+    let timeout_ms = command_timeout.as_millis() as u64;
+    if worst_case_ms > timeout_ms.saturating_sub(TYPE_TEXT_HEADROOM_MS) {
+        return Err(format!(
+            "Typing {} characters at up to {}ms each would exceed the {}s command timeout; lower delayMs/jitterMs or split the text",
+            total_chars,
+            delay_ms + jitter_ms,
+            command_timeout.as_secs()
+        ));
+    }
+
+    Ok(serde_json::json!({
+        "fields": fields,
+        "delayMs": delay_ms,
+        "jitterMs": jitter_ms,
+    }))
+}
+
+/// Load states browser_wait_for_load and playwright_navigate's waitUntil accept
+const LOAD_STATES: &[&str] = &["domcontentloaded", "load", "networkidle"];
+const DEFAULT_LOAD_TIMEOUT_MS: u64 = 20_000;
+/// Time a load wait leaves inside the command timeout to report back
+const LOAD_WAIT_HEADROOM_MS: u64 = 2000;
+const DEFAULT_NETWORK_IDLE_MS: u64 = 500;
+const MAX_NETWORK_IDLE_MS: u64 = 10_000;
+const MAX_IDLE_INFLIGHT: u64 = 10;
+
+/// Validate a load wait's state and limits; the wait has to finish inside the command timeout
+fn prepare_load_wait(
+    state: &str,
+    arguments: &serde_json::Value,
+    command_timeout: Duration,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    if !LOAD_STATES.contains(&state) {
+        return Err(format!("Invalid load state '{}': expected domcontentloaded, load, or networkidle", state));
+    }
+    let budget_ms = (command_timeout.as_millis() as u64).saturating_sub(LOAD_WAIT_HEADROOM_MS);
+    let timeout_ms = match arguments.get("timeoutMs").filter(|v| !v.is_null()) {
+        None => DEFAULT_LOAD_TIMEOUT_MS.min(budget_ms),
+        Some(v) => match v.as_u64() {
+            Some(ms) if ms > 0 && ms <= budget_ms => ms,
+            _ => {
+                return Err(format!(
+                    "timeoutMs must be between 1 and {} to finish inside the {}s command timeout",
+                    budget_ms,
+                    command_timeout.as_secs()
+                ))
+            }
+        },
+    };
+    let idle_ms = match arguments.get("idleMs").filter(|v| !v.is_null()) {
+        None => DEFAULT_NETWORK_IDLE_MS,
+        Some(v) => v
+            .as_u64()
+            .filter(|ms| *ms <= MAX_NETWORK_IDLE_MS)
+            .ok_or(format!("idleMs must be between 0 and {}", MAX_NETWORK_IDLE_MS))?,
+    };
+    let max_inflight = match arguments.get("maxInflight").filter(|v| !v.is_null()) {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .filter(|count| *count <= MAX_IDLE_INFLIGHT)
+            .ok_or(format!("maxInflight must be between 0 and {}", MAX_IDLE_INFLIGHT))?,
+    };
+
+    let mut params = serde_json::Map::new();
+    params.insert("timeoutMs".to_string(), serde_json::json!(timeout_ms));
+    params.insert("idleMs".to_string(), serde_json::json!(idle_ms));
+    params.insert("maxInflight".to_string(), serde_json::json!(max_inflight));
+    Ok(params)
+}
+
+/// Validate browser_wait_for_load arguments for the wait_for_load command
+fn prepare_wait_for_load(arguments: &serde_json::Value, command_timeout: Duration) -> Result<serde_json::Value, String> {
+    let state = arguments.get("state").and_then(|v| v.as_str()).unwrap_or("load");
+    let mut params = prepare_load_wait(state, arguments, command_timeout)?;
+    params.insert("state".to_string(), serde_json::json!(state));
+    params.insert(
+        "expectNavigation".to_string(),
+        serde_json::json!(arguments.get("expectNavigation").and_then(|v| v.as_bool()).unwrap_or(false)),
+    );
+    Ok(serde_json::Value::Object(params))
+}
+
+/// Validate playwright_navigate's optional waitUntil; without it the command
+/// returns once the navigation has started
+fn prepare_navigate(arguments: &serde_json::Value, command_timeout: Duration) -> Result<serde_json::Value, String> {
+    let mut params = arguments.as_object().cloned().unwrap_or_default();
+    match params.get("url").and_then(|v| v.as_str()) {
+        Some(url) if !url.trim().is_empty() => {}
+        _ => return Err("Missing url".to_string()),
+    }
+    match arguments.get("waitUntil").filter(|v| !v.is_null()) {
+        None => {
+            params.remove("timeoutMs");
+        }
+        Some(state) => {
+            let state = state.as_str().ok_or("waitUntil must be a string")?;
+            params.extend(prepare_load_wait(state, arguments, command_timeout)?);
+        }
+    }
+    Ok(serde_json::Value::Object(params))
+}
+
+const MAX_STICKY_OFFSET_PX: f64 = 2000.0;
+
+/// Normalize scroll_into_view options; explicit offsets win over sticky-chrome detection
+fn prepare_scroll_into_view(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = selector_or_locator(arguments)?;
+    let block = arguments.get("block").and_then(|v| v.as_str()).unwrap_or("center");
+    if !matches!(block, "start" | "center" | "end" | "nearest") {
+        return Err(format!("Invalid block '{}': expected start, center, end, or nearest", block));
+    }
+    let compensation = arguments
+        .get("stickyCompensation")
+        .and_then(|v| v.as_str())
+        .unwrap_or("auto");
+    if compensation != "auto" && compensation != "none" {
+        return Err(format!("Invalid stickyCompensation '{}': expected auto or none", compensation));
+    }
+
+    let offset = |key: &str| -> Result<Option<f64>, String> {
+        match arguments.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(v) => match v.as_f64() {
+                Some(px) if (0.0..=MAX_STICKY_OFFSET_PX).contains(&px) => Ok(Some(px)),
+                _ => Err(format!("{} must be between 0 and {} pixels", key, MAX_STICKY_OFFSET_PX)),
+            },
+        }
+    };
+    let offset_top = offset("offsetTop")?;
+    let offset_bottom = offset("offsetBottom")?;
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "block": block,
+        "detectSticky": compensation == "auto",
+        "offsetTop": offset_top,
+        "offsetBottom": offset_bottom,
+    }))
+}
+
+const MOUSE_BUTTONS: &[&str] = &["left", "right", "middle"];
+const MAX_CLICK_COUNT: u64 = 3;
+
+/// Validate playwright_click's button and clickCount; playwright_dblclick and
+/// playwright_context_click are the click command with those fixed
+fn prepare_click(name: &str, arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    selector_or_locator(arguments)?;
+    let mut params = arguments.as_object().cloned().unwrap_or_default();
+
+    let button = match name {
+        "playwright_context_click" => "right",
+        _ => match params.get("button") {
+            None | Some(serde_json::Value::Null) => "left",
+            Some(v) => v
+                .as_str()
+                .filter(|button| MOUSE_BUTTONS.contains(button))
+                .ok_or("button must be left, right, or middle")?,
+        },
+    };
+    let click_count = match name {
+        "playwright_dblclick" => 2,
+        "playwright_context_click" => 1,
+        _ => match params.get("clickCount") {
+            None | Some(serde_json::Value::Null) => 1,
+            Some(v) => v
+                .as_u64()
+                .filter(|count| (1..=MAX_CLICK_COUNT).contains(count))
+                .ok_or(format!("clickCount must be between 1 and {}", MAX_CLICK_COUNT))?,
+        },
+    };
+    if button == "right" && click_count > 1 {
+        return Err("A right click opens the context menu once; clickCount must be 1".to_string());
+    }
+
+    params.insert("button".to_string(), serde_json::json!(button));
+    params.insert("clickCount".to_string(), serde_json::json!(click_count));
+    Ok(serde_json::Value::Object(params))
+}
+
+/// Normalize playwright_select's value/label/index (each a single entry or a list) into
+/// the select command's `by` plus `options`
+fn prepare_select(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = selector_or_locator(arguments)?;
+    let given: Vec<(&str, &serde_json::Value)> = ["value", "label", "index"]
+        .into_iter()
+        .filter_map(|key| arguments.get(key).filter(|v| !v.is_null()).map(|v| (key, v)))
+        .collect();
+    let [(by, wanted)] = given[..] else {
+        return Err("Provide exactly one of value, label, or index".to_string());
+    };
+
+    let entries = match wanted {
+        serde_json::Value::Array(entries) => entries.clone(),
+        single => vec![single.clone()],
+    };
+    if entries.is_empty() {
+        return Err(format!("{} must not be empty", by));
+    }
+    let valid = |entry: &serde_json::Value| match by {
+        "index" => entry.is_u64(),
+        _ => entry.is_string(),
+    };
+    if !entries.iter().all(valid) {
+        return Err(match by {
+            "index" => "index must be a non-negative integer or a list of them".to_string(),
+            _ => format!("{} must be a string or a list of strings", by),
+        });
+    }
+
+    Ok(serde_json::json!({ "selector": selector, "by": by, "options": entries }))
+}
+
+/// Field types playwright_fill_form accepts; "auto" goes by the element found
+const FORM_FIELD_TYPES: &[&str] = &["auto", "text", "checkbox", "radio", "select"];
+const MAX_FORM_FIELDS: usize = 100;
+
+/// Validate playwright_fill_form fields, checking each value fits its field type
+fn prepare_fill_form(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let fields = arguments
+        .get("fields")
+        .and_then(|v| v.as_array())
+        .filter(|f| !f.is_empty())
+        .ok_or("fields must be a non-empty array of { selector, value }")?;
+    if fields.len() > MAX_FORM_FIELDS {
+        return Err(format!("At most {} fields per call", MAX_FORM_FIELDS));
+    }
+
+    let mut prepared = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        let target = match (field.get("selector"), field.get("locator")) {
+            (Some(_), Some(_)) => return Err(format!("fields[{}] takes selector or locator, not both", index)),
+            (Some(selector), None) => {
+                let selector = selector
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .ok_or(format!("fields[{}].selector must be a non-empty string", index))?;
+                ("selector", serde_json::json!(selector))
+            }
+            (None, Some(locator)) => ("locator", prepare_locator(locator).map_err(|e| format!("fields[{}]: {}", index, e))?),
+            (None, None) => return Err(format!("fields[{}] needs a selector or locator", index)),
+        };
+        let kind = match field.get("type") {
+            None => "auto",
+            Some(kind) => kind.as_str().filter(|k| FORM_FIELD_TYPES.contains(k)).ok_or(format!(
+                "fields[{}].type must be one of: {}",
+                index,
+                FORM_FIELD_TYPES.join(", ")
+            ))?,
+        };
+        let value = field.get("value").ok_or(format!("fields[{}] is missing value", index))?;
+        // Numbers are typed as text; select values are always lists
+        let value = match (kind, value) {
+            ("auto" | "text", serde_json::Value::Number(n)) => serde_json::json!(n.to_string()),
+            ("auto" | "text" | "radio" | "select", serde_json::Value::String(_)) => value.clone(),
+            ("auto" | "checkbox" | "radio", serde_json::Value::Bool(_)) => value.clone(),
+            ("auto" | "select", serde_json::Value::Array(options)) if options.iter().all(|o| o.is_string()) => value.clone(),
+            ("text", _) => return Err(format!("fields[{}].value must be a string for a text field", index)),
+            ("checkbox", _) => return Err(format!("fields[{}].value must be true or false for a checkbox", index)),
+            ("radio", _) => return Err(format!("fields[{}].value must be the radio's value, or true", index)),
+            ("select", _) => return Err(format!("fields[{}].value must be an option or a list of options", index)),
+            _ => return Err(format!("fields[{}].value must be a string, number, boolean or list of strings", index)),
+        };
+
+        let mut params = serde_json::json!({ target.0: target.1, "type": kind, "value": value });
+        if let Some(clear) = field.get("clear") {
+            params["clear"] = serde_json::json!(clear.as_bool().ok_or(format!("fields[{}].clear must be a boolean", index))?);
+        }
+        prepared.push(params);
+    }
+
+    let stop_on_error = match arguments.get("stopOnError") {
+        None => false,
+        Some(v) => v.as_bool().ok_or("stopOnError must be a boolean")?,
+    };
+    Ok(serde_json::json!({ "fields": prepared, "stopOnError": stop_on_error }))
+}
+
+const DEFAULT_TEXT_LENGTH: u64 = 50_000;
+const MAX_TEXT_LENGTH: u64 = 1_000_000;
+const DEFAULT_TEXT_MATCHES: u64 = 100;
+const MAX_TEXT_MATCHES: u64 = 1_000;
+
+/// Validate playwright_get_text arguments and fill in defaults for the get_text command
+fn prepare_get_text(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(selector) => Some(
+            selector
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let property = match arguments.get("property").and_then(|v| v.as_str()) {
+        None => "innerText",
+        Some(property @ ("innerText" | "textContent")) => property,
+        Some(other) => return Err(format!("property must be innerText or textContent, not {}", other)),
+    };
+    let bounded = |key: &str, default: u64, max: u64| match arguments.get(key) {
+        None => Ok(default),
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=max).contains(n))
+            .ok_or(format!("{} must be an integer from 1 to {}", key, max)),
+    };
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "all": arguments.get("all").and_then(|v| v.as_bool()).unwrap_or(false),
+        "property": property,
+        "maxLength": bounded("maxLength", DEFAULT_TEXT_LENGTH, MAX_TEXT_LENGTH)?,
+        "limit": bounded("limit", DEFAULT_TEXT_MATCHES, MAX_TEXT_MATCHES)?,
+    }))
+}
+
+/// KeyboardEvent.key values playwright_press_key accepts by name, with aliases
+const NAMED_KEYS: &[(&str, &[&str])] = &[
+    ("Enter", &["enter", "return"]),
+    ("Tab", &["tab"]),
+    ("Escape", &["escape", "esc"]),
+    ("Backspace", &["backspace"]),
+    ("Delete", &["delete", "del"]),
+    (" ", &["space", " "]),
+    ("ArrowUp", &["arrowup", "up"]),
+    ("ArrowDown", &["arrowdown", "down"]),
+    ("ArrowLeft", &["arrowleft", "left"]),
+    ("ArrowRight", &["arrowright", "right"]),
+    ("Home", &["home"]),
+    ("End", &["end"]),
+    ("PageUp", &["pageup"]),
+    ("PageDown", &["pagedown"]),
+];
+const MODIFIER_KEYS: &[(&str, &[&str])] = &[
+    ("Alt", &["alt", "option"]),
+    ("Control", &["control", "ctrl"]),
+    ("Meta", &["meta", "cmd", "command"]),
+    ("Shift", &["shift"]),
+];
+const MAX_KEY_PRESSES: u64 = 50;
+
+fn normalize_key(name: &str, table: &[(&'static str, &[&str])]) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    table.iter().find(|(_, aliases)| aliases.contains(&lower.as_str())).map(|(key, _)| *key)
+}
+
+/// Normalize playwright_press_key's key (optionally a "Shift+Tab" style combo),
+/// modifiers and count for the press_key command
+fn prepare_press_key(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let combo = arguments.get("key").and_then(|v| v.as_str()).filter(|k| !k.is_empty()).ok_or("Missing key")?;
+    // "+" alone (or as the last part of "Shift++") is the plus key itself
+    let (prefix, key) = match combo.strip_suffix("++") {
+        Some(prefix) => (Some(prefix), "+"),
+        None => match combo.rsplit_once('+').filter(|(_, key)| !key.is_empty()) {
+            Some((prefix, key)) => (Some(prefix), key),
+            None => (None, combo),
+        },
+    };
+
+    let mut modifiers: Vec<&str> = Vec::new();
+    let listed = arguments.get("modifiers").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let from_combo = prefix.map(|p| p.split('+').map(|m| serde_json::json!(m)).collect()).unwrap_or_else(Vec::new);
+    for modifier in listed.iter().chain(&from_combo) {
+        let name = modifier.as_str().unwrap_or_default();
+        let modifier = normalize_key(name, MODIFIER_KEYS)
+            .ok_or_else(|| format!("Unknown modifier '{}': expected Alt, Control, Meta or Shift", name))?;
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+        }
+    }
+
+    let key = if let Some(named) = normalize_key(key, NAMED_KEYS) {
+        named.to_string()
+    } else if key.chars().count() == 1 {
+        key.to_string()
+    } else if key.len() <= 3 && key.starts_with(['F', 'f']) && key[1..].parse::<u8>().is_ok_and(|n| (1..=12).contains(&n)) {
+        key.to_ascii_uppercase()
+    } else {
+        return Err(format!(
+            "Unknown key '{}': use a single character, F1-F12, or one of {}",
+            key,
+            NAMED_KEYS.iter().map(|(k, aliases)| if *k == " " { aliases[0] } else { k }).collect::<Vec<_>>().join(", ")
+        ));
+    };
+
+    let count = match arguments.get("count") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=MAX_KEY_PRESSES).contains(n))
+            .ok_or(format!("count must be an integer from 1 to {}", MAX_KEY_PRESSES))?,
+    };
+
+    let mut params = serde_json::json!({ "key": key, "modifiers": modifiers, "count": count });
+    if let Some(selector) = arguments.get("selector").and_then(|v| v.as_str()) {
+        params["selector"] = serde_json::json!(selector);
+    }
+    Ok(params)
+}
+
+const DEFAULT_HTML_LENGTH: u64 = 200_000;
+const MAX_HTML_LENGTH: u64 = 5_000_000;
+
+/// What a locator finds elements by
+const LOCATOR_KINDS: &[&str] = &["role", "text", "label", "placeholder"];
+
+/// Validate a Playwright-style locator: exactly one of role, text, label or
+/// placeholder, with name (role only), exact and nth
+fn prepare_locator(locator: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let spec = locator.as_object().ok_or("locator must be an object like { \"role\": \"button\", \"name\": \"Submit\" }")?;
+    let given: Vec<&str> = LOCATOR_KINDS.iter().copied().filter(|kind| spec.contains_key(*kind)).collect();
+    let [kind] = given[..] else {
+        return Err(format!("locator needs exactly one of: {}", LOCATOR_KINDS.join(", ")));
+    };
+    if let Some(unknown) = spec.keys().find(|key| !LOCATOR_KINDS.contains(&key.as_str()) && !["name", "exact", "nth"].contains(&key.as_str())) {
+        return Err(format!("Unknown locator key '{}'", unknown));
+    }
+    let text = |key: &str| -> Result<Option<&str>, String> {
+        spec.get(key)
+            .map(|v| v.as_str().filter(|v| !v.trim().is_empty()).ok_or(format!("locator.{} must be a non-empty string", key)))
+            .transpose()
+    };
+
+    let mut params = serde_json::json!({ kind: text(kind)?.unwrap_or_default() });
+    if kind == "role" {
+        params["role"] = serde_json::json!(params["role"].as_str().unwrap_or_default().trim().to_ascii_lowercase());
+    }
+    match text("name")? {
+        Some(_) if kind != "role" => return Err("locator.name only applies with role".to_string()),
+        Some(name) => params["name"] = serde_json::json!(name),
+        None => {}
+    }
+    if let Some(exact) = spec.get("exact") {
+        params["exact"] = serde_json::json!(exact.as_bool().ok_or("locator.exact must be a boolean")?);
+    }
+    if let Some(nth) = spec.get("nth") {
+        params["nth"] = serde_json::json!(nth.as_u64().ok_or("locator.nth must be a non-negative integer")?);
+    }
+    Ok(params)
+}
+
+/// A tool's selector, or None when it targets a locator instead (applied
+/// separately, see prepare_locator)
+fn selector_or_locator(arguments: &serde_json::Value) -> Result<Option<&str>, String> {
+    match (arguments.get("selector").and_then(|v| v.as_str()), arguments.get("locator")) {
+        (Some(_), Some(_)) => Err("Pass selector or locator, not both".to_string()),
+        (Some(selector), None) => Ok(Some(selector)),
+        (None, Some(_)) => Ok(None),
+        (None, None) => Err("Missing selector or locator".to_string()),
+    }
+}
+
+/// Normalize a selector tool's `frame`: an index among the page's iframes, a
+/// frame id from browser_list_frames, a name, or a URL substring
+fn prepare_frame(frame: &serde_json::Value) -> Result<serde_json::Value, String> {
+    match frame {
+        serde_json::Value::Number(index) => {
+            let index = index.as_u64().ok_or("frame index must be a non-negative integer")?;
+            Ok(serde_json::json!({ "index": index }))
+        }
+        serde_json::Value::String(text) if !text.trim().is_empty() => Ok(serde_json::json!({ "match": text.trim() })),
+        serde_json::Value::Object(spec) => {
+            let given: Vec<(&String, &serde_json::Value)> = spec.iter().collect();
+            let [(key, value)] = given[..] else {
+                return Err("frame must have exactly one of index, id, name or url".to_string());
+            };
+            let valid = match key.as_str() {
+                "index" => value.is_u64(),
+                "id" => value.as_u64().is_some_and(|id| id > 0),
+                "name" | "url" => value.as_str().is_some_and(|v| !v.is_empty()),
+                _ => return Err(format!("Unknown frame key '{}': expected index, id, name or url", key)),
+            };
+            if !valid {
+                return Err(match key.as_str() {
+                    "index" => "frame.index must be a non-negative integer".to_string(),
+                    "id" => "frame.id must be a frame id from browser_list_frames (the top frame is 0)".to_string(),
+                    _ => format!("frame.{} must be a non-empty string", key),
+                });
+            }
+            Ok(serde_json::json!({ key.as_str(): value }))
+        }
+        _ => Err("frame must be an index, a name or URL substring, or { index | id | name | url }".to_string()),
+    }
+}
+
+/// Validate playwright_get_element arguments
+fn prepare_get_element(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = selector_or_locator(arguments)?.map(str::trim);
+    if selector == Some("") {
+        return Err("Missing selector".to_string());
+    }
+    Ok(serde_json::json!({ "selector": selector }))
+}
+
+/// Validate playwright_get_html arguments and fill in defaults for the get_html
+/// command (which, called bare by snapshot_save, returns the raw document)
+fn prepare_get_html(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(selector) => Some(
+            selector
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let flag = |key: &str, default: bool| arguments.get(key).and_then(|v| v.as_bool()).unwrap_or(default);
+    let max_length = match arguments.get("maxLength") {
+        None => DEFAULT_HTML_LENGTH,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=MAX_HTML_LENGTH).contains(n))
+            .ok_or(format!("maxLength must be an integer from 1 to {}", MAX_HTML_LENGTH))?,
+    };
+
+    let mut params = serde_json::json!({
+        "stripScripts": flag("stripScripts", true),
+        "stripStyles": flag("stripStyles", false),
+        "stripComments": flag("stripComments", true),
+        "maxLength": max_length,
+    });
+    if let Some(selector) = selector {
+        params["selector"] = serde_json::json!(selector);
+    }
+    Ok(params)
+}
+
+const DEFAULT_CONTENT_LENGTH: u64 = 20_000;
+const MAX_CONTENT_LENGTH: u64 = 1_000_000;
+
+/// Validate browser_extract_content arguments and fill in defaults
+fn prepare_extract_content(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(selector) => Some(
+            selector
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let max_length = match arguments.get("maxLength") {
+        None => DEFAULT_CONTENT_LENGTH,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=MAX_CONTENT_LENGTH).contains(n))
+            .ok_or(format!("maxLength must be an integer from 1 to {}", MAX_CONTENT_LENGTH))?,
+    };
+    let flag = |key: &str, default: bool| arguments.get(key).and_then(|v| v.as_bool()).unwrap_or(default);
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "maxLength": max_length,
+        "includeLinks": flag("includeLinks", true),
+        "includeImages": flag("includeImages", false),
+    }))
+}
+
+const MAX_HIGHLIGHT_LABEL_CHARS: usize = 80;
+
+/// Validate playwright_screenshot's highlight arguments; the rest pass through
+fn prepare_screenshot(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut params = arguments.clone();
+    let Some(map) = params.as_object_mut() else {
+        return Ok(params);
+    };
+    match map.get("highlightSelector").filter(|v| !v.is_null()) {
+        Some(selector) => {
+            let selector = selector
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("highlightSelector must be a non-empty string")?;
+            map.insert("highlightSelector".to_string(), selector.into());
+        }
+        None if map.get("highlightLabel").is_some_and(|v| !v.is_null()) => {
+            return Err("highlightLabel needs highlightSelector".to_string());
+        }
+        None => return Ok(params),
+    }
+    if let Some(label) = map.get("highlightLabel").filter(|v| !v.is_null()) {
+        let label = label.as_str().ok_or("highlightLabel must be a string")?;
+        if label.chars().count() > MAX_HIGHLIGHT_LABEL_CHARS {
+            return Err(format!("highlightLabel must be at most {} characters", MAX_HIGHLIGHT_LABEL_CHARS));
+        }
+    }
+    Ok(params)
+}
+
+const DEFAULT_FIND_CONTEXT: u64 = 40;
+const MAX_FIND_CONTEXT: u64 = 500;
+const DEFAULT_FIND_RESULTS: u64 = 20;
+const MAX_FIND_RESULTS: u64 = 500;
+
+/// Validate browser_list_forms arguments
+fn prepare_list_forms(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(v) => Some(
+            v.as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let max_options = match arguments.get("maxOptions") {
+        None => 50,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=1000).contains(n))
+            .ok_or("maxOptions must be an integer from 1 to 1000")?,
+    };
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "includeHidden": arguments.get("includeHidden").and_then(|v| v.as_bool()).unwrap_or(false),
+        "maxOptions": max_options,
+    }))
+}
+
+const DEFAULT_COLLECT_TIMEOUT_MS: u64 = 30_000;
+
+/// Validate browser_collect_items arguments; timeoutMs must leave the
+/// command timeout's headroom for the reply
+fn prepare_collect_items(arguments: &serde_json::Value, command_timeout: Duration) -> Result<serde_json::Value, String> {
+    let selector = |key: &str| -> Result<Option<&str>, String> {
+        match arguments.get(key).filter(|v| !v.is_null()) {
+            None => Ok(None),
+            Some(v) => Ok(Some(
+                v.as_str()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .ok_or(format!("{} must be a non-empty string", key))?,
+            )),
+        }
+    };
+    let item_selector = selector("itemSelector")?.ok_or("Missing itemSelector")?;
+    let fields = match arguments.get("fields").filter(|v| !v.is_null()) {
+        None => None,
+        Some(v) => {
+            let fields = v.as_object().filter(|f| !f.is_empty()).ok_or("fields must be a non-empty object")?;
+            if fields.values().any(|spec| spec.as_str().is_none_or(|s| s.trim().is_empty() || s.trim() == "@")) {
+                return Err("Each field must be \"selector\", \"selector@attribute\" or \"@attribute\"".to_string());
+            }
+            Some(fields)
+        }
+    };
+    let bounded = |key: &str, default: u64, min: u64, max: u64| -> Result<u64, String> {
+        match arguments.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or(format!("{} must be an integer from {} to {}", key, min, max)),
+        }
+    };
+    let budget_ms = (command_timeout.as_millis() as u64).saturating_sub(LOAD_WAIT_HEADROOM_MS);
+    if budget_ms == 0 {
+        return Err("The command timeout is too short to collect items".to_string());
+    }
+
+    Ok(serde_json::json!({
+        "itemSelector": item_selector,
+        "fields": fields,
+        "container": selector("container")?,
+        "loadMoreSelector": selector("loadMoreSelector")?,
+        "maxItems": bounded("maxItems", 100, 1, 2000)?,
+        "maxScrolls": bounded("maxScrolls", 50, 1, 500)?,
+        "timeoutMs": bounded("timeoutMs", DEFAULT_COLLECT_TIMEOUT_MS.min(budget_ms), 1, budget_ms)?,
+        "waitMs": bounded("waitMs", 1500, 100, 10_000)?,
+        "idleScrolls": bounded("idleScrolls", 3, 1, 10)?,
+        "maxTextLength": bounded("maxTextLength", 300, 1, 5000)?,
+    }))
+}
+
+/// Validate browser_find_text arguments and fill in defaults
+fn prepare_find_text(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let flag = |key: &str, default: bool| arguments.get(key).and_then(|v| v.as_bool()).unwrap_or(default);
+    if flag("clear", false) {
+        return Ok(serde_json::json!({ "clear": true }));
+    }
+
+    let query = arguments
+        .get("query")
+        .and_then(|v| v.as_str())
+        .filter(|q| !q.is_empty())
+        .ok_or("Missing query")?;
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(selector) => Some(
+            selector
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let bounded = |key: &str, default: u64, min: u64, max: u64| -> Result<u64, String> {
+        match arguments.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or(format!("{} must be an integer from {} to {}", key, min, max)),
+        }
+    };
+    let scroll_to = match arguments.get("scrollTo").filter(|v| !v.is_null()) {
+        Some(v) => Some(v.as_u64().ok_or("scrollTo must be a non-negative integer")?),
+        None => None,
+    };
+
+    Ok(serde_json::json!({
+        "query": query,
+        "regex": flag("regex", false),
+        "caseSensitive": flag("caseSensitive", false),
+        "wholeWord": flag("wholeWord", false),
+        "selector": selector,
+        "contextChars": bounded("contextChars", DEFAULT_FIND_CONTEXT, 0, MAX_FIND_CONTEXT)?,
+        "maxResults": bounded("maxResults", DEFAULT_FIND_RESULTS, 1, MAX_FIND_RESULTS)?,
+        "scrollTo": scroll_to,
+        "highlight": flag("highlight", true),
+    }))
+}
+
+const DEFAULT_AX_NODES: u64 = 2_000;
+const MAX_AX_NODES: u64 = 10_000;
+const MAX_AX_DEPTH: u64 = 100;
+
+/// Validate browser_accessibility_tree arguments into accessibility_snapshot params
+fn prepare_accessibility_tree(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let limit = |key: &str, default: u64, max: u64| -> Result<u64, String> {
+        match arguments.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (1..=max).contains(n))
+                .ok_or(format!("{} must be an integer from 1 to {}", key, max)),
+        }
+    };
+    let mut params = serde_json::json!({
+        "interestingOnly": arguments.get("interestingOnly").and_then(|v| v.as_bool()).unwrap_or(true),
+        "maxDepth": limit("maxDepth", MAX_AX_DEPTH, MAX_AX_DEPTH)?,
+        "maxNodes": limit("maxNodes", DEFAULT_AX_NODES, MAX_AX_NODES)?,
+    });
+    if let Some(selector) = arguments.get("selector") {
+        let selector = selector
+            .as_str()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or("selector must be a non-empty string")?;
+        params["selector"] = serde_json::json!(selector);
+    }
+    Ok(params)
+}
+
+const EVALUATE_DISABLED_ERROR: &str =
+    "playwright_evaluate is disabled: call passkey_authorize first or start the server with --allow-evaluate";
+const MAX_EVALUATE_CHARS: usize = 100_000;
+
+/// Validate playwright_evaluate arguments; the extension receives them with defaults filled in
+fn prepare_evaluate(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let expression = arguments
+        .get("expression")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .ok_or("Missing expression")?;
+    if expression.chars().count() > MAX_EVALUATE_CHARS {
+        return Err(format!("expression exceeds {} characters", MAX_EVALUATE_CHARS));
+    }
+    let args = match arguments.get("args") {
+        None | Some(serde_json::Value::Null) => serde_json::json!([]),
+        Some(args) if args.is_array() => args.clone(),
+        Some(_) => return Err("args must be an array".to_string()),
+    };
+    let frame_id = match arguments.get("frameId") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => Some(v.as_u64().ok_or("frameId must be a non-negative integer")?),
+    };
+
+    Ok(serde_json::json!({
+        "expression": expression,
+        "args": args,
+        "frameId": frame_id,
+        "awaitPromise": arguments.get("awaitPromise").and_then(|v| v.as_bool()).unwrap_or(true),
+    }))
+}
+
+/// Parse the requested date up front so the extension gets unambiguous components
+/// for both value injection and calendar navigation
+fn prepare_set_date(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+    let selector = arguments
+        .get("selector")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing selector")?;
+    let raw = arguments
+        .get("date")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing date")?
+        .trim();
+    let strategy = arguments.get("strategy").and_then(|v| v.as_str()).unwrap_or("auto");
+
+    let strategies = match strategy {
+        "auto" => vec!["inject", "widget"],
+        "inject" => vec!["inject"],
+        "widget" => vec!["widget"],
+        other => return Err(format!("Invalid strategy '{}': expected auto, inject, or widget", other)),
+    };
+
+    let (date, time) = match NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M") {
+        Ok(dt) => (dt.date(), Some(dt.format("%H:%M").to_string())),
+        Err(_) => {
+            let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD or YYYY-MM-DDTHH:MM", raw))?;
+            (date, None)
+        }
+    };
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "date": date.format("%Y-%m-%d").to_string(),
+        "time": time,
+        "year": date.year(),
+        "month": date.month(),
+        "day": date.day(),
+        "monthName": date.format("%B").to_string(),
+        "strategies": strategies,
+    }))
+}
+
+/// Canonicalize a BCP 47 tag: "EN-us" -> "en-US", "zh-hant-tw" -> "zh-Hant-TW"
+fn canonical_locale(raw: &str) -> Result<String, String> {
+    let valid = regex::Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").expect("valid regex");
+    if !valid.is_match(raw) {
+        return Err(format!("Invalid locale '{}': expected a BCP 47 tag like en-US", raw));
+    }
+    let parts: Vec<String> = raw
+        .split('-')
+        .enumerate()
+        .map(|(i, part)| match (i, part.len()) {
+            (0, _) => part.to_ascii_lowercase(),
+            (_, 2) => part.to_ascii_uppercase(),
+            (_, 4) if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let lower = part.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            _ => part.to_ascii_lowercase(),
+        })
+        .collect();
+    Ok(parts.join("-"))
+}
+
+/// Validate locale/timezone overrides and derive Accept-Language when not given
+fn prepare_emulate_locale(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if arguments.get("reset").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(serde_json::json!({ "reset": true }));
+    }
+
+    let locale = arguments
+        .get("locale")
+        .and_then(|v| v.as_str())
+        .map(canonical_locale)
+        .transpose()?;
+    let timezone = arguments.get("timezone").and_then(|v| v.as_str());
+    if let Some(timezone) = timezone {
+        let valid = regex::Regex::new(r"^(UTC|GMT|[A-Za-z_]+(/[A-Za-z0-9_+\-]+)+)$").expect("valid regex");
+        if !valid.is_match(timezone) {
+            return Err(format!("Invalid timezone '{}': expected an IANA name like Europe/Berlin", timezone));
+        }
+    }
+    let accept_language = match (arguments.get("acceptLanguage").and_then(|v| v.as_str()), &locale) {
+        (Some(header), _) => Some(header.to_string()),
+        (None, Some(locale)) => Some(match locale.split_once('-') {
+            Some((language, _)) => format!("{},{};q=0.9", locale, language),
+            None => locale.clone(),
+        }),
+        (None, None) => None,
+    };
+    if locale.is_none() && timezone.is_none() && accept_language.is_none() {
+        return Err("Provide locale, timezone, or acceptLanguage (or reset: true)".to_string());
+    }
+
+    Ok(serde_json::json!({
+        "locale": locale,
+        "timezone": timezone,
+        "acceptLanguage": accept_language,
+    }))
+}
+
+const DEFAULT_GEOLOCATION_ACCURACY_M: f64 = 100.0;
+const MAX_GEOLOCATION_ACCURACY_M: f64 = 100_000.0;
+
+/// Validate browser_set_geolocation coordinates and normalize the origin to scheme://host[:port]
+fn prepare_set_geolocation(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if arguments.get("reset").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(serde_json::json!({ "reset": true }));
+    }
+
+    let coordinate = |key: &str, limit: f64| -> Result<f64, String> {
+        let value = arguments.get(key).and_then(|v| v.as_f64()).ok_or(format!("Missing {}", key))?;
+        if !(-limit..=limit).contains(&value) {
+            return Err(format!("{} must be between -{} and {}", key, limit, limit));
+        }
+        Ok(value)
+    };
+    let latitude = coordinate("latitude", 90.0)?;
+    let longitude = coordinate("longitude", 180.0)?;
+    let accuracy = match arguments.get("accuracy").filter(|v| !v.is_null()) {
+        None => DEFAULT_GEOLOCATION_ACCURACY_M,
+        Some(v) => v
+            .as_f64()
+            .filter(|m| (0.0..=MAX_GEOLOCATION_ACCURACY_M).contains(m))
+            .ok_or(format!("accuracy must be between 0 and {} meters", MAX_GEOLOCATION_ACCURACY_M))?,
+    };
+    let origin = match arguments.get("origin").and_then(|v| v.as_str()) {
+        Some(origin) => {
+            let target = preflight::Target::parse(origin)?;
+            if target.path != "/" {
+                return Err(format!("origin '{}' must not have a path", origin));
+            }
+            let scheme = if target.tls { "https" } else { "http" };
+            Some(format!("{}://{}", scheme, target.host_header()))
+        }
+        None => None,
+    };
+
+    Ok(serde_json::json!({
+        "latitude": latitude,
+        "longitude": longitude,
+        "accuracy": accuracy,
+        "origin": origin,
+        "grantPermission": arguments.get("grantPermission").and_then(|v| v.as_bool()).unwrap_or(true),
+    }))
+}
+
+fn prepare_dialog_policy(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let mode = arguments.get("mode").and_then(|v| v.as_str()).ok_or("Missing mode")?;
+    if !dialogs::DIALOG_MODES.contains(&mode) {
+        return Err(format!("mode must be one of: {}", dialogs::DIALOG_MODES.join(", ")));
+    }
+    let prompt_text = arguments.get("promptText").filter(|v| !v.is_null());
+    if prompt_text.is_some_and(|v| !v.is_string()) {
+        return Err("promptText must be a string".to_string());
+    }
+    if prompt_text.is_some() && mode != "accept" {
+        return Err("promptText only applies to mode accept".to_string());
+    }
+    Ok(serde_json::json!({ "mode": mode, "promptText": prompt_text }))
+}
+
+/// Network profiles as (name, latency ms, download kbps, upload kbps), after Chrome DevTools' presets
+const NETWORK_PROFILES: &[(&str, u64, u64, u64)] = &[
+    ("slow-3g", 2000, 400, 400),
+    ("3g", 563, 1440, 675),
+    ("4g", 165, 9000, 1500),
+];
+const MAX_CPU_THROTTLE_RATE: f64 = 20.0;
+
+/// Network conditions for the extension; throughput is in bytes per second, -1 for unthrottled
+fn network_conditions(offline: bool, latency_ms: u64, download_kbps: Option<u64>, upload_kbps: Option<u64>) -> serde_json::Value {
+    let bytes_per_sec = |kbps: Option<u64>| kbps.map_or(-1, |kbps| (kbps * 125) as i64);
+    serde_json::json!({
+        "offline": offline,
+        "latencyMs": latency_ms,
+        "downloadThroughput": bytes_per_sec(download_kbps),
+        "uploadThroughput": bytes_per_sec(upload_kbps),
+    })
+}
+
+/// Resolve a throttle profile plus overrides into network conditions and a CPU slowdown rate
+fn prepare_throttle(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if arguments.get("reset").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(serde_json::json!({ "network": network_conditions(false, 0, None, None), "cpuRate": 1 }));
+    }
+
+    let number = |key: &str| match arguments.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => v.as_u64().map(Some).ok_or(format!("{} must be a non-negative integer", key)),
+    };
+    let latency = number("latencyMs")?;
+    let download = number("downloadKbps")?;
+    let upload = number("uploadKbps")?;
+    let custom = latency.is_some() || download.is_some() || upload.is_some();
+
+    let network = match arguments.get("network").and_then(|v| v.as_str()) {
+        Some(profile @ ("offline" | "none")) if custom => {
+            return Err(format!("latencyMs, downloadKbps and uploadKbps don't apply to network '{}'", profile));
+        }
+        Some("offline") => Some(network_conditions(true, 0, Some(0), Some(0))),
+        Some("none") => Some(network_conditions(false, 0, None, None)),
+        Some(name) => {
+            let (_, default_latency, default_download, default_upload) = NETWORK_PROFILES
+                .iter()
+                .find(|(profile, ..)| *profile == name)
+                .ok_or_else(|| format!("Unknown network profile '{}': expected slow-3g, 3g, 4g, offline, or none", name))?;
+            Some(network_conditions(
+                false,
+                latency.unwrap_or(*default_latency),
+                Some(download.unwrap_or(*default_download)),
+                Some(upload.unwrap_or(*default_upload)),
+            ))
+        }
+        // Custom conditions without a profile leave unspecified directions unthrottled
+        None if custom => Some(network_conditions(false, latency.unwrap_or(0), download, upload)),
+        None => None,
+    };
+
+    let cpu_rate = match arguments.get("cpuRate") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => match v.as_f64() {
+            Some(rate) if (1.0..=MAX_CPU_THROTTLE_RATE).contains(&rate) => Some(rate),
+            _ => return Err(format!("cpuRate must be a number from 1 to {}", MAX_CPU_THROTTLE_RATE)),
+        },
+    };
+    if network.is_none() && cpu_rate.is_none() {
+        return Err("Provide network, latencyMs, downloadKbps, uploadKbps, or cpuRate (or reset: true)".to_string());
+    }
+
+    Ok(serde_json::json!({ "network": network, "cpuRate": cpu_rate }))
+}
+
+const MAX_CANVAS_REGION_PIXELS: u64 = 4096 * 4096;
+const MAX_CANVAS_GRID: u64 = 16;
+
+/// Validate the canvas region and sampling mode before asking the extension to read pixels
+fn prepare_canvas_read_pixels(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = arguments
+        .get("selector")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing selector")?;
+    let mode = arguments.get("mode").and_then(|v| v.as_str()).unwrap_or("image");
+    if mode != "image" && mode != "average" {
+        return Err(format!("Invalid mode '{}': expected image or average", mode));
+    }
+
+    let x = arguments.get("x").and_then(|v| v.as_u64()).unwrap_or(0);
+    let y = arguments.get("y").and_then(|v| v.as_u64()).unwrap_or(0);
+    let width = arguments.get("width").and_then(|v| v.as_u64());
+    let height = arguments.get("height").and_then(|v| v.as_u64());
+    if width == Some(0) || height == Some(0) {
+        return Err("width and height must be positive".to_string());
+    }
+    if let (Some(w), Some(h)) = (width, height) {
+        if w * h > MAX_CANVAS_REGION_PIXELS {
+            return Err(format!(
+                "Region of {}x{} exceeds the {} pixel limit",
+                w, h, MAX_CANVAS_REGION_PIXELS
+            ));
+        }
+    }
+
+    let grid = arguments.get("grid").and_then(|v| v.as_u64()).unwrap_or(1);
+    if !(1..=MAX_CANVAS_GRID).contains(&grid) {
+        return Err(format!("grid must be between 1 and {}", MAX_CANVAS_GRID));
+    }
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "x": x,
+        "y": y,
+        "width": width,
+        "height": height,
+        "mode": mode,
+        "grid": grid,
+        "maxPixels": MAX_CANVAS_REGION_PIXELS,
+    }))
+}
+
+/// Check media actions and seek targets before they reach the page
+fn validate_media_arguments(name: &str, arguments: &serde_json::Value) -> Result<(), String> {
+    if name == "media_control" {
+        let action = arguments
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing action")?;
+        if !matches!(action, "play" | "pause" | "seek" | "mute" | "unmute") {
+            return Err(format!(
+                "Invalid action '{}': expected play, pause, seek, mute, or unmute",
+                action
+            ));
+        }
+        if action == "seek" && arguments.get("time").is_none() {
+            return Err("seek requires time".to_string());
+        }
+    }
+
+    if let Some(time) = arguments.get("time") {
+        match time.as_f64() {
+            Some(t) if t.is_finite() && t >= 0.0 => {}
+            _ => return Err("time must be a non-negative number of seconds".to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+const SITE_DATA_TYPES: &[&str] = &[
+    "cache",
+    "cookies",
+    "localStorage",
+    "indexedDB",
+    "cacheStorage",
+    "serviceWorkers",
+    "fileSystems",
+];
+
+/// Reject unknown data types and non-http(s) origins before they reach the extension
+fn validate_clear_site_data(arguments: &serde_json::Value) -> Result<(), String> {
+    if let Some(origin) = arguments.get("origin").and_then(|v| v.as_str()) {
+        if !(origin.starts_with("http://") || origin.starts_with("https://")) {
+            return Err(format!("Origin must start with http:// or https://: {}", origin));
+        }
+    }
+
+    if let Some(types) = arguments.get("dataTypes") {
+        let types = types.as_array().ok_or("dataTypes must be an array")?;
+        for t in types {
+            let t = t.as_str().ok_or("dataTypes entries must be strings")?;
+            if !SITE_DATA_TYPES.contains(&t) {
+                return Err(format!(
+                    "Unknown data type '{}' (expected one of: {})",
+                    t,
+                    SITE_DATA_TYPES.join(", ")
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Assertion types assert_page checks, and the matchers each compares against
+const ASSERTION_TYPES: &[&str] = &["exists", "visible", "text", "url", "cookie"];
+const ASSERTION_MATCHERS: &[&str] = &["equals", "contains", "matches"];
+const MAX_ASSERTIONS: usize = 50;
+
+/// Validate assert_page assertions, normalizing each and adding the
+/// description its report line uses
+fn prepare_assert_page(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let assertions = arguments
+        .get("assertions")
+        .and_then(|v| v.as_array())
+        .filter(|a| !a.is_empty())
+        .ok_or("assertions must be a non-empty array")?;
+    if assertions.len() > MAX_ASSERTIONS {
+        return Err(format!("At most {} assertions per call", MAX_ASSERTIONS));
+    }
+
+    let mut prepared = Vec::with_capacity(assertions.len());
+    for (index, assertion) in assertions.iter().enumerate() {
+        let field = |key: &str| -> Result<Option<&str>, String> {
+            match assertion.get(key) {
+                None => Ok(None),
+                Some(v) => v
+                    .as_str()
+                    .filter(|s| !s.is_empty())
+                    .map(Some)
+                    .ok_or(format!("assertions[{}].{} must be a non-empty string", index, key)),
+            }
+        };
+        let kind = field("type")?
+            .filter(|kind| ASSERTION_TYPES.contains(kind))
+            .ok_or(format!("assertions[{}].type must be one of: {}", index, ASSERTION_TYPES.join(", ")))?;
+        let mut params = serde_json::json!({ "type": kind });
+
+        let selector = field("selector")?;
+        match (kind, selector) {
+            ("exists" | "visible", None) => return Err(format!("assertions[{}] ({}) requires selector", index, kind)),
+            ("url" | "cookie", Some(_)) => return Err(format!("assertions[{}] ({}) does not take selector", index, kind)),
+            (_, Some(selector)) => params["selector"] = serde_json::json!(selector),
+            _ => {}
+        }
+        let cookie_name = field("name")?;
+        match (kind, cookie_name) {
+            ("cookie", None) => return Err(format!("assertions[{}] (cookie) requires name", index)),
+            ("cookie", Some(name)) => params["name"] = serde_json::json!(name),
+            (_, Some(_)) => return Err(format!("assertions[{}].name only applies to cookie assertions", index)),
+            _ => {}
+        }
+
+        // equals may be empty, e.g. to assert a field was cleared
+        let mut matchers = ASSERTION_MATCHERS.iter().filter_map(|m| assertion.get(*m).map(|v| (*m, v)));
+        let matcher = match (matchers.next(), matchers.next()) {
+            (Some(_), Some(_)) => {
+                return Err(format!("assertions[{}] takes one of {}, not several", index, ASSERTION_MATCHERS.join(", ")));
+            }
+            (Some((matcher, value)), None) => {
+                let value = value
+                    .as_str()
+                    .filter(|v| matcher == "equals" || !v.is_empty())
+                    .ok_or(format!("assertions[{}].{} must be a non-empty string", index, matcher))?;
+                if matches!(kind, "exists" | "visible") {
+                    return Err(format!("assertions[{}] ({}) does not take {}", index, kind, matcher));
+                }
+                params[matcher] = serde_json::json!(value);
+                Some((matcher, value))
+            }
+            (None, _) if matches!(kind, "text" | "url") => {
+                return Err(format!("assertions[{}] ({}) requires one of: {}", index, kind, ASSERTION_MATCHERS.join(", ")));
+            }
+            (None, _) => None,
+        };
+
+        let mut flags = serde_json::Map::new();
+        for flag in ["ignoreCase", "not"] {
+            if let Some(v) = assertion.get(flag) {
+                let v = v.as_bool().ok_or(format!("assertions[{}].{} must be a boolean", index, flag))?;
+                flags.insert(flag.to_string(), serde_json::json!(v));
+            }
+        }
+        if flags.contains_key("ignoreCase") && matcher.is_none() {
+            return Err(format!("assertions[{}].ignoreCase needs equals, contains or matches", index));
+        }
+        let negated = flags.get("not") == Some(&serde_json::json!(true));
+        for (flag, value) in flags {
+            params[flag] = value;
+        }
+
+        let subject = match kind {
+            "exists" | "visible" => format!("{} is {}", selector.unwrap_or_default(), kind_state(kind, negated)),
+            "text" => format!("text of {}", selector.unwrap_or("the page")),
+            "url" => "URL".to_string(),
+            _ => format!("cookie {}", cookie_name.unwrap_or_default()),
+        };
+        let description = match matcher {
+            Some((matcher, value)) => {
+                let verb = match (matcher, negated) {
+                    (_, false) => matcher,
+                    ("equals", true) => "does not equal",
+                    ("contains", true) => "does not contain",
+                    _ => "does not match",
+                };
+                format!("{} {} {:?}", subject, verb, value)
+            }
+            None if kind == "cookie" => format!("{} is {}", subject, if negated { "absent" } else { "present" }),
+            None => subject,
+        };
+        params["description"] = serde_json::json!(match field("label")? {
+            Some(label) => format!("{}: {}", label, description),
+            None => description,
+        });
+        prepared.push(params);
+    }
+    Ok(serde_json::json!({ "assertions": prepared }))
+}
+
+/// "exists"/"visible" as a predicate, e.g. "missing" when negated
+fn kind_state(kind: &str, negated: bool) -> &'static str {
+    match (kind, negated) {
+        ("exists", false) => "present",
+        ("exists", true) => "missing",
+        (_, false) => "visible",
+        (_, true) => "hidden",
+    }
+}
+
+/// Cookie SameSite values accepted by browser_set_cookie, as chrome.cookies names them
+const COOKIE_SAME_SITE: &[(&str, &str)] = &[("strict", "strict"), ("lax", "lax"), ("none", "no_restriction")];
+
+/// Validate the domain/url scope shared by the cookie tools; with neither, the
+/// extension uses the current tab's URL
+fn prepare_cookie_scope(arguments: &serde_json::Value) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut params = serde_json::Map::new();
+    let domain = arguments.get("domain").and_then(|v| v.as_str());
+    let url = arguments.get("url").and_then(|v| v.as_str());
+    match (domain, url) {
+        (Some(_), Some(_)) => return Err("Pass domain or url, not both".to_string()),
+        (Some(domain), None) => {
+            let domain = domain.trim().to_ascii_lowercase();
+            if domain.trim_start_matches('.').is_empty() || domain.contains(['/', ':', ' ']) {
+                return Err(format!("domain must be a host name like example.com: {}", domain));
+            }
+            params.insert("domain".to_string(), serde_json::json!(domain));
+        }
+        (None, Some(url)) => {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(format!("url must start with http:// or https://: {}", url));
+            }
+            params.insert("url".to_string(), serde_json::json!(url));
+        }
+        (None, None) => {}
+    }
+    if let Some(name) = arguments.get("name") {
+        let name = name.as_str().filter(|n| !n.is_empty()).ok_or("name must be a non-empty string")?;
+        params.insert("name".to_string(), serde_json::json!(name));
+    }
+    Ok(params)
+}
+
+/// Validate browser_get_cookies / browser_clear_cookies arguments
+fn prepare_cookie_query(name: &str, arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut params = prepare_cookie_scope(arguments)?;
+    if name == "browser_get_cookies" {
+        let include_values = arguments.get("includeValues").and_then(|v| v.as_bool()).unwrap_or(false);
+        params.insert("includeValues".to_string(), serde_json::json!(include_values));
+    }
+    Ok(serde_json::Value::Object(params))
+}
+
+/// Validate browser_set_cookie arguments and convert them to chrome.cookies.set details
+fn prepare_set_cookie(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut params = prepare_cookie_scope(arguments)?;
+    let name = params.get("name").and_then(|v| v.as_str()).ok_or("Missing name")?;
+    if name.contains([';', '=', ' ']) {
+        return Err(format!("Invalid cookie name: {}", name));
+    }
+    let value = arguments.get("value").and_then(|v| v.as_str()).ok_or("Missing value")?;
+    if value.contains([';', '\r', '\n']) {
+        return Err("Cookie value may not contain ';' or line breaks".to_string());
+    }
+    params.insert("value".to_string(), serde_json::json!(value));
+
+    let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("/");
+    if !path.starts_with('/') {
+        return Err(format!("path must start with '/': {}", path));
+    }
+    params.insert("path".to_string(), serde_json::json!(path));
+
+    for flag in ["secure", "httpOnly"] {
+        if let Some(v) = arguments.get(flag) {
+            params.insert(flag.to_string(), serde_json::json!(v.as_bool().ok_or(format!("{} must be a boolean", flag))?));
+        }
+    }
+    if let Some(same_site) = arguments.get("sameSite") {
+        let same_site = same_site.as_str().map(str::to_ascii_lowercase).unwrap_or_default();
+        let Some((_, chrome_name)) = COOKIE_SAME_SITE.iter().find(|(name, _)| *name == same_site) else {
+            return Err("sameSite must be one of: strict, lax, none".to_string());
+        };
+        // Browsers drop SameSite=None cookies that aren't Secure
+        if *chrome_name == "no_restriction" {
+            if params.get("secure") == Some(&serde_json::json!(false)) {
+                return Err("sameSite none requires secure".to_string());
+            }
+            params.insert("secure".to_string(), serde_json::json!(true));
+        }
+        params.insert("sameSite".to_string(), serde_json::json!(chrome_name));
+    }
+    match (arguments.get("expires"), arguments.get("maxAgeSeconds")) {
+        (Some(_), Some(_)) => return Err("Pass expires or maxAgeSeconds, not both".to_string()),
+        (Some(expires), None) => {
+            let expires = expires
+                .as_f64()
+                .filter(|t| *t > 0.0)
+                .ok_or("expires must be a Unix timestamp in seconds")?;
+            params.insert("expirationDate".to_string(), serde_json::json!(expires));
+        }
+        (None, Some(max_age)) => {
+            let max_age = max_age.as_u64().ok_or("maxAgeSeconds must be a non-negative integer")?;
+            let expires = chrono::Utc::now().timestamp() as u64 + max_age;
+            params.insert("expirationDate".to_string(), serde_json::json!(expires));
+        }
+        // Without an expiry the cookie lasts for the browser session
+        (None, None) => {}
+    }
+    Ok(serde_json::Value::Object(params))
+}
+
+/// Validate browser_list_downloads filters
+fn prepare_list_downloads(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut params = serde_json::Map::new();
+    if let Some(state) = arguments.get("state") {
+        let state = state.as_str().filter(|s| downloads::DOWNLOAD_STATES.contains(s)).ok_or(format!(
+            "state must be one of: {}",
+            downloads::DOWNLOAD_STATES.join(", ")
+        ))?;
+        params.insert("state".to_string(), serde_json::json!(state));
+    }
+    let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(20);
+    if !(1..=100).contains(&limit) {
+        return Err("limit must be between 1 and 100".to_string());
+    }
+    params.insert("limit".to_string(), serde_json::json!(limit));
+    for key in ["urlContains", "filenameContains"] {
+        if let Some(v) = arguments.get(key) {
+            params.insert(key.to_string(), serde_json::json!(v.as_str().ok_or(format!("{} must be a string", key))?));
+        }
+    }
+    Ok(serde_json::Value::Object(params))
+}
+
+/// Poll the extension until the newest matching download finishes, then
+/// optionally move the file into the session's artifacts or roots
+async fn handle_wait_for_download(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let query = match downloads::WaitQuery::from_arguments(arguments) {
+        Ok(query) => query,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let save_artifact = arguments.get("saveArtifact").and_then(|v| v.as_bool()).unwrap_or(false);
+    let save_path = match arguments.get("savePath").and_then(|v| v.as_str()) {
+        Some(_) if save_artifact => {
+            return JsonRpcRes::err(id, -32602, "Pass savePath or saveArtifact, not both", None);
+        }
+        Some(path) => match state.roots.resolve(session, path).await {
+            Ok(resolved) => Some(resolved),
+            Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+        },
+        None => None,
+    };
+
+    *state.active_session.write().await = Some(session.to_string());
+    let deadline = tokio::time::Instant::now() + query.timeout;
+    let mut last_seen = None;
+    let download = loop {
+        let result = match state.send_to_extension("downloads_list", query.list_params()).await {
+            Ok(result) => result,
+            Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+        };
+        match downloads::newest(&result) {
+            Ok(Some(download)) if download.state == "complete" => break download,
+            Ok(Some(download)) if download.state == "interrupted" => {
+                let reason = download.error.as_deref().unwrap_or("unknown error");
+                return JsonRpcRes::err(id, -32000, format!("Download {} failed: {}", download.id, reason), None);
+            }
+            Ok(seen) => last_seen = seen.or(last_seen),
+            Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+        }
+        if tokio::time::Instant::now() + downloads::POLL_INTERVAL > deadline {
+            let message = match last_seen {
+                Some(download) => format!(
+                    "Download {} ({}) still in progress after {}ms: {} of {} bytes",
+                    download.id,
+                    download.filename,
+                    query.timeout.as_millis(),
+                    download.bytes_received,
+                    download.total_bytes
+                ),
+                None => format!("No matching download started within {}ms", query.timeout.as_millis()),
+            };
+            return JsonRpcRes::err(id, -32000, message, None);
+        }
+        tokio::time::sleep(downloads::POLL_INTERVAL).await;
+    };
+
+    let destination = match save_path {
+        Some(path) => Some(path),
+        None if save_artifact => match downloads::artifact_path(session, &download) {
+            Ok(path) => Some(path),
+            Err(e) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+        },
+        None => None,
+    };
+    let path = match destination {
+        Some(destination) => {
+            if let Err(e) = downloads::move_file(std::path::Path::new(&download.filename), &destination) {
+                return JsonRpcRes::err(id, -32000, e.to_string(), None);
+            }
+            destination.display().to_string()
+        }
+        None => download.filename.clone(),
+    };
+    JsonRpcRes::ok(id, serde_json::json!({ "download": download, "path": path }))
+}
+
+/// What browser_profiling_start records: coverage kinds, and trace categories
+fn prepare_profiling_start(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let coverage: Vec<&str> = match arguments.get("coverage") {
+        None => profiling::COVERAGE_KINDS.to_vec(),
+        Some(kinds) => kinds
+            .as_array()
+            .ok_or("coverage must be an array")?
+            .iter()
+            .map(|kind| {
+                kind.as_str()
+                    .filter(|kind| profiling::COVERAGE_KINDS.contains(kind))
+                    .ok_or(format!("coverage entries must be one of: {}", profiling::COVERAGE_KINDS.join(", ")))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let trace = match arguments.get("trace") {
+        None => true,
+        Some(v) => v.as_bool().ok_or("trace must be a boolean")?,
+    };
+    if coverage.is_empty() && !trace {
+        return Err("Nothing to record: pass coverage kinds or trace: true".to_string());
+    }
+    let mut params = serde_json::json!({
+        "js": coverage.contains(&"js"),
+        "css": coverage.contains(&"css"),
+        "trace": trace,
+    });
+    match arguments.get("categories") {
+        Some(_) if !trace => return Err("categories only apply when trace is true".to_string()),
+        Some(categories) => {
+            let categories: Vec<&str> = categories
+                .as_array()
+                .filter(|c| !c.is_empty())
+                .ok_or("categories must be a non-empty array")?
+                .iter()
+                .map(|c| c.as_str().filter(|c| !c.is_empty()).ok_or("categories must be non-empty strings"))
+                .collect::<Result<_, _>>()?;
+            params["categories"] = serde_json::json!(categories);
+        }
+        None if trace => params["categories"] = serde_json::json!(profiling::DEFAULT_TRACE_CATEGORIES),
+        None => {}
+    }
+    Ok(params)
+}
+
+/// Collect coverage and the trace from the extension and save them as artifacts
+async fn handle_profiling_stop(state: &ServerState, session: &str, id: Option<serde_json::Value>) -> JsonRpcRes {
+    let mut params = serde_json::json!({});
+    if let Some(tab_id) = state.targets.current_tab(session).await {
+        params["tabId"] = serde_json::json!(tab_id);
+    }
+    *state.active_session.write().await = Some(session.to_string());
+    let result = match state.send_to_extension("profiling_stop", params).await {
+        Ok(result) => result,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    match profiling::save(session, &result) {
+        Ok(saved) => JsonRpcRes::ok(id, saved),
+        Err(e) => JsonRpcRes::err(id, -32000, format!("Failed to save profile: {}", e), None),
+    }
+}
+
+/// Validate browser_screenshot_elements arguments; the capture budget is
+/// what the command timeout leaves after the headroom
+fn prepare_screenshot_elements(arguments: &serde_json::Value, command_timeout: Duration) -> Result<serde_json::Value, String> {
+    let selectors = arguments
+        .get("selectors")
+        .and_then(|v| v.as_array())
+        .filter(|list| (1..=element_screenshots::MAX_SELECTORS).contains(&list.len()))
+        .ok_or(format!("selectors must be an array of 1 to {} CSS selectors", element_screenshots::MAX_SELECTORS))?;
+    let selectors = selectors
+        .iter()
+        .map(|v| v.as_str().map(str::trim).filter(|s| !s.is_empty()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("selectors must be non-empty strings")?;
+    let bounded = |key: &str, default: u64, min: u64, max: u64| -> Result<u64, String> {
+        match arguments.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or(format!("{} must be an integer from {} to {}", key, min, max)),
+        }
+    };
+    let budget_ms = (command_timeout.as_millis() as u64).saturating_sub(element_screenshots::BUDGET_HEADROOM_MS);
+    if budget_ms == 0 {
+        return Err("The command timeout is too short to capture elements".to_string());
+    }
+
+    Ok(serde_json::json!({
+        "selectors": selectors,
+        "all": arguments.get("all").and_then(|v| v.as_bool()).unwrap_or(false),
+        "maxElements": bounded("maxElements", element_screenshots::DEFAULT_MAX_ELEMENTS, 1, element_screenshots::MAX_ELEMENTS)?,
+        "padding": bounded("padding", 0, 0, element_screenshots::MAX_PADDING)?,
+        "budgetMs": budget_ms,
+    }))
+}
+
+/// Capture several elements in one extension command, optionally saving them as artifacts
+async fn handle_screenshot_elements(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let mut params = match prepare_screenshot_elements(arguments, state.request_timeout) {
+        Ok(params) => params,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    if let Some(tab_id) = state.targets.current_tab(session).await {
+        params["tabId"] = serde_json::json!(tab_id);
+    }
+    *state.active_session.write().await = Some(session.to_string());
+    let mut result = match state.send_to_extension("screenshot_elements", params).await {
+        Ok(result) => result,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    if arguments.get("save").and_then(|v| v.as_bool()).unwrap_or(false) {
+        match element_screenshots::save(session, &mut result) {
+            Ok(dir) => result["directory"] = serde_json::json!(dir),
+            Err(e) => return JsonRpcRes::err(id, -32000, format!("Failed to save element screenshots: {}", e), None),
+        }
+        if let Err(e) = quota::enforce(quota::Category::Artifacts) {
+            warn!("Failed to enforce artifacts quota: {}", e);
+        }
+    }
+    JsonRpcRes::ok(id, result)
+}
+
+/// Validate browser_extract_table arguments into extract_table params
+fn prepare_extract_table(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let selector = match arguments.get("selector").filter(|v| !v.is_null()) {
+        Some(v) => Some(
+            v.as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("selector must be a non-empty string")?,
+        ),
+        None => None,
+    };
+    let optional = |key: &str, max: u64| -> Result<Option<u64>, String> {
+        match arguments.get(key).filter(|v| !v.is_null()) {
+            None => Ok(None),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| *n <= max)
+                .map(Some)
+                .ok_or(format!("{} must be an integer from 0 to {}", key, max)),
+        }
+    };
+    let index = optional("index", u32::MAX as u64)?;
+    if selector.is_some() && index.is_some() {
+        return Err("Pass selector or index, not both".to_string());
+    }
+    let max_rows = match arguments.get("maxRows") {
+        None => tables::DEFAULT_MAX_ROWS,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=tables::MAX_ROWS).contains(n))
+            .ok_or(format!("maxRows must be an integer from 1 to {}", tables::MAX_ROWS))?,
+    };
+    let format = arguments.get("format").and_then(|v| v.as_str()).unwrap_or("objects");
+    if !matches!(format, "objects" | "arrays") {
+        return Err("format must be objects or arrays".to_string());
+    }
+
+    Ok(serde_json::json!({
+        "selector": selector,
+        "index": index,
+        "headerRows": optional("headerRows", tables::MAX_HEADER_ROWS)?,
+        "maxRows": max_rows,
+    }))
+}
+
+/// Extract a table as row objects (or arrays), optionally saving it as CSV
+async fn handle_extract_table(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let mut params = match prepare_extract_table(arguments) {
+        Ok(params) => params,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    if let Some(tab_id) = state.targets.current_tab(session).await {
+        params["tabId"] = serde_json::json!(tab_id);
+    }
+    *state.active_session.write().await = Some(session.to_string());
+    let mut result = match state.send_to_extension("extract_table", params).await {
+        Ok(result) => result,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let headers: Vec<String> = serde_json::from_value(result["headers"].take()).unwrap_or_default();
+    let rows: Vec<Vec<String>> = serde_json::from_value(result["rows"].take()).unwrap_or_default();
+
+    if arguments.get("saveCsv").and_then(|v| v.as_bool()).unwrap_or(false) {
+        match tables::save_csv(session, &headers, &rows) {
+            Ok(path) => result["csvPath"] = serde_json::json!(path.display().to_string()),
+            Err(e) => return JsonRpcRes::err(id, -32000, format!("Failed to save CSV: {}", e), None),
+        }
+        if let Err(e) = quota::enforce(quota::Category::Artifacts) {
+            warn!("Failed to enforce artifacts quota: {}", e);
+        }
+    }
+    result["rows"] = match arguments.get("format").and_then(|v| v.as_str()) {
+        Some("arrays") => serde_json::json!(rows),
+        _ => serde_json::json!(tables::row_objects(&headers, &rows)),
+    };
+    result["headers"] = serde_json::json!(tables::unique_headers(&headers));
+    JsonRpcRes::ok(id, result)
+}
+
+/// Validate browser_recording_start arguments into screencast_start params
+fn prepare_recording_start(arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let format = arguments.get("format").and_then(|v| v.as_str()).unwrap_or("webm");
+    if !recordings::FORMATS.contains(&format) {
+        return Err(format!("format must be one of: {}", recordings::FORMATS.join(", ")));
+    }
+    let bounded = |key: &str, default: u64, min: u64, max: u64| -> Result<u64, String> {
+        match arguments.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or(format!("{} must be an integer from {} to {}", key, min, max)),
+        }
+    };
+    let max_width = bounded("maxWidth", recordings::DEFAULT_MAX_WIDTH, recordings::MIN_MAX_WIDTH, recordings::MAX_MAX_WIDTH)?;
+
+    Ok(serde_json::json!({
+        "format": format,
+        "maxWidth": max_width,
+        "maxHeight": max_width,
+        "quality": bounded("quality", recordings::DEFAULT_QUALITY, 1, 100)?,
+    }))
+}
+
+/// Start a screencast of the session's tab; frames arrive as events until browser_recording_stop
+async fn handle_recording_start(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let mut params = match prepare_recording_start(arguments) {
+        Ok(params) => params,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    *state.active_session.write().await = Some(session.to_string());
+    // The recording is registered before the screencast starts so its first frame isn't dropped
+    let tab_id = match state.targets.current_tab(session).await {
+        Some(tab_id) => tab_id,
+        None => match state.send_to_extension("active_tab", serde_json::json!({})).await {
+            Ok(result) => match result.get("tabId").and_then(|v| v.as_i64()) {
+                Some(tab_id) => tab_id,
+                None => return JsonRpcRes::err(id, -32000, "No active tab", None),
+            },
+            Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+        },
+    };
+    let format = params["format"].as_str().unwrap_or("webm").to_string();
+    let (recording_id, dir) = match state.recordings.start(session, tab_id, &format).await {
+        Ok(started) => started,
+        Err(e) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+    };
+    params["tabId"] = serde_json::json!(tab_id);
+    match state.send_to_extension("screencast_start", params).await {
+        Ok(result) => JsonRpcRes::ok(
+            id,
+            serde_json::json!({
+                "recordingId": recording_id,
+                "tabId": tab_id,
+                "url": result.get("url"),
+                "format": format,
+                "directory": dir.display().to_string(),
+            }),
+        ),
+        Err(e) => {
+            state.recordings.discard(tab_id).await;
+            JsonRpcRes::err(id, -32000, e, None)
+        }
+    }
+}
+
+/// Stop the session's screencast and encode its frames into a video
+async fn handle_recording_stop(state: &ServerState, session: &str, id: Option<serde_json::Value>) -> JsonRpcRes {
+    let Some(recording) = state.recordings.take(session).await else {
+        return JsonRpcRes::err(id, -32000, "No recording in progress; start one with browser_recording_start", None);
+    };
+    *state.active_session.write().await = Some(session.to_string());
+    // A closed tab or a detached debugger already ended the screencast; its frames still count
+    let stop_error = state
+        .send_to_extension("screencast_stop", serde_json::json!({ "tabId": recording.tab_id }))
+        .await
+        .err();
+    let stopped_at = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+    let encoded = match tokio::task::spawn_blocking(move || recordings::encode(recording, stopped_at)).await {
+        Ok(Ok(encoded)) => encoded,
+        Ok(Err(e)) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+        Err(e) => return JsonRpcRes::err(id, -32000, format!("Encoding failed: {}", e), None),
+    };
+    if let Err(e) = quota::enforce(quota::Category::Recordings) {
+        warn!("Failed to enforce recordings quota: {}", e);
+    }
+    let mut result = serde_json::json!(encoded);
+    if let Some(e) = stop_error {
+        result["stopError"] = serde_json::json!(e);
+    }
+    JsonRpcRes::ok(id, result)
+}
+
+/// List captured HTTP requests for the session's tab (the active tab when it
+/// isn't attached to one), or for every tab
+async fn handle_network_list_requests(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let status = match arguments.get("status").map(StatusFilter::parse).transpose() {
+        Ok(status) => status,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(50);
+    if !(1..=500).contains(&limit) {
+        return JsonRpcRes::err(id, -32602, "limit must be between 1 and 500", None);
+    }
+    let all_tabs = arguments.get("allTabs").and_then(|v| v.as_bool()).unwrap_or(false);
+    let tab_id = match arguments.get("tabId") {
+        Some(_) if all_tabs => return JsonRpcRes::err(id, -32602, "Pass tabId or allTabs, not both", None),
+        Some(tab_id) => match tab_id.as_i64() {
+            Some(tab_id) => Some(tab_id),
+            None => return JsonRpcRes::err(id, -32602, "tabId must be an integer", None),
+        },
+        None if all_tabs => None,
+        None => match state.targets.current_tab(session).await {
+            Some(tab_id) => Some(tab_id),
+            None => match state.send_to_extension("active_tab", serde_json::json!({})).await {
+                Ok(result) => match result.get("tabId").and_then(|v| v.as_i64()) {
+                    Some(tab_id) => Some(tab_id),
+                    None => return JsonRpcRes::err(id, -32000, "No active tab", None),
+                },
+                Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+            },
+        },
+    };
+
+    let filter = HttpFilter {
+        tab_id,
+        url_pattern: arguments.get("urlPattern").and_then(|v| v.as_str()),
+        status,
+        resource_type: arguments.get("type").and_then(|v| v.as_str()),
+        limit: Some(limit as usize),
+    };
+    let requests = state.network_capture.list_http(&filter).await;
+    JsonRpcRes::ok(
+        id,
+        serde_json::json!({ "tabId": tab_id, "requests": requests, "count": requests.len() }),
+    )
+}
+
+/// Update the network capture policy and tell the extension what to collect
+async fn handle_network_capture_configure(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let strings = |key: &str| -> Vec<String> {
+        arguments
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+
+    let mut policy: CapturePolicy = state.network_capture.policy().await;
+    if let Some(capture_bodies) = arguments.get("captureBodies").and_then(|v| v.as_bool()) {
+        policy.capture_bodies = capture_bodies;
+    }
+    if let Some(max) = arguments.get("maxBodyBytes").and_then(|v| v.as_u64()) {
+        policy.max_body_bytes = max as usize;
+    }
+    for header in strings("redactHeaders") {
+        if !policy.redaction.headers.contains(&header) {
+            policy.redaction.headers.push(header);
+        }
+    }
+    for pattern in strings("redactPatterns") {
+        if !policy.redaction.patterns.contains(&pattern) {
+            policy.redaction.patterns.push(pattern);
+        }
+    }
+
+    if let Err(e) = state.network_capture.set_policy(policy.clone()).await {
+        return JsonRpcRes::err(id, -32602, e.to_string(), None);
+    }
+
+    // The extension only needs to know whether (and how much) body data to send
+    let extension_updated = state
+        .send_to_extension(
+            "network_capture_config",
+            serde_json::json!({
+                "captureBodies": policy.capture_bodies,
+                "maxBodyBytes": policy.max_body_bytes
+            }),
+        )
+        .await
+        .is_ok();
+
+    JsonRpcRes::ok(
+        id,
+        serde_json::json!({ "policy": policy, "extension_updated": extension_updated }),
+    )
+}
+
+/// Handle the watch_add / watch_list / watch_remove tools
+async fn handle_watch_tool(
+    name: &str,
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let arg = |key: &str| arguments.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let result = match name {
+        "watch_add" => {
+            let Some(url) = arg("url") else {
+                return JsonRpcRes::err(id, -32602, "Missing url", None);
+            };
+            let rule = watch::ExtractionRule {
+                selector: arg("selector"),
+                attribute: arg("attribute"),
+                pattern: arg("pattern"),
+            };
+            let interval = arguments
+                .get("interval_seconds")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(watch::DEFAULT_INTERVAL_SECS);
+            match state.watches.add(&url, rule, interval, arg("webhook")).await {
+                Ok(watch) => Ok(serde_json::json!({ "watch": watch })),
+                Err(e) => return JsonRpcRes::err(id, -32602, e.to_string(), None),
+            }
+        }
+        "watch_list" => {
+            let watches = state.watches.list().await;
+            Ok(serde_json::json!({ "count": watches.len(), "watches": watches }))
+        }
+        _ => {
+            let Some(watch_id) = arg("id") else {
+                return JsonRpcRes::err(id, -32602, "Missing id", None);
+            };
+            state
+                .watches
+                .remove(&watch_id)
+                .await
+                .map(|removed| serde_json::json!({ "id": watch_id, "removed": removed }))
+        }
+    };
+    match result {
+        Ok(result) => JsonRpcRes::ok(id, result),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+/// Check every due watch; runs on the scheduler tick. Watches load in the
+/// companion browser when one is configured (several at once), otherwise in
+/// a background tab of the user's browser while the extension is connected.
+async fn run_due_watches(state: &ServerState) {
+    let concurrency = match &state.companion {
+        Some(pool) => pool.size(),
+        None if state.extension_tx.read().await.is_some() => 1,
+        None => return,
+    };
+    let due = state.watches.due(chrono::Utc::now().timestamp()).await;
+    futures_util::stream::iter(due)
+        .for_each_concurrent(concurrency, |due| check_watch(state, due))
+        .await;
+}
+
+async fn check_watch(state: &ServerState, due: watch::Watch) {
+    let extracted = match &state.companion {
+        Some(pool) => {
+            pool.extract(
+                &due.url,
+                due.rule.selector.as_deref(),
+                due.rule.attribute.as_deref(),
+                state.request_timeout,
+            )
+            .await
+        }
+        None => state.send_to_extension("watch_extract", due.rule.extract_params(&due.url)).await,
+    };
+    let outcome = match extracted {
+        Ok(result) => match result.get("value").and_then(|v| v.as_str()) {
+            Some(raw) => due.rule.apply(raw),
+            None => Err("Element not found".to_string()),
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &outcome {
+        debug!("Watch {} ({}) failed: {}", due.id, due.url, e);
+    }
+    let change = match state.watches.record(&due.id, outcome, chrono::Utc::now().timestamp()).await {
+        Ok(change) => change,
+        Err(e) => {
+            warn!("Failed to save watch {}: {}", due.id, e);
+            return;
+        }
+    };
+    let Some(change) = change else {
+        return;
+    };
+
+    info!("Watch {} changed ({})", due.id, due.url);
+    state.alerts.send(Alert::new(EventClass::TaskCompletion, "Watched page changed", due.url.clone()));
+    let payload = change.payload();
+    state.notify("notifications/watch_changed", payload.clone());
+    if let Some(webhook) = change.watch.webhook.clone() {
+        // Slow receivers don't hold up the remaining watches
+        tokio::spawn(async move {
+            match watch::post_webhook(&webhook, &payload).await {
+                Ok(status) if status < 400 => debug!("Watch webhook {} answered {}", webhook, status),
+                Ok(status) => warn!("Watch webhook {} answered {}", webhook, status),
+                Err(e) => warn!("Watch webhook {} failed: {}", webhook, e),
+            }
+        });
+    }
+}
+
+/// Answer a recovery secret call the session may not make, alerting the operator
+fn refuse_recovery_secret(
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+    rp_id: &str,
+    error: anyhow::Error,
+) -> JsonRpcRes {
+    state.alerts.send(
+        Alert::new(EventClass::PolicyViolation, "Recovery secret refused", format!("{}: {}", rp_id, error))
+            .for_session(session),
+    );
+    JsonRpcRes::err(id, -32001, error.to_string(), None)
+}
+
+/// Handle the state_set / state_get / state_delete tools
+async fn handle_state_tool(
+    name: &str,
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let workspace = arguments
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or(state_store::DEFAULT_WORKSPACE);
+    let key = arguments.get("key").and_then(|v| v.as_str());
+    let store = &state.state_store;
+
+    let result = match (name, key) {
+        ("state_set", Some(key)) => {
+            let value = arguments.get("value").cloned().unwrap_or(serde_json::Value::Null);
+            store.set(workspace, key, value).await.map(|previous| {
+                serde_json::json!({ "workspace": workspace, "key": key, "previous": previous })
+            })
+        }
+        ("state_get", Some(key)) => store.get(workspace, key).await.map(|entry| {
+            serde_json::json!({
+                "workspace": workspace,
+                "key": key,
+                "found": entry.is_some(),
+                "value": entry.as_ref().map(|e| &e.value),
+                "updated": entry.as_ref().map(|e| e.updated)
+            })
+        }),
+        ("state_get", None) => {
+            let prefix = arguments.get("prefix").and_then(|v| v.as_str());
+            store.list(workspace, prefix).await.map(|entries| {
+                serde_json::json!({ "workspace": workspace, "count": entries.len(), "entries": entries })
+            })
+        }
+        ("state_delete", Some(key)) => store.delete(workspace, key).await.map(|deleted| {
+            serde_json::json!({ "workspace": workspace, "key": key, "deleted": deleted })
+        }),
+        _ => return JsonRpcRes::err(id, -32602, "Missing key", None),
+    };
+
+    match result {
+        Ok(result) => JsonRpcRes::ok(id, result),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+/// Evaluate a transform expression over inline data or a stored value
+async fn handle_data_transform(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(expression) = arguments.get("expression").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing expression", None);
+    };
+    let workspace = arguments
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or(state_store::DEFAULT_WORKSPACE);
+    let key = arguments.get("key").and_then(|v| v.as_str());
+    let data = match (arguments.get("data"), key) {
+        (Some(_), Some(_)) => return JsonRpcRes::err(id, -32602, "Pass data or key, not both", None),
+        (Some(data), None) => data.clone(),
+        (None, Some(key)) => match state.state_store.get(workspace, key).await {
+            Ok(Some(entry)) => entry.value,
+            Ok(None) => {
+                return JsonRpcRes::err(id, -32602, format!("No key {} in workspace {}", key, workspace), None);
+            }
+            Err(e) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+        },
+        (None, None) => serde_json::Value::Null,
+    };
+
+    // CPU-bound, but bounded by the interpreter's step budget
+    let source = expression.to_string();
+    let result = match tokio::task::spawn_blocking(move || transform::evaluate(&source, &data)).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => return JsonRpcRes::err(id, -32602, e, None),
+        Err(e) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+    };
+
+    let mut response = serde_json::json!({ "result": result });
+    if let Some(save_as) = arguments.get("saveAs").and_then(|v| v.as_str()) {
+        if let Err(e) = state.state_store.set(workspace, save_as, response["result"].clone()).await {
+            return JsonRpcRes::err(id, -32000, e.to_string(), None);
+        }
+        response["saved"] = serde_json::json!({ "workspace": workspace, "key": save_as });
+    }
+    JsonRpcRes::ok(id, response)
+}
+
+/// Parse the `include` argument of the snapshot tools (default: every part)
+fn parse_snapshot_parts(arguments: &serde_json::Value) -> Vec<SnapshotPart> {
+    arguments
+        .get("include")
+        .cloned()
+        .and_then(|v| serde_json::from_value::<Vec<SnapshotPart>>(v).ok())
+        .unwrap_or_else(|| SnapshotPart::ALL.to_vec())
+}
+
+/// Capture the requested parts from the extension and persist them
+async fn handle_snapshot_save(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing name", None);
+    };
+    let include = parse_snapshot_parts(arguments);
+    let metadata = arguments.get("metadata").cloned().unwrap_or(serde_json::json!({}));
+    let overwrite = arguments.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    *state.active_session.write().await = Some(session.to_string());
+
+    let mut capture = SnapshotCapture::default();
+    let mut errors = serde_json::Map::new();
+
+    for part in &include {
+        let (method, params) = match part {
+            SnapshotPart::Dom => ("get_html", serde_json::json!({})),
+            SnapshotPart::Accessibility => ("accessibility_snapshot", serde_json::json!({})),
+            SnapshotPart::Screenshot => ("screenshot", serde_json::json!({})),
+        };
+
+        match state.send_to_extension(method, params).await {
+            Ok(result) => {
+                if capture.url.is_none() {
+                    capture.url = result.get("url").and_then(|v| v.as_str()).map(str::to_string);
+                }
+                if capture.title.is_none() {
+                    capture.title = result.get("title").and_then(|v| v.as_str()).map(str::to_string);
+                }
+                match part {
+                    SnapshotPart::Dom => {
+                        capture.dom = result.get("html").and_then(|v| v.as_str()).map(str::to_string);
+                    }
+                    SnapshotPart::Accessibility => {
+                        capture.accessibility = result.get("tree").cloned();
+                    }
+                    SnapshotPart::Screenshot => {
+                        capture.screenshot_b64 =
+                            result.get("data").and_then(|v| v.as_str()).map(str::to_string);
+                    }
+                }
+            }
+            Err(e) => {
+                errors.insert(method.to_string(), serde_json::Value::String(e));
+            }
+        }
+    }
+
+    if capture.dom.is_none() && capture.accessibility.is_none() && capture.screenshot_b64.is_none() {
+        return JsonRpcRes::err(
+            id,
+            -32000,
+            "Failed to capture any snapshot parts",
+            Some(serde_json::Value::Object(errors)),
+        );
+    }
+
+    match state.snapshot_store.save(name, capture, metadata, Some(session), overwrite) {
+        Ok(meta) => JsonRpcRes::ok(id, serde_json::json!({ "snapshot": meta, "errors": errors })),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+/// Screenshot the active tab, returning the PNG (base64), its viewport and URL
+async fn capture_for_baseline(state: &ServerState) -> Result<(String, Viewport, Option<String>), String> {
+    let result = state.send_to_extension("screenshot", serde_json::json!({})).await?;
+    let data = result
+        .get("data")
+        .and_then(|v| v.as_str())
+        .ok_or("Extension returned no screenshot data")?;
+    let viewport = Viewport::from_result(&result)
+        .ok_or("Extension did not report the viewport size; update the extension")?;
+    let url = result.get("url").and_then(|v| v.as_str()).map(str::to_string);
+    Ok((data.to_string(), viewport, url))
+}
+
+/// Capture the current page as the baseline for its viewport size
+async fn handle_baseline_set(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing name", None);
+    };
+    if let Err(e) = storage::validate_name("Baseline", name) {
+        return JsonRpcRes::err(id, -32602, e.to_string(), None);
+    }
+    let overwrite = arguments.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    *state.active_session.write().await = Some(session.to_string());
+    let (data, viewport, url) = match capture_for_baseline(state).await {
+        Ok(capture) => capture,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    match state.baselines.set(name, viewport, url, &data, Some(session), overwrite) {
+        Ok(meta) => JsonRpcRes::ok(id, serde_json::json!({ "baseline": meta })),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+fn handle_baseline_tool(
+    name: &str,
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let arg = |key: &str| arguments.get(key).and_then(|v| v.as_str());
+    let viewport = match arg("viewport") {
+        None => None,
+        Some(value) => match Viewport::parse(value) {
+            Some(viewport) => Some(viewport),
+            None => return JsonRpcRes::err(id, -32602, "viewport must look like 1280x720", None),
+        },
+    };
+    let result = if name == "baseline_list" {
+        state.baselines.list(arg("name"), arg("url")).map(|baselines| {
+            let baselines: Vec<_> = baselines
+                .into_iter()
+                .filter(|b| viewport.is_none_or(|v| b.viewport == v))
+                .collect();
+            serde_json::json!({ "count": baselines.len(), "baselines": baselines })
+        })
+    } else {
+        let Some(baseline) = arg("name") else {
+            return JsonRpcRes::err(id, -32602, "Missing name", None);
+        };
+        state.baselines.delete(baseline, viewport).map(|removed| {
+            let removed: Vec<String> = removed.iter().map(Viewport::label).collect();
+            serde_json::json!({ "deleted": !removed.is_empty(), "viewports": removed })
+        })
+    };
+    match result {
+        Ok(result) => JsonRpcRes::ok(id, result),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+/// Validate screenshot_diff arguments: (baseline name, tolerance, maxDiffRatio)
+fn prepare_screenshot_diff(arguments: &serde_json::Value) -> Result<(String, u64, f64), String> {
+    let name = arguments.get("baseline").and_then(|v| v.as_str()).ok_or("Missing baseline")?;
+    storage::validate_name("Baseline", name).map_err(|e| e.to_string())?;
+    let tolerance = match arguments.get("tolerance") {
+        None => baselines::DEFAULT_TOLERANCE,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| *n <= 255)
+            .ok_or("tolerance must be an integer from 0 to 255")?,
+    };
+    let max_diff_ratio = match arguments.get("maxDiffRatio") {
+        None => baselines::DEFAULT_MAX_DIFF_RATIO,
+        Some(v) => v
+            .as_f64()
+            .filter(|r| (0.0..=1.0).contains(r))
+            .ok_or("maxDiffRatio must be a number from 0 to 1")?,
+    };
+    Ok((name.to_string(), tolerance, max_diff_ratio))
+}
+
+/// Compare the current page with a stored baseline at the same viewport size;
+/// failures leave the capture and a diff image in the session's artifacts
+async fn handle_screenshot_diff(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+
+    let (name, tolerance, max_diff_ratio) = match prepare_screenshot_diff(arguments) {
+        Ok(prepared) => prepared,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+
+    *state.active_session.write().await = Some(session.to_string());
+    let (data, viewport, url) = match capture_for_baseline(state).await {
+        Ok(capture) => capture,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let (baseline, expected) = match state.baselines.get(&name, viewport) {
+        Ok(baseline) => baseline,
+        Err(e) => return JsonRpcRes::err(id, -32000, e.to_string(), None),
+    };
+    let actual = match BASE64.decode(&data) {
+        Ok(actual) => actual,
+        Err(e) => return JsonRpcRes::err(id, -32000, format!("Invalid screenshot data: {}", e), None),
+    };
+
+    let mut result = serde_json::json!({
+        "baseline": name,
+        "viewport": viewport.label(),
+        "url": url,
+        "baselineUrl": baseline.url,
+        "urlMatches": url == baseline.url,
+        "tolerance": tolerance,
+        "maxDiffRatio": max_diff_ratio,
+    });
+
+    if baselines::sha256_hex(&actual) == baseline.sha256 {
+        result["passed"] = serde_json::json!(true);
+        result["identical"] = serde_json::json!(true);
+        result["diffPixels"] = serde_json::json!(0);
+        result["diffRatio"] = serde_json::json!(0.0);
+        return JsonRpcRes::ok(id, result);
+    }
+
+    let params = serde_json::json!({
+        "expected": BASE64.encode(&expected),
+        "actual": data,
+        "tolerance": tolerance,
+    });
+    let diff = match state.send_to_extension("image_diff", params).await {
+        Ok(diff) => diff,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let size_mismatch = diff["sizeMismatch"].as_bool().unwrap_or(false);
+    let diff_ratio = diff["diffRatio"].as_f64().unwrap_or(1.0);
+    let passed = !size_mismatch && diff_ratio <= max_diff_ratio;
+
+    result["passed"] = serde_json::json!(passed);
+    result["identical"] = serde_json::json!(false);
+    result["diffPixels"] = diff["diffPixels"].clone();
+    result["diffRatio"] = serde_json::json!(diff_ratio);
+    if size_mismatch {
+        result["sizeMismatch"] = serde_json::json!({ "expected": diff["expectedSize"], "actual": diff["actualSize"] });
+    }
+    if !passed {
+        let diff_png = diff["diff"].as_str().and_then(|d| BASE64.decode(d).ok());
+        match baselines::write_diff(session, &name, viewport, &actual, diff_png.as_deref()) {
+            Ok(dir) => {
+                result["artifacts"] = serde_json::json!(dir.display().to_string());
+                if let Err(e) = quota::enforce(quota::Category::Artifacts) {
+                    warn!("Failed to enforce artifacts quota: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to write diff artifacts for baseline '{}': {}", name, e),
+        }
+    }
+    JsonRpcRes::ok(id, result)
+}
+
+const DEFAULT_MENU_SELECTOR: &str =
+    "[role='menu'], [role='menubar'], [role='listbox'], [aria-expanded='true'] + ul, [aria-expanded='true'] + div";
+const DEFAULT_MENU_ITEM_SELECTOR: &str =
+    "[role='menuitem'], [role='menuitemcheckbox'], [role='menuitemradio'], [role='option'], a, button";
+const DEFAULT_MENU_TIMEOUT_MS: u64 = 2000;
+
+/// Trim item text and drop empty or duplicate entries reported by the extension
+fn normalize_menu_items(raw: &serde_json::Value) -> Vec<serde_json::Value> {
+    let mut seen = std::collections::HashSet::new();
+    raw.as_array()
+        .map(|items| items.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|item| {
+            let text = item
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default();
+            let selector = item.get("selector").and_then(|v| v.as_str())?;
+            if text.is_empty() || !seen.insert(selector.to_string()) {
+                return None;
+            }
+            let mut entry = serde_json::json!({ "text": text, "selector": selector });
+            for key in ["role", "href", "disabled"] {
+                if let Some(value) = item.get(key).filter(|v| !v.is_null()) {
+                    entry[key] = value.clone();
+                }
+            }
+            Some(entry)
+        })
+        .collect()
+}
+
+/// Error data for a selector that matched nothing: ranked look-alike elements from the page
+async fn suggest_selectors(
+    state: &ServerState,
+    selector: &str,
+    tab_id: Option<serde_json::Value>,
+    session: &str,
+) -> serde_json::Value {
+    let hint = SelectorHint::parse(selector);
+    let mut params = hint.to_params(selector);
+    if let Some(tab_id) = tab_id {
+        params["tabId"] = tab_id;
+    }
+
+    let candidates = match timeout(
+        Duration::from_millis(suggestions::SUGGESTION_TIMEOUT_MS),
+        state.send_to_extension_with_progress("find_similar_elements", params, None, Some(session)),
+    )
+    .await
+    {
+        Ok(Ok(result)) => result
+            .get("candidates")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default(),
+        Ok(Err(e)) => {
+            debug!("No selector suggestions for {}: {}", selector, e);
+            Vec::new()
+        }
+        Err(_) => {
+            debug!("Selector suggestions for {} timed out", selector);
+            Vec::new()
+        }
+    };
+
+    serde_json::json!({
+        "selector": selector,
+        "suggestions": suggestions::rank(&hint, &candidates, suggestions::MAX_SUGGESTIONS),
+    })
+}
+
+/// Tools whose base64 `data` result can be written to `savePath`
+const FILE_OUTPUT_TOOLS: &[&str] = &["playwright_screenshot", "browser_export_pdf"];
+
+/// Decode a result's base64 `data`, with or without a data: URL prefix
+fn decode_result_data(result: &serde_json::Value) -> Result<Vec<u8>, String> {
+    use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+
+    let data = result
+        .get("data")
+        .and_then(|v| v.as_str())
+        .ok_or("Extension result has no data to save")?;
+    let data = data.split_once("base64,").map(|(_, d)| d).unwrap_or(data);
+    BASE64.decode(data).map_err(|e| format!("Invalid base64 data: {}", e))
+}
+
+/// Write a result's base64 `data` to disk, replacing it with `savedTo` and `bytes`
+async fn save_artifact(path: &std::path::Path, result: &mut serde_json::Value) -> Result<(), String> {
+    let bytes = decode_result_data(result)?;
+    tokio::fs::write(path, &bytes)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    if let Err(e) = quota::enforce(quota::Category::Artifacts) {
+        warn!("Failed to enforce artifacts quota: {}", e);
+    }
+
+    if let Some(obj) = result.as_object_mut() {
+        obj.remove("data");
+        obj.insert("savedTo".to_string(), serde_json::json!(path.display().to_string()));
+        obj.insert("bytes".to_string(), serde_json::json!(bytes.len()));
+    }
+    Ok(())
+}
+
+/// Screenshot the page after a submitting action and save it with the final
+/// URL and confirmation text to the session's receipts. Failures are reported
+/// in the receipt rather than failing the action that already happened.
+async fn capture_receipt(
+    state: &ServerState,
+    tool: &str,
+    selector: &str,
+    tab_id: Option<serde_json::Value>,
+    session: &str,
+) -> serde_json::Value {
+    tokio::time::sleep(Duration::from_millis(receipts::SETTLE_MS)).await;
+
+    let mut receipt = receipts::Receipt::new(tool, Some(selector));
+    let params = match tab_id {
+        Some(tab_id) => serde_json::json!({ "tabId": tab_id }),
+        None => serde_json::json!({}),
+    };
+    let screenshot = match state
+        .send_to_extension_with_progress("screenshot", params.clone(), None, Some(session))
+        .await
+        .and_then(|shot| decode_result_data(&shot))
+    {
+        Ok(png) => Some(png),
+        Err(e) => {
+            receipt.errors.push(format!("screenshot: {}", e));
+            None
+        }
+    };
+    match state.send_to_extension_with_progress("get_text", params, None, Some(session)).await {
+        Ok(page) => receipt.set_page(&page),
+        Err(e) => receipt.errors.push(format!("page: {}", e)),
+    }
+
+    let saved = receipts::receipt_dir(session, tool)
+        .and_then(|dir| receipts::write(&dir, &mut receipt, screenshot.as_deref()).map(|()| dir));
+    if let Err(e) = quota::enforce(quota::Category::Artifacts) {
+        warn!("Failed to enforce artifacts quota: {}", e);
+    }
+    let mut value = serde_json::to_value(&receipt).unwrap_or_default();
+    match saved {
+        Ok(dir) => value["directory"] = serde_json::json!(dir.display().to_string()),
+        Err(e) => value["saveError"] = serde_json::json!(e.to_string()),
+    }
+    value
+}
+
+/// How long a click waits for a popup it opened to be reported (follow mode only)
+const POPUP_SETTLE_MS: u64 = 300;
+
+/// Give consent banners time to render after a navigation commits
+const CONSENT_AUTO_DELAY_MS: u64 = 1500;
+
+/// Try the consent ruleset, then (optionally) the generic modal dismissal
+async fn run_consent_dismissal(
+    state: &ServerState,
+    mode: ConsentMode,
+    tab_id: Option<i64>,
+    fallback_to_modal: bool,
+) -> Result<serde_json::Value, String> {
+    let mut params = serde_json::json!({
+        "mode": mode,
+        "rules": consent::rules_for(mode),
+    });
+    if let Some(tab_id) = tab_id {
+        params["tabId"] = serde_json::json!(tab_id);
+    }
+
+    let result = state.send_to_extension("dismiss_consent", params).await?;
+    if result.get("dismissed").and_then(|v| v.as_bool()) == Some(true) || !fallback_to_modal {
+        return Ok(result);
+    }
+
+    let mut modal_params = serde_json::json!({ "strategy": "button" });
+    if let Some(tab_id) = tab_id {
+        modal_params["tabId"] = serde_json::json!(tab_id);
+    }
+    let modal = state.send_to_extension("dismiss_modal", modal_params).await?;
+    Ok(serde_json::json!({
+        "dismissed": modal.get("success").and_then(|v| v.as_bool()).unwrap_or(false),
+        "platform": serde_json::Value::Null,
+        "fallback": "modal",
+        "modal": modal,
+    }))
+}
+
+async fn handle_dismiss_consent(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let mode = match arguments.get("mode").cloned() {
+        None => ConsentMode::Reject,
+        Some(v) => match serde_json::from_value::<ConsentMode>(v) {
+            Ok(mode) => mode,
+            Err(_) => return JsonRpcRes::err(id, -32602, "Invalid mode: expected accept or reject", None),
+        },
+    };
+
+    if let Some(auto) = arguments.get("auto").and_then(|v| v.as_bool()) {
+        *state.consent_auto.write().await = auto.then_some(mode);
+        return JsonRpcRes::ok(
+            id,
+            serde_json::json!({ "auto": auto, "mode": if auto { Some(mode) } else { None } }),
+        );
+    }
+
+    let fallback = arguments.get("fallbackToModal").and_then(|v| v.as_bool()).unwrap_or(true);
+    *state.active_session.write().await = Some(session.to_string());
+    match run_consent_dismissal(state, mode, None, fallback).await {
+        Ok(result) => JsonRpcRes::ok(id, result),
+        Err(e) => JsonRpcRes::err(id, -32000, e, None),
+    }
+}
+
+const DEFAULT_AUDIT_NODES: u64 = 10;
+const MAX_AUDIT_NODES: u64 = 100;
+
+/// Validate audit_accessibility arguments into the extension command's params
+/// plus the number of example elements to keep per rule
+fn prepare_audit_accessibility(arguments: &serde_json::Value) -> Result<(serde_json::Value, usize), String> {
+    let rules: Vec<&str> = match arguments.get("rules") {
+        None => accessibility::RULES.iter().map(|rule| rule.id).collect(),
+        Some(serde_json::Value::Array(rules)) if !rules.is_empty() => rules
+            .iter()
+            .map(|rule| match rule.as_str() {
+                Some(id) if accessibility::rule(id).is_some() => Ok(id),
+                _ => Err(format!("Unknown rule {}; expected one of: {}", rule, accessibility::RULES.iter().map(|r| r.id).collect::<Vec<_>>().join(", "))),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("rules must be a non-empty list of rule ids".to_string()),
+    };
+    let selector = arguments.get("selector").and_then(|v| v.as_str());
+    let max_nodes = match arguments.get("maxNodes") {
+        None => DEFAULT_AUDIT_NODES,
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=MAX_AUDIT_NODES).contains(n))
+            .ok_or(format!("maxNodes must be an integer from 1 to {}", MAX_AUDIT_NODES))?,
+    };
+    Ok((serde_json::json!({ "selector": selector, "rules": rules }), max_nodes as usize))
+}
+
+/// Collect rule findings in the page through the extension, then score them here
+async fn handle_audit_accessibility(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let (params, max_nodes) = match prepare_audit_accessibility(arguments) {
+        Ok(prepared) => prepared,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    *state.active_session.write().await = Some(session.to_string());
+    let collected = match state.send_to_extension("audit_accessibility", params).await {
+        Ok(collected) => collected,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let findings = match serde_json::from_value::<Vec<accessibility::RuleFinding>>(collected["findings"].clone()) {
+        Ok(findings) => findings,
+        Err(e) => return JsonRpcRes::err(id, -32000, format!("Malformed audit findings from extension: {}", e), None),
+    };
+
+    let report = accessibility::aggregate(findings, max_nodes);
+    let mut result = serde_json::to_value(&report).unwrap_or_default();
+    result["url"] = collected["url"].clone();
+    result["title"] = collected["title"].clone();
+    JsonRpcRes::ok(id, result)
+}
+
+/// Extract the page's links through the extension and verify them from the server
+async fn handle_check_links(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let bounded = |key: &str, default: usize, max: usize| match arguments.get(key) {
+        None => Ok(default),
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=max as u64).contains(n))
+            .map(|n| n as usize)
+            .ok_or(format!("{} must be an integer from 1 to {}", key, max)),
+    };
+    let (max_links, concurrency) = match (
+        bounded("maxLinks", link_check::DEFAULT_MAX_LINKS, link_check::MAX_LINKS),
+        bounded("concurrency", link_check::DEFAULT_CONCURRENCY, link_check::MAX_CONCURRENCY),
+    ) {
+        (Ok(max_links), Ok(concurrency)) => (max_links, concurrency),
+        (Err(e), _) | (_, Err(e)) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let include_resources = arguments.get("includeResources").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    *state.active_session.write().await = Some(session.to_string());
+    let collected = match state
+        .send_to_extension("list_links", serde_json::json!({ "includeResources": include_resources }))
+        .await
+    {
+        Ok(collected) => collected,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let page_url = collected["url"].as_str().unwrap_or_default().to_string();
+    let Ok(page) = preflight::Target::parse(&page_url) else {
+        return JsonRpcRes::err(id, -32000, format!("Links can only be checked on http(s) pages, not {}", page_url), None);
+    };
+
+    let policy = link_check::Policy {
+        same_origin_only: arguments.get("sameOriginOnly").and_then(|v| v.as_bool()).unwrap_or(false),
+        // Intranet pages link to intranet hosts; checking those is the point
+        allow_private: arguments.get("allowPrivate").and_then(|v| v.as_bool()).unwrap_or(false)
+            || link_check::is_internal_host(&page).await,
+        timeout_ms: arguments
+            .get("timeoutMs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(link_check::DEFAULT_TIMEOUT_MS)
+            .min(preflight::MAX_TIMEOUT_MS),
+    };
+
+    // One check per distinct URL; the first link text found names it
+    let mut texts: HashMap<String, String> = HashMap::new();
+    let mut urls = Vec::new();
+    let mut skipped: HashMap<&str, usize> = HashMap::new();
+    for link in collected["links"].as_array().into_iter().flatten() {
+        let href = link["href"].as_str().unwrap_or_default();
+        let href = href.split('#').next().unwrap_or_default().to_string();
+        if texts.contains_key(&href) {
+            continue;
+        }
+        texts.insert(href.clone(), link["text"].as_str().unwrap_or_default().to_string());
+        match link_check::skip_reason(&href, &page, &policy) {
+            Some(reason) => *skipped.entry(reason).or_insert(0) += 1,
+            None => urls.push(href),
+        }
+    }
+    let found = urls.len();
+    urls.truncate(max_links);
+
+    let results = link_check::check_all(urls, policy, concurrency).await;
+    let count = |f: &dyn Fn(&link_check::LinkResult) -> bool| results.iter().filter(|r| f(r)).count();
+    let summary = serde_json::json!({
+        "checked": results.len(),
+        "ok": count(&|r| r.ok),
+        "broken": count(&|r| r.status.is_some_and(|s| s >= 400)),
+        "failed": count(&|r| r.error.is_some()),
+        "redirected": count(&|r| !r.redirects.is_empty()),
+    });
+    let results: Vec<serde_json::Value> = results
+        .into_iter()
+        .map(|result| {
+            let mut value = serde_json::to_value(&result).unwrap_or_default();
+            value["text"] = serde_json::json!(texts.get(&result.url));
+            value
+        })
+        .collect();
+
+    JsonRpcRes::ok(
+        id,
+        serde_json::json!({
+            "page": page_url,
+            "summary": summary,
+            "skipped": skipped,
+            "truncated": found > max_links,
+            "results": results,
+        }),
+    )
+}
+
+/// Gather the page's SEO facts through the extension, then check robots.txt,
+/// sitemaps and key link statuses from the server
+async fn handle_audit_seo(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let link_limit = match arguments.get("linkChecks") {
+        None => seo::DEFAULT_LINK_CHECKS,
+        Some(v) => match v.as_u64().filter(|n| *n <= seo::MAX_LINK_CHECKS as u64) {
+            Some(n) => n as usize,
+            None => {
+                return JsonRpcRes::err(id, -32602, format!("linkChecks must be an integer from 0 to {}", seo::MAX_LINK_CHECKS), None)
+            }
+        },
+    };
+    let site_checks = arguments.get("siteChecks").and_then(|v| v.as_bool()).unwrap_or(true);
+    let timeout_ms = arguments
+        .get("timeoutMs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(preflight::DEFAULT_TIMEOUT_MS)
+        .min(preflight::MAX_TIMEOUT_MS);
+
+    *state.active_session.write().await = Some(session.to_string());
+    let collected = match state.send_to_extension("audit_seo", serde_json::json!({})).await {
+        Ok(collected) => collected,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    let facts: seo::PageFacts = match serde_json::from_value(collected) {
+        Ok(facts) => facts,
+        Err(e) => return JsonRpcRes::err(id, -32000, format!("Malformed SEO facts from extension: {}", e), None),
+    };
+
+    let mut issues = seo::check_page(&facts);
+    let site = if site_checks {
+        let (site, site_issues) = seo::check_site(&facts, link_limit, timeout_ms).await;
+        issues.extend(site_issues);
+        site
+    } else {
+        serde_json::Value::Null
+    };
+
+    let mut result = seo::summarize(issues);
+    result["page"] = serde_json::to_value(&facts).unwrap_or_default();
+    result["site"] = site;
+    JsonRpcRes::ok(id, result)
+}
+
+/// Re-run a failed call from a session's timeline (this session's unless
+/// `session` names another), with optional argument edits. The re-run is
+/// recorded on that session's timeline and in the audit log with the id of
+/// the call it retries and the first call of the chain.
+async fn handle_history_retry(
+    arguments: &serde_json::Value,
+    state: &Arc<ServerState>,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let target = arguments.get("session").and_then(|v| v.as_str()).unwrap_or(session);
+    let call_id = arguments.get("callId").and_then(|v| v.as_str());
+    let original = match state.history.failed_call(target, call_id).await {
+        Ok(original) => original,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    if original.tool == "history_retry" {
+        return JsonRpcRes::err(id, -32602, "history_retry calls can't themselves be retried", None);
+    }
+
+    // Edits replace individual arguments; null removes one
+    let mut retry_arguments = original.replay_arguments.clone().unwrap_or_else(|| serde_json::json!({}));
+    if let Some(edits) = arguments.get("arguments") {
+        let (Some(edits), Some(current)) = (edits.as_object(), retry_arguments.as_object_mut()) else {
+            return JsonRpcRes::err(id, -32602, "arguments must be an object", None);
+        };
+        for (key, value) in edits {
+            if value.is_null() {
+                current.remove(key);
+            } else {
+                current.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    let params = serde_json::json!({ "name": original.tool, "arguments": retry_arguments });
+    let call = PendingCall::begin(Some(&params)).retrying(&original);
+    let req = JsonRpcReq {
+        jsonrpc: Some("2.0".to_string()),
+        id: id.clone(),
+        method: "tools/call".to_string(),
+        params: Some(params),
+    };
+    let res = Box::pin(dispatch_mcp_request(req, Arc::clone(state), target)).await;
+    let call = call.finish(&res);
+    let root = call.retry_root.clone().unwrap_or_default();
+    let call_id = state.finish_call(target, call, &res).await;
+    state
+        .credential_store
+        .record_tool_retry(target, &original.tool, &call_id, &original.call_id, &root, session)
+        .await;
+
+    let mut lineage = serde_json::json!({
+        "session": target,
+        "tool": original.tool,
+        "callId": call_id,
+        "retryOf": original.call_id,
+        "firstCall": root,
+    });
+    match (res.result, res.error) {
+        (_, Some(error)) => {
+            if let Some(data) = error.data {
+                lineage["data"] = data;
+            }
+            JsonRpcRes::err(id, error.code, error.message, Some(lineage))
+        }
+        (result, None) => {
+            lineage["result"] = result.unwrap_or(serde_json::Value::Null);
+            JsonRpcRes::ok(id, lineage)
+        }
+    }
+}
+
+/// Run a tool call as another connected session on behalf of a supervisor.
+/// The call is attributed to the supervisor on the target's timeline and in
+/// the audit log, and the target is notified.
+async fn handle_session_inject(
+    arguments: &serde_json::Value,
+    state: &Arc<ServerState>,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(target) = arguments.get("session").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing session", None);
+    };
+    let Some(tool) = arguments.get("tool").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing tool", None);
+    };
+    if let Err(e) = sessions::check_injectable(tool) {
+        return JsonRpcRes::err(id, -32602, e, None);
+    }
+    let tool_arguments = arguments.get("arguments").cloned().unwrap_or_else(|| serde_json::json!({}));
+    if !tool_arguments.is_object() {
+        return JsonRpcRes::err(id, -32602, "arguments must be an object", None);
+    }
+    if let Err(e) = state.sessions.check_target(session, target).await {
+        return JsonRpcRes::err(id, -32602, e, None);
+    }
+
+    let params = serde_json::json!({ "name": tool, "arguments": tool_arguments });
+    let call = PendingCall::begin(Some(&params)).injected_by(session);
+    let req = JsonRpcReq {
+        jsonrpc: Some("2.0".to_string()),
+        id: id.clone(),
+        method: "tools/call".to_string(),
+        params: Some(params),
+    };
+    let res = Box::pin(dispatch_mcp_request(req, Arc::clone(state), target)).await;
+    let call = call.finish(&res);
+    let error = call.error.clone();
+    let call_id = state.finish_call(target, call, &res).await;
+    state.credential_store.record_tool_injection(target, tool, &call_id, session).await;
+    state.notify_session(
+        target,
+        "notifications/session_injected",
+        serde_json::json!({ "callId": call_id, "tool": tool, "injectedBy": session, "error": error }),
+    );
+
+    let mut attribution = serde_json::json!({ "session": target, "tool": tool, "callId": call_id });
+    match (res.result, res.error) {
+        (_, Some(error)) => {
+            if let Some(data) = error.data {
+                attribution["data"] = data;
+            }
+            JsonRpcRes::err(id, error.code, error.message, Some(attribution))
+        }
+        (result, None) => {
+            attribution["result"] = result.unwrap_or(serde_json::Value::Null);
+            JsonRpcRes::ok(id, attribution)
+        }
+    }
+}
+
+/// Parse the artifacts_search filters
+fn search_filters(arguments: &serde_json::Value) -> Result<search::Filters, String> {
+    let kinds = match arguments.get("kinds") {
+        None => Vec::new(),
+        Some(kinds) => kinds
+            .as_array()
+            .ok_or("kinds must be an array")?
+            .iter()
+            .map(|kind| match kind.as_str() {
+                Some(kind) if search::KINDS.contains(&kind) => Ok(kind.to_string()),
+                _ => Err(format!("kinds must be among {}", search::KINDS.join(", "))),
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let now = chrono::Utc::now();
+    let time = |key: &str| -> Result<Option<i64>, String> {
+        match arguments.get(key) {
+            None => Ok(None),
+            Some(v) => search::parse_time(v.as_str().ok_or(format!("{} must be a string", key))?, now).map(Some),
+        }
+    };
+    Ok(search::Filters {
+        kinds,
+        session: arguments.get("session").and_then(|v| v.as_str()).map(str::to_string),
+        since: time("since")?,
+        until: time("until")?,
+    })
+}
+
+/// Full-text search over snapshots, artifacts and audit events
+async fn handle_artifacts_search(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(text) = arguments.get("query").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing query", None);
+    };
+    let query = match search::Query::parse(text) {
+        Ok(query) => query,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let filters = match search_filters(arguments) {
+        Ok(filters) => filters,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let limit = arguments
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map_or(search::DEFAULT_LIMIT, |n| (n as usize).clamp(1, search::MAX_LIMIT));
+
+    // Indexing reads files, so it runs off the async workers
+    let index = Arc::clone(&state.search);
+    match tokio::task::spawn_blocking(move || index.search(&query, &filters, limit)).await {
+        Ok((hits, total)) => JsonRpcRes::ok(
+            id,
+            serde_json::json!({ "query": text, "total": total, "count": hits.len(), "results": hits }),
+        ),
+        Err(e) => JsonRpcRes::err(id, -32000, e.to_string(), None),
+    }
+}
+
+/// Render the session's timeline, screenshots, network summary and audit events to an HTML file
+async fn handle_session_report(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let calls = state.history.for_session(session).await;
+    let include_screenshots = arguments.get("includeScreenshots").and_then(|v| v.as_bool()).unwrap_or(true);
+    let screenshots = if include_screenshots {
+        match report::screenshots(session) {
+            Ok(screenshots) => screenshots,
+            Err(e) => return JsonRpcRes::err(id, -32000, format!("Failed to read artifacts: {}", e), None),
+        }
+    } else {
+        Vec::new()
+    };
+    // Traffic is captured browser-wide, so only requests since the session's first call count
+    let since = calls.first().map_or(i64::MAX, |call| call.started_ms);
+    let network = report::summarize_network(&state.network_capture.find_http(None, None, usize::MAX).await, since);
+    let audit = state.credential_store.session_audit_events(session);
+
+    let report = report::Report {
+        title: arguments
+            .get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Agent session report")
+            .to_string(),
+        session: session.to_string(),
+        calls,
+        screenshots,
+        network,
+        audit,
+    };
+    let html = report::render(&report);
+    match report::write(session, &html) {
+        Ok(path) => {
+            state.alerts.send(
+                Alert::new(EventClass::TaskCompletion, "Session report written", path.display().to_string()).for_session(session),
+            );
+            JsonRpcRes::ok(
+                id,
+                serde_json::json!({
+                    "path": path.display().to_string(),
+                    "bytes": html.len(),
+                    "toolCalls": report.calls.len(),
+                    "failedCalls": report.calls.iter().filter(|c| c.error.is_some()).count(),
+                    "screenshots": report.screenshots.len(),
+                    "networkRequests": report.network.requests,
+                    "auditEvents": report.audit.len(),
+                }),
+            )
+        }
+        Err(e) => JsonRpcRes::err(id, -32000, format!("Failed to write report: {}", e), None),
+    }
+}
+
+/// Compare the live tool registry with a saved manifest (inline or from a file)
+async fn handle_capabilities_diff(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let saved = match (arguments.get("manifest"), arguments.get("path").and_then(|v| v.as_str())) {
+        (Some(manifest), _) => manifest.clone(),
+        (None, Some(path)) => {
+            let resolved = match state.roots.resolve(session, path).await {
+                Ok(resolved) => resolved,
+                Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+            };
+            let contents = match tokio::fs::read_to_string(&resolved).await {
+                Ok(contents) => contents,
+                Err(e) => return JsonRpcRes::err(id, -32602, format!("Failed to read {}: {}", path, e), None),
+            };
+            match serde_json::from_str(&contents) {
+                Ok(manifest) => manifest,
+                Err(e) => return JsonRpcRes::err(id, -32602, format!("Invalid manifest in {}: {}", path, e), None),
+            }
+        }
+        (None, None) => return JsonRpcRes::err(id, -32602, "Provide manifest or path", None),
+    };
+
+    let saved = match mcp::manifest::tools_by_name(&saved) {
+        Ok(saved) => saved,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+    let live = match mcp::manifest::tools_by_name(&serde_json::Value::Array(mcp::tools::builtin_tools())) {
+        Ok(live) => live,
+        Err(e) => return JsonRpcRes::err(id, -32000, e, None),
+    };
+    JsonRpcRes::ok(id, mcp::manifest::diff(&saved, &live))
+}
+
+/// Open a menu, capture its items, and (optionally) close it, as one operation.
+/// Menus close between independent tool calls, so the steps run back to back here.
+async fn handle_explore_menu(
+    arguments: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(trigger) = arguments.get("trigger").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing trigger", None);
+    };
+    let action = arguments.get("action").and_then(|v| v.as_str()).unwrap_or("hover");
+    if action != "hover" && action != "click" {
+        return JsonRpcRes::err(id, -32602, format!("Invalid action '{}': expected hover or click", action), None);
+    }
+    let menu_selector = arguments
+        .get("menuSelector")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_MENU_SELECTOR);
+    let item_selector = arguments
+        .get("itemSelector")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_MENU_ITEM_SELECTOR);
+    let timeout_ms = arguments
+        .get("timeoutMs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_MENU_TIMEOUT_MS);
+    let restore = arguments.get("restore").and_then(|v| v.as_bool()).unwrap_or(true);
+
+    *state.active_session.write().await = Some(session.to_string());
+
+    if let Err(e) = state
+        .send_to_extension(action, serde_json::json!({ "selector": trigger }))
+        .await
+    {
+        return JsonRpcRes::err(id, -32000, format!("Failed to {} trigger: {}", action, e), None);
+    }
+
+    if let Err(e) = state
+        .send_to_extension(
+            "wait_for_selector",
+            serde_json::json!({ "selector": menu_selector, "state": "visible", "timeout": timeout_ms }),
+        )
+        .await
+    {
+        return JsonRpcRes::err(
+            id,
+            -32000,
+            format!("Menu did not appear within {}ms: {}", timeout_ms, e),
+            Some(serde_json::json!({ "trigger": trigger, "menuSelector": menu_selector })),
+        );
+    }
+
+    let items = match state
+        .send_to_extension(
+            "query_elements",
+            serde_json::json!({ "selector": item_selector, "within": menu_selector, "visibleOnly": true }),
+        )
+        .await
+    {
+        Ok(result) => normalize_menu_items(result.get("elements").unwrap_or(&serde_json::Value::Null)),
+        Err(e) => return JsonRpcRes::err(id, -32000, format!("Failed to read menu items: {}", e), None),
+    };
+
+    // Escape closes click-opened menus; moving the pointer away closes hover menus
+    let mut restored = false;
+    if restore {
+        restored = state
+            .send_to_extension("press_key", serde_json::json!({ "key": "Escape" }))
+            .await
+            .is_ok();
+        if action == "hover" {
+            let moved_away = state
+                .send_to_extension("hover", serde_json::json!({ "selector": "body", "position": { "x": 0, "y": 0 } }))
+                .await
+                .is_ok();
+            restored |= moved_away;
+        }
+    }
+
+    JsonRpcRes::ok(
+        id,
+        serde_json::json!({
+            "trigger": trigger,
+            "action": action,
+            "menuSelector": menu_selector,
+            "count": items.len(),
+            "items": items,
+            "restored": restored
+        }),
+    )
+}
+
+/// List per-tab page resources plus recent screenshots
+async fn handle_resources_list(state: &ServerState, id: Option<serde_json::Value>) -> JsonRpcRes {
+    let mut list = Vec::new();
+
+    if state.extension_tx.read().await.is_some() {
+        match state.send_to_extension("list_tabs", serde_json::json!({})).await {
+            Ok(result) => {
+                for tab in result.get("tabs").and_then(|v| v.as_array()).into_iter().flatten() {
+                    let Some(tab_id) = tab.get("id").and_then(|v| v.as_u64()) else {
+                        continue;
+                    };
+                    list.extend(resources::tab_resources(
+                        &tab_id.to_string(),
+                        tab.get("title").and_then(|v| v.as_str()),
+                        tab.get("url").and_then(|v| v.as_str()),
+                    ));
+                }
+            }
+            Err(e) => {
+                debug!("list_tabs failed, listing the active tab only: {}", e);
+                list.extend(resources::tab_resources(resources::ACTIVE_TAB, Some("active tab"), None));
+            }
+        }
+    }
+
+    list.extend(state.screenshots.resources().await);
+    JsonRpcRes::ok(id, serde_json::json!({ "resources": list }))
+}
+
+/// Read one browser:// resource, fetching live page content from the extension
+async fn handle_resources_read(
+    uri: &str,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let Some(resource) = BrowserResource::parse(uri) else {
+        return JsonRpcRes::err(id, -32002, format!("Resource not found: {}", uri), None);
+    };
+
+    let (method, tab, field, mime_type) = match &resource {
+        BrowserResource::Screenshot { id: shot_id } => {
+            return match state.screenshots.get(*shot_id).await {
+                Some(shot) => JsonRpcRes::ok(
+                    id,
+                    serde_json::json!({
+                        "contents": [{ "uri": uri, "mimeType": "image/png", "blob": shot.data }]
+                    }),
+                ),
+                None => JsonRpcRes::err(id, -32002, format!("Resource not found: {}", uri), None),
+            };
+        }
+        BrowserResource::Dom { tab } => ("get_html", tab, "html", "text/html"),
+        BrowserResource::Text { tab } => ("get_text", tab, "text", "text/plain"),
+    };
+
+    *state.active_session.write().await = Some(session.to_string());
+    match state.send_to_extension(method, BrowserResource::tab_params(tab)).await {
+        Ok(result) => {
+            let text = result.get(field).and_then(|v| v.as_str()).unwrap_or_default();
+            JsonRpcRes::ok(
+                id,
+                serde_json::json!({
+                    "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }]
+                }),
+            )
+        }
+        Err(e) => JsonRpcRes::err(id, -32000, e, None),
+    }
+}
+
+/// Assemble a built-in prompt from live page, credential, and network state
+async fn handle_completion(
+    params: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let reference = params.get("ref").cloned().unwrap_or(serde_json::Value::Null);
+    let Some(argument) = params.pointer("/argument/name").and_then(|v| v.as_str()) else {
+        return JsonRpcRes::err(id, -32602, "Missing argument name", None);
+    };
+    let prefix = params.pointer("/argument/value").and_then(|v| v.as_str()).unwrap_or_default();
+    let source = match completion::source(&reference, argument) {
+        Ok(source) => source,
+        Err(e) => return JsonRpcRes::err(id, -32602, e, None),
+    };
+
+    let candidates: Vec<String> = match source {
+        completion::Source::RpId | completion::Source::LoginUrl => {
+            let mut rp_ids: Vec<String> = state
+                .credential_store
+                .list_credentials()
+                .await
+                .map(|creds| creds.into_iter().map(|c| c.rp_id).collect())
+                .unwrap_or_default();
+            if source == completion::Source::RpId {
+                if let Ok(secrets) = state.credential_store.list_recovery_secrets(None).await {
+                    rp_ids.extend(secrets.into_iter().map(|s| s.rp_id));
+                }
+                rp_ids
+            } else {
+                rp_ids.into_iter().map(|rp_id| format!("https://{}", rp_id)).collect()
+            }
+        }
+        completion::Source::TabId | completion::Source::ResourceTabId => {
+            let mut tabs: Vec<String> = Vec::new();
+            if source == completion::Source::ResourceTabId {
+                tabs.push(resources::ACTIVE_TAB.to_string());
+            }
+            if let Some(current) = state.targets.current_tab(session).await {
+                tabs.push(current.to_string());
+            }
+            tabs.extend(state.navigation.known_tabs().await.iter().map(|tab| tab.to_string()));
+            tabs
+        }
+        completion::Source::Selector => state.recent_selectors.for_session(session).await,
+        completion::Source::None => Vec::new(),
+    };
+    JsonRpcRes::ok(
+        id,
+        serde_json::json!({ "completion": completion::complete(candidates, prefix) }),
+    )
+}
+
+async fn handle_prompts_get(
+    params: &serde_json::Value,
+    state: &ServerState,
+    session: &str,
+    id: Option<serde_json::Value>,
+) -> JsonRpcRes {
+    let name = params.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+    if !prompts::exists(name) {
+        return JsonRpcRes::err(id, -32602, format!("Unknown prompt: {}", name), None);
+    }
+    let arg = |key: &str| {
+        params
+            .get("arguments")
+            .and_then(|a| a.get(key))
+            .and_then(|v| v.as_str())
+    };
+
+    let current_page = if state.extension_tx.read().await.is_some() && name != "debug_failed_requests" {
+        *state.active_session.write().await = Some(session.to_string());
+        state
+            .send_to_extension("get_text", serde_json::json!({}))
+            .await
+            .map(|result| prompts::PageContext::from_extension(&result))
+    } else {
+        Err("Extension not connected".to_string())
+    };
+
+    let prompt = match name {
+        "summarize_page" => match current_page {
+            Ok(page) => prompts::summarize_page(&page, arg("focus")),
+            Err(e) => return JsonRpcRes::err(id, -32000, format!("Failed to read current page: {}", e), None),
+        },
+        "fill_login_form" => {
+            let Some(url) = arg("url") else {
+                return JsonRpcRes::err(id, -32602, "Missing required argument: url", None);
+            };
+            let host = prompts::host_of(url).unwrap_or(url);
+            let passkey_count = state
+                .credential_store
+                .list_credentials()
+                .await
+                .map(|creds| {
+                    creds
+                        .iter()
+                        .filter(|c| host == c.rp_id || host.ends_with(&format!(".{}", c.rp_id)))
+                        .count()
+                })
+                .unwrap_or(0);
+            prompts::fill_login_form(url, arg("username"), passkey_count, current_page.ok().as_ref())
+        }
+        _ => {
+            let entries = state.network_capture.find_http(None, arg("urlPattern"), 200).await;
+            prompts::debug_failed_requests(&entries)
+        }
+    };
+
+    JsonRpcRes::ok(id, prompt)
+}
+
+// ============================================================================
+// MCP TCP Server
+// ============================================================================
+
+/// Bind the MCP TCP listener, refusing plaintext on non-loopback addresses
+async fn bind_mcp_tcp(addr: &str) -> Result<(TcpListener, Option<tokio_rustls::TlsAcceptor>)> {
+    let acceptor = tls::acceptor_from_env()?;
+    tls::check_bind(addr, acceptor.is_some())?;
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind MCP TCP {}: {}", addr, e))?;
+    info!(
+        "MCP TCP server listening on {}{}",
+        listener.local_addr()?,
+        if acceptor.is_some() { " (TLS)" } else { "" }
+    );
+    Ok((listener, acceptor))
+}
+
+async fn run_mcp_tcp(
+    state: Arc<ServerState>,
+    listener: TcpListener,
+    acceptor: Option<tokio_rustls::TlsAcceptor>,
+) -> Result<()> {
+    loop {
+        let (socket, peer) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        let Some(acceptor) = acceptor.clone() else {
+            tokio::spawn(handle_mcp_tcp_connection(socket, peer, state));
+            continue;
+        };
+        // Handshake off the accept loop so a stalled client can't block others
+        tokio::spawn(async move {
+            match timeout(Duration::from_secs(tls::HANDSHAKE_TIMEOUT_SECS), acceptor.accept(socket)).await {
+                Ok(Ok(stream)) => handle_mcp_tcp_connection(stream, peer, state).await,
+                Ok(Err(e)) => warn!("MCP TLS handshake with {} failed: {}", peer, e),
+                Err(_) => warn!("MCP TLS handshake with {} timed out", peer),
+            }
+        });
+    }
+}
+
+async fn handle_mcp_tcp_connection<S>(socket: S, peer: std::net::SocketAddr, state: Arc<ServerState>)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    info!("MCP TCP client connected: {}", peer);
+
+    let session = format!("tcp:{}", peer);
+    let (reader, writer) = tokio::io::split(socket);
+    let writer = Arc::new(Mutex::new(writer));
+    // Notifications and server requests only flow once the client has authenticated
+    let mut attached = None;
+    if !state.auth.is_required() {
+        attached = Some(attach_mcp_connection(&state, &writer, &session).await);
+    }
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    // Requests run concurrently and answer as they finish; ids let the client match them up
+    let mut in_flight = JoinSet::new();
+    // Every line counts as activity, including replies to keep-alive pings
+    let activity = Arc::new(keepalive::Activity::default());
+    let (unresponsive_tx, mut unresponsive) = mpsc::channel::<String>(1);
+    let mut keepalive_task = None;
+    if attached.is_some() {
+        keepalive_task = spawn_keepalive(&state, &session, &activity, &unresponsive_tx);
+    }
+
+    loop {
+        line.clear();
+        // Partially read input is lost when the keep-alive wins, but the connection is closing anyway
+        let read = tokio::select! {
+            read = reader.read_line(&mut line) => read,
+            Some(reason) = unresponsive.recv() => {
+                warn!("Closing MCP TCP client {}: {}", peer, reason);
+                break;
+            }
+        };
+        match read {
+            Ok(0) => break, // EOF
+            Ok(_) => {
+                activity.touch();
+                // Reap finished requests so the set doesn't grow with the connection
+                while in_flight.try_join_next().is_some() {}
+
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+
+                debug!("MCP TCP received: {}", trimmed);
+
+                if attached.is_none() {
+                    let (response, authenticated) = state.auth.handle_unauthenticated(trimmed);
+                    state.alert_on_rejected_token(&session, response.as_ref());
+                    if let Some(json) = response.and_then(|r| serde_json::to_string(&r).ok()) {
+                        if let Err(e) = write_line(&writer, &json).await {
+                            error!("Failed to write MCP TCP response: {}", e);
+                            break;
+                        }
+                    }
+                    if authenticated {
+                        attached = Some(attach_mcp_connection(&state, &writer, &session).await);
+                        keepalive_task = spawn_keepalive(&state, &session, &activity, &unresponsive_tx);
+                    }
+                    continue;
+                }
+
+                let message = trimmed.to_string();
+                let state = Arc::clone(&state);
+                let session = session.clone();
+                let writer = Arc::clone(&writer);
+                in_flight.spawn(async move {
+                    let Some(response_json) = handle_mcp_message(&message, &state, &session).await else {
+                        return;
+                    };
+                    // The write mutex keeps concurrent responses from interleaving
+                    if let Err(e) = write_line(&writer, &response_json).await {
+                        error!("Failed to write MCP TCP response: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("MCP TCP read error: {}", e);
+                break;
+            }
+        }
+    }
+
+    in_flight.abort_all();
+    if let Some(task) = keepalive_task {
+        task.abort();
+    }
+    state.sampling.unregister(&session).await;
+    state.forget_session(&session).await;
+    if let Some((notify_task, outbound_task)) = attached {
+        outbound_task.abort();
+        notify_task.abort();
+    }
+    info!("MCP TCP client disconnected: {}", peer);
+}
+
+/// Ping an authenticated TCP client whenever it goes quiet for the configured
+/// interval, reporting on `unresponsive` once a ping goes unanswered
+fn spawn_keepalive(
+    state: &Arc<ServerState>,
+    session: &str,
+    activity: &Arc<keepalive::Activity>,
+    unresponsive: &mpsc::Sender<String>,
+) -> Option<JoinHandle<()>> {
+    let interval = state.keepalive?;
+    let state = Arc::clone(state);
+    let session = session.to_string();
+    let activity = Arc::clone(activity);
+    let unresponsive = unresponsive.clone();
+    Some(tokio::spawn(async move {
+        let reason = keepalive::monitor(interval, &activity, || {
+            state
+                .sampling
+                .request(&session, "ping", serde_json::json!({}), keepalive::PING_TIMEOUT_SECS)
+        })
+        .await;
+        let _ = unresponsive.send(reason).await;
+    }))
+}
+
+/// Handle one line from a line-delimited transport: a single request or a batch.
+/// Batch members run concurrently; returns None when there is nothing to send back
+/// (a batch made only of notifications).
+async fn handle_mcp_message(text: &str, state: &Arc<ServerState>, session: &str) -> Option<String> {
+    let response = match mcp::parse_message(text) {
+        Ok(JsonRpcIncoming::Single(req)) if req.id.is_none() => {
+            tokio::spawn(handle_mcp_notification(req, Arc::clone(state), session.to_string()));
+            return None;
+        }
+        Ok(JsonRpcIncoming::Single(req)) => {
+            serde_json::to_value(handle_mcp_request(req, Arc::clone(state), session).await)
+        }
+        Ok(JsonRpcIncoming::Response(response)) => {
+            state.sampling.handle_response(&response).await;
+            return None;
+        }
+        Ok(JsonRpcIncoming::Batch(items)) if items.is_empty() => serde_json::to_value(JsonRpcRes::err(
+            None,
+            -32600,
+            "Invalid Request: empty batch",
+            None,
+        )),
+        Ok(JsonRpcIncoming::Batch(items)) => {
+            let responses = futures_util::future::join_all(items.into_iter().map(|item| async move {
+                if mcp::is_response(&item) {
+                    state.sampling.handle_response(&item).await;
+                    return None;
+                }
+                match serde_json::from_value::<JsonRpcReq>(item) {
+                    Ok(req) if req.id.is_none() => {
+                        // Notifications get no response entry and must not hold up the batch
+                        tokio::spawn(handle_mcp_notification(req, Arc::clone(state), session.to_string()));
+                        None
+                    }
+                    Ok(req) => Some(handle_mcp_request(req, Arc::clone(state), session).await),
+                    Err(e) => Some(JsonRpcRes::err(None, -32600, format!("Invalid Request: {}", e), None)),
+                }
+            }))
+            .await;
+            let responses: Vec<JsonRpcRes> = responses.into_iter().flatten().collect();
+            if responses.is_empty() {
+                return None;
+            }
+            serde_json::to_value(responses)
+        }
+        Err(e) => serde_json::to_value(JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None)),
+    };
+
+    match response {
+        Ok(value) => Some(value.to_string()),
+        Err(e) => {
+            error!("Failed to serialize MCP response: {}", e);
+            None
+        }
+    }
+}
+
+/// Write one newline-delimited JSON message and flush
+async fn write_line<W: AsyncWrite + Unpin>(writer: &Mutex<W>, json: &str) -> std::io::Result<()> {
+    let mut writer = writer.lock().await;
+    writer.write_all(json.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await
 }
 
-async fn handle_mcp_tcp_connection(
-    socket: TcpStream,
-    peer: std::net::SocketAddr,
-    state: Arc<ServerState>,
-) {
-    info!("MCP TCP client connected: {}", peer);
-
-    let (reader, mut writer) = socket.into_split();
-    let mut reader = BufReader::new(reader);
-    let mut line = String::new();
+/// Start notification forwarding and server-initiated requests for a connection
+async fn attach_mcp_connection<W>(
+    state: &ServerState,
+    writer: &Arc<Mutex<W>>,
+    session: &str,
+) -> (JoinHandle<()>, JoinHandle<()>)
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let notify_task = spawn_notification_forwarder(state, Arc::clone(writer), session.to_string());
+    let (outbound_tx, outbound_task) = spawn_outbound_writer(Arc::clone(writer));
+    state.sampling.register(session, outbound_tx).await;
+    (notify_task, outbound_task)
+}
 
-    loop {
-        line.clear();
-        match reader.read_line(&mut line).await {
-            Ok(0) => break, // EOF
-            Ok(_) => {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    continue;
-                }
+/// Writer task for server-initiated requests (e.g. sampling) on one MCP connection
+fn spawn_outbound_writer<W>(writer: Arc<Mutex<W>>) -> (mpsc::Sender<String>, JoinHandle<()>)
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::channel::<String>(16);
+    let task = tokio::spawn(async move {
+        while let Some(json) = rx.recv().await {
+            if let Err(e) = write_line(&writer, &json).await {
+                debug!("Failed to write server request: {}", e);
+                break;
+            }
+        }
+    });
+    (tx, task)
+}
 
-                debug!("MCP TCP received: {}", trimmed);
+/// Forward server notifications to one MCP connection until it goes away
+fn spawn_notification_forwarder<W>(state: &ServerState, writer: Arc<Mutex<W>>, session: String) -> JoinHandle<()>
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let mut rx = state.notifications.subscribe();
 
-                // Parse JSON-RPC request
-                let req: Result<JsonRpcReq, _> = serde_json::from_str(trimmed);
-                let response = match req {
-                    Ok(req) => handle_mcp_request(req, Arc::clone(&state)).await,
-                    Err(e) => {
-                        JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None)
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(notification) if !notification.is_visible_to(&session) => {}
+                Ok(notification) => {
+                    let json = match serde_json::to_string(&notification) {
+                        Ok(j) => j,
+                        Err(e) => {
+                            error!("Failed to serialize notification: {}", e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = write_line(&writer, &json).await {
+                        debug!("Failed to write notification: {}", e);
+                        break;
                     }
-                };
-
-                // Send response
-                let response_json = serde_json::to_string(&response).unwrap();
-                if let Err(e) = writer.write_all(response_json.as_bytes()).await {
-                    error!("Failed to write MCP TCP response: {}", e);
-                    break;
-                }
-                if let Err(e) = writer.write_all(b"\n").await {
-                    error!("Failed to write newline: {}", e);
-                    break;
                 }
-                if let Err(e) = writer.flush().await {
-                    error!("Failed to flush: {}", e);
-                    break;
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("MCP client lagged, dropped {} notifications", skipped);
                 }
-            }
-            Err(e) => {
-                error!("MCP TCP read error: {}", e);
-                break;
+                Err(broadcast::error::RecvError::Closed) => break,
             }
         }
-    }
-
-    info!("MCP TCP client disconnected: {}", peer);
+    })
 }
 
 // ============================================================================
@@ -546,7 +5684,10 @@ async fn run_mcp_stdio(state: Arc<ServerState>) -> Result<()> {
 
     let stdin = tokio::io::stdin();
     let mut reader = BufReader::new(stdin);
-    let mut stdout = tokio::io::stdout();
+    let stdout = Arc::new(Mutex::new(tokio::io::stdout()));
+    let notify_task = spawn_notification_forwarder(&state, Arc::clone(&stdout), "stdio".to_string());
+    let (outbound_tx, outbound_task) = spawn_outbound_writer(Arc::clone(&stdout));
+    state.sampling.register("stdio", outbound_tx).await;
     let mut line = String::new();
 
     loop {
@@ -561,29 +5702,15 @@ async fn run_mcp_stdio(state: Arc<ServerState>) -> Result<()> {
 
                 debug!("MCP stdio received: {}", trimmed);
 
-                // Parse JSON-RPC request
-                let req: Result<JsonRpcReq, _> = serde_json::from_str(trimmed);
-                let response = match req {
-                    Ok(req) => handle_mcp_request(req, Arc::clone(&state)).await,
-                    Err(e) => {
-                        JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None)
-                    }
+                let Some(response_json) = handle_mcp_message(trimmed, &state, "stdio").await else {
+                    continue;
                 };
 
                 // Send response
-                let response_json = serde_json::to_string(&response).unwrap();
-                if let Err(e) = stdout.write_all(response_json.as_bytes()).await {
+                if let Err(e) = write_line(&stdout, &response_json).await {
                     error!("Failed to write MCP stdio response: {}", e);
                     break;
                 }
-                if let Err(e) = stdout.write_all(b"\n").await {
-                    error!("Failed to write newline: {}", e);
-                    break;
-                }
-                if let Err(e) = stdout.flush().await {
-                    error!("Failed to flush: {}", e);
-                    break;
-                }
             }
             Err(e) => {
                 error!("MCP stdio read error: {}", e);
@@ -592,6 +5719,10 @@ async fn run_mcp_stdio(state: Arc<ServerState>) -> Result<()> {
         }
     }
 
+    state.sampling.unregister("stdio").await;
+    state.forget_session("stdio").await;
+    outbound_task.abort();
+    notify_task.abort();
     info!("MCP stdio server stopped");
     Ok(())
 }
@@ -600,10 +5731,7 @@ async fn run_mcp_stdio(state: Arc<ServerState>) -> Result<()> {
 // WebSocket Server (for Extension)
 // ============================================================================
 
-async fn run_websocket_server(state: Arc<ServerState>) -> Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:8085").await?;
-    info!("WebSocket server listening on 127.0.0.1:8085");
-
+async fn run_websocket_server(state: Arc<ServerState>, listener: TcpListener) -> Result<()> {
     loop {
         let (stream, peer) = listener.accept().await?;
         let state = Arc::clone(&state);
@@ -616,9 +5744,29 @@ async fn handle_websocket_connection(
     peer: std::net::SocketAddr,
     state: Arc<ServerState>,
 ) {
-    info!("WebSocket client connected: {}", peer);
-
-    let ws_stream = match accept_async(stream).await {
+    // The request path decides whether this is an MCP client or the extension,
+    // and which origins may open it
+    let mut path = String::new();
+    let origins = Arc::clone(&state.origins);
+    // The error type is fixed by tungstenite's handshake callback signature
+    #[allow(clippy::result_large_err)]
+    let record_path = |request: &WsRequest, response: WsResponse| {
+        path = request.uri().path().to_string();
+        let origin = request.headers().get("origin").and_then(|o| o.to_str().ok());
+        let allowed = if path == ws_transport::MCP_WS_PATH {
+            origins.allows_client(origin)
+        } else {
+            origins.allows_extension(origin)
+        };
+        if !allowed {
+            warn!("Rejected WebSocket upgrade to {} from origin {:?} ({})", path, origin, peer);
+            let mut forbidden = WsErrorResponse::new(Some("Origin not allowed".to_string()));
+            *forbidden.status_mut() = StatusCode::FORBIDDEN;
+            return Err(forbidden);
+        }
+        Ok(response)
+    };
+    let ws_stream = match accept_hdr_async(stream, record_path).await {
         Ok(ws) => ws,
         Err(e) => {
             error!("Failed to accept WebSocket: {}", e);
@@ -626,16 +5774,25 @@ async fn handle_websocket_connection(
         }
     };
 
+    if path == ws_transport::MCP_WS_PATH {
+        ws_transport::handle_mcp_websocket(ws_stream, peer, state).await;
+        return;
+    }
+
+    info!("WebSocket client connected: {}", peer);
+
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
     // Create channel for sending commands to extension
-    let (cmd_tx, mut cmd_rx) = mpsc::channel::<ExtensionCommand>(100);
+    // Sized to the admission limit: in-flight commands never queue here for long
+    let (cmd_tx, mut cmd_rx) = mpsc::channel::<ExtensionCommand>(state.commands.limit());
 
     // Register this extension
     {
         let mut ext_tx = state.extension_tx.write().await;
         *ext_tx = Some(cmd_tx);
     }
+    state.tools_changed();
 
     // Spawn task to send commands from channel to WebSocket
     let send_task = tokio::spawn(async move {
@@ -661,9 +5818,11 @@ async fn handle_websocket_connection(
             Ok(WsMessage::Text(text)) => {
                 debug!("WebSocket received: {}", text);
 
-                // Try to parse as response
+                // Try to parse as response, then as an unsolicited event
                 if let Ok(response) = serde_json::from_str::<ExtensionResponse>(&text) {
                     state.handle_extension_response(response).await;
+                } else if let Ok(event) = serde_json::from_str::<ExtensionEvent>(&text) {
+                    state.handle_extension_event(event).await;
                 } else {
                     warn!("Unknown WebSocket message format: {}", text);
                 }
@@ -685,6 +5844,7 @@ async fn handle_websocket_connection(
         let mut ext_tx = state.extension_tx.write().await;
         *ext_tx = None;
     }
+    state.tools_changed();
 
     send_task.abort();
     info!("WebSocket client disconnected: {}", peer);
@@ -700,7 +5860,9 @@ async fn main() -> Result<()> {
     // Otherwise, completely disable stderr output to avoid interfering with MCP stdio
     // Errors will be sent to extension via WebSocket or returned in MCP error responses
     if env::var("RUST_LOG").is_ok() {
+        // Logs go to stderr; stdout carries MCP stdio or the --print-ports report
         tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
             .with_max_level(tracing::Level::INFO)
             .with_target(false)
             .with_thread_ids(false)
@@ -719,12 +5881,62 @@ async fn main() -> Result<()> {
             .init();
     }
 
-    let state = Arc::new(ServerState::new().await);
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let run_doctor = args.first().is_some_and(|a| a == "doctor");
+    if run_doctor {
+        args.remove(0);
+    }
+    let config = match ServerConfig::from_env()?.with_args(args)? {
+        Some(config) => config,
+        None => {
+            eprintln!("{}", config::USAGE);
+            return Ok(());
+        }
+    };
+    if run_doctor {
+        // Checks run against the same addresses the server would bind
+        std::process::exit(if doctor::run(&config).await { 0 } else { 1 });
+    }
+
+    // Stores must be in this build's format before anything opens them
+    migrations::run_pending()?;
+
+    let state = Arc::new(ServerState::new(&config).await);
+
+    // Bind every listener before serving so port 0 can be resolved and reported
+    let ws_listener = TcpListener::bind(&config.ws_addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind WebSocket {}: {}", config.ws_addr, e))?;
+    info!("WebSocket server listening on {}", ws_listener.local_addr()?);
+    let (tcp_listener, acceptor) = bind_mcp_tcp(&config.tcp_addr).await?;
+    let http_listener = match config.http {
+        true => Some(
+            TcpListener::bind(&config.http_addr)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to bind MCP HTTP {}: {}", config.http_addr, e))?,
+        ),
+        false => None,
+    };
+    let bound = BoundAddrs {
+        tcp: tcp_listener.local_addr()?,
+        ws: ws_listener.local_addr()?,
+        http: http_listener.as_ref().map(|l| l.local_addr()).transpose()?,
+    };
+
+    // Published only once bound, so an instance that failed to start can't
+    // replace the token or ports of the one that is running
+    state.auth.write_token_file()?;
+    if let Err(e) = bound.write_ports_file() {
+        warn!("Failed to write ports file: {}", e);
+    }
+    if config.print_ports {
+        println!("{}", bound.to_json());
+    }
 
     // Start WebSocket server (for extension)
     let ws_state = Arc::clone(&state);
     let ws_task = tokio::spawn(async move {
-        if let Err(e) = run_websocket_server(ws_state).await {
+        if let Err(e) = run_websocket_server(ws_state, ws_listener).await {
             error!("WebSocket server error: {}", e);
         }
     });
@@ -732,19 +5944,75 @@ async fn main() -> Result<()> {
     // Start MCP TCP server
     let tcp_state = Arc::clone(&state);
     let tcp_task = tokio::spawn(async move {
-        if let Err(e) = run_mcp_tcp(tcp_state).await {
+        if let Err(e) = run_mcp_tcp(tcp_state, tcp_listener, acceptor).await {
             error!("MCP TCP server error: {}", e);
         }
     });
 
-    // Start MCP stdio server
-    let stdio_state = Arc::clone(&state);
-    let _stdio_task = tokio::spawn(async move {
-        if let Err(e) = run_mcp_stdio(stdio_state).await {
-            error!("MCP stdio server error: {}", e);
+    // Start MCP Streamable HTTP server (opt-in)
+    if let Some(http_listener) = http_listener {
+        let http_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(e) = http_transport::run_mcp_http(http_state, http_listener).await {
+                error!("MCP HTTP server error: {}", e);
+            }
+        });
+    }
+
+    // Remove artifact directories of sessions idle past the retention period,
+    // trim every quota-managed category of the data directory, and compact
+    // audit events older than their retention window
+    let sweep_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        let retention = artifacts::retention();
+        let mut ticker = tokio::time::interval(Duration::from_secs(artifacts::SWEEP_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            let active = sweep_state.protocol_versions.sessions().await;
+            match artifacts::sweep(retention, &active) {
+                Ok(0) => {}
+                Ok(removed) => info!("Removed {} idle session artifact directories", removed),
+                Err(e) => warn!("Artifact cleanup failed: {}", e),
+            }
+            match quota::enforce_all() {
+                Ok(reports) => {
+                    for report in reports {
+                        info!("Quota cleanup: {}", report);
+                    }
+                }
+                Err(e) => warn!("Quota cleanup failed: {}", e),
+            }
+            match sweep_state.credential_store.compact_audit_log(compaction::retention_days()).await {
+                Ok(report) if report.events > 0 => info!(
+                    "Compacted {} audit events into {} daily summaries",
+                    report.events, report.days
+                ),
+                Ok(_) => {}
+                Err(e) => warn!("Audit log compaction failed: {}", e),
+            }
+        }
+    });
+
+    // Re-check watched pages as they come due
+    let watch_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(watch::SCHEDULER_TICK_SECS));
+        loop {
+            ticker.tick().await;
+            run_due_watches(&watch_state).await;
         }
     });
 
+    // Start MCP stdio server, unless stdout is reserved for the port report
+    if !config.print_ports {
+        let stdio_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(e) = run_mcp_stdio(stdio_state).await {
+                error!("MCP stdio server error: {}", e);
+            }
+        });
+    }
+
     // Wait for WebSocket or TCP tasks to complete (stdio can exit normally)
     // Run all three servers concurrently - stdio is fire-and-forget
     tokio::select! {
@@ -767,6 +6035,20 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_menu_items() {
+        let raw = serde_json::json!([
+            { "text": "  New\n  file ", "selector": "#new", "role": "menuitem" },
+            { "text": "", "selector": "#sep" },
+            { "text": "New file", "selector": "#new" },
+            { "text": "Docs", "selector": "a.docs", "href": "/docs", "disabled": null }
+        ]);
+        let items = normalize_menu_items(&raw);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0], serde_json::json!({ "text": "New file", "selector": "#new", "role": "menuitem" }));
+        assert_eq!(items[1], serde_json::json!({ "text": "Docs", "selector": "a.docs", "href": "/docs" }));
+    }
+
     #[test]
     fn test_basic_operations() {
         assert_eq!(2 + 2, 4);
@@ -779,6 +6061,661 @@ mod tests {
         assert_eq!(greeting.len(), 13);
     }
 
+    #[test]
+    fn test_type_text_composition_detection() {
+        let params = prepare_type_text(
+            &serde_json::json!({
+                "fields": [
+                    { "selector": "#name", "text": "山田太郎" },
+                    { "selector": "#email", "text": "taro@example.com" }
+                ]
+            }),
+            Duration::from_secs(config::DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .unwrap();
+        assert_eq!(params["fields"][0]["composition"], true);
+        assert_eq!(params["fields"][1]["composition"], false);
+    }
+
+    #[test]
+    fn test_type_text_rejects_runs_exceeding_timeout() {
+        let long_text = "a".repeat(1000);
+        let args = serde_json::json!({
+            "selector": "#q",
+            "text": long_text,
+            "delayMs": 100
+        });
+        assert!(prepare_type_text(&args, Duration::from_secs(config::DEFAULT_REQUEST_TIMEOUT_SECS)).is_err());
+        // A longer configured timeout leaves room for the same run
+        assert!(prepare_type_text(&args, Duration::from_secs(300)).is_ok());
+    }
+
+    #[test]
+    fn test_set_date_parsing() {
+        let params = prepare_set_date(&serde_json::json!({
+            "selector": "#checkin",
+            "date": "2024-02-29T09:05"
+        }))
+        .unwrap();
+        assert_eq!(params["date"], "2024-02-29");
+        assert_eq!(params["time"], "09:05");
+        assert_eq!(params["monthName"], "February");
+        assert_eq!(params["strategies"], serde_json::json!(["inject", "widget"]));
+
+        assert!(prepare_set_date(&serde_json::json!({ "selector": "#d", "date": "2023-02-29" })).is_err());
+        assert!(prepare_set_date(&serde_json::json!({ "selector": "#d", "date": "2024-01-01", "strategy": "guess" })).is_err());
+    }
+
+    #[test]
+    fn test_emulate_locale_params() {
+        let params = prepare_emulate_locale(&serde_json::json!({
+            "locale": "de-de",
+            "timezone": "Europe/Berlin"
+        }))
+        .unwrap();
+        assert_eq!(params["locale"], "de-DE");
+        assert_eq!(params["acceptLanguage"], "de-DE,de;q=0.9");
+        assert_eq!(params["timezone"], "Europe/Berlin");
+
+        assert_eq!(canonical_locale("zh-hant-tw").unwrap(), "zh-Hant-TW");
+        assert_eq!(prepare_emulate_locale(&serde_json::json!({ "reset": true })).unwrap()["reset"], true);
+        assert!(prepare_emulate_locale(&serde_json::json!({ "locale": "english" })).is_err());
+        assert!(prepare_emulate_locale(&serde_json::json!({ "timezone": "Berlin time" })).is_err());
+        assert!(prepare_emulate_locale(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_click_arguments() {
+        let params = prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "captureReceipt": true })).unwrap();
+        assert_eq!(params, serde_json::json!({ "selector": "#go", "captureReceipt": true, "button": "left", "clickCount": 1 }));
+        let params = prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "button": "middle", "clickCount": 3 })).unwrap();
+        assert_eq!((params["button"].as_str(), params["clickCount"].as_u64()), (Some("middle"), Some(3)));
+
+        let params = prepare_click("playwright_dblclick", &serde_json::json!({ "locator": { "text": "Title" } })).unwrap();
+        assert_eq!((params["button"].as_str(), params["clickCount"].as_u64()), (Some("left"), Some(2)));
+        let params = prepare_click("playwright_context_click", &serde_json::json!({ "selector": "#row", "button": "left" })).unwrap();
+        assert_eq!((params["button"].as_str(), params["clickCount"].as_u64()), (Some("right"), Some(1)));
+
+        assert!(prepare_click("playwright_click", &serde_json::json!({})).is_err());
+        assert!(prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "button": "back" })).is_err());
+        assert!(prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "clickCount": 0 })).is_err());
+        assert!(prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "clickCount": 4 })).is_err());
+        assert!(prepare_click("playwright_click", &serde_json::json!({ "selector": "#go", "button": "right", "clickCount": 2 })).is_err());
+    }
+
+    #[test]
+    fn test_select_arguments() {
+        let params = prepare_select(&serde_json::json!({ "selector": "#country", "label": "Germany" })).unwrap();
+        assert_eq!(params["by"], "label");
+        assert_eq!(params["options"], serde_json::json!(["Germany"]));
+
+        let params = prepare_select(&serde_json::json!({ "selector": "#tags", "index": [0, 2] })).unwrap();
+        assert_eq!(params["options"], serde_json::json!([0, 2]));
+
+        assert!(prepare_select(&serde_json::json!({ "selector": "#a" })).is_err());
+        assert!(prepare_select(&serde_json::json!({ "selector": "#a", "value": "x", "index": 1 })).is_err());
+        assert!(prepare_select(&serde_json::json!({ "selector": "#a", "index": "1" })).is_err());
+        assert!(prepare_select(&serde_json::json!({ "selector": "#a", "value": [] })).is_err());
+        assert!(prepare_select(&serde_json::json!({ "value": "x" })).is_err());
+    }
+
+    #[test]
+    fn test_audit_accessibility_arguments() {
+        let (params, max_nodes) = prepare_audit_accessibility(&serde_json::json!({})).unwrap();
+        assert_eq!(params["rules"].as_array().unwrap().len(), accessibility::RULES.len());
+        assert_eq!(max_nodes, DEFAULT_AUDIT_NODES as usize);
+
+        let (params, max_nodes) =
+            prepare_audit_accessibility(&serde_json::json!({ "selector": "main", "rules": ["image-alt"], "maxNodes": 3 })).unwrap();
+        assert_eq!(params, serde_json::json!({ "selector": "main", "rules": ["image-alt"] }));
+        assert_eq!(max_nodes, 3);
+
+        assert!(prepare_audit_accessibility(&serde_json::json!({ "rules": ["no-such-rule"] })).is_err());
+        assert!(prepare_audit_accessibility(&serde_json::json!({ "rules": [] })).is_err());
+        assert!(prepare_audit_accessibility(&serde_json::json!({ "maxNodes": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_press_key_arguments() {
+        let params = prepare_press_key(&serde_json::json!({ "key": "enter", "selector": "#q" })).unwrap();
+        assert_eq!(params, serde_json::json!({ "key": "Enter", "modifiers": [], "count": 1, "selector": "#q" }));
+
+        let params = prepare_press_key(&serde_json::json!({ "key": "Shift+Tab", "count": 3 })).unwrap();
+        assert_eq!(params["key"], "Tab");
+        assert_eq!(params["modifiers"], serde_json::json!(["Shift"]));
+        assert_eq!(params["count"], 3);
+
+        let params = prepare_press_key(&serde_json::json!({ "key": "Ctrl++", "modifiers": ["control"] })).unwrap();
+        assert_eq!(params["key"], "+");
+        assert_eq!(params["modifiers"], serde_json::json!(["Control"]));
+
+        assert_eq!(prepare_press_key(&serde_json::json!({ "key": "space" })).unwrap()["key"], " ");
+        assert_eq!(prepare_press_key(&serde_json::json!({ "key": "f5" })).unwrap()["key"], "F5");
+        assert_eq!(prepare_press_key(&serde_json::json!({ "key": "a" })).unwrap()["key"], "a");
+
+        assert!(prepare_press_key(&serde_json::json!({})).is_err());
+        assert!(prepare_press_key(&serde_json::json!({ "key": "Hyper+a" })).is_err());
+        assert!(prepare_press_key(&serde_json::json!({ "key": "F13" })).is_err());
+        assert!(prepare_press_key(&serde_json::json!({ "key": "Enter", "count": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_get_html_arguments() {
+        let params = prepare_get_html(&serde_json::json!({})).unwrap();
+        assert!(params.get("selector").is_none());
+        assert_eq!(params["stripScripts"], true);
+        assert_eq!(params["stripStyles"], false);
+        assert_eq!(params["maxLength"], DEFAULT_HTML_LENGTH);
+
+        let params = prepare_get_html(&serde_json::json!({ "selector": "form#login", "stripScripts": false, "maxLength": 1000 })).unwrap();
+        assert_eq!(params["selector"], "form#login");
+        assert_eq!(params["stripScripts"], false);
+        assert_eq!(params["maxLength"], 1000);
+
+        assert!(prepare_get_html(&serde_json::json!({ "selector": "" })).is_err());
+        assert!(prepare_get_html(&serde_json::json!({ "maxLength": MAX_HTML_LENGTH + 1 })).is_err());
+    }
+
+    #[test]
+    fn test_extract_content_arguments() {
+        let params = prepare_extract_content(&serde_json::json!({})).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({ "selector": null, "maxLength": 20000, "includeLinks": true, "includeImages": false })
+        );
+        let params = prepare_extract_content(&serde_json::json!({ "selector": " article ", "maxLength": 500, "includeLinks": false })).unwrap();
+        assert_eq!((params["selector"].as_str(), params["includeLinks"].as_bool()), (Some("article"), Some(false)));
+        assert!(prepare_extract_content(&serde_json::json!({ "selector": "" })).is_err());
+        assert!(prepare_extract_content(&serde_json::json!({ "maxLength": 0 })).is_err());
+        assert!(prepare_extract_content(&serde_json::json!({ "maxLength": 2_000_000 })).is_err());
+    }
+
+    #[test]
+    fn test_screenshot_elements_arguments() {
+        let timeout = Duration::from_secs(30);
+        let params = prepare_screenshot_elements(&serde_json::json!({ "selectors": [" .card ", "#hero"] }), timeout).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "selectors": [".card", "#hero"], "all": false, "maxElements": 20, "padding": 0, "budgetMs": 27000
+            })
+        );
+        let params = prepare_screenshot_elements(&serde_json::json!({ "selectors": [".card"], "all": true, "padding": 8 }), timeout).unwrap();
+        assert_eq!((params["all"].as_bool(), params["padding"].as_u64()), (Some(true), Some(8)));
+
+        assert!(prepare_screenshot_elements(&serde_json::json!({}), timeout).is_err());
+        assert!(prepare_screenshot_elements(&serde_json::json!({ "selectors": [] }), timeout).is_err());
+        assert!(prepare_screenshot_elements(&serde_json::json!({ "selectors": [""] }), timeout).is_err());
+        assert!(prepare_screenshot_elements(&serde_json::json!({ "selectors": vec!["a"; 51] }), timeout).is_err());
+        assert!(prepare_screenshot_elements(&serde_json::json!({ "selectors": ["a"], "maxElements": 101 }), timeout).is_err());
+        assert!(prepare_screenshot_elements(&serde_json::json!({ "selectors": ["a"] }), Duration::from_secs(2)).is_err());
+    }
+
+    #[test]
+    fn test_extract_table_arguments() {
+        let params = prepare_extract_table(&serde_json::json!({})).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({ "selector": null, "index": null, "headerRows": null, "maxRows": 500 })
+        );
+        let params = prepare_extract_table(&serde_json::json!({ "selector": " #prices ", "headerRows": 0, "format": "arrays" })).unwrap();
+        assert_eq!(params["selector"], "#prices");
+        assert_eq!(params["headerRows"], 0);
+        assert_eq!(prepare_extract_table(&serde_json::json!({ "index": 2 })).unwrap()["index"], 2);
+
+        assert!(prepare_extract_table(&serde_json::json!({ "selector": "table", "index": 0 })).is_err());
+        assert!(prepare_extract_table(&serde_json::json!({ "index": -1 })).is_err());
+        assert!(prepare_extract_table(&serde_json::json!({ "headerRows": 11 })).is_err());
+        assert!(prepare_extract_table(&serde_json::json!({ "maxRows": 0 })).is_err());
+        assert!(prepare_extract_table(&serde_json::json!({ "format": "csv" })).is_err());
+    }
+
+    #[test]
+    fn test_recording_start_arguments() {
+        let params = prepare_recording_start(&serde_json::json!({})).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({ "format": "webm", "maxWidth": 1280, "maxHeight": 1280, "quality": 70 })
+        );
+        let params = prepare_recording_start(&serde_json::json!({ "format": "mp4", "maxWidth": 800, "quality": 40 })).unwrap();
+        assert_eq!(params["format"], "mp4");
+        assert_eq!(params["maxHeight"], 800);
+        assert!(prepare_recording_start(&serde_json::json!({ "format": "gif" })).is_err());
+        assert!(prepare_recording_start(&serde_json::json!({ "maxWidth": 100 })).is_err());
+        assert!(prepare_recording_start(&serde_json::json!({ "quality": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_list_forms_arguments() {
+        let params = prepare_list_forms(&serde_json::json!({})).unwrap();
+        assert_eq!(params, serde_json::json!({ "selector": null, "includeHidden": false, "maxOptions": 50 }));
+        let params = prepare_list_forms(&serde_json::json!({ "selector": " #checkout ", "includeHidden": true, "maxOptions": 5 })).unwrap();
+        assert_eq!(params["selector"], "#checkout");
+        assert_eq!(params["includeHidden"], true);
+        assert!(prepare_list_forms(&serde_json::json!({ "selector": " " })).is_err());
+        assert!(prepare_list_forms(&serde_json::json!({ "maxOptions": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_collect_items_arguments() {
+        let timeout = Duration::from_secs(30);
+        let params = prepare_collect_items(&serde_json::json!({ "itemSelector": " article " }), timeout).unwrap();
+        assert_eq!(params["itemSelector"], "article");
+        assert_eq!(params["maxItems"], 100);
+        // The default budget shrinks to fit the command timeout
+        assert_eq!(params["timeoutMs"], 28_000);
+        assert!(params["fields"].is_null());
+
+        let params = prepare_collect_items(
+            &serde_json::json!({ "itemSelector": "li", "fields": { "title": "h2", "url": "a@href", "id": "@data-id" }, "timeoutMs": 5000 }),
+            timeout,
+        )
+        .unwrap();
+        assert_eq!(params["fields"]["url"], "a@href");
+        assert_eq!(params["timeoutMs"], 5000);
+
+        assert!(prepare_collect_items(&serde_json::json!({}), timeout).is_err());
+        assert!(prepare_collect_items(&serde_json::json!({ "itemSelector": "li", "timeoutMs": 29_000 }), timeout).is_err());
+        assert!(prepare_collect_items(&serde_json::json!({ "itemSelector": "li", "fields": {} }), timeout).is_err());
+        assert!(prepare_collect_items(&serde_json::json!({ "itemSelector": "li", "fields": { "x": "@" } }), timeout).is_err());
+        assert!(prepare_collect_items(&serde_json::json!({ "itemSelector": "li", "maxItems": 0 }), timeout).is_err());
+    }
+
+    #[test]
+    fn test_screenshot_arguments() {
+        let params = prepare_screenshot(&serde_json::json!({ "fullPage": true })).unwrap();
+        assert_eq!(params, serde_json::json!({ "fullPage": true }));
+        let params = prepare_screenshot(&serde_json::json!({ "highlightSelector": " #buy ", "highlightLabel": "Target" })).unwrap();
+        assert_eq!(params, serde_json::json!({ "highlightSelector": "#buy", "highlightLabel": "Target" }));
+
+        assert!(prepare_screenshot(&serde_json::json!({ "highlightSelector": "" })).is_err());
+        assert!(prepare_screenshot(&serde_json::json!({ "highlightLabel": "Target" })).is_err());
+        assert!(prepare_screenshot(&serde_json::json!({ "highlightSelector": "a", "highlightLabel": "x".repeat(81) })).is_err());
+    }
+
+    #[test]
+    fn test_find_text_arguments() {
+        let params = prepare_find_text(&serde_json::json!({ "query": "Total" })).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "query": "Total", "regex": false, "caseSensitive": false, "wholeWord": false, "selector": null,
+                "contextChars": 40, "maxResults": 20, "scrollTo": null, "highlight": true
+            })
+        );
+        let params = prepare_find_text(&serde_json::json!({ "query": "\\d+ items", "regex": true, "scrollTo": 2, "contextChars": 0 })).unwrap();
+        assert_eq!((params["scrollTo"].as_u64(), params["contextChars"].as_u64()), (Some(2), Some(0)));
+        assert_eq!(prepare_find_text(&serde_json::json!({ "clear": true })).unwrap(), serde_json::json!({ "clear": true }));
+
+        assert!(prepare_find_text(&serde_json::json!({})).is_err());
+        assert!(prepare_find_text(&serde_json::json!({ "query": "" })).is_err());
+        assert!(prepare_find_text(&serde_json::json!({ "query": "a", "maxResults": 0 })).is_err());
+        assert!(prepare_find_text(&serde_json::json!({ "query": "a", "contextChars": 501 })).is_err());
+        assert!(prepare_find_text(&serde_json::json!({ "query": "a", "scrollTo": -1 })).is_err());
+        assert!(prepare_find_text(&serde_json::json!({ "query": "a", "selector": " " })).is_err());
+    }
+
+    #[test]
+    fn test_cookie_arguments() {
+        let params = prepare_cookie_query("browser_get_cookies", &serde_json::json!({ "domain": "Example.com" })).unwrap();
+        assert_eq!(params, serde_json::json!({ "domain": "example.com", "includeValues": false }));
+        let params = prepare_cookie_query("browser_clear_cookies", &serde_json::json!({ "url": "https://a.test/x", "name": "sid" })).unwrap();
+        assert_eq!(params, serde_json::json!({ "url": "https://a.test/x", "name": "sid" }));
+        assert!(prepare_cookie_query("browser_get_cookies", &serde_json::json!({ "domain": "a.test", "url": "https://a.test" })).is_err());
+        assert!(prepare_cookie_query("browser_get_cookies", &serde_json::json!({ "domain": "https://a.test" })).is_err());
+        assert!(prepare_cookie_query("browser_clear_cookies", &serde_json::json!({ "url": "file:///etc" })).is_err());
+
+        let params = prepare_set_cookie(&serde_json::json!({
+            "name": "sid", "value": "abc", "domain": ".a.test", "sameSite": "None", "maxAgeSeconds": 60
+        }))
+        .unwrap();
+        assert_eq!(params["path"], "/");
+        assert_eq!(params["sameSite"], "no_restriction");
+        assert_eq!(params["secure"], true);
+        assert!(params["expirationDate"].as_u64().unwrap() > chrono::Utc::now().timestamp() as u64);
+
+        assert!(prepare_set_cookie(&serde_json::json!({ "value": "abc" })).is_err());
+        assert!(prepare_set_cookie(&serde_json::json!({ "name": "a b", "value": "abc" })).is_err());
+        assert!(prepare_set_cookie(&serde_json::json!({ "name": "sid", "value": "a;b" })).is_err());
+        assert!(prepare_set_cookie(&serde_json::json!({ "name": "sid", "value": "x", "sameSite": "none", "secure": false })).is_err());
+        assert!(prepare_set_cookie(&serde_json::json!({ "name": "sid", "value": "x", "expires": 1, "maxAgeSeconds": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_accessibility_tree_arguments() {
+        let params = prepare_accessibility_tree(&serde_json::json!({})).unwrap();
+        assert_eq!(params, serde_json::json!({ "interestingOnly": true, "maxDepth": 100, "maxNodes": 2000 }));
+        let params = prepare_accessibility_tree(&serde_json::json!({ "selector": " form ", "interestingOnly": false })).unwrap();
+        assert_eq!((params["selector"].as_str(), params["interestingOnly"].as_bool()), (Some("form"), Some(false)));
+        assert!(prepare_accessibility_tree(&serde_json::json!({ "maxNodes": 0 })).is_err());
+        assert!(prepare_accessibility_tree(&serde_json::json!({ "maxDepth": 101 })).is_err());
+        assert!(prepare_accessibility_tree(&serde_json::json!({ "selector": "" })).is_err());
+    }
+
+    #[test]
+    fn test_list_downloads_arguments() {
+        let params = prepare_list_downloads(&serde_json::json!({ "state": "complete", "filenameContains": ".csv" })).unwrap();
+        assert_eq!(params, serde_json::json!({ "state": "complete", "limit": 20, "filenameContains": ".csv" }));
+        assert!(prepare_list_downloads(&serde_json::json!({ "state": "done" })).is_err());
+        assert!(prepare_list_downloads(&serde_json::json!({ "limit": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_locator_arguments() {
+        let locator = prepare_locator(&serde_json::json!({ "role": " Button ", "name": "Submit", "exact": true })).unwrap();
+        assert_eq!(locator, serde_json::json!({ "role": "button", "name": "Submit", "exact": true }));
+        let locator = prepare_locator(&serde_json::json!({ "label": "Email", "nth": 1 })).unwrap();
+        assert_eq!(locator, serde_json::json!({ "label": "Email", "nth": 1 }));
+
+        assert!(prepare_locator(&serde_json::json!("button")).is_err());
+        assert!(prepare_locator(&serde_json::json!({})).is_err());
+        assert!(prepare_locator(&serde_json::json!({ "text": "a", "label": "b" })).is_err());
+        assert!(prepare_locator(&serde_json::json!({ "text": "Sign in", "name": "x" })).is_err());
+        assert!(prepare_locator(&serde_json::json!({ "placeholder": "" })).is_err());
+        assert!(prepare_locator(&serde_json::json!({ "testId": "buy" })).is_err());
+        assert!(prepare_locator(&serde_json::json!({ "role": "link", "nth": -1 })).is_err());
+
+        let params = prepare_select(&serde_json::json!({ "locator": { "label": "Country" }, "value": "NZ" })).unwrap();
+        assert_eq!(params["selector"], serde_json::Value::Null);
+        assert!(prepare_select(&serde_json::json!({ "value": "NZ" })).is_err());
+        assert!(prepare_get_element(&serde_json::json!({ "selector": "#a", "locator": { "text": "a" } })).is_err());
+    }
+
+    #[test]
+    fn test_frame_arguments() {
+        assert_eq!(prepare_frame(&serde_json::json!(1)).unwrap(), serde_json::json!({ "index": 1 }));
+        assert_eq!(prepare_frame(&serde_json::json!(" stripe ")).unwrap(), serde_json::json!({ "match": "stripe" }));
+        assert_eq!(prepare_frame(&serde_json::json!({ "id": 12 })).unwrap(), serde_json::json!({ "id": 12 }));
+        assert_eq!(
+            prepare_frame(&serde_json::json!({ "url": "js.stripe.com" })).unwrap(),
+            serde_json::json!({ "url": "js.stripe.com" })
+        );
+
+        assert!(prepare_frame(&serde_json::json!(-1)).is_err());
+        assert!(prepare_frame(&serde_json::json!("")).is_err());
+        assert!(prepare_frame(&serde_json::json!({ "id": 0 })).is_err());
+        assert!(prepare_frame(&serde_json::json!({ "name": "a", "url": "b" })).is_err());
+        assert!(prepare_frame(&serde_json::json!({ "src": "b" })).is_err());
+        assert!(prepare_frame(&serde_json::json!(true)).is_err());
+    }
+
+    #[test]
+    fn test_get_element_arguments() {
+        let params = prepare_get_element(&serde_json::json!({ "selector": " #buy " })).unwrap();
+        assert_eq!(params, serde_json::json!({ "selector": "#buy" }));
+        assert!(prepare_get_element(&serde_json::json!({})).is_err());
+        assert!(prepare_get_element(&serde_json::json!({ "selector": "  " })).is_err());
+    }
+
+    #[test]
+    fn test_fill_form_arguments() {
+        let params = prepare_fill_form(&serde_json::json!({ "fields": [
+            { "selector": "#email", "value": "a@example.com" },
+            { "selector": "#age", "value": 42 },
+            { "selector": "#terms", "value": true },
+            { "selector": "#country", "type": "select", "value": "NZ" },
+            { "selector": "#tags", "value": ["a", "b"], "clear": false },
+            { "locator": { "label": "Phone" }, "value": "555" },
+        ] }))
+        .unwrap();
+        assert_eq!(params["fields"][5]["locator"], serde_json::json!({ "label": "Phone" }));
+        assert_eq!(params["fields"][1]["value"], "42");
+        assert_eq!(params["fields"][2]["type"], "auto");
+        assert_eq!(params["fields"][3]["value"], "NZ");
+        assert_eq!(params["fields"][4]["clear"], false);
+        assert_eq!(params["stopOnError"], false);
+
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": { "#email": "a" } })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "value": "a" }] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "locator": { "nth": 0 }, "value": "a" }] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "selector": "#a" }] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "selector": "#a", "type": "checkbox", "value": "yes" }] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "selector": "#a", "type": "text", "value": true }] })).is_err());
+        assert!(prepare_fill_form(&serde_json::json!({ "fields": [{ "selector": "#a", "type": "file", "value": "x" }] })).is_err());
+    }
+
+    #[test]
+    fn test_assert_page_arguments() {
+        let params = prepare_assert_page(&serde_json::json!({ "assertions": [
+            { "type": "visible", "selector": "#cart" },
+            { "type": "text", "selector": "h1", "contains": "Order", "label": "heading" },
+            { "type": "url", "matches": "/orders/\\d+$" },
+            { "type": "cookie", "name": "session", "not": true },
+            { "type": "exists", "selector": ".error", "not": true },
+        ] }))
+        .unwrap();
+        let descriptions: Vec<&str> = params["assertions"].as_array().unwrap().iter().map(|a| a["description"].as_str().unwrap()).collect();
+        assert_eq!(
+            descriptions,
+            [
+                "#cart is visible",
+                "heading: text of h1 contains \"Order\"",
+                "URL matches \"/orders/\\\\d+$\"",
+                "cookie session is absent",
+                ".error is missing",
+            ]
+        );
+        assert_eq!(params["assertions"][3]["not"], true);
+
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "title" }] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "visible" }] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "url" }] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "url", "equals": "a", "contains": "b" }] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "exists", "selector": "a", "contains": "b" }] })).is_err());
+        assert!(prepare_assert_page(&serde_json::json!({ "assertions": [{ "type": "cookie" }] })).is_err());
+    }
+
+    #[test]
+    fn test_profiling_start_arguments() {
+        let params = prepare_profiling_start(&serde_json::json!({})).unwrap();
+        assert_eq!((params["js"].as_bool(), params["css"].as_bool(), params["trace"].as_bool()), (Some(true), Some(true), Some(true)));
+        assert_eq!(params["categories"], serde_json::json!(profiling::DEFAULT_TRACE_CATEGORIES));
+
+        let params = prepare_profiling_start(&serde_json::json!({ "coverage": ["css"], "trace": false })).unwrap();
+        assert_eq!(params, serde_json::json!({ "js": false, "css": true, "trace": false }));
+
+        assert!(prepare_profiling_start(&serde_json::json!({ "coverage": [], "trace": false })).is_err());
+        assert!(prepare_profiling_start(&serde_json::json!({ "coverage": ["html"] })).is_err());
+        assert!(prepare_profiling_start(&serde_json::json!({ "trace": false, "categories": ["loading"] })).is_err());
+        assert!(prepare_profiling_start(&serde_json::json!({ "categories": [] })).is_err());
+    }
+
+    #[test]
+    fn test_screenshot_diff_arguments() {
+        let (name, tolerance, max_ratio) = prepare_screenshot_diff(&serde_json::json!({ "baseline": "home" })).unwrap();
+        assert_eq!(name, "home");
+        assert_eq!(tolerance, baselines::DEFAULT_TOLERANCE);
+        assert_eq!(max_ratio, baselines::DEFAULT_MAX_DIFF_RATIO);
+
+        let (_, tolerance, max_ratio) =
+            prepare_screenshot_diff(&serde_json::json!({ "baseline": "home", "tolerance": 0, "maxDiffRatio": 0.05 })).unwrap();
+        assert_eq!((tolerance, max_ratio), (0, 0.05));
+
+        assert!(prepare_screenshot_diff(&serde_json::json!({})).is_err());
+        assert!(prepare_screenshot_diff(&serde_json::json!({ "baseline": "../home" })).is_err());
+        assert!(prepare_screenshot_diff(&serde_json::json!({ "baseline": "home", "tolerance": 256 })).is_err());
+        assert!(prepare_screenshot_diff(&serde_json::json!({ "baseline": "home", "maxDiffRatio": 2 })).is_err());
+    }
+
+    #[test]
+    fn test_get_text_arguments() {
+        let params = prepare_get_text(&serde_json::json!({})).unwrap();
+        assert_eq!(params["selector"], serde_json::Value::Null);
+        assert_eq!(params["all"], false);
+        assert_eq!(params["property"], "innerText");
+        assert_eq!(params["maxLength"], DEFAULT_TEXT_LENGTH);
+
+        let params = prepare_get_text(&serde_json::json!({ "selector": ".price", "all": true, "property": "textContent", "limit": 5 })).unwrap();
+        assert_eq!(params["selector"], ".price");
+        assert_eq!(params["limit"], 5);
+
+        assert!(prepare_get_text(&serde_json::json!({ "selector": " " })).is_err());
+        assert!(prepare_get_text(&serde_json::json!({ "property": "outerHTML" })).is_err());
+        assert!(prepare_get_text(&serde_json::json!({ "maxLength": 0 })).is_err());
+        assert!(prepare_get_text(&serde_json::json!({ "limit": 5000 })).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_arguments() {
+        let params = prepare_evaluate(&serde_json::json!({
+            "expression": " (sel) => document.querySelectorAll(sel).length ",
+            "args": ["a"],
+            "frameId": 3
+        }))
+        .unwrap();
+        assert_eq!(params["expression"], "(sel) => document.querySelectorAll(sel).length");
+        assert_eq!(params["args"], serde_json::json!(["a"]));
+        assert_eq!(params["frameId"], 3);
+        assert_eq!(params["awaitPromise"], true);
+
+        assert_eq!(prepare_evaluate(&serde_json::json!({ "expression": "1" })).unwrap()["args"], serde_json::json!([]));
+        assert!(prepare_evaluate(&serde_json::json!({ "expression": "  " })).is_err());
+        assert!(prepare_evaluate(&serde_json::json!({ "expression": "f", "args": "a" })).is_err());
+        assert!(prepare_evaluate(&serde_json::json!({ "expression": "f", "frameId": -1 })).is_err());
+    }
+
+    #[test]
+    fn test_set_geolocation_arguments() {
+        let params = prepare_set_geolocation(&serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.405,
+            "origin": "https://Shop.example:443/"
+        }))
+        .unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.405,
+                "accuracy": 100.0,
+                "origin": "https://shop.example",
+                "grantPermission": true
+            })
+        );
+        let params = prepare_set_geolocation(&serde_json::json!({ "latitude": 0, "longitude": 0, "origin": "http://localhost:8080" })).unwrap();
+        assert_eq!(params["origin"], "http://localhost:8080");
+        assert_eq!(prepare_set_geolocation(&serde_json::json!({ "reset": true })).unwrap(), serde_json::json!({ "reset": true }));
+
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 91, "longitude": 0 })).is_err());
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 0, "longitude": -181 })).is_err());
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 0 })).is_err());
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 0, "longitude": 0, "accuracy": -1 })).is_err());
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 0, "longitude": 0, "origin": "https://a.example/shop" })).is_err());
+        assert!(prepare_set_geolocation(&serde_json::json!({ "latitude": 0, "longitude": 0, "origin": "file:///tmp" })).is_err());
+    }
+
+    #[test]
+    fn test_dialog_policy_arguments() {
+        let params = prepare_dialog_policy(&serde_json::json!({ "mode": "accept", "promptText": "Ada" })).unwrap();
+        assert_eq!(params["mode"], "accept");
+        assert_eq!(params["promptText"], "Ada");
+        assert!(prepare_dialog_policy(&serde_json::json!({ "mode": "manual" })).unwrap()["promptText"].is_null());
+
+        assert!(prepare_dialog_policy(&serde_json::json!({})).is_err());
+        assert!(prepare_dialog_policy(&serde_json::json!({ "mode": "ignore" })).is_err());
+        assert!(prepare_dialog_policy(&serde_json::json!({ "mode": "dismiss", "promptText": "x" })).is_err());
+        assert!(prepare_dialog_policy(&serde_json::json!({ "mode": "accept", "promptText": 3 })).is_err());
+    }
+
+    #[test]
+    fn test_throttle_params() {
+        let params = prepare_throttle(&serde_json::json!({ "network": "3g", "latencyMs": 300, "cpuRate": 4 })).unwrap();
+        assert_eq!(params["network"]["latencyMs"], 300);
+        assert_eq!(params["network"]["downloadThroughput"], 1440 * 125);
+        assert_eq!(params["network"]["offline"], false);
+        assert_eq!(params["cpuRate"], 4.0);
+
+        let params = prepare_throttle(&serde_json::json!({ "downloadKbps": 800 })).unwrap();
+        assert_eq!(params["network"]["downloadThroughput"], 100_000);
+        assert_eq!(params["network"]["uploadThroughput"], -1);
+        assert!(params["cpuRate"].is_null());
+
+        assert_eq!(prepare_throttle(&serde_json::json!({ "network": "offline" })).unwrap()["network"]["offline"], true);
+        assert_eq!(prepare_throttle(&serde_json::json!({ "reset": true })).unwrap()["cpuRate"], 1);
+        assert!(prepare_throttle(&serde_json::json!({ "network": "5g" })).is_err());
+        assert!(prepare_throttle(&serde_json::json!({ "network": "offline", "latencyMs": 100 })).is_err());
+        assert!(prepare_throttle(&serde_json::json!({ "cpuRate": 0.5 })).is_err());
+        assert!(prepare_throttle(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_canvas_read_pixels_validation() {
+        let params = prepare_canvas_read_pixels(&serde_json::json!({
+            "selector": "#chart",
+            "mode": "average",
+            "grid": 4
+        }))
+        .unwrap();
+        assert_eq!(params["x"], 0);
+        assert_eq!(params["width"], serde_json::Value::Null);
+
+        assert!(prepare_canvas_read_pixels(&serde_json::json!({ "selector": "c", "width": 0 })).is_err());
+        assert!(prepare_canvas_read_pixels(&serde_json::json!({ "selector": "c", "grid": 32 })).is_err());
+        assert!(prepare_canvas_read_pixels(&serde_json::json!({
+            "selector": "c",
+            "width": 10000,
+            "height": 10000
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_media_argument_validation() {
+        assert!(validate_media_arguments("media_control", &serde_json::json!({ "action": "play" })).is_ok());
+        assert!(validate_media_arguments("media_control", &serde_json::json!({ "action": "seek" })).is_err());
+        assert!(validate_media_arguments("media_control", &serde_json::json!({ "action": "rewind" })).is_err());
+        assert!(validate_media_arguments("media_capture_frame", &serde_json::json!({ "time": -1 })).is_err());
+    }
+
+    #[test]
+    fn test_load_wait_arguments() {
+        let timeout = Duration::from_secs(30);
+        let params = prepare_wait_for_load(&serde_json::json!({}), timeout).unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({ "state": "load", "expectNavigation": false, "timeoutMs": 20000, "idleMs": 500, "maxInflight": 0 })
+        );
+        let params = prepare_wait_for_load(&serde_json::json!({ "state": "networkidle", "idleMs": 1000, "maxInflight": 2 }), timeout).unwrap();
+        assert_eq!((params["idleMs"].as_u64(), params["maxInflight"].as_u64()), (Some(1000), Some(2)));
+        // The default shrinks to fit a short command timeout
+        let params = prepare_wait_for_load(&serde_json::json!({}), Duration::from_secs(10)).unwrap();
+        assert_eq!(params["timeoutMs"], 8000);
+
+        assert!(prepare_wait_for_load(&serde_json::json!({ "state": "idle" }), timeout).is_err());
+        assert!(prepare_wait_for_load(&serde_json::json!({ "timeoutMs": 29000 }), timeout).is_err());
+        assert!(prepare_wait_for_load(&serde_json::json!({ "idleMs": 60000 }), timeout).is_err());
+        assert!(prepare_wait_for_load(&serde_json::json!({ "maxInflight": 11 }), timeout).is_err());
+
+        let params = prepare_navigate(&serde_json::json!({ "url": "https://a", "waitUntil": "domcontentloaded" }), timeout).unwrap();
+        assert_eq!(params["waitUntil"], "domcontentloaded");
+        assert_eq!(params["timeoutMs"], 20000);
+        let params = prepare_navigate(&serde_json::json!({ "url": "https://a", "bypassServiceWorker": true }), timeout).unwrap();
+        assert_eq!(params, serde_json::json!({ "url": "https://a", "bypassServiceWorker": true }));
+        assert!(prepare_navigate(&serde_json::json!({ "url": "https://a", "waitUntil": "ready" }), timeout).is_err());
+        assert!(prepare_navigate(&serde_json::json!({}), timeout).is_err());
+    }
+
+    #[test]
+    fn test_scroll_into_view_options() {
+        let params = prepare_scroll_into_view(&serde_json::json!({
+            "selector": "#buy",
+            "offsetTop": 64
+        }))
+        .unwrap();
+        assert_eq!(params["block"], "center");
+        assert_eq!(params["detectSticky"], true);
+        assert_eq!(params["offsetTop"], 64.0);
+        assert_eq!(params["offsetBottom"], serde_json::Value::Null);
+
+        assert!(prepare_scroll_into_view(&serde_json::json!({ "selector": "#a", "offsetTop": -5 })).is_err());
+        assert!(prepare_scroll_into_view(&serde_json::json!({ "selector": "#a", "block": "top" })).is_err());
+        assert!(prepare_scroll_into_view(&serde_json::json!({})).is_err());
+        let params = prepare_scroll_into_view(&serde_json::json!({ "locator": { "role": "button" } })).unwrap();
+        assert_eq!(params["selector"], serde_json::Value::Null);
+    }
+
     #[test]
     fn test_uuid_generation() {
         let id1 = Uuid::new_v4().to_string();
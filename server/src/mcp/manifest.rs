@@ -0,0 +1,151 @@
+//! Tool manifest diffs
+//!
+//! Compares the live tool registry against a saved manifest (a `tools/list`
+//! result or a bare array of tools) so client maintainers can detect schema
+//! changes between server versions. Removed tools, removed or retyped
+//! properties and newly required arguments are flagged as breaking.
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tools keyed by name from a `{"tools": [...]}` object or a bare array
+pub fn tools_by_name(manifest: &Value) -> Result<BTreeMap<String, Value>, String> {
+    let tools = manifest
+        .get("tools")
+        .unwrap_or(manifest)
+        .as_array()
+        .ok_or("Manifest must be a tools/list result or an array of tools")?;
+    tools
+        .iter()
+        .map(|tool| {
+            let name = tool
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("Manifest tool without a name")?;
+            Ok((name.to_string(), tool.clone()))
+        })
+        .collect()
+}
+
+fn properties(tool: &Value) -> Map<String, Value> {
+    tool.pointer("/inputSchema/properties")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn required(tool: &Value) -> BTreeSet<String> {
+    tool.pointer("/inputSchema/required")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Differences in one tool present on both sides, or None if identical
+fn diff_tool(name: &str, saved: &Value, live: &Value) -> Option<Value> {
+    if saved == live {
+        return None;
+    }
+
+    let (old_props, new_props) = (properties(saved), properties(live));
+    let added: Vec<&String> = new_props.keys().filter(|k| !old_props.contains_key(*k)).collect();
+    let removed: Vec<&String> = old_props.keys().filter(|k| !new_props.contains_key(*k)).collect();
+    let retyped: Vec<Value> = old_props
+        .iter()
+        .filter_map(|(key, old)| {
+            let new = new_props.get(key)?;
+            (old.get("type") != new.get("type")).then(|| {
+                serde_json::json!({ "property": key, "from": old.get("type"), "to": new.get("type") })
+            })
+        })
+        .collect();
+
+    let (old_required, new_required) = (required(saved), required(live));
+    let newly_required: Vec<&String> = new_required.difference(&old_required).collect();
+    let no_longer_required: Vec<&String> = old_required.difference(&new_required).collect();
+
+    let breaking = !removed.is_empty() || !retyped.is_empty() || !newly_required.is_empty();
+    Some(serde_json::json!({
+        "name": name,
+        "breaking": breaking,
+        "descriptionChanged": saved.get("description") != live.get("description"),
+        "addedProperties": added,
+        "removedProperties": removed,
+        "retypedProperties": retyped,
+        "newlyRequired": newly_required,
+        "noLongerRequired": no_longer_required,
+    }))
+}
+
+/// Structured diff of a saved manifest against the live tools
+pub fn diff(saved: &BTreeMap<String, Value>, live: &BTreeMap<String, Value>) -> Value {
+    let added: Vec<&String> = live.keys().filter(|k| !saved.contains_key(*k)).collect();
+    let removed: Vec<&String> = saved.keys().filter(|k| !live.contains_key(*k)).collect();
+    let changed: Vec<Value> = saved
+        .iter()
+        .filter_map(|(name, tool)| diff_tool(name, tool, live.get(name)?))
+        .collect();
+
+    let breaking = !removed.is_empty() || changed.iter().any(|c| c["breaking"] == true);
+    serde_json::json!({
+        "breaking": breaking,
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+        "unchanged": saved.len() - removed.len() - changed.len(),
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, props: Value, required: Value) -> Value {
+        serde_json::json!({
+            "name": name,
+            "description": name,
+            "inputSchema": { "type": "object", "properties": props, "required": required }
+        })
+    }
+
+    #[test]
+    fn test_diff_flags_breaking_changes() {
+        let saved = tools_by_name(&serde_json::json!({ "tools": [
+            tool("navigate", serde_json::json!({ "url": { "type": "string" } }), serde_json::json!(["url"])),
+            tool("click", serde_json::json!({ "selector": { "type": "string" }, "x": { "type": "number" } }), serde_json::json!([])),
+            tool("gone", serde_json::json!({}), serde_json::json!([])),
+        ]}))
+        .unwrap();
+        let live = tools_by_name(&serde_json::json!([
+            tool("navigate", serde_json::json!({ "url": { "type": "string" } }), serde_json::json!(["url"])),
+            tool("click", serde_json::json!({ "selector": { "type": "string" }, "x": { "type": "string" }, "force": { "type": "boolean" } }), serde_json::json!(["selector"])),
+            tool("fresh", serde_json::json!({}), serde_json::json!([])),
+        ]))
+        .unwrap();
+
+        let report = diff(&saved, &live);
+        assert_eq!(report["breaking"], true);
+        assert_eq!(report["added"], serde_json::json!(["fresh"]));
+        assert_eq!(report["removed"], serde_json::json!(["gone"]));
+        assert_eq!(report["unchanged"], 1);
+
+        let click = &report["changed"][0];
+        assert_eq!(click["name"], "click");
+        assert_eq!(click["addedProperties"], serde_json::json!(["force"]));
+        assert_eq!(click["retypedProperties"][0]["property"], "x");
+        assert_eq!(click["newlyRequired"], serde_json::json!(["selector"]));
+    }
+
+    #[test]
+    fn test_identical_manifest_has_no_changes() {
+        let live = tools_by_name(&Value::Array(crate::mcp::tools::builtin_tools())).unwrap();
+        let report = diff(&live, &live);
+        assert_eq!(report["breaking"], false);
+        assert_eq!(report["changed"], serde_json::json!([]));
+        assert!(tools_by_name(&serde_json::json!({ "tools": 5 })).is_err());
+    }
+}
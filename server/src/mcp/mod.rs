@@ -1,5 +1,9 @@
 //! MCP JSON-RPC types
 
+pub mod manifest;
+pub mod protocol;
+pub mod tools;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -59,3 +63,89 @@ impl JsonRpcRes {
         }
     }
 }
+
+/// Server-initiated JSON-RPC notification (no id, no response expected)
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: &'static str,
+    pub method: String,
+    pub params: Value,
+    /// Only delivered to this MCP session when set (e.g. progress for its own request)
+    #[serde(skip)]
+    pub session: Option<String>,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: impl Into<String>, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            method: method.into(),
+            params,
+            session: None,
+        }
+    }
+
+    pub fn for_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    pub fn is_visible_to(&self, session: &str) -> bool {
+        self.session.as_deref().is_none_or(|target| target == session)
+    }
+}
+
+/// One line/frame from a client: a single request, a JSON-RPC 2.0 batch, or a
+/// response to a server-initiated request (e.g. sampling/createMessage)
+#[derive(Debug)]
+pub enum JsonRpcIncoming {
+    Single(JsonRpcReq),
+    /// Members are parsed individually so one malformed entry doesn't fail the batch
+    Batch(Vec<Value>),
+    Response(Value),
+}
+
+/// Whether a message is a response (result/error with an id) rather than a request
+pub fn is_response(value: &Value) -> bool {
+    value.get("method").is_none()
+        && value.get("id").is_some()
+        && (value.get("result").is_some() || value.get("error").is_some())
+}
+
+pub fn parse_message(text: &str) -> Result<JsonRpcIncoming, serde_json::Error> {
+    match serde_json::from_str::<Value>(text)? {
+        Value::Array(items) => Ok(JsonRpcIncoming::Batch(items)),
+        value if is_response(&value) => Ok(JsonRpcIncoming::Response(value)),
+        value => Ok(JsonRpcIncoming::Single(serde_json::from_value(value)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_and_batch() {
+        let single = parse_message(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#).unwrap();
+        assert!(matches!(single, JsonRpcIncoming::Single(req) if req.method == "ping"));
+
+        let batch = parse_message(r#" [{"jsonrpc":"2.0","id":1,"method":"initialize"}, 5]"#).unwrap();
+        assert!(matches!(batch, JsonRpcIncoming::Batch(items) if items.len() == 2));
+
+        let response = parse_message(r#"{"jsonrpc":"2.0","id":"s-1","result":{}}"#).unwrap();
+        assert!(matches!(response, JsonRpcIncoming::Response(_)));
+
+        assert!(parse_message("[{").is_err());
+    }
+
+    #[test]
+    fn test_session_scoped_notification() {
+        let broadcast = JsonRpcNotification::new("notifications/tools/list_changed", serde_json::json!({}));
+        assert!(broadcast.is_visible_to("tcp:a"));
+
+        let progress = JsonRpcNotification::new("notifications/progress", serde_json::json!({})).for_session("tcp:a");
+        assert!(progress.is_visible_to("tcp:a"));
+        assert!(!progress.is_visible_to("tcp:b"));
+        assert!(!serde_json::to_string(&progress).unwrap().contains("tcp:a"));
+    }
+}
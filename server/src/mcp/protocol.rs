@@ -0,0 +1,106 @@
+//! MCP protocol version negotiation
+//!
+//! `initialize` answers with the highest version both sides support. The
+//! negotiated version is remembered per session so features introduced by
+//! newer revisions are only used with clients that asked for them.
+
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Supported protocol revisions, newest first
+pub const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Assumed for sessions that never sent `initialize`
+pub const DEFAULT_VERSION: &str = "2024-11-05";
+
+/// Revision that introduced the Streamable HTTP transport
+const STREAMABLE_HTTP_SINCE: &str = "2025-03-26";
+
+/// Revision that introduced `structuredContent` in tool results
+const STRUCTURED_OUTPUT_SINCE: &str = "2025-06-18";
+
+/// Highest supported version not newer than the one requested. Clients
+/// older than every supported revision get the latest, per the spec, and
+/// are expected to disconnect if they can't speak it.
+pub fn negotiate(requested: Option<&str>) -> &'static str {
+    let Some(requested) = requested else {
+        return DEFAULT_VERSION;
+    };
+    // Revisions are ISO dates, so string order is chronological
+    SUPPORTED_VERSIONS
+        .iter()
+        .find(|v| **v <= requested)
+        .copied()
+        .unwrap_or(SUPPORTED_VERSIONS[0])
+}
+
+pub fn is_supported(version: &str) -> bool {
+    SUPPORTED_VERSIONS.contains(&version)
+}
+
+pub fn supports_structured_output(version: &str) -> bool {
+    version >= STRUCTURED_OUTPUT_SINCE
+}
+
+pub fn supports_streamable_http(version: &str) -> bool {
+    version >= STREAMABLE_HTTP_SINCE
+}
+
+/// Negotiated version per MCP session
+#[derive(Default)]
+pub struct NegotiatedVersions {
+    sessions: RwLock<HashMap<String, &'static str>>,
+}
+
+impl NegotiatedVersions {
+    pub async fn set(&self, session: &str, version: &'static str) {
+        self.sessions.write().await.insert(session.to_string(), version);
+    }
+
+    pub async fn get(&self, session: &str) -> &'static str {
+        self.sessions
+            .read()
+            .await
+            .get(session)
+            .copied()
+            .unwrap_or(DEFAULT_VERSION)
+    }
+
+    /// Sessions that initialized and haven't disconnected
+    pub async fn sessions(&self) -> Vec<String> {
+        self.sessions.read().await.keys().cloned().collect()
+    }
+
+    pub async fn forget(&self, session: &str) {
+        self.sessions.write().await.remove(session);
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(Some("2025-06-18")), "2025-06-18");
+        assert_eq!(negotiate(Some("2025-03-26")), "2025-03-26");
+        // Newer than anything we know: answer with our latest
+        assert_eq!(negotiate(Some("2026-01-01")), "2025-06-18");
+        // Between revisions: the older one both sides understand
+        assert_eq!(negotiate(Some("2025-05-01")), "2025-03-26");
+        assert_eq!(negotiate(Some("2024-01-01")), "2025-06-18");
+        assert_eq!(negotiate(None), DEFAULT_VERSION);
+    }
+
+    #[test]
+    fn test_feature_gates() {
+        assert!(!supports_structured_output("2025-03-26"));
+        assert!(supports_structured_output("2025-06-18"));
+        assert!(!supports_streamable_http("2024-11-05"));
+        assert!(supports_streamable_http("2025-03-26"));
+    }
+}
@@ -0,0 +1,2782 @@
+//! Built-in MCP tool definitions served by tools/list
+
+use serde_json::{json, Value};
+
+/// Tools the server answers on its own, so they stay listed while no extension is connected
+const EXTENSION_INDEPENDENT_TOOLS: &[&str] = &[
+    "passkey_authorize",
+    "passkey_authorization_status",
+    "passkey_grant_create",
+    "passkey_grant_redeem",
+    "recovery_secret_store",
+    "recovery_secret_list",
+    "recovery_authorize",
+    "recovery_secret_get",
+    "recovery_secret_delete",
+    "state_set",
+    "state_get",
+    "state_delete",
+    "data_transform",
+    "snapshot_get",
+    "baseline_list",
+    "baseline_delete",
+    "watch_add",
+    "watch_list",
+    "watch_remove",
+    "network_list_websockets",
+    "browser_list_dialogs",
+    "network_capture_configure",
+    "network_get_request",
+    "network_list_requests",
+    "browser_follow_popups",
+    "preflight",
+    "capabilities_diff",
+    "session_artifacts_list",
+    "artifacts_search",
+    "session_report",
+    "history_retry",
+    "session_list",
+    "session_observe",
+    "session_inject",
+    "storage_status",
+    "history_stats",
+    "alerts_test",
+    "server_metrics",
+    "telemetry_status",
+];
+
+/// Tools that only make sense while passkey automation is enabled
+const PASSKEY_AUTOMATION_TOOLS: &[&str] = &["passkey_list", "passkey_clear"];
+
+/// Tools forwarded as-is to an extension command, as (tool, command). An
+/// extension that sends `register_tools` replaces this set with its own.
+pub const EXTENSION_COMMANDS: &[(&str, &str)] = &[
+    ("playwright_navigate", "navigate"),
+    ("playwright_go_back", "go_back"),
+    ("playwright_go_forward", "go_forward"),
+    ("playwright_reload", "reload"),
+    ("browser_wait_for_load", "wait_for_load"),
+    ("browser_page_info", "page_info"),
+    ("browser_bring_to_front", "bring_to_front"),
+    ("playwright_click", "click"),
+    ("playwright_dblclick", "click"),
+    ("playwright_context_click", "click"),
+    ("scroll_into_view", "scroll_into_view"),
+    ("playwright_fill", "type"),
+    ("playwright_select", "select"),
+    ("playwright_fill_form", "fill_form"),
+    ("browser_list_forms", "list_forms"),
+    ("playwright_press_key", "press_key"),
+    ("playwright_get_text", "get_text"),
+    ("playwright_get_html", "get_html"),
+    ("browser_extract_content", "extract_content"),
+    ("browser_find_text", "find_text"),
+    ("browser_collect_items", "collect_items"),
+    ("assert_page", "assert_page"),
+    ("browser_accessibility_tree", "accessibility_snapshot"),
+    ("type_text", "type_text"),
+    ("set_date", "set_date"),
+    ("browser_export_pdf", "print_to_pdf"),
+    ("browser_emulate_locale", "emulate_locale"),
+    ("browser_set_geolocation", "set_geolocation"),
+    ("browser_throttle", "throttle"),
+    ("canvas_read_pixels", "canvas_read_pixels"),
+    ("media_control", "media_control"),
+    ("media_get_state", "media_get_state"),
+    ("media_capture_frame", "media_capture_frame"),
+    ("playwright_focus", "focus"),
+    ("playwright_blur", "blur"),
+    ("playwright_get_focused_element", "get_focused_element"),
+    ("playwright_get_element", "get_element"),
+    ("playwright_evaluate", "evaluate"),
+    ("playwright_screenshot", "screenshot"),
+    ("playwright_detect_modal", "detect_modal"),
+    ("playwright_dismiss_modal", "dismiss_modal"),
+    ("browser_handle_dialogs", "dialog_policy"),
+    ("browser_respond_dialog", "dialog_respond"),
+    ("browser_list_frames", "list_frames"),
+    ("browser_list_service_workers", "list_service_workers"),
+    ("browser_unregister_service_workers", "unregister_service_workers"),
+    ("browser_clear_site_data", "clear_site_data"),
+    ("browser_get_cookies", "cookies_get"),
+    ("browser_set_cookie", "cookies_set"),
+    ("browser_clear_cookies", "cookies_clear"),
+    ("browser_list_downloads", "downloads_list"),
+    ("browser_profiling_start", "profiling_start"),
+    ("passkey_enable", "passkey_enable"),
+    ("passkey_status", "passkey_status"),
+    ("passkey_list", "passkey_list"),
+    ("passkey_clear", "passkey_clear"),
+];
+
+/// Selector-based tools that accept `frame` to run inside an iframe
+pub const FRAME_TOOLS: &[&str] = &[
+    "playwright_click",
+    "playwright_dblclick",
+    "playwright_context_click",
+    "scroll_into_view",
+    "playwright_fill",
+    "playwright_select",
+    "playwright_fill_form",
+    "playwright_press_key",
+    "playwright_get_text",
+    "playwright_get_html",
+    "playwright_get_element",
+    "playwright_screenshot",
+    "browser_find_text",
+    "browser_collect_items",
+    "browser_list_forms",
+];
+
+/// Schema of the `frame` argument shared by FRAME_TOOLS
+fn frame_schema() -> Value {
+    json!({
+        "oneOf": [
+            { "type": "integer", "minimum": 0 },
+            { "type": "string" },
+            {
+                "type": "object",
+                "properties": {
+                    "index": { "type": "integer", "minimum": 0 },
+                    "id": { "type": "integer", "minimum": 1 },
+                    "name": { "type": "string" },
+                    "url": { "type": "string" }
+                }
+            }
+        ],
+        "description": "Run inside an iframe instead of the top page: an index among the page's iframes in document order, a frame name or URL substring, or { id } from browser_list_frames"
+    })
+}
+
+/// Selector-based tools that accept `locator` in place of `selector`
+pub const LOCATOR_TOOLS: &[&str] = &[
+    "playwright_click",
+    "playwright_dblclick",
+    "playwright_context_click",
+    "scroll_into_view",
+    "playwright_fill",
+    "playwright_select",
+    "playwright_press_key",
+    "playwright_get_text",
+    "playwright_get_html",
+    "playwright_get_element",
+];
+
+/// Schema of the Playwright-style `locator` shared by LOCATOR_TOOLS and fill_form fields
+fn locator_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "role": {
+                "type": "string",
+                "description": "ARIA role, explicit or implied by the tag, e.g. button, link, textbox, checkbox, combobox, heading"
+            },
+            "name": {
+                "type": "string",
+                "description": "Accessible name the role match must have (with role only)"
+            },
+            "text": {
+                "type": "string",
+                "description": "Visible text; matches the innermost element containing it"
+            },
+            "label": {
+                "type": "string",
+                "description": "Text of the form control's <label>, aria-label or aria-labelledby"
+            },
+            "placeholder": {
+                "type": "string",
+                "description": "Placeholder of an input or textarea"
+            },
+            "exact": {
+                "type": "boolean",
+                "description": "Match the whole text case-sensitively instead of a case-insensitive substring (default: false)"
+            },
+            "nth": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Which match to use when several match (default: 0, the first in document order)"
+            }
+        },
+        "description": "Find the element like Playwright's getByRole/getByText/getByLabel/getByPlaceholder instead of by CSS: exactly one of role, text, label or placeholder. Use instead of selector"
+    })
+}
+
+/// Extension commands that server-implemented tools send, with the minimum
+/// command version each needs (see `capabilities` in extension_tools.rs)
+pub const SERVER_TOOL_COMMANDS: &[(&str, &[(&str, u32)])] = &[
+    ("audit_accessibility", &[("audit_accessibility", 1)]),
+    ("audit_seo", &[("audit_seo", 1)]),
+    ("check_links", &[("list_links", 1)]),
+    // Version 2 screenshots report the viewport baselines are keyed by
+    ("baseline_set", &[("screenshot", 2)]),
+    ("screenshot_diff", &[("screenshot", 2), ("image_diff", 1)]),
+    ("browser_wait_for_download", &[("downloads_list", 1)]),
+    ("browser_profiling_stop", &[("profiling_stop", 1)]),
+    ("browser_screenshot_elements", &[("screenshot_elements", 1)]),
+    ("browser_extract_table", &[("extract_table", 1)]),
+    ("browser_recording_start", &[("active_tab", 1), ("screencast_start", 1)]),
+    ("browser_recording_stop", &[("screencast_stop", 1)]),
+    ("network_list_requests", &[("active_tab", 1)]),
+    ("dismiss_consent", &[("dismiss_consent", 1)]),
+    ("explore_menu", &[("hover", 1), ("wait_for_selector", 1), ("query_elements", 1)]),
+];
+
+/// Extension commands a server-implemented tool relies on
+pub fn server_tool_commands(tool: &str) -> &'static [(&'static str, u32)] {
+    SERVER_TOOL_COMMANDS
+        .iter()
+        .find(|(name, _)| *name == tool)
+        .map(|(_, commands)| *commands)
+        .unwrap_or_default()
+}
+
+/// Extension command a built-in forwarded tool maps to
+pub fn extension_command(tool: &str) -> Option<&'static str> {
+    EXTENSION_COMMANDS.iter().find(|(name, _)| *name == tool).map(|(_, command)| *command)
+}
+
+/// Tool definitions handled by the server or forwarded to the extension
+pub fn builtin_tools() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "playwright_navigate",
+            "description": "Navigate to a URL in the browser",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "The URL to navigate to"
+                    },
+                    "bypassServiceWorker": {
+                        "type": "boolean",
+                        "description": "Bypass registered service workers for this navigation so the network response is used instead of a cached app shell (default: false)"
+                    },
+                    "waitUntil": {
+                        "type": "string",
+                        "enum": ["domcontentloaded", "load", "networkidle"],
+                        "description": "Block until the new page reaches this state: DOMContentLoaded fired, load fired, or load plus 500 ms without network requests (default: don't wait)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait for waitUntil (default: 20000)"
+                    }
+                },
+                "required": ["url"]
+            }
+        }),
+        json!({
+            "name": "browser_page_info",
+            "description": "Answer \"where am I?\" in one cheap call: the current URL, title, ready state, scroll position, viewport and document size",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "browser_bring_to_front",
+            "description": "Make the automated tab the active tab of a focused, unminimized window and report whether the page is now visible. Background tabs throttle timers and pause animations, media and requestAnimationFrame, which silently stalls some pages",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "focusWindow": {
+                        "type": "boolean",
+                        "description": "Also focus the tab's window above other windows (default: true)"
+                    },
+                    "checkOnly": {
+                        "type": "boolean",
+                        "description": "Only report whether the tab is in the foreground, without changing anything (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_wait_for_load",
+            "description": "Wait until the page reaches a load state, instead of sleeping after an action. With expectNavigation, waits for the page the previous action navigated to",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "state": {
+                        "type": "string",
+                        "enum": ["domcontentloaded", "load", "networkidle"],
+                        "description": "domcontentloaded: the HTML is parsed; load: the page and its subresources finished loading; networkidle: load, then idleMs without network requests (default: load)"
+                    },
+                    "expectNavigation": {
+                        "type": "boolean",
+                        "description": "Only accept a document created after the previous command started, e.g. the page a click submitted a form to; without it the current page counts (default: false)"
+                    },
+                    "idleMs": {
+                        "type": "number",
+                        "description": "Quiet period networkidle needs (default: 500)"
+                    },
+                    "maxInflight": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Requests allowed to stay open during the quiet period, for pages with long polls or streams (default: 0)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait (default: 20000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_go_back",
+            "description": "Go back one page in the tab's history and wait for it to load; returns the resulting URL and title",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait for the page to load (default: 20000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_go_forward",
+            "description": "Go forward one page in the tab's history and wait for it to load; returns the resulting URL and title",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait for the page to load (default: 20000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_reload",
+            "description": "Reload the current page and wait for it to load; returns the resulting URL and title",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "bypassCache": {
+                        "type": "boolean",
+                        "description": "Ignore the HTTP cache, like a hard reload (default: false)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait for the page to load (default: 20000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_click",
+            "description": "Click an element on the page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element to click"
+                    },
+                    "button": {
+                        "type": "string",
+                        "enum": ["left", "right", "middle"],
+                        "description": "Mouse button (default: left)"
+                    },
+                    "clickCount": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 3,
+                        "description": "Number of clicks; 2 also fires dblclick (default: 1)"
+                    },
+                    "avoidStickyChrome": {
+                        "type": "boolean",
+                        "description": "Scroll the element clear of fixed/sticky headers and footers before clicking (default: false)"
+                    },
+                    "captureReceipt": {
+                        "type": "boolean",
+                        "description": "After the click, save a screenshot, the final URL, and confirmation text to the session's receipts as evidence of the submission (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_dblclick",
+            "description": "Double-click an element, e.g. to edit a cell or select a word",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element to double-click"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_context_click",
+            "description": "Right-click an element to open its context menu",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element to right-click"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "scroll_into_view",
+            "description": "Scroll an element into view, compensating for fixed/sticky headers and footers so it is not left underneath them. Reports whether the element ended up visible, and why not if it didn't",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element"
+                    },
+                    "block": {
+                        "type": "string",
+                        "enum": ["start", "center", "end", "nearest"],
+                        "description": "Vertical alignment within the unobscured viewport (default: center)"
+                    },
+                    "stickyCompensation": {
+                        "type": "string",
+                        "enum": ["auto", "none"],
+                        "description": "auto detects fixed/sticky chrome at the viewport edges, none scrolls like element.scrollIntoView (default: auto)"
+                    },
+                    "offsetTop": {
+                        "type": "number",
+                        "description": "Pixels obscured at the top of the viewport; overrides detection"
+                    },
+                    "offsetBottom": {
+                        "type": "number",
+                        "description": "Pixels obscured at the bottom of the viewport; overrides detection"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_fill",
+            "description": "Fill out an input field",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the input element"
+                    },
+                    "value": {
+                        "type": "string",
+                        "description": "The text to type into the input"
+                    },
+                    "captureReceipt": {
+                        "type": "boolean",
+                        "description": "After filling, save a screenshot, the final URL, and confirmation text to the session's receipts (the typed value is not recorded; default: false)"
+                    }
+                },
+                "required": ["value"]
+            }
+        }),
+        json!({
+            "name": "playwright_select",
+            "description": "Choose options in a <select> dropdown by value, visible label, or index (playwright_fill does not work on selects). Pass a list to choose several options in a multi-select; the previous selection is replaced",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the <select> element"
+                    },
+                    "value": {
+                        "oneOf": [
+                            { "type": "string" },
+                            { "type": "array", "items": { "type": "string" } }
+                        ],
+                        "description": "Option value attribute(s) to select"
+                    },
+                    "label": {
+                        "oneOf": [
+                            { "type": "string" },
+                            { "type": "array", "items": { "type": "string" } }
+                        ],
+                        "description": "Visible option text(s) to select"
+                    },
+                    "index": {
+                        "oneOf": [
+                            { "type": "number" },
+                            { "type": "array", "items": { "type": "number" } }
+                        ],
+                        "description": "Zero-based option position(s) to select"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_list_forms",
+            "description": "List the forms on the page with their fields and submit buttons, to plan a playwright_fill_form call instead of guessing selectors. Each field has a selector addressing only it, its fillType for playwright_fill_form, label, name, required flag, current value (never passwords), and the options of selects and radio groups. Fields outside any <form> are listed as one extra form marked formless",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "Only list forms inside this CSS selector, or just this form (default: the whole page)"
+                    },
+                    "includeHidden": {
+                        "type": "boolean",
+                        "description": "Also list hidden inputs and fields that aren't displayed, each with a visible flag (default: false)"
+                    },
+                    "maxOptions": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 1000,
+                        "description": "Options listed per select; optionCount has the total (default: 50)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_fill_form",
+            "description": "Fill several form fields in one call, in order, and report success per field. Each field's value decides how it is filled unless type is given: a string or number is typed into a text input, textarea or contenteditable (replacing its content) or chooses a <select> option by value or label; true/false sets a checkbox; a string or true picks a radio button (the selector may match any radio in the group); a list chooses options in a multi-select. Fields that fail are reported and the rest are still filled unless stopOnError is set",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "fields": {
+                        "type": "array",
+                        "minItems": 1,
+                        "maxItems": 100,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "selector": {
+                                    "type": "string",
+                                    "description": "CSS selector for the field"
+                                },
+                                "locator": locator_schema(),
+                                "value": {
+                                    "oneOf": [
+                                        { "type": "string" },
+                                        { "type": "number" },
+                                        { "type": "boolean" },
+                                        { "type": "array", "items": { "type": "string" } }
+                                    ],
+                                    "description": "Text to enter, checkbox state, radio value, or option(s) to select"
+                                },
+                                "type": {
+                                    "type": "string",
+                                    "enum": ["auto", "text", "checkbox", "radio", "select"],
+                                    "description": "Expected field type; the call fails for this field if the element is something else (default: auto)"
+                                },
+                                "clear": {
+                                    "type": "boolean",
+                                    "description": "Clear a text field before typing (default: true)"
+                                }
+                            },
+                            "required": ["value"]
+                        }
+                    },
+                    "stopOnError": {
+                        "type": "boolean",
+                        "description": "Stop at the first field that fails (default: false)"
+                    }
+                },
+                "required": ["fields"]
+            }
+        }),
+        json!({
+            "name": "playwright_press_key",
+            "description": "Press a key, optionally focusing an element first: Enter to submit a search box, Tab/Shift+Tab to move focus, Escape to close a popup, arrows to move through menus, lists and selects. Key events go to the focused element, and their usual effect (submit, focus change, toggle, scroll) is applied unless the page cancels it",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "key": {
+                        "type": "string",
+                        "description": "Enter, Tab, Escape, Backspace, Delete, Space, ArrowUp/Down/Left/Right, Home, End, PageUp, PageDown, F1-F12 or a single character; a combo like \"Shift+Tab\" or \"Control+a\" is also accepted"
+                    },
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of an element to focus before pressing (default: the currently focused element)"
+                    },
+                    "modifiers": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["Alt", "Control", "Meta", "Shift"] },
+                        "description": "Modifier keys held during the press"
+                    },
+                    "count": {
+                        "type": "number",
+                        "description": "Number of times to press the key (default: 1, max: 50)"
+                    }
+                },
+                "required": ["key"]
+            }
+        }),
+        json!({
+            "name": "playwright_get_text",
+            "description": "Read the text of an element (or the whole page body) without a screenshot. Returns the rendered innerText by default, or the raw textContent including hidden nodes; with all: true, returns the text of every match",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector (default: body)"
+                    },
+                    "all": {
+                        "type": "boolean",
+                        "description": "Return every matching element's text instead of the first (default: false)"
+                    },
+                    "property": {
+                        "type": "string",
+                        "enum": ["innerText", "textContent"],
+                        "description": "innerText (as rendered, hidden elements skipped) or textContent (all text nodes) (default: innerText)"
+                    },
+                    "maxLength": {
+                        "type": "number",
+                        "description": "Characters returned per element before truncating (default: 50000, max: 1000000)"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Matches returned with all: true (default: 100, max: 1000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_get_html",
+            "description": "Return the outerHTML of an element, or of the whole document, to inspect page structure and build selectors. Current form values are included (passwords never are); scripts and inline event handlers are stripped by default",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the element (default: the whole document)"
+                    },
+                    "stripScripts": {
+                        "type": "boolean",
+                        "description": "Remove <script>, <noscript>, <template>, on* attributes and javascript: links (default: true)"
+                    },
+                    "stripStyles": {
+                        "type": "boolean",
+                        "description": "Remove <style>, stylesheet links and style attributes (default: false)"
+                    },
+                    "stripComments": {
+                        "type": "boolean",
+                        "description": "Remove HTML comments (default: true)"
+                    },
+                    "maxLength": {
+                        "type": "number",
+                        "description": "Characters returned before truncating (default: 200000, max: 5000000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_extract_content",
+            "description": "Extract the page's main content as clean Markdown (title, byline, body, links), leaving out navigation, ads and other boilerplate. Far smaller than the HTML or a screenshot; use it to read articles, docs and search results",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the element to convert, skipping main-content detection"
+                    },
+                    "maxLength": {
+                        "type": "number",
+                        "description": "Characters of Markdown returned before truncating at a paragraph break (default: 20000, max: 1000000)"
+                    },
+                    "includeLinks": {
+                        "type": "boolean",
+                        "description": "Render links inline as [text](url); they are listed in links either way (default: true)"
+                    },
+                    "includeImages": {
+                        "type": "boolean",
+                        "description": "Render images as ![alt](src) (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_find_text",
+            "description": "Find a string or regular expression in the page's visible text, like Ctrl+F: returns the match count and each match with surrounding context and its element, and can scroll to and highlight one match. Use it to locate a phrase instead of reading the whole page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Text to find, or a JavaScript regular expression with regex"
+                    },
+                    "regex": {
+                        "type": "boolean",
+                        "description": "Treat query as a regular expression (default: false)"
+                    },
+                    "caseSensitive": {
+                        "type": "boolean",
+                        "description": "Match case exactly (default: false)"
+                    },
+                    "wholeWord": {
+                        "type": "boolean",
+                        "description": "Only match at word boundaries (default: false)"
+                    },
+                    "selector": {
+                        "type": "string",
+                        "description": "Only search inside this CSS selector (default: the whole page)"
+                    },
+                    "contextChars": {
+                        "type": "number",
+                        "description": "Characters of context returned on each side of a match (default: 40, max: 500)"
+                    },
+                    "maxResults": {
+                        "type": "number",
+                        "description": "Maximum matches returned; the count covers all of them (default: 20, max: 500)"
+                    },
+                    "scrollTo": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Zero-based index of the match to scroll into view and mark as current"
+                    },
+                    "highlight": {
+                        "type": "boolean",
+                        "description": "Highlight the matches on the page until the next search (default: true)"
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Only remove the highlights of the previous search"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_collect_items",
+            "description": "Harvest an infinite-scroll feed in one call: scrolls repeatedly, waits for new items to load, and returns every distinct item matching itemSelector until maxItems, maxScrolls or timeoutMs is reached or the feed stops growing. Each item has its text and first link, or the named fields. The page is left scrolled where collection stopped",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "itemSelector": {
+                        "type": "string",
+                        "description": "CSS selector matching each item of the feed, e.g. \"article.post\""
+                    },
+                    "fields": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "Values to read from each item: name -> \"selector\" (text), \"selector@attribute\" or \"@attribute\" (of the item itself), e.g. {\"title\": \"h2\", \"url\": \"a@href\"}. Default: text and link"
+                    },
+                    "container": {
+                        "type": "string",
+                        "description": "CSS selector of the element that scrolls (default: the items' scrolling ancestor, or the page)"
+                    },
+                    "loadMoreSelector": {
+                        "type": "string",
+                        "description": "Button to click when the bottom is reached, for feeds with a \"Load more\" button"
+                    },
+                    "maxItems": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 2000,
+                        "description": "Stop after this many items (default: 100)"
+                    },
+                    "maxScrolls": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 500,
+                        "description": "Stop after this many scrolls (default: 50)"
+                    },
+                    "timeoutMs": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Stop after this long (default: 30000, at most the command timeout less 2 s)"
+                    },
+                    "waitMs": {
+                        "type": "integer",
+                        "minimum": 100,
+                        "maximum": 10000,
+                        "description": "How long to wait for new items after each scroll (default: 1500)"
+                    },
+                    "idleScrolls": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 10,
+                        "description": "Scrolls at the bottom without new items before the feed counts as ended (default: 3)"
+                    },
+                    "maxTextLength": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 5000,
+                        "description": "Longest text returned per value (default: 300)"
+                    }
+                },
+                "required": ["itemSelector"]
+            }
+        }),
+        json!({
+            "name": "browser_extract_table",
+            "description": "Extract an HTML table as structured rows keyed by column header. Cells spanning several rows or columns are repeated into each one, and header rows are detected from <thead> or <th> cells. Picks the table by selector or index, or else the page's largest visible table. saveCsv also writes it as a CSV file to the session's artifact directory",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the table, or of an element containing it"
+                    },
+                    "index": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Zero-based index among the page's visible tables"
+                    },
+                    "headerRows": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 10,
+                        "description": "Number of leading rows that hold column headers; 0 names columns \"Column 1\", ... (default: detected)"
+                    },
+                    "maxRows": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 10000,
+                        "description": "Maximum data rows returned (default: 500)"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["objects", "arrays"],
+                        "description": "Rows as objects keyed by header, or as arrays in column order (default: objects)"
+                    },
+                    "saveCsv": {
+                        "type": "boolean",
+                        "description": "Also save the table as tables/<time>.csv in the session's artifact directory and return csvPath (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "assert_page",
+            "description": "Check a list of assertions against the current page in one call and return a pass/fail report. Types: exists/visible (selector), text (selector, default the whole page), url, and cookie (name; present, or with a matcher on its value). text and url need one of equals, contains or matches (a JavaScript regular expression); not inverts an assertion",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "assertions": {
+                        "type": "array",
+                        "minItems": 1,
+                        "maxItems": 50,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "type": {
+                                    "type": "string",
+                                    "enum": ["exists", "visible", "text", "url", "cookie"]
+                                },
+                                "selector": {
+                                    "type": "string",
+                                    "description": "CSS selector, for exists, visible and text"
+                                },
+                                "name": {
+                                    "type": "string",
+                                    "description": "Cookie name, for cookie"
+                                },
+                                "equals": {
+                                    "type": "string",
+                                    "description": "Expected exact value (text is trimmed)"
+                                },
+                                "contains": {
+                                    "type": "string",
+                                    "description": "Expected substring"
+                                },
+                                "matches": {
+                                    "type": "string",
+                                    "description": "Regular expression the value must match"
+                                },
+                                "ignoreCase": {
+                                    "type": "boolean",
+                                    "description": "Compare case-insensitively (default: false)"
+                                },
+                                "not": {
+                                    "type": "boolean",
+                                    "description": "Pass when the assertion does not hold (default: false)"
+                                },
+                                "label": {
+                                    "type": "string",
+                                    "description": "Name for this assertion in the report"
+                                }
+                            },
+                            "required": ["type"]
+                        }
+                    }
+                },
+                "required": ["assertions"]
+            }
+        }),
+        json!({
+            "name": "browser_accessibility_tree",
+            "description": "Return the page's accessibility tree as JSON: each node's role, accessible name, value, heading level and states (checked, expanded, disabled, focused, ...) plus a CSS selector to act on it. A cheaper, more reliable page representation than screenshots for text-only models",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the subtree to return (default: the whole page)"
+                    },
+                    "interestingOnly": {
+                        "type": "boolean",
+                        "description": "Only landmarks, headings, widgets, lists, tables and images; generic containers and plain text are dropped and their children hoisted (default: true)"
+                    },
+                    "maxDepth": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 100,
+                        "description": "Deepest level of nodes returned (default: 100)"
+                    },
+                    "maxNodes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 10000,
+                        "description": "Nodes returned before the tree is truncated (default: 2000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_focus",
+            "description": "Focus an element, firing focus/focusin events so focus-triggered validation and widgets run",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element to focus"
+                    }
+                },
+                "required": ["selector"]
+            }
+        }),
+        json!({
+            "name": "playwright_blur",
+            "description": "Remove focus from an element (default: the focused element), firing blur/focusout so on-blur validation runs",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the element to blur (default: document.activeElement)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_get_focused_element",
+            "description": "Describe the currently focused element (tag, id, name, type, value, unique selector)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "playwright_get_element",
+            "description": "Describe an element's geometry and state: bounding box (viewport and page coordinates), whether it is visible (and what hides it), enabled, how much of it is in the viewport, and whether a click at its center would reach it or what covers it. Use it to decide whether to scroll first, whether a click can work, or where to crop a screenshot",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "locator": locator_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector; the first match is described and count reports how many matched"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_evaluate",
+            "description": "Run a JavaScript expression or function in the page and return its JSON-serializable result. Disabled unless the server runs with --allow-evaluate or the session is inside a passkey_authorize window",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "expression": {
+                        "type": "string",
+                        "description": "Expression to evaluate, e.g. document.title, or a function such as (sel) => document.querySelectorAll(sel).length, which is called with args"
+                    },
+                    "args": {
+                        "type": "array",
+                        "description": "JSON arguments passed to the function (ignored for plain expressions)"
+                    },
+                    "frameId": {
+                        "type": "number",
+                        "description": "Frame to run in, from browser_list_frames (default: the top frame)"
+                    },
+                    "awaitPromise": {
+                        "type": "boolean",
+                        "description": "Wait for a returned promise and return its value (default: true)"
+                    }
+                },
+                "required": ["expression"]
+            }
+        }),
+        json!({
+            "name": "type_text",
+            "description": "Type into one or more fields with realistic keyboard events (keydown/keypress/input/keyup per character, IME composition events for CJK) instead of setting values directly, for frameworks that ignore programmatic value changes",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the field to type into (single-field form)"
+                    },
+                    "text": {
+                        "type": "string",
+                        "description": "Text to type (single-field form)"
+                    },
+                    "fields": {
+                        "type": "array",
+                        "description": "Fields typed in order within one command",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "selector": { "type": "string" },
+                                "text": { "type": "string" },
+                                "clear": { "type": "boolean" }
+                            },
+                            "required": ["selector", "text"]
+                        }
+                    },
+                    "delayMs": {
+                        "type": "number",
+                        "description": "Delay between keystrokes in milliseconds (default: 50)"
+                    },
+                    "jitterMs": {
+                        "type": "number",
+                        "description": "Random extra delay added to each keystroke, 0..jitterMs (default: 0)"
+                    },
+                    "composition": {
+                        "type": "string",
+                        "enum": ["auto", "always", "never"],
+                        "description": "Emit compositionstart/update/end events: auto enables them for CJK text (default: auto)"
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Clear existing field contents before typing (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "set_date",
+            "description": "Set a date on a native date input or JS date-picker widget, injecting the value first and falling back to navigating the calendar",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the date input or the element that opens the picker"
+                    },
+                    "date": {
+                        "type": "string",
+                        "description": "Date as YYYY-MM-DD, or YYYY-MM-DDTHH:MM for datetime inputs"
+                    },
+                    "strategy": {
+                        "type": "string",
+                        "enum": ["auto", "inject", "widget"],
+                        "description": "inject sets the value and fires input/change events, widget clicks through the calendar, auto tries inject then widget (default: auto)"
+                    }
+                },
+                "required": ["selector", "date"]
+            }
+        }),
+        json!({
+            "name": "browser_emulate_locale",
+            "description": "Override Accept-Language, the JavaScript locale (navigator.language, Intl) and the timezone for this session's tab, to reproduce region-specific prices and formats",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "locale": {
+                        "type": "string",
+                        "description": "BCP 47 locale, e.g. de-DE or ja-JP"
+                    },
+                    "timezone": {
+                        "type": "string",
+                        "description": "IANA timezone, e.g. Europe/Berlin or America/New_York"
+                    },
+                    "acceptLanguage": {
+                        "type": "string",
+                        "description": "Accept-Language header value (default: derived from locale, e.g. de-DE,de;q=0.9)"
+                    },
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Remove all overrides and return to the browser's settings"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_set_geolocation",
+            "description": "Spoof the position navigator.geolocation reports for this session's tab and allow the location permission for the site, to exercise store locators and delivery flows. The override lasts until reset or the tab closes",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "latitude": {
+                        "type": "number",
+                        "minimum": -90,
+                        "maximum": 90,
+                        "description": "Latitude in degrees"
+                    },
+                    "longitude": {
+                        "type": "number",
+                        "minimum": -180,
+                        "maximum": 180,
+                        "description": "Longitude in degrees"
+                    },
+                    "accuracy": {
+                        "type": "number",
+                        "description": "Accuracy radius in meters (default: 100)"
+                    },
+                    "origin": {
+                        "type": "string",
+                        "description": "Origin to allow location access for, e.g. https://shop.example (default: the current page's)"
+                    },
+                    "grantPermission": {
+                        "type": "boolean",
+                        "description": "Allow the location permission for the origin so no prompt appears (default: true)"
+                    },
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Remove the override and the permissions this tool granted"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_throttle",
+            "description": "Emulate a slow network (3G, 4G, offline) and/or CPU slowdown for this session's tab, to measure pages under realistic constraints",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "network": {
+                        "type": "string",
+                        "enum": ["slow-3g", "3g", "4g", "offline", "none"],
+                        "description": "Network profile; none removes network throttling"
+                    },
+                    "latencyMs": {
+                        "type": "number",
+                        "description": "Added round-trip latency in milliseconds (overrides the profile's)"
+                    },
+                    "downloadKbps": {
+                        "type": "number",
+                        "description": "Download bandwidth in kilobits per second (overrides the profile's)"
+                    },
+                    "uploadKbps": {
+                        "type": "number",
+                        "description": "Upload bandwidth in kilobits per second (overrides the profile's)"
+                    },
+                    "cpuRate": {
+                        "type": "number",
+                        "description": "CPU slowdown factor, 1 (none) to 20; 4 approximates a mid-range phone"
+                    },
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Remove all network and CPU throttling"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_screenshot",
+            "description": "Take a screenshot of the current page or a specific element. With highlightSelector, the matched elements are outlined and labeled in the image, to check what an action targeted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "frame": frame_schema(),
+                    "selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector to screenshot a specific element"
+                    },
+                    "fullPage": {
+                        "type": "boolean",
+                        "description": "Whether to take a full page screenshot"
+                    },
+                    "highlightSelector": {
+                        "type": "string",
+                        "description": "CSS selector of elements to outline and label in the screenshot; the overlay is removed after capture"
+                    },
+                    "highlightLabel": {
+                        "type": "string",
+                        "maxLength": 80,
+                        "description": "Label drawn on the highlighted elements, numbered when several match (default: each element's tag, id and classes)"
+                    },
+                    "savePath": {
+                        "type": "string",
+                        "description": "Write the PNG to this file instead of returning it; must be inside the client's declared roots (relative paths use the first root)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_screenshot_elements",
+            "description": "Screenshot several elements in one call, e.g. every product card, instead of one playwright_screenshot per element. Elements visible together share one capture. Returns one PNG per element, or with save, file paths in the session's artifact directory",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selectors": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "minItems": 1,
+                        "maxItems": 50,
+                        "description": "CSS selectors of the elements to capture, in the order images are returned"
+                    },
+                    "all": {
+                        "type": "boolean",
+                        "description": "Capture every element each selector matches rather than the first (default: false)"
+                    },
+                    "maxElements": {
+                        "type": "number",
+                        "description": "Maximum elements captured in total (default: 20, max: 100)"
+                    },
+                    "padding": {
+                        "type": "number",
+                        "description": "Pixels of surrounding page included on each side (default: 0, max: 100)"
+                    },
+                    "save": {
+                        "type": "boolean",
+                        "description": "Write the images to elements/<time>/ in the session's artifact directory and return their paths instead of base64 (default: false)"
+                    }
+                },
+                "required": ["selectors"]
+            }
+        }),
+        json!({
+            "name": "browser_export_pdf",
+            "description": "Export the current page as a PDF, returned as base64 or written to a file inside the client's declared roots",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "landscape": {
+                        "type": "boolean",
+                        "description": "Landscape orientation (default: false)"
+                    },
+                    "printBackground": {
+                        "type": "boolean",
+                        "description": "Include background graphics (default: true)"
+                    },
+                    "scale": {
+                        "type": "number",
+                        "description": "Rendering scale between 0.1 and 2 (default: 1)"
+                    },
+                    "savePath": {
+                        "type": "string",
+                        "description": "Write the PDF to this file; must be inside the client's declared roots (relative paths use the first root)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "explore_menu",
+            "description": "Open a menu by hovering or clicking its trigger, capture the revealed items (text and selectors), and optionally close it again, all in one operation",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "trigger": {
+                        "type": "string",
+                        "description": "CSS selector for the element that opens the menu"
+                    },
+                    "action": {
+                        "type": "string",
+                        "enum": ["hover", "click"],
+                        "description": "How to open the menu (default: hover)"
+                    },
+                    "menuSelector": {
+                        "type": "string",
+                        "description": "CSS selector for the revealed menu container (default: common ARIA menu/listbox roles)"
+                    },
+                    "itemSelector": {
+                        "type": "string",
+                        "description": "CSS selector for items inside the menu (default: menu items, options, links, and buttons)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "How long to wait for the menu to appear (default: 2000)"
+                    },
+                    "restore": {
+                        "type": "boolean",
+                        "description": "Close the menu again after capturing it (default: true)"
+                    }
+                },
+                "required": ["trigger"]
+            }
+        }),
+        json!({
+            "name": "canvas_read_pixels",
+            "description": "Read a region of a canvas (2D or WebGL) as a PNG image or as average colors over a grid, to verify chart or canvas rendering",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the canvas element"
+                    },
+                    "x": {
+                        "type": "number",
+                        "description": "Left edge of the region in canvas pixels (default: 0)"
+                    },
+                    "y": {
+                        "type": "number",
+                        "description": "Top edge of the region in canvas pixels (default: 0)"
+                    },
+                    "width": {
+                        "type": "number",
+                        "description": "Region width (default: rest of the canvas)"
+                    },
+                    "height": {
+                        "type": "number",
+                        "description": "Region height (default: rest of the canvas)"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["image", "average"],
+                        "description": "image returns a base64 PNG, average returns mean RGBA per grid cell (default: image)"
+                    },
+                    "grid": {
+                        "type": "number",
+                        "description": "Cells per side for average mode, 1-16 (default: 1)"
+                    }
+                },
+                "required": ["selector"]
+            }
+        }),
+        json!({
+            "name": "media_control",
+            "description": "Play, pause, seek, mute, or unmute an audio/video element",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the media element (default: first audio/video on the page)"
+                    },
+                    "action": {
+                        "type": "string",
+                        "enum": ["play", "pause", "seek", "mute", "unmute"],
+                        "description": "Operation to perform"
+                    },
+                    "time": {
+                        "type": "number",
+                        "description": "Target position in seconds (required for seek)"
+                    }
+                },
+                "required": ["action"]
+            }
+        }),
+        json!({
+            "name": "media_get_state",
+            "description": "Read playback state of audio/video elements: paused, currentTime, duration, muted, volume, readyState, and errors",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the media element (default: every audio/video on the page)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "media_capture_frame",
+            "description": "Capture the current frame of a video (or its poster image before playback) as a PNG",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector for the video element (default: first video on the page)"
+                    },
+                    "time": {
+                        "type": "number",
+                        "description": "Seek to this position in seconds before capturing"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_follow_popups",
+            "description": "Automatically switch this session to tabs/windows opened by its current tab (OAuth popups, external links) and back when they close; switches are reported in tool results",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "enabled": {
+                        "type": "boolean",
+                        "description": "Turn follow mode on or off for this session"
+                    }
+                },
+                "required": ["enabled"]
+            }
+        }),
+        json!({
+            "name": "browser_list_frames",
+            "description": "List the frame hierarchy of the current tab (frame id, URL, name, parent frame id) to find which frame contains a widget",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "includeAboutBlank": {
+                        "type": "boolean",
+                        "description": "Include about:blank and srcdoc frames (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_list_service_workers",
+            "description": "List service worker registrations for the current origin (scope, script URL, state)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "browser_unregister_service_workers",
+            "description": "Unregister service workers for the current origin so the next load fetches a fresh app shell",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "scope": {
+                        "type": "string",
+                        "description": "Only unregister the registration with this scope URL (default: all for the origin)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_clear_site_data",
+            "description": "Clear cache, cookies, and storage for an origin (default: current tab's origin) to reproduce a first visit or recover from corrupted client state",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "origin": {
+                        "type": "string",
+                        "description": "Origin to clear, e.g. https://example.com (default: current tab's origin)"
+                    },
+                    "dataTypes": {
+                        "type": "array",
+                        "items": {
+                            "type": "string",
+                            "enum": ["cache", "cookies", "localStorage", "indexedDB", "cacheStorage", "serviceWorkers", "fileSystems"]
+                        },
+                        "description": "Data types to clear (default: all)"
+                    },
+                    "reload": {
+                        "type": "boolean",
+                        "description": "Reload the tab after clearing (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_get_cookies",
+            "description": "List cookies for a domain or URL (default: the current tab's URL) to check login state or carry a session into another flow. Values are omitted unless includeValues is true",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "domain": {
+                        "type": "string",
+                        "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "Only cookies the browser would send to this URL (instead of domain)"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Only cookies with this name"
+                    },
+                    "includeValues": {
+                        "type": "boolean",
+                        "description": "Include cookie values (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_set_cookie",
+            "description": "Set a cookie for a domain or URL (default: the current tab's URL)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Cookie name"
+                    },
+                    "value": {
+                        "type": "string",
+                        "description": "Cookie value"
+                    },
+                    "domain": {
+                        "type": "string",
+                        "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "Only cookies the browser would send to this URL (instead of domain)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Cookie path (default: /)"
+                    },
+                    "secure": {
+                        "type": "boolean",
+                        "description": "Only send over HTTPS"
+                    },
+                    "httpOnly": {
+                        "type": "boolean",
+                        "description": "Hide the cookie from page scripts"
+                    },
+                    "sameSite": {
+                        "type": "string",
+                        "enum": ["strict", "lax", "none"],
+                        "description": "SameSite policy; none implies secure"
+                    },
+                    "expires": {
+                        "type": "number",
+                        "description": "Expiry as a Unix timestamp in seconds (default: session cookie)"
+                    },
+                    "maxAgeSeconds": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Expiry in seconds from now (instead of expires)"
+                    }
+                },
+                "required": ["name", "value"]
+            }
+        }),
+        json!({
+            "name": "browser_clear_cookies",
+            "description": "Delete cookies for a domain or URL (default: the current tab's URL), optionally only those with a given name; leaves other site data alone",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "domain": {
+                        "type": "string",
+                        "description": "Cookie domain, e.g. example.com; subdomain cookies are included (default: the current tab's URL)"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "Only cookies the browser would send to this URL (instead of domain)"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Only delete cookies with this name"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_list_downloads",
+            "description": "List the browser's downloads, newest first, with their state (in_progress, complete, interrupted), progress and file path on disk",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "state": {
+                        "type": "string",
+                        "enum": ["in_progress", "complete", "interrupted"],
+                        "description": "Only downloads in this state"
+                    },
+                    "urlContains": {
+                        "type": "string",
+                        "description": "Only downloads whose URL contains this text"
+                    },
+                    "filenameContains": {
+                        "type": "string",
+                        "description": "Only downloads whose file path contains this text, e.g. .csv"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 100,
+                        "description": "Maximum downloads to return (default: 20)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_wait_for_download",
+            "description": "Wait for a download to finish (e.g. after clicking \"Export CSV\") and return its file path. Without id, waits for the newest matching download started in the last sinceSeconds. Optionally moves the file into this session's artifacts or to savePath",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "type": "integer",
+                        "description": "Download id from browser_list_downloads"
+                    },
+                    "urlContains": {
+                        "type": "string",
+                        "description": "Only a download whose URL contains this text"
+                    },
+                    "filenameContains": {
+                        "type": "string",
+                        "description": "Only a download whose file path contains this text, e.g. .csv"
+                    },
+                    "sinceSeconds": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Also match downloads that started this many seconds before the call (default: 60)"
+                    },
+                    "timeoutMs": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 600000,
+                        "description": "How long to wait for the download to finish (default: 30000)"
+                    },
+                    "saveArtifact": {
+                        "type": "boolean",
+                        "description": "Move the file into downloads/ in this session's artifact directory (default: false)"
+                    },
+                    "savePath": {
+                        "type": "string",
+                        "description": "Move the file to this path inside the session's roots (instead of saveArtifact)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_profiling_start",
+            "description": "Start recording JS/CSS code coverage and a performance trace in the current tab over the DevTools Protocol (Chrome shows a debugging banner while recording). Interact with the page, then call browser_profiling_stop",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "coverage": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["js", "css"] },
+                        "description": "Coverage to record (default: both; [] for none)"
+                    },
+                    "trace": {
+                        "type": "boolean",
+                        "description": "Record a performance trace (default: true)"
+                    },
+                    "categories": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Trace categories (default: those the DevTools Performance panel records)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_profiling_stop",
+            "description": "Stop recording started by browser_profiling_start, save the trace (opens in DevTools or Perfetto) and coverage files to this session's artifact directory, and summarize unused bytes per script/stylesheet and long tasks",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "browser_recording_start",
+            "description": "Start recording a video of the current tab, e.g. to review later why a multi-step run failed. Chrome only paints visible tabs, so keep the tab in front (Chrome shows a debugging banner while recording). Call browser_recording_stop to save the video",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "enum": ["webm", "mp4"],
+                        "description": "Video format; needs ffmpeg, without which a Motion-JPEG AVI is saved instead (default: webm)"
+                    },
+                    "maxWidth": {
+                        "type": "integer",
+                        "minimum": 320,
+                        "maximum": 3840,
+                        "description": "Scale frames down to fit this many pixels wide and high (default: 1280)"
+                    },
+                    "quality": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 100,
+                        "description": "JPEG quality of the captured frames (default: 70)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_recording_stop",
+            "description": "Stop the recording started by browser_recording_start and encode it into a video in ~/.agent-browser/recordings. Returns the path, duration and frame count",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "network_list_websockets",
+            "description": "List captured WebSocket connections (lifecycle, message counts/bytes, recent message previews) and WebRTC connection events",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "urlPattern": {
+                        "type": "string",
+                        "description": "Only include connections whose URL contains this substring"
+                    },
+                    "includeMessages": {
+                        "type": "boolean",
+                        "description": "Include recent message previews (default: true)"
+                    },
+                    "includeWebRTC": {
+                        "type": "boolean",
+                        "description": "Include WebRTC connection events (default: true)"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Maximum number of connections/events to return (default: 50)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "network_capture_configure",
+            "description": "Configure network capture: optionally keep request/response bodies up to a size limit, and add header names or regex patterns to redact before storage. Auth headers and common secret patterns are always redacted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "captureBodies": {
+                        "type": "boolean",
+                        "description": "Capture request/response bodies (default: false)"
+                    },
+                    "maxBodyBytes": {
+                        "type": "number",
+                        "description": "Maximum stored body size in bytes; longer bodies are truncated (default: 65536)"
+                    },
+                    "redactHeaders": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Additional header names whose values are redacted"
+                    },
+                    "redactPatterns": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Additional regex patterns replaced with [REDACTED] in URLs, headers, and bodies"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "network_list_requests",
+            "description": "List recent HTTP requests the page made (method, URL, status, type, start time, duration, size), newest first, to see which API calls actually fired and how they answered. Defaults to this session's tab, or the active tab. Use network_get_request with an id for headers",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "urlPattern": {
+                        "type": "string",
+                        "description": "Only requests whose URL contains this substring, e.g. /api/"
+                    },
+                    "status": {
+                        "type": ["integer", "string"],
+                        "description": "Only requests with this status: a code like 404, a class like \"4xx\", or \"failed\" for requests that got no response"
+                    },
+                    "type": {
+                        "type": "string",
+                        "enum": ["main_frame", "sub_frame", "stylesheet", "script", "image", "font", "object", "xmlhttprequest", "ping", "csp_report", "media", "websocket", "other"],
+                        "description": "Only requests of this resource type; fetch() and XHR calls are xmlhttprequest"
+                    },
+                    "tabId": {
+                        "type": "integer",
+                        "description": "List requests from this tab instead"
+                    },
+                    "allTabs": {
+                        "type": "boolean",
+                        "description": "List requests from every tab (default: false)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 500,
+                        "description": "Maximum requests to return (default: 50)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "network_get_request",
+            "description": "Get captured HTTP requests (headers, redacted bodies when enabled) by request id or URL substring",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "type": "string",
+                        "description": "Captured request id"
+                    },
+                    "urlPattern": {
+                        "type": "string",
+                        "description": "Match requests whose URL contains this substring"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Maximum number of entries to return, newest first (default: 10)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "session_artifacts_list",
+            "description": "List files this session has written to its artifact directory (used for savePath when the client declares no roots); idle session directories are cleaned up automatically",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "artifacts_search",
+            "description": "Full-text search across everything the server kept, from every session: page text of saved snapshots, session reports and text artifacts, and audit log events. Use it to find where something was seen before (e.g. query \"invoice total\" with since \"7d\"). Results are ranked, newest first among equals, with a snippet",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Words that must all appear; \"quoted phrases\", prefix* and -excluded words are supported"
+                    },
+                    "kinds": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["page", "report", "artifact", "audit"] },
+                        "description": "Only search these sources (default: all)"
+                    },
+                    "session": {
+                        "type": "string",
+                        "description": "Only results from this session label (see session_list)"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Only results from this time on: RFC 3339, YYYY-MM-DD, or an age such as 24h, 7d or 2w"
+                    },
+                    "until": {
+                        "type": "string",
+                        "description": "Only results up to this time, in the same formats as since"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum results (default 20, max 100)"
+                    }
+                },
+                "required": ["query"]
+            }
+        }),
+        json!({
+            "name": "session_report",
+            "description": "Write a self-contained HTML report of what this session did: every tool call with its timing and outcome, the session's screenshots, a network summary and credential/authorization audit events. Typed values and secrets are left out; returns the file path",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": {
+                        "type": "string",
+                        "description": "Report heading (default: Agent session report)"
+                    },
+                    "includeScreenshots": {
+                        "type": "boolean",
+                        "description": "Embed the session's PNG/JPEG artifacts (default: true)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "history_retry",
+            "description": "Re-run a failed tool call from a session's timeline, optionally with edited arguments, to nudge a stuck session forward. The retry is recorded with the id of the call it re-ran and the first call of the chain, on the timeline and in the audit log. Call ids (c1, c2, ...) appear in session_report",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "callId": {
+                        "type": "string",
+                        "description": "Failed call to re-run, e.g. c12 (default: the session's most recent failure)"
+                    },
+                    "session": {
+                        "type": "string",
+                        "description": "Session whose call to re-run, as listed by session_list (default: this session); the call runs in that session"
+                    },
+                    "arguments": {
+                        "type": "object",
+                        "description": "Arguments to change before re-running; each key replaces the original value, null removes it"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "session_list",
+            "description": "List the MCP sessions connected on any transport (stdio agent, TCP, WebSocket, HTTP, SSE) with their client, connection time and observers, plus this session's own id",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "session_observe",
+            "description": "Receive another session's tool calls as notifications/session_activity (call id, tool, redacted arguments, duration, error) so a supervisor can follow an agent; stop with stop: true",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session to observe, from session_list"
+                    },
+                    "stop": {
+                        "type": "boolean",
+                        "description": "Stop observing instead (default: false)"
+                    }
+                },
+                "required": ["session"]
+            }
+        }),
+        json!({
+            "name": "session_inject",
+            "description": "Run a tool call as another session (e.g. the stdio agent), for a supervisor taking a step on its behalf. The call is recorded on that session's timeline and in the audit log as injected by this session, and that session receives notifications/session_injected. Session, passkey and recovery tools and playwright_evaluate can't be injected",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session to run the call as, from session_list"
+                    },
+                    "tool": {
+                        "type": "string",
+                        "description": "Tool to call"
+                    },
+                    "arguments": {
+                        "type": "object",
+                        "description": "The tool's arguments"
+                    }
+                },
+                "required": ["session", "tool"]
+            }
+        }),
+        json!({
+            "name": "history_stats",
+            "description": "Summarize authorization and credential activity per day from the audit log: event counts by kind and relying party and distinct sessions. Events older than the retention window are kept as daily summaries; optionally compact now",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "days": {
+                        "type": "number",
+                        "description": "Most recent days with activity to include (default: 30, max: 366)"
+                    },
+                    "compact": {
+                        "type": "boolean",
+                        "description": "Fold events older than the retention window into daily summaries before reporting (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "alerts_test",
+            "description": "Send a test alert through the channels configured in ~/.agent-browser/alerts.json and report each channel's delivery result, along with the configured channels and per-class routes",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "class": {
+                        "type": "string",
+                        "enum": ["authorization", "policy_violation", "task_completion"],
+                        "description": "Event class whose route to test (default: every class)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "storage_status",
+            "description": "Report disk usage of the ~/.agent-browser data directory against the quotas for artifacts, recordings and audit log segments; optionally run the least-recently-used cleanup now",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "cleanup": {
+                        "type": "boolean",
+                        "description": "Delete least recently used files in categories over quota before reporting (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "server_metrics",
+            "description": "Report the extension command queue (in-flight depth, limit, high-water mark, admitted/rejected/shed counts), pending requests, connected MCP sessions and the companion browser pool",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "telemetry_status",
+            "description": "Show the opt-in local usage telemetry (tool call and failure counts only, never URLs or content; enabled with --telemetry) and a differentially private export of it for sharing with maintainers",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "epsilon": {
+                        "type": "number",
+                        "exclusiveMinimum": 0,
+                        "maximum": 10,
+                        "description": "Privacy budget for the export; smaller adds more noise (default: 1)"
+                    },
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Delete the collected counts first (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "capabilities_diff",
+            "description": "Developer tool: diff the server's tool registry against a saved manifest (a tools/list result) and report added, removed and changed tool schemas, flagging breaking changes",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "manifest": {
+                        "type": "object",
+                        "description": "Saved tools/list result ({\"tools\": [...]})"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Path to a saved manifest JSON file on the server host (used when manifest is omitted)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "preflight",
+            "description": "Check a URL's reachability from the server (DNS, TCP connect, TLS handshake, HTTP status) without navigating the browser; each stage is timed and the verdict says where it failed",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "http:// or https:// URL to check"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "Timeout per stage in milliseconds (default: 5000, max: 30000)"
+                    }
+                },
+                "required": ["url"]
+            }
+        }),
+        json!({
+            "name": "passkey_enable",
+            "description": "Enable or disable passkey automation for WebAuthn flows",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "enabled": {
+                        "type": "boolean",
+                        "description": "Whether to enable passkey automation"
+                    }
+                },
+                "required": ["enabled"]
+            }
+        }),
+        json!({
+            "name": "passkey_status",
+            "description": "Get the current status of passkey automation",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "passkey_list",
+            "description": "List all stored passkey credentials",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "passkey_clear",
+            "description": "Clear all stored passkey credentials",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "passkey_authorize",
+            "description": "Authorize AI agent to use passkeys for a limited time (requires Touch ID on macOS)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "duration_hours": {
+                        "type": "number",
+                        "description": "Number of hours to authorize access (default: 8)"
+                    }
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "passkey_authorization_status",
+            "description": "Check if AI agent is currently authorized to use passkeys",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "passkey_grant_create",
+            "description": "Mint a signed token that lets another MCP client (e.g. a sub-agent) use one site's passkey exactly once, without authorizing that client generally. Requires an active passkey authorization; the token expires within 15 minutes and when this session's authorization ends",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "rp_id": {
+                        "type": "string",
+                        "description": "Relying party whose credential the grant covers, e.g. github.com"
+                    },
+                    "operation": {
+                        "type": "string",
+                        "enum": ["get", "create"],
+                        "description": "Limit the grant to signing in (get) or registering (create) (default: either)"
+                    },
+                    "duration_minutes": {
+                        "type": "number",
+                        "description": "Minutes the grant stays valid (default: 5, max: 15)"
+                    }
+                },
+                "required": ["rp_id"]
+            }
+        }),
+        json!({
+            "name": "passkey_grant_redeem",
+            "description": "Redeem a token from passkey_grant_create for this session. The session may then use that site's passkey once; the token cannot be redeemed again",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "token": {
+                        "type": "string",
+                        "description": "Grant token (abgrant1....)"
+                    }
+                },
+                "required": ["token"]
+            }
+        }),
+        json!({
+            "name": "recovery_secret_store",
+            "description": "Store account recovery secrets (backup codes or security question answers) encrypted at rest. Requires elevated authorization (recovery_authorize)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "rp_id": {
+                        "type": "string",
+                        "description": "Relying party / site the secrets belong to (e.g. github.com)"
+                    },
+                    "kind": {
+                        "type": "string",
+                        "enum": ["backup_code", "security_answer"],
+                        "description": "Type of recovery secret"
+                    },
+                    "values": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Secret values; each backup code is stored and consumed individually"
+                    },
+                    "label": {
+                        "type": "string",
+                        "description": "Security question text (required for security_answer)"
+                    }
+                },
+                "required": ["rp_id", "kind", "values"]
+            }
+        }),
+        json!({
+            "name": "recovery_secret_list",
+            "description": "List stored recovery secrets with values redacted. Requires an active passkey authorization",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "rp_id": {
+                        "type": "string",
+                        "description": "Only list secrets for this relying party"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "recovery_authorize",
+            "description": "Grant short-lived elevated authorization to store, retrieve or delete recovery secrets (requires an active passkey authorization, max 15 minutes)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "duration_minutes": {
+                        "type": "number",
+                        "description": "Number of minutes to allow retrieval (default: 5, max: 15)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "recovery_secret_get",
+            "description": "Retrieve a recovery secret under elevated authorization. Backup codes are consumed and never returned again",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "rp_id": {
+                        "type": "string",
+                        "description": "Relying party to retrieve a secret for"
+                    },
+                    "kind": {
+                        "type": "string",
+                        "enum": ["backup_code", "security_answer"],
+                        "description": "Type of recovery secret"
+                    },
+                    "label": {
+                        "type": "string",
+                        "description": "Security question text to match (security_answer only)"
+                    }
+                },
+                "required": ["rp_id", "kind"]
+            }
+        }),
+        json!({
+            "name": "recovery_secret_delete",
+            "description": "Delete all recovery secrets stored for a relying party. Requires elevated authorization (recovery_authorize)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "rp_id": {
+                        "type": "string",
+                        "description": "Relying party whose secrets should be removed"
+                    }
+                },
+                "required": ["rp_id"]
+            }
+        }),
+        json!({
+            "name": "state_set",
+            "description": "Persist a small JSON value under a key so later sessions can read it back",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "key": {
+                        "type": "string",
+                        "description": "Key to store the value under"
+                    },
+                    "value": {
+                        "description": "Any JSON value (max 64KB)"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Workspace namespace (default: \"default\")"
+                    }
+                },
+                "required": ["key", "value"]
+            }
+        }),
+        json!({
+            "name": "state_get",
+            "description": "Read a stored value by key, or list all keys in a workspace when key is omitted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "key": {
+                        "type": "string",
+                        "description": "Key to read (omit to list the workspace)"
+                    },
+                    "prefix": {
+                        "type": "string",
+                        "description": "When listing, only include keys starting with this prefix"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Workspace namespace (default: \"default\")"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "state_delete",
+            "description": "Delete a stored key",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "key": {
+                        "type": "string",
+                        "description": "Key to delete"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "Workspace namespace (default: \"default\")"
+                    }
+                },
+                "required": ["key"]
+            }
+        }),
+        json!({
+            "name": "data_transform",
+            "description": "Reshape JSON with a JavaScript expression evaluated in the server (e.g. filter, map or rename fields of extracted rows) without another round trip or page evaluate. The input is bound to `data`. Supports literals, template strings, regex literals, spread, optional chaining, arrow functions and common Array/String/Number/Object/Math/JSON methods; no statements, assignments or host access, and evaluation is step-limited",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "expression": {
+                        "type": "string",
+                        "description": "Expression over `data`, e.g. \"data.filter(r => r.price > 10).map(r => ({ name: r.name.trim(), price: r.price }))\""
+                    },
+                    "data": {
+                        "description": "Input value (omit to read it from the state store with key)"
+                    },
+                    "key": {
+                        "type": "string",
+                        "description": "State store key to read the input from instead of data"
+                    },
+                    "workspace": {
+                        "type": "string",
+                        "description": "State store workspace for key and saveAs (default: \"default\")"
+                    },
+                    "saveAs": {
+                        "type": "string",
+                        "description": "Also store the result under this state store key"
+                    }
+                },
+                "required": ["expression"]
+            }
+        }),
+        json!({
+            "name": "snapshot_save",
+            "description": "Capture the current page (DOM, accessibility tree, screenshot) and persist it under a name for later sessions to reference",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Snapshot name (letters, digits, '-', '_', '.')"
+                    },
+                    "include": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["dom", "accessibility", "screenshot"] },
+                        "description": "Parts to capture (default: all)"
+                    },
+                    "metadata": {
+                        "type": "object",
+                        "description": "Free-form metadata stored with the snapshot"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace an existing snapshot with the same name (default: false)"
+                    }
+                },
+                "required": ["name"]
+            }
+        }),
+        json!({
+            "name": "snapshot_get",
+            "description": "Load a saved snapshot by name, or list saved snapshots when name is omitted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Snapshot name (omit to list all snapshots)"
+                    },
+                    "include": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["dom", "accessibility", "screenshot"] },
+                        "description": "Parts to return (default: all stored parts)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "baseline_set",
+            "description": "Screenshot the current page and store it as a named visual regression baseline for the current viewport size (one image per viewport under each name)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Baseline name (letters, digits, '-', '_', '.')"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace an existing image for this name and viewport (default: false)"
+                    }
+                },
+                "required": ["name"]
+            }
+        }),
+        json!({
+            "name": "baseline_list",
+            "description": "List stored visual regression baselines with their URL, viewport and capture time",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Only this baseline"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "Only baselines captured on URLs containing this text"
+                    },
+                    "viewport": {
+                        "type": "string",
+                        "description": "Only this viewport size, e.g. 1280x720"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "baseline_delete",
+            "description": "Delete a visual regression baseline, or only its image for one viewport",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Baseline name"
+                    },
+                    "viewport": {
+                        "type": "string",
+                        "description": "Delete only this viewport's image, e.g. 390x844 (default: all viewports)"
+                    }
+                },
+                "required": ["name"]
+            }
+        }),
+        json!({
+            "name": "screenshot_diff",
+            "description": "Screenshot the current page and compare it pixel by pixel with a stored baseline at the same viewport size. Reports the share of differing pixels; on failure the capture and a diff image are saved to the session's artifacts",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "baseline": {
+                        "type": "string",
+                        "description": "Baseline name (see baseline_set)"
+                    },
+                    "tolerance": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 255,
+                        "description": "Per-channel difference ignored as noise (default: 16)"
+                    },
+                    "maxDiffRatio": {
+                        "type": "number",
+                        "minimum": 0,
+                        "maximum": 1,
+                        "description": "Largest share of differing pixels that still passes (default: 0.001)"
+                    }
+                },
+                "required": ["baseline"]
+            }
+        }),
+        json!({
+            "name": "watch_add",
+            "description": "Watch a page for changes: re-visit the URL on an interval in a background tab, extract a value, and send notifications/watch_changed (and POST to a webhook) when it differs from the previous run",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "Page to watch (http or https)"
+                    },
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of the element to read (default: the page body)"
+                    },
+                    "attribute": {
+                        "type": "string",
+                        "description": "Read this attribute instead of the element's text"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Regex applied to the extracted text; the first capture group (or the whole match) is compared"
+                    },
+                    "interval_seconds": {
+                        "type": "number",
+                        "description": "Seconds between checks (default: 3600, minimum: 60)"
+                    },
+                    "webhook": {
+                        "type": "string",
+                        "description": "URL that receives a JSON POST on every change"
+                    }
+                },
+                "required": ["url"]
+            }
+        }),
+        json!({
+            "name": "watch_list",
+            "description": "List page watches with their last value, last check, and change count",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "watch_remove",
+            "description": "Stop watching a page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "type": "string",
+                        "description": "Watch ID returned by watch_add"
+                    }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "playwright_detect_modal",
+            "description": "Detect if a modal, popup, or overlay is present on the page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "minZIndex": {
+                        "type": "number",
+                        "description": "Minimum z-index to consider (default: 100)"
+                    },
+                    "includeHidden": {
+                        "type": "boolean",
+                        "description": "Include hidden modals (default: false)"
+                    },
+                    "maxResults": {
+                        "type": "number",
+                        "description": "Maximum number of modals to detect (default: 1)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "playwright_dismiss_modal",
+            "description": "Attempt to dismiss any detected modals on the page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "strategy": {
+                        "type": "string",
+                        "enum": ["auto", "button", "escape", "backdrop", "remove"],
+                        "description": "Dismissal strategy: auto tries all methods, button clicks dismiss button, escape presses ESC, backdrop clicks overlay, remove forcibly removes from DOM (default: auto)"
+                    },
+                    "timeout": {
+                        "type": "number",
+                        "description": "Timeout in milliseconds (default: 5000)"
+                    },
+                    "waitAfter": {
+                        "type": "number",
+                        "description": "Wait time after dismissal to verify (default: 500)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_handle_dialogs",
+            "description": "Choose how this session's tab answers JavaScript dialogs (alert, confirm, prompt, beforeunload). accept and dismiss answer them automatically; manual leaves them open and fails other commands on the tab with a 'JavaScript dialog open' error until browser_respond_dialog answers. Without a policy an unexpected confirm() blocks the page until the command times out",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "mode": {
+                        "type": "string",
+                        "enum": ["accept", "dismiss", "manual", "off"],
+                        "description": "accept clicks OK (and leaves the page on beforeunload), dismiss clicks Cancel, manual waits for browser_respond_dialog, off stops watching"
+                    },
+                    "promptText": {
+                        "type": "string",
+                        "description": "Text accept enters into prompt() dialogs (default: the prompt's default value)"
+                    }
+                },
+                "required": ["mode"]
+            }
+        }),
+        json!({
+            "name": "browser_respond_dialog",
+            "description": "Answer the JavaScript dialog open on this session's tab, e.g. after a 'JavaScript dialog open' error under the manual policy of browser_handle_dialogs",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "accept": {
+                        "type": "boolean",
+                        "description": "Click OK rather than Cancel (default: true)"
+                    },
+                    "promptText": {
+                        "type": "string",
+                        "description": "Text to enter into a prompt() dialog before accepting"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "browser_list_dialogs",
+            "description": "List recent JavaScript dialogs reported while browser_handle_dialogs was on, newest first: type, message, page URL, whether it is still open, and how it was answered",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tabId": {
+                        "type": "number",
+                        "description": "Only include dialogs from this tab"
+                    },
+                    "openOnly": {
+                        "type": "boolean",
+                        "description": "Only include dialogs still waiting for an answer (default: false)"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Maximum number of dialogs to return (default: 20)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "audit_accessibility",
+            "description": "Check the current page for accessibility problems (missing alt text and labels, unnamed buttons and links, low color contrast, invalid ARIA, heading order, and more) and return violations grouped by rule with impact, WCAG reference and example elements, plus a 0-100 score over the rules that applied",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "Only audit elements inside this CSS selector (default: whole document)"
+                    },
+                    "rules": {
+                        "type": "array",
+                        "items": {
+                            "type": "string",
+                            "enum": ["image-alt", "label", "button-name", "link-name", "color-contrast", "html-has-lang", "document-title", "aria-valid-role", "aria-hidden-focus", "aria-labelledby-target", "duplicate-id", "heading-order", "frame-title"]
+                        },
+                        "description": "Rules to run (default: all)"
+                    },
+                    "maxNodes": {
+                        "type": "number",
+                        "description": "Example elements returned per violated rule (default: 10, max: 100)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "check_links",
+            "description": "Find broken links on the current page: extracts its links and checks each from the server (HEAD, falling back to GET) with bounded concurrency, following redirects. Returns each link's final status, redirect chain and link text, with a summary of ok, broken (4xx/5xx) and unreachable links. Hosts on private or loopback addresses are skipped unless the page is on one or allowPrivate is set",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sameOriginOnly": {
+                        "type": "boolean",
+                        "description": "Only check links on the page's own origin (default: false)"
+                    },
+                    "includeResources": {
+                        "type": "boolean",
+                        "description": "Also check image, script, stylesheet and media URLs (default: false, anchors only)"
+                    },
+                    "maxLinks": {
+                        "type": "number",
+                        "description": "Most distinct links to check (default: 100, max: 500)"
+                    },
+                    "concurrency": {
+                        "type": "number",
+                        "description": "Requests in flight at once (default: 8, max: 32)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "Per-link timeout including redirects (default: 8000, max: 30000)"
+                    },
+                    "allowPrivate": {
+                        "type": "boolean",
+                        "description": "Check links to loopback, private and link-local addresses (default: false)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "audit_seo",
+            "description": "Audit the current page for search-engine readiness: title, meta description, canonical, robots meta, heading structure, hreflang, Open Graph and structured data from the page, plus server-side checks of robots.txt rules for the page, sitemap availability and the HTTP status of the canonical URL and key links. Returns the collected facts and issues graded error/warning/notice",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "siteChecks": {
+                        "type": "boolean",
+                        "description": "Fetch robots.txt, sitemaps and key links from the server (default: true)"
+                    },
+                    "linkChecks": {
+                        "type": "number",
+                        "description": "How many key links (canonical, alternates, then page links) to check the status of (default: 10, max: 50)"
+                    },
+                    "timeoutMs": {
+                        "type": "number",
+                        "description": "Per-request timeout for site checks (default: 5000, max: 30000)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "dismiss_consent",
+            "description": "Accept or reject a cookie-consent banner using rules for common consent platforms (OneTrust, Cookiebot, Didomi, Quantcast, Usercentrics, ...), or turn automatic handling after every navigation on or off",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "mode": {
+                        "type": "string",
+                        "enum": ["accept", "reject"],
+                        "description": "Accept all or reject all non-essential cookies (default: reject)"
+                    },
+                    "auto": {
+                        "type": "boolean",
+                        "description": "Enable or disable running dismiss_consent automatically after each navigation (with the given mode) instead of running it now"
+                    },
+                    "fallbackToModal": {
+                        "type": "boolean",
+                        "description": "Fall back to generic modal dismissal when no known platform matches (default: true)"
+                    }
+                }
+            }
+        }),
+    ]
+}
+
+/// Tools currently usable given the extension connection and passkey automation
+/// state. `registered` is the set the extension described with `register_tools`,
+/// which takes the place of the built-in forwarded tools.
+pub fn available_tools(extension_connected: bool, passkey_automation: bool, registered: Option<&[Value]>) -> Vec<Value> {
+    let replaced = registered.is_some();
+    builtin_tools()
+        .into_iter()
+        .filter(|tool| !replaced || extension_command(tool["name"].as_str().unwrap_or_default()).is_none())
+        .chain(registered.unwrap_or_default().iter().cloned())
+        .filter(|tool| {
+            let name = tool["name"].as_str().unwrap_or_default();
+            if !extension_connected && !EXTENSION_INDEPENDENT_TOOLS.contains(&name) {
+                return false;
+            }
+            passkey_automation || !PASSKEY_AUTOMATION_TOOLS.contains(&name)
+        })
+        .collect()
+}
+
+/// Whether a tool is implemented by the server itself, so an extension can't register it
+pub fn is_server_tool(name: &str) -> bool {
+    extension_command(name).is_none() && builtin_tools().iter().any(|tool| tool["name"] == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(tools: &[Value]) -> Vec<&str> {
+        tools.iter().filter_map(|t| t["name"].as_str()).collect()
+    }
+
+    #[test]
+    fn test_available_tools_track_connection_and_passkeys() {
+        let all = builtin_tools();
+        assert_eq!(available_tools(true, true, None).len(), all.len());
+
+        let offline = available_tools(false, true, None);
+        assert!(names(&offline).contains(&"state_get"));
+        assert!(!names(&offline).contains(&"playwright_click"));
+
+        let no_passkeys = available_tools(true, false, None);
+        assert!(!names(&no_passkeys).contains(&"passkey_list"));
+        assert!(names(&no_passkeys).contains(&"passkey_enable"));
+    }
+
+    #[test]
+    fn test_registered_tools_replace_forwarded_builtins() {
+        let registered = [json!({ "name": "playwright_click", "inputSchema": { "type": "object" } })];
+        let tools = available_tools(true, true, Some(&registered));
+        let listed = names(&tools);
+        assert!(listed.contains(&"playwright_click"));
+        assert!(!listed.contains(&"playwright_navigate"));
+        assert!(listed.contains(&"state_get") && listed.contains(&"explore_menu"));
+
+        // Every forwarded tool has a definition, and no server tool is forwarded
+        let all = builtin_tools();
+        assert!(EXTENSION_COMMANDS.iter().all(|(name, _)| names(&all).contains(name)));
+        assert!(is_server_tool("state_get"));
+        assert!(!is_server_tool("playwright_click"));
+    }
+}
@@ -0,0 +1,270 @@
+/*!
+ * On-Disk Format Migrations
+ *
+ * Every file or directory the server persists under `~/.agent-browser` has a
+ * format version, recorded in `formats.json`. At startup, before any store
+ * opens its data, stores behind the version this build writes are migrated
+ * one step at a time. The previous copy is first saved to
+ * `backups/<time>/`, and it is put back if a step fails. Data written by a
+ * newer build stops startup instead of being misread.
+ *
+ * Data that predates `formats.json` is version 1. A format change adds a
+ * `Migration` to its entry in `FORMATS`; the entry's current version is one
+ * more than its number of migrations.
+ */
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::storage;
+
+pub const VERSIONS_FILE: &str = "formats.json";
+pub const BACKUP_DIR: &str = "backups";
+
+/// One step from the previous version to `to`, rewriting the store in place
+pub struct Migration {
+    pub to: u32,
+    pub description: &'static str,
+    pub run: fn(&Path) -> Result<()>,
+}
+
+/// A persisted store: a file or directory relative to the data directory
+pub struct Format {
+    pub path: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+impl Format {
+    pub fn current(&self) -> u32 {
+        1 + self.migrations.len() as u32
+    }
+}
+
+/// Every versioned store. Credentials and recovery secrets are listed
+/// first so a failure there stops before anything else is touched.
+pub const FORMATS: &[Format] = &[
+    Format { path: "credentials.json", migrations: &[] },
+    Format { path: "credential_usage.json", migrations: &[] },
+    Format { path: "recovery_secrets.json", migrations: &[] },
+    Format { path: "alerts.json", migrations: &[] },
+    Format { path: "network_policy.json", migrations: &[] },
+    Format { path: "watches.json", migrations: &[] },
+    Format { path: "state", migrations: &[] },
+    Format { path: "snapshots", migrations: &[] },
+    Format { path: "baselines", migrations: &[] },
+    Format { path: "telemetry.json", migrations: &[] },
+];
+
+// ============================================================================
+// Planning
+// ============================================================================
+
+/// A store whose recorded version differs from the one this build writes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pending {
+    pub path: &'static str,
+    pub from: u32,
+    pub to: u32,
+}
+
+fn read_versions(data_dir: &Path) -> Result<BTreeMap<String, u32>> {
+    match fs::read_to_string(data_dir.join(VERSIONS_FILE)) {
+        Ok(data) => serde_json::from_str(&data).with_context(|| format!("Invalid {}", VERSIONS_FILE)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_versions(data_dir: &Path, versions: &BTreeMap<String, u32>) -> Result<()> {
+    storage::write_private_file(&data_dir.join(VERSIONS_FILE), serde_json::to_string_pretty(versions)?)
+}
+
+/// Stores that need migrating; errors if any was written by a newer build
+pub fn pending(data_dir: &Path, formats: &[Format]) -> Result<Vec<Pending>> {
+    let versions = read_versions(data_dir)?;
+    let mut pending = Vec::new();
+    for format in formats {
+        if !data_dir.join(format.path).exists() {
+            continue;
+        }
+        let from = versions.get(format.path).copied().unwrap_or(1);
+        let to = format.current();
+        if from > to {
+            bail!(
+                "{} is format version {}, but this build only understands up to {}; upgrade agent-browser or restore it from {}/",
+                format.path,
+                from,
+                to,
+                BACKUP_DIR
+            );
+        }
+        if from < to {
+            pending.push(Pending { path: format.path, from, to });
+        }
+    }
+    Ok(pending)
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        storage::ensure_private_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// Put a store back the way it was before a failed migration
+fn restore(path: &Path, backup: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else if path.exists() {
+        fs::remove_file(path)?;
+    }
+    copy_recursive(backup, path)
+}
+
+/// Migrate every store that needs it and record all current versions;
+/// returns the migrations applied
+pub fn run(data_dir: &Path, formats: &[Format]) -> Result<Vec<Pending>> {
+    let pending = pending(data_dir, formats)?;
+    let backup_root = data_dir
+        .join(BACKUP_DIR)
+        .join(chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+
+    let mut versions = read_versions(data_dir)?;
+    for step in &pending {
+        let format = formats.iter().find(|f| f.path == step.path).expect("pending store has a format");
+        let path = data_dir.join(format.path);
+        let backup = backup_root.join(format.path);
+        storage::ensure_private_dir(&backup_root)?;
+        copy_recursive(&path, &backup)
+            .with_context(|| format!("Failed to back up {} before migrating it", format.path))?;
+
+        for migration in format.migrations.iter().filter(|m| m.to > step.from) {
+            info!("Migrating {} to format {}: {}", format.path, migration.to, migration.description);
+            if let Err(e) = (migration.run)(&path) {
+                let restored = restore(&path, &backup);
+                if let Err(restore_error) = restored {
+                    warn!("Failed to restore {} from {:?}: {}", format.path, backup, restore_error);
+                }
+                return Err(anyhow!(
+                    "Migrating {} to format {} failed ({}); the previous copy is kept in {}",
+                    format.path,
+                    migration.to,
+                    e,
+                    backup.display()
+                ));
+            }
+        }
+        // Recorded straight away so a later failure doesn't migrate this store twice
+        versions.insert(format.path.to_string(), step.to);
+        write_versions(data_dir, &versions)?;
+    }
+
+    // Stores that don't exist yet will be created by this build, so they
+    // are recorded as current too
+    for format in formats {
+        versions.entry(format.path.to_string()).or_insert(format.current());
+    }
+    write_versions(data_dir, &versions)?;
+    Ok(pending)
+}
+
+/// Run pending migrations for the real data directory at startup
+pub fn run_pending() -> Result<()> {
+    let data_dir = storage::data_dir()?;
+    for step in run(&data_dir, FORMATS)? {
+        info!("Migrated {} from format {} to {}", step.path, step.from, step.to);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_data_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("agent-browser-migrations-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn add_version_field(path: &Path) -> Result<()> {
+        let mut value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        value["version"] = serde_json::json!(2);
+        Ok(fs::write(path, value.to_string())?)
+    }
+
+    fn corrupt_then_fail(path: &Path) -> Result<()> {
+        fs::write(path, "half-written")?;
+        bail!("disk full")
+    }
+
+    const UPGRADE: &[Format] = &[Format {
+        path: "store.json",
+        migrations: &[Migration { to: 2, description: "add version field", run: add_version_field }],
+    }];
+    const BROKEN: &[Format] = &[Format {
+        path: "store.json",
+        migrations: &[Migration { to: 2, description: "always fails", run: corrupt_then_fail }],
+    }];
+
+    #[test]
+    fn test_migrates_once_with_backup() {
+        let dir = temp_data_dir();
+        fs::write(dir.join("store.json"), r#"{"a":1}"#).unwrap();
+
+        let applied = run(&dir, UPGRADE).unwrap();
+        assert_eq!(applied, vec![Pending { path: "store.json", from: 1, to: 2 }]);
+        let migrated: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("store.json")).unwrap()).unwrap();
+        assert_eq!(migrated["version"], 2);
+
+        // The pre-migration copy is kept
+        let backup = fs::read_dir(dir.join(BACKUP_DIR)).unwrap().next().unwrap().unwrap().path();
+        assert_eq!(fs::read_to_string(backup.join("store.json")).unwrap(), r#"{"a":1}"#);
+
+        // Recorded as current, so nothing runs again
+        assert!(run(&dir, UPGRADE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_migration_restores_previous_copy() {
+        let dir = temp_data_dir();
+        fs::write(dir.join("store.json"), r#"{"a":1}"#).unwrap();
+
+        let error = run(&dir, BROKEN).unwrap_err().to_string();
+        assert!(error.contains("disk full"), "{}", error);
+        assert_eq!(fs::read_to_string(dir.join("store.json")).unwrap(), r#"{"a":1}"#);
+        assert!(!dir.join(VERSIONS_FILE).exists());
+    }
+
+    #[test]
+    fn test_fresh_stores_are_current_and_newer_versions_refused() {
+        let dir = temp_data_dir();
+        assert!(run(&dir, UPGRADE).unwrap().is_empty());
+
+        // Created after the first run by this build, so already current
+        fs::write(dir.join("store.json"), "{}").unwrap();
+        assert!(pending(&dir, UPGRADE).unwrap().is_empty());
+
+        fs::write(dir.join(VERSIONS_FILE), r#"{"store.json":3}"#).unwrap();
+        assert!(pending(&dir, UPGRADE).unwrap_err().to_string().contains("version 3"));
+    }
+}
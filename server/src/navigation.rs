@@ -0,0 +1,144 @@
+/*!
+ * Navigation Tracking
+ *
+ * Keeps a navigation generation counter per tab, fed by `navigation`
+ * events from the extension. Element-scoped commands still in flight when
+ * their tab navigates are failed with a distinct "page navigated" error
+ * instead of timing out against a document that no longer exists.
+ */
+
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Prefix of the error returned for commands interrupted by a navigation
+pub const PAGE_NAVIGATED_ERROR: &str = "Page navigated";
+
+/// JSON-RPC error code for commands interrupted by a navigation
+pub const PAGE_NAVIGATED_CODE: i64 = -32010;
+
+/// Navigation as reported by the extension (main frame commits only)
+#[derive(Debug, Clone)]
+pub struct NavigationEvent {
+    pub tab_id: i64,
+    pub url: Option<String>,
+    /// Whether the tab is the one commands without a tabId go to
+    pub active: bool,
+}
+
+impl NavigationEvent {
+    pub fn from_extension(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            tab_id: data.get("tabId").and_then(|v| v.as_i64())?,
+            url: data.get("url").and_then(|v| v.as_str()).map(str::to_string),
+            active: data.get("active").and_then(|v| v.as_bool()).unwrap_or(true),
+        })
+    }
+}
+
+/// Target of an in-flight command, captured when it was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandScope {
+    /// Explicit tabId from the command parameters (None means the active tab)
+    pub tab_id: Option<i64>,
+}
+
+impl CommandScope {
+    /// Element-scoped commands (anything addressing a selector or locator, or
+    /// form fields) are tied to the current document
+    pub fn for_command(method: &str, params: &serde_json::Value) -> Option<Self> {
+        if method == "navigate" || !["selector", "locator", "fields"].iter().any(|key| params.get(key).is_some()) {
+            return None;
+        }
+        Some(Self {
+            tab_id: params.get("tabId").and_then(|v| v.as_i64()),
+        })
+    }
+
+    /// Whether a navigation invalidates this command
+    pub fn is_invalidated_by(&self, event: &NavigationEvent) -> bool {
+        self.targets(event.tab_id, event.active)
+    }
+
+    /// Whether the command addresses `tab_id`, given whether that tab is active
+    pub fn targets(&self, tab_id: i64, active: bool) -> bool {
+        match self.tab_id {
+            Some(own) => own == tab_id,
+            None => active,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct NavigationTracker {
+    generations: RwLock<HashMap<i64, u64>>,
+}
+
+impl NavigationTracker {
+    /// Bump and return the generation of the navigated tab
+    pub async fn record(&self, event: &NavigationEvent) -> u64 {
+        let mut generations = self.generations.write().await;
+        let generation = generations.entry(event.tab_id).or_insert(0);
+        *generation += 1;
+        *generation
+    }
+
+    /// Tabs that have navigated since startup and are still open
+    pub async fn known_tabs(&self) -> Vec<i64> {
+        let mut tabs: Vec<i64> = self.generations.read().await.keys().copied().collect();
+        tabs.sort_unstable();
+        tabs
+    }
+
+    pub async fn forget(&self, tab_id: i64) {
+        self.generations.write().await.remove(&tab_id);
+    }
+}
+
+pub fn page_navigated_error(event: &NavigationEvent, generation: u64) -> String {
+    format!(
+        "{}: tab {} navigated to {} (generation {}) while the command was pending",
+        PAGE_NAVIGATED_ERROR,
+        event.tab_id,
+        event.url.as_deref().unwrap_or("a new page"),
+        generation
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_scope() {
+        let click = CommandScope::for_command("click", &serde_json::json!({ "selector": "#go" })).unwrap();
+        let nav = NavigationEvent::from_extension(&serde_json::json!({ "tabId": 4, "url": "https://a" })).unwrap();
+        assert!(click.is_invalidated_by(&nav));
+
+        let background = NavigationEvent { active: false, ..nav.clone() };
+        assert!(!click.is_invalidated_by(&background));
+
+        let pinned = CommandScope::for_command("click", &serde_json::json!({ "selector": "#go", "tabId": 9 })).unwrap();
+        assert!(!pinned.is_invalidated_by(&nav));
+
+        assert!(CommandScope::for_command("navigate", &serde_json::json!({ "url": "https://a" })).is_none());
+        assert!(CommandScope::for_command("screenshot", &serde_json::json!({})).is_none());
+        assert!(CommandScope::for_command("fill_form", &serde_json::json!({ "fields": [] })).is_some());
+        assert!(CommandScope::for_command("click", &serde_json::json!({ "locator": { "text": "Buy" } })).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_generations_increment_per_tab() {
+        let tracker = NavigationTracker::default();
+        let event = NavigationEvent { tab_id: 1, url: None, active: true };
+        assert_eq!(tracker.record(&event).await, 1);
+        assert_eq!(tracker.record(&event).await, 2);
+        assert_eq!(tracker.record(&NavigationEvent { tab_id: 2, ..event }).await, 1);
+        assert_eq!(tracker.known_tabs().await, [1, 2]);
+        tracker.forget(1).await;
+        assert_eq!(tracker.known_tabs().await, [2]);
+    }
+}
@@ -0,0 +1,729 @@
+/*!
+ * Network Capture
+ *
+ * Collects network activity reported by the extension as unsolicited
+ * events. HTTP capture only sees request/response pairs, so WebSocket
+ * lifecycle + message summaries and WebRTC connection events are tracked
+ * here as well.
+ *
+ * - Bounded in memory (oldest connections/events dropped first)
+ * - Message payloads are reduced to short previews, never stored whole
+ * - HTTP bodies are only kept when the capture policy enables them, and
+ *   headers/bodies/URLs pass through redaction before storage
+ */
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::redaction::{RedactionPolicy, Redactor};
+use crate::storage;
+
+const MAX_HTTP_ENTRIES: usize = 500;
+const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+const MAX_WEBSOCKETS: usize = 200;
+const MAX_WEBRTC_EVENTS: usize = 500;
+const MAX_RECENT_MESSAGES: usize = 20;
+const MAX_PREVIEW_CHARS: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketStatus {
+    Connecting,
+    Open,
+    Closed,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageStats {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSummary {
+    pub direction: Direction,
+    pub timestamp: i64,
+    pub size: u64,
+    pub binary: bool,
+    pub preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketConnection {
+    pub id: String,
+    pub url: String,
+    pub tab_id: Option<i64>,
+    pub status: WebSocketStatus,
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    pub close_code: Option<i64>,
+    pub close_reason: Option<String>,
+    pub sent: MessageStats,
+    pub received: MessageStats,
+    pub recent_messages: VecDeque<MessageSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebRtcEvent {
+    pub connection_id: String,
+    pub tab_id: Option<i64>,
+    pub event: String,
+    pub state: Option<String>,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub detail: serde_json::Value,
+}
+
+/// WebSocket event as reported by the extension
+#[derive(Debug, Deserialize)]
+struct WebSocketReport {
+    id: String,
+    phase: String,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default, rename = "tabId")]
+    tab_id: Option<i64>,
+    #[serde(default)]
+    direction: Option<Direction>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    binary: bool,
+    #[serde(default)]
+    payload: Option<String>,
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedBody {
+    pub content: String,
+    pub truncated: bool,
+    pub original_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpEntry {
+    pub id: String,
+    pub url: String,
+    pub method: String,
+    pub status: Option<u16>,
+    pub resource_type: Option<String>,
+    pub tab_id: Option<i64>,
+    pub started_at: i64,
+    pub duration_ms: Option<f64>,
+    pub size: Option<u64>,
+    pub request_headers: serde_json::Map<String, serde_json::Value>,
+    pub response_headers: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<CapturedBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<CapturedBody>,
+    pub redactions: u32,
+    /// Network error for requests that got no response, e.g. net::ERR_BLOCKED_BY_CLIENT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One line of network_list_requests: an HttpEntry without headers and bodies
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpSummary {
+    pub id: String,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub resource_type: Option<String>,
+    pub started_at: i64,
+    pub duration_ms: Option<f64>,
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// HTTP request/response as reported by the extension
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpReport {
+    id: String,
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    status: Option<u16>,
+    #[serde(default, rename = "type")]
+    resource_type: Option<String>,
+    #[serde(default)]
+    tab_id: Option<i64>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+    #[serde(default)]
+    duration_ms: Option<f64>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    request_headers: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    response_headers: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    request_body: Option<String>,
+    #[serde(default)]
+    response_body: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// What the capture keeps and how it is scrubbed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturePolicy {
+    /// Keep request/response bodies (off by default)
+    #[serde(default)]
+    pub capture_bodies: bool,
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    #[serde(default)]
+    pub redaction: RedactionPolicy,
+}
+
+fn default_max_body_bytes() -> usize {
+    DEFAULT_MAX_BODY_BYTES
+}
+
+impl Default for CapturePolicy {
+    fn default() -> Self {
+        Self {
+            capture_bodies: false,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            redaction: RedactionPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CaptureState {
+    http: VecDeque<HttpEntry>,
+    websockets: VecDeque<WebSocketConnection>,
+    webrtc: VecDeque<WebRtcEvent>,
+}
+
+/// Status filter for network_list_requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    /// An exact status code, e.g. 404
+    Code(u16),
+    /// A status class given as "4xx"; holds the leading digit
+    Class(u16),
+    /// Requests that failed without a response
+    Failed,
+}
+
+impl StatusFilter {
+    /// Parse 404, "404", "4xx" or "failed"
+    pub fn parse(value: &serde_json::Value) -> Result<Self, String> {
+        let invalid = || format!("status must be a code like 404, a class like \"4xx\", or \"failed\": {}", value);
+        if let Some(code) = value.as_u64() {
+            return u16::try_from(code).ok().filter(|c| (100..600).contains(c)).map(Self::Code).ok_or_else(invalid);
+        }
+        let text = value.as_str().ok_or_else(invalid)?.trim().to_ascii_lowercase();
+        if text == "failed" {
+            return Ok(Self::Failed);
+        }
+        if let Some(class) = text.strip_suffix("xx") {
+            return class.parse().ok().filter(|c| (1..=5).contains(c)).map(Self::Class).ok_or_else(invalid);
+        }
+        text.parse().ok().filter(|c| (100..600).contains(c)).map(Self::Code).ok_or_else(invalid)
+    }
+
+    fn matches(self, entry: &HttpEntry) -> bool {
+        match (self, entry.status) {
+            (Self::Code(code), Some(status)) => status == code,
+            (Self::Class(class), Some(status)) => status / 100 == class,
+            (Self::Failed, status) => status.is_none() || entry.error.is_some(),
+            _ => false,
+        }
+    }
+}
+
+/// Filters for listing captured HTTP requests
+#[derive(Debug, Default)]
+pub struct HttpFilter<'a> {
+    pub tab_id: Option<i64>,
+    pub url_pattern: Option<&'a str>,
+    pub status: Option<StatusFilter>,
+    pub resource_type: Option<&'a str>,
+    pub limit: Option<usize>,
+}
+
+/// Filters for listing captured connections
+#[derive(Debug, Default)]
+pub struct WebSocketFilter<'a> {
+    pub url_pattern: Option<&'a str>,
+    pub include_messages: bool,
+    pub limit: Option<usize>,
+}
+
+// ============================================================================
+// Network Capture
+// ============================================================================
+
+pub struct NetworkCapture {
+    state: RwLock<CaptureState>,
+    policy: RwLock<(CapturePolicy, Redactor)>,
+    policy_path: Option<PathBuf>,
+}
+
+impl NetworkCapture {
+    /// Initialize with the policy saved in ~/.agent-browser/network_policy.json
+    pub fn new() -> Result<Self> {
+        let policy_path = storage::data_dir()?.join("network_policy.json");
+        let policy = if policy_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&policy_path)?)?
+        } else {
+            CapturePolicy::default()
+        };
+        Self::with_policy(policy, Some(policy_path))
+    }
+
+    fn with_policy(policy: CapturePolicy, policy_path: Option<PathBuf>) -> Result<Self> {
+        let redactor = Redactor::new(&policy.redaction)?;
+        Ok(Self {
+            state: RwLock::new(CaptureState::default()),
+            policy: RwLock::new((policy, redactor)),
+            policy_path,
+        })
+    }
+
+    /// Current capture policy
+    pub async fn policy(&self) -> CapturePolicy {
+        self.policy.read().await.0.clone()
+    }
+
+    /// Replace the capture policy (validated before it takes effect) and persist it
+    pub async fn set_policy(&self, policy: CapturePolicy) -> Result<()> {
+        let redactor = Redactor::new(&policy.redaction)?;
+        if let Some(path) = &self.policy_path {
+            storage::write_private_file(path, serde_json::to_string_pretty(&policy)?)?;
+        }
+        info!(
+            "Network capture policy updated: bodies={} max_body_bytes={}",
+            policy.capture_bodies, policy.max_body_bytes
+        );
+        *self.policy.write().await = (policy, redactor);
+        Ok(())
+    }
+
+    /// Record a completed HTTP request from the extension
+    pub async fn record_http(&self, data: &serde_json::Value) -> Result<(), String> {
+        let report: HttpReport = serde_json::from_value(data.clone())
+            .map_err(|e| format!("Invalid http event: {}", e))?;
+
+        let entry = {
+            let policy = self.policy.read().await;
+            let (policy, redactor) = (&policy.0, &policy.1);
+
+            let mut redactions = 0;
+            let (url, n) = redactor.redact_text(&report.url);
+            redactions += n;
+            let mut request_headers = report.request_headers;
+            redactions += redactor.redact_headers(&mut request_headers);
+            let mut response_headers = report.response_headers;
+            redactions += redactor.redact_headers(&mut response_headers);
+
+            let mut capture_body = |body: Option<String>| {
+                let body = body.filter(|_| policy.capture_bodies)?;
+                let (content, n) = redactor.redact_text(&body);
+                redactions += n;
+                Some(truncate_body(content, body.len(), policy.max_body_bytes))
+            };
+            let request_body = capture_body(report.request_body);
+            let response_body = capture_body(report.response_body);
+
+            HttpEntry {
+                id: report.id,
+                url,
+                method: report.method,
+                status: report.status,
+                resource_type: report.resource_type,
+                tab_id: report.tab_id,
+                started_at: report
+                    .timestamp
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+                duration_ms: report.duration_ms,
+                size: report.size,
+                request_headers,
+                response_headers,
+                request_body,
+                response_body,
+                redactions,
+                error: report.error,
+            }
+        };
+
+        let mut state = self.state.write().await;
+        if state.http.len() >= MAX_HTTP_ENTRIES {
+            state.http.pop_front();
+        }
+        state.http.push_back(entry);
+        Ok(())
+    }
+
+    /// Look up captured HTTP entries by id or URL substring, newest first
+    pub async fn find_http(&self, id: Option<&str>, url_pattern: Option<&str>, limit: usize) -> Vec<HttpEntry> {
+        let state = self.state.read().await;
+        state
+            .http
+            .iter()
+            .rev()
+            .filter(|e| id.is_none_or(|id| e.id == id))
+            .filter(|e| url_pattern.is_none_or(|p| e.url.contains(p)))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Captured HTTP requests matching the filter, newest first
+    pub async fn list_http(&self, filter: &HttpFilter<'_>) -> Vec<HttpSummary> {
+        let state = self.state.read().await;
+        state
+            .http
+            .iter()
+            .rev()
+            .filter(|e| filter.tab_id.is_none_or(|tab| e.tab_id == Some(tab)))
+            .filter(|e| filter.url_pattern.is_none_or(|p| e.url.contains(p)))
+            .filter(|e| filter.status.is_none_or(|s| s.matches(e)))
+            .filter(|e| filter.resource_type.is_none_or(|t| e.resource_type.as_deref() == Some(t)))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|e| HttpSummary {
+                id: e.id.clone(),
+                method: e.method.clone(),
+                url: e.url.clone(),
+                status: e.status,
+                resource_type: e.resource_type.clone(),
+                started_at: e.started_at,
+                duration_ms: e.duration_ms,
+                size: e.size,
+                error: e.error.clone(),
+            })
+            .collect()
+    }
+
+    /// Record a WebSocket lifecycle or message event from the extension
+    pub async fn record_websocket(&self, data: &serde_json::Value) -> Result<(), String> {
+        let mut report: WebSocketReport = serde_json::from_value(data.clone())
+            .map_err(|e| format!("Invalid websocket event: {}", e))?;
+        if let Some(payload) = report.payload.take() {
+            report.payload = Some(self.policy.read().await.1.redact_text(&payload).0);
+        }
+        let timestamp = report.timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+
+        let mut state = self.state.write().await;
+        let existing = state.websockets.iter().position(|c| c.id == report.id);
+
+        let conn = match existing {
+            Some(index) => &mut state.websockets[index],
+            None => {
+                if state.websockets.len() >= MAX_WEBSOCKETS {
+                    state.websockets.pop_front();
+                }
+                state.websockets.push_back(WebSocketConnection {
+                    id: report.id.clone(),
+                    url: report.url.clone().unwrap_or_default(),
+                    tab_id: report.tab_id,
+                    status: WebSocketStatus::Connecting,
+                    opened_at: timestamp,
+                    closed_at: None,
+                    close_code: None,
+                    close_reason: None,
+                    sent: MessageStats::default(),
+                    received: MessageStats::default(),
+                    recent_messages: VecDeque::new(),
+                });
+                state.websockets.back_mut().expect("just pushed")
+            }
+        };
+
+        match report.phase.as_str() {
+            "connecting" => conn.status = WebSocketStatus::Connecting,
+            "open" => {
+                conn.status = WebSocketStatus::Open;
+                if let Some(url) = report.url {
+                    conn.url = url;
+                }
+            }
+            "message" => {
+                let direction = report.direction.unwrap_or(Direction::Received);
+                let size = report
+                    .size
+                    .or_else(|| report.payload.as_ref().map(|p| p.len() as u64))
+                    .unwrap_or(0);
+                let stats = match direction {
+                    Direction::Sent => &mut conn.sent,
+                    Direction::Received => &mut conn.received,
+                };
+                stats.count += 1;
+                stats.bytes += size;
+
+                if conn.recent_messages.len() >= MAX_RECENT_MESSAGES {
+                    conn.recent_messages.pop_front();
+                }
+                conn.recent_messages.push_back(MessageSummary {
+                    direction,
+                    timestamp,
+                    size,
+                    binary: report.binary,
+                    preview: if report.binary {
+                        None
+                    } else {
+                        report.payload.as_deref().map(preview)
+                    },
+                });
+            }
+            "close" => {
+                conn.status = WebSocketStatus::Closed;
+                conn.closed_at = Some(timestamp);
+                conn.close_code = report.code;
+                conn.close_reason = report.reason;
+            }
+            "error" => {
+                conn.status = WebSocketStatus::Error;
+                conn.closed_at = Some(timestamp);
+                conn.close_reason = report.reason;
+            }
+            other => return Err(format!("Unknown websocket phase: {}", other)),
+        }
+
+        Ok(())
+    }
+
+    /// Record a WebRTC peer connection event from the extension
+    pub async fn record_webrtc(&self, data: &serde_json::Value) -> Result<(), String> {
+        let connection_id = data
+            .get("connection_id")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing connection_id in webrtc event")?;
+        let event = WebRtcEvent {
+            connection_id: connection_id.to_string(),
+            tab_id: data.get("tabId").and_then(|v| v.as_i64()),
+            event: data
+                .get("event")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            state: data.get("state").and_then(|v| v.as_str()).map(str::to_string),
+            timestamp: data
+                .get("timestamp")
+                .and_then(|v| v.as_i64())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+            detail: data.get("detail").cloned().unwrap_or_default(),
+        };
+
+        let mut state = self.state.write().await;
+        if state.webrtc.len() >= MAX_WEBRTC_EVENTS {
+            state.webrtc.pop_front();
+        }
+        state.webrtc.push_back(event);
+        Ok(())
+    }
+
+    /// Captured WebSocket connections, newest first
+    pub async fn list_websockets(&self, filter: &WebSocketFilter<'_>) -> Vec<WebSocketConnection> {
+        let state = self.state.read().await;
+        state
+            .websockets
+            .iter()
+            .rev()
+            .filter(|c| filter.url_pattern.is_none_or(|p| c.url.contains(p)))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|c| {
+                let mut c = c.clone();
+                if !filter.include_messages {
+                    c.recent_messages.clear();
+                }
+                c
+            })
+            .collect()
+    }
+
+    /// Captured WebRTC events, newest first
+    pub async fn list_webrtc_events(&self, limit: Option<usize>) -> Vec<WebRtcEvent> {
+        let state = self.state.read().await;
+        state
+            .webrtc
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Cap a (redacted) body at `max_bytes`, respecting char boundaries
+fn truncate_body(content: String, original_size: usize, max_bytes: usize) -> CapturedBody {
+    if content.len() <= max_bytes {
+        return CapturedBody {
+            content,
+            truncated: false,
+            original_size,
+        };
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    CapturedBody {
+        content: content[..end].to_string(),
+        truncated: true,
+        original_size,
+    }
+}
+
+/// Shorten a text payload for display
+fn preview(payload: &str) -> String {
+    if payload.chars().count() <= MAX_PREVIEW_CHARS {
+        payload.to_string()
+    } else {
+        let truncated: String = payload.chars().take(MAX_PREVIEW_CHARS).collect();
+        format!("{}…", truncated)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(policy: CapturePolicy) -> NetworkCapture {
+        NetworkCapture::with_policy(policy, None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_websocket_lifecycle_and_stats() {
+        let capture = capture(CapturePolicy::default());
+        let events = [
+            serde_json::json!({"id": "ws1", "phase": "open", "url": "wss://example.com/live"}),
+            serde_json::json!({"id": "ws1", "phase": "message", "direction": "sent", "payload": "hello"}),
+            serde_json::json!({"id": "ws1", "phase": "message", "direction": "received", "payload": "x".repeat(500)}),
+            serde_json::json!({"id": "ws1", "phase": "close", "code": 1000}),
+        ];
+        for event in &events {
+            capture.record_websocket(event).await.unwrap();
+        }
+
+        let conns = capture
+            .list_websockets(&WebSocketFilter { include_messages: true, ..Default::default() })
+            .await;
+        assert_eq!(conns.len(), 1);
+        let conn = &conns[0];
+        assert_eq!(conn.status, WebSocketStatus::Closed);
+        assert_eq!(conn.sent.count, 1);
+        assert_eq!(conn.received.bytes, 500);
+        // Long payloads are reduced to a preview
+        let preview = conn.recent_messages[1].preview.as_ref().unwrap();
+        assert_eq!(preview.chars().count(), MAX_PREVIEW_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_oldest_connections_dropped() {
+        let capture = capture(CapturePolicy::default());
+        for i in 0..(MAX_WEBSOCKETS + 5) {
+            let event = serde_json::json!({"id": format!("ws{}", i), "phase": "open", "url": "wss://a"});
+            capture.record_websocket(&event).await.unwrap();
+        }
+
+        let conns = capture.list_websockets(&WebSocketFilter::default()).await;
+        assert_eq!(conns.len(), MAX_WEBSOCKETS);
+        assert!(conns.iter().all(|c| c.id != "ws0"));
+    }
+
+    #[tokio::test]
+    async fn test_http_bodies_respect_policy_and_redaction() {
+        let event = serde_json::json!({
+            "id": "r1",
+            "url": "https://api.example.com/login?token=abc",
+            "method": "POST",
+            "status": 200,
+            "requestHeaders": {"Authorization": "Bearer abc", "Content-Type": "application/json"},
+            "requestBody": "{\"user\":\"bob\",\"password\":\"hunter2\"}",
+            "responseBody": "x".repeat(100)
+        });
+
+        // Bodies are dropped unless the policy asks for them
+        let off = capture(CapturePolicy::default());
+        off.record_http(&event).await.unwrap();
+        let entry = &off.find_http(Some("r1"), None, 1).await[0];
+        assert!(entry.request_body.is_none());
+        assert_eq!(entry.url, "https://api.example.com/login?token=[REDACTED]");
+        assert_eq!(entry.request_headers["Authorization"], "[REDACTED]");
+
+        let on = capture(CapturePolicy {
+            capture_bodies: true,
+            max_body_bytes: 10,
+            ..Default::default()
+        });
+        on.record_http(&event).await.unwrap();
+        let entry = &on.find_http(Some("r1"), None, 1).await[0];
+        let request_body = entry.request_body.as_ref().unwrap();
+        assert!(!request_body.content.contains("hunter2"));
+        let response_body = entry.response_body.as_ref().unwrap();
+        assert!(response_body.truncated);
+        assert_eq!(response_body.content.len(), 10);
+        assert_eq!(response_body.original_size, 100);
+    }
+
+    #[tokio::test]
+    async fn test_list_http_filters_by_tab_status_and_type() {
+        let capture = capture(CapturePolicy::default());
+        let events = [
+            serde_json::json!({"id": "1", "url": "https://shop.test/api/cart", "status": 200, "type": "xmlhttprequest", "tabId": 3}),
+            serde_json::json!({"id": "2", "url": "https://shop.test/api/price", "status": 404, "type": "xmlhttprequest", "tabId": 3}),
+            serde_json::json!({"id": "3", "url": "https://cdn.test/app.js", "error": "net::ERR_BLOCKED_BY_CLIENT", "type": "script", "tabId": 3}),
+            serde_json::json!({"id": "4", "url": "https://shop.test/api/cart", "status": 500, "type": "xmlhttprequest", "tabId": 8}),
+        ];
+        for event in &events {
+            capture.record_http(event).await.unwrap();
+        }
+
+        let ids = |entries: Vec<HttpSummary>| entries.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        let tab = |filter: HttpFilter<'static>| HttpFilter { tab_id: Some(3), ..filter };
+        assert_eq!(ids(capture.list_http(&tab(HttpFilter::default())).await), ["3", "2", "1"]);
+        assert_eq!(ids(capture.list_http(&HttpFilter { url_pattern: Some("/api/cart"), ..Default::default() }).await), ["4", "1"]);
+        let status = |value| Some(StatusFilter::parse(&value).unwrap());
+        assert_eq!(ids(capture.list_http(&tab(HttpFilter { status: status(serde_json::json!("4xx")), ..Default::default() })).await), ["2"]);
+        assert_eq!(ids(capture.list_http(&tab(HttpFilter { status: status(serde_json::json!("failed")), ..Default::default() })).await), ["3"]);
+        assert_eq!(ids(capture.list_http(&HttpFilter { status: status(serde_json::json!(500)), ..Default::default() }).await), ["4"]);
+        assert_eq!(ids(capture.list_http(&tab(HttpFilter { resource_type: Some("script"), ..Default::default() })).await), ["3"]);
+
+        for invalid in [serde_json::json!("6xx"), serde_json::json!(42), serde_json::json!("ok"), serde_json::json!(true)] {
+            assert!(StatusFilter::parse(&invalid).is_err());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/*!
+ * Origin Checks and CORS
+ *
+ * Any page open in the browser can try to reach the local ports. Browsers
+ * always send `Origin` on WebSocket upgrades and cross-origin HTTP requests,
+ * so:
+ * - the extension WebSocket only accepts the extension's own
+ *   `chrome-extension://<id>` origin (`AGENT_BROWSER_EXTENSION_IDS` /
+ *   `--extension-id`, default the ID pinned by the manifest key)
+ * - `/mcp` WebSocket and HTTP requests that carry an `Origin` must name one
+ *   of the configured CORS origins (`MCP_CORS_ORIGINS` / `--cors-origin`,
+ *   default none); requests without one come from native clients and pass
+ *
+ * Allowed HTTP origins get CORS response headers, including preflights.
+ */
+
+use anyhow::{bail, Result};
+
+/// ID derived from the `key` in extension/public/manifest.json
+pub const DEFAULT_EXTENSION_ID: &str = "jephebfdidlihjhgghkkffaaihnhidfj";
+
+pub const ALLOW_METHODS: &str = "GET, POST, DELETE, OPTIONS";
+pub const ALLOW_HEADERS: &str = "Authorization, Content-Type, Accept, Mcp-Session-Id, MCP-Protocol-Version, Last-Event-ID";
+pub const EXPOSE_HEADERS: &str = "Mcp-Session-Id, WWW-Authenticate";
+pub const PREFLIGHT_MAX_AGE_SECS: u64 = 600;
+
+/// Chrome extension IDs are 32 letters from a to p
+pub fn parse_extension_id(id: &str) -> Result<String> {
+    let id = id.trim().trim_start_matches("chrome-extension://").trim_end_matches('/');
+    if id.len() != 32 || !id.bytes().all(|b| (b'a'..=b'p').contains(&b)) {
+        bail!("Invalid extension ID '{}' (expected 32 letters a-p)", id);
+    }
+    Ok(id.to_string())
+}
+
+/// Normalize a CORS origin to `scheme://host[:port]`
+pub fn parse_cors_origin(origin: &str) -> Result<String> {
+    let origin = origin.trim().trim_end_matches('/');
+    let Some((scheme, host)) = origin.split_once("://") else {
+        bail!("Invalid CORS origin '{}' (expected scheme://host[:port])", origin);
+    };
+    if scheme.is_empty() || host.is_empty() || host.contains('/') || origin == "*" {
+        bail!("Invalid CORS origin '{}' (expected scheme://host[:port])", origin);
+    }
+    Ok(origin.to_ascii_lowercase())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginPolicy {
+    extension_origins: Vec<String>,
+    cors_origins: Vec<String>,
+}
+
+impl OriginPolicy {
+    pub fn new(extension_ids: &[String], cors_origins: &[String]) -> Self {
+        Self {
+            extension_origins: extension_ids
+                .iter()
+                .map(|id| format!("chrome-extension://{}", id))
+                .collect(),
+            cors_origins: cors_origins.to_vec(),
+        }
+    }
+
+    /// The extension socket requires the extension's own origin
+    pub fn allows_extension(&self, origin: Option<&str>) -> bool {
+        origin.is_some_and(|o| self.extension_origins.iter().any(|allowed| allowed == o.trim_end_matches('/')))
+    }
+
+    /// MCP surfaces accept native clients (no Origin) and configured CORS origins
+    pub fn allows_client(&self, origin: Option<&str>) -> bool {
+        origin.is_none_or(|o| self.cors_origins.contains(&o.trim_end_matches('/').to_ascii_lowercase()))
+    }
+}
+
+/// Headers added to every response to an allowed cross-origin request
+pub fn cors_headers(origin: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("Access-Control-Allow-Origin", origin.to_string()),
+        ("Access-Control-Expose-Headers", EXPOSE_HEADERS.to_string()),
+        ("Vary", "Origin".to_string()),
+    ]
+}
+
+/// Headers answering a CORS preflight (`OPTIONS`)
+pub fn preflight_headers() -> Vec<(&'static str, String)> {
+    vec![
+        ("Access-Control-Allow-Methods", ALLOW_METHODS.to_string()),
+        ("Access-Control-Allow-Headers", ALLOW_HEADERS.to_string()),
+        ("Access-Control-Max-Age", PREFLIGHT_MAX_AGE_SECS.to_string()),
+    ]
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> OriginPolicy {
+        OriginPolicy::new(
+            &[DEFAULT_EXTENSION_ID.to_string()],
+            &[parse_cors_origin("http://localhost:5173/").unwrap()],
+        )
+    }
+
+    #[test]
+    fn test_extension_socket_requires_extension_origin() {
+        let policy = policy();
+        assert!(policy.allows_extension(Some("chrome-extension://jephebfdidlihjhgghkkffaaihnhidfj")));
+        assert!(!policy.allows_extension(Some("chrome-extension://aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")));
+        assert!(!policy.allows_extension(Some("https://evil.example")));
+        assert!(!policy.allows_extension(None));
+    }
+
+    #[test]
+    fn test_clients_need_no_origin_or_a_cors_origin() {
+        let policy = policy();
+        assert!(policy.allows_client(None));
+        assert!(policy.allows_client(Some("http://localhost:5173")));
+        assert!(policy.allows_client(Some("HTTP://LOCALHOST:5173")));
+        assert!(!policy.allows_client(Some("http://localhost:5174")));
+        assert!(!policy.allows_client(Some("null")));
+    }
+
+    #[test]
+    fn test_parsing() {
+        assert!(parse_extension_id("chrome-extension://jephebfdidlihjhgghkkffaaihnhidfj/").is_ok());
+        assert!(parse_extension_id("jephebfdidlihjhgghkkffaaihnhidfz").is_err());
+        assert_eq!(parse_cors_origin("https://App.example/").unwrap(), "https://app.example");
+        assert!(parse_cors_origin("*").is_err());
+        assert!(parse_cors_origin("https://app.example/path").is_err());
+        assert!(parse_cors_origin("app.example").is_err());
+    }
+}
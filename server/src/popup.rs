@@ -0,0 +1,229 @@
+/*!
+ * Popup Follow Mode
+ *
+ * Tracks which tab each MCP session is attached to. With follow mode on,
+ * a tab opened from the session's current tab (OAuth popups, target=_blank
+ * links) becomes the session's target, and closing it returns the session
+ * to the opener. Switches are queued and reported in the next tool result
+ * so agents know their commands now go somewhere else.
+ *
+ * It also records which tabs each session has sent commands to, so that
+ * something the page starts on its own, like a passkey request, can be
+ * attributed to the one session driving that tab.
+ */
+
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+#[derive(Default)]
+struct Targets {
+    follow: HashSet<String>,
+    // Attached tabs per session; the last entry is the current target
+    stacks: HashMap<String, Vec<i64>>,
+    // Switches not yet reported to the session
+    pending: HashMap<String, Vec<Value>>,
+    // Tabs each session has sent commands to; None is the active tab
+    driven: HashMap<String, HashSet<Option<i64>>>,
+}
+
+#[derive(Default)]
+pub struct SessionTargets {
+    inner: RwLock<Targets>,
+}
+
+impl SessionTargets {
+    pub async fn set_follow(&self, session: &str, enabled: bool) {
+        let mut inner = self.inner.write().await;
+        if enabled {
+            inner.follow.insert(session.to_string());
+        } else {
+            inner.follow.remove(session);
+            inner.stacks.remove(session);
+            inner.pending.remove(session);
+        }
+    }
+
+    pub async fn is_following(&self, session: &str) -> bool {
+        self.inner.read().await.follow.contains(session)
+    }
+
+    /// Tab commands from this session should target (None = the active tab)
+    pub async fn current_tab(&self, session: &str) -> Option<i64> {
+        self.inner
+            .read()
+            .await
+            .stacks
+            .get(session)
+            .and_then(|stack| stack.last().copied())
+    }
+
+    /// A tab was opened; attach following sessions whose current tab opened it.
+    /// Sessions not yet attached to a tab follow popups from the active tab if
+    /// they were the last to drive the browser.
+    pub async fn on_tab_opened(&self, tab_id: i64, opener: Option<i64>, url: Option<&str>, active_session: Option<&str>) {
+        let Some(opener) = opener else {
+            return;
+        };
+        let mut inner = self.inner.write().await;
+        let Targets { follow, stacks, pending, .. } = &mut *inner;
+
+        for session in follow.iter() {
+            let stack = stacks.entry(session.clone()).or_default();
+            let owns_opener = match stack.last() {
+                Some(current) => *current == opener,
+                None => active_session == Some(session.as_str()),
+            };
+            if !owns_opener {
+                continue;
+            }
+            if stack.is_empty() {
+                stack.push(opener);
+            }
+            stack.push(tab_id);
+            pending.entry(session.clone()).or_default().push(serde_json::json!({
+                "event": "attached",
+                "tabId": tab_id,
+                "openerTabId": opener,
+                "url": url,
+            }));
+        }
+    }
+
+    /// A session sent a command to a tab (None: whichever tab is active)
+    pub async fn record_command(&self, session: &str, tab_id: Option<i64>) {
+        let mut inner = self.inner.write().await;
+        inner.driven.entry(session.to_string()).or_default().insert(tab_id);
+    }
+
+    /// The one session driving any of `tabs`, given as (tab id, whether it is
+    /// the active tab). A tab belongs to sessions attached to it or that sent
+    /// it commands; the active tab also to sessions that sent commands without
+    /// a tab and to `active_session`, which drove the browser last. No owner,
+    /// or more than one, is an error.
+    pub async fn owner_of(&self, tabs: &[(i64, bool)], active_session: Option<&str>) -> Result<String, String> {
+        if tabs.is_empty() {
+            return Err("The request can't be traced to a tab".to_string());
+        }
+        let inner = self.inner.read().await;
+        let mut owners: Vec<&str> = Vec::new();
+        for (tab_id, active) in tabs {
+            let attached = inner.stacks.iter().filter(|(_, stack)| stack.last() == Some(tab_id)).map(|(s, _)| s);
+            let driving = inner
+                .driven
+                .iter()
+                .filter(|(_, driven)| driven.contains(&Some(*tab_id)) || (*active && driven.contains(&None)))
+                .map(|(s, _)| s);
+            let last = active_session.filter(|_| *active);
+            for session in attached.chain(driving).map(String::as_str).chain(last) {
+                if !owners.contains(&session) {
+                    owners.push(session);
+                }
+            }
+        }
+        let tab_list = tabs.iter().map(|(tab_id, _)| tab_id.to_string()).collect::<Vec<_>>().join(", ");
+        match owners.as_slice() {
+            [] => Err(format!("No MCP session is driving tab {}", tab_list)),
+            [owner] => Ok(owner.to_string()),
+            _ => {
+                owners.sort_unstable();
+                Err(format!(
+                    "Tab {} is driven by more than one session ({}), so the request can't be attributed",
+                    tab_list,
+                    owners.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Drop a session that has ended
+    pub async fn forget(&self, session: &str) {
+        let mut inner = self.inner.write().await;
+        inner.follow.remove(session);
+        inner.stacks.remove(session);
+        inner.pending.remove(session);
+        inner.driven.remove(session);
+    }
+
+    /// A tab was closed; sessions attached to it fall back to the previous tab
+    pub async fn on_tab_closed(&self, tab_id: i64) {
+        let mut inner = self.inner.write().await;
+        let Targets { stacks, pending, driven, .. } = &mut *inner;
+        for tabs in driven.values_mut() {
+            tabs.remove(&Some(tab_id));
+        }
+
+        for (session, stack) in stacks.iter_mut() {
+            let was_current = stack.last() == Some(&tab_id);
+            stack.retain(|t| *t != tab_id);
+            if was_current {
+                pending.entry(session.clone()).or_default().push(serde_json::json!({
+                    "event": "returned",
+                    "closedTabId": tab_id,
+                    "tabId": stack.last(),
+                }));
+            }
+        }
+    }
+
+    /// Switches since the last tool result for this session
+    pub async fn take_switches(&self, session: &str) -> Vec<Value> {
+        self.inner.write().await.pending.remove(session).unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_follow_popup_and_return() {
+        let targets = SessionTargets::default();
+        targets.set_follow("tcp:a", true).await;
+
+        // Popup from the active tab attaches the session that drove the browser
+        targets.on_tab_opened(7, Some(3), Some("https://idp.example/auth"), Some("tcp:a")).await;
+        assert_eq!(targets.current_tab("tcp:a").await, Some(7));
+        let switches = targets.take_switches("tcp:a").await;
+        assert_eq!(switches[0]["event"], "attached");
+        assert_eq!(switches[0]["openerTabId"], 3);
+
+        // Unrelated tabs are ignored
+        targets.on_tab_opened(9, Some(1), None, Some("tcp:a")).await;
+        assert_eq!(targets.current_tab("tcp:a").await, Some(7));
+
+        targets.on_tab_closed(7).await;
+        assert_eq!(targets.current_tab("tcp:a").await, Some(3));
+        assert_eq!(targets.take_switches("tcp:a").await[0]["tabId"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_owner_of_tab() {
+        let targets = SessionTargets::default();
+        targets.record_command("tcp:a", Some(4)).await;
+        targets.record_command("tcp:b", None).await;
+
+        assert_eq!(targets.owner_of(&[(4, false)], None).await.unwrap(), "tcp:a");
+        // The active tab belongs to sessions that address it implicitly
+        assert_eq!(targets.owner_of(&[(9, true)], Some("tcp:b")).await.unwrap(), "tcp:b");
+        assert!(targets.owner_of(&[(4, true)], None).await.unwrap_err().contains("tcp:a, tcp:b"));
+        assert!(targets.owner_of(&[(9, false)], Some("tcp:b")).await.is_err());
+        assert!(targets.owner_of(&[], Some("tcp:b")).await.is_err());
+
+        targets.on_tab_closed(4).await;
+        targets.forget("tcp:b").await;
+        assert!(targets.owner_of(&[(4, true)], None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_without_follow_are_untouched() {
+        let targets = SessionTargets::default();
+        targets.on_tab_opened(7, Some(3), None, Some("stdio")).await;
+        assert_eq!(targets.current_tab("stdio").await, None);
+        assert!(targets.take_switches("stdio").await.is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+/*!
+ * Site Reachability Preflight
+ *
+ * Checks DNS resolution, TCP connect, TLS handshake and the HTTP status of a
+ * URL from the server before the browser is pointed at it. Each stage is
+ * timed and reported separately so agents can tell "site is down" from
+ * "page rendered blank" without paying for a navigation.
+ */
+
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+pub const MAX_TIMEOUT_MS: u64 = 30_000;
+const MAX_RESPONSE_HEAD_BYTES: usize = 16 * 1024;
+const USER_AGENT: &str = "agent-browser-preflight/0.1";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Target {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path and query, always starting with '/'
+    pub path: String,
+}
+
+impl Target {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!("Unsupported URL '{}': expected http:// or https://", url));
+        };
+
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        // Credentials in the URL are never sent
+        let authority = authority.rsplit('@').next().unwrap_or_default();
+
+        let default_port = if tls { 443 } else { 80 };
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, after) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("Invalid IPv6 host in '{}'", url))?;
+            (host, after.strip_prefix(':'))
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| format!("Invalid port in '{}'", url))?,
+            None => default_port,
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in '{}'", url));
+        }
+
+        Ok(Self {
+            tls,
+            host: host.to_ascii_lowercase(),
+            port,
+            path,
+        })
+    }
+
+    pub fn host_header(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.port == if self.tls { 443 } else { 80 } {
+            host
+        } else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+}
+
+/// Client TLS configuration verifying against the webpki roots
+pub fn tls_connector() -> TlsConnector {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    let config = CONFIG.get_or_init(|| {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        Arc::new(ClientConfig::builder().with_root_certificates(roots).with_no_client_auth())
+    });
+    TlsConnector::from(Arc::clone(config))
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+/// Run every stage against the URL; stops at the first failing stage
+pub async fn check(target: &Target, timeout_ms: u64) -> Value {
+    let limit = Duration::from_millis(timeout_ms);
+    let mut report = serde_json::json!({
+        "host": target.host,
+        "port": target.port,
+    });
+
+    // DNS
+    let start = Instant::now();
+    let addrs: Vec<SocketAddr> = match timeout(limit, tokio::net::lookup_host((target.host.as_str(), target.port))).await {
+        Ok(Ok(addrs)) => addrs.collect(),
+        Ok(Err(e)) => return failed(report, "dns", "dns_failed", start, e.to_string()),
+        Err(_) => return failed(report, "dns", "dns_failed", start, "timed out".to_string()),
+    };
+    if addrs.is_empty() {
+        return failed(report, "dns", "dns_failed", start, "no addresses".to_string());
+    }
+    report["dns"] = serde_json::json!({
+        "ok": true,
+        "ms": elapsed_ms(start),
+        "addresses": addrs.iter().map(|a| a.ip().to_string()).collect::<Vec<_>>(),
+    });
+
+    // TCP connect, trying each address in turn
+    let start = Instant::now();
+    let mut last_error = String::new();
+    let mut connected = None;
+    for addr in &addrs {
+        match timeout(limit, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => {
+                connected = Some((stream, *addr));
+                break;
+            }
+            Ok(Err(e)) => last_error = format!("{}: {}", addr, e),
+            Err(_) => last_error = format!("{}: timed out", addr),
+        }
+    }
+    let Some((stream, addr)) = connected else {
+        return failed(report, "connect", "connect_failed", start, last_error);
+    };
+    report["connect"] = serde_json::json!({ "ok": true, "ms": elapsed_ms(start), "address": addr.to_string() });
+
+    if !target.tls {
+        return http_stage(report, stream, target, limit).await;
+    }
+
+    // TLS handshake with certificate verification against the webpki roots
+    let start = Instant::now();
+    let server_name = match ServerName::try_from(target.host.clone()) {
+        Ok(name) => name,
+        Err(e) => return failed(report, "tls", "tls_failed", start, e.to_string()),
+    };
+    let tls_stream = match timeout(limit, tls_connector().connect(server_name, stream)).await {
+        Ok(Ok(tls_stream)) => tls_stream,
+        Ok(Err(e)) => return failed(report, "tls", "tls_failed", start, e.to_string()),
+        Err(_) => return failed(report, "tls", "tls_failed", start, "timed out".to_string()),
+    };
+    let (_, connection) = tls_stream.get_ref();
+    report["tls"] = serde_json::json!({
+        "ok": true,
+        "ms": elapsed_ms(start),
+        "protocol": connection.protocol_version().map(|v| format!("{:?}", v)),
+        "alpn": connection.alpn_protocol().map(|p| String::from_utf8_lossy(p).into_owned()),
+    });
+
+    http_stage(report, tls_stream, target, limit).await
+}
+
+/// Send a GET and read only the response head
+async fn http_stage<S>(mut report: Value, mut stream: S, target: &Target, limit: Duration) -> Value
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let start = Instant::now();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        target.path,
+        target.host_header(),
+        USER_AGENT
+    );
+
+    let head = timeout(limit, async {
+        stream.write_all(request.as_bytes()).await?;
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok::<_, std::io::Error>(buf);
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            if buf.windows(4).any(|w| w == b"\r\n\r\n") || buf.len() >= MAX_RESPONSE_HEAD_BYTES {
+                return Ok(buf);
+            }
+        }
+    })
+    .await;
+
+    let buf = match head {
+        Ok(Ok(buf)) => buf,
+        Ok(Err(e)) => return failed(report, "http", "http_failed", start, e.to_string()),
+        Err(_) => return failed(report, "http", "http_failed", start, "timed out".to_string()),
+    };
+
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut response = httparse::Response::new(&mut headers);
+    let status = match response.parse(&buf) {
+        Ok(_) => response.code,
+        Err(e) => return failed(report, "http", "http_failed", start, format!("Invalid HTTP response: {}", e)),
+    };
+    let Some(status) = status else {
+        return failed(report, "http", "http_failed", start, "Incomplete HTTP response".to_string());
+    };
+    let header = |name: &str| {
+        response
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| String::from_utf8_lossy(h.value).into_owned())
+    };
+
+    report["http"] = serde_json::json!({
+        "ok": status < 400,
+        "ms": elapsed_ms(start),
+        "status": status,
+        "reason": response.reason,
+        "location": header("location"),
+        "contentType": header("content-type"),
+        "server": header("server"),
+    });
+    report["reachable"] = serde_json::json!(true);
+    report["verdict"] = serde_json::json!(verdict_for_status(status));
+    report
+}
+
+/// GET a small text resource (robots.txt and the like) and return its status
+/// and up to `max_bytes` of body. Redirects are not followed.
+pub async fn fetch_text(target: &Target, timeout_ms: u64, max_bytes: usize) -> Result<(u16, String), String> {
+    let limit = Duration::from_millis(timeout_ms);
+    let exchange = async {
+        let stream = TcpStream::connect((target.host.as_str(), target.port))
+            .await
+            .map_err(|e| format!("connect failed: {}", e))?;
+        if target.tls {
+            let server_name = ServerName::try_from(target.host.clone()).map_err(|e| e.to_string())?;
+            let tls_stream = tls_connector()
+                .connect(server_name, stream)
+                .await
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+            read_text(tls_stream, target, max_bytes).await
+        } else {
+            read_text(stream, target, max_bytes).await
+        }
+    };
+    timeout(limit, exchange).await.map_err(|_| "timed out".to_string())?
+}
+
+async fn read_text<S>(mut stream: S, target: &Target, max_bytes: usize) -> Result<(u16, String), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: text/plain, */*\r\nAccept-Encoding: identity\r\nConnection: close\r\n\r\n",
+        target.path,
+        target.host_header(),
+        USER_AGENT
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+
+    // Head plus a little slack for chunk framing
+    let cap = MAX_RESPONSE_HEAD_BYTES + max_bytes + 1024;
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while buf.len() < cap {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut response = httparse::Response::new(&mut headers);
+    let head_len = match response.parse(&buf).map_err(|e| format!("Invalid HTTP response: {}", e))? {
+        httparse::Status::Complete(len) => len,
+        httparse::Status::Partial => return Err("Incomplete HTTP response".to_string()),
+    };
+    let status = response.code.ok_or("Incomplete HTTP response")?;
+    let chunked = response.headers.iter().any(|h| {
+        h.name.eq_ignore_ascii_case("transfer-encoding") && String::from_utf8_lossy(h.value).to_ascii_lowercase().contains("chunked")
+    });
+
+    let raw = &buf[head_len..];
+    let mut body = if chunked { dechunk(raw) } else { raw.to_vec() };
+    body.truncate(max_bytes);
+    Ok((status, String::from_utf8_lossy(&body).into_owned()))
+}
+
+/// Decode a chunked body, stopping quietly at the first incomplete chunk
+fn dechunk(mut raw: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    while let Some(line_end) = raw.windows(2).position(|w| w == b"\r\n") {
+        let size_field = String::from_utf8_lossy(&raw[..line_end]);
+        let Ok(size) = usize::from_str_radix(size_field.split(';').next().unwrap_or_default().trim(), 16) else {
+            break;
+        };
+        let start = line_end + 2;
+        if size == 0 {
+            break;
+        }
+        let end = (start + size).min(raw.len());
+        body.extend_from_slice(&raw[start..end]);
+        if end < start + size || end + 2 > raw.len() {
+            break;
+        }
+        raw = &raw[end + 2..];
+    }
+    body
+}
+
+fn verdict_for_status(status: u16) -> &'static str {
+    match status {
+        0..=299 => "ok",
+        300..=399 => "redirect",
+        400..=499 => "client_error",
+        _ => "server_error",
+    }
+}
+
+fn failed(mut report: Value, stage: &str, verdict: &str, start: Instant, error: String) -> Value {
+    report[stage] = serde_json::json!({ "ok": false, "ms": elapsed_ms(start), "error": error });
+    report["reachable"] = serde_json::json!(false);
+    report["verdict"] = serde_json::json!(verdict);
+    report
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = Target::parse("https://Example.com/login?next=%2F#top").unwrap();
+        assert_eq!(
+            target,
+            Target {
+                tls: true,
+                host: "example.com".to_string(),
+                port: 443,
+                path: "/login?next=%2F".to_string(),
+            }
+        );
+        assert_eq!(target.host_header(), "example.com");
+
+        let target = Target::parse("http://user:pw@[::1]:8080?q=1").unwrap();
+        assert_eq!((target.host.as_str(), target.port, target.path.as_str()), ("::1", 8080, "/?q=1"));
+        assert_eq!(target.host_header(), "[::1]:8080");
+
+        assert!(Target::parse("ftp://example.com").is_err());
+        assert!(Target::parse("https://example.com:99999/").is_err());
+        assert!(Target::parse("https:///path").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_http_status() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 503 Service Unavailable\r\nServer: test\r\nContent-Length: 0\r\n\r\n")
+                .await;
+        });
+
+        let target = Target::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let report = check(&target, 2000).await;
+        assert_eq!(report["reachable"], true);
+        assert_eq!(report["http"]["status"], 503);
+        assert_eq!(report["verdict"], "server_error");
+        assert_eq!(report["http"]["server"], "test");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_text_decodes_chunked_body() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nUser-a\r\n8\r\ngent: *\n\r\n0\r\n\r\n")
+                .await;
+        });
+
+        let target = Target::parse(&format!("http://127.0.0.1:{}/robots.txt", port)).unwrap();
+        let (status, body) = fetch_text(&target, 2000, 1024).await.unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, "User-agent: *\n");
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_connect_failure() {
+        // Bind then drop to get a port nothing listens on
+        let port = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let target = Target::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let report = check(&target, 2000).await;
+        assert_eq!(report["reachable"], false);
+        assert_eq!(report["verdict"], "connect_failed");
+        assert_eq!(report["dns"]["ok"], true);
+    }
+}
@@ -0,0 +1,330 @@
+/*!
+ * Coverage and Performance Traces
+ *
+ * `browser_profiling_start` has the extension attach Chrome's debugger
+ * (`chrome.debugger`, the DevTools Protocol backend for the user's tabs) to
+ * the tab and start any of precise JS coverage (`Profiler`), CSS rule usage
+ * (`CSS`) and a performance trace (`Tracing`). While attached, Chrome shows
+ * its "started debugging this browser" bar, and the tab can't also be
+ * profiled from an open DevTools window. `browser_profiling_stop` collects
+ * the data and detaches. The server writes the raw data to
+ * `profiles/<time>/` in the session's artifacts and returns a summary. The
+ * trace (`trace.json`) opens in the DevTools Performance panel or Perfetto.
+ * The summary lists used bytes per script and stylesheet, most unused
+ * first, and the trace's duration and long tasks.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{artifacts, storage};
+
+/// Categories the DevTools Performance panel records
+pub const DEFAULT_TRACE_CATEGORIES: &[&str] = &[
+    "-*",
+    "devtools.timeline",
+    "v8.execute",
+    "disabled-by-default-devtools.timeline",
+    "disabled-by-default-devtools.timeline.frame",
+    "disabled-by-default-devtools.timeline.stack",
+    "disabled-by-default-v8.cpu_profiler",
+    "toplevel",
+    "blink.console",
+    "blink.user_timing",
+    "latencyInfo",
+    "loading",
+];
+pub const COVERAGE_KINDS: &[&str] = &["js", "css"];
+/// Tasks at least this long block input (the RAIL/Lighthouse threshold)
+const LONG_TASK_US: u64 = 50_000;
+/// Resources and long tasks listed in a summary
+const SUMMARY_ITEMS: usize = 20;
+
+// ============================================================================
+// Coverage
+// ============================================================================
+
+/// Pop the open ranges ending by `until`, counting their used tails
+fn close_ranges(until: u64, open: &mut Vec<(u64, u64)>, position: &mut u64, used: &mut u64) {
+    while let Some(&(end, count)) = open.last() {
+        if end > until {
+            break;
+        }
+        if count > 0 {
+            *used += end.saturating_sub(*position);
+        }
+        *position = (*position).max(end);
+        open.pop();
+    }
+}
+
+/// Bytes inside ranges whose innermost covering range ran at least once.
+/// V8 block ranges nest, so an inner range's count overrides its parent's.
+fn used_bytes(ranges: &mut [(u64, u64, u64)]) -> u64 {
+    ranges.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    let mut used = 0;
+    let mut position = 0;
+    // (end, count) of the ranges enclosing `position`, innermost last
+    let mut open: Vec<(u64, u64)> = Vec::new();
+    for &(start, end, count) in ranges.iter() {
+        close_ranges(start, &mut open, &mut position, &mut used);
+        if let Some(&(_, parent)) = open.last() {
+            if parent > 0 {
+                used += start.saturating_sub(position);
+            }
+        }
+        position = position.max(start);
+        open.push((end, count));
+    }
+    close_ranges(u64::MAX, &mut open, &mut position, &mut used);
+    used
+}
+
+fn resource_entry(url: &str, total: u64, used: u64) -> serde_json::Value {
+    serde_json::json!({
+        "url": url,
+        "totalBytes": total,
+        "usedBytes": used,
+        "unusedBytes": total.saturating_sub(used),
+        "usedPercent": percent(used, total),
+    })
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 / whole as f64 * 1000.0).round() / 10.0
+}
+
+/// Totals plus the resources with the most unused bytes
+fn coverage_summary(mut resources: Vec<(String, u64, u64)>) -> serde_json::Value {
+    let total: u64 = resources.iter().map(|(_, total, _)| total).sum();
+    let used: u64 = resources.iter().map(|(_, _, used)| used).sum();
+    resources.sort_by_key(|(_, total, used)| std::cmp::Reverse(total.saturating_sub(*used)));
+    serde_json::json!({
+        "resourceCount": resources.len(),
+        "totalBytes": total,
+        "usedBytes": used,
+        "usedPercent": percent(used, total),
+        "resources": resources
+            .iter()
+            .take(SUMMARY_ITEMS)
+            .map(|(url, total, used)| resource_entry(url, *total, *used))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Summarize `Profiler.takePreciseCoverage` results (each script with the
+/// `length` the extension added from `Debugger.scriptParsed`). Scripts
+/// without a URL (evals, devtools snippets) are left out.
+pub fn js_summary(scripts: &serde_json::Value) -> serde_json::Value {
+    let mut resources = Vec::new();
+    for script in scripts.as_array().into_iter().flatten() {
+        let url = script.get("url").and_then(|v| v.as_str()).unwrap_or_default();
+        if url.is_empty() {
+            continue;
+        }
+        let mut ranges: Vec<(u64, u64, u64)> = script
+            .get("functions")
+            .and_then(|f| f.as_array())
+            .into_iter()
+            .flatten()
+            .flat_map(|function| function.get("ranges").and_then(|r| r.as_array()).into_iter().flatten())
+            .filter_map(|range| {
+                Some((
+                    range.get("startOffset")?.as_u64()?,
+                    range.get("endOffset")?.as_u64()?,
+                    range.get("count")?.as_u64()?,
+                ))
+            })
+            .collect();
+        let extent = ranges.iter().map(|(_, end, _)| *end).max().unwrap_or(0);
+        let total = script.get("length").and_then(|v| v.as_u64()).unwrap_or(extent).max(extent);
+        resources.push((url.to_string(), total, used_bytes(&mut ranges)));
+    }
+    coverage_summary(resources)
+}
+
+/// Summarize `CSS.stopRuleUsageTracking` rule usage against the
+/// stylesheets seen while tracking (`CSS.styleSheetAdded` headers)
+pub fn css_summary(coverage: &serde_json::Value) -> serde_json::Value {
+    let mut resources: Vec<(String, u64, u64)> = Vec::new();
+    let mut ids: Vec<String> = Vec::new();
+    for sheet in coverage.get("styleSheets").and_then(|s| s.as_array()).into_iter().flatten() {
+        let url = sheet.get("sourceURL").and_then(|v| v.as_str()).filter(|u| !u.is_empty());
+        ids.push(sheet.get("styleSheetId").and_then(|v| v.as_str()).unwrap_or_default().to_string());
+        resources.push((
+            url.unwrap_or("(inline)").to_string(),
+            sheet.get("length").and_then(|v| v.as_f64()).unwrap_or(0.0) as u64,
+            0,
+        ));
+    }
+    for rule in coverage.get("ruleUsage").and_then(|r| r.as_array()).into_iter().flatten() {
+        if rule.get("used").and_then(|v| v.as_bool()) != Some(true) {
+            continue;
+        }
+        let id = rule.get("styleSheetId").and_then(|v| v.as_str()).unwrap_or_default();
+        let offset = |key: &str| rule.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0) as u64;
+        if let Some(index) = ids.iter().position(|sheet| sheet == id) {
+            resources[index].2 += offset("endOffset").saturating_sub(offset("startOffset"));
+        }
+    }
+    for resource in &mut resources {
+        resource.2 = resource.2.min(resource.1);
+    }
+    coverage_summary(resources)
+}
+
+// ============================================================================
+// Traces
+// ============================================================================
+
+/// Event count, duration and long main-thread tasks of a Chrome trace,
+/// given as `{"traceEvents": [...]}` or a bare event array
+pub fn trace_summary(trace: &serde_json::Value) -> serde_json::Value {
+    let empty = Vec::new();
+    let events = trace
+        .get("traceEvents")
+        .and_then(|e| e.as_array())
+        .or_else(|| trace.as_array())
+        .unwrap_or(&empty);
+    let field = |event: &serde_json::Value, key: &str| event.get(key).and_then(|v| v.as_u64());
+    let start = events.iter().filter_map(|e| field(e, "ts").filter(|ts| *ts > 0)).min().unwrap_or(0);
+    let end = events
+        .iter()
+        .filter_map(|e| Some(field(e, "ts")? + field(e, "dur").unwrap_or(0)))
+        .max()
+        .unwrap_or(start);
+
+    let mut long_tasks: Vec<(u64, u64)> = events
+        .iter()
+        .filter(|e| e.get("ph").and_then(|v| v.as_str()) == Some("X"))
+        .filter(|e| matches!(e.get("name").and_then(|v| v.as_str()), Some("RunTask" | "ThreadControllerImpl::RunTask")))
+        .filter_map(|e| Some((field(e, "ts")?, field(e, "dur")?)))
+        .filter(|(_, dur)| *dur >= LONG_TASK_US)
+        .collect();
+    let blocking_us: u64 = long_tasks.iter().map(|(_, dur)| dur - LONG_TASK_US).sum();
+    long_tasks.sort_by_key(|(_, dur)| std::cmp::Reverse(*dur));
+    let ms = |us: u64| (us as f64 / 100.0).round() / 10.0;
+    serde_json::json!({
+        "eventCount": events.len(),
+        "durationMs": ms(end.saturating_sub(start)),
+        "longTaskCount": long_tasks.len(),
+        "totalBlockingTimeMs": ms(blocking_us),
+        "longTasks": long_tasks
+            .iter()
+            .take(SUMMARY_ITEMS)
+            .map(|(ts, dur)| serde_json::json!({ "startMs": ms(ts.saturating_sub(start)), "durationMs": ms(*dur) }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+// ============================================================================
+// Artifacts
+// ============================================================================
+
+fn write_json(dir: &Path, name: &str, value: &serde_json::Value) -> Result<String> {
+    let path = dir.join(name);
+    storage::write_private_file(&path, serde_json::to_vec(value)?)?;
+    Ok(path.display().to_string())
+}
+
+/// Write what `profiling_stop` returned to `profiles/<time>/` in the
+/// session's artifacts; returns the files written and their summaries
+pub fn save(session: &str, result: &serde_json::Value) -> Result<serde_json::Value> {
+    let dir = storage::ensure_private_dir(
+        &artifacts::session_dir(session)?
+            .join("profiles")
+            .join(chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string()),
+    )?;
+    let mut files = serde_json::Map::new();
+    let mut summary = serde_json::Map::new();
+    if let Some(js) = result.get("jsCoverage") {
+        files.insert("jsCoverage".to_string(), write_json(&dir, "js-coverage.json", js)?.into());
+        summary.insert("js".to_string(), js_summary(js));
+    }
+    if let Some(css) = result.get("cssCoverage") {
+        files.insert("cssCoverage".to_string(), write_json(&dir, "css-coverage.json", css)?.into());
+        summary.insert("css".to_string(), css_summary(css));
+    }
+    if let Some(trace) = result.get("trace").and_then(|t| t.as_str()) {
+        let path = dir.join("trace.json");
+        storage::write_private_file(&path, trace)?;
+        files.insert("trace".to_string(), path.display().to_string().into());
+        let parsed = serde_json::from_str(trace).unwrap_or(serde_json::Value::Null);
+        summary.insert("trace".to_string(), trace_summary(&parsed));
+    }
+    Ok(serde_json::json!({
+        "tabId": result.get("tabId"),
+        "url": result.get("url"),
+        "recordedMs": result.get("durationMs"),
+        "directory": dir.display().to_string(),
+        "files": files,
+        "summary": summary,
+    }))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_coverage_counts_innermost_ranges() {
+        // 0..100 ran; 20..50 never did, except 30..40 inside it (unreachable
+        // in practice, but nesting decides); 60..70 ran again
+        let mut ranges = vec![(0, 100, 1), (20, 50, 0), (30, 40, 2), (60, 70, 5)];
+        assert_eq!(used_bytes(&mut ranges), 100 - 30 + 10);
+
+        let scripts = serde_json::json!([
+            { "scriptId": "1", "url": "https://example.com/app.js", "length": 200, "functions": [
+                { "functionName": "", "ranges": [{ "startOffset": 0, "endOffset": 200, "count": 1 }] },
+                { "functionName": "unused", "ranges": [{ "startOffset": 50, "endOffset": 150, "count": 0 }] }
+            ] },
+            { "scriptId": "2", "url": "", "functions": [
+                { "functionName": "", "ranges": [{ "startOffset": 0, "endOffset": 10, "count": 1 }] }
+            ] }
+        ]);
+        let summary = js_summary(&scripts);
+        assert_eq!(summary["resourceCount"], 1);
+        assert_eq!(summary["resources"][0]["usedBytes"], 100);
+        assert_eq!(summary["usedPercent"], 50.0);
+    }
+
+    #[test]
+    fn test_css_and_trace_summaries() {
+        let css = serde_json::json!({
+            "styleSheets": [
+                { "styleSheetId": "a", "sourceURL": "https://example.com/site.css", "length": 1000 },
+                { "styleSheetId": "b", "sourceURL": "", "length": 100 }
+            ],
+            "ruleUsage": [
+                { "styleSheetId": "a", "startOffset": 0, "endOffset": 250, "used": true },
+                { "styleSheetId": "a", "startOffset": 250, "endOffset": 900, "used": false },
+                { "styleSheetId": "b", "startOffset": 0, "endOffset": 100, "used": true }
+            ]
+        });
+        let summary = css_summary(&css);
+        assert_eq!(summary["resources"][0]["url"], "https://example.com/site.css");
+        assert_eq!(summary["resources"][0]["unusedBytes"], 750);
+        assert_eq!(summary["resources"][1]["url"], "(inline)");
+        assert_eq!(summary["usedBytes"], 350);
+
+        let trace = serde_json::json!({ "traceEvents": [
+            { "name": "TracingStartedInBrowser", "ph": "I", "ts": 1_000_000 },
+            { "name": "RunTask", "ph": "X", "ts": 1_100_000, "dur": 120_000 },
+            { "name": "RunTask", "ph": "X", "ts": 1_300_000, "dur": 10_000 },
+            { "name": "RunTask", "ph": "X", "ts": 1_400_000, "dur": 60_000 }
+        ] });
+        let summary = trace_summary(&trace);
+        assert_eq!(summary["eventCount"], 4);
+        assert_eq!(summary["durationMs"], 460.0);
+        assert_eq!(summary["longTaskCount"], 2);
+        assert_eq!(summary["totalBlockingTimeMs"], 80.0);
+        assert_eq!(summary["longTasks"][0], serde_json::json!({ "startMs": 100.0, "durationMs": 120.0 }));
+    }
+}
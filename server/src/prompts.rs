@@ -0,0 +1,200 @@
+/*!
+ * Built-in MCP Prompts
+ *
+ * Parameterized browsing prompts served through prompts/list and
+ * prompts/get. Messages are assembled from live state (page text, stored
+ * passkeys, captured network failures) at the time the prompt is fetched.
+ */
+
+use serde_json::{json, Value};
+
+use crate::network_capture::HttpEntry;
+
+/// Page text beyond this is cut so prompts stay within a reasonable context budget
+const MAX_PAGE_TEXT_CHARS: usize = 20_000;
+
+/// Snapshot of the active page used to fill prompt templates
+#[derive(Debug, Default)]
+pub struct PageContext {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub text: Option<String>,
+}
+
+impl PageContext {
+    pub fn from_extension(result: &Value) -> Self {
+        let field = |key: &str| result.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            url: field("url"),
+            title: field("title"),
+            text: field("text"),
+        }
+    }
+}
+
+/// prompts/list entries
+pub fn list() -> Value {
+    json!([
+        {
+            "name": "summarize_page",
+            "description": "Summarize the page currently open in the browser",
+            "arguments": [
+                { "name": "focus", "description": "What the summary should concentrate on", "required": false }
+            ]
+        },
+        {
+            "name": "fill_login_form",
+            "description": "Sign in at a URL, preferring stored passkeys over passwords",
+            "arguments": [
+                { "name": "url", "description": "Login page URL", "required": true },
+                { "name": "username", "description": "Account to sign in as", "required": false }
+            ]
+        },
+        {
+            "name": "debug_failed_requests",
+            "description": "Investigate recent failed HTTP requests captured from the page",
+            "arguments": [
+                { "name": "urlPattern", "description": "Only consider requests whose URL contains this", "required": false }
+            ]
+        }
+    ])
+}
+
+/// Whether a prompt with this name exists
+pub fn exists(name: &str) -> bool {
+    list()
+        .as_array()
+        .is_some_and(|prompts| prompts.iter().any(|p| p["name"] == name))
+}
+
+fn user_message(description: &str, text: String) -> Value {
+    json!({
+        "description": description,
+        "messages": [
+            { "role": "user", "content": { "type": "text", "text": text } }
+        ]
+    })
+}
+
+fn truncate_chars(text: &str, max: usize) -> (&str, bool) {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => (&text[..idx], true),
+        None => (text, false),
+    }
+}
+
+/// Host portion of an http(s) URL, without port or credentials
+pub fn host_of(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+pub fn summarize_page(page: &PageContext, focus: Option<&str>) -> Value {
+    let (text, truncated) = truncate_chars(page.text.as_deref().unwrap_or_default(), MAX_PAGE_TEXT_CHARS);
+
+    let mut prompt = format!(
+        "Summarize the web page \"{}\" ({}).",
+        page.title.as_deref().unwrap_or("untitled"),
+        page.url.as_deref().unwrap_or("unknown URL")
+    );
+    if let Some(focus) = focus {
+        prompt.push_str(&format!(" Concentrate on: {}.", focus));
+    }
+    prompt.push_str("\n\nPage text:\n\n");
+    prompt.push_str(text);
+    if truncated {
+        prompt.push_str(&format!("\n\n[Truncated after {} characters]", MAX_PAGE_TEXT_CHARS));
+    }
+
+    user_message("Summary of the current page", prompt)
+}
+
+pub fn fill_login_form(url: &str, username: Option<&str>, passkey_count: usize, current: Option<&PageContext>) -> Value {
+    let mut prompt = format!("Sign in at {}", url);
+    if let Some(username) = username {
+        prompt.push_str(&format!(" as \"{}\"", username));
+    }
+    prompt.push_str(".\n\n");
+
+    match current.and_then(|p| p.url.as_deref()) {
+        Some(current_url) if current_url.starts_with(url) => {
+            prompt.push_str("The browser is already on the login page.\n");
+        }
+        Some(current_url) => {
+            prompt.push_str(&format!(
+                "The browser is currently on {}; navigate with playwright_navigate first.\n",
+                current_url
+            ));
+        }
+        None => prompt.push_str("Navigate there with playwright_navigate first.\n"),
+    }
+
+    if passkey_count > 0 {
+        prompt.push_str(&format!(
+            "{} stored passkey(s) match this site. Prefer the \"Sign in with a passkey\" option: check passkey_authorization_status and ask the user to run passkey_authorize if the session is not authorized.\n",
+            passkey_count
+        ));
+    } else {
+        prompt.push_str("No stored passkeys match this site; use the password flow.\n");
+    }
+
+    prompt.push_str(
+        "Locate the username and password fields, fill them with playwright_fill, submit, and confirm the sign-in succeeded before continuing. Never invent credentials; ask the user if any are missing.",
+    );
+
+    user_message("Sign in to a site", prompt)
+}
+
+pub fn debug_failed_requests(entries: &[HttpEntry]) -> Value {
+    let failed: Vec<&HttpEntry> = entries
+        .iter()
+        .filter(|e| e.status.is_none_or(|status| status >= 400))
+        .collect();
+
+    let mut prompt = String::from("Investigate why these requests from the current page failed.\n\n");
+    if failed.is_empty() {
+        prompt.push_str("No failed requests have been captured. Reproduce the problem in the browser and fetch this prompt again.");
+    } else {
+        for entry in &failed {
+            let status = entry
+                .status
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "no response".to_string());
+            prompt.push_str(&format!("- {} {} -> {} (id {})\n", entry.method, entry.url, status, entry.id));
+        }
+        prompt.push_str("\nUse network_get_request with an id to inspect headers and bodies, then explain the likely cause and a fix.");
+    }
+
+    user_message("Failed network requests", prompt)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://user@login.example.com:8443/path?q"), Some("login.example.com"));
+        assert_eq!(host_of("example.org/login"), Some("example.org"));
+        assert_eq!(host_of("https:///nohost"), None);
+    }
+
+    #[test]
+    fn test_summarize_page_truncates_long_text() {
+        let page = PageContext {
+            url: Some("https://example.com".to_string()),
+            title: Some("Example".to_string()),
+            text: Some("x".repeat(MAX_PAGE_TEXT_CHARS + 10)),
+        };
+        let prompt = summarize_page(&page, Some("pricing"));
+        let text = prompt["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.contains("Concentrate on: pricing"));
+        assert!(text.ends_with(&format!("[Truncated after {} characters]", MAX_PAGE_TEXT_CHARS)));
+    }
+}
@@ -0,0 +1,274 @@
+/*!
+ * Data Directory Quotas
+ *
+ * Bounds the disk space used under `~/.agent-browser` by files the server
+ * produces on its own: session artifacts, recordings, and rotated audit log
+ * segments. When a category exceeds its quota the least recently used files
+ * are deleted until it fits again. Credentials, named snapshots, baselines
+ * and state files are reported by `storage_status` but never cleaned up.
+ *
+ * Quotas are set in megabytes with `AGENT_BROWSER_QUOTA_<CATEGORY>_MB`.
+ */
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::storage;
+
+/// Audit log size at which it is rotated into a dated segment
+pub const AUDIT_SEGMENT_BYTES: u64 = 1024 * 1024;
+pub const AUDIT_LOG_NAME: &str = "audit.log";
+
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Artifacts,
+    Recordings,
+    Audit,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::Artifacts, Category::Recordings, Category::Audit];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Artifacts => "artifacts",
+            Category::Recordings => "recordings",
+            Category::Audit => "audit",
+        }
+    }
+
+    fn default_quota_mb(self) -> u64 {
+        match self {
+            Category::Artifacts => 1024,
+            Category::Recordings => 2048,
+            Category::Audit => 50,
+        }
+    }
+
+    pub fn quota_bytes(self) -> u64 {
+        let var = format!("AGENT_BROWSER_QUOTA_{}_MB", self.name().to_ascii_uppercase());
+        std::env::var(var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(self.default_quota_mb())
+            * MB
+    }
+
+    /// Files counted against this category's quota, and the ones that may be deleted
+    fn files(self, data_dir: &Path) -> Vec<TrackedFile> {
+        match self {
+            Category::Artifacts | Category::Recordings => {
+                let mut files = Vec::new();
+                collect(&data_dir.join(self.name()), &mut files);
+                files
+            }
+            Category::Audit => fs::read_dir(data_dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|e| {
+                            let name = e.file_name().to_string_lossy().into_owned();
+                            name == AUDIT_LOG_NAME || (name.starts_with("audit-") && name.ends_with(".log"))
+                        })
+                        .filter_map(|e| TrackedFile::from_path(e.path()))
+                        // The live log is counted but never deleted
+                        .map(|mut f| {
+                            f.protected = f.path.file_name().is_some_and(|n| n == AUDIT_LOG_NAME);
+                            f
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+struct TrackedFile {
+    path: PathBuf,
+    bytes: u64,
+    last_used: SystemTime,
+    protected: bool,
+}
+
+impl TrackedFile {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let metadata = fs::metadata(&path).ok()?;
+        // atime is often coarse (relatime) or disabled; never older than the last write
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let accessed = metadata.accessed().unwrap_or(modified);
+        Some(Self {
+            path,
+            bytes: metadata.len(),
+            last_used: accessed.max(modified),
+            protected: false,
+        })
+    }
+}
+
+fn collect(dir: &Path, out: &mut Vec<TrackedFile>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, out);
+        } else if let Some(file) = TrackedFile::from_path(path) {
+            out.push(file);
+        }
+    }
+}
+
+fn dir_size(path: &Path) -> (u64, usize) {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect(path, &mut files);
+    } else if let Some(file) = TrackedFile::from_path(path.to_path_buf()) {
+        files.push(file);
+    }
+    (files.iter().map(|f| f.bytes).sum(), files.len())
+}
+
+/// Delete least recently used files until the category fits its quota
+fn enforce_in(data_dir: &Path, category: Category, quota: u64) -> (usize, u64) {
+    let mut files = category.files(data_dir);
+    let mut total: u64 = files.iter().map(|f| f.bytes).sum();
+    files.sort_by_key(|f| f.last_used);
+
+    let (mut removed, mut freed) = (0, 0);
+    for file in files.iter().filter(|f| !f.protected) {
+        if total <= quota {
+            break;
+        }
+        if fs::remove_file(&file.path).is_ok() {
+            total -= file.bytes;
+            freed += file.bytes;
+            removed += 1;
+        }
+    }
+    (removed, freed)
+}
+
+/// Enforce one category's quota; returns (files removed, bytes freed)
+pub fn enforce(category: Category) -> Result<(usize, u64)> {
+    Ok(enforce_in(&storage::data_dir()?, category, category.quota_bytes()))
+}
+
+/// Enforce every category's quota; returns a report per category that removed anything
+pub fn enforce_all() -> Result<Vec<serde_json::Value>> {
+    let data_dir = storage::data_dir()?;
+    Ok(Category::ALL
+        .iter()
+        .filter_map(|&category| {
+            let (removed, freed) = enforce_in(&data_dir, category, category.quota_bytes());
+            (removed > 0).then(|| {
+                serde_json::json!({ "category": category.name(), "removedFiles": removed, "freedBytes": freed })
+            })
+        })
+        .collect())
+}
+
+/// Rotate the audit log into a dated segment once it reaches the segment size
+pub fn rotate_audit_log(path: &Path) -> Result<Option<PathBuf>> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(None);
+    };
+    if metadata.len() < AUDIT_SEGMENT_BYTES {
+        return Ok(None);
+    }
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f");
+    let segment = path.with_file_name(format!("audit-{}.log", stamp));
+    fs::rename(path, &segment)?;
+    Ok(Some(segment))
+}
+
+/// Usage of the data directory: quota-managed categories plus everything else
+pub fn status() -> Result<serde_json::Value> {
+    let data_dir = storage::data_dir()?;
+
+    let categories: Vec<serde_json::Value> = Category::ALL
+        .iter()
+        .map(|&category| {
+            let files = category.files(&data_dir);
+            let bytes: u64 = files.iter().map(|f| f.bytes).sum();
+            let quota = category.quota_bytes();
+            serde_json::json!({
+                "category": category.name(),
+                "bytes": bytes,
+                "files": files.len(),
+                "quotaBytes": quota,
+                "usedPercent": if quota == 0 { 100.0 } else { (bytes as f64 / quota as f64 * 1000.0).round() / 10.0 },
+            })
+        })
+        .collect();
+
+    let mut other = Vec::new();
+    for name in ["credentials.json", "credential_usage.json", "recovery_secrets.json", "network_policy.json", "state", "snapshots", "baselines", "backups"] {
+        let (bytes, files) = dir_size(&data_dir.join(name));
+        if files > 0 {
+            other.push(serde_json::json!({ "name": name, "bytes": bytes, "files": files }));
+        }
+    }
+
+    let (total, _) = dir_size(&data_dir);
+    Ok(serde_json::json!({
+        "dataDir": data_dir.display().to_string(),
+        "totalBytes": total,
+        "categories": categories,
+        "unmanaged": other,
+    }))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn write_with_age(path: &Path, bytes: usize, age_secs: u64) {
+        fs::write(path, vec![b'x'; bytes]).unwrap();
+        let time = SystemTime::now() - Duration::from_secs(age_secs);
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_times(fs::FileTimes::new().set_accessed(time).set_modified(time)).unwrap();
+    }
+
+    #[test]
+    fn test_enforce_removes_least_recently_used() {
+        let root = std::env::temp_dir().join(format!("ab-quota-{}", uuid::Uuid::new_v4()));
+        let session = root.join("artifacts").join("tcp_a");
+        fs::create_dir_all(&session).unwrap();
+        write_with_age(&session.join("old.png"), 600, 300);
+        write_with_age(&session.join("mid.png"), 600, 200);
+        write_with_age(&session.join("new.png"), 600, 10);
+
+        let (removed, freed) = enforce_in(&root, Category::Artifacts, 1300);
+        assert_eq!((removed, freed), (1, 600));
+        assert!(!session.join("old.png").exists());
+        assert!(session.join("mid.png").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_live_audit_log_is_never_removed() {
+        let root = std::env::temp_dir().join(format!("ab-quota-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        write_with_age(&root.join(AUDIT_LOG_NAME), 500, 500);
+        write_with_age(&root.join("audit-20260101T000000.000.log"), 500, 100);
+        write_with_age(&root.join("credentials.json"), 500, 900);
+
+        let (removed, _) = enforce_in(&root, Category::Audit, 0);
+        assert_eq!(removed, 1);
+        assert!(root.join(AUDIT_LOG_NAME).exists());
+        assert!(root.join("credentials.json").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+/*!
+ * Submission Receipts
+ *
+ * With `captureReceipt: true`, playwright_click and playwright_fill record
+ * what the page showed after the action: a screenshot, the final URL and
+ * title, and the confirmation text ("Thank you", order numbers, ...). Each
+ * receipt is written to `receipts/<time>-<tool>/` in the session's artifact
+ * directory as `screenshot.png` plus `receipt.json`, giving users an
+ * evidence trail of what agents submitted on their behalf. Typed values are
+ * never recorded, and URLs and text pass through the default redaction rules.
+ */
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::artifacts;
+use crate::redaction::Redactor;
+
+/// Tools that accept `captureReceipt`
+pub const RECEIPT_TOOLS: &[&str] = &["playwright_click", "playwright_fill"];
+
+/// Give the page time to navigate or render its confirmation after submitting
+pub const SETTLE_MS: u64 = 1500;
+
+/// Confirmation text beyond this is cut
+const MAX_CONFIRMATION_CHARS: usize = 2000;
+
+/// Lines containing these (lowercased) are treated as confirmation text
+const CONFIRMATION_MARKERS: &[&str] = &[
+    "thank you",
+    "thanks",
+    "confirm",
+    "success",
+    "submitted",
+    "received",
+    "complete",
+    "order number",
+    "order #",
+    "reference",
+    "booking",
+    "receipt",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub tool: String,
+    pub selector: Option<String>,
+    pub captured_at: String,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub confirmation: Option<String>,
+    pub screenshot: Option<String>,
+    /// Why a part could not be captured
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl Receipt {
+    pub fn new(tool: &str, selector: Option<&str>) -> Self {
+        Self {
+            tool: tool.to_string(),
+            selector: selector.map(str::to_string),
+            captured_at: chrono::Utc::now().to_rfc3339(),
+            url: None,
+            title: None,
+            confirmation: None,
+            screenshot: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Fill URL, title and confirmation from a `get_text` result
+    pub fn set_page(&mut self, page: &serde_json::Value) {
+        let redactor = Redactor::default();
+        let field = |key: &str| page.get(key).and_then(|v| v.as_str());
+        self.url = field("url").map(|url| redactor.redact_text(url).0);
+        self.title = field("title").map(str::to_string);
+        self.confirmation = field("text")
+            .and_then(confirmation_text)
+            .map(|text| redactor.redact_text(&text).0);
+    }
+}
+
+/// Lines of page text that look like a confirmation, or None if there are none
+pub fn confirmation_text(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            !line.is_empty() && CONFIRMATION_MARKERS.iter().any(|marker| lower.contains(marker))
+        })
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines.join("\n").chars().take(MAX_CONFIRMATION_CHARS).collect())
+}
+
+/// A new receipt directory in the session's artifacts
+pub fn receipt_dir(session: &str, tool: &str) -> Result<PathBuf> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let dir = artifacts::session_dir(session)?.join("receipts").join(format!("{}-{}", stamp, tool));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Write receipt.json (and the screenshot, if any) into `dir`
+pub fn write(dir: &Path, receipt: &mut Receipt, screenshot: Option<&[u8]>) -> Result<()> {
+    if let Some(png) = screenshot {
+        let path = dir.join("screenshot.png");
+        std::fs::write(&path, png)?;
+        receipt.screenshot = Some(path.display().to_string());
+    }
+    std::fs::write(dir.join("receipt.json"), serde_json::to_string_pretty(receipt)?)?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_text_keeps_matching_lines() {
+        let page = "Home\nCart\n\nThank you for your order!\nOrder number: 12345\nContinue shopping";
+        assert_eq!(
+            confirmation_text(page).as_deref(),
+            Some("Thank you for your order!\nOrder number: 12345")
+        );
+        assert_eq!(confirmation_text("Sign in\nPassword"), None);
+    }
+
+    #[test]
+    fn test_receipt_redacts_and_writes() {
+        let mut receipt = Receipt::new("playwright_click", Some("#submit"));
+        receipt.set_page(&serde_json::json!({
+            "url": "https://shop.example/done?access_token=abc123",
+            "title": "Done",
+            "text": "Payment received"
+        }));
+        assert!(!receipt.url.as_deref().unwrap().contains("abc123"));
+        assert_eq!(receipt.confirmation.as_deref(), Some("Payment received"));
+
+        let dir = std::env::temp_dir().join(format!("agent-browser-receipt-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write(&dir, &mut receipt, Some(b"png")).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("receipt.json")).unwrap()).unwrap();
+        assert_eq!(saved["selector"], "#submit");
+        assert!(saved["screenshot"].as_str().unwrap().ends_with("screenshot.png"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
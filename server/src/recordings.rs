@@ -0,0 +1,463 @@
+/*!
+ * Screencast Recordings
+ *
+ * `browser_recording_start` has the extension start a DevTools screencast
+ * (`Page.startScreencast`) on the tab. Chrome produces a JPEG whenever the
+ * page repaints, and the extension forwards each one as a `screencast_frame`
+ * event. The server writes the frames to
+ * `~/.agent-browser/recordings/<id>/` as they arrive.
+ * `browser_recording_stop` ends the screencast and encodes the frames into
+ * `<id>.webm` or `<id>.mp4` with ffmpeg, keeping their real timing. Without
+ * ffmpeg the server writes a Motion-JPEG `<id>.avi` itself, at a fixed rate
+ * with repeat markers for unchanged stretches. The frames are deleted once
+ * encoded. Recordings count against the `recordings` quota.
+ */
+
+use anyhow::{anyhow, Result};
+use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+use crate::storage;
+
+pub const FORMATS: &[&str] = &["webm", "mp4"];
+pub const DEFAULT_MAX_WIDTH: u64 = 1280;
+pub const MIN_MAX_WIDTH: u64 = 320;
+pub const MAX_MAX_WIDTH: u64 = 3840;
+pub const DEFAULT_QUALITY: u64 = 70;
+
+/// Frame data kept per recording; later frames are dropped (the AVI
+/// fallback can't exceed 1 GiB either)
+const MAX_RECORDING_BYTES: u64 = 1 << 30;
+
+/// Frame rate of the Motion-JPEG fallback
+const AVI_FPS: u32 = 10;
+
+/// How long the last frame is shown when the recording stops long after it
+const MAX_LAST_FRAME_SECS: f64 = 2.0;
+
+struct Frame {
+    path: PathBuf,
+    /// Seconds since the epoch, from the screencast metadata
+    timestamp: f64,
+}
+
+pub struct Recording {
+    pub id: String,
+    pub session: String,
+    pub tab_id: i64,
+    pub format: String,
+    dir: PathBuf,
+    frames: Vec<Frame>,
+    bytes: u64,
+    dropped: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedRecording {
+    pub recording_id: String,
+    pub path: String,
+    pub format: String,
+    /// "ffmpeg", or "mjpeg" for the built-in AVI writer
+    pub encoder: String,
+    pub frames: usize,
+    pub dropped_frames: usize,
+    pub duration_ms: u64,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Recordings in progress, keyed by tab
+#[derive(Default)]
+pub struct RecordingStore {
+    recordings: Mutex<HashMap<i64, Recording>>,
+}
+
+impl RecordingStore {
+    /// Register a recording of `tab_id` before the extension starts its screencast
+    pub async fn start(&self, session: &str, tab_id: i64, format: &str) -> Result<(String, PathBuf)> {
+        let mut recordings = self.recordings.lock().await;
+        if recordings.contains_key(&tab_id) {
+            return Err(anyhow!("Tab {} is already being recorded", tab_id));
+        }
+        let id = format!("rec-{}-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"), tab_id);
+        let dir = storage::ensure_private_dir(&storage::data_subdir("recordings")?.join(&id))?;
+        recordings.insert(
+            tab_id,
+            Recording {
+                id: id.clone(),
+                session: session.to_string(),
+                tab_id,
+                format: format.to_string(),
+                dir: dir.clone(),
+                frames: Vec::new(),
+                bytes: 0,
+                dropped: 0,
+            },
+        );
+        Ok((id, dir))
+    }
+
+    /// Store a `screencast_frame` event's JPEG
+    pub async fn add_frame(&self, data: &serde_json::Value) -> Result<()> {
+        let tab_id = data.get("tabId").and_then(|v| v.as_i64()).ok_or_else(|| anyhow!("frame without tabId"))?;
+        let jpeg = BASE64.decode(data.get("data").and_then(|v| v.as_str()).unwrap_or_default())?;
+        let timestamp = data
+            .get("timestamp")
+            .and_then(|v| v.as_f64())
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as f64 / 1000.0);
+
+        let mut recordings = self.recordings.lock().await;
+        let recording = recordings
+            .get_mut(&tab_id)
+            .ok_or_else(|| anyhow!("frame for tab {}, which is not being recorded", tab_id))?;
+        if recording.bytes + jpeg.len() as u64 > MAX_RECORDING_BYTES {
+            recording.dropped += 1;
+            return Ok(());
+        }
+        let path = recording.dir.join(format!("{:06}.jpg", recording.frames.len() + 1));
+        tokio::fs::write(&path, &jpeg).await?;
+        recording.bytes += jpeg.len() as u64;
+        recording.frames.push(Frame { path, timestamp });
+        Ok(())
+    }
+
+    /// Remove and return the session's recording
+    pub async fn take(&self, session: &str) -> Option<Recording> {
+        let mut recordings = self.recordings.lock().await;
+        let tab_id = recordings.values().find(|r| r.session == session).map(|r| r.tab_id)?;
+        recordings.remove(&tab_id)
+    }
+
+    /// Drop a recording the extension failed to start
+    pub async fn discard(&self, tab_id: i64) {
+        if let Some(recording) = self.recordings.lock().await.remove(&tab_id) {
+            let _ = fs::remove_dir_all(&recording.dir);
+        }
+    }
+}
+
+// ============================================================================
+// Encoding
+// ============================================================================
+
+/// ffmpeg from `AGENT_BROWSER_FFMPEG` or PATH
+fn ffmpeg_binary() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("AGENT_BROWSER_FFMPEG") {
+        return Some(PathBuf::from(path));
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" }))
+        .find(|p| p.is_file())
+}
+
+/// How long each frame stays on screen: until the next one, and the last
+/// until the recording stopped (capped)
+fn frame_durations(timestamps: &[f64], stopped_at: f64) -> Vec<f64> {
+    let mut durations: Vec<f64> = timestamps.windows(2).map(|w| (w[1] - w[0]).max(0.001)).collect();
+    if let Some(last) = timestamps.last() {
+        durations.push((stopped_at - last).clamp(0.1, MAX_LAST_FRAME_SECS));
+    }
+    durations
+}
+
+/// Width and height from a JPEG's start-of-frame marker
+fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        // SOF0-SOF15, except DHT (C4), JPG (C8) and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            let height = u16::from_be_bytes([*data.get(pos + 5)?, *data.get(pos + 6)?]);
+            let width = u16::from_be_bytes([*data.get(pos + 7)?, *data.get(pos + 8)?]);
+            return Some((width as u32, height as u32));
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+/// Encode the frames and delete them. Blocking; run off the async runtime.
+pub fn encode(recording: Recording, stopped_at: f64) -> Result<EncodedRecording> {
+    if recording.frames.is_empty() {
+        let _ = fs::remove_dir_all(&recording.dir);
+        return Err(anyhow!(
+            "No frames were recorded; Chrome only paints visible tabs, so bring the tab to front while recording"
+        ));
+    }
+    let timestamps: Vec<f64> = recording.frames.iter().map(|f| f.timestamp).collect();
+    let durations = frame_durations(&timestamps, stopped_at);
+    let duration_ms = (durations.iter().sum::<f64>() * 1000.0).round() as u64;
+    let parent = recording.dir.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let (path, encoder, format, note) = match ffmpeg_binary() {
+        Some(ffmpeg) => {
+            let path = parent.join(format!("{}.{}", recording.id, recording.format));
+            encode_ffmpeg(&ffmpeg, &recording, &durations, &path)?;
+            (path, "ffmpeg", recording.format.clone(), None)
+        }
+        None => {
+            let path = parent.join(format!("{}.avi", recording.id));
+            write_mjpeg_avi(&recording, &timestamps, &durations, &path)?;
+            let note = format!(
+                "ffmpeg was not found, so a Motion-JPEG AVI was written instead of {}; install ffmpeg or set AGENT_BROWSER_FFMPEG",
+                recording.format
+            );
+            (path, "mjpeg", "avi".to_string(), Some(note))
+        }
+    };
+    let _ = fs::remove_dir_all(&recording.dir);
+
+    Ok(EncodedRecording {
+        recording_id: recording.id,
+        bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        path: path.display().to_string(),
+        format,
+        encoder: encoder.to_string(),
+        frames: recording.frames.len(),
+        dropped_frames: recording.dropped,
+        duration_ms,
+        note,
+    })
+}
+
+fn encode_ffmpeg(ffmpeg: &Path, recording: &Recording, durations: &[f64], output: &Path) -> Result<()> {
+    // The concat demuxer's own timing keeps pauses in the page as pauses in the video
+    let mut list = String::from("ffconcat version 1.0\n");
+    for (frame, duration) in recording.frames.iter().zip(durations) {
+        list.push_str(&format!("file '{}'\nduration {:.3}\n", frame.path.display(), duration));
+    }
+    // The last entry's duration is only honored when the file is repeated
+    if let Some(last) = recording.frames.last() {
+        list.push_str(&format!("file '{}'\n", last.path.display()));
+    }
+    let list_path = recording.dir.join("frames.ffconcat");
+    fs::write(&list_path, list)?;
+
+    // Frames change size when the window is resized; fit them all to the first
+    let (width, height) = fs::read(&recording.frames[0].path)
+        .ok()
+        .and_then(|data| jpeg_size(&data))
+        .unwrap_or((1280, 720));
+    let (width, height) = (width + width % 2, height + height % 2);
+    let filter = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,format=yuv420p",
+        w = width,
+        h = height
+    );
+    let codec: &[&str] = match recording.format.as_str() {
+        "mp4" => &["-c:v", "libx264", "-preset", "veryfast", "-crf", "28", "-movflags", "+faststart"],
+        _ => &["-c:v", "libvpx-vp9", "-deadline", "realtime", "-cpu-used", "8", "-b:v", "0", "-crf", "40"],
+    };
+
+    let output_text = std::process::Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-vf", &filter, "-fps_mode", "vfr"])
+        .args(codec)
+        .arg(output)
+        .output()?;
+    if !output_text.status.success() {
+        let stderr = String::from_utf8_lossy(&output_text.stderr);
+        return Err(anyhow!("ffmpeg failed: {}", stderr.lines().last().unwrap_or("unknown error")));
+    }
+    Ok(())
+}
+
+/// Write a RIFF AVI with one MJPG stream. Slots where the picture didn't
+/// change get an empty chunk, which players show as a repeat of the last frame.
+fn write_mjpeg_avi(recording: &Recording, timestamps: &[f64], durations: &[f64], output: &Path) -> Result<()> {
+    let first = timestamps[0];
+    let total: f64 = durations.iter().sum();
+    let slots = ((total * AVI_FPS as f64).ceil() as usize).max(1);
+    let (width, height) = fs::read(&recording.frames[0].path)
+        .ok()
+        .and_then(|data| jpeg_size(&data))
+        .ok_or_else(|| anyhow!("First frame is not a JPEG"))?;
+
+    let mut file = BufWriter::new(File::create(output)?);
+    let u32le = |v: u32| v.to_le_bytes();
+
+    // Header sizes are patched in once the frames are written
+    file.write_all(b"RIFF\0\0\0\0AVI ")?;
+    file.write_all(b"LIST")?;
+    file.write_all(&u32le(4 + 8 + 56 + 8 + 4 + 8 + 56 + 8 + 40))?;
+    file.write_all(b"hdrl")?;
+
+    file.write_all(b"avih")?;
+    file.write_all(&u32le(56))?;
+    file.write_all(&u32le(1_000_000 / AVI_FPS))?; // microseconds per frame
+    file.write_all(&u32le(0))?; // max bytes per second
+    file.write_all(&u32le(0))?; // padding granularity
+    file.write_all(&u32le(0x10))?; // AVIF_HASINDEX
+    file.write_all(&u32le(slots as u32))?; // total frames
+    file.write_all(&u32le(0))?; // initial frames
+    file.write_all(&u32le(1))?; // streams
+    file.write_all(&u32le(0))?; // suggested buffer size
+    file.write_all(&u32le(width))?;
+    file.write_all(&u32le(height))?;
+    file.write_all(&[0; 16])?; // reserved
+
+    file.write_all(b"LIST")?;
+    file.write_all(&u32le(4 + 8 + 56 + 8 + 40))?;
+    file.write_all(b"strl")?;
+    file.write_all(b"strh")?;
+    file.write_all(&u32le(56))?;
+    file.write_all(b"vidsMJPG")?;
+    file.write_all(&u32le(0))?; // flags
+    file.write_all(&[0; 4])?; // priority, language
+    file.write_all(&u32le(0))?; // initial frames
+    file.write_all(&u32le(1))?; // scale
+    file.write_all(&u32le(AVI_FPS))?; // rate
+    file.write_all(&u32le(0))?; // start
+    file.write_all(&u32le(slots as u32))?; // length
+    file.write_all(&u32le(0))?; // suggested buffer size
+    file.write_all(&u32le(u32::MAX))?; // quality: default
+    file.write_all(&u32le(0))?; // sample size
+    file.write_all(&[0; 4])?; // frame rectangle left, top
+    file.write_all(&(width as u16).to_le_bytes())?;
+    file.write_all(&(height as u16).to_le_bytes())?;
+    file.write_all(b"strf")?;
+    file.write_all(&u32le(40))?;
+    file.write_all(&u32le(40))?; // BITMAPINFOHEADER size
+    file.write_all(&u32le(width))?;
+    file.write_all(&u32le(height))?;
+    file.write_all(&1u16.to_le_bytes())?; // planes
+    file.write_all(&24u16.to_le_bytes())?; // bit count
+    file.write_all(b"MJPG")?;
+    file.write_all(&u32le(width * height * 3))?;
+    file.write_all(&[0; 16])?; // resolution, palette
+
+    let movi_start = file.stream_position()?;
+    file.write_all(b"LIST\0\0\0\0movi")?;
+    // Index entries: (offset from the "movi" tag, size)
+    let mut index: Vec<(u32, u32)> = Vec::with_capacity(slots);
+    let mut shown = None;
+    for slot in 0..slots {
+        let at = first + slot as f64 / AVI_FPS as f64;
+        let current = timestamps.partition_point(|t| *t <= at + 1e-6).saturating_sub(1);
+        let offset = (file.stream_position()? - movi_start - 8) as u32;
+        if shown == Some(current) {
+            file.write_all(b"00dc\0\0\0\0")?;
+            index.push((offset, 0));
+            continue;
+        }
+        let data = fs::read(&recording.frames[current].path)?;
+        file.write_all(b"00dc")?;
+        file.write_all(&u32le(data.len() as u32))?;
+        file.write_all(&data)?;
+        if data.len() % 2 == 1 {
+            file.write_all(&[0])?;
+        }
+        index.push((offset, data.len() as u32));
+        shown = Some(current);
+    }
+    let movi_end = file.stream_position()?;
+
+    file.write_all(b"idx1")?;
+    file.write_all(&u32le(16 * index.len() as u32))?;
+    for (offset, size) in index {
+        file.write_all(b"00dc")?;
+        file.write_all(&u32le(0x10))?; // AVIIF_KEYFRAME
+        file.write_all(&u32le(offset))?;
+        file.write_all(&u32le(size))?;
+    }
+    let end = file.stream_position()?;
+
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&u32le((end - 8) as u32))?;
+    file.seek(SeekFrom::Start(movi_start + 4))?;
+    file.write_all(&u32le((movi_end - movi_start - 8) as u32))?;
+    file.flush()?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest JPEG-shaped bytes with a baseline start-of-frame marker
+    fn jpeg(width: u16, height: u16) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x0B, 0x08];
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&[0x01, 0x01, 0x11, 0x00, 0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_jpeg_size() {
+        assert_eq!(jpeg_size(&jpeg(1280, 720)), Some((1280, 720)));
+        assert_eq!(jpeg_size(b"\x89PNG"), None);
+        assert_eq!(jpeg_size(&[0xFF, 0xD8]), None);
+    }
+
+    #[test]
+    fn test_frame_durations() {
+        assert_eq!(frame_durations(&[10.0, 10.5, 10.5], 11.0), vec![0.5, 0.001, 0.5]);
+        // The last frame shows briefly even when stopped right away, and not forever
+        assert_eq!(frame_durations(&[10.0], 10.0), vec![0.1]);
+        assert_eq!(frame_durations(&[10.0], 100.0), vec![MAX_LAST_FRAME_SECS]);
+        assert!(frame_durations(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_mjpeg_avi_layout() {
+        let dir = std::env::temp_dir().join(format!("agent-browser-avi-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let frames: Vec<Frame> = [0.0, 0.35]
+            .iter()
+            .enumerate()
+            .map(|(i, timestamp)| {
+                let path = dir.join(format!("{}.jpg", i));
+                fs::write(&path, jpeg(64, 48)).unwrap();
+                Frame { path, timestamp: *timestamp }
+            })
+            .collect();
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        let recording = Recording {
+            id: "test".to_string(),
+            session: "s".to_string(),
+            tab_id: 1,
+            format: "webm".to_string(),
+            dir: dir.clone(),
+            frames,
+            bytes: 0,
+            dropped: 0,
+        };
+        let output = dir.join("out.avi");
+        write_mjpeg_avi(&recording, &timestamps, &frame_durations(&timestamps, 0.55), &output).unwrap();
+
+        let data = fs::read(&output).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize, data.len() - 8);
+        assert_eq!(&data[8..12], b"AVI ");
+        // 0.55 s at 10 fps: frame 0 for slots 0-3 (three repeats), then frame 1 for slots 4-5
+        let total_frames = u32::from_le_bytes(data[48..52].try_into().unwrap());
+        assert_eq!(total_frames, 6);
+        let idx = data.windows(4).rposition(|w| w == b"idx1").unwrap();
+        let sizes: Vec<u32> = (0..6)
+            .map(|i| u32::from_le_bytes(data[idx + 8 + i * 16 + 12..idx + 8 + i * 16 + 16].try_into().unwrap()))
+            .collect();
+        let size = jpeg(64, 48).len() as u32;
+        assert_eq!(sizes, vec![size, 0, 0, 0, size, 0]);
+    }
+}
@@ -0,0 +1,188 @@
+/*!
+ * Redaction Rules
+ *
+ * Scrubs auth headers and known secret patterns from captured data before
+ * it is stored or returned to agents.
+ *
+ * - Header redaction is by (case-insensitive) name
+ * - Built-in patterns cover bearer tokens, JWTs, cloud keys, and common
+ *   secret-looking JSON fields / query parameters
+ * - Operators can add their own header names and regex patterns
+ */
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+const DEFAULT_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+    "x-csrf-token",
+    "x-xsrf-token",
+];
+
+/// (pattern, replacement) — replacements keep the non-secret prefix in `$1`
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    (r"(?i)(bearer\s+)[A-Za-z0-9\-._~+/]+=*", "${1}[REDACTED]"),
+    (r"(?i)(basic\s+)[A-Za-z0-9+/]+=*", "${1}[REDACTED]"),
+    (r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+", "[REDACTED]"),
+    (r"\b(AKIA|ASIA)[0-9A-Z]{16}\b", "[REDACTED]"),
+    (r"\b(ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{36}\b", "[REDACTED]"),
+    (r"\bsk-[A-Za-z0-9_-]{20,}\b", "[REDACTED]"),
+    (
+        r#"(?i)("(?:password|passwd|secret|token|api_?key|access_?token|refresh_?token|client_?secret|otp)"\s*:\s*)"[^"]*""#,
+        r#"${1}"[REDACTED]""#,
+    ),
+    (
+        r"(?i)\b((?:password|passwd|secret|token|api_?key|access_?token|refresh_?token|client_?secret|otp)=)[^&\s]+",
+        "${1}[REDACTED]",
+    ),
+];
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Operator-configurable additions to the built-in rules
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionPolicy {
+    /// Extra header names to redact (case-insensitive)
+    #[serde(default)]
+    pub headers: Vec<String>,
+    /// Extra regex patterns; every match is replaced with `[REDACTED]`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// Compiled form of a `RedactionPolicy`
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    headers: Vec<String>,
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    pub fn new(policy: &RedactionPolicy) -> Result<Self> {
+        let mut rules = Vec::with_capacity(BUILTIN_PATTERNS.len() + policy.patterns.len());
+        for (pattern, replacement) in BUILTIN_PATTERNS {
+            rules.push((Regex::new(pattern)?, replacement.to_string()));
+        }
+        for pattern in &policy.patterns {
+            let regex = Regex::new(pattern)
+                .map_err(|e| anyhow!("Invalid redaction pattern '{}': {}", pattern, e))?;
+            rules.push((regex, REDACTED.to_string()));
+        }
+
+        let headers = DEFAULT_HEADERS
+            .iter()
+            .map(|h| h.to_string())
+            .chain(policy.headers.iter().map(|h| h.to_ascii_lowercase()))
+            .collect();
+
+        Ok(Self { headers, rules })
+    }
+
+    /// Whether a header's value should be hidden entirely
+    pub fn is_sensitive_header(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.headers.contains(&name)
+    }
+
+    /// Redact a header map in place, returning the number of values hidden
+    pub fn redact_headers(&self, headers: &mut serde_json::Map<String, serde_json::Value>) -> u32 {
+        let mut count = 0;
+        for (name, value) in headers.iter_mut() {
+            if self.is_sensitive_header(name) {
+                *value = serde_json::Value::String(REDACTED.to_string());
+                count += 1;
+            } else if let Some(text) = value.as_str() {
+                let (redacted, n) = self.redact_text(text);
+                if n > 0 {
+                    *value = serde_json::Value::String(redacted);
+                    count += n;
+                }
+            }
+        }
+        count
+    }
+
+    /// Apply every pattern to free text, returning the text and match count
+    pub fn redact_text(&self, text: &str) -> (String, u32) {
+        let mut out = text.to_string();
+        let mut count = 0;
+        for (regex, replacement) in &self.rules {
+            let matches = regex.find_iter(&out).count() as u32;
+            if matches > 0 {
+                out = regex.replace_all(&out, replacement.as_str()).into_owned();
+                count += matches;
+            }
+        }
+        (out, count)
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(&RedactionPolicy::default()).expect("built-in redaction patterns are valid")
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_patterns() {
+        let redactor = Redactor::default();
+
+        let (text, n) = redactor.redact_text(r#"{"user":"bob","password":"hunter2"}"#);
+        assert_eq!(text, r#"{"user":"bob","password":"[REDACTED]"}"#);
+        assert_eq!(n, 1);
+
+        let (text, _) = redactor.redact_text("https://api.example.com/v1?api_key=abc123&page=2");
+        assert_eq!(text, "https://api.example.com/v1?api_key=[REDACTED]&page=2");
+
+        let (text, _) = redactor.redact_text("Bearer abc.def-ghi");
+        assert_eq!(text, "Bearer [REDACTED]");
+    }
+
+    #[test]
+    fn test_custom_headers_and_patterns() {
+        let redactor = Redactor::new(&RedactionPolicy {
+            headers: vec!["X-Tenant-Secret".to_string()],
+            patterns: vec![r"acct-\d{6}".to_string()],
+        })
+        .unwrap();
+
+        let mut headers = serde_json::json!({
+            "x-tenant-secret": "s3cr3t",
+            "Authorization": "Bearer xyz",
+            "accept": "application/json"
+        });
+        let map = headers.as_object_mut().unwrap();
+        assert_eq!(redactor.redact_headers(map), 2);
+        assert_eq!(map["accept"], "application/json");
+
+        let (text, _) = redactor.redact_text("account acct-123456 updated");
+        assert_eq!(text, "account [REDACTED] updated");
+    }
+
+    #[test]
+    fn test_invalid_pattern_rejected() {
+        let policy = RedactionPolicy {
+            headers: vec![],
+            patterns: vec!["(".to_string()],
+        };
+        assert!(Redactor::new(&policy).is_err());
+    }
+}
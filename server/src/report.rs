@@ -0,0 +1,587 @@
+/*!
+ * Session Reports
+ *
+ * Every tool call an MCP session makes is kept in a bounded in-memory
+ * timeline: the tool, its arguments with typed values and secrets removed,
+ * when it ran, how long it took and whether it failed. `session_report`
+ * renders that timeline together with the session's screenshots, a summary
+ * of the network traffic captured while it ran and its audit log entries
+ * into a single HTML file under `reports/` in the session's artifact
+ * directory. Images are inlined as data: URLs and the page has no scripts
+ * or external resources, so the file can be mailed or attached as-is to
+ * show people what the agent did. The timeline is dropped when the session
+ * ends.
+ *
+ * Each call gets a per-session id ("c1", "c2", ...). Failed calls also keep
+ * their unredacted arguments in memory so `history_retry` can run them
+ * again; a retry is recorded as a new call that names the call it re-ran
+ * and the first call of the chain. Calls another session ran through
+ * `session_inject` name that session.
+ */
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+use crate::artifacts;
+use crate::mcp::JsonRpcRes;
+use crate::network_capture::HttpEntry;
+use crate::redaction::{Redactor, REDACTED};
+
+/// Oldest calls are dropped beyond this
+const MAX_CALLS: usize = 1000;
+const MAX_ARGUMENT_CHARS: usize = 300;
+const MAX_ERROR_CHARS: usize = 500;
+const MAX_SCREENSHOTS: usize = 20;
+const MAX_SCREENSHOT_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_FAILED_REQUESTS: usize = 20;
+const SLOWEST_REQUESTS: usize = 5;
+
+/// Arguments holding typed values or secrets, never recorded
+const SECRET_ARGUMENTS: &[&str] = &["value", "values", "text", "password", "secret", "secrets", "code", "otp", "token"];
+
+// ============================================================================
+// Timeline
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    /// Per-session sequence, assigned when recorded
+    pub call_id: String,
+    pub tool: String,
+    /// Compact JSON of the redacted arguments
+    pub arguments: String,
+    pub started_at: String,
+    #[serde(skip)]
+    pub started_ms: i64,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Call this one re-ran through history_retry
+    pub retry_of: Option<String>,
+    /// First call of the retry chain
+    pub retry_root: Option<String>,
+    /// Session that ran this call through session_inject
+    pub injected_by: Option<String>,
+    /// Unredacted arguments, kept only for failed calls
+    #[serde(skip)]
+    pub replay_arguments: Option<serde_json::Value>,
+}
+
+/// A tools/call request being timed
+pub struct PendingCall {
+    tool: String,
+    arguments: String,
+    raw_arguments: serde_json::Value,
+    started_at: chrono::DateTime<chrono::Utc>,
+    started: std::time::Instant,
+    retry_of: Option<(String, String)>,
+    injected_by: Option<String>,
+}
+
+impl PendingCall {
+    pub fn begin(params: Option<&serde_json::Value>) -> Self {
+        let field = |key: &str| params.and_then(|p| p.get(key));
+        let arguments = field("arguments").cloned().unwrap_or(serde_json::Value::Null);
+        Self {
+            tool: field("name").and_then(|v| v.as_str()).unwrap_or("?").to_string(),
+            arguments: redact_arguments(&arguments),
+            raw_arguments: arguments,
+            started_at: chrono::Utc::now(),
+            started: std::time::Instant::now(),
+            retry_of: None,
+            injected_by: None,
+        }
+    }
+
+    /// Attribute this call to the session that injected it
+    pub fn injected_by(mut self, session: &str) -> Self {
+        self.injected_by = Some(session.to_string());
+        self
+    }
+
+    /// Mark this call as a retry of `original`
+    pub fn retrying(mut self, original: &ToolCall) -> Self {
+        let root = original.retry_root.clone().unwrap_or_else(|| original.call_id.clone());
+        self.retry_of = Some((original.call_id.clone(), root));
+        self
+    }
+
+    pub fn finish(self, res: &JsonRpcRes) -> ToolCall {
+        let error = match (&res.error, &res.result) {
+            (Some(error), _) => Some(error.message.clone()),
+            (None, Some(result)) if result.get("isError").and_then(|v| v.as_bool()) == Some(true) => Some(
+                result["content"][0]["text"]
+                    .as_str()
+                    .unwrap_or("Tool reported an error")
+                    .to_string(),
+            ),
+            _ => None,
+        };
+        let (retry_of, retry_root) = self.retry_of.unzip();
+        ToolCall {
+            call_id: String::new(),
+            tool: self.tool,
+            arguments: self.arguments,
+            started_at: self.started_at.to_rfc3339(),
+            started_ms: self.started_at.timestamp_millis(),
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            replay_arguments: error.is_some().then_some(self.raw_arguments),
+            error: error.map(|e| truncate(&Redactor::default().redact_text(&e).0, MAX_ERROR_CHARS)),
+            retry_of,
+            retry_root,
+            injected_by: self.injected_by,
+        }
+    }
+}
+
+/// Arguments as compact JSON with secret-bearing fields masked and known secret patterns scrubbed
+fn redact_arguments(arguments: &serde_json::Value) -> String {
+    let mut arguments = arguments.clone();
+    if let Some(map) = arguments.as_object_mut() {
+        for (key, value) in map.iter_mut() {
+            if SECRET_ARGUMENTS.contains(&key.to_ascii_lowercase().as_str()) {
+                *value = serde_json::Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    let json = match &arguments {
+        serde_json::Value::Object(map) if map.is_empty() => String::new(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    truncate(&Redactor::default().redact_text(&json).0, MAX_ARGUMENT_CHARS)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[derive(Default)]
+struct Timeline {
+    calls: VecDeque<ToolCall>,
+    recorded: u64,
+}
+
+/// Tool calls per MCP session, oldest first
+#[derive(Default)]
+pub struct SessionHistory {
+    sessions: RwLock<HashMap<String, Timeline>>,
+}
+
+impl SessionHistory {
+    /// Append a call, returning the id it was given
+    pub async fn record(&self, session: &str, mut call: ToolCall) -> String {
+        let mut sessions = self.sessions.write().await;
+        let timeline = sessions.entry(session.to_string()).or_default();
+        if timeline.calls.len() >= MAX_CALLS {
+            timeline.calls.pop_front();
+        }
+        timeline.recorded += 1;
+        call.call_id = format!("c{}", timeline.recorded);
+        let call_id = call.call_id.clone();
+        timeline.calls.push_back(call);
+        call_id
+    }
+
+    pub async fn for_session(&self, session: &str) -> Vec<ToolCall> {
+        self.sessions
+            .read()
+            .await
+            .get(session)
+            .map(|timeline| timeline.calls.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// A failed call that can be re-run: `call_id`, or the session's most
+    /// recent failure other than a failed retry request
+    pub async fn failed_call(&self, session: &str, call_id: Option<&str>) -> Result<ToolCall, String> {
+        let sessions = self.sessions.read().await;
+        let calls = sessions.get(session).map(|timeline| &timeline.calls);
+        let mut calls = calls.into_iter().flatten().rev();
+        match call_id {
+            Some(call_id) => {
+                let call = calls
+                    .find(|call| call.call_id == call_id)
+                    .ok_or(format!("No call {} in the history of session {}", call_id, session))?;
+                if call.replay_arguments.is_none() {
+                    return Err(format!("Call {} ({}) succeeded; only failed calls can be retried", call_id, call.tool));
+                }
+                Ok(call.clone())
+            }
+            None => calls
+                .find(|call| call.replay_arguments.is_some() && call.tool != "history_retry")
+                .cloned()
+                .ok_or(format!("Session {} has no failed calls to retry", session)),
+        }
+    }
+
+    pub async fn forget(&self, session: &str) {
+        self.sessions.write().await.remove(session);
+    }
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedRequest {
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSummary {
+    pub requests: usize,
+    pub bytes: u64,
+    pub by_type: BTreeMap<String, usize>,
+    /// (url, duration ms)
+    pub slowest: Vec<(String, f64)>,
+    pub failed: Vec<FailedRequest>,
+    pub failed_count: usize,
+}
+
+/// Summarize HTTP entries captured at or after `since_ms`
+pub fn summarize_network(entries: &[HttpEntry], since_ms: i64) -> NetworkSummary {
+    let entries: Vec<&HttpEntry> = entries.iter().filter(|e| e.started_at >= since_ms).collect();
+    let mut summary = NetworkSummary {
+        requests: entries.len(),
+        bytes: entries.iter().filter_map(|e| e.size).sum(),
+        ..Default::default()
+    };
+    for entry in &entries {
+        let kind = entry.resource_type.clone().unwrap_or_else(|| "other".to_string());
+        *summary.by_type.entry(kind).or_default() += 1;
+        if entry.status.is_none_or(|status| status >= 400) {
+            summary.failed_count += 1;
+            if summary.failed.len() < MAX_FAILED_REQUESTS {
+                summary.failed.push(FailedRequest {
+                    method: entry.method.clone(),
+                    url: entry.url.clone(),
+                    status: entry.status,
+                });
+            }
+        }
+    }
+    let mut timed: Vec<(String, f64)> = entries
+        .iter()
+        .filter_map(|e| e.duration_ms.map(|ms| (e.url.clone(), ms)))
+        .collect();
+    timed.sort_by(|a, b| b.1.total_cmp(&a.1));
+    timed.truncate(SLOWEST_REQUESTS);
+    summary.slowest = timed;
+    summary
+}
+
+pub struct Screenshot {
+    pub name: String,
+    pub modified: String,
+    pub data_url: String,
+}
+
+/// The session's newest PNG/JPEG artifacts as data: URLs, oldest first
+pub fn screenshots(session: &str) -> Result<Vec<Screenshot>> {
+    use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+
+    let mut shots = Vec::new();
+    for file in artifacts::list(session)? {
+        let name = file["name"].as_str().unwrap_or_default();
+        let mime = match name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()) {
+            Some(ext) if ext == "png" => "image/png",
+            Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+            _ => continue,
+        };
+        if file["bytes"].as_u64().unwrap_or(0) > MAX_SCREENSHOT_BYTES {
+            continue;
+        }
+        let Some(path) = file["path"].as_str() else { continue };
+        let Ok(bytes) = std::fs::read(path) else { continue };
+        shots.push(Screenshot {
+            name: name.to_string(),
+            modified: file["modified"].as_str().unwrap_or_default().to_string(),
+            data_url: format!("data:{};base64,{}", mime, BASE64.encode(bytes)),
+        });
+        if shots.len() == MAX_SCREENSHOTS {
+            break;
+        }
+    }
+    shots.reverse();
+    Ok(shots)
+}
+
+pub struct Report {
+    pub title: String,
+    pub session: String,
+    pub calls: Vec<ToolCall>,
+    pub screenshots: Vec<Screenshot>,
+    pub network: NetworkSummary,
+    pub audit: Vec<String>,
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "body{font:14px/1.5 system-ui,sans-serif;margin:2rem auto;max-width:1100px;padding:0 1rem;color:#222}\
+h1{margin-bottom:.2rem}.meta{color:#666}\
+table{border-collapse:collapse;width:100%;margin:.5rem 0 1.5rem}\
+th,td{border-bottom:1px solid #ddd;padding:.35rem .5rem;text-align:left;vertical-align:top}\
+th{background:#f5f5f5}code{font-size:12px;word-break:break-all}\
+.failed{color:#b00020}.ok{color:#1b7f3b}\
+figure{margin:0 0 1.5rem}figure img{max-width:100%;border:1px solid #ddd}figcaption{color:#666;font-size:12px}\
+.stats span{display:inline-block;margin-right:1.5rem}";
+
+/// Render the report as a standalone HTML document
+pub fn render(report: &Report) -> String {
+    let failures = report.calls.iter().filter(|c| c.error.is_some()).count();
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\
+         <title>{title}</title><style>{STYLE}</style></head><body>\
+         <h1>{title}</h1><p class=\"meta\">Session {session} · generated {generated}</p>\
+         <p class=\"stats\"><span><b>{calls}</b> actions</span><span><b>{failures}</b> failed</span>\
+         <span><b>{shots}</b> screenshots</span><span><b>{requests}</b> network requests</span></p>",
+        title = escape(&report.title),
+        session = escape(&report.session),
+        generated = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        calls = report.calls.len(),
+        shots = report.screenshots.len(),
+        requests = report.network.requests,
+    );
+
+    html.push_str("<h2>Timeline</h2>");
+    if report.calls.is_empty() {
+        html.push_str("<p>No actions recorded.</p>");
+    } else {
+        html.push_str("<table><tr><th>Time</th><th>Call</th><th>Action</th><th>Details</th><th>Duration</th><th>Result</th></tr>");
+        for call in &report.calls {
+            let outcome = match &call.error {
+                Some(error) => format!("<span class=\"failed\">Failed: {}</span>", escape(error)),
+                None => "<span class=\"ok\">OK</span>".to_string(),
+            };
+            let mut call_id = call.call_id.clone();
+            if let Some(retry_of) = &call.retry_of {
+                let _ = write!(call_id, " (retry of {})", retry_of);
+            }
+            if let Some(injected_by) = &call.injected_by {
+                let _ = write!(call_id, " (injected by {})", injected_by);
+            }
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td><td>{} ms</td><td>{}</td></tr>",
+                escape(&call.started_at),
+                escape(&call_id),
+                escape(&call.tool),
+                escape(&call.arguments),
+                call.duration_ms,
+                outcome
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    if !report.screenshots.is_empty() {
+        html.push_str("<h2>Screenshots</h2>");
+        for shot in &report.screenshots {
+            let _ = write!(
+                html,
+                "<figure><img src=\"{}\" alt=\"{name}\"><figcaption>{name} · {}</figcaption></figure>",
+                shot.data_url,
+                escape(&shot.modified),
+                name = escape(&shot.name),
+            );
+        }
+    }
+
+    let network = &report.network;
+    let _ = write!(
+        html,
+        "<h2>Network</h2><p class=\"stats\"><span><b>{}</b> requests</span><span><b>{}</b> KB</span>\
+         <span><b>{}</b> failed</span></p>",
+        network.requests,
+        network.bytes / 1024,
+        network.failed_count
+    );
+    if !network.by_type.is_empty() {
+        html.push_str("<table><tr><th>Type</th><th>Requests</th></tr>");
+        for (kind, count) in &network.by_type {
+            let _ = write!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(kind), count);
+        }
+        html.push_str("</table>");
+    }
+    if !network.slowest.is_empty() {
+        html.push_str("<h3>Slowest requests</h3><table><tr><th>URL</th><th>Duration</th></tr>");
+        for (url, ms) in &network.slowest {
+            let _ = write!(html, "<tr><td><code>{}</code></td><td>{:.0} ms</td></tr>", escape(url), ms);
+        }
+        html.push_str("</table>");
+    }
+    if !network.failed.is_empty() {
+        html.push_str("<h3>Failed requests</h3><table><tr><th>Method</th><th>URL</th><th>Status</th></tr>");
+        for request in &network.failed {
+            let status = request.status.map_or("no response".to_string(), |s| s.to_string());
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td><code>{}</code></td><td class=\"failed\">{}</td></tr>",
+                escape(&request.method),
+                escape(&request.url),
+                status
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    html.push_str("<h2>Audit events</h2>");
+    if report.audit.is_empty() {
+        html.push_str("<p>No credential or authorization events.</p>");
+    } else {
+        html.push_str("<ul>");
+        for line in &report.audit {
+            let _ = write!(html, "<li><code>{}</code></li>", escape(line));
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Write the report to `reports/<time>.html` in the session's artifacts
+pub fn write(session: &str, html: &str) -> Result<PathBuf> {
+    let dir = artifacts::session_dir(session)?.join("reports");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.html", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    std::fs::write(&path, html)?;
+    Ok(path)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http(url: &str, status: Option<u16>, started_at: i64, duration_ms: f64) -> HttpEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": url,
+            "url": url,
+            "method": "GET",
+            "status": status,
+            "resource_type": "xhr",
+            "tab_id": 1,
+            "started_at": started_at,
+            "duration_ms": duration_ms,
+            "size": 2048,
+            "request_headers": {},
+            "response_headers": {},
+            "redactions": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_arguments_are_redacted() {
+        let args = redact_arguments(&serde_json::json!({
+            "selector": "#password",
+            "value": "hunter2",
+            "url": "https://example.com/?access_token=abc123"
+        }));
+        assert!(args.contains("#password"));
+        assert!(!args.contains("hunter2"));
+        assert!(!args.contains("abc123"));
+        assert_eq!(redact_arguments(&serde_json::json!({})), "");
+    }
+
+    #[tokio::test]
+    async fn test_history_records_outcomes() {
+        let history = SessionHistory::default();
+        let params = serde_json::json!({ "name": "playwright_click", "arguments": { "selector": "#go" } });
+        let call = PendingCall::begin(Some(&params));
+        history.record("a", call.finish(&JsonRpcRes::err(Some(1.into()), -32000, "Element not found", None))).await;
+
+        let calls = history.for_session("a").await;
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].call_id, "c1");
+        assert_eq!(calls[0].tool, "playwright_click");
+        assert_eq!(calls[0].error.as_deref(), Some("Element not found"));
+        history.forget("a").await;
+        assert!(history.for_session("a").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_calls_keep_arguments_for_retry() {
+        let history = SessionHistory::default();
+        let params = serde_json::json!({ "name": "playwright_fill", "arguments": { "selector": "#pw", "value": "hunter2" } });
+        let failed = PendingCall::begin(Some(&params)).finish(&JsonRpcRes::err(None, -32000, "Element not found", None));
+        assert_eq!(history.record("a", failed).await, "c1");
+        let ok = PendingCall::begin(Some(&params)).finish(&JsonRpcRes::ok(None, serde_json::json!({})));
+        history.record("a", ok).await;
+
+        // The failed call keeps its real arguments; the timeline shows them redacted
+        let original = history.failed_call("a", None).await.unwrap();
+        assert_eq!(original.replay_arguments.as_ref().unwrap()["value"], "hunter2");
+        assert!(!original.arguments.contains("hunter2"));
+        assert!(history.failed_call("a", Some("c2")).await.unwrap_err().contains("succeeded"));
+        assert!(history.failed_call("b", None).await.is_err());
+
+        // Retries of retries point back to the first call
+        let retry = PendingCall::begin(Some(&params)).retrying(&original);
+        let retry = retry.finish(&JsonRpcRes::err(None, -32000, "Still not found", None));
+        history.record("a", retry).await;
+        let retry = history.failed_call("a", None).await.unwrap();
+        assert_eq!((retry.call_id.as_str(), retry.retry_of.as_deref()), ("c3", Some("c1")));
+        let again = PendingCall::begin(Some(&params)).retrying(&retry).finish(&JsonRpcRes::ok(None, serde_json::json!({})));
+        assert_eq!((again.retry_of.as_deref(), again.retry_root.as_deref()), (Some("c3"), Some("c1")));
+    }
+
+    #[test]
+    fn test_network_summary_and_render() {
+        let entries = [
+            http("https://old.example/", Some(200), 100, 10.0),
+            http("https://shop.example/api", Some(500), 2000, 900.0),
+            http("https://shop.example/<script>", Some(200), 3000, 50.0),
+        ];
+        let network = summarize_network(&entries, 1000);
+        assert_eq!(network.requests, 2);
+        assert_eq!(network.failed_count, 1);
+        assert_eq!(network.slowest[0].0, "https://shop.example/api");
+
+        let html = render(&Report {
+            title: "Checkout run".to_string(),
+            session: "tcp:127.0.0.1:5000".to_string(),
+            calls: Vec::new(),
+            screenshots: Vec::new(),
+            network,
+            audit: vec!["[t] Session tcp:127.0.0.1:5000 authorized for 8 hours".to_string()],
+        });
+        assert!(html.contains("<title>Checkout run</title>"));
+        assert!(html.contains("https://shop.example/&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("authorized for 8 hours"));
+    }
+}
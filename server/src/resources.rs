@@ -0,0 +1,199 @@
+/*!
+ * MCP Resources for Page Artifacts
+ *
+ * Exposes page context as MCP resources so agents can pull it on demand
+ * instead of receiving it inline in tool results:
+ * - browser://tab/{tabId}/dom - current HTML of a tab ("active" for the focused tab)
+ * - browser://tab/{tabId}/text - extracted visible text of a tab
+ * - browser://screenshots/{id} - recent screenshots taken through the tools
+ */
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+
+pub const ACTIVE_TAB: &str = "active";
+const TAB_PREFIX: &str = "browser://tab/";
+const SCREENSHOT_PREFIX: &str = "browser://screenshots/";
+const MAX_RECENT_SCREENSHOTS: usize = 10;
+
+// ============================================================================
+// URIs
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowserResource {
+    Dom { tab: String },
+    Text { tab: String },
+    Screenshot { id: u64 },
+}
+
+impl BrowserResource {
+    pub fn parse(uri: &str) -> Option<Self> {
+        if let Some(rest) = uri.strip_prefix(TAB_PREFIX) {
+            let (tab, kind) = rest.split_once('/')?;
+            if tab != ACTIVE_TAB && tab.parse::<u64>().is_err() {
+                return None;
+            }
+            let tab = tab.to_string();
+            return match kind {
+                "dom" => Some(Self::Dom { tab }),
+                "text" => Some(Self::Text { tab }),
+                _ => None,
+            };
+        }
+        let id = uri.strip_prefix(SCREENSHOT_PREFIX)?.parse().ok()?;
+        Some(Self::Screenshot { id })
+    }
+
+    pub fn uri(&self) -> String {
+        match self {
+            Self::Dom { tab } => format!("{}{}/dom", TAB_PREFIX, tab),
+            Self::Text { tab } => format!("{}{}/text", TAB_PREFIX, tab),
+            Self::Screenshot { id } => format!("{}{}", SCREENSHOT_PREFIX, id),
+        }
+    }
+
+    /// Extension parameters selecting the tab (empty for the active tab)
+    pub fn tab_params(tab: &str) -> serde_json::Value {
+        match tab.parse::<u64>() {
+            Ok(tab_id) => serde_json::json!({ "tabId": tab_id }),
+            Err(_) => serde_json::json!({}),
+        }
+    }
+}
+
+/// resources/templates/list entries
+pub fn templates() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "uriTemplate": "browser://tab/{tabId}/dom",
+            "name": "Tab HTML",
+            "description": "Current HTML of a tab (use \"active\" for the focused tab)",
+            "mimeType": "text/html"
+        },
+        {
+            "uriTemplate": "browser://tab/{tabId}/text",
+            "name": "Tab text",
+            "description": "Visible text extracted from a tab",
+            "mimeType": "text/plain"
+        }
+    ])
+}
+
+/// resources/list entries for one tab
+pub fn tab_resources(tab: &str, title: Option<&str>, url: Option<&str>) -> Vec<serde_json::Value> {
+    let label = title.or(url).unwrap_or(tab);
+    vec![
+        serde_json::json!({
+            "uri": BrowserResource::Dom { tab: tab.to_string() }.uri(),
+            "name": format!("HTML: {}", label),
+            "mimeType": "text/html"
+        }),
+        serde_json::json!({
+            "uri": BrowserResource::Text { tab: tab.to_string() }.uri(),
+            "name": format!("Text: {}", label),
+            "mimeType": "text/plain"
+        }),
+    ]
+}
+
+// ============================================================================
+// Recent Screenshots
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ScreenshotEntry {
+    pub id: u64,
+    /// Base64-encoded PNG
+    pub data: String,
+    pub url: Option<String>,
+    pub taken: i64,
+}
+
+/// Bounded buffer of the most recent screenshots
+#[derive(Default)]
+pub struct RecentScreenshots {
+    next_id: AtomicU64,
+    entries: RwLock<VecDeque<ScreenshotEntry>>,
+}
+
+impl RecentScreenshots {
+    pub async fn record(&self, data: String, url: Option<String>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut entries = self.entries.write().await;
+        if entries.len() == MAX_RECENT_SCREENSHOTS {
+            entries.pop_front();
+        }
+        entries.push_back(ScreenshotEntry {
+            id,
+            data,
+            url,
+            taken: chrono::Utc::now().timestamp(),
+        });
+        id
+    }
+
+    pub async fn get(&self, id: u64) -> Option<ScreenshotEntry> {
+        self.entries.read().await.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// resources/list entries, newest first
+    pub async fn resources(&self) -> Vec<serde_json::Value> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .rev()
+            .map(|e| {
+                serde_json::json!({
+                    "uri": BrowserResource::Screenshot { id: e.id }.uri(),
+                    "name": format!("Screenshot {}", e.id),
+                    "description": e.url.as_deref().map(|u| format!("Captured from {}", u)),
+                    "mimeType": "image/png",
+                    "annotations": {
+                        "lastModified": chrono::DateTime::from_timestamp(e.taken, 0).map(|t| t.to_rfc3339())
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uris() {
+        assert_eq!(
+            BrowserResource::parse("browser://tab/1/dom"),
+            Some(BrowserResource::Dom { tab: "1".to_string() })
+        );
+        assert_eq!(
+            BrowserResource::parse("browser://tab/active/text"),
+            Some(BrowserResource::Text { tab: "active".to_string() })
+        );
+        assert_eq!(
+            BrowserResource::parse("browser://screenshots/3"),
+            Some(BrowserResource::Screenshot { id: 3 })
+        );
+        assert_eq!(BrowserResource::parse("browser://tab/x/dom"), None);
+        assert_eq!(BrowserResource::parse("file:///etc/passwd"), None);
+    }
+
+    #[tokio::test]
+    async fn test_recent_screenshots_are_bounded() {
+        let shots = RecentScreenshots::default();
+        for _ in 0..MAX_RECENT_SCREENSHOTS + 2 {
+            shots.record("AAAA".to_string(), None).await;
+        }
+        assert!(shots.get(1).await.is_none());
+        assert!(shots.get(MAX_RECENT_SCREENSHOTS as u64 + 2).await.is_some());
+        assert_eq!(shots.resources().await.len(), MAX_RECENT_SCREENSHOTS);
+    }
+}
@@ -0,0 +1,210 @@
+/*!
+ * MCP Roots
+ *
+ * Clients that declare the `roots` capability are asked for `roots/list`
+ * after `notifications/initialized` and again on
+ * `notifications/roots/list_changed`. The declared directories bound where
+ * tools may read and write files (screenshots, exported PDFs, manifests).
+ * Sessions without roots are confined to their artifact directory
+ * (`~/.agent-browser/artifacts/<session>/`).
+ */
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use tokio::sync::RwLock;
+
+use crate::artifacts;
+
+/// How long to wait for a client to answer roots/list
+pub const ROOTS_LIST_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Default)]
+struct SessionRoots {
+    supported: bool,
+    roots: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct ClientRoots {
+    sessions: RwLock<HashMap<String, SessionRoots>>,
+}
+
+impl ClientRoots {
+    /// Record whether a client declared the roots capability in `initialize`
+    pub async fn set_capabilities(&self, session: &str, capabilities: Option<&Value>) {
+        let supported = capabilities.and_then(|c| c.get("roots")).is_some();
+        self.sessions.write().await.insert(
+            session.to_string(),
+            SessionRoots {
+                supported,
+                roots: Vec::new(),
+            },
+        );
+    }
+
+    pub async fn supports_roots(&self, session: &str) -> bool {
+        self.sessions.read().await.get(session).is_some_and(|s| s.supported)
+    }
+
+    /// Store the result of roots/list; returns the accepted directories
+    pub async fn set_roots(&self, session: &str, result: &Value) -> Vec<PathBuf> {
+        let roots: Vec<PathBuf> = result
+            .get("roots")
+            .and_then(|v| v.as_array())
+            .map(|roots| {
+                roots
+                    .iter()
+                    .filter_map(|r| r.get("uri").and_then(|v| v.as_str()))
+                    .filter_map(file_uri_to_path)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(entry) = self.sessions.write().await.get_mut(session) {
+            entry.roots = roots.clone();
+        }
+        roots
+    }
+
+    pub async fn forget(&self, session: &str) {
+        self.sessions.write().await.remove(session);
+    }
+
+    /// Directories a session may touch: its roots, or its artifact directory
+    pub async fn allowed_dirs(&self, session: &str) -> Result<Vec<PathBuf>, String> {
+        let roots = self
+            .sessions
+            .read()
+            .await
+            .get(session)
+            .map(|s| s.roots.clone())
+            .unwrap_or_default();
+        if !roots.is_empty() {
+            return Ok(roots);
+        }
+        artifacts::session_dir(session)
+            .map(|dir| vec![dir])
+            .map_err(|e| format!("Artifacts directory unavailable: {}", e))
+    }
+
+    /// Resolve a tool-supplied file path and check it falls under an allowed directory
+    pub async fn resolve(&self, session: &str, requested: &str) -> Result<PathBuf, String> {
+        resolve_within(&self.allowed_dirs(session).await?, requested)
+    }
+}
+
+/// `file:///home/me/My%20Project` -> `/home/me/My Project`; other schemes are ignored
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Only local files: an empty or localhost authority
+    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !path.starts_with('/') {
+        return None;
+    }
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    let path = PathBuf::from(String::from_utf8(decoded).ok()?);
+
+    // Windows drive paths arrive as /C:/Users/...
+    #[cfg(windows)]
+    let path = path.strip_prefix("/").map(Path::to_path_buf).unwrap_or(path);
+
+    Some(path)
+}
+
+/// Relative paths are taken against the first allowed directory. `..` is
+/// rejected outright and the parent directory is canonicalized so symlinks
+/// can't point outside the roots.
+pub fn resolve_within(allowed: &[PathBuf], requested: &str) -> Result<PathBuf, String> {
+    let first = allowed.first().ok_or("No allowed directories")?;
+    let requested_path = Path::new(requested);
+    if requested_path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("Path '{}' must not contain '..'", requested));
+    }
+    let joined = if requested_path.is_absolute() {
+        requested_path.to_path_buf()
+    } else {
+        first.join(requested_path)
+    };
+
+    let file_name = joined
+        .file_name()
+        .ok_or_else(|| format!("Path '{}' has no file name", requested))?
+        .to_owned();
+    let parent = joined.parent().unwrap_or(first);
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("Directory {} is not accessible: {}", parent.display(), e))?;
+
+    let inside = allowed.iter().any(|root| {
+        root.canonicalize()
+            .map(|root| parent.starts_with(&root))
+            .unwrap_or(false)
+    });
+    if !inside {
+        let roots: Vec<String> = allowed.iter().map(|r| r.display().to_string()).collect();
+        return Err(format!(
+            "Path '{}' is outside the allowed directories ({})",
+            requested,
+            roots.join(", ")
+        ));
+    }
+    Ok(parent.join(file_name))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri_to_path() {
+        assert_eq!(
+            file_uri_to_path("file:///home/me/My%20Project"),
+            Some(PathBuf::from("/home/me/My Project"))
+        );
+        assert_eq!(file_uri_to_path("file://localhost/tmp"), Some(PathBuf::from("/tmp")));
+        assert_eq!(file_uri_to_path("https://example.com/"), None);
+        assert_eq!(file_uri_to_path("file://server/share"), None);
+    }
+
+    #[test]
+    fn test_resolve_within_roots() {
+        let root = std::env::temp_dir().join(format!("ab-roots-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("shots")).unwrap();
+        let allowed = vec![root.clone()];
+        let canonical = root.canonicalize().unwrap();
+
+        assert_eq!(
+            resolve_within(&allowed, "shots/a.png").unwrap(),
+            canonical.join("shots").join("a.png")
+        );
+        let absolute = root.join("b.pdf");
+        assert_eq!(
+            resolve_within(&allowed, absolute.to_str().unwrap()).unwrap(),
+            canonical.join("b.pdf")
+        );
+        assert!(resolve_within(&allowed, "../escape.png").is_err());
+        assert!(resolve_within(&allowed, "/etc/passwd").is_err());
+        assert!(resolve_within(&allowed, "missing/dir/c.png").is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
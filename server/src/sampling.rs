@@ -0,0 +1,226 @@
+/*!
+ * MCP Sampling
+ *
+ * Lets the server ask a connected MCP client's LLM for help through
+ * `sampling/createMessage` (e.g. the extension asking which detected modal
+ * is a cookie banner).
+ *
+ * - Each bidirectional connection registers an outbound channel here
+ * - `initialize` records whether the client declared the sampling capability
+ * - Client responses to server-initiated requests are routed back by id
+ *
+ * The same channels carry other server-initiated requests such as `roots/list`.
+ */
+
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::time::{timeout, Duration};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Sampling waits on a model (and possibly a human approving the request)
+const SAMPLING_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_MAX_TOKENS: u64 = 512;
+
+struct ClientChannel {
+    tx: mpsc::Sender<String>,
+    supports_sampling: bool,
+}
+
+type PendingReply = oneshot::Sender<Result<Value, String>>;
+
+#[derive(Default)]
+pub struct SamplingClients {
+    clients: RwLock<HashMap<String, ClientChannel>>,
+    pending: RwLock<HashMap<String, PendingReply>>,
+}
+
+impl SamplingClients {
+    /// Register a connection that can receive server-initiated requests
+    pub async fn register(&self, session: &str, tx: mpsc::Sender<String>) {
+        self.clients.write().await.insert(
+            session.to_string(),
+            ClientChannel {
+                tx,
+                supports_sampling: false,
+            },
+        );
+    }
+
+    pub async fn unregister(&self, session: &str) {
+        self.clients.write().await.remove(session);
+    }
+
+    /// Record the capabilities a client sent with `initialize`
+    pub async fn set_capabilities(&self, session: &str, capabilities: Option<&Value>) {
+        let supports_sampling = capabilities.and_then(|c| c.get("sampling")).is_some();
+        if let Some(client) = self.clients.write().await.get_mut(session) {
+            client.supports_sampling = supports_sampling;
+        }
+    }
+
+    /// Route a client's response to a server-initiated request; false if nobody was waiting
+    pub async fn handle_response(&self, response: &Value) -> bool {
+        let Some(id) = response.get("id").and_then(|v| v.as_str()) else {
+            return false;
+        };
+        let Some(reply) = self.pending.write().await.remove(id) else {
+            warn!("Response for unknown server request: {}", id);
+            return false;
+        };
+
+        let result = match (response.get("result"), response.get("error")) {
+            (Some(result), _) => Ok(result.clone()),
+            (None, Some(error)) => Err(error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Client returned an error")
+                .to_string()),
+            (None, None) => Err("Malformed response".to_string()),
+        };
+        let _ = reply.send(result);
+        true
+    }
+
+    /// Pick the preferred session if it supports sampling, else any client that does
+    async fn target(&self, preferred: Option<&str>) -> Option<(String, mpsc::Sender<String>)> {
+        let clients = self.clients.read().await;
+        preferred
+            .and_then(|session| clients.get_key_value(session))
+            .filter(|(_, c)| c.supports_sampling)
+            .or_else(|| clients.iter().find(|(_, c)| c.supports_sampling))
+            .map(|(session, c)| (session.clone(), c.tx.clone()))
+    }
+
+    /// Send sampling/createMessage to a client and wait for its result
+    pub async fn create_message(&self, preferred: Option<&str>, params: Value) -> Result<Value, String> {
+        let Some((session, tx)) = self.target(preferred).await else {
+            return Err("No connected MCP client supports sampling".to_string());
+        };
+        send_request(&self.pending, &session, tx, "sampling/createMessage", params, SAMPLING_TIMEOUT_SECS).await
+    }
+
+    /// Send any server-initiated request to a specific session and wait for its result
+    pub async fn request(&self, session: &str, method: &str, params: Value, timeout_secs: u64) -> Result<Value, String> {
+        let Some(tx) = self.clients.read().await.get(session).map(|c| c.tx.clone()) else {
+            return Err(format!("MCP client {} cannot receive server requests", session));
+        };
+        send_request(&self.pending, session, tx, method, params, timeout_secs).await
+    }
+}
+
+async fn send_request(
+    pending: &RwLock<HashMap<String, PendingReply>>,
+    session: &str,
+    tx: mpsc::Sender<String>,
+    method: &str,
+    params: Value,
+    timeout_secs: u64,
+) -> Result<Value, String> {
+    let prefix = method.split('/').next().unwrap_or(method);
+    let id = format!("{}-{}", prefix, Uuid::new_v4());
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    });
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    pending.write().await.insert(id.clone(), reply_tx);
+
+    debug!("Sending {} to {}", method, session);
+    if tx.send(request.to_string()).await.is_err() {
+        pending.write().await.remove(&id);
+        return Err(format!("MCP client {} disconnected", session));
+    }
+
+    match timeout(Duration::from_secs(timeout_secs), reply_rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(format!("{} request dropped", method)),
+        Err(_) => {
+            pending.write().await.remove(&id);
+            Err(format!("{} request timed out after {}s", method, timeout_secs))
+        }
+    }
+}
+
+/// Build createMessage params for a single user turn, optionally with a screenshot
+pub fn text_request(prompt: &str, screenshot: Option<&str>, system: Option<&str>, max_tokens: Option<u64>) -> Value {
+    let mut messages = vec![serde_json::json!({
+        "role": "user",
+        "content": { "type": "text", "text": prompt }
+    })];
+    if let Some(screenshot) = screenshot {
+        // Accept data URLs as sent by the extension
+        let data = screenshot.split_once("base64,").map(|(_, d)| d).unwrap_or(screenshot);
+        messages.push(serde_json::json!({
+            "role": "user",
+            "content": { "type": "image", "data": data, "mimeType": "image/png" }
+        }));
+    }
+
+    let mut params = serde_json::json!({
+        "messages": messages,
+        "maxTokens": max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "includeContext": "none",
+    });
+    if let Some(system) = system {
+        params["systemPrompt"] = serde_json::json!(system);
+    }
+    params
+}
+
+/// Text of a createMessage result
+pub fn result_text(result: &Value) -> Option<&str> {
+    result.get("content").and_then(|c| c.get("text")).and_then(|t| t.as_str())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_message_roundtrip() {
+        let clients = std::sync::Arc::new(SamplingClients::default());
+        let (tx, mut rx) = mpsc::channel(4);
+        clients.register("tcp:1", tx).await;
+
+        assert!(clients.create_message(None, serde_json::json!({})).await.is_err());
+        clients
+            .set_capabilities("tcp:1", Some(&serde_json::json!({ "sampling": {} })))
+            .await;
+
+        let requester = std::sync::Arc::clone(&clients);
+        let call = tokio::spawn(async move {
+            requester
+                .create_message(Some("stdio"), text_request("Which modal is the cookie banner?", None, None, None))
+                .await
+        });
+
+        let request: Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(request["method"], "sampling/createMessage");
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": { "role": "assistant", "content": { "type": "text", "text": "#2" } }
+        });
+        assert!(clients.handle_response(&response).await);
+
+        let result = call.await.unwrap().unwrap();
+        assert_eq!(result_text(&result), Some("#2"));
+    }
+
+    #[test]
+    fn test_text_request_strips_data_url() {
+        let params = text_request("q", Some("data:image/png;base64,AAAA"), Some("sys"), Some(64));
+        assert_eq!(params["messages"][1]["content"]["data"], "AAAA");
+        assert_eq!(params["systemPrompt"], "sys");
+        assert_eq!(params["maxTokens"], 64);
+    }
+}
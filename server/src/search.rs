@@ -0,0 +1,630 @@
+/*!
+ * Artifact Search
+ *
+ * `artifacts_search` runs full-text queries over what the server has kept:
+ * page text from saved snapshots, session reports and other text artifacts
+ * (`artifacts/<session>/`), and audit log lines, so "where did I see X last
+ * week" has an answer. Each source file is tokenized into its own inverted
+ * index segment, held in memory and rebuilt only when the file's size or
+ * modification time changes, so a search after the first re-reads just the
+ * files written since. Results are ranked with BM25 and carry a snippet
+ * around the first match.
+ *
+ * Queries are words (all must match), `"quoted phrases"`, `prefix*` and
+ * `-excluded` words, matched case-insensitively. Filters narrow by kind,
+ * session and time (`since` / `until` as RFC 3339, a date, or an age such
+ * as `7d`).
+ */
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::artifacts;
+use crate::quota::AUDIT_LOG_NAME;
+use crate::storage;
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+pub const KINDS: &[&str] = &["page", "report", "artifact", "audit"];
+/// Larger files (and binary artifacts) aren't indexed
+const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+const TEXT_EXTENSIONS: &[&str] = &["html", "htm", "txt", "md", "json", "csv", "xml", "log"];
+const SNIPPET_CHARS: usize = 160;
+const MAX_TOKEN_CHARS: usize = 64;
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+// ============================================================================
+// Documents
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub kind: &'static str,
+    pub path: String,
+    /// 1-based line, for audit events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Unix seconds
+    pub time: i64,
+    #[serde(skip)]
+    text: String,
+}
+
+/// Lowercased alphanumeric runs
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty() && token.chars().count() <= MAX_TOKEN_CHARS)
+        .map(str::to_lowercase)
+}
+
+/// Visible text of an HTML document: tags, scripts and styles removed,
+/// common entities decoded, whitespace collapsed
+pub fn html_text(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut i = 0;
+    while i < html.len() {
+        if lower[i..].starts_with("<script") || lower[i..].starts_with("<style") {
+            let close = if lower[i..].starts_with("<script") { "</script" } else { "</style" };
+            i = lower[i..].find(close).map_or(html.len(), |end| i + end + close.len());
+            continue;
+        }
+        if html.as_bytes()[i] == b'<' {
+            i = html[i..].find('>').map_or(html.len(), |end| i + end + 1);
+            text.push(' ');
+            continue;
+        }
+        let next = html[i..].find('<').map_or(html.len(), |end| i + end);
+        text.push_str(&html[i..next]);
+        i = next;
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(html_text(&html[start..end])).filter(|title| !title.is_empty())
+}
+
+fn modified_secs(modified: SystemTime) -> i64 {
+    chrono::DateTime::<chrono::Utc>::from(modified).timestamp()
+}
+
+/// Documents of one source file
+fn read_documents(root: &Path, path: &Path, modified: SystemTime) -> Result<Vec<Document>> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let display = path.display().to_string();
+    let mut components = relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned());
+    let top = components.next().unwrap_or_default();
+    let time = modified_secs(modified);
+
+    if top == AUDIT_LOG_NAME || (top.starts_with("audit-") && top.ends_with(".log")) {
+        return Ok(fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                // "[2026-01-02T03:04:05+00:00] message"
+                let (time, message) = line
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once("] "))
+                    .and_then(|(stamp, message)| Some((chrono::DateTime::parse_from_rfc3339(stamp).ok()?.timestamp(), message)))
+                    .unwrap_or((time, line));
+                Document {
+                    kind: "audit",
+                    path: display.clone(),
+                    line: Some(index + 1),
+                    title: None,
+                    url: None,
+                    session: None,
+                    time,
+                    text: message.to_string(),
+                }
+            })
+            .collect());
+    }
+
+    if top == "snapshots" {
+        // snapshots/<name>/dom.html, described by its meta.json
+        let dir = path.parent().ok_or_else(|| anyhow!("Snapshot file has no directory"))?;
+        let meta: serde_json::Value = fs::read_to_string(dir.join("meta.json"))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        let html = fs::read_to_string(path)?;
+        let text_of = |key: &str| meta.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        return Ok(vec![Document {
+            kind: "page",
+            path: display,
+            line: None,
+            title: text_of("title").or_else(|| html_title(&html)).or_else(|| text_of("name")),
+            url: text_of("url"),
+            session: text_of("session"),
+            time: meta.get("created").and_then(|v| v.as_i64()).unwrap_or(time),
+            text: html_text(&html),
+        }]);
+    }
+
+    // artifacts/<session dir>/...
+    let session = components.next();
+    let kind = if relative.components().nth(2).is_some_and(|c| c.as_os_str() == "reports") {
+        "report"
+    } else {
+        "artifact"
+    };
+    let raw = fs::read_to_string(path)?;
+    let is_html = path.extension().is_some_and(|e| e == "html" || e == "htm");
+    Ok(vec![Document {
+        kind,
+        path: display,
+        line: None,
+        title: if is_html { html_title(&raw) } else { None }
+            .or_else(|| path.file_name().map(|name| name.to_string_lossy().into_owned())),
+        url: None,
+        session,
+        time,
+        text: if is_html { html_text(&raw) } else { raw },
+    }])
+}
+
+// ============================================================================
+// Index
+// ============================================================================
+
+/// The inverted index of one source file
+struct Segment {
+    modified: SystemTime,
+    len: u64,
+    docs: Vec<Document>,
+    /// Token count of each document
+    lengths: Vec<u32>,
+    /// token → (document, term frequency)
+    postings: HashMap<String, Vec<(u32, u32)>>,
+}
+
+impl Segment {
+    fn build(docs: Vec<Document>, modified: SystemTime, len: u64) -> Self {
+        let mut postings: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+        let mut lengths = Vec::with_capacity(docs.len());
+        for (index, doc) in docs.iter().enumerate() {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            let mut length = 0;
+            for token in tokenize(&doc.text).chain(doc.title.iter().flat_map(|t| tokenize(t))) {
+                *counts.entry(token).or_default() += 1;
+                length += 1;
+            }
+            lengths.push(length);
+            for (token, count) in counts {
+                postings.entry(token).or_default().push((index as u32, count));
+            }
+        }
+        Self { modified, len, docs, lengths, postings }
+    }
+
+    /// Postings of a term, or of every token it prefixes
+    fn matches(&self, term: &Term) -> HashMap<u32, u32> {
+        let mut found = HashMap::new();
+        if term.prefix {
+            for (token, postings) in &self.postings {
+                if token.starts_with(&term.text) {
+                    for (doc, count) in postings {
+                        *found.entry(*doc).or_default() += count;
+                    }
+                }
+            }
+        } else if let Some(postings) = self.postings.get(&term.text) {
+            found.extend(postings.iter().copied());
+        }
+        found
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    text: String,
+    prefix: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+    /// Lowercased phrases that must appear verbatim
+    phrases: Vec<String>,
+    excluded: Vec<String>,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut query = Query::default();
+        let mut rest = text;
+        while let Some(start) = rest.find('"') {
+            let end = rest[start + 1..].find('"').ok_or("Unclosed quote in query")? + start + 1;
+            let phrase = rest[start + 1..end].to_lowercase();
+            query.terms.extend(tokenize(&phrase).map(|text| Term { text, prefix: false }));
+            query.phrases.push(phrase.split_whitespace().collect::<Vec<_>>().join(" "));
+            query.parse_words(&rest[..start]);
+            rest = &rest[end + 1..];
+        }
+        query.parse_words(rest);
+        if query.terms.is_empty() {
+            return Err("Query needs at least one word to search for".to_string());
+        }
+        Ok(query)
+    }
+
+    fn parse_words(&mut self, text: &str) {
+        for word in text.split_whitespace() {
+            if let Some(excluded) = word.strip_prefix('-') {
+                self.excluded.extend(tokenize(excluded));
+            } else if let Some(prefix) = word.strip_suffix('*').filter(|p| !p.is_empty()) {
+                self.terms.extend(tokenize(prefix).map(|text| Term { text, prefix: true }));
+            } else {
+                self.terms.extend(tokenize(word).map(|text| Term { text, prefix: false }));
+            }
+        }
+    }
+}
+
+/// Which documents a search considers
+#[derive(Debug, Default)]
+pub struct Filters {
+    pub kinds: Vec<String>,
+    /// Session label, compared by its artifact directory name
+    pub session: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl Filters {
+    fn accepts(&self, doc: &Document) -> bool {
+        (self.kinds.is_empty() || self.kinds.iter().any(|k| k == doc.kind))
+            && self.session.as_ref().is_none_or(|session| match &doc.session {
+                Some(s) => s == session || *s == artifacts::session_dir_name(session),
+                // Audit lines name their session in the message
+                None => [format!("Session {} ", session), format!("session {} ", session), format!("(session: {})", session)]
+                    .iter()
+                    .any(|marker| doc.text.contains(marker.as_str())),
+            })
+            && self.since.is_none_or(|since| doc.time >= since)
+            && self.until.is_none_or(|until| doc.time <= until)
+    }
+}
+
+/// RFC 3339, a date (YYYY-MM-DD), or an age ("30m", "12h", "7d", "2w")
+/// before `now`, as Unix seconds
+pub fn parse_time(text: &str, now: chrono::DateTime<chrono::Utc>) -> Result<i64, String> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Ok(time.timestamp());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc().timestamp());
+    }
+    let unit_secs = match text.chars().last() {
+        Some('m') => 60,
+        Some('h') => 3600,
+        Some('d') => 86_400,
+        Some('w') => 7 * 86_400,
+        _ => 0,
+    };
+    match text[..text.len().saturating_sub(1)].parse::<i64>() {
+        Ok(count) if unit_secs > 0 && count >= 0 => Ok(now.timestamp() - count.saturating_mul(unit_secs)),
+        _ => Err(format!("Invalid time {:?} (use RFC 3339, YYYY-MM-DD, or an age like 7d)", text)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hit {
+    #[serde(flatten)]
+    pub document: Document,
+    pub score: f64,
+    pub snippet: String,
+}
+
+/// Text around the first match of any query word
+fn snippet(text: &str, query: &Query) -> String {
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths; fall back to the start when it did
+    let position = if lower.len() == text.len() {
+        query
+            .phrases
+            .iter()
+            .chain(query.terms.iter().map(|t| &t.text))
+            .filter_map(|needle| lower.find(needle.as_str()))
+            .min()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let chars_before = text[..position].chars().count();
+    let start = chars_before.saturating_sub(SNIPPET_CHARS / 3);
+    let mut snippet: String = text.chars().skip(start).take(SNIPPET_CHARS).collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if start + SNIPPET_CHARS < text.chars().count() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+pub struct ArtifactSearch {
+    root: PathBuf,
+    segments: Mutex<HashMap<PathBuf, Segment>>,
+}
+
+impl ArtifactSearch {
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_root(storage::data_dir()?))
+    }
+
+    fn with_root(root: PathBuf) -> Self {
+        Self { root, segments: Mutex::new(HashMap::new()) }
+    }
+
+    /// Every indexable source file with its modification time and size
+    fn sources(&self) -> Vec<(PathBuf, SystemTime, u64)> {
+        let mut files = Vec::new();
+        let mut add = |path: PathBuf| {
+            if let Ok(metadata) = fs::metadata(&path) {
+                if metadata.is_file() && metadata.len() <= MAX_FILE_BYTES {
+                    files.push((path, metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len()));
+                }
+            }
+        };
+        if let Ok(entries) = fs::read_dir(&self.root) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name == AUDIT_LOG_NAME || (name.starts_with("audit-") && name.ends_with(".log")) {
+                    add(entry.path());
+                }
+            }
+        }
+        if let Ok(entries) = fs::read_dir(self.root.join("snapshots")) {
+            for entry in entries.flatten() {
+                add(entry.path().join("dom.html"));
+            }
+        }
+        let mut stack = vec![self.root.join("artifacts")];
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    stack.push(path);
+                } else if path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| TEXT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+                {
+                    add(path);
+                }
+            }
+        }
+        files
+    }
+
+    /// Bring the index up to date with the files on disk; returns how many
+    /// files were (re)indexed
+    pub fn refresh(&self) -> usize {
+        let sources = self.sources();
+        let mut segments = self.segments.lock().unwrap();
+        segments.retain(|path, _| sources.iter().any(|(source, _, _)| source == path));
+        let mut indexed = 0;
+        for (path, modified, len) in sources {
+            let current = segments
+                .get(&path)
+                .is_some_and(|segment| segment.modified == modified && segment.len == len);
+            if current {
+                continue;
+            }
+            match read_documents(&self.root, &path, modified) {
+                Ok(docs) => {
+                    segments.insert(path, Segment::build(docs, modified, len));
+                    indexed += 1;
+                }
+                // Unreadable (e.g. not UTF-8) files are left out
+                Err(_) => {
+                    segments.remove(&path);
+                }
+            }
+        }
+        indexed
+    }
+
+    /// Refresh, then return the best `limit` matches and the total count
+    pub fn search(&self, query: &Query, filters: &Filters, limit: usize) -> (Vec<Hit>, usize) {
+        self.refresh();
+        let segments = self.segments.lock().unwrap();
+
+        let (mut total_docs, mut total_length) = (0usize, 0u64);
+        for segment in segments.values() {
+            total_docs += segment.docs.len();
+            total_length += segment.lengths.iter().map(|l| u64::from(*l)).sum::<u64>();
+        }
+        let average_length = (total_length as f64 / total_docs.max(1) as f64).max(1.0);
+
+        let per_segment: Vec<(&Segment, Vec<HashMap<u32, u32>>)> = segments
+            .values()
+            .map(|segment| (segment, query.terms.iter().map(|term| segment.matches(term)).collect()))
+            .collect();
+        let idf: Vec<f64> = (0..query.terms.len())
+            .map(|i| {
+                let frequency = per_segment.iter().map(|(_, matches)| matches[i].len()).sum::<usize>() as f64;
+                ((total_docs as f64 - frequency + 0.5) / (frequency + 0.5) + 1.0).ln()
+            })
+            .collect();
+
+        let mut hits = Vec::new();
+        for (segment, matches) in &per_segment {
+            let Some((first, others)) = matches.split_first() else {
+                continue;
+            };
+            for &doc in first.keys() {
+                if !others.iter().all(|m| m.contains_key(&doc)) {
+                    continue;
+                }
+                let document = &segment.docs[doc as usize];
+                if !filters.accepts(document) {
+                    continue;
+                }
+                if !query.phrases.is_empty() {
+                    let normalized = document.text.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !query.phrases.iter().all(|phrase| normalized.contains(phrase.as_str())) {
+                        continue;
+                    }
+                }
+                if !query.excluded.is_empty() && tokenize(&document.text).any(|token| query.excluded.contains(&token)) {
+                    continue;
+                }
+                let length = f64::from(segment.lengths[doc as usize]);
+                let score: f64 = matches
+                    .iter()
+                    .zip(&idf)
+                    .map(|(m, idf)| {
+                        let tf = f64::from(m[&doc]);
+                        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length))
+                    })
+                    .sum();
+                hits.push((score, document));
+            }
+        }
+
+        let total = hits.len();
+        // Best first; newer wins ties
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.time.cmp(&a.1.time)));
+        let hits = hits
+            .into_iter()
+            .take(limit)
+            .map(|(score, document)| Hit {
+                snippet: snippet(&document.text, query),
+                document: document.clone(),
+                score: (score * 1000.0).round() / 1000.0,
+            })
+            .collect();
+        (hits, total)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("agent-browser-search-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("snapshots/pricing")).unwrap();
+        fs::create_dir_all(dir.join("artifacts/tcp_127.0.0.1_5000/reports")).unwrap();
+        fs::write(
+            dir.join("snapshots/pricing/dom.html"),
+            "<html><head><title>Pricing</title><script>var hidden = 'zebra';</script></head>\
+             <body><h1>Enterprise plan</h1><p>Contact sales for volume discounts &amp; invoicing.</p></body></html>",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("snapshots/pricing/meta.json"),
+            r#"{"name":"pricing","url":"https://example.com/pricing","title":"Pricing","created":1700000000,"session":"stdio","parts":["dom"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("artifacts/tcp_127.0.0.1_5000/reports/20260101T000000.000Z.html"),
+            "<html><title>Session report</title><body>playwright_click failed: selector #checkout not found</body></html>",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("audit.log"),
+            "[2026-01-02T03:04:05+00:00] Session stdio authorized for 300 seconds\n\
+             [2026-01-03T03:04:05+00:00] Credential abc used for get on rpId: example.com (session: stdio)\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn search(index: &ArtifactSearch, query: &str, filters: &Filters) -> Vec<Hit> {
+        index.search(&Query::parse(query).unwrap(), filters, DEFAULT_LIMIT).0
+    }
+
+    #[test]
+    fn test_searches_pages_reports_and_audit_lines() {
+        let root = temp_root();
+        let index = ArtifactSearch::with_root(root.clone());
+
+        let hits = search(&index, "volume discounts", &Filters::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].document.kind, hits[0].document.url.as_deref()), ("page", Some("https://example.com/pricing")));
+        assert!(hits[0].snippet.contains("volume discounts & invoicing"), "{}", hits[0].snippet);
+        // Script contents aren't page text
+        assert!(search(&index, "zebra", &Filters::default()).is_empty());
+
+        let report = search(&index, "checkout", &Filters::default());
+        assert_eq!(report[0].document.kind, "report");
+        assert_eq!(report[0].document.session.as_deref(), Some("tcp_127.0.0.1_5000"));
+        let by_session = Filters { session: Some("tcp:127.0.0.1:5000".to_string()), ..Filters::default() };
+        assert_eq!(search(&index, "checkout", &by_session).len(), 1);
+
+        let audit = search(&index, "credential example*", &Filters::default());
+        assert_eq!((audit.len(), audit[0].document.line), (1, Some(2)));
+        assert_eq!(audit[0].document.time, 1767409445);
+        let since = Filters {
+            kinds: vec!["audit".to_string()],
+            since: Some(parse_time("2026-01-03", chrono::Utc::now()).unwrap()),
+            ..Filters::default()
+        };
+        assert_eq!(search(&index, "session", &since).len(), 1);
+
+        assert_eq!(search(&index, "\"for 300 seconds\"", &Filters::default()).len(), 1);
+        assert!(search(&index, "\"300 for seconds\"", &Filters::default()).is_empty());
+        assert_eq!(search(&index, "stdio -credential", &Filters::default()).len(), 1);
+
+        // Only changed files are re-read
+        assert_eq!(index.refresh(), 0);
+        fs::write(root.join("audit.log"), "[2026-01-04T00:00:00+00:00] Cleared 2 credentials\n").unwrap();
+        assert_eq!(index.refresh(), 1);
+        assert!(search(&index, "authorized", &Filters::default()).is_empty());
+    }
+
+    #[test]
+    fn test_query_and_time_parsing() {
+        let query = Query::parse("\"Enterprise Plan\" price* -draft").unwrap();
+        assert_eq!(query.phrases, vec!["enterprise plan"]);
+        assert_eq!(query.excluded, vec!["draft"]);
+        assert_eq!(query.terms.len(), 3);
+        assert!(query.terms[2].prefix);
+        assert!(Query::parse("-only").is_err());
+        assert!(Query::parse("\"open").is_err());
+
+        let now = chrono::Utc::now();
+        assert_eq!(parse_time("7d", now).unwrap(), now.timestamp() - 7 * 86_400);
+        assert_eq!(parse_time("2026-01-02T03:04:05Z", now).unwrap(), 1767323045);
+        assert!(parse_time("last week", now).is_err());
+    }
+}
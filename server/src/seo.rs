@@ -0,0 +1,419 @@
+/*!
+ * SEO / Meta Audit
+ *
+ * `audit_seo` combines what the page says about itself (title, meta
+ * description, canonical, robots meta, headings, hreflang, Open Graph),
+ * collected by the extension's `audit_seo` command, with what the site
+ * serves around it, checked from the server: robots.txt rules for the page,
+ * sitemap availability, and the HTTP status of the canonical URL, alternates
+ * and a sample of the page's links. Everything is folded into one list of
+ * issues graded error / warning / notice.
+ */
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::preflight::{self, Target};
+
+pub const DEFAULT_LINK_CHECKS: usize = 10;
+pub const MAX_LINK_CHECKS: usize = 50;
+const ROBOTS_MAX_BYTES: usize = 256 * 1024;
+const SITEMAP_PROBE_BYTES: usize = 1024;
+const MAX_SITEMAPS_CHECKED: usize = 3;
+
+const TITLE_RANGE: (usize, usize) = (10, 60);
+const DESCRIPTION_RANGE: (usize, usize) = (50, 160);
+const THIN_CONTENT_WORDS: u64 = 300;
+
+// ============================================================================
+// Page Facts
+// ============================================================================
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PageFacts {
+    pub url: String,
+    pub title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical: Option<String>,
+    /// Content of <meta name="robots">
+    pub robots: Option<String>,
+    pub lang: Option<String>,
+    pub viewport: bool,
+    pub headings: Vec<Heading>,
+    pub hreflang: Vec<Alternate>,
+    pub open_graph: BTreeMap<String, String>,
+    pub structured_data: u64,
+    pub word_count: u64,
+    pub images_missing_alt: u64,
+    /// Absolute http(s) links in document order
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alternate {
+    pub lang: String,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Notice,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+}
+
+fn issue(severity: Severity, code: &'static str, message: impl Into<String>) -> Issue {
+    Issue { severity, code, message: message.into() }
+}
+
+/// Checks that need only the page's own markup
+pub fn check_page(facts: &PageFacts) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let present = |v: &Option<String>| v.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+
+    match present(&facts.title) {
+        None => issues.push(issue(Severity::Error, "title-missing", "Page has no <title>")),
+        Some(title) => {
+            let len = title.chars().count();
+            if len < TITLE_RANGE.0 {
+                issues.push(issue(Severity::Warning, "title-short", format!("Title is {} characters; aim for {}-{}", len, TITLE_RANGE.0, TITLE_RANGE.1)));
+            } else if len > TITLE_RANGE.1 {
+                issues.push(issue(Severity::Warning, "title-long", format!("Title is {} characters and will likely be truncated in results", len)));
+            }
+        }
+    }
+
+    match present(&facts.meta_description) {
+        None => issues.push(issue(Severity::Warning, "description-missing", "Page has no meta description")),
+        Some(description) => {
+            let len = description.chars().count();
+            if len < DESCRIPTION_RANGE.0 {
+                issues.push(issue(Severity::Notice, "description-short", format!("Meta description is {} characters; aim for {}-{}", len, DESCRIPTION_RANGE.0, DESCRIPTION_RANGE.1)));
+            } else if len > DESCRIPTION_RANGE.1 {
+                issues.push(issue(Severity::Notice, "description-long", format!("Meta description is {} characters and will likely be truncated", len)));
+            }
+        }
+    }
+
+    let h1s = facts.headings.iter().filter(|h| h.level == 1).count();
+    if h1s == 0 {
+        issues.push(issue(Severity::Error, "h1-missing", "Page has no <h1>"));
+    } else if h1s > 1 {
+        issues.push(issue(Severity::Warning, "h1-multiple", format!("Page has {} <h1> elements", h1s)));
+    }
+    for pair in facts.headings.windows(2) {
+        if pair[1].level > pair[0].level + 1 {
+            issues.push(issue(Severity::Notice, "heading-skip", format!("h{} \"{}\" follows h{}", pair[1].level, pair[1].text, pair[0].level)));
+        }
+    }
+
+    match present(&facts.canonical) {
+        None => issues.push(issue(Severity::Notice, "canonical-missing", "Page has no canonical link")),
+        Some(canonical) => {
+            let host = |url: &str| Target::parse(url).ok().map(|t| t.host);
+            if host(&canonical).is_none() {
+                issues.push(issue(Severity::Warning, "canonical-invalid", format!("Canonical {} is not an absolute http(s) URL", canonical)));
+            } else if host(&canonical) != host(&facts.url) {
+                issues.push(issue(Severity::Warning, "canonical-cross-host", format!("Canonical points to another host: {}", canonical)));
+            }
+        }
+    }
+
+    let directives: Vec<String> = facts
+        .robots
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|d| d.trim().to_ascii_lowercase())
+        .collect();
+    let has = |directive: &str| directives.iter().any(|d| d == directive || d == "none");
+    if has("noindex") {
+        issues.push(issue(Severity::Warning, "meta-noindex", "Robots meta tag asks search engines not to index this page"));
+    }
+    if has("nofollow") {
+        issues.push(issue(Severity::Notice, "meta-nofollow", "Robots meta tag asks search engines not to follow links"));
+    }
+
+    if !facts.viewport {
+        issues.push(issue(Severity::Warning, "viewport-missing", "No viewport meta tag; the page may not be treated as mobile-friendly"));
+    }
+    if present(&facts.lang).is_none() {
+        issues.push(issue(Severity::Notice, "lang-missing", "The <html> element has no lang attribute"));
+    }
+    if facts.images_missing_alt > 0 {
+        issues.push(issue(Severity::Notice, "image-alt-missing", format!("{} images have no alt text", facts.images_missing_alt)));
+    }
+    let missing_og: Vec<&str> = ["og:title", "og:description", "og:image"]
+        .into_iter()
+        .filter(|key| !facts.open_graph.contains_key(*key))
+        .collect();
+    if !missing_og.is_empty() {
+        issues.push(issue(Severity::Notice, "open-graph-incomplete", format!("Missing Open Graph tags: {}", missing_og.join(", "))));
+    }
+    if facts.word_count < THIN_CONTENT_WORDS {
+        issues.push(issue(Severity::Notice, "thin-content", format!("Only {} words of visible text", facts.word_count)));
+    }
+
+    issues
+}
+
+// ============================================================================
+// robots.txt
+// ============================================================================
+
+#[derive(Debug, Default)]
+pub struct RobotsTxt {
+    pub sitemaps: Vec<String>,
+    /// (allow, pattern) rules of the group that applies to all crawlers
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsTxt {
+    pub fn parse(body: &str) -> Self {
+        let mut robots = RobotsTxt::default();
+        // A group is one or more user-agent lines followed by rules
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            match key.as_str() {
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_string());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    if agents.iter().any(|a| a == "*") && !value.is_empty() {
+                        robots.rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        robots
+    }
+
+    /// Whether a generic crawler may fetch `path`: the longest matching rule
+    /// wins, and allow wins a tie
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path pattern: prefix match with `*` wildcards and a `$` end anchor
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+// ============================================================================
+// Site Checks
+// ============================================================================
+
+fn origin(target: &Target) -> String {
+    format!("{}://{}", if target.tls { "https" } else { "http" }, target.host_header())
+}
+
+/// Fetch robots.txt and sitemaps and check the status of key links
+pub async fn check_site(facts: &PageFacts, link_limit: usize, timeout_ms: u64) -> (Value, Vec<Issue>) {
+    let mut issues = Vec::new();
+    let Ok(page) = Target::parse(&facts.url) else {
+        // about:, file: and similar pages have no site to check
+        return (Value::Null, issues);
+    };
+    let origin = origin(&page);
+
+    // robots.txt
+    let robots_url = format!("{}/robots.txt", origin);
+    let robots = match Target::parse(&robots_url) {
+        Ok(target) => preflight::fetch_text(&target, timeout_ms, ROBOTS_MAX_BYTES).await,
+        Err(e) => Err(e),
+    };
+    let (robots_status, parsed) = match robots {
+        Ok((status, body)) if status < 300 => (Some(status), RobotsTxt::parse(&body)),
+        Ok((status, _)) => (Some(status), RobotsTxt::default()),
+        Err(e) => {
+            issues.push(issue(Severity::Notice, "robots-unreachable", format!("robots.txt could not be fetched: {}", e)));
+            (None, RobotsTxt::default())
+        }
+    };
+    let allowed = parsed.allows(&page.path);
+    if !allowed {
+        issues.push(issue(Severity::Error, "robots-disallowed", format!("robots.txt disallows crawling {}", page.path)));
+    }
+
+    // Sitemaps: those robots.txt names, else the conventional location
+    let candidates = if parsed.sitemaps.is_empty() {
+        vec![format!("{}/sitemap.xml", origin)]
+    } else {
+        parsed.sitemaps.iter().take(MAX_SITEMAPS_CHECKED).cloned().collect()
+    };
+    let sitemaps = join_all(candidates.into_iter().map(|url| async move {
+        let result = match Target::parse(&url) {
+            Ok(target) => preflight::fetch_text(&target, timeout_ms, SITEMAP_PROBE_BYTES).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((status, body)) => {
+                let xml = ["<urlset", "<sitemapindex"].iter().any(|tag| body.contains(tag));
+                serde_json::json!({ "url": url, "status": status, "valid": status < 300 && (xml || body.trim_start().starts_with("<?xml")) })
+            }
+            Err(e) => serde_json::json!({ "url": url, "error": e, "valid": false }),
+        }
+    }))
+    .await;
+    if !sitemaps.iter().any(|s| s["valid"] == true) {
+        issues.push(issue(Severity::Warning, "sitemap-missing", "No reachable XML sitemap (checked robots.txt Sitemap lines or /sitemap.xml)"));
+    }
+
+    // Key links: canonical, hreflang alternates, then the page's own links
+    let canonical = facts.canonical.clone().filter(|c| Target::parse(c).is_ok());
+    let mut seen = HashSet::new();
+    let keys: Vec<String> = canonical
+        .iter()
+        .cloned()
+        .chain(facts.hreflang.iter().map(|a| a.href.clone()))
+        .chain(facts.links.iter().cloned())
+        .map(|url| url.split('#').next().unwrap_or_default().to_string())
+        .filter(|url| Target::parse(url).is_ok() && seen.insert(url.clone()))
+        .take(link_limit)
+        .collect();
+    let links = join_all(keys.into_iter().map(|url| async move {
+        let report = preflight::check(&Target::parse(&url).expect("filtered above"), timeout_ms).await;
+        serde_json::json!({
+            "url": url,
+            "status": report["http"]["status"],
+            "verdict": report["verdict"],
+            "location": report["http"]["location"],
+        })
+    }))
+    .await;
+    for link in &links {
+        let url = link["url"].as_str().unwrap_or_default();
+        let verdict = link["verdict"].as_str().unwrap_or_default();
+        if canonical.as_deref().is_some_and(|c| c.split('#').next() == Some(url)) && verdict != "ok" {
+            issues.push(issue(Severity::Error, "canonical-not-ok", format!("Canonical {} answered {}", url, link["status"])));
+        } else if verdict == "client_error" || verdict == "server_error" {
+            issues.push(issue(Severity::Warning, "broken-link", format!("{} answered {}", url, link["status"])));
+        } else if !matches!(verdict, "ok" | "redirect") {
+            issues.push(issue(Severity::Warning, "unreachable-link", format!("{} is unreachable ({})", url, verdict)));
+        }
+    }
+
+    let site = serde_json::json!({
+        "robotsTxt": {
+            "url": robots_url,
+            "status": robots_status,
+            "allowsPage": allowed,
+            "sitemaps": parsed.sitemaps,
+        },
+        "sitemaps": sitemaps,
+        "links": links,
+    });
+    (site, issues)
+}
+
+/// Order issues by severity and count them
+pub fn summarize(mut issues: Vec<Issue>) -> Value {
+    issues.sort_by_key(|i| i.severity);
+    let count = |severity| issues.iter().filter(|i| i.severity == severity).count();
+    serde_json::json!({
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+        "notices": count(Severity::Notice),
+        "issues": issues,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_page_flags_markup_problems() {
+        let facts = PageFacts {
+            url: "https://shop.example.com/p/1".to_string(),
+            title: Some("Shoes".to_string()),
+            canonical: Some("https://other.example.net/p/1".to_string()),
+            robots: Some("noindex, follow".to_string()),
+            headings: vec![
+                Heading { level: 1, text: "Shoes".to_string() },
+                Heading { level: 3, text: "Sizes".to_string() },
+            ],
+            word_count: 1000,
+            ..Default::default()
+        };
+        let codes: Vec<&str> = check_page(&facts).iter().map(|i| i.code).collect();
+        for expected in ["title-short", "description-missing", "heading-skip", "canonical-cross-host", "meta-noindex", "viewport-missing", "lang-missing"] {
+            assert!(codes.contains(&expected), "missing {}", expected);
+        }
+        assert!(!codes.contains(&"h1-missing"));
+        assert!(!codes.contains(&"thin-content"));
+    }
+
+    #[test]
+    fn test_robots_longest_rule_wins_for_generic_agents() {
+        let robots = RobotsTxt::parse(
+            "User-agent: Googlebot\nDisallow: /\n\nUser-agent: *\nDisallow: /private\nAllow: /private/press\nDisallow: /*.pdf$\n# comment\nSitemap: https://example.com/sitemap_index.xml\n",
+        );
+        assert_eq!(robots.sitemaps, ["https://example.com/sitemap_index.xml"]);
+        assert!(robots.allows("/products"));
+        assert!(!robots.allows("/private/accounts"));
+        assert!(robots.allows("/private/press/2024"));
+        assert!(!robots.allows("/docs/manual.pdf"));
+        assert!(robots.allows("/docs/manual.pdf?download=1"));
+        assert!(RobotsTxt::parse("").allows("/anything"));
+    }
+}
@@ -0,0 +1,182 @@
+/*!
+ * Session Registry
+ *
+ * Every MCP session that initialized, whatever its transport (the stdio
+ * agent, TCP, WebSocket, HTTP, SSE), is listed here with the client it
+ * announced. A supervising human connected on another transport can use it
+ * to cooperate with an agent: `session_list` shows who is connected,
+ * `session_observe` streams another session's tool calls to the observer as
+ * `notifications/session_activity`, and `session_inject` runs a tool call
+ * as that session. Injected calls land on the target's timeline and in the
+ * audit log attributed to the injecting session, and the target is told
+ * with `notifications/session_injected` so the agent can account for the
+ * change. A session can't observe or inject into itself.
+ */
+
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use tokio::sync::RwLock;
+
+/// Tools that can't be injected: session management, and credential and
+/// authorization tools, which would otherwise run with the target's
+/// authorization rather than the injecting session's
+pub const NOT_INJECTABLE: &[&str] = &[
+    "session_inject",
+    "session_observe",
+    "history_retry",
+    "passkey_enable",
+    "passkey_status",
+    "passkey_list",
+    "passkey_clear",
+    "passkey_authorize",
+    "passkey_authorization_status",
+    "passkey_grant_create",
+    "passkey_grant_redeem",
+    "recovery_authorize",
+    "recovery_secret_store",
+    "recovery_secret_list",
+    "recovery_secret_get",
+    "recovery_secret_delete",
+    "playwright_evaluate",
+];
+
+/// Refuse injecting a tool listed in `NOT_INJECTABLE`
+pub fn check_injectable(tool: &str) -> Result<(), String> {
+    if NOT_INJECTABLE.contains(&tool) {
+        return Err(format!("{} can't be injected into another session", tool));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub session: String,
+    pub transport: String,
+    /// "name version" from the client's initialize, when given
+    pub client: Option<String>,
+    pub connected_at: String,
+    /// Sessions receiving this session's activity
+    pub observers: BTreeSet<String>,
+}
+
+/// Transport of a session label: "tcp:127.0.0.1:5123" is tcp, "stdio" is stdio
+pub fn transport(session: &str) -> &str {
+    session.split_once(':').map_or(session, |(transport, _)| transport)
+}
+
+#[derive(Default)]
+pub struct SessionRegistry {
+    sessions: RwLock<HashMap<String, SessionInfo>>,
+}
+
+impl SessionRegistry {
+    /// Add (or refresh, on a repeated initialize) a session
+    pub async fn register(&self, session: &str, client_info: Option<&serde_json::Value>) {
+        let client = client_info.and_then(|info| {
+            let name = info.get("name")?.as_str()?;
+            Some(match info.get("version").and_then(|v| v.as_str()) {
+                Some(version) => format!("{} {}", name, version),
+                None => name.to_string(),
+            })
+        });
+        let mut sessions = self.sessions.write().await;
+        let info = sessions.entry(session.to_string()).or_insert_with(|| SessionInfo {
+            session: session.to_string(),
+            transport: transport(session).to_string(),
+            client: None,
+            connected_at: chrono::Utc::now().to_rfc3339(),
+            observers: BTreeSet::new(),
+        });
+        info.client = client;
+    }
+
+    /// Remove a session and stop anything it was observing
+    pub async fn forget(&self, session: &str) {
+        let mut sessions = self.sessions.write().await;
+        sessions.remove(session);
+        for info in sessions.values_mut() {
+            info.observers.remove(session);
+        }
+    }
+
+    pub async fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self.sessions.read().await.values().cloned().collect();
+        sessions.sort_by(|a, b| a.connected_at.cmp(&b.connected_at).then(a.session.cmp(&b.session)));
+        sessions
+    }
+
+    /// Check that `target` is another connected session
+    pub async fn check_target(&self, actor: &str, target: &str) -> Result<(), String> {
+        if actor == target {
+            return Err("A session can't observe or inject into itself".to_string());
+        }
+        if !self.sessions.read().await.contains_key(target) {
+            return Err(format!("No connected session {} (see session_list)", target));
+        }
+        Ok(())
+    }
+
+    /// Start or stop sending `target`'s activity to `observer`
+    pub async fn observe(&self, observer: &str, target: &str, observe: bool) -> Result<(), String> {
+        self.check_target(observer, target).await?;
+        let mut sessions = self.sessions.write().await;
+        let info = sessions.get_mut(target).ok_or(format!("No connected session {}", target))?;
+        if observe {
+            info.observers.insert(observer.to_string());
+        } else {
+            info.observers.remove(observer);
+        }
+        Ok(())
+    }
+
+    pub async fn observers(&self, session: &str) -> Vec<String> {
+        self.sessions
+            .read()
+            .await
+            .get(session)
+            .map(|info| info.observers.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_observe_forget() {
+        let registry = SessionRegistry::default();
+        registry
+            .register("stdio", Some(&serde_json::json!({ "name": "agent", "version": "1.2" })))
+            .await;
+        registry.register("tcp:127.0.0.1:5000", None).await;
+
+        let sessions = registry.list().await;
+        assert_eq!(sessions.len(), 2);
+        let stdio = sessions.iter().find(|s| s.session == "stdio").unwrap();
+        assert_eq!((stdio.transport.as_str(), stdio.client.as_deref()), ("stdio", Some("agent 1.2")));
+        assert_eq!(transport("tcp:127.0.0.1:5000"), "tcp");
+
+        registry.observe("tcp:127.0.0.1:5000", "stdio", true).await.unwrap();
+        assert_eq!(registry.observers("stdio").await, vec!["tcp:127.0.0.1:5000"]);
+        assert!(registry.observe("stdio", "stdio", true).await.is_err());
+        assert!(registry.observe("stdio", "http:gone", true).await.is_err());
+
+        // An observer that disconnects stops observing
+        registry.forget("tcp:127.0.0.1:5000").await;
+        assert!(registry.observers("stdio").await.is_empty());
+    }
+
+    #[test]
+    fn test_credential_tools_are_not_injectable() {
+        assert!(check_injectable("recovery_secret_get").is_err());
+        assert!(check_injectable("passkey_grant_redeem").is_err());
+        assert!(check_injectable("session_inject").is_err());
+        assert!(check_injectable("browser_navigate").is_ok());
+    }
+}
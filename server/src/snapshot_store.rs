@@ -0,0 +1,201 @@
+/*!
+ * Named Page Snapshot Store
+ *
+ * Persists DOM / accessibility tree / screenshot captures under
+ * user-provided names so later sessions (or other agents) can reference
+ * and diff against earlier page states.
+ *
+ * Layout: ~/.agent-browser/snapshots/<name>/
+ *   meta.json, dom.html, accessibility.json, screenshot.png
+ */
+
+use anyhow::{anyhow, Result};
+use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::storage;
+
+const META_FILE: &str = "meta.json";
+const DOM_FILE: &str = "dom.html";
+const ACCESSIBILITY_FILE: &str = "accessibility.json";
+const SCREENSHOT_FILE: &str = "screenshot.png";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotPart {
+    Dom,
+    Accessibility,
+    Screenshot,
+}
+
+impl SnapshotPart {
+    pub const ALL: [SnapshotPart; 3] = [
+        SnapshotPart::Dom,
+        SnapshotPart::Accessibility,
+        SnapshotPart::Screenshot,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMetadata {
+    pub name: String,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub created: i64,
+    pub session: Option<String>,
+    pub parts: Vec<SnapshotPart>,
+    /// Free-form metadata supplied by the caller
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+/// Captured page content to persist
+#[derive(Debug, Default)]
+pub struct SnapshotCapture {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub dom: Option<String>,
+    pub accessibility: Option<serde_json::Value>,
+    /// Base64-encoded PNG as returned by the extension
+    pub screenshot_b64: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    #[serde(flatten)]
+    pub meta: SnapshotMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dom: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessibility: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<String>,
+}
+
+// ============================================================================
+// Snapshot Store
+// ============================================================================
+
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new() -> Result<Self> {
+        let dir = storage::data_subdir("snapshots")?;
+        info!("Snapshot store initialized at {:?}", dir);
+        Ok(Self { dir })
+    }
+
+    fn snapshot_dir(&self, name: &str) -> Result<PathBuf> {
+        storage::validate_name("Snapshot", name)?;
+        Ok(self.dir.join(name))
+    }
+
+    /// Persist a capture under `name`
+    pub fn save(
+        &self,
+        name: &str,
+        capture: SnapshotCapture,
+        metadata: serde_json::Value,
+        session: Option<&str>,
+        overwrite: bool,
+    ) -> Result<SnapshotMetadata> {
+        let dir = self.snapshot_dir(name)?;
+        if dir.exists() {
+            if !overwrite {
+                return Err(anyhow!("Snapshot '{}' already exists (pass overwrite: true to replace it)", name));
+            }
+            fs::remove_dir_all(&dir)?;
+        }
+        storage::ensure_private_dir(&dir)?;
+
+        let mut parts = Vec::new();
+        if let Some(dom) = &capture.dom {
+            storage::write_private_file(&dir.join(DOM_FILE), dom)?;
+            parts.push(SnapshotPart::Dom);
+        }
+        if let Some(tree) = &capture.accessibility {
+            storage::write_private_file(&dir.join(ACCESSIBILITY_FILE), serde_json::to_string_pretty(tree)?)?;
+            parts.push(SnapshotPart::Accessibility);
+        }
+        if let Some(b64) = &capture.screenshot_b64 {
+            let png = BASE64
+                .decode(b64)
+                .map_err(|e| anyhow!("Invalid screenshot data: {}", e))?;
+            storage::write_private_file(&dir.join(SCREENSHOT_FILE), png)?;
+            parts.push(SnapshotPart::Screenshot);
+        }
+
+        let meta = SnapshotMetadata {
+            name: name.to_string(),
+            url: capture.url,
+            title: capture.title,
+            created: chrono::Utc::now().timestamp(),
+            session: session.map(str::to_string),
+            parts,
+            metadata,
+        };
+        storage::write_private_file(&dir.join(META_FILE), serde_json::to_string_pretty(&meta)?)?;
+
+        info!("Saved snapshot '{}' ({} parts)", name, meta.parts.len());
+        Ok(meta)
+    }
+
+    /// Load a snapshot, including only the requested parts
+    pub fn get(&self, name: &str, include: &[SnapshotPart]) -> Result<Snapshot> {
+        let dir = self.snapshot_dir(name)?;
+        let meta_path = dir.join(META_FILE);
+        if !meta_path.exists() {
+            return Err(anyhow!("Snapshot '{}' not found", name));
+        }
+        let meta: SnapshotMetadata = serde_json::from_str(&fs::read_to_string(meta_path)?)?;
+
+        let wants = |part: SnapshotPart| include.contains(&part) && meta.parts.contains(&part);
+
+        let dom = if wants(SnapshotPart::Dom) {
+            Some(fs::read_to_string(dir.join(DOM_FILE))?)
+        } else {
+            None
+        };
+        let accessibility = if wants(SnapshotPart::Accessibility) {
+            Some(serde_json::from_str(&fs::read_to_string(dir.join(ACCESSIBILITY_FILE))?)?)
+        } else {
+            None
+        };
+        let screenshot = if wants(SnapshotPart::Screenshot) {
+            Some(BASE64.encode(fs::read(dir.join(SCREENSHOT_FILE))?))
+        } else {
+            None
+        };
+
+        Ok(Snapshot {
+            meta,
+            dom,
+            accessibility,
+            screenshot,
+        })
+    }
+
+    /// List metadata for every stored snapshot, newest first
+    pub fn list(&self) -> Result<Vec<SnapshotMetadata>> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let meta_path = entry?.path().join(META_FILE);
+            if let Ok(data) = fs::read_to_string(&meta_path) {
+                if let Ok(meta) = serde_json::from_str::<SnapshotMetadata>(&data) {
+                    snapshots.push(meta);
+                }
+            }
+        }
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created));
+        Ok(snapshots)
+    }
+}
@@ -0,0 +1,217 @@
+/*!
+ * MCP HTTP+SSE Transport (legacy)
+ *
+ * The pre-2025 MCP HTTP transport, still used by several hosts. Served by
+ * the HTTP listener alongside /mcp:
+ * - GET /sse - opens the event stream; the first `endpoint` event tells the
+ *   client where to POST, then responses and notifications arrive as
+ *   `message` events
+ * - POST /messages?sessionId=<id> - one JSON-RPC message; answered with
+ *   202 Accepted, the response is delivered on the session's stream
+ *
+ * Requests share handle_mcp_request with every other transport.
+ */
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::http_transport::{
+    write_response, write_sse_event, write_sse_head, write_sse_keepalive, write_sse_named_event, HttpRequest,
+    SSE_KEEPALIVE_SECS,
+};
+use crate::mcp::{JsonRpcReq, JsonRpcRes};
+use crate::{handle_mcp_notification, handle_mcp_request, ServerState};
+
+pub const SSE_PATH: &str = "/sse";
+pub const MESSAGES_PATH: &str = "/messages";
+const SESSION_PARAM: &str = "sessionId";
+const SESSION_QUEUE: usize = 64;
+
+/// Open SSE streams, keyed by session id; each holds the sender for its stream
+#[derive(Clone, Default)]
+pub struct SseSessions(Arc<RwLock<HashMap<String, mpsc::Sender<String>>>>);
+
+impl SseSessions {
+    async fn open(&self) -> (String, mpsc::Receiver<String>) {
+        let session_id = Uuid::new_v4().to_string();
+        let (tx, rx) = mpsc::channel(SESSION_QUEUE);
+        self.0.write().await.insert(session_id.clone(), tx);
+        (session_id, rx)
+    }
+
+    async fn sender(&self, session_id: &str) -> Option<mpsc::Sender<String>> {
+        self.0.read().await.get(session_id).cloned()
+    }
+
+    async fn close(&self, session_id: &str) {
+        self.0.write().await.remove(session_id);
+    }
+}
+
+/// Route a request for /sse or /messages; returns whether the connection can serve another request
+pub async fn handle_request(
+    stream: &mut TcpStream,
+    request: &HttpRequest,
+    state: &Arc<ServerState>,
+    sessions: &SseSessions,
+) -> Result<bool> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", SSE_PATH) => {
+            handle_stream(stream, request, state, sessions).await?;
+            Ok(false)
+        }
+        ("POST", MESSAGES_PATH) => handle_message(stream, request, state, sessions).await,
+        (_, SSE_PATH) => {
+            write_response(stream, 405, &[("Allow", "GET".to_string())], b"").await?;
+            Ok(true)
+        }
+        _ => {
+            write_response(stream, 405, &[("Allow", "POST".to_string())], b"").await?;
+            Ok(true)
+        }
+    }
+}
+
+/// Handle GET /sse: announce the POST endpoint, then stream until the client goes away
+async fn handle_stream(
+    stream: &mut TcpStream,
+    request: &HttpRequest,
+    state: &Arc<ServerState>,
+    sessions: &SseSessions,
+) -> Result<()> {
+    if !request.accepts("text/event-stream") {
+        write_response(stream, 406, &[], b"GET requires Accept: text/event-stream").await?;
+        return Ok(());
+    }
+
+    let (session_id, mut responses) = sessions.open().await;
+    info!("MCP SSE session opened: {}", session_id);
+
+    let result = stream_session(stream, &session_id, &mut responses, state).await;
+
+    sessions.close(&session_id).await;
+    state.forget_session(&format!("sse:{}", session_id)).await;
+    info!("MCP SSE session closed: {}", session_id);
+    result
+}
+
+async fn stream_session(
+    stream: &mut TcpStream,
+    session_id: &str,
+    responses: &mut mpsc::Receiver<String>,
+    state: &Arc<ServerState>,
+) -> Result<()> {
+    let session = format!("sse:{}", session_id);
+    let mut notifications = state.notifications.subscribe();
+    let mut keepalive = interval(Duration::from_secs(SSE_KEEPALIVE_SECS));
+
+    write_sse_head(stream, &[]).await?;
+    let endpoint = format!("{}?{}={}", MESSAGES_PATH, SESSION_PARAM, session_id);
+    write_sse_named_event(stream, "endpoint", &endpoint).await?;
+
+    loop {
+        tokio::select! {
+            response = responses.recv() => match response {
+                Some(json) => write_sse_event(stream, &json).await?,
+                None => return Ok(()),
+            },
+            notification = notifications.recv() => match notification {
+                Ok(notification) if !notification.is_visible_to(&session) => {}
+                Ok(notification) => {
+                    write_sse_event(stream, &serde_json::to_string(&notification)?).await?;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("MCP SSE stream lagged, dropped {} notifications", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = keepalive.tick() => {
+                write_sse_keepalive(stream).await?;
+            }
+        }
+    }
+}
+
+/// Handle POST /messages: accept the message and answer on the session's stream
+async fn handle_message(
+    stream: &mut TcpStream,
+    request: &HttpRequest,
+    state: &Arc<ServerState>,
+    sessions: &SseSessions,
+) -> Result<bool> {
+    let Some(session_id) = request.query_param(SESSION_PARAM) else {
+        write_response(stream, 400, &[], b"Missing sessionId query parameter").await?;
+        return Ok(true);
+    };
+    let Some(tx) = sessions.sender(session_id).await else {
+        write_response(stream, 404, &[], b"Unknown session").await?;
+        return Ok(true);
+    };
+
+    let req: JsonRpcReq = match serde_json::from_slice(&request.body) {
+        Ok(req) => req,
+        Err(e) => {
+            let res = JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None);
+            write_response(
+                stream,
+                400,
+                &[("Content-Type", "application/json".to_string())],
+                &serde_json::to_vec(&res)?,
+            )
+            .await?;
+            return Ok(true);
+        }
+    };
+
+    write_response(stream, 202, &[], b"Accepted").await?;
+
+    // Notifications get no JSON-RPC response
+    if req.id.is_none() {
+        tokio::spawn(handle_mcp_notification(req, Arc::clone(state), format!("sse:{}", session_id)));
+        return Ok(true);
+    }
+
+    let session = format!("sse:{}", session_id);
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        let res = handle_mcp_request(req, state, &session).await;
+        match serde_json::to_string(&res) {
+            Ok(json) => {
+                if tx.send(json).await.is_err() {
+                    debug!("MCP SSE stream for {} closed before response", session);
+                }
+            }
+            Err(e) => error!("Failed to serialize MCP SSE response: {}", e),
+        }
+    });
+
+    Ok(true)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_route_to_their_stream() {
+        let sessions = SseSessions::default();
+        let (id, mut rx) = sessions.open().await;
+
+        let tx = sessions.sender(&id).await.unwrap();
+        tx.send("hello".to_string()).await.unwrap();
+        assert_eq!(rx.recv().await.as_deref(), Some("hello"));
+
+        sessions.close(&id).await;
+        assert!(sessions.sender(&id).await.is_none());
+    }
+}
@@ -0,0 +1,211 @@
+/*!
+ * Key-Value State Store
+ *
+ * Small per-workspace key/value store so agents can keep workflow state
+ * (e.g. "last processed order id") between sessions.
+ *
+ * - One JSON file per workspace under ~/.agent-browser/state/
+ * - Values are arbitrary JSON, capped in size to keep the store "small bits"
+ * - Writes are persisted immediately
+ */
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::storage;
+
+pub const DEFAULT_WORKSPACE: &str = "default";
+const MAX_KEY_LEN: usize = 256;
+const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateEntry {
+    pub value: serde_json::Value,
+    pub updated: i64,
+}
+
+type Workspace = BTreeMap<String, StateEntry>;
+
+// ============================================================================
+// State Store
+// ============================================================================
+
+pub struct StateStore {
+    dir: PathBuf,
+    // Workspaces loaded lazily on first access
+    workspaces: RwLock<HashMap<String, Workspace>>,
+}
+
+impl StateStore {
+    /// Initialize the state store under the data directory
+    pub fn new() -> Result<Self> {
+        let dir = storage::data_subdir("state")?;
+        info!("State store initialized at {:?}", dir);
+        Ok(Self::with_dir(dir))
+    }
+
+    fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            workspaces: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn workspace_path(&self, workspace: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", workspace))
+    }
+
+    /// Load a workspace from disk into the cache if not already present
+    async fn ensure_loaded(&self, workspace: &str) -> Result<()> {
+        storage::validate_name("Workspace", workspace)?;
+
+        if self.workspaces.read().await.contains_key(workspace) {
+            return Ok(());
+        }
+
+        let path = self.workspace_path(workspace);
+        let entries: Workspace = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Workspace::new()
+        };
+
+        self.workspaces
+            .write()
+            .await
+            .entry(workspace.to_string())
+            .or_insert(entries);
+        Ok(())
+    }
+
+    fn persist(&self, workspace: &str, entries: &Workspace) -> Result<()> {
+        let path = self.workspace_path(workspace);
+        if entries.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        storage::write_private_file(&path, serde_json::to_string_pretty(entries)?)
+    }
+
+    /// Set a key, returning the previous value if any
+    pub async fn set(
+        &self,
+        workspace: &str,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>> {
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return Err(anyhow!("Key must be 1-{} bytes", MAX_KEY_LEN));
+        }
+        let size = serde_json::to_vec(&value)?.len();
+        if size > MAX_VALUE_BYTES {
+            return Err(anyhow!(
+                "Value is {} bytes; state values are limited to {} bytes",
+                size,
+                MAX_VALUE_BYTES
+            ));
+        }
+
+        self.ensure_loaded(workspace).await?;
+
+        let mut workspaces = self.workspaces.write().await;
+        let entries = workspaces.entry(workspace.to_string()).or_default();
+        let previous = entries.insert(
+            key.to_string(),
+            StateEntry {
+                value,
+                updated: chrono::Utc::now().timestamp(),
+            },
+        );
+        self.persist(workspace, entries)?;
+
+        Ok(previous.map(|e| e.value))
+    }
+
+    /// Get a single key
+    pub async fn get(&self, workspace: &str, key: &str) -> Result<Option<StateEntry>> {
+        self.ensure_loaded(workspace).await?;
+        Ok(self
+            .workspaces
+            .read()
+            .await
+            .get(workspace)
+            .and_then(|entries| entries.get(key).cloned()))
+    }
+
+    /// Get every key in a workspace, optionally filtered by prefix
+    pub async fn list(&self, workspace: &str, prefix: Option<&str>) -> Result<Workspace> {
+        self.ensure_loaded(workspace).await?;
+        Ok(self
+            .workspaces
+            .read()
+            .await
+            .get(workspace)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|(k, _)| prefix.is_none_or(|p| k.starts_with(p)))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Delete a key, returning whether it existed
+    pub async fn delete(&self, workspace: &str, key: &str) -> Result<bool> {
+        self.ensure_loaded(workspace).await?;
+
+        let mut workspaces = self.workspaces.write().await;
+        let Some(entries) = workspaces.get_mut(workspace) else {
+            return Ok(false);
+        };
+        let existed = entries.remove(key).is_some();
+        if existed {
+            self.persist(workspace, entries)?;
+        }
+        Ok(existed)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_get_delete_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StateStore::with_dir(dir.path().to_path_buf());
+        store.set("orders", "last_id", serde_json::json!(42)).await.unwrap();
+
+        // A fresh store over the same directory sees the persisted value
+        let reloaded = StateStore::with_dir(store.dir.clone());
+        let entry = reloaded.get("orders", "last_id").await.unwrap().unwrap();
+        assert_eq!(entry.value, serde_json::json!(42));
+
+        assert!(reloaded.delete("orders", "last_id").await.unwrap());
+        assert!(reloaded.get("orders", "last_id").await.unwrap().is_none());
+        assert!(!reloaded.workspace_path("orders").exists());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_path_traversal_workspace() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StateStore::with_dir(dir.path().to_path_buf());
+        assert!(store.set("../etc", "k", serde_json::json!(1)).await.is_err());
+    }
+}
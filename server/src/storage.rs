@@ -0,0 +1,96 @@
+//! Shared helpers for files under the `~/.agent-browser` data directory
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Root data directory (`~/.agent-browser`), created owner-only on first use
+pub fn data_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    ensure_private_dir(&home.join(".agent-browser"))
+}
+
+/// Subdirectory of the data directory, created owner-only on first use
+pub fn data_subdir(name: &str) -> Result<PathBuf> {
+    ensure_private_dir(&data_dir()?.join(name))
+}
+
+/// Create a directory (and parents) restricted to the current user
+pub fn ensure_private_dir(dir: &Path) -> Result<PathBuf> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(dir)?.permissions();
+            perms.set_mode(0o700); // Only owner can access
+            fs::set_permissions(dir, perms)?;
+        }
+    }
+
+    Ok(dir.to_path_buf())
+}
+
+/// Write a file and restrict it to owner read/write
+pub fn write_private_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    fs::write(path, contents)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(0o600); // Only owner can read/write
+        fs::set_permissions(path, perms)?;
+    }
+
+    Ok(())
+}
+
+/// Validate a user-supplied name used as a file or directory component
+pub fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        return Err(anyhow!("{} name must be 1-64 characters", kind));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        || name.starts_with('.')
+    {
+        return Err(anyhow!(
+            "{} name may only contain letters, digits, '-', '_' and '.'",
+            kind
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_dir_and_file() {
+        let root = tempfile::TempDir::new().unwrap();
+        let dir = ensure_private_dir(&root.path().join("a/b")).unwrap();
+        let file = dir.join("secret.json");
+        write_private_file(&file, "{}").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "{}");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+            assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("Workspace", "orders-2024_v1.json").is_ok());
+        assert!(validate_name("Workspace", "").is_err());
+        assert!(validate_name("Workspace", ".hidden").is_err());
+        assert!(validate_name("Workspace", "../etc").is_err());
+        assert!(validate_name("Workspace", &"x".repeat(65)).is_err());
+    }
+}
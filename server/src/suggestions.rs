@@ -0,0 +1,244 @@
+/*!
+ * Element-Not-Found Suggestions
+ *
+ * When a selector-based command fails because nothing matched, the server
+ * asks the extension for candidate elements resembling the selector (same
+ * tag, similar text, overlapping id/classes), ranks them here, and returns
+ * the best few as structured error data so agents can retry without
+ * re-reading the whole page.
+ */
+
+use serde_json::Value;
+
+/// Suggestions included in an error
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Candidates requested from the extension before ranking
+pub const CANDIDATE_LIMIT: usize = 25;
+
+/// Upper bound on the extra latency a failing command pays for suggestions
+pub const SUGGESTION_TIMEOUT_MS: u64 = 2000;
+
+/// Below this score a candidate is noise rather than a suggestion
+const MIN_SCORE: f64 = 0.2;
+
+pub fn is_element_not_found(error: &str) -> bool {
+    error.contains("Element not found") || error.contains("No element")
+}
+
+/// What a failed selector was looking for
+#[derive(Debug, Default, PartialEq)]
+pub struct SelectorHint {
+    pub tag: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub text: Option<String>,
+}
+
+impl SelectorHint {
+    /// Best-effort parse of the last compound selector, e.g. `form button.submit:has-text("Log in")`
+    pub fn parse(selector: &str) -> Self {
+        let mut hint = Self::default();
+
+        // Text hints: text=..., :has-text("..."), :contains("..."), [aria-label="..."], [title="..."]
+        for marker in [":has-text(", ":contains(", ":text(", "aria-label=", "title=", "placeholder="] {
+            if let Some(start) = selector.find(marker) {
+                let rest = &selector[start + marker.len()..];
+                let quoted = rest.trim_start_matches(['"', '\'']);
+                let end = quoted.find(['"', '\'', ')', ']']).unwrap_or(quoted.len());
+                if end > 0 {
+                    hint.text = Some(quoted[..end].to_string());
+                    break;
+                }
+            }
+        }
+        if let Some(text) = selector.strip_prefix("text=") {
+            hint.text = Some(text.trim_matches(['"', '\'']).to_string());
+            return hint;
+        }
+
+        // Structural part of the last compound selector, pseudo-classes and attributes removed
+        let mut depth = 0usize;
+        let outer: String = selector
+            .chars()
+            .filter(|c| match c {
+                '(' | '[' => {
+                    depth += 1;
+                    false
+                }
+                ')' | ']' => {
+                    depth = depth.saturating_sub(1);
+                    false
+                }
+                _ => depth == 0,
+            })
+            .collect();
+        let last = outer.split([' ', '>', '+', '~']).rfind(|s| !s.is_empty()).unwrap_or("");
+        let structural = last.split([':', '[']).next().unwrap_or("");
+        let mut rest = structural;
+        let tag_end = rest.find(['#', '.']).unwrap_or(rest.len());
+        if tag_end > 0 && rest[..tag_end] != *"*" {
+            hint.tag = Some(rest[..tag_end].to_ascii_lowercase());
+        }
+        rest = &rest[tag_end..];
+        while !rest.is_empty() {
+            let kind = rest.as_bytes()[0];
+            let body = &rest[1..];
+            let end = body.find(['#', '.']).unwrap_or(body.len());
+            let name = body[..end].to_string();
+            if !name.is_empty() {
+                if kind == b'#' {
+                    hint.id = Some(name);
+                } else {
+                    hint.classes.push(name);
+                }
+            }
+            rest = &body[end..];
+        }
+        hint
+    }
+
+    /// Parameters for the extension's find_similar_elements command
+    pub fn to_params(&self, selector: &str) -> Value {
+        serde_json::json!({
+            "selector": selector,
+            "tag": self.tag,
+            "id": self.id,
+            "classes": self.classes,
+            "text": self.text,
+            "limit": CANDIDATE_LIMIT,
+        })
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { prev } else { 1 + prev.min(row[j]).min(row[j + 1]) };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// 1.0 for identical strings (case-insensitive), 0.0 for nothing in common
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (a.trim().to_lowercase(), b.trim().to_lowercase());
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if b.contains(&a) || a.contains(&b) {
+        return 0.9;
+    }
+    let longest = a.chars().count().max(b.chars().count());
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+fn score(hint: &SelectorHint, candidate: &Value) -> f64 {
+    let field = |name: &str| candidate.get(name).and_then(|v| v.as_str()).unwrap_or("");
+    let mut score = 0.0;
+    let mut weight = 0.0;
+
+    if let Some(tag) = &hint.tag {
+        weight += 1.0;
+        if field("tag").eq_ignore_ascii_case(tag) {
+            score += 1.0;
+        }
+    }
+    if let Some(text) = &hint.text {
+        weight += 2.0;
+        score += 2.0 * similarity(text, field("text"));
+    }
+    if let Some(id) = &hint.id {
+        weight += 1.5;
+        score += 1.5 * similarity(id, field("id"));
+    }
+    if !hint.classes.is_empty() {
+        weight += 1.0;
+        let classes: Vec<&str> = field("className").split_whitespace().collect();
+        let best: f64 = hint
+            .classes
+            .iter()
+            .map(|wanted| classes.iter().map(|c| similarity(wanted, c)).fold(0.0, f64::max))
+            .sum();
+        score += best / hint.classes.len() as f64;
+    }
+
+    if weight == 0.0 {
+        0.0
+    } else {
+        score / weight
+    }
+}
+
+/// Rank extension candidates against the failed selector, best first
+pub fn rank(hint: &SelectorHint, candidates: &[Value], limit: usize) -> Vec<Value> {
+    let mut scored: Vec<(f64, &Value)> = candidates
+        .iter()
+        .filter(|c| c.get("selector").and_then(|v| v.as_str()).is_some())
+        .map(|c| (score(hint, c), c))
+        .filter(|(score, _)| *score >= MIN_SCORE)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(score, c)| {
+            serde_json::json!({
+                "selector": c.get("selector"),
+                "tag": c.get("tag"),
+                "text": c.get("text"),
+                "score": (score * 100.0).round() / 100.0,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selector_hint() {
+        let hint = SelectorHint::parse(r#"form > button.btn.submit:has-text("Log in")"#);
+        assert_eq!(hint.tag.as_deref(), Some("button"));
+        assert_eq!(hint.classes, vec!["btn", "submit"]);
+        assert_eq!(hint.text.as_deref(), Some("Log in"));
+
+        let hint = SelectorHint::parse("#login-btn");
+        assert_eq!(hint.id.as_deref(), Some("login-btn"));
+        assert_eq!(hint.tag, None);
+
+        assert_eq!(SelectorHint::parse("text=Sign up").text.as_deref(), Some("Sign up"));
+    }
+
+    #[test]
+    fn test_rank_prefers_similar_text_and_tag() {
+        let hint = SelectorHint::parse(r#"button:has-text("Log in")"#);
+        let candidates = vec![
+            serde_json::json!({ "selector": "a.nav", "tag": "a", "text": "Pricing" }),
+            serde_json::json!({ "selector": "#signin", "tag": "button", "text": "Login" }),
+            serde_json::json!({ "selector": "button.cta", "tag": "button", "text": "Start free trial" }),
+            serde_json::json!({ "tag": "button", "text": "Log in" }),
+        ];
+        let ranked = rank(&hint, &candidates, 2);
+        assert_eq!(ranked[0]["selector"], "#signin");
+        assert!(ranked.iter().all(|s| s["selector"] != "a.nav"));
+        assert!(ranked.len() <= 2);
+    }
+
+    #[test]
+    fn test_is_element_not_found() {
+        assert!(is_element_not_found("Element not found: #go"));
+        assert!(!is_element_not_found("Timeout waiting for response"));
+    }
+}
@@ -0,0 +1,355 @@
+/*!
+ * Per-Tab Command Locks
+ *
+ * Commands that change a page (navigate, click, type, fill, ...) take the
+ * tab's lock so two clients, or one client's parallel calls, can't
+ * interleave on the same tab. Locks are granted first come, first served.
+ * When a command finishes, the session that ran it keeps the tab for a
+ * short lease so its next step (fill, then click) isn't cut in on; other
+ * sessions queue until the lease runs out.
+ *
+ * Commands without a tabId address the active tab and share one lock.
+ * Read-only commands skip locking. Waiters that have not been granted the
+ * lock within the command timeout fail with a "Tab busy" error.
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// How long a session keeps a tab after its command finishes
+pub const TAB_LEASE_MS: u64 = 1500;
+
+/// How often queued callers are told their position
+pub const QUEUE_REPORT_INTERVAL_MS: u64 = 1000;
+
+/// Prefix of the error returned when a tab stays locked past the timeout
+pub const TAB_BUSY_ERROR: &str = "Tab busy";
+
+/// JSON-RPC error code for commands that gave up waiting for a tab
+pub const TAB_BUSY_CODE: i64 = -32013;
+
+/// Commands that only read a page, or don't touch one, and never wait for a lock
+const UNLOCKED_COMMANDS: &[&str] = &[
+    "get_focused_element",
+    "get_element",
+    "get_text",
+    "get_html",
+    "extract_content",
+    "assert_page",
+    "accessibility_snapshot",
+    "screenshot",
+    "detect_modal",
+    "list_frames",
+    "list_service_workers",
+    "media_get_state",
+    "media_capture_frame",
+    "canvas_read_pixels",
+    "print_to_pdf",
+    "image_diff",
+    "cookies_get",
+    "downloads_list",
+    "active_tab",
+    "wait_for_load",
+    "page_info",
+    "dialog_respond",
+    "profiling_start",
+    "profiling_stop",
+    "screencast_start",
+    "screencast_stop",
+    "find_similar_elements",
+    "list_forms",
+    "passkey_enable",
+    "passkey_status",
+    "passkey_list",
+    "passkey_clear",
+];
+
+pub fn needs_lock(method: &str) -> bool {
+    !UNLOCKED_COMMANDS.contains(&method)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabKey {
+    Active,
+    Tab(i64),
+}
+
+impl TabKey {
+    pub fn for_params(params: &serde_json::Value) -> Self {
+        match params.get("tabId").and_then(|v| v.as_i64()) {
+            Some(tab_id) => Self::Tab(tab_id),
+            None => Self::Active,
+        }
+    }
+}
+
+impl fmt::Display for TabKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Active => write!(f, "the active tab"),
+            Self::Tab(tab_id) => write!(f, "tab {}", tab_id),
+        }
+    }
+}
+
+/// Where a queued caller stands, reported while it waits
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueStatus {
+    /// Callers ahead, counting the one holding the tab
+    pub ahead: usize,
+    pub waited: Duration,
+    /// Session running a command on the tab or holding its lease
+    pub holder: Option<String>,
+}
+
+impl QueueStatus {
+    pub fn message(&self, key: TabKey) -> String {
+        format!(
+            "Waiting for {}: {} ahead in queue, waited {:.1}s",
+            key,
+            self.ahead,
+            self.waited.as_secs_f64()
+        )
+    }
+}
+
+#[derive(Default)]
+struct TabState {
+    /// Session whose command is running on the tab
+    running: Option<String>,
+    /// Session that last ran a command, and when its lease ends
+    lease: Option<(String, Instant)>,
+    /// Tickets of waiting callers, oldest first
+    queue: VecDeque<u64>,
+}
+
+#[derive(Default)]
+struct Inner {
+    tabs: HashMap<TabKey, TabState>,
+    next_ticket: u64,
+}
+
+enum Attempt {
+    Granted,
+    Wait { status: QueueStatus, retry_in: Option<Duration> },
+}
+
+#[derive(Default)]
+pub struct TabLocks {
+    inner: Mutex<Inner>,
+    changed: Notify,
+}
+
+/// Held while a command runs on a tab; starts the session's lease when dropped
+pub struct TabGuard {
+    locks: Arc<TabLocks>,
+    key: TabKey,
+    session: String,
+}
+
+impl Drop for TabGuard {
+    fn drop(&mut self) {
+        {
+            let mut inner = self.locks.inner.lock().unwrap();
+            if let Some(tab) = inner.tabs.get_mut(&self.key) {
+                tab.running = None;
+                tab.lease = Some((self.session.clone(), Instant::now() + Duration::from_millis(TAB_LEASE_MS)));
+            }
+        }
+        self.locks.changed.notify_waiters();
+    }
+}
+
+/// Removes an abandoned caller from the queue
+struct Ticket<'a> {
+    locks: &'a TabLocks,
+    key: TabKey,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.locks.inner.lock().unwrap();
+        if let Some(tab) = inner.tabs.get_mut(&self.key) {
+            tab.queue.retain(|&t| t != self.id);
+        }
+        drop(inner);
+        self.locks.changed.notify_waiters();
+    }
+}
+
+impl TabLocks {
+    /// Wait for the tab, calling `on_wait` when the caller's queue status
+    /// changes and at least every QUEUE_REPORT_INTERVAL_MS
+    pub async fn acquire(
+        self: &Arc<Self>,
+        key: TabKey,
+        session: &str,
+        wait_limit: Duration,
+        mut on_wait: impl FnMut(&QueueStatus),
+    ) -> Result<TabGuard, String> {
+        let started = Instant::now();
+        let ticket = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.next_ticket += 1;
+            let id = inner.next_ticket;
+            inner.tabs.entry(key).or_default().queue.push_back(id);
+            Ticket { locks: self, key, id }
+        };
+
+        let mut last_reported: Option<(usize, Instant)> = None;
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let (status, retry_in) = match self.try_take(key, session, ticket.id, started) {
+                Attempt::Granted => {
+                    return Ok(TabGuard {
+                        locks: Arc::clone(self),
+                        key,
+                        session: session.to_string(),
+                    })
+                }
+                Attempt::Wait { status, retry_in } => (status, retry_in),
+            };
+
+            let elapsed = started.elapsed();
+            if elapsed >= wait_limit {
+                return Err(format!(
+                    "{}: {} is still in use by {} after {:.1}s ({} ahead in queue)",
+                    TAB_BUSY_ERROR,
+                    key,
+                    status.holder.as_deref().unwrap_or("another command"),
+                    elapsed.as_secs_f64(),
+                    status.ahead
+                ));
+            }
+            let report_due = last_reported.is_none_or(|(ahead, at)| {
+                ahead != status.ahead || at.elapsed() >= Duration::from_millis(QUEUE_REPORT_INTERVAL_MS)
+            });
+            if report_due {
+                on_wait(&status);
+                last_reported = Some((status.ahead, Instant::now()));
+            }
+
+            let sleep = retry_in
+                .unwrap_or(Duration::MAX)
+                .min(Duration::from_millis(QUEUE_REPORT_INTERVAL_MS))
+                .min(wait_limit - elapsed);
+            let _ = tokio::time::timeout(sleep, notified).await;
+        }
+    }
+
+    fn try_take(&self, key: TabKey, session: &str, ticket: u64, started: Instant) -> Attempt {
+        let mut inner = self.inner.lock().unwrap();
+        let tab = inner.tabs.entry(key).or_default();
+        let now = Instant::now();
+        if tab.lease.as_ref().is_some_and(|(_, until)| *until <= now) {
+            tab.lease = None;
+        }
+
+        let position = tab.queue.iter().position(|&t| t == ticket).unwrap_or(0);
+        let leased_to = tab.lease.as_ref().map(|(holder, _)| holder.as_str());
+        let granted = tab.running.is_none()
+            && match leased_to {
+                // The lease holder's next step goes ahead of the queue
+                Some(holder) => holder == session,
+                None => position == 0,
+            };
+        if granted {
+            tab.queue.retain(|&t| t != ticket);
+            tab.running = Some(session.to_string());
+            tab.lease = None;
+            return Attempt::Granted;
+        }
+
+        let holder = tab.running.clone().or_else(|| leased_to.map(str::to_string));
+        let busy = usize::from(holder.is_some());
+        Attempt::Wait {
+            status: QueueStatus {
+                ahead: position + busy,
+                waited: started.elapsed(),
+                holder,
+            },
+            retry_in: tab.lease.as_ref().map(|(_, until)| until.saturating_duration_since(now)),
+        }
+    }
+
+    /// Drop the state of a closed tab
+    pub fn forget(&self, tab_id: i64) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.tabs.get(&TabKey::Tab(tab_id)).is_some_and(|tab| tab.running.is_none() && tab.queue.is_empty()) {
+            inner.tabs.remove(&TabKey::Tab(tab_id));
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_lock_scope() {
+        assert!(needs_lock("click"));
+        assert!(needs_lock("navigate"));
+        assert!(!needs_lock("screenshot"));
+        assert_eq!(TabKey::for_params(&serde_json::json!({ "tabId": 7 })), TabKey::Tab(7));
+        assert_eq!(TabKey::for_params(&serde_json::json!({})), TabKey::Active);
+    }
+
+    #[tokio::test]
+    async fn test_other_sessions_queue_behind_holder_and_lease() {
+        let locks = Arc::new(TabLocks::default());
+        let guard = locks.acquire(TabKey::Tab(1), "a", WAIT, |_| {}).await.unwrap();
+
+        let waiter = {
+            let locks = Arc::clone(&locks);
+            tokio::spawn(async move {
+                let mut reports = Vec::new();
+                let started = Instant::now();
+                let guard = locks
+                    .acquire(TabKey::Tab(1), "b", WAIT, |status| reports.push(status.clone()))
+                    .await
+                    .unwrap();
+                drop(guard);
+                (started.elapsed(), reports)
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Other tabs are independent
+        drop(locks.acquire(TabKey::Tab(2), "c", WAIT, |_| {}).await.unwrap());
+
+        // Session a keeps the tab through its lease and cuts ahead of b
+        drop(guard);
+        drop(locks.acquire(TabKey::Tab(1), "a", WAIT, |_| {}).await.unwrap());
+
+        let (waited, reports) = waiter.await.unwrap();
+        assert!(waited >= Duration::from_millis(TAB_LEASE_MS));
+        assert_eq!(reports[0].ahead, 1);
+        assert_eq!(reports[0].holder.as_deref(), Some("a"));
+    }
+
+    #[tokio::test]
+    async fn test_waiters_time_out_and_leave_the_queue() {
+        let locks = Arc::new(TabLocks::default());
+        let _guard = locks.acquire(TabKey::Active, "a", WAIT, |_| {}).await.unwrap();
+        let err = locks
+            .acquire(TabKey::Active, "b", Duration::from_millis(100), |_| {})
+            .await
+            .err()
+            .unwrap();
+        assert!(err.starts_with(TAB_BUSY_ERROR));
+        assert!(locks.inner.lock().unwrap().tabs[&TabKey::Active].queue.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+/*!
+ * Table Extraction
+ *
+ * `browser_extract_table` has the content script lay a table out as a grid
+ * of cell texts, with colspan/rowspan cells repeated into every slot they
+ * cover and the header rows split off into column names. The server turns
+ * the grid into row objects keyed by those names, and with `saveCsv` writes
+ * it as `tables/<time>.csv` in the session's artifacts.
+ */
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::{artifacts, storage};
+
+pub const DEFAULT_MAX_ROWS: u64 = 500;
+pub const MAX_ROWS: u64 = 10_000;
+pub const MAX_HEADER_ROWS: u64 = 10;
+
+/// Column names made unique, so no column is lost when rows become objects:
+/// a second "Price" becomes "Price (2)"
+pub fn unique_headers(headers: &[String]) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(headers.len());
+    for header in headers {
+        let mut name = header.clone();
+        let mut n = 1;
+        while unique.contains(&name) {
+            n += 1;
+            name = format!("{} ({})", header, n);
+        }
+        unique.push(name);
+    }
+    unique
+}
+
+/// Rows as objects keyed by column name
+pub fn row_objects(headers: &[String], rows: &[Vec<String>]) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    let headers = unique_headers(headers);
+    rows.iter()
+        .map(|row| {
+            headers
+                .iter()
+                .zip(row.iter().map(String::as_str).chain(std::iter::repeat("")))
+                .map(|(header, cell)| (header.clone(), serde_json::Value::String(cell.to_string())))
+                .collect()
+        })
+        .collect()
+}
+
+/// RFC 4180 CSV: CRLF line endings, fields with quotes, commas or line breaks quoted
+pub fn to_csv(headers: &[String], rows: &[Vec<String>]) -> String {
+    let field = |text: &str| {
+        if text.contains(['"', ',', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    };
+    std::iter::once(headers)
+        .chain(rows.iter().map(Vec::as_slice))
+        .map(|row| row.iter().map(|text| field(text)).collect::<Vec<_>>().join(",") + "\r\n")
+        .collect()
+}
+
+/// Write the table to `tables/<time>.csv` in the session's artifacts
+pub fn save_csv(session: &str, headers: &[String], rows: &[Vec<String>]) -> Result<PathBuf> {
+    let dir = storage::ensure_private_dir(&artifacts::session_dir(session)?.join("tables"))?;
+    let path = dir.join(format!("{}.csv", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    storage::write_private_file(&path, to_csv(headers, rows))?;
+    Ok(path)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_unique_headers() {
+        assert_eq!(
+            unique_headers(&strings(&["Name", "Price", "Price", "Price (2)"])),
+            strings(&["Name", "Price", "Price (2)", "Price (2) (2)"])
+        );
+    }
+
+    #[test]
+    fn test_row_objects() {
+        let objects = row_objects(&strings(&["Name", "Name"]), &[strings(&["a", "b"]), strings(&["c"])]);
+        assert_eq!(serde_json::json!(objects), serde_json::json!([
+            { "Name": "a", "Name (2)": "b" },
+            { "Name": "c", "Name (2)": "" },
+        ]));
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(&strings(&["Name", "Note"]), &[strings(&["Widget, large", "say \"hi\""]), strings(&["Plain", "two\nlines"])]);
+        assert_eq!(csv, "Name,Note\r\n\"Widget, large\",\"say \"\"hi\"\"\"\r\nPlain,\"two\nlines\"\r\n");
+    }
+}
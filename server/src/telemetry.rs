@@ -0,0 +1,293 @@
+/*!
+ * Opt-In Usage Telemetry
+ *
+ * With `--telemetry` (or `AGENT_BROWSER_TELEMETRY=1`) the server counts tool
+ * calls and their failures in `~/.agent-browser/telemetry.json`. Only the
+ * built-in tool name and an error class are recorded; arguments, URLs,
+ * page content and error messages never are, and tools the extension
+ * registered itself are counted as "other". Nothing is sent anywhere.
+ *
+ * `telemetry_status` shows the local counts and an export for sharing with
+ * maintainers. The export adds Laplace noise to every count (event-level
+ * differential privacy: one call changes a tool's calls, errors and one
+ * error class by 1, so the L1 sensitivity is 3), which hides whether any
+ * single call happened while keeping frequencies and error rates usable.
+ * Each export draws fresh noise, so share one export rather than several.
+ */
+
+use anyhow::Result;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::mcp::JsonRpcRes;
+use crate::storage;
+
+pub const TELEMETRY_FILE: &str = "telemetry.json";
+pub const DEFAULT_EPSILON: f64 = 1.0;
+pub const MAX_EPSILON: f64 = 10.0;
+
+/// Counts are written at most this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Calls to tools outside the built-in set
+const OTHER_TOOL: &str = "other";
+/// Counters one call can change: calls, errors and one error class
+const SENSITIVITY: f64 = 3.0;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub errors: u64,
+    /// Failures by class (see `error_class`)
+    #[serde(default)]
+    pub error_classes: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Aggregate {
+    /// Date counting started (UTC, day precision)
+    pub since: Option<String>,
+    pub tools: BTreeMap<String, ToolStats>,
+}
+
+/// Coarse failure class of a tools/call response, or None on success
+pub fn error_class(res: &JsonRpcRes) -> Option<&'static str> {
+    if let Some(error) = &res.error {
+        return Some(match error.code {
+            -32602 => "invalid_params",
+            -32601 => "unknown_tool",
+            -32001 => "unauthorized",
+            _ => "failed",
+        });
+    }
+    let tool_error = res
+        .result
+        .as_ref()
+        .and_then(|result| result.get("isError"))
+        .and_then(|v| v.as_bool())
+        == Some(true);
+    tool_error.then_some("tool_error")
+}
+
+// ============================================================================
+// Recorder
+// ============================================================================
+
+pub struct Telemetry {
+    enabled: bool,
+    path: PathBuf,
+    known_tools: HashSet<String>,
+    state: Mutex<(Aggregate, Option<Instant>)>,
+}
+
+impl Telemetry {
+    pub fn new(enabled: bool) -> Result<Self> {
+        Ok(Self::with_path(enabled, storage::data_dir()?.join(TELEMETRY_FILE)))
+    }
+
+    fn with_path(enabled: bool, path: PathBuf) -> Self {
+        let aggregate = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        let known_tools = crate::mcp::tools::builtin_tools()
+            .iter()
+            .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+            .collect();
+        Self {
+            enabled,
+            path,
+            known_tools,
+            state: Mutex::new((aggregate, None)),
+        }
+    }
+
+    /// Count a finished tool call; a no-op unless telemetry is enabled
+    pub fn record(&self, tool: &str, res: &JsonRpcRes) {
+        if !self.enabled {
+            return;
+        }
+        let tool = if self.known_tools.contains(tool) { tool } else { OTHER_TOOL };
+        let mut state = self.state.lock().unwrap();
+        let (aggregate, last_flush) = &mut *state;
+        aggregate
+            .since
+            .get_or_insert_with(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+        let stats = aggregate.tools.entry(tool.to_string()).or_default();
+        stats.calls += 1;
+        if let Some(class) = error_class(res) {
+            stats.errors += 1;
+            *stats.error_classes.entry(class.to_string()).or_default() += 1;
+        }
+        if last_flush.is_none_or(|at| at.elapsed() >= FLUSH_INTERVAL) {
+            *last_flush = Some(Instant::now());
+            if let Err(e) = self.write(aggregate) {
+                warn!("Failed to write telemetry: {}", e);
+            }
+        }
+    }
+
+    fn write(&self, aggregate: &Aggregate) -> Result<()> {
+        storage::write_private_file(&self.path, serde_json::to_string_pretty(aggregate)?)
+    }
+
+    /// Forget all counts and remove the file
+    pub fn reset(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        *state = (Aggregate::default(), None);
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Local counts plus a noised export for `telemetry_status`
+    pub fn status(&self, epsilon: f64) -> serde_json::Value {
+        let aggregate = {
+            let mut state = self.state.lock().unwrap();
+            // Make the file match what is shown
+            if self.enabled && !state.0.tools.is_empty() {
+                if let Err(e) = self.write(&state.0) {
+                    warn!("Failed to write telemetry: {}", e);
+                }
+                state.1 = Some(Instant::now());
+            }
+            state.0.clone()
+        };
+        let mut universe: Vec<&str> = self.known_tools.iter().map(String::as_str).collect();
+        universe.push(OTHER_TOOL);
+        universe.sort_unstable();
+        serde_json::json!({
+            "enabled": self.enabled,
+            "path": self.path.display().to_string(),
+            "since": aggregate.since,
+            "local": aggregate.tools,
+            "export": export(&aggregate, &universe, epsilon, &mut laplace_sampler()),
+        })
+    }
+}
+
+// ============================================================================
+// Differentially Private Export
+// ============================================================================
+
+/// Laplace(0, 1) samples from the system RNG
+fn laplace_sampler() -> impl FnMut() -> f64 {
+    let rng = SystemRandom::new();
+    move || {
+        let mut bytes = [0u8; 8];
+        rng.fill(&mut bytes).expect("system RNG failed");
+        // Uniform in (-0.5, 0.5), never exactly ±0.5
+        let u = ((u64::from_le_bytes(bytes) >> 11) as f64 + 0.5) / (1u64 << 53) as f64 - 0.5;
+        -u.signum() * (1.0 - 2.0 * u.abs()).ln()
+    }
+}
+
+/// Noise every count of every tool in `universe` (including tools with no
+/// calls, so the set of tools used isn't revealed), then drop the rows that
+/// round to zero calls
+fn export(aggregate: &Aggregate, universe: &[&str], epsilon: f64, noise: &mut impl FnMut() -> f64) -> serde_json::Value {
+    let scale = SENSITIVITY / epsilon;
+    let mut noisy = |count: u64| (count as f64 + noise() * scale).round().max(0.0) as u64;
+    let classes = ["invalid_params", "unknown_tool", "unauthorized", "failed", "tool_error"];
+
+    let mut tools = serde_json::Map::new();
+    for tool in universe {
+        let stats = aggregate.tools.get(*tool).cloned().unwrap_or_default();
+        let calls = noisy(stats.calls);
+        let errors = noisy(stats.errors).min(calls);
+        let error_classes: BTreeMap<&str, u64> = classes
+            .iter()
+            .map(|class| (*class, noisy(stats.error_classes.get(*class).copied().unwrap_or(0))))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        if calls == 0 {
+            continue;
+        }
+        tools.insert(
+            tool.to_string(),
+            serde_json::json!({
+                "calls": calls,
+                "errors": errors,
+                "errorRate": (errors as f64 / calls as f64 * 1000.0).round() / 1000.0,
+                "errorClasses": error_classes,
+            }),
+        );
+    }
+    serde_json::json!({
+        "epsilon": epsilon,
+        "since": aggregate.since,
+        "tools": tools,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_telemetry(enabled: bool) -> Telemetry {
+        let path = std::env::temp_dir().join(format!("agent-browser-telemetry-{}.json", uuid::Uuid::new_v4()));
+        Telemetry::with_path(enabled, path)
+    }
+
+    #[test]
+    fn test_record_counts_classes_and_hides_unknown_tools() {
+        let telemetry = temp_telemetry(true);
+        telemetry.record("playwright_click", &JsonRpcRes::ok(None, serde_json::json!({})));
+        telemetry.record("playwright_click", &JsonRpcRes::err(None, -32602, "Missing selector", None));
+        telemetry.record("https://secret.example/", &JsonRpcRes::ok(None, serde_json::json!({ "isError": true })));
+
+        let (aggregate, _) = telemetry.state.lock().unwrap().clone();
+        let click = &aggregate.tools["playwright_click"];
+        assert_eq!((click.calls, click.errors), (2, 1));
+        assert_eq!(click.error_classes["invalid_params"], 1);
+        assert_eq!(aggregate.tools[OTHER_TOOL].error_classes["tool_error"], 1);
+        assert!(!aggregate.tools.keys().any(|k| k.contains("secret")));
+
+        // Persisted on the first call, and reloaded by a new recorder
+        let reloaded = Telemetry::with_path(true, telemetry.path.clone());
+        assert_eq!(reloaded.state.lock().unwrap().0.tools["playwright_click"].calls, 1);
+        telemetry.reset().unwrap();
+        assert!(!telemetry.path.exists());
+    }
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let telemetry = temp_telemetry(false);
+        telemetry.record("playwright_click", &JsonRpcRes::ok(None, serde_json::json!({})));
+        assert!(telemetry.state.lock().unwrap().0.tools.is_empty());
+        assert!(!telemetry.path.exists());
+    }
+
+    #[test]
+    fn test_export_noise() {
+        let mut aggregate = Aggregate::default();
+        aggregate.tools.insert(
+            "playwright_click".to_string(),
+            ToolStats { calls: 1000, errors: 100, error_classes: BTreeMap::from([("failed".to_string(), 100)]) },
+        );
+        let universe = ["playwright_click", "playwright_fill"];
+
+        // Without noise the export is exact and unused tools are dropped
+        let exact = export(&aggregate, &universe, 1.0, &mut || 0.0);
+        assert_eq!(exact["tools"]["playwright_click"]["errorRate"], 0.1);
+        assert!(exact["tools"].get("playwright_fill").is_none());
+
+        // Real noise at epsilon 1 stays within a few units of the truth
+        let noisy = export(&aggregate, &universe, 1.0, &mut laplace_sampler());
+        let calls = noisy["tools"]["playwright_click"]["calls"].as_u64().unwrap();
+        assert!((900..=1100).contains(&calls), "calls {}", calls);
+    }
+}
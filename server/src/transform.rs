@@ -0,0 +1,1931 @@
+/*!
+ * Sandboxed Expression Evaluation
+ *
+ * `data_transform` reshapes JSON (rows a page extraction returned, values in
+ * the state store) with a JavaScript expression evaluated in the server, so
+ * simple post-processing needs neither another model round trip nor a page
+ * `evaluate`. No JavaScript engine crate is vendored, so this is a small
+ * interpreter for the expression subset such transformations use:
+ * literals, template strings, regex literals, object and array literals
+ * with spread, member access with optional chaining, arrow functions, the
+ * usual operators, and the common Array, String, Number, Object, Math and
+ * JSON functions. There are no statements, assignments or loops, and no
+ * globals beyond those (no `fetch`, timers or host access). Every
+ * evaluation runs under a step budget, charged per operation and per
+ * element or character built, and a call depth limit, so any expression
+ * finishes quickly and in bounded memory.
+ *
+ * The input is bound to `data` (and `$`). Values are immutable: methods
+ * such as `sort` and `reverse` return new arrays.
+ */
+
+use regex::{Regex, RegexBuilder};
+use std::cmp::Ordering;
+use std::ops::Deref;
+use std::rc::Rc;
+
+pub const MAX_EXPRESSION_CHARS: usize = 10_000;
+/// Work units per evaluation: operations plus elements and characters built
+pub const STEP_BUDGET: u64 = 2_000_000;
+const MAX_CALL_DEPTH: usize = 100;
+const MAX_NESTING: usize = 100;
+/// Arrays and objects nested deeper than this are refused when built; values
+/// are converted and dropped recursively, so depth must stay bounded
+const MAX_VALUE_DEPTH: usize = 1000;
+
+// ============================================================================
+// Values
+// ============================================================================
+
+#[derive(Clone)]
+enum Val {
+    Undefined,
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(Rc<str>),
+    Arr(Rc<Nested<Val>>),
+    /// Properties in insertion order
+    Obj(Rc<Nested<(String, Val)>>),
+    Func(Rc<Closure>),
+    Regex(Rc<JsRegex>),
+    /// Callable globals: Number, String, Boolean, parseInt, ...
+    Builtin(&'static str),
+    /// Non-callable globals: Math, Object, JSON, Array
+    Namespace(&'static str),
+}
+
+/// Array elements or object properties, with how deeply the value nests
+struct Nested<T> {
+    items: Vec<T>,
+    depth: usize,
+}
+
+impl<T> Deref for Nested<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.items
+    }
+}
+
+struct Closure {
+    params: Vec<String>,
+    body: Rc<Expr>,
+    scope: Scope,
+}
+
+struct JsRegex {
+    regex: Regex,
+    global: bool,
+    source: String,
+    flags: String,
+}
+
+type Scope = Rc<Frame>;
+
+struct Frame {
+    vars: Vec<(String, Val)>,
+    parent: Option<Scope>,
+}
+
+fn lookup(scope: &Scope, name: &str) -> Option<Val> {
+    let mut frame = Some(scope);
+    while let Some(current) = frame {
+        if let Some((_, value)) = current.vars.iter().find(|(var, _)| var == name) {
+            return Some(value.clone());
+        }
+        frame = current.parent.as_ref();
+    }
+    None
+}
+
+fn global(name: &str) -> Option<Val> {
+    Some(match name {
+        "Math" => Val::Namespace("Math"),
+        "Object" => Val::Namespace("Object"),
+        "JSON" => Val::Namespace("JSON"),
+        "Array" => Val::Namespace("Array"),
+        "Number" => Val::Builtin("Number"),
+        "String" => Val::Builtin("String"),
+        "Boolean" => Val::Builtin("Boolean"),
+        "parseInt" => Val::Builtin("parseInt"),
+        "parseFloat" => Val::Builtin("parseFloat"),
+        "isNaN" => Val::Builtin("isNaN"),
+        "isFinite" => Val::Builtin("isFinite"),
+        _ => return None,
+    })
+}
+
+fn str_val(s: impl Into<Rc<str>>) -> Val {
+    Val::Str(s.into())
+}
+
+/// Levels of arrays and objects in the value (0 for primitives)
+fn depth(value: &Val) -> usize {
+    match value {
+        Val::Arr(items) => items.depth,
+        Val::Obj(props) => props.depth,
+        _ => 0,
+    }
+}
+
+fn arr_val(items: Vec<Val>) -> Val {
+    let depth = 1 + items.iter().map(depth).max().unwrap_or(0);
+    Val::Arr(Rc::new(Nested { items, depth }))
+}
+
+fn obj_val(props: Vec<(String, Val)>) -> Val {
+    let depth = 1 + props.iter().map(|(_, v)| depth(v)).max().unwrap_or(0);
+    Val::Obj(Rc::new(Nested { items: props, depth }))
+}
+
+fn obj_set(props: &mut Vec<(String, Val)>, key: String, value: Val) {
+    match props.iter_mut().find(|(k, _)| *k == key) {
+        Some(slot) => slot.1 = value,
+        None => props.push((key, value)),
+    }
+}
+
+fn from_json(value: &serde_json::Value) -> Val {
+    match value {
+        serde_json::Value::Null => Val::Null,
+        serde_json::Value::Bool(b) => Val::Bool(*b),
+        serde_json::Value::Number(n) => Val::Num(n.as_f64().unwrap_or(f64::NAN)),
+        serde_json::Value::String(s) => str_val(s.as_str()),
+        serde_json::Value::Array(items) => arr_val(items.iter().map(from_json).collect()),
+        serde_json::Value::Object(map) => obj_val(map.iter().map(|(k, v)| (k.clone(), from_json(v))).collect()),
+    }
+}
+
+/// JSON.stringify semantics: undefined and functions are dropped from
+/// objects and become null in arrays
+fn to_json(value: &Val) -> Option<serde_json::Value> {
+    Some(match value {
+        Val::Undefined | Val::Func(_) | Val::Builtin(_) | Val::Namespace(_) => return None,
+        Val::Null => serde_json::Value::Null,
+        Val::Bool(b) => serde_json::Value::Bool(*b),
+        Val::Num(n) if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 => serde_json::json!(*n as i64),
+        Val::Num(n) => serde_json::Number::from_f64(*n).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Val::Str(s) => serde_json::Value::String(s.to_string()),
+        Val::Arr(items) => serde_json::Value::Array(
+            items.iter().map(|item| to_json(item).unwrap_or(serde_json::Value::Null)).collect(),
+        ),
+        Val::Obj(props) => serde_json::Value::Object(
+            props.iter().filter_map(|(k, v)| Some((k.clone(), to_json(v)?))).collect(),
+        ),
+        Val::Regex(_) => serde_json::json!({}),
+    })
+}
+
+fn num_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n == 0.0 {
+        "0".to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+fn to_string(value: &Val) -> String {
+    match value {
+        Val::Undefined => "undefined".to_string(),
+        Val::Null => "null".to_string(),
+        Val::Bool(b) => b.to_string(),
+        Val::Num(n) => num_to_string(*n),
+        Val::Str(s) => s.to_string(),
+        Val::Arr(items) => items
+            .iter()
+            .map(|item| match item {
+                Val::Undefined | Val::Null => String::new(),
+                other => to_string(other),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        Val::Obj(_) => "[object Object]".to_string(),
+        Val::Func(_) | Val::Builtin(_) => "function".to_string(),
+        Val::Namespace(name) => format!("[object {}]", name),
+        Val::Regex(re) => format!("/{}/{}", re.source, re.flags),
+    }
+}
+
+fn parse_number(text: &str) -> f64 {
+    let text = text.trim();
+    if text.is_empty() {
+        return 0.0;
+    }
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return i64::from_str_radix(hex, 16).map_or(f64::NAN, |n| n as f64);
+    }
+    match text {
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        _ if text.chars().any(|c| c.is_ascii_alphabetic() && c != 'e' && c != 'E') => f64::NAN,
+        _ => text.parse().unwrap_or(f64::NAN),
+    }
+}
+
+fn to_number(value: &Val) -> f64 {
+    match value {
+        Val::Undefined => f64::NAN,
+        Val::Null => 0.0,
+        Val::Bool(b) => f64::from(u8::from(*b)),
+        Val::Num(n) => *n,
+        Val::Str(s) => parse_number(s),
+        Val::Arr(_) => parse_number(&to_string(value)),
+        _ => f64::NAN,
+    }
+}
+
+fn truthy(value: &Val) -> bool {
+    match value {
+        Val::Undefined | Val::Null => false,
+        Val::Bool(b) => *b,
+        Val::Num(n) => *n != 0.0 && !n.is_nan(),
+        Val::Str(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+fn type_of(value: &Val) -> &'static str {
+    match value {
+        Val::Undefined => "undefined",
+        Val::Bool(_) => "boolean",
+        Val::Num(_) => "number",
+        Val::Str(_) => "string",
+        Val::Func(_) | Val::Builtin(_) => "function",
+        _ => "object",
+    }
+}
+
+fn strict_eq(a: &Val, b: &Val) -> bool {
+    match (a, b) {
+        (Val::Undefined, Val::Undefined) | (Val::Null, Val::Null) => true,
+        (Val::Bool(x), Val::Bool(y)) => x == y,
+        (Val::Num(x), Val::Num(y)) => x == y,
+        (Val::Str(x), Val::Str(y)) => x == y,
+        (Val::Arr(x), Val::Arr(y)) => Rc::ptr_eq(x, y),
+        (Val::Obj(x), Val::Obj(y)) => Rc::ptr_eq(x, y),
+        (Val::Func(x), Val::Func(y)) => Rc::ptr_eq(x, y),
+        (Val::Regex(x), Val::Regex(y)) => Rc::ptr_eq(x, y),
+        (Val::Builtin(x), Val::Builtin(y)) | (Val::Namespace(x), Val::Namespace(y)) => x == y,
+        _ => false,
+    }
+}
+
+/// Array.prototype.includes equality: strict, except NaN equals NaN
+fn same_value_zero(a: &Val, b: &Val) -> bool {
+    matches!((a, b), (Val::Num(x), Val::Num(y)) if x.is_nan() && y.is_nan()) || strict_eq(a, b)
+}
+
+fn is_primitive(value: &Val) -> bool {
+    matches!(value, Val::Undefined | Val::Null | Val::Bool(_) | Val::Num(_) | Val::Str(_))
+}
+
+fn loose_eq(a: &Val, b: &Val) -> bool {
+    match (a, b) {
+        (Val::Undefined | Val::Null, Val::Undefined | Val::Null) => true,
+        (Val::Undefined | Val::Null, _) | (_, Val::Undefined | Val::Null) => false,
+        (Val::Bool(_), _) => loose_eq(&Val::Num(to_number(a)), b),
+        (_, Val::Bool(_)) => loose_eq(a, &Val::Num(to_number(b))),
+        (Val::Num(x), Val::Str(_)) => *x == to_number(b),
+        (Val::Str(_), Val::Num(y)) => to_number(a) == *y,
+        (x, y) if !is_primitive(x) && is_primitive(y) => loose_eq(&str_val(to_string(x)), y),
+        (x, y) if is_primitive(x) && !is_primitive(y) => loose_eq(x, &str_val(to_string(y))),
+        _ => strict_eq(a, b),
+    }
+}
+
+/// Relative index as used by slice/at: negative counts from the end
+fn relative_index(value: Option<&Val>, len: usize, default: usize) -> usize {
+    match value {
+        None | Some(Val::Undefined) => default,
+        Some(v) => {
+            let n = to_number(v);
+            let n = if n.is_nan() { 0.0 } else { n.trunc() };
+            if n < 0.0 {
+                (len as f64 + n).max(0.0) as usize
+            } else {
+                n.min(len as f64) as usize
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Lexer
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Expr(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(f64),
+    Str(String),
+    Template(Vec<TemplatePart>),
+    Regex(String, String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+const PUNCTS: &[&str] = &[
+    "===", "!==", "...", "**", "?.", "??", "=>", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<",
+    ">", "!", "?", ":", ".", ",", "(", ")", "[", "]", "{", "}", ";",
+];
+
+fn lex(source: &str) -> Result<Vec<Tok>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .ok_or("SyntaxError: unterminated comment")?;
+            i = end + 2;
+            continue;
+        }
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            if c == '0' && matches!(chars.get(i + 1), Some('x' | 'X')) {
+                i += 2;
+                while i < chars.len() && chars[i].is_ascii_hexdigit() {
+                    i += 1;
+                }
+                let text: String = chars[start + 2..i].iter().collect();
+                toks.push(Tok::Num(i64::from_str_radix(&text, 16).map_err(|_| "SyntaxError: invalid hex number")? as f64));
+                continue;
+            }
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            toks.push(Tok::Num(text.parse().map_err(|_| format!("SyntaxError: invalid number {}", text))?));
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            toks.push(Tok::Ident(chars[start..i].iter().collect()));
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            let (text, end) = lex_string(&chars, i + 1, c)?;
+            toks.push(Tok::Str(text));
+            i = end;
+            continue;
+        }
+        if c == '`' {
+            let (parts, end) = lex_template(&chars, i + 1)?;
+            toks.push(Tok::Template(parts));
+            i = end;
+            continue;
+        }
+        // A slash where an operand is expected starts a regex literal
+        let operand_expected = match toks.last() {
+            None => true,
+            Some(Tok::Punct(p)) => !matches!(*p, ")" | "]" | "}"),
+            Some(Tok::Ident(word)) => matches!(word.as_str(), "typeof" | "return"),
+            _ => false,
+        };
+        if c == '/' && operand_expected {
+            let (source, flags, end) = lex_regex(&chars, i + 1)?;
+            toks.push(Tok::Regex(source, flags));
+            i = end;
+            continue;
+        }
+        let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+        let punct = PUNCTS
+            .iter()
+            .find(|p| rest.starts_with(**p))
+            .ok_or(format!("SyntaxError: unexpected character '{}'", c))?;
+        // "a?.5:1" is a conditional, not optional chaining
+        if *punct == "?." && chars.get(i + 2).is_some_and(|d| d.is_ascii_digit()) {
+            toks.push(Tok::Punct("?"));
+            i += 1;
+            continue;
+        }
+        toks.push(Tok::Punct(punct));
+        i += punct.len();
+    }
+    Ok(toks)
+}
+
+fn lex_escape(chars: &[char], i: &mut usize) -> Result<char, String> {
+    let c = *chars.get(*i).ok_or("SyntaxError: unterminated string")?;
+    *i += 1;
+    Ok(match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'v' => '\u{b}',
+        '0' => '\0',
+        'x' | 'u' => {
+            let (start, end) = if c == 'u' && chars.get(*i) == Some(&'{') {
+                let close = chars[*i..].iter().position(|&d| d == '}').ok_or("SyntaxError: invalid unicode escape")?;
+                (*i + 1, *i + close)
+            } else {
+                (*i, *i + if c == 'x' { 2 } else { 4 })
+            };
+            let hex: String = chars.get(start..end).ok_or("SyntaxError: invalid escape")?.iter().collect();
+            *i = if chars.get(end) == Some(&'}') { end + 1 } else { end };
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or("SyntaxError: invalid escape")?
+        }
+        other => other,
+    })
+}
+
+fn lex_string(chars: &[char], mut i: usize, quote: char) -> Result<(String, usize), String> {
+    let mut text = String::new();
+    loop {
+        match chars.get(i) {
+            None | Some('\n') => return Err("SyntaxError: unterminated string".to_string()),
+            Some(&c) if c == quote => return Ok((text, i + 1)),
+            Some('\\') => {
+                i += 1;
+                text.push(lex_escape(chars, &mut i)?);
+            }
+            Some(&c) => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn lex_template(chars: &[char], mut i: usize) -> Result<(Vec<TemplatePart>, usize), String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    loop {
+        match chars.get(i) {
+            None => return Err("SyntaxError: unterminated template literal".to_string()),
+            Some('`') => {
+                parts.push(TemplatePart::Text(text));
+                return Ok((parts, i + 1));
+            }
+            Some('\\') => {
+                i += 1;
+                text.push(lex_escape(chars, &mut i)?);
+            }
+            Some('$') if chars.get(i + 1) == Some(&'{') => {
+                parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                // Find the matching brace, skipping over nested strings
+                let start = i + 2;
+                let mut depth = 1;
+                let mut j = start;
+                let mut quote: Option<char> = None;
+                while depth > 0 {
+                    let c = *chars.get(j).ok_or("SyntaxError: unterminated template expression")?;
+                    match (quote, c) {
+                        (Some(_), '\\') => j += 1,
+                        (Some(q), c) if c == q => quote = None,
+                        (Some(_), _) => {}
+                        (None, '"' | '\'' | '`') => quote = Some(c),
+                        (None, '{') => depth += 1,
+                        (None, '}') => depth -= 1,
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                parts.push(TemplatePart::Expr(chars[start..j - 1].iter().collect()));
+                i = j;
+            }
+            Some(&c) => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn lex_regex(chars: &[char], mut i: usize) -> Result<(String, String, usize), String> {
+    let mut source = String::new();
+    let mut in_class = false;
+    loop {
+        let c = *chars.get(i).ok_or("SyntaxError: unterminated regular expression")?;
+        match c {
+            '\n' => return Err("SyntaxError: unterminated regular expression".to_string()),
+            '\\' => {
+                source.push(c);
+                source.push(*chars.get(i + 1).ok_or("SyntaxError: unterminated regular expression")?);
+                i += 2;
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => break,
+            _ => {}
+        }
+        source.push(c);
+        i += 1;
+    }
+    i += 1;
+    let mut flags = String::new();
+    while i < chars.len() && chars[i].is_ascii_alphabetic() {
+        flags.push(chars[i]);
+        i += 1;
+    }
+    Ok((source, flags, i))
+}
+
+fn compile_regex(source: &str, flags: &str) -> Result<JsRegex, String> {
+    let mut builder = RegexBuilder::new(source);
+    for flag in flags.chars() {
+        match flag {
+            'g' => {}
+            'i' => {
+                builder.case_insensitive(true);
+            }
+            'm' => {
+                builder.multi_line(true);
+            }
+            's' => {
+                builder.dot_matches_new_line(true);
+            }
+            'u' | 'y' | 'd' => {}
+            other => return Err(format!("SyntaxError: invalid regular expression flag '{}'", other)),
+        }
+    }
+    let regex = builder
+        .build()
+        .map_err(|e| format!("SyntaxError: unsupported regular expression /{}/ ({})", source, e.to_string().lines().last().unwrap_or("")))?;
+    Ok(JsRegex { regex, global: flags.contains('g'), source: source.to_string(), flags: flags.to_string() })
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+enum Expr {
+    Lit(Val),
+    Ident(String),
+    Template(Vec<Expr>),
+    Array(Vec<(bool, Expr)>),
+    Object(Vec<Prop>),
+    /// object, property, optional (a?.b, and links after it in the chain)
+    Member(Box<Expr>, Box<Expr>, bool),
+    Call(Box<Expr>, Vec<(bool, Expr)>, bool),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Logical(&'static str, Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+    Arrow(Rc<Vec<String>>, Rc<Expr>),
+}
+
+enum Prop {
+    Pair(Expr, Expr),
+    Spread(Expr),
+}
+
+const RESERVED: &[&str] = &[
+    "true", "false", "null", "undefined", "typeof", "return", "function", "var", "let", "const", "new", "this",
+    "class", "delete", "void", "in", "instanceof", "import", "await", "yield", "if", "else", "for", "while",
+];
+
+fn binary_precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "??" => 1,
+        "||" => 2,
+        "&&" => 3,
+        "==" | "!=" | "===" | "!==" => 4,
+        "<" | ">" | "<=" | ">=" => 5,
+        "+" | "-" => 6,
+        "*" | "/" | "%" => 7,
+        "**" => 8,
+        _ => return None,
+    })
+}
+
+struct Parser {
+    toks: Vec<Tok>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Tok> {
+        self.toks.get(self.pos + offset)
+    }
+
+    fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Punct(p)) if *p == punct)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(format!("SyntaxError: expected '{}' but found {}", punct, self.describe()))
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            None => "end of expression".to_string(),
+            Some(Tok::Punct(p)) => format!("'{}'", p),
+            Some(Tok::Ident(name)) => format!("'{}'", name),
+            Some(Tok::Num(n)) => num_to_string(*n),
+            Some(Tok::Str(_)) | Some(Tok::Template(_)) => "a string".to_string(),
+            Some(Tok::Regex(..)) => "a regular expression".to_string(),
+        }
+    }
+
+    fn nest(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err("SyntaxError: expression is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        self.nest()?;
+        let expr = if self.arrow_ahead() { self.arrow() } else { self.conditional() };
+        self.depth -= 1;
+        expr
+    }
+
+    fn arrow_ahead(&self) -> bool {
+        match self.peek() {
+            Some(Tok::Ident(_)) => matches!(self.peek_at(1), Some(Tok::Punct("=>"))),
+            Some(Tok::Punct("(")) => {
+                let mut offset = 1;
+                loop {
+                    match self.peek_at(offset) {
+                        Some(Tok::Punct(")")) => return matches!(self.peek_at(offset + 1), Some(Tok::Punct("=>"))),
+                        Some(Tok::Ident(_)) | Some(Tok::Punct(",")) => offset += 1,
+                        _ => return false,
+                    }
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.peek().cloned() {
+            Some(Tok::Ident(name)) if !RESERVED.contains(&name.as_str()) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(format!("SyntaxError: expected a name but found {}", self.describe())),
+        }
+    }
+
+    fn arrow(&mut self) -> Result<Expr, String> {
+        let mut params = Vec::new();
+        if self.eat("(") {
+            while !self.eat(")") {
+                params.push(self.identifier()?);
+                if !self.is(")") {
+                    self.expect(",")?;
+                }
+            }
+        } else {
+            params.push(self.identifier()?);
+        }
+        self.expect("=>")?;
+        let body = if self.eat("{") {
+            // Only a single return statement is allowed in a block body
+            if !matches!(self.peek(), Some(Tok::Ident(word)) if word == "return") {
+                return Err("SyntaxError: arrow function bodies may only contain `return <expression>` (wrap object literals in parentheses)".to_string());
+            }
+            self.pos += 1;
+            let body = self.expression()?;
+            self.eat(";");
+            self.expect("}")?;
+            body
+        } else {
+            self.expression()?
+        };
+        Ok(Expr::Arrow(Rc::new(params), Rc::new(body)))
+    }
+
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let test = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(test);
+        }
+        let consequent = self.expression()?;
+        self.expect(":")?;
+        let alternate = self.expression()?;
+        Ok(Expr::Cond(Box::new(test), Box::new(consequent), Box::new(alternate)))
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(Tok::Punct(op)) = self.peek().cloned() {
+            let Some(precedence) = binary_precedence(op).filter(|p| *p >= min_precedence) else {
+                break;
+            };
+            self.pos += 1;
+            self.nest()?;
+            // ** is right-associative
+            let right = self.binary(if op == "**" { precedence } else { precedence + 1 })?;
+            self.depth -= 1;
+            left = if matches!(op, "&&" | "||" | "??") {
+                Expr::Logical(op, Box::new(left), Box::new(right))
+            } else {
+                Expr::Binary(op, Box::new(left), Box::new(right))
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let op = match self.peek() {
+            Some(Tok::Punct(p @ ("!" | "-" | "+"))) => *p,
+            Some(Tok::Ident(word)) if word == "typeof" => "typeof",
+            _ => return self.postfix(),
+        };
+        self.pos += 1;
+        self.nest()?;
+        let operand = self.unary()?;
+        self.depth -= 1;
+        Ok(Expr::Unary(op, Box::new(operand)))
+    }
+
+    fn arguments(&mut self, close: &str) -> Result<Vec<(bool, Expr)>, String> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            let spread = self.eat("...");
+            items.push((spread, self.expression()?));
+            if !self.is(close) {
+                self.expect(",")?;
+            }
+        }
+        Ok(items)
+    }
+
+    fn property_name(&mut self) -> Result<String, String> {
+        match self.peek().cloned() {
+            Some(Tok::Ident(name)) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(format!("SyntaxError: expected a property name but found {}", self.describe())),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        let mut optional = false;
+        loop {
+            if self.eat(".") {
+                let name = self.property_name()?;
+                expr = Expr::Member(Box::new(expr), Box::new(Expr::Lit(str_val(name))), optional);
+            } else if self.eat("?.") {
+                optional = true;
+                if self.eat("(") {
+                    expr = Expr::Call(Box::new(expr), self.arguments(")")?, true);
+                } else if self.eat("[") {
+                    let property = self.expression()?;
+                    self.expect("]")?;
+                    expr = Expr::Member(Box::new(expr), Box::new(property), true);
+                } else {
+                    let name = self.property_name()?;
+                    expr = Expr::Member(Box::new(expr), Box::new(Expr::Lit(str_val(name))), true);
+                }
+            } else if self.eat("[") {
+                let property = self.expression()?;
+                self.expect("]")?;
+                expr = Expr::Member(Box::new(expr), Box::new(property), optional);
+            } else if self.eat("(") {
+                expr = Expr::Call(Box::new(expr), self.arguments(")")?, optional);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let tok = self.peek().cloned().ok_or("SyntaxError: unexpected end of expression")?;
+        self.pos += 1;
+        Ok(match tok {
+            Tok::Num(n) => Expr::Lit(Val::Num(n)),
+            Tok::Str(s) => Expr::Lit(str_val(s)),
+            Tok::Regex(source, flags) => Expr::Lit(Val::Regex(Rc::new(compile_regex(&source, &flags)?))),
+            Tok::Template(parts) => {
+                let mut pieces = Vec::new();
+                for part in parts {
+                    match part {
+                        TemplatePart::Text(text) => pieces.push(Expr::Lit(str_val(text))),
+                        TemplatePart::Expr(source) => pieces.push(parse(&source)?),
+                    }
+                }
+                Expr::Template(pieces)
+            }
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Expr::Lit(Val::Bool(true)),
+                "false" => Expr::Lit(Val::Bool(false)),
+                "null" => Expr::Lit(Val::Null),
+                "undefined" => Expr::Lit(Val::Undefined),
+                "NaN" => Expr::Lit(Val::Num(f64::NAN)),
+                "Infinity" => Expr::Lit(Val::Num(f64::INFINITY)),
+                word if RESERVED.contains(&word) => {
+                    return Err(format!("SyntaxError: '{}' is not supported in transform expressions", word));
+                }
+                _ => Expr::Ident(name),
+            },
+            Tok::Punct("(") => {
+                let expr = self.expression()?;
+                self.expect(")")?;
+                expr
+            }
+            Tok::Punct("[") => Expr::Array(self.arguments("]")?),
+            Tok::Punct("{") => {
+                let mut props = Vec::new();
+                while !self.eat("}") {
+                    if self.eat("...") {
+                        props.push(Prop::Spread(self.expression()?));
+                    } else {
+                        let key = match self.peek().cloned() {
+                            Some(Tok::Ident(name)) => {
+                                self.pos += 1;
+                                if !self.is(":") {
+                                    // Shorthand {name}
+                                    props.push(Prop::Pair(Expr::Lit(str_val(name.as_str())), Expr::Ident(name)));
+                                    if !self.is("}") {
+                                        self.expect(",")?;
+                                    }
+                                    continue;
+                                }
+                                Expr::Lit(str_val(name))
+                            }
+                            Some(Tok::Str(s)) => {
+                                self.pos += 1;
+                                Expr::Lit(str_val(s))
+                            }
+                            Some(Tok::Num(n)) => {
+                                self.pos += 1;
+                                Expr::Lit(str_val(num_to_string(n)))
+                            }
+                            Some(Tok::Punct("[")) => {
+                                self.pos += 1;
+                                let key = self.expression()?;
+                                self.expect("]")?;
+                                key
+                            }
+                            _ => return Err(format!("SyntaxError: expected a property name but found {}", self.describe())),
+                        };
+                        self.expect(":")?;
+                        props.push(Prop::Pair(key, self.expression()?));
+                    }
+                    if !self.is("}") {
+                        self.expect(",")?;
+                    }
+                }
+                Expr::Object(props)
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(format!("SyntaxError: unexpected {}", self.describe()));
+            }
+        })
+    }
+}
+
+fn parse(source: &str) -> Result<Expr, String> {
+    let mut parser = Parser { toks: lex(source)?, pos: 0, depth: 0 };
+    let expr = parser.expression()?;
+    parser.eat(";");
+    if parser.peek().is_some() {
+        return Err(format!("SyntaxError: unexpected {}", parser.describe()));
+    }
+    Ok(expr)
+}
+
+// ============================================================================
+// Evaluator
+// ============================================================================
+
+struct Interp {
+    steps: u64,
+    depth: usize,
+}
+
+fn type_error<T>(message: String) -> Result<T, String> {
+    Err(format!("TypeError: {}", message))
+}
+
+impl Interp {
+    fn charge(&mut self, units: u64) -> Result<(), String> {
+        self.steps += units;
+        if self.steps > STEP_BUDGET {
+            return Err(format!("Expression exceeded its budget of {} steps", STEP_BUDGET));
+        }
+        Ok(())
+    }
+
+    fn string(&mut self, text: String) -> Result<Val, String> {
+        self.charge(text.len() as u64 / 16)?;
+        Ok(str_val(text))
+    }
+
+    fn array(&mut self, items: Vec<Val>) -> Result<Val, String> {
+        self.charge(items.len() as u64)?;
+        Self::nested(arr_val(items))
+    }
+
+    fn object(&mut self, props: Vec<(String, Val)>) -> Result<Val, String> {
+        Self::nested(obj_val(props))
+    }
+
+    fn nested(value: Val) -> Result<Val, String> {
+        if depth(&value) > MAX_VALUE_DEPTH {
+            return Err(format!("RangeError: arrays and objects nest deeper than {} levels", MAX_VALUE_DEPTH));
+        }
+        Ok(value)
+    }
+
+    fn eval(&mut self, expr: &Expr, scope: &Scope) -> Result<Val, String> {
+        self.charge(1)?;
+        match expr {
+            Expr::Lit(value) => Ok(value.clone()),
+            Expr::Ident(name) => lookup(scope, name)
+                .or_else(|| global(name))
+                .ok_or(format!("ReferenceError: {} is not defined", name)),
+            Expr::Template(pieces) => {
+                let mut text = String::new();
+                for piece in pieces {
+                    text.push_str(&to_string(&self.eval(piece, scope)?));
+                }
+                self.string(text)
+            }
+            Expr::Array(items) => {
+                let items = self.spread(items, scope)?;
+                self.array(items)
+            }
+            Expr::Object(props) => {
+                let mut object = Vec::new();
+                for prop in props {
+                    match prop {
+                        Prop::Pair(key, value) => {
+                            let key = to_string(&self.eval(key, scope)?);
+                            let value = self.eval(value, scope)?;
+                            obj_set(&mut object, key, value);
+                        }
+                        Prop::Spread(source) => match self.eval(source, scope)? {
+                            Val::Obj(props) => {
+                                self.charge(props.len() as u64)?;
+                                for (key, value) in props.iter() {
+                                    obj_set(&mut object, key.clone(), value.clone());
+                                }
+                            }
+                            Val::Arr(items) => {
+                                for (index, value) in items.iter().enumerate() {
+                                    obj_set(&mut object, index.to_string(), value.clone());
+                                }
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+                self.object(object)
+            }
+            Expr::Member(object, property, optional) => {
+                let object = self.eval(object, scope)?;
+                if *optional && matches!(object, Val::Undefined | Val::Null) {
+                    return Ok(Val::Undefined);
+                }
+                let property = self.eval(property, scope)?;
+                get_property(&object, &property)
+            }
+            Expr::Call(callee, args, optional) => self.call_expression(callee, args, *optional, scope),
+            Expr::Unary(op, operand) => {
+                if *op == "typeof" {
+                    if let Expr::Ident(name) = operand.as_ref() {
+                        if lookup(scope, name).is_none() && global(name).is_none() {
+                            return Ok(str_val("undefined"));
+                        }
+                    }
+                }
+                let value = self.eval(operand, scope)?;
+                Ok(match *op {
+                    "!" => Val::Bool(!truthy(&value)),
+                    "-" => Val::Num(-to_number(&value)),
+                    "+" => Val::Num(to_number(&value)),
+                    _ => str_val(type_of(&value)),
+                })
+            }
+            Expr::Logical(op, left, right) => {
+                let left = self.eval(left, scope)?;
+                let short_circuit = match *op {
+                    "&&" => !truthy(&left),
+                    "||" => truthy(&left),
+                    _ => !matches!(left, Val::Undefined | Val::Null),
+                };
+                if short_circuit {
+                    Ok(left)
+                } else {
+                    self.eval(right, scope)
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, scope)?;
+                let right = self.eval(right, scope)?;
+                self.binary(op, left, right)
+            }
+            Expr::Cond(test, consequent, alternate) => {
+                if truthy(&self.eval(test, scope)?) {
+                    self.eval(consequent, scope)
+                } else {
+                    self.eval(alternate, scope)
+                }
+            }
+            Expr::Arrow(params, body) => Ok(Val::Func(Rc::new(Closure {
+                params: params.to_vec(),
+                body: Rc::clone(body),
+                scope: Rc::clone(scope),
+            }))),
+        }
+    }
+
+    fn spread(&mut self, items: &[(bool, Expr)], scope: &Scope) -> Result<Vec<Val>, String> {
+        let mut values = Vec::new();
+        for (spread, item) in items {
+            let value = self.eval(item, scope)?;
+            if !spread {
+                values.push(value);
+                continue;
+            }
+            match value {
+                Val::Arr(items) => {
+                    self.charge(items.len() as u64)?;
+                    values.extend(items.iter().cloned());
+                }
+                Val::Str(s) => values.extend(s.chars().map(|c| str_val(c.to_string()))),
+                other => return type_error(format!("{} is not iterable", to_string(&other))),
+            }
+        }
+        Ok(values)
+    }
+
+    fn binary(&mut self, op: &str, left: Val, right: Val) -> Result<Val, String> {
+        Ok(match op {
+            "===" => Val::Bool(strict_eq(&left, &right)),
+            "!==" => Val::Bool(!strict_eq(&left, &right)),
+            "==" => Val::Bool(loose_eq(&left, &right)),
+            "!=" => Val::Bool(!loose_eq(&left, &right)),
+            "+" => {
+                let prim = |v: Val| if is_primitive(&v) { v } else { str_val(to_string(&v)) };
+                let (left, right) = (prim(left), prim(right));
+                if matches!(left, Val::Str(_)) || matches!(right, Val::Str(_)) {
+                    let text = to_string(&left) + &to_string(&right);
+                    return self.string(text);
+                }
+                Val::Num(to_number(&left) + to_number(&right))
+            }
+            "<" | ">" | "<=" | ">=" => {
+                let ordering = match (&left, &right) {
+                    (Val::Str(a), Val::Str(b)) => Some(a.cmp(b)),
+                    _ => to_number(&left).partial_cmp(&to_number(&right)),
+                };
+                Val::Bool(match (op, ordering) {
+                    (_, None) => false,
+                    ("<", Some(o)) => o == Ordering::Less,
+                    (">", Some(o)) => o == Ordering::Greater,
+                    ("<=", Some(o)) => o != Ordering::Greater,
+                    (_, Some(o)) => o != Ordering::Less,
+                })
+            }
+            _ => {
+                let (a, b) = (to_number(&left), to_number(&right));
+                Val::Num(match op {
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    "%" => a % b,
+                    _ => a.powf(b),
+                })
+            }
+        })
+    }
+
+    fn call_expression(&mut self, callee: &Expr, args: &[(bool, Expr)], optional: bool, scope: &Scope) -> Result<Val, String> {
+        if let Expr::Member(object, property, optional_member) = callee {
+            let object = self.eval(object, scope)?;
+            if (*optional_member || optional) && matches!(object, Val::Undefined | Val::Null) {
+                return Ok(Val::Undefined);
+            }
+            let name = to_string(&self.eval(property, scope)?);
+            let args = self.spread(args, scope)?;
+            return self.call_method(&object, &name, args, optional);
+        }
+        let function = self.eval(callee, scope)?;
+        if optional && matches!(function, Val::Undefined | Val::Null) {
+            return Ok(Val::Undefined);
+        }
+        let args = self.spread(args, scope)?;
+        self.call(&function, args)
+    }
+
+    fn call(&mut self, function: &Val, args: Vec<Val>) -> Result<Val, String> {
+        match function {
+            Val::Func(closure) => {
+                if self.depth >= MAX_CALL_DEPTH {
+                    return Err(format!("RangeError: maximum call depth of {} exceeded", MAX_CALL_DEPTH));
+                }
+                let mut args = args.into_iter();
+                let vars = closure
+                    .params
+                    .iter()
+                    .map(|param| (param.clone(), args.next().unwrap_or(Val::Undefined)))
+                    .collect();
+                let scope = Rc::new(Frame { vars, parent: Some(Rc::clone(&closure.scope)) });
+                self.depth += 1;
+                let result = self.eval(&closure.body, &scope);
+                self.depth -= 1;
+                result
+            }
+            Val::Builtin(name) => self.call_builtin(name, &args),
+            other => type_error(format!("{} is not a function", to_string(other))),
+        }
+    }
+
+    fn call_builtin(&mut self, name: &str, args: &[Val]) -> Result<Val, String> {
+        let arg = args.first().cloned().unwrap_or(Val::Undefined);
+        Ok(match name {
+            "Number" => Val::Num(if args.is_empty() { 0.0 } else { to_number(&arg) }),
+            "String" => str_val(if args.is_empty() { String::new() } else { to_string(&arg) }),
+            "Boolean" => Val::Bool(truthy(&arg)),
+            "isNaN" => Val::Bool(to_number(&arg).is_nan()),
+            "isFinite" => Val::Bool(to_number(&arg).is_finite()),
+            "parseFloat" => {
+                let text = to_string(&arg);
+                let text = text.trim_start();
+                let end = (1..=text.len())
+                    .rev()
+                    .find(|&end| text.is_char_boundary(end) && text[..end].parse::<f64>().is_ok() && !text[..end].ends_with(['e', 'E']))
+                    .unwrap_or(0);
+                Val::Num(text[..end].parse().unwrap_or(f64::NAN))
+            }
+            "parseInt" => {
+                let text = to_string(&arg);
+                let text = text.trim();
+                let (negative, text) = match text.strip_prefix('-') {
+                    Some(rest) => (true, rest),
+                    None => (false, text.strip_prefix('+').unwrap_or(text)),
+                };
+                let mut radix = args.get(1).map(to_number).filter(|r| *r != 0.0 && !r.is_nan()).unwrap_or(10.0) as u32;
+                let text = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                    Some(rest) if radix == 16 || args.get(1).is_none() => {
+                        radix = 16;
+                        rest
+                    }
+                    _ => text,
+                };
+                if !(2..=36).contains(&radix) {
+                    return Ok(Val::Num(f64::NAN));
+                }
+                let digits: String = text.chars().take_while(|c| c.is_digit(radix)).collect();
+                match i64::from_str_radix(&digits, radix) {
+                    Ok(n) => Val::Num(if negative { -(n as f64) } else { n as f64 }),
+                    Err(_) => Val::Num(f64::NAN),
+                }
+            }
+            _ => return type_error(format!("{} is not a function", name)),
+        })
+    }
+
+    fn call_method(&mut self, object: &Val, name: &str, args: Vec<Val>, optional: bool) -> Result<Val, String> {
+        match object {
+            Val::Arr(items) => self.array_method(items, name, args),
+            Val::Str(text) => self.string_method(text, name, args),
+            Val::Num(n) => number_method(*n, name, &args),
+            Val::Namespace(namespace) => self.namespace_method(namespace, name, args),
+            Val::Builtin("Number") if matches!(name, "isInteger" | "isFinite" | "isNaN" | "parseFloat" | "parseInt") => {
+                let arg = args.first().cloned().unwrap_or(Val::Undefined);
+                Ok(match name {
+                    "isInteger" => Val::Bool(matches!(arg, Val::Num(n) if n.is_finite() && n.fract() == 0.0)),
+                    "isFinite" => Val::Bool(matches!(arg, Val::Num(n) if n.is_finite())),
+                    "isNaN" => Val::Bool(matches!(arg, Val::Num(n) if n.is_nan())),
+                    other => self.call_builtin(other, &args)?,
+                })
+            }
+            Val::Regex(re) if name == "test" => {
+                Ok(Val::Bool(re.regex.is_match(&to_string(args.first().unwrap_or(&Val::Undefined)))))
+            }
+            Val::Undefined | Val::Null => type_error(format!("Cannot read properties of {} (reading '{}')", to_string(object), name)),
+            _ => {
+                let method = get_property(object, &str_val(name))?;
+                if optional && matches!(method, Val::Undefined | Val::Null) {
+                    return Ok(Val::Undefined);
+                }
+                match method {
+                    Val::Func(_) | Val::Builtin(_) => self.call(&method, args),
+                    _ => type_error(format!("{}.{} is not a function", type_of(object), name)),
+                }
+            }
+        }
+    }
+
+    fn callback(&mut self, args: &[Val], method: &str) -> Result<Val, String> {
+        match args.first() {
+            Some(f @ (Val::Func(_) | Val::Builtin(_))) => Ok(f.clone()),
+            _ => type_error(format!("{} needs a function argument", method)),
+        }
+    }
+
+    fn array_method(&mut self, items: &Rc<Nested<Val>>, name: &str, args: Vec<Val>) -> Result<Val, String> {
+        let this = Val::Arr(Rc::clone(items));
+        let arg = |i: usize| args.get(i).cloned().unwrap_or(Val::Undefined);
+        let call_each = |interp: &mut Interp, f: &Val, index: usize, item: &Val| {
+            interp.call(f, vec![item.clone(), Val::Num(index as f64), this.clone()])
+        };
+        match name {
+            "map" | "filter" | "flatMap" => {
+                let f = self.callback(&args, name)?;
+                let mut out = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    let result = call_each(self, &f, index, item)?;
+                    match name {
+                        "map" => out.push(result),
+                        "filter" => {
+                            if truthy(&result) {
+                                out.push(item.clone());
+                            }
+                        }
+                        _ => match result {
+                            Val::Arr(inner) => out.extend(inner.iter().cloned()),
+                            other => out.push(other),
+                        },
+                    }
+                }
+                self.array(out)
+            }
+            "find" | "findIndex" | "findLast" | "findLastIndex" | "some" | "every" => {
+                let f = self.callback(&args, name)?;
+                let reverse = name.starts_with("findLast");
+                let indices: Box<dyn Iterator<Item = usize>> =
+                    if reverse { Box::new((0..items.len()).rev()) } else { Box::new(0..items.len()) };
+                for index in indices {
+                    let hit = truthy(&call_each(self, &f, index, &items[index])?);
+                    match name {
+                        "every" if !hit => return Ok(Val::Bool(false)),
+                        "some" if hit => return Ok(Val::Bool(true)),
+                        "find" | "findLast" if hit => return Ok(items[index].clone()),
+                        "findIndex" | "findLastIndex" if hit => return Ok(Val::Num(index as f64)),
+                        _ => {}
+                    }
+                }
+                Ok(match name {
+                    "every" => Val::Bool(true),
+                    "some" => Val::Bool(false),
+                    "find" | "findLast" => Val::Undefined,
+                    _ => Val::Num(-1.0),
+                })
+            }
+            "reduce" => {
+                let f = self.callback(&args, name)?;
+                let mut iter = items.iter().enumerate();
+                let mut acc = match args.get(1) {
+                    Some(initial) => initial.clone(),
+                    None => match iter.next() {
+                        Some((_, first)) => first.clone(),
+                        None => return type_error("Reduce of empty array with no initial value".to_string()),
+                    },
+                };
+                for (index, item) in iter {
+                    acc = self.call(&f, vec![acc, item.clone(), Val::Num(index as f64), this.clone()])?;
+                }
+                Ok(acc)
+            }
+            "slice" => {
+                let start = relative_index(args.first(), items.len(), 0);
+                let end = relative_index(args.get(1), items.len(), items.len());
+                self.array(items.get(start..end.max(start)).unwrap_or_default().to_vec())
+            }
+            "at" => Ok(match to_number(&arg(0)).trunc() {
+                n if n < 0.0 => items.len().checked_sub((-n) as usize).and_then(|i| items.get(i)).cloned(),
+                n => items.get(n as usize).cloned(),
+            }
+            .unwrap_or(Val::Undefined)),
+            "concat" => {
+                let mut out = items.to_vec();
+                for value in args {
+                    match value {
+                        Val::Arr(more) => out.extend(more.iter().cloned()),
+                        other => out.push(other),
+                    }
+                }
+                self.array(out)
+            }
+            "flat" => {
+                let depth = match arg(0) {
+                    Val::Undefined => 1,
+                    other => to_number(&other).max(0.0) as usize,
+                };
+                let mut out = Vec::new();
+                self.flatten(items, depth, &mut out)?;
+                self.array(out)
+            }
+            "join" => {
+                let separator = match arg(0) {
+                    Val::Undefined => ",".to_string(),
+                    other => to_string(&other),
+                };
+                let text = items
+                    .iter()
+                    .map(|item| if matches!(item, Val::Undefined | Val::Null) { String::new() } else { to_string(item) })
+                    .collect::<Vec<_>>()
+                    .join(&separator);
+                self.string(text)
+            }
+            "includes" => Ok(Val::Bool(items.iter().any(|item| same_value_zero(item, &arg(0))))),
+            "indexOf" | "lastIndexOf" => {
+                let target = arg(0);
+                let found = if name == "indexOf" {
+                    items.iter().position(|item| strict_eq(item, &target))
+                } else {
+                    items.iter().rposition(|item| strict_eq(item, &target))
+                };
+                Ok(Val::Num(found.map_or(-1.0, |i| i as f64)))
+            }
+            "reverse" | "toReversed" => self.array(items.iter().rev().cloned().collect()),
+            "sort" | "toSorted" => {
+                let comparator = args.first().filter(|f| !matches!(f, Val::Undefined)).cloned();
+                let mut out = items.to_vec();
+                self.charge(out.len() as u64)?;
+                let mut failure = None;
+                out.sort_by(|a, b| {
+                    // undefined sorts last, as in JavaScript
+                    match (a, b) {
+                        (Val::Undefined, Val::Undefined) => return Ordering::Equal,
+                        (Val::Undefined, _) => return Ordering::Greater,
+                        (_, Val::Undefined) => return Ordering::Less,
+                        _ => {}
+                    }
+                    if failure.is_some() {
+                        return Ordering::Equal;
+                    }
+                    match &comparator {
+                        None => to_string(a).cmp(&to_string(b)),
+                        Some(f) => match self.call(f, vec![a.clone(), b.clone()]) {
+                            Ok(result) => to_number(&result).partial_cmp(&0.0).unwrap_or(Ordering::Equal),
+                            Err(e) => {
+                                failure = Some(e);
+                                Ordering::Equal
+                            }
+                        },
+                    }
+                });
+                match failure {
+                    Some(e) => Err(e),
+                    None => Self::nested(arr_val(out)),
+                }
+            }
+            "keys" => self.array((0..items.len()).map(|i| Val::Num(i as f64)).collect()),
+            "entries" => self.array(items.iter().enumerate().map(|(i, item)| arr_val(vec![Val::Num(i as f64), item.clone()])).collect()),
+            "toString" => self.string(to_string(&this)),
+            _ => type_error(format!("array.{} is not a function", name)),
+        }
+    }
+
+    fn flatten(&mut self, items: &[Val], depth: usize, out: &mut Vec<Val>) -> Result<(), String> {
+        self.charge(items.len() as u64)?;
+        for item in items {
+            match item {
+                Val::Arr(inner) if depth > 0 => self.flatten(inner, depth - 1, out)?,
+                other => out.push(other.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    fn string_method(&mut self, text: &Rc<str>, name: &str, args: Vec<Val>) -> Result<Val, String> {
+        let arg = |i: usize| args.get(i).cloned().unwrap_or(Val::Undefined);
+        let chars = || text.chars().collect::<Vec<char>>();
+        let substring = |start: usize, end: usize| -> String {
+            text.chars().skip(start).take(end.saturating_sub(start)).collect()
+        };
+        let len = text.chars().count();
+        Ok(match name {
+            "toLowerCase" | "toLocaleLowerCase" => self.string(text.to_lowercase())?,
+            "toUpperCase" | "toLocaleUpperCase" => self.string(text.to_uppercase())?,
+            "trim" => str_val(text.trim()),
+            "trimStart" => str_val(text.trim_start()),
+            "trimEnd" => str_val(text.trim_end()),
+            "toString" | "valueOf" => Val::Str(Rc::clone(text)),
+            "includes" => Val::Bool(text.contains(to_string(&arg(0)).as_str())),
+            "startsWith" => Val::Bool(text.starts_with(to_string(&arg(0)).as_str())),
+            "endsWith" => Val::Bool(text.ends_with(to_string(&arg(0)).as_str())),
+            "indexOf" | "lastIndexOf" => {
+                let needle = to_string(&arg(0));
+                let found = if name == "indexOf" { text.find(needle.as_str()) } else { text.rfind(needle.as_str()) };
+                Val::Num(found.map_or(-1.0, |byte| text[..byte].chars().count() as f64))
+            }
+            "slice" => {
+                let start = relative_index(args.first(), len, 0);
+                let end = relative_index(args.get(1), len, len);
+                str_val(substring(start, end))
+            }
+            "substring" => {
+                let clamp = |v: Option<&Val>, default: usize| match v {
+                    None | Some(Val::Undefined) => default,
+                    Some(v) => to_number(v).max(0.0).min(len as f64) as usize,
+                };
+                let (a, b) = (clamp(args.first(), 0), clamp(args.get(1), len));
+                str_val(substring(a.min(b), a.max(b)))
+            }
+            "at" | "charAt" => {
+                let n = to_number(&arg(0));
+                let n = if n.is_nan() { 0.0 } else { n.trunc() };
+                let index = if n < 0.0 && name == "at" { len.checked_sub((-n) as usize) } else if n < 0.0 { None } else { Some(n as usize) };
+                match index.and_then(|i| chars().get(i).copied()) {
+                    Some(c) => str_val(c.to_string()),
+                    None if name == "at" => Val::Undefined,
+                    None => str_val(""),
+                }
+            }
+            "charCodeAt" | "codePointAt" => match chars().get(to_number(&arg(0)).max(0.0) as usize) {
+                Some(c) => Val::Num(f64::from(u32::from(*c))),
+                None => Val::Num(f64::NAN),
+            },
+            "padStart" | "padEnd" => {
+                let target = to_number(&arg(0)).max(0.0) as usize;
+                let fill = match arg(1) {
+                    Val::Undefined => " ".to_string(),
+                    other => to_string(&other),
+                };
+                if target <= len || fill.is_empty() {
+                    Val::Str(Rc::clone(text))
+                } else {
+                    self.charge(target as u64 / 16)?;
+                    let padding: String = fill.chars().cycle().take(target - len).collect();
+                    if name == "padStart" { str_val(padding + text) } else { str_val(format!("{}{}", text, padding)) }
+                }
+            }
+            "repeat" => {
+                let count = to_number(&arg(0));
+                if !(0.0..=f64::from(u32::MAX)).contains(&count) {
+                    return Err("RangeError: invalid repeat count".to_string());
+                }
+                self.charge((text.len() as u64).saturating_mul(count as u64) / 16)?;
+                str_val(text.repeat(count as usize))
+            }
+            "concat" => {
+                let mut out = text.to_string();
+                for value in &args {
+                    out.push_str(&to_string(value));
+                }
+                self.string(out)?
+            }
+            "split" => {
+                let limit = match arg(1) {
+                    Val::Undefined => usize::MAX,
+                    other => to_number(&other).max(0.0) as usize,
+                };
+                let parts: Vec<Val> = match arg(0) {
+                    Val::Undefined => vec![Val::Str(Rc::clone(text))],
+                    Val::Regex(re) => re.regex.split(text).map(str_val).collect(),
+                    separator => {
+                        let separator = to_string(&separator);
+                        if separator.is_empty() {
+                            text.chars().map(|c| str_val(c.to_string())).collect()
+                        } else {
+                            text.split(separator.as_str()).map(str_val).collect()
+                        }
+                    }
+                };
+                self.array(parts.into_iter().take(limit).collect())?
+            }
+            "replace" | "replaceAll" => self.replace(text, name == "replaceAll", arg(0), arg(1))?,
+            "match" => {
+                let Val::Regex(re) = arg(0) else {
+                    return type_error("match needs a regular expression".to_string());
+                };
+                if re.global {
+                    let matches: Vec<Val> = re.regex.find_iter(text).map(|m| str_val(m.as_str())).collect();
+                    if matches.is_empty() { Val::Null } else { self.array(matches)? }
+                } else {
+                    match re.regex.captures(text) {
+                        Some(captures) => self.array(
+                            captures
+                                .iter()
+                                .map(|group| group.map_or(Val::Undefined, |g| str_val(g.as_str())))
+                                .collect(),
+                        )?,
+                        None => Val::Null,
+                    }
+                }
+            }
+            "search" => match arg(0) {
+                Val::Regex(re) => Val::Num(re.regex.find(text).map_or(-1.0, |m| text[..m.start()].chars().count() as f64)),
+                _ => return type_error("search needs a regular expression".to_string()),
+            },
+            "localeCompare" => Val::Num(match text.as_ref().cmp(to_string(&arg(0)).as_str()) {
+                Ordering::Less => -1.0,
+                Ordering::Equal => 0.0,
+                Ordering::Greater => 1.0,
+            }),
+            _ => return type_error(format!("string.{} is not a function", name)),
+        })
+    }
+
+    fn replace(&mut self, text: &str, all: bool, pattern: Val, replacement: Val) -> Result<Val, String> {
+        let callback = matches!(replacement, Val::Func(_) | Val::Builtin(_));
+        let replacement_text = to_string(&replacement);
+        let (regex, global) = match &pattern {
+            Val::Regex(re) => (re.regex.clone(), re.global || all),
+            other => (Regex::new(&regex::escape(&to_string(other))).map_err(|e| e.to_string())?, all),
+        };
+        let mut out = String::new();
+        let mut last = 0;
+        for captures in regex.captures_iter(text) {
+            let whole = captures.get(0).expect("group 0 always matches");
+            out.push_str(&text[last..whole.start()]);
+            if callback {
+                let mut args: Vec<Val> = captures.iter().map(|g| g.map_or(Val::Undefined, |g| str_val(g.as_str()))).collect();
+                args.push(Val::Num(text[..whole.start()].chars().count() as f64));
+                args.push(str_val(text));
+                out.push_str(&to_string(&self.call(&replacement, args)?));
+            } else if matches!(pattern, Val::Regex(_)) {
+                captures.expand(&js_replacement(&replacement_text), &mut out);
+            } else {
+                out.push_str(&replacement_text);
+            }
+            last = whole.end();
+            if !global {
+                break;
+            }
+        }
+        out.push_str(&text[last..]);
+        self.string(out)
+    }
+
+    fn namespace_method(&mut self, namespace: &str, name: &str, args: Vec<Val>) -> Result<Val, String> {
+        let arg = |i: usize| args.get(i).cloned().unwrap_or(Val::Undefined);
+        let number = |i: usize| to_number(&arg(i));
+        Ok(match (namespace, name) {
+            ("Math", "abs") => Val::Num(number(0).abs()),
+            ("Math", "floor") => Val::Num(number(0).floor()),
+            ("Math", "ceil") => Val::Num(number(0).ceil()),
+            ("Math", "round") => Val::Num((number(0) + 0.5).floor()),
+            ("Math", "trunc") => Val::Num(number(0).trunc()),
+            ("Math", "sign") => Val::Num(match number(0) {
+                n if n > 0.0 => 1.0,
+                n if n < 0.0 => -1.0,
+                n => n,
+            }),
+            ("Math", "sqrt") => Val::Num(number(0).sqrt()),
+            ("Math", "cbrt") => Val::Num(number(0).cbrt()),
+            ("Math", "pow") => Val::Num(number(0).powf(number(1))),
+            ("Math", "log") => Val::Num(number(0).ln()),
+            ("Math", "log10") => Val::Num(number(0).log10()),
+            ("Math", "log2") => Val::Num(number(0).log2()),
+            ("Math", "exp") => Val::Num(number(0).exp()),
+            ("Math", "min") => Val::Num(args.iter().map(to_number).fold(f64::INFINITY, |a, b| if a.is_nan() || b.is_nan() { f64::NAN } else { a.min(b) })),
+            ("Math", "max") => Val::Num(args.iter().map(to_number).fold(f64::NEG_INFINITY, |a, b| if a.is_nan() || b.is_nan() { f64::NAN } else { a.max(b) })),
+            ("Object", "keys") | ("Object", "values") | ("Object", "entries") => {
+                let props: Vec<(String, Val)> = match arg(0) {
+                    Val::Obj(props) => props.to_vec(),
+                    Val::Arr(items) => items.iter().enumerate().map(|(i, v)| (i.to_string(), v.clone())).collect(),
+                    Val::Undefined | Val::Null => return type_error("Cannot convert undefined or null to object".to_string()),
+                    _ => Vec::new(),
+                };
+                self.array(
+                    props
+                        .into_iter()
+                        .map(|(key, value)| match name {
+                            "keys" => str_val(key),
+                            "values" => value,
+                            _ => arr_val(vec![str_val(key), value]),
+                        })
+                        .collect(),
+                )?
+            }
+            ("Object", "fromEntries") => {
+                let Val::Arr(entries) = arg(0) else {
+                    return type_error("Object.fromEntries needs an array of [key, value] pairs".to_string());
+                };
+                self.charge(entries.len() as u64)?;
+                let mut object = Vec::new();
+                for entry in entries.iter() {
+                    let Val::Arr(pair) = entry else {
+                        return type_error("Object.fromEntries needs an array of [key, value] pairs".to_string());
+                    };
+                    let key = to_string(pair.first().unwrap_or(&Val::Undefined));
+                    obj_set(&mut object, key, pair.get(1).cloned().unwrap_or(Val::Undefined));
+                }
+                self.object(object)?
+            }
+            ("Object", "assign") => {
+                let mut object = Vec::new();
+                for source in &args {
+                    if let Val::Obj(props) = source {
+                        self.charge(props.len() as u64)?;
+                        for (key, value) in props.iter() {
+                            obj_set(&mut object, key.clone(), value.clone());
+                        }
+                    }
+                }
+                self.object(object)?
+            }
+            ("Array", "isArray") => Val::Bool(matches!(arg(0), Val::Arr(_))),
+            ("Array", "from") => {
+                let items = match arg(0) {
+                    Val::Arr(items) => items.to_vec(),
+                    Val::Str(s) => s.chars().map(|c| str_val(c.to_string())).collect(),
+                    _ => Vec::new(),
+                };
+                let items = match args.get(1) {
+                    Some(f) => {
+                        let mut mapped = Vec::with_capacity(items.len());
+                        for (index, item) in items.into_iter().enumerate() {
+                            mapped.push(self.call(f, vec![item, Val::Num(index as f64)])?);
+                        }
+                        mapped
+                    }
+                    None => items,
+                };
+                self.array(items)?
+            }
+            ("JSON", "stringify") => match to_json(&arg(0)) {
+                None => Val::Undefined,
+                Some(json) => {
+                    let indent = to_number(&arg(2));
+                    let text = if indent > 0.0 || matches!(arg(2), Val::Str(_)) {
+                        serde_json::to_string_pretty(&json)
+                    } else {
+                        serde_json::to_string(&json)
+                    };
+                    self.string(text.map_err(|e| e.to_string())?)?
+                }
+            },
+            ("JSON", "parse") => {
+                let text = to_string(&arg(0));
+                self.charge(text.len() as u64 / 16)?;
+                let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("SyntaxError: {}", e))?;
+                from_json(&json)
+            }
+            _ => return type_error(format!("{}.{} is not a function", namespace, name)),
+        })
+    }
+}
+
+fn number_method(n: f64, name: &str, args: &[Val]) -> Result<Val, String> {
+    match name {
+        "toFixed" => {
+            let digits = args.first().map_or(0.0, to_number);
+            if !(0.0..=100.0).contains(&digits) {
+                return Err("RangeError: toFixed() digits argument must be between 0 and 100".to_string());
+            }
+            Ok(str_val(format!("{:.*}", digits as usize, n)))
+        }
+        "toString" => match args.first().map(to_number) {
+            None | Some(10.0) => Ok(str_val(num_to_string(n))),
+            Some(radix) if (2.0..=36.0).contains(&radix) && n.fract() == 0.0 => {
+                let radix = radix as u32;
+                let mut value = n.abs() as u64;
+                let mut digits = Vec::new();
+                loop {
+                    digits.push(std::char::from_digit((value % u64::from(radix)) as u32, radix).unwrap_or('0'));
+                    value /= u64::from(radix);
+                    if value == 0 {
+                        break;
+                    }
+                }
+                if n < 0.0 {
+                    digits.push('-');
+                }
+                Ok(str_val(digits.iter().rev().collect::<String>()))
+            }
+            Some(_) => Err("RangeError: toString() radix must be between 2 and 36 for integers".to_string()),
+        },
+        "valueOf" => Ok(Val::Num(n)),
+        _ => type_error(format!("number.{} is not a function", name)),
+    }
+}
+
+/// Translate a JavaScript replacement string ($1, $&, $$) to regex crate syntax
+fn js_replacement(replacement: &str) -> String {
+    let mut out = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some('&') => {
+                chars.next();
+                out.push_str("${0}");
+            }
+            Some(d) if d.is_ascii_digit() => {
+                chars.next();
+                out.push_str(&format!("${{{}}}", d));
+            }
+            Some('$') => {
+                chars.next();
+                out.push_str("$$");
+            }
+            _ => out.push_str("$$"),
+        }
+    }
+    out
+}
+
+fn get_property(object: &Val, property: &Val) -> Result<Val, String> {
+    let key = match property {
+        Val::Num(n) => num_to_string(*n),
+        other => to_string(other),
+    };
+    Ok(match object {
+        Val::Undefined | Val::Null => {
+            return type_error(format!("Cannot read properties of {} (reading '{}')", to_string(object), key));
+        }
+        Val::Arr(items) if key == "length" => Val::Num(items.len() as f64),
+        Val::Arr(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)).cloned().unwrap_or(Val::Undefined),
+        Val::Str(text) if key == "length" => Val::Num(text.chars().count() as f64),
+        Val::Str(text) => key
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| text.chars().nth(i))
+            .map_or(Val::Undefined, |c| str_val(c.to_string())),
+        Val::Obj(props) => props.iter().find(|(k, _)| *k == key).map_or(Val::Undefined, |(_, v)| v.clone()),
+        Val::Namespace("Math") => match key.as_str() {
+            "PI" => Val::Num(std::f64::consts::PI),
+            "E" => Val::Num(std::f64::consts::E),
+            _ => Val::Undefined,
+        },
+        Val::Builtin("Number") => match key.as_str() {
+            "MAX_SAFE_INTEGER" => Val::Num(9_007_199_254_740_991.0),
+            "MIN_SAFE_INTEGER" => Val::Num(-9_007_199_254_740_991.0),
+            "EPSILON" => Val::Num(f64::EPSILON),
+            _ => Val::Undefined,
+        },
+        Val::Regex(re) => match key.as_str() {
+            "source" => str_val(re.source.as_str()),
+            "flags" => str_val(re.flags.as_str()),
+            "global" => Val::Bool(re.global),
+            _ => Val::Undefined,
+        },
+        _ => Val::Undefined,
+    })
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+/// Evaluate `expression` with `data` (and `$`) bound to the input
+pub fn evaluate(expression: &str, data: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if expression.chars().count() > MAX_EXPRESSION_CHARS {
+        return Err(format!("Expression is longer than {} characters", MAX_EXPRESSION_CHARS));
+    }
+    let expr = parse(expression)?;
+    let input = from_json(data);
+    let scope = Rc::new(Frame {
+        vars: vec![("data".to_string(), input.clone()), ("$".to_string(), input)],
+        parent: None,
+    });
+    let mut interp = Interp { steps: 0, depth: 0 };
+    let result = interp.eval(&expr, &scope)?;
+    Ok(to_json(&result).unwrap_or(serde_json::Value::Null))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(expression: &str, data: serde_json::Value) -> serde_json::Value {
+        evaluate(expression, &data).unwrap_or_else(|e| panic!("{}: {}", expression, e))
+    }
+
+    #[test]
+    fn test_reshapes_extracted_rows() {
+        let rows = json!([
+            { "name": "  Widget ", "price": "$12.50", "tags": ["a", "b"] },
+            { "name": "Gadget", "price": "$3", "tags": [] },
+            { "name": "Gizmo", "price": "n/a" }
+        ]);
+        assert_eq!(
+            eval(
+                "data.map(r => ({ name: r.name.trim(), price: parseFloat(r.price.replace(/[^0-9.]/g, '')) }))
+                     .filter(r => !isNaN(r.price))
+                     .sort((a, b) => a.price - b.price)",
+                rows.clone()
+            ),
+            json!([{ "name": "Gadget", "price": 3 }, { "name": "Widget", "price": 12.5 }])
+        );
+        assert_eq!(eval("data.flatMap(r => r.tags ?? []).join('|')", rows.clone()), json!("a|b"));
+        assert_eq!(eval("data[2].tags?.length ?? 0", rows.clone()), json!(0));
+        assert_eq!(
+            eval("Object.fromEntries(data.map((r, i) => [`row${i + 1}`, r.name.trim().toLowerCase()]))", rows),
+            json!({ "row1": "widget", "row2": "gadget", "row3": "gizmo" })
+        );
+    }
+
+    #[test]
+    fn test_operators_and_builtins() {
+        assert_eq!(eval("1 + '2'", json!(null)), json!("12"));
+        assert_eq!(eval("[1, 2] + ''", json!(null)), json!("1,2"));
+        assert_eq!(eval("2 ** 3 ** 2", json!(null)), json!(512));
+        assert_eq!(eval("0.1 + 0.2 === 0.3", json!(null)), json!(false));
+        assert_eq!(eval("null == undefined && '1' == 1 && !(null === undefined)", json!(null)), json!(true));
+        assert_eq!(eval("typeof missing + ' ' + typeof data", json!({})), json!("undefined object"));
+        assert_eq!(eval("$.a?.b.c", json!({})), json!(null));
+        assert_eq!(eval("data.reduce((sum, n) => sum + n, 0) / data.length", json!([1, 2, 3, 6])), json!(3));
+        assert_eq!(eval("Math.max(...data) + Math.round(2.5)", json!([4, 9, 1])), json!(12));
+        assert_eq!(eval("(1234.5).toFixed(1).padStart(8, '*')", json!(null)), json!("**1234.5"));
+        assert_eq!(eval("'2024-05-06'.match(/(\\d+)-(\\d+)/)[2]", json!(null)), json!("05"));
+        assert_eq!(eval("'a-b_c'.split(/[-_]/)", json!(null)), json!(["a", "b", "c"]));
+        assert_eq!(eval("'John Smith'.replace(/(\\w+) (\\w+)/, '$2, $1')", json!(null)), json!("Smith, John"));
+        assert_eq!(eval("JSON.parse(data).x", json!("{\"x\": [1]}")), json!([1]));
+        assert_eq!(eval("data.map(x => { return x * 2; })", json!([1, 2])), json!([2, 4]));
+        assert_eq!(eval("[3, 1, 2].sort()", json!(null)), json!([1, 2, 3]));
+        assert_eq!(eval("data.x ? 'yes' : 'no'", json!({ "x": 0 })), json!("no"));
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let error = |expression: &str| evaluate(expression, &json!(null)).unwrap_err();
+        assert!(error("fetch('https://example.com')").starts_with("ReferenceError"));
+        assert!(error("(f => f(f))(f => f(f))").starts_with("RangeError"));
+        assert!(error("'x'.repeat(1e9)").contains("budget"));
+        assert!(error("Array.from('x'.repeat(1000)).map(a => Array.from('y'.repeat(1000)).map(b => a + b))").contains("budget"));
+        assert!(error("data = 1").starts_with("SyntaxError"));
+        assert!(error("new Date()").starts_with("SyntaxError"));
+        assert!(error("x => { const y = x; return y }").starts_with("SyntaxError"));
+        assert!(error("undefined.x").starts_with("TypeError"));
+        assert!(error(&"(".repeat(500)).starts_with("SyntaxError"));
+    }
+
+    #[test]
+    fn test_deeply_nested_values_are_refused() {
+        // The evaluator runs on blocking-pool threads, which have 2 MiB stacks
+        let handle = std::thread::Builder::new()
+            .stack_size(2 << 20)
+            .spawn(|| {
+                let data = json!(null);
+                for expression in [
+                    "Array.from('x'.repeat(200000)).reduce(a => [a], [])",
+                    "Array.from('x'.repeat(200000)).reduce(a => ({a}), 0)",
+                    "JSON.stringify(Array.from('x'.repeat(200000)).reduce(a => [a], []))",
+                    "Array.from('x'.repeat(200000)).reduce(a => [a], []).length",
+                    "Array.from('x'.repeat(200000)).reduce(a => [a], []).flat(Infinity)",
+                ] {
+                    let error = evaluate(expression, &data).unwrap_err();
+                    assert!(error.starts_with("RangeError"), "{}: {}", expression, error);
+                }
+                // Nesting up to the limit still works
+                let nested = evaluate("Array.from('x'.repeat(999)).reduce(a => [a], 1)", &data).unwrap();
+                assert_eq!(serde_json::to_string(&nested).unwrap().matches('[').count(), 999);
+                assert_eq!(evaluate("Array.from('x'.repeat(999)).reduce(a => [a], []).flat(Infinity)", &data).unwrap(), json!([]));
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,412 @@
+/*!
+ * Page Watches
+ *
+ * A watch is a URL plus an extraction rule (CSS selector, optional
+ * attribute, optional regex) checked on an interval. The scheduler loads
+ * the page in the headless companion browser when one is configured (see
+ * companion.rs), otherwise asks the extension to load it in a background
+ * tab, and extracts the value,
+ * compares it with the previous run, and on change broadcasts
+ * `notifications/watch_changed` and POSTs the same payload to the watch's
+ * webhook, if it has one.
+ *
+ * Watches are persisted to ~/.agent-browser/watches.json, so they survive
+ * restarts and keep their last value.
+ */
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::RwLock;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tracing::info;
+
+use crate::preflight::{self, Target};
+use crate::storage;
+
+pub const WATCHES_FILE: &str = "watches.json";
+
+/// How often the scheduler looks for due watches
+pub const SCHEDULER_TICK_SECS: u64 = 15;
+
+pub const DEFAULT_INTERVAL_SECS: u64 = 3600;
+pub const MIN_INTERVAL_SECS: u64 = 60;
+pub const MAX_WATCHES: usize = 50;
+
+/// Extracted values are cut to this many characters before comparing
+const MAX_VALUE_CHARS: usize = 10_000;
+const WEBHOOK_TIMEOUT_MS: u64 = 5000;
+const USER_AGENT: &str = "agent-browser-watch/0.1";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractionRule {
+    /// Element to read (default: the page body)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    /// Attribute to read instead of the element's text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+    /// Regex applied to the extracted text; the first capture group (or the whole match) is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl ExtractionRule {
+    /// Extension parameters for `watch_extract`
+    pub fn extract_params(&self, url: &str) -> Value {
+        serde_json::json!({
+            "url": url,
+            "selector": self.selector,
+            "attribute": self.attribute,
+        })
+    }
+
+    /// Normalize what the extension extracted: collapse whitespace, apply the pattern, cap length
+    pub fn apply(&self, raw: &str) -> Result<String, String> {
+        let text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        let value = match &self.pattern {
+            Some(pattern) => {
+                let re = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+                let captures = re
+                    .captures(&text)
+                    .ok_or_else(|| format!("Pattern '{}' did not match", pattern))?;
+                captures.get(1).or_else(|| captures.get(0)).map_or("", |m| m.as_str())
+            }
+            None => &text,
+        };
+        Ok(value.chars().take(MAX_VALUE_CHARS).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Watch {
+    pub id: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub rule: ExtractionRule,
+    pub interval_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    pub created: i64,
+    #[serde(default)]
+    pub last_checked: Option<i64>,
+    #[serde(default)]
+    pub last_value: Option<String>,
+    #[serde(default)]
+    pub last_changed: Option<i64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub change_count: u64,
+}
+
+impl Watch {
+    pub fn is_due(&self, now: i64) -> bool {
+        self.last_checked.is_none_or(|checked| now - checked >= self.interval_secs as i64)
+    }
+}
+
+/// A watch whose extracted value differs from the previous run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub watch: Watch,
+    pub previous: Option<String>,
+}
+
+impl Change {
+    /// Body of notifications/watch_changed and of the webhook POST
+    pub fn payload(&self) -> Value {
+        serde_json::json!({
+            "id": self.watch.id,
+            "url": self.watch.url,
+            "selector": self.watch.rule.selector,
+            "previous": self.previous,
+            "current": self.watch.last_value,
+            "changedAt": self.watch.last_changed,
+            "changeCount": self.watch.change_count,
+        })
+    }
+}
+
+// ============================================================================
+// Watch Store
+// ============================================================================
+
+pub struct WatchStore {
+    path: PathBuf,
+    watches: RwLock<BTreeMap<String, Watch>>,
+}
+
+impl WatchStore {
+    /// Load persisted watches from the data directory
+    pub fn new() -> Result<Self> {
+        let store = Self::with_path(storage::data_dir()?.join(WATCHES_FILE))?;
+        info!("Watch store loaded {} watches", store.watches.try_read()?.len());
+        Ok(store)
+    }
+
+    fn with_path(path: PathBuf) -> Result<Self> {
+        let watches = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path,
+            watches: RwLock::new(watches),
+        })
+    }
+
+    fn persist(&self, watches: &BTreeMap<String, Watch>) -> Result<()> {
+        storage::write_private_file(&self.path, serde_json::to_string_pretty(watches)?)
+    }
+
+    pub async fn add(
+        &self,
+        url: &str,
+        rule: ExtractionRule,
+        interval_secs: u64,
+        webhook: Option<String>,
+    ) -> Result<Watch> {
+        Target::parse(url).map_err(|e| anyhow!(e))?;
+        if let Some(webhook) = &webhook {
+            Target::parse(webhook).map_err(|e| anyhow!("Invalid webhook: {}", e))?;
+        }
+        if let Some(pattern) = &rule.pattern {
+            Regex::new(pattern).map_err(|e| anyhow!("Invalid pattern: {}", e))?;
+        }
+        if interval_secs < MIN_INTERVAL_SECS {
+            bail!("interval_seconds must be at least {}", MIN_INTERVAL_SECS);
+        }
+
+        let mut watches = self.watches.write().await;
+        if watches.len() >= MAX_WATCHES {
+            bail!("At most {} watches can be registered; remove one first", MAX_WATCHES);
+        }
+        let watch = Watch {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            rule,
+            interval_secs,
+            webhook,
+            created: chrono::Utc::now().timestamp(),
+            last_checked: None,
+            last_value: None,
+            last_changed: None,
+            last_error: None,
+            change_count: 0,
+        };
+        watches.insert(watch.id.clone(), watch.clone());
+        self.persist(&watches)?;
+        Ok(watch)
+    }
+
+    /// Remove a watch, returning whether it existed
+    pub async fn remove(&self, id: &str) -> Result<bool> {
+        let mut watches = self.watches.write().await;
+        let existed = watches.remove(id).is_some();
+        if existed {
+            self.persist(&watches)?;
+        }
+        Ok(existed)
+    }
+
+    pub async fn list(&self) -> Vec<Watch> {
+        self.watches.read().await.values().cloned().collect()
+    }
+
+    pub async fn due(&self, now: i64) -> Vec<Watch> {
+        self.watches.read().await.values().filter(|w| w.is_due(now)).cloned().collect()
+    }
+
+    /// Store the outcome of a check; returns the change when the value differs
+    /// from the previous successful run (the first run only sets a baseline)
+    pub async fn record(&self, id: &str, outcome: Result<String, String>, now: i64) -> Result<Option<Change>> {
+        let mut watches = self.watches.write().await;
+        let Some(watch) = watches.get_mut(id) else {
+            // Removed while it was being checked
+            return Ok(None);
+        };
+        watch.last_checked = Some(now);
+        let change = match outcome {
+            Ok(value) => {
+                watch.last_error = None;
+                let previous = watch.last_value.replace(value);
+                if previous.is_some() && previous != watch.last_value {
+                    watch.last_changed = Some(now);
+                    watch.change_count += 1;
+                    Some(Change {
+                        watch: watch.clone(),
+                        previous,
+                    })
+                } else {
+                    None
+                }
+            }
+            Err(e) => {
+                watch.last_error = Some(e);
+                None
+            }
+        };
+        self.persist(&watches)?;
+        Ok(change)
+    }
+}
+
+// ============================================================================
+// Webhooks
+// ============================================================================
+
+/// POST a JSON payload to a webhook and return the response status
+pub async fn post_webhook(url: &str, payload: &Value) -> Result<u16, String> {
+    let target = Target::parse(url)?;
+    let limit = Duration::from_millis(WEBHOOK_TIMEOUT_MS);
+    let stream = match timeout(limit, TcpStream::connect((target.host.as_str(), target.port))).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => return Err(format!("connect failed: {}", e)),
+        Err(_) => return Err("connect timed out".to_string()),
+    };
+    let body = payload.to_string();
+
+    let exchange = async {
+        if target.tls {
+            let server_name = ServerName::try_from(target.host.clone()).map_err(|e| e.to_string())?;
+            let tls_stream = preflight::tls_connector()
+                .connect(server_name, stream)
+                .await
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+            send_post(tls_stream, &target, &body).await
+        } else {
+            send_post(stream, &target, &body).await
+        }
+    };
+    timeout(limit, exchange).await.map_err(|_| "timed out".to_string())?
+}
+
+async fn send_post<S>(mut stream: S, target: &Target, body: &str) -> Result<u16, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        target.path,
+        target.host_header(),
+        USER_AGENT,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let mut headers = [httparse::EMPTY_HEADER; 32];
+    let mut response = httparse::Response::new(&mut headers);
+    response.parse(&buf).map_err(|e| format!("Invalid HTTP response: {}", e))?;
+    response.code.ok_or_else(|| "Incomplete HTTP response".to_string())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> WatchStore {
+        let path = std::env::temp_dir().join(format!("agent-browser-watches-{}.json", uuid::Uuid::new_v4()));
+        WatchStore::with_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_rule_applies_pattern_and_normalizes_whitespace() {
+        let rule = ExtractionRule {
+            pattern: Some(r"\$([\d.]+)".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rule.apply("Price:\n  $19.99 today").unwrap(), "19.99");
+        assert!(rule.apply("sold out").is_err());
+        assert_eq!(ExtractionRule::default().apply("  a \n\t b ").unwrap(), "a b");
+    }
+
+    #[tokio::test]
+    async fn test_record_sets_baseline_then_reports_changes() {
+        let store = temp_store();
+        let watch = store
+            .add("https://example.com/item", ExtractionRule::default(), 60, None)
+            .await
+            .unwrap();
+        assert_eq!(store.due(0).await.len(), 1);
+
+        assert!(store.record(&watch.id, Ok("10".into()), 100).await.unwrap().is_none());
+        assert!(store.due(120).await.is_empty());
+        assert!(store.record(&watch.id, Err("timeout".into()), 160).await.unwrap().is_none());
+        assert!(store.record(&watch.id, Ok("10".into()), 220).await.unwrap().is_none());
+
+        let change = store.record(&watch.id, Ok("12".into()), 280).await.unwrap().unwrap();
+        assert_eq!(change.previous.as_deref(), Some("10"));
+        assert_eq!(change.payload()["current"], "12");
+        assert_eq!(change.watch.change_count, 1);
+
+        // Persisted state is reloaded
+        let reloaded = WatchStore::with_path(store.path.clone()).unwrap();
+        assert_eq!(reloaded.list().await[0].last_value.as_deref(), Some("12"));
+        assert!(reloaded.remove(&watch.id).await.unwrap());
+        let _ = fs::remove_file(&store.path);
+    }
+
+    #[tokio::test]
+    async fn test_add_validates_input() {
+        let store = temp_store();
+        assert!(store.add("ftp://example.com", ExtractionRule::default(), 60, None).await.is_err());
+        assert!(store.add("https://example.com", ExtractionRule::default(), 5, None).await.is_err());
+        let bad_pattern = ExtractionRule {
+            pattern: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(store.add("https://example.com", bad_pattern, 60, None).await.is_err());
+        assert!(store
+            .add("https://example.com", ExtractionRule::default(), 60, Some("mailto:x".into()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_payload() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        let status = post_webhook(&format!("http://127.0.0.1:{}/hook", port), &serde_json::json!({ "id": "w" }))
+            .await
+            .unwrap();
+        assert_eq!(status, 204);
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        assert!(request.ends_with(r#"{"id":"w"}"#));
+    }
+}
@@ -0,0 +1,165 @@
+/*!
+ * MCP WebSocket Transport
+ *
+ * MCP clients (e.g. browser-based agent UIs) can connect to
+ * ws://localhost:8085/mcp and exchange JSON-RPC messages, one per text
+ * frame. Every other path on 8085 stays reserved for the extension.
+ *
+ * Requests run concurrently and share handle_mcp_request with the other
+ * transports; server notifications are pushed as they happen.
+ */
+
+use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{debug, error, info, warn};
+
+use crate::mcp::{JsonRpcIncoming, JsonRpcReq, JsonRpcRes};
+use crate::{handle_mcp_notification, handle_mcp_request, ServerState};
+
+pub const MCP_WS_PATH: &str = "/mcp";
+const OUTBOUND_QUEUE: usize = 100;
+
+/// Serve one MCP client over an accepted WebSocket until it disconnects
+pub async fn handle_mcp_websocket(
+    ws_stream: WebSocketStream<TcpStream>,
+    peer: std::net::SocketAddr,
+    state: Arc<ServerState>,
+) {
+    info!("MCP WebSocket client connected: {}", peer);
+
+    let session = format!("ws:{}", peer);
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let (out_tx, mut out_rx) = mpsc::channel::<String>(OUTBOUND_QUEUE);
+
+    // Single writer: responses and notifications are funnelled through out_rx
+    let send_task = tokio::spawn(async move {
+        while let Some(json) = out_rx.recv().await {
+            if let Err(e) = ws_sender.send(WsMessage::Text(json)).await {
+                debug!("Failed to send MCP WebSocket message: {}", e);
+                break;
+            }
+        }
+    });
+
+    // Notifications and server requests only flow once the client has authenticated
+    let mut notify_task = None;
+    if !state.auth.is_required() {
+        notify_task = Some(attach(&state, &out_tx, &session).await);
+    }
+
+    while let Some(result) = ws_receiver.next().await {
+        let text = match result {
+            Ok(WsMessage::Text(text)) => text,
+            Ok(WsMessage::Binary(bytes)) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => {
+                    warn!("Ignoring non-UTF-8 binary frame from MCP WebSocket client");
+                    continue;
+                }
+            },
+            Ok(WsMessage::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => {
+                debug!("MCP WebSocket error: {}", e);
+                break;
+            }
+        };
+
+        debug!("MCP WebSocket received: {}", text);
+
+        if notify_task.is_none() {
+            let (response, authenticated) = state.auth.handle_unauthenticated(&text);
+            state.alert_on_rejected_token(&session, response.as_ref());
+            if let Some(json) = response.and_then(|r| serde_json::to_string(&r).ok()) {
+                let _ = out_tx.send(json).await;
+            }
+            if authenticated {
+                notify_task = Some(attach(&state, &out_tx, &session).await);
+            }
+            continue;
+        }
+
+        let req: JsonRpcReq = match crate::mcp::parse_message(&text) {
+            Ok(JsonRpcIncoming::Single(req)) => req,
+            Ok(JsonRpcIncoming::Response(response)) => {
+                state.sampling.handle_response(&response).await;
+                continue;
+            }
+            Ok(JsonRpcIncoming::Batch(_)) => {
+                let res = JsonRpcRes::err(None, -32600, "Batches are not supported on WebSocket", None);
+                if let Ok(json) = serde_json::to_string(&res) {
+                    let _ = out_tx.send(json).await;
+                }
+                continue;
+            }
+            Err(e) => {
+                let res = JsonRpcRes::err(None, -32700, format!("Parse error: {}", e), None);
+                if let Ok(json) = serde_json::to_string(&res) {
+                    let _ = out_tx.send(json).await;
+                }
+                continue;
+            }
+        };
+
+        // Notifications get no JSON-RPC response
+        if req.id.is_none() {
+            tokio::spawn(handle_mcp_notification(req, Arc::clone(&state), session.clone()));
+            continue;
+        }
+
+        // Long-running tool calls must not block later requests on the same socket
+        let state = Arc::clone(&state);
+        let session = session.clone();
+        let out_tx = out_tx.clone();
+        tokio::spawn(async move {
+            let res = handle_mcp_request(req, state, &session).await;
+            match serde_json::to_string(&res) {
+                Ok(json) => {
+                    let _ = out_tx.send(json).await;
+                }
+                Err(e) => error!("Failed to serialize MCP WebSocket response: {}", e),
+            }
+        });
+    }
+
+    state.sampling.unregister(&session).await;
+    state.forget_session(&session).await;
+    if let Some(notify_task) = notify_task {
+        notify_task.abort();
+    }
+    send_task.abort();
+    info!("MCP WebSocket client disconnected: {}", peer);
+}
+
+/// Register for server requests and start forwarding notifications to the client
+async fn attach(state: &ServerState, out_tx: &mpsc::Sender<String>, session: &str) -> JoinHandle<()> {
+    state.sampling.register(session, out_tx.clone()).await;
+
+    let notify_tx = out_tx.clone();
+    let notify_session = session.to_string();
+    let mut notifications = state.notifications.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(notification) if !notification.is_visible_to(&notify_session) => {}
+                Ok(notification) => match serde_json::to_string(&notification) {
+                    Ok(json) => {
+                        if notify_tx.send(json).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => error!("Failed to serialize notification: {}", e),
+                },
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("MCP WebSocket client lagged, dropped {} notifications", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
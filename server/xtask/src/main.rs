@@ -0,0 +1,489 @@
+/*!
+ * Repository Tasks
+ *
+ * `cargo xtask codegen` regenerates the Python and Node clients in
+ * `clients/` and the extension's forwarded tool definitions from the
+ * server's tool registry (`src/mcp/tools.rs`, compiled in here as-is).
+ * `cargo xtask codegen --check` fails if the checked-in outputs are stale
+ * instead of rewriting them.
+ *
+ * `cargo xtask handlers` fails if the server sends a command, or the
+ * extension's `COMMAND_VERSIONS` lists one, that neither the background nor
+ * the content script dispatches.
+ */
+
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[allow(dead_code)]
+#[path = "../../src/mcp/tools.rs"]
+mod tools;
+
+const PYTHON_TEMPLATE: &str = include_str!("../templates/client.py");
+const NODE_TEMPLATE: &str = include_str!("../templates/client.mjs");
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+/// Checked-in outputs, relative to the repository root
+const PYTHON_CLIENT: &str = "clients/python/agent_browser_client.py";
+const NODE_CLIENT: &str = "clients/node/agent-browser-client.mjs";
+const EXTENSION_TOOLS: &str = "extension/lib/forwarded-tools.ts";
+
+/// Where commands are sent from and dispatched, relative to the repository root
+const SERVER_SOURCES: &str = "server/src";
+const BACKGROUND_SCRIPT: &str = "extension/entrypoints/background.ts";
+const CONTENT_SCRIPT: &str = "extension/entrypoints/content.ts";
+const CAPABILITIES: &str = "extension/lib/capabilities.ts";
+
+/// A tool's input schema flattened to what the generators need
+struct Tool<'a> {
+    name: &'a str,
+    description: &'a str,
+    params: Vec<Param<'a>>,
+}
+
+struct Param<'a> {
+    name: &'a str,
+    schema: &'a Value,
+    required: bool,
+}
+
+impl<'a> Param<'a> {
+    fn description(&self) -> &'a str {
+        self.schema.get("description").and_then(|d| d.as_str()).unwrap_or_default()
+    }
+
+    fn json_type(&self) -> Option<&'a str> {
+        self.schema.get("type").and_then(|t| t.as_str())
+    }
+
+    fn item_type(&self) -> Option<&'a str> {
+        self.schema.pointer("/items/type").and_then(|t| t.as_str())
+    }
+}
+
+fn parse_tools(registry: &[Value]) -> Vec<Tool<'_>> {
+    registry
+        .iter()
+        .map(|tool| {
+            let required: Vec<&str> = tool
+                .pointer("/inputSchema/required")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            let mut params: Vec<Param> = tool
+                .pointer("/inputSchema/properties")
+                .and_then(|p| p.as_object())
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(name, schema)| Param {
+                            name,
+                            schema,
+                            required: required.contains(&name.as_str()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Required parameters first, each group in schema order
+            params.sort_by_key(|p| !p.required);
+            Tool {
+                name: tool["name"].as_str().expect("tool without a name"),
+                description: tool["description"].as_str().unwrap_or_default(),
+                params,
+            }
+        })
+        .collect()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else {
+            out.push('_');
+        }
+    }
+    out
+}
+
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn pascal_case(name: &str) -> String {
+    let camel = camel_case(name);
+    let mut chars = camel.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+fn python_type(param: &Param) -> &'static str {
+    match param.json_type() {
+        Some("string") => "str",
+        Some("boolean") => "bool",
+        Some("integer") => "int",
+        Some("number") => "float",
+        Some("array") => match param.item_type() {
+            Some("string") => "List[str]",
+            Some("number") => "List[float]",
+            Some("integer") => "List[int]",
+            _ => "List[Any]",
+        },
+        Some("object") => "Dict[str, Any]",
+        _ => "Any",
+    }
+}
+
+fn node_type(param: &Param) -> &'static str {
+    match param.json_type() {
+        Some("string") => "string",
+        Some("boolean") => "boolean",
+        Some("integer") | Some("number") => "number",
+        Some("array") => match param.item_type() {
+            Some("string") => "string[]",
+            Some("integer") | Some("number") => "number[]",
+            _ => "any[]",
+        },
+        Some("object") => "Object",
+        _ => "any",
+    }
+}
+
+fn generate_python(tools: &[Tool]) -> String {
+    let names: Vec<String> = tools.iter().map(|t| format!("    {:?},", t.name)).collect();
+    let tool_names = format!(
+        "# Every tool in the server's registry, in tools/list order\nTOOL_NAMES = [\n{}\n]\n",
+        names.join("\n")
+    );
+
+    let mut methods = String::new();
+    for tool in tools {
+        let args: Vec<(String, &Param)> = tool
+            .params
+            .iter()
+            .map(|p| {
+                let arg = snake_case(p.name);
+                let arg = if PYTHON_KEYWORDS.contains(&arg.as_str()) { arg + "_" } else { arg };
+                (arg, p)
+            })
+            .collect();
+
+        let mut signature = String::from("self");
+        if !args.is_empty() {
+            signature.push_str(", *");
+        }
+        for (arg, param) in &args {
+            if param.required {
+                write!(signature, ", {}: {}", arg, python_type(param)).unwrap();
+            } else {
+                write!(signature, ", {}: Optional[{}] = None", arg, python_type(param)).unwrap();
+            }
+        }
+
+        writeln!(methods).unwrap();
+        writeln!(methods, "    def {}({}) -> ToolResult:", tool.name, signature).unwrap();
+        let mut doc = python_doc(tool.description);
+        if !args.is_empty() {
+            doc.push('\n');
+            for (arg, param) in &args {
+                write!(doc, "\n        :param {}: {}", arg, python_doc(param.description())).unwrap();
+            }
+            doc.push_str("\n        ");
+        }
+        writeln!(methods, "        \"\"\"{}\"\"\"", doc).unwrap();
+        let body: Vec<String> = args.iter().map(|(arg, p)| format!("{:?}: {}", p.name, arg)).collect();
+        writeln!(methods, "        return self.call_tool({:?}, {{{}}})", tool.name, body.join(", ")).unwrap();
+    }
+
+    PYTHON_TEMPLATE
+        .replace("# @@TOOL_NAMES@@\n", &tool_names)
+        .replace("# @@TOOLS@@\n", &methods)
+}
+
+fn python_doc(text: &str) -> String {
+    text.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"")
+}
+
+fn generate_node(tools: &[Tool]) -> String {
+    let names: Vec<String> = tools.iter().map(|t| format!("  {:?},", t.name)).collect();
+    let tool_names = format!(
+        "/** Every tool in the server's registry, in tools/list order */\nexport const TOOL_NAMES = [\n{}\n];\n",
+        names.join("\n")
+    );
+
+    let mut typedefs = Vec::new();
+    let mut methods = String::new();
+    for tool in tools {
+        let args_type = format!("{}Args", pascal_case(tool.name));
+        let mut typedef = format!("/**\n * @typedef {{Object}} {}", args_type);
+        for param in &tool.params {
+            let name = if param.required {
+                param.name.to_string()
+            } else {
+                format!("[{}]", param.name)
+            };
+            write!(typedef, "\n * @property {{{}}} {}", node_type(param), name).unwrap();
+            if !param.description().is_empty() {
+                write!(typedef, " - {}", jsdoc(param.description())).unwrap();
+            }
+        }
+        typedef.push_str("\n */\n");
+        typedefs.push(typedef);
+
+        let optional = tool.params.iter().all(|p| !p.required);
+        writeln!(methods).unwrap();
+        writeln!(methods, "  /**").unwrap();
+        writeln!(methods, "   * {}", jsdoc(tool.description)).unwrap();
+        writeln!(methods, "   * @param {{{}}} {}", args_type, if optional { "[args]" } else { "args" }).unwrap();
+        writeln!(methods, "   * @returns {{Promise<ToolResult>}}").unwrap();
+        writeln!(methods, "   */").unwrap();
+        writeln!(
+            methods,
+            "  {}(args{}) {{\n    return this.callTool({:?}, args);\n  }}",
+            camel_case(tool.name),
+            if optional { " = {}" } else { "" },
+            tool.name
+        )
+        .unwrap();
+    }
+
+    NODE_TEMPLATE
+        .replace("// @@TOOL_NAMES@@\n", &tool_names)
+        .replace("// @@TYPEDEFS@@\n", &typedefs.join("\n"))
+        .replace("// @@TOOLS@@\n", &methods)
+}
+
+/// The forwarded tools with their extension command. The extension registers
+/// those whose command its handler table lists, so it needs the schemas but
+/// not a say in which tools exist.
+fn generate_extension_tools(registry: &[Value]) -> String {
+    let tools: Vec<Value> = registry
+        .iter()
+        .filter_map(|tool| {
+            let command = tools::extension_command(tool["name"].as_str()?)?;
+            let mut tool = tool.clone();
+            tool["command"] = Value::from(command);
+            Some(tool)
+        })
+        .collect();
+    format!(
+        "// @generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit\n\n\
+         export interface ForwardedTool {{\n  name: string;\n  command: string;\n  description: string;\n  inputSchema: Record<string, unknown>;\n}}\n\n\
+         /**\n * Definitions of the tools the server forwards to an extension command. The\n * extension registers the ones whose command is in COMMAND_VERSIONS\n * (lib/capabilities.ts), so a build lacking a handler doesn't offer its tool.\n */\n\
+         export const FORWARDED_TOOLS: ForwardedTool[] = {};\n",
+        serde_json::to_string_pretty(&tools).unwrap()
+    )
+}
+
+fn jsdoc(text: &str) -> String {
+    text.replace("*/", "*\\/")
+}
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+fn outputs() -> Vec<(&'static str, String)> {
+    let registry = tools::builtin_tools();
+    let tools = parse_tools(&registry);
+    vec![
+        (PYTHON_CLIENT, generate_python(&tools)),
+        (NODE_CLIENT, generate_node(&tools)),
+        (EXTENSION_TOOLS, generate_extension_tools(&registry)),
+    ]
+}
+
+fn codegen(check: bool) -> Result<(), String> {
+    let root = repo_root();
+    let mut stale = Vec::new();
+    for (relative, contents) in outputs() {
+        let path = root.join(relative);
+        if std::fs::read_to_string(&path).ok().as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+        if check {
+            stale.push(relative);
+            continue;
+        }
+        std::fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+        println!("wrote {}", relative);
+    }
+    if stale.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Out of date (run `cargo xtask codegen`): {}", stale.join(", ")))
+    }
+}
+
+/// String literals following `prefix`, e.g. the commands in `case 'click':`
+fn literals_after<'a>(source: &'a str, prefix: &str, quote: char) -> Vec<&'a str> {
+    source
+        .match_indices(prefix)
+        .filter_map(|(start, _)| {
+            let rest = source[start + prefix.len()..].trim_start().strip_prefix(quote)?;
+            let end = rest.find(quote)?;
+            let literal = &rest[..end];
+            literal
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                .then_some(literal)
+        })
+        .collect()
+}
+
+fn read(relative: &str) -> Result<String, String> {
+    std::fs::read_to_string(repo_root().join(relative)).map_err(|e| format!("Failed to read {}: {}", relative, e))
+}
+
+/// Commands the server sends by name: forwarded tools, server tools' commands,
+/// and literal send_to_extension / notify_extension calls
+fn server_commands() -> Result<Vec<(String, String)>, String> {
+    let mut commands: Vec<(String, String)> = tools::EXTENSION_COMMANDS
+        .iter()
+        .map(|(tool, command)| (command.to_string(), format!("tool {}", tool)))
+        .collect();
+    for (tool, required) in tools::SERVER_TOOL_COMMANDS {
+        commands.extend(required.iter().map(|(command, _)| (command.to_string(), format!("tool {}", tool))));
+    }
+
+    let dir = repo_root().join(SERVER_SOURCES);
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut pending = vec![dir.clone()];
+    while let Some(next) = pending.pop() {
+        for entry in std::fs::read_dir(&next).map_err(|e| format!("Failed to list {}: {}", next.display(), e))? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    for path in files {
+        let source = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let file = path.strip_prefix(&dir).unwrap_or(&path).display().to_string();
+        for prefix in ["send_to_extension(", "send_to_extension_with_progress(", "notify_extension("] {
+            commands.extend(literals_after(&source, prefix, '"').into_iter().map(|c| (c.to_string(), file.clone())));
+        }
+    }
+    Ok(commands)
+}
+
+fn handlers() -> Result<(), String> {
+    let background = read(BACKGROUND_SCRIPT)?;
+    let content = read(CONTENT_SCRIPT)?;
+    let capabilities = read(CAPABILITIES)?;
+
+    let mut handled: Vec<&str> = literals_after(&background, "message.method === ", '\'');
+    handled.extend(literals_after(&content, "case ", '\''));
+
+    let mut missing: Vec<String> = Vec::new();
+    for (command, sender) in server_commands()? {
+        if !handled.contains(&command.as_str()) && !missing.iter().any(|m| m.starts_with(&format!("{} ", command))) {
+            missing.push(format!("{} (sent by {})", command, sender));
+        }
+    }
+    // COMMAND_VERSIONS entries are `  name: version,` lines
+    let versions = capabilities
+        .split_once("COMMAND_VERSIONS")
+        .and_then(|(_, rest)| rest.split_once("};"))
+        .map(|(table, _)| table)
+        .ok_or_else(|| format!("No COMMAND_VERSIONS table in {}", CAPABILITIES))?;
+    for line in versions.lines() {
+        let Some((command, version)) = line.trim().split_once(':') else {
+            continue;
+        };
+        if version.trim().trim_end_matches(',').parse::<u32>().is_ok() && !handled.contains(&command) {
+            missing.push(format!("{} (listed in COMMAND_VERSIONS)", command));
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "No handler in {} or {} for: {}",
+            BACKGROUND_SCRIPT,
+            CONTENT_SCRIPT,
+            missing.join(", ")
+        ))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("codegen") => codegen(args.iter().any(|a| a == "--check")),
+        Some("handlers") => handlers(),
+        _ => Err("Usage: cargo xtask codegen [--check] | cargo xtask handlers".to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(snake_case("bypassServiceWorker"), "bypass_service_worker");
+        assert_eq!(camel_case("playwright_get_focused_element"), "playwrightGetFocusedElement");
+        assert_eq!(pascal_case("state_get"), "StateGet");
+    }
+
+    #[test]
+    fn test_generated_clients_are_up_to_date() {
+        codegen(true).unwrap();
+    }
+
+    #[test]
+    fn test_literals_after() {
+        let source = "case 'click':\n  case 'type_text': case other: message.method === 'Page.enable'";
+        assert_eq!(literals_after(source, "case ", '\''), vec!["click", "type_text"]);
+        assert_eq!(literals_after("send_to_extension(\n    \"list_tabs\",", "send_to_extension(", '"'), vec!["list_tabs"]);
+    }
+
+    #[test]
+    fn test_every_command_has_a_handler() {
+        handlers().unwrap();
+    }
+}
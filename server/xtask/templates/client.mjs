@@ -0,0 +1,221 @@
+/**
+ * Thin Node client for the agent-browser MCP TCP interface (localhost:8084).
+ *
+ * @generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit.
+ *
+ * No dependencies. Requests may run concurrently and are matched by id. If
+ * the connection drops, pending calls reject and the next call reconnects
+ * (a new MCP session). Server notifications are emitted as "notification".
+ * Each connection authenticates with `token`, or the one the server wrote
+ * to ~/.agent-browser/token.
+ */
+
+import { EventEmitter } from "node:events";
+import { readFileSync } from "node:fs";
+import net from "node:net";
+import { homedir } from "node:os";
+import { join } from "node:path";
+
+export const PROTOCOL_VERSION = "2025-06-18";
+export const DEFAULT_HOST = "127.0.0.1";
+export const DEFAULT_PORT = 8084;
+
+// @@TOOL_NAMES@@
+
+/** JSON-RPC error returned by the server */
+export class AgentBrowserError extends Error {
+  constructor(code, message, data) {
+    super(`${message} (${code})`);
+    this.code = code;
+    this.data = data;
+  }
+}
+
+/**
+ * @typedef {Object} ToolResult
+ * @property {Array<{type: string, text?: string, data?: string, mimeType?: string}>} content
+ * @property {any} [structuredContent]
+ * @property {boolean} [isError]
+ */
+
+/** Token written by a server on this machine, re-read on every connect */
+function localToken() {
+  try {
+    return readFileSync(join(homedir(), ".agent-browser", "token"), "utf8").trim() || null;
+  } catch {
+    return null;
+  }
+}
+
+/** Text blocks of a tool result joined with newlines */
+export function toolText(result) {
+  return (result.content ?? []).filter((c) => c.text !== undefined).map((c) => c.text).join("\n");
+}
+
+/** structuredContent, or the text block parsed as JSON */
+export function toolJson(result) {
+  return result.structuredContent ?? JSON.parse(toolText(result));
+}
+
+// @@TYPEDEFS@@
+
+export class AgentBrowserClient extends EventEmitter {
+  constructor({
+    host = DEFAULT_HOST,
+    port = DEFAULT_PORT,
+    timeoutMs = 60000,
+    clientName = "agent-browser-node",
+    token = null,
+  } = {}) {
+    super();
+    this.token = token;
+    this.host = host;
+    this.port = port;
+    this.timeoutMs = timeoutMs;
+    this.clientName = clientName;
+    this.protocolVersion = null;
+    this.socket = null;
+    this.connecting = null;
+    this.pending = new Map();
+    this.nextId = 1;
+  }
+
+  /** Connect and initialize; called implicitly by every request */
+  connect() {
+    if (this.socket) return Promise.resolve();
+    this.connecting ??= this.#open().finally(() => {
+      this.connecting = null;
+    });
+    return this.connecting;
+  }
+
+  close() {
+    this.socket?.end();
+    this.socket = null;
+  }
+
+  /** Send a raw JSON-RPC request and resolve with its result */
+  async request(method, params = {}) {
+    await this.connect();
+    return this.#send(method, params);
+  }
+
+  /** Call any tool by name; undefined arguments are omitted */
+  async callTool(name, args = {}) {
+    const argumentsObject = Object.fromEntries(Object.entries(args).filter(([, v]) => v !== undefined));
+    const result = await this.request("tools/call", { name, arguments: argumentsObject });
+    // Tools the server answers itself return their JSON without a content wrapper
+    return result?.content === undefined ? { content: [], structuredContent: result } : result;
+  }
+
+  async listTools() {
+    return (await this.request("tools/list")).tools ?? [];
+  }
+
+  async ping() {
+    await this.request("ping");
+  }
+
+  async #open() {
+    const socket = net.createConnection({ host: this.host, port: this.port });
+    socket.setNoDelay(true);
+    await new Promise((resolve, reject) => {
+      socket.once("connect", resolve);
+      socket.once("error", reject);
+    });
+
+    let buffer = "";
+    socket.setEncoding("utf8");
+    socket.on("data", (chunk) => {
+      buffer += chunk;
+      let newline;
+      while ((newline = buffer.indexOf("\n")) >= 0) {
+        const line = buffer.slice(0, newline).trim();
+        buffer = buffer.slice(newline + 1);
+        if (line) this.#dispatch(socket, line);
+      }
+    });
+    socket.on("error", () => {});
+    socket.on("close", () => {
+      if (this.socket === socket) this.socket = null;
+      for (const { reject, timer } of this.pending.values()) {
+        clearTimeout(timer);
+        reject(new Error("Connection to agent-browser closed"));
+      }
+      this.pending.clear();
+    });
+
+    this.socket = socket;
+    try {
+      const token = this.token ?? localToken();
+      if (token) await this.#send("authenticate", { token });
+      const result = await this.#send("initialize", {
+        protocolVersion: PROTOCOL_VERSION,
+        capabilities: {},
+        clientInfo: { name: this.clientName, version: "0.1.0" },
+      });
+      this.protocolVersion = result.protocolVersion ?? null;
+      this.#write({ jsonrpc: "2.0", method: "notifications/initialized", params: {} });
+    } catch (error) {
+      this.close();
+      throw error;
+    }
+  }
+
+  #write(message) {
+    this.socket.write(JSON.stringify(message) + "\n");
+  }
+
+  #send(method, params) {
+    const id = this.nextId++;
+    return new Promise((resolve, reject) => {
+      const timer = setTimeout(() => {
+        this.pending.delete(id);
+        reject(new Error(`Request ${method} timed out`));
+      }, this.timeoutMs);
+      this.pending.set(id, { resolve, reject, timer });
+      this.#write({ jsonrpc: "2.0", id, method, params });
+    });
+  }
+
+  #dispatch(socket, line) {
+    let message;
+    try {
+      message = JSON.parse(line);
+    } catch {
+      return;
+    }
+    if (message.method !== undefined) {
+      if (message.id !== undefined && message.method === "ping") {
+        // Server keep-alive
+        socket.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result: {} }) + "\n");
+      } else if (message.id !== undefined) {
+        // No client capabilities are declared, so other server requests are unsupported
+        socket.write(
+          JSON.stringify({
+            jsonrpc: "2.0",
+            id: message.id,
+            error: { code: -32601, message: `Method not found: ${message.method}` },
+          }) + "\n",
+        );
+      } else {
+        this.emit("notification", message.method, message.params);
+      }
+      return;
+    }
+    const pending = this.pending.get(message.id);
+    if (!pending) return;
+    this.pending.delete(message.id);
+    clearTimeout(pending.timer);
+    if (message.error) {
+      pending.reject(new AgentBrowserError(message.error.code, message.error.message, message.error.data));
+    } else {
+      pending.resolve(message.result);
+    }
+  }
+
+  // --------------------------------------------------------------------------
+  // Tools
+  // --------------------------------------------------------------------------
+// @@TOOLS@@
+}
@@ -0,0 +1,196 @@
+"""Thin Python client for the agent-browser MCP TCP interface (localhost:8084).
+
+@generated by `cargo xtask codegen` from server/src/mcp/tools.rs; do not edit.
+
+Standard library only. One request is in flight at a time per client; use a
+client per thread for concurrency. If the connection drops, the failing call
+raises and the next call reconnects (a new MCP session). Each connection
+authenticates with `token`, or the one the server wrote to
+~/.agent-browser/token.
+"""
+
+import itertools
+import json
+import os
+import socket
+import threading
+from typing import Any, Callable, Dict, List, Optional
+
+PROTOCOL_VERSION = "2025-06-18"
+DEFAULT_HOST = "127.0.0.1"
+DEFAULT_PORT = 8084
+
+# @@TOOL_NAMES@@
+
+
+def _local_token() -> Optional[str]:
+    """Token written by a server on this machine, re-read on every connect"""
+    try:
+        with open(os.path.expanduser("~/.agent-browser/token")) as f:
+            return f.read().strip() or None
+    except OSError:
+        return None
+
+
+class AgentBrowserError(Exception):
+    """JSON-RPC error returned by the server"""
+
+    def __init__(self, code: int, message: str, data: Any = None):
+        super().__init__(f"{message} ({code})")
+        self.code = code
+        self.message = message
+        self.data = data
+
+
+class ToolResult:
+    """Result of tools/call"""
+
+    def __init__(self, raw: Any):
+        self.raw = raw
+        if not isinstance(raw, dict) or "content" not in raw:
+            # Tools the server answers itself return their JSON without a content wrapper
+            raw = {"content": [], "structuredContent": raw}
+        self.content: List[Dict[str, Any]] = raw.get("content", [])
+        self.structured_content: Optional[Any] = raw.get("structuredContent")
+        self.is_error: bool = raw.get("isError", False)
+
+    @property
+    def text(self) -> str:
+        return "\n".join(c["text"] for c in self.content if "text" in c)
+
+    def json(self) -> Any:
+        """structuredContent, or the text block parsed as JSON"""
+        if self.structured_content is not None:
+            return self.structured_content
+        return json.loads(self.text)
+
+
+class AgentBrowserClient:
+    def __init__(
+        self,
+        host: str = DEFAULT_HOST,
+        port: int = DEFAULT_PORT,
+        timeout: float = 60.0,
+        client_name: str = "agent-browser-python",
+        on_notification: Optional[Callable[[str, Any], None]] = None,
+        token: Optional[str] = None,
+    ):
+        self.host = host
+        self.port = port
+        self.timeout = timeout
+        self.client_name = client_name
+        self.on_notification = on_notification
+        self.token = token
+        self.protocol_version: Optional[str] = None
+        self._sock: Optional[socket.socket] = None
+        self._file = None
+        self._lock = threading.Lock()
+        self._ids = itertools.count(1)
+
+    def __enter__(self) -> "AgentBrowserClient":
+        self.connect()
+        return self
+
+    def __exit__(self, *exc) -> None:
+        self.close()
+
+    def connect(self) -> None:
+        with self._lock:
+            if self._sock is None:
+                self._connect_locked()
+
+    def close(self) -> None:
+        with self._lock:
+            self._close_locked()
+
+    def request(self, method: str, params: Optional[Dict[str, Any]] = None) -> Any:
+        """Send a raw JSON-RPC request and return its result"""
+        with self._lock:
+            if self._sock is None:
+                self._connect_locked()
+            try:
+                return self._exchange(method, params or {})
+            except (OSError, ValueError):
+                self._close_locked()
+                raise
+
+    def call_tool(self, name: str, arguments: Optional[Dict[str, Any]] = None) -> ToolResult:
+        """Call any tool by name; arguments set to None are omitted"""
+        arguments = {k: v for k, v in (arguments or {}).items() if v is not None}
+        return ToolResult(self.request("tools/call", {"name": name, "arguments": arguments}))
+
+    def list_tools(self) -> List[Dict[str, Any]]:
+        return self.request("tools/list").get("tools", [])
+
+    def ping(self) -> None:
+        self.request("ping")
+
+    def _connect_locked(self) -> None:
+        self._sock = socket.create_connection((self.host, self.port), timeout=self.timeout)
+        self._file = self._sock.makefile("rb")
+        try:
+            token = self.token or _local_token()
+            if token:
+                self._exchange("authenticate", {"token": token})
+            result = self._exchange(
+                "initialize",
+                {
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": self.client_name, "version": "0.1.0"},
+                },
+            )
+            self.protocol_version = result.get("protocolVersion")
+            self._send({"jsonrpc": "2.0", "method": "notifications/initialized", "params": {}})
+        except Exception:
+            self._close_locked()
+            raise
+
+    def _close_locked(self) -> None:
+        if self._sock is not None:
+            try:
+                self._sock.close()
+            finally:
+                self._sock = None
+                self._file = None
+
+    def _send(self, message: Dict[str, Any]) -> None:
+        self._sock.sendall(json.dumps(message).encode() + b"\n")
+
+    def _exchange(self, method: str, params: Dict[str, Any]) -> Any:
+        request_id = next(self._ids)
+        self._send({"jsonrpc": "2.0", "id": request_id, "method": method, "params": params})
+        while True:
+            line = self._file.readline()
+            if not line:
+                raise ConnectionError("Connection to agent-browser closed")
+            if not line.strip():
+                continue
+            message = json.loads(line)
+            if "method" in message:
+                if "id" in message and message["method"] == "ping":
+                    # Server keep-alive
+                    self._send({"jsonrpc": "2.0", "id": message["id"], "result": {}})
+                elif "id" in message:
+                    # No client capabilities are declared, so other server requests are unsupported
+                    self._send(
+                        {
+                            "jsonrpc": "2.0",
+                            "id": message["id"],
+                            "error": {"code": -32601, "message": f"Method not found: {message['method']}"},
+                        }
+                    )
+                elif self.on_notification is not None:
+                    self.on_notification(message["method"], message.get("params"))
+                continue
+            if message.get("id") != request_id:
+                continue
+            if "error" in message:
+                error = message["error"]
+                raise AgentBrowserError(error.get("code", -32000), error.get("message", ""), error.get("data"))
+            return message.get("result")
+
+    # ------------------------------------------------------------------
+    # Tools
+    # ------------------------------------------------------------------
+# @@TOOLS@@